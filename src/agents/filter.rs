@@ -0,0 +1,305 @@
+use anyhow::{bail, Result};
+
+use super::types::{AgentState, MonitoredAgent};
+
+/// State matched by an [`AgentFilter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateFilter {
+    Idle,
+    Working,
+    /// Asking a question or for permission
+    Waiting,
+    Input,
+    Permission,
+    Error,
+    /// Flagged by the watchdog
+    Stalled,
+}
+
+impl StateFilter {
+    /// Parses a state name like "idle" or "waiting"
+    pub fn parse(name: &str) -> Result<Self> {
+        Ok(match name.to_lowercase().as_str() {
+            "idle" => StateFilter::Idle,
+            "working" => StateFilter::Working,
+            "waiting" => StateFilter::Waiting,
+            "input" => StateFilter::Input,
+            "permission" => StateFilter::Permission,
+            "error" => StateFilter::Error,
+            "stalled" => StateFilter::Stalled,
+            _ => bail!(
+                "unknown state {:?} (idle, working, waiting, input, permission, error, stalled)",
+                name
+            ),
+        })
+    }
+
+    /// Returns true if `agent` is in this state
+    pub fn matches(self, agent: &MonitoredAgent) -> bool {
+        match self {
+            StateFilter::Idle => agent.state == Some(AgentState::Idle),
+            StateFilter::Working => agent.state == Some(AgentState::Working),
+            StateFilter::Waiting => matches!(
+                agent.state,
+                Some(AgentState::WaitingForInput | AgentState::WaitingForPermission)
+            ),
+            StateFilter::Input => agent.state == Some(AgentState::WaitingForInput),
+            StateFilter::Permission => agent.state == Some(AgentState::WaitingForPermission),
+            StateFilter::Error => agent.state == Some(AgentState::Error),
+            StateFilter::Stalled => agent.stalled_since.is_some(),
+        }
+    }
+}
+
+/// Returns true if `haystack` contains `needle`, ignoring case
+fn contains(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+/// Returns true if the characters of `needle` appear in order in `haystack`,
+/// ignoring case
+fn fuzzy_contains(haystack: &str, needle: &str) -> bool {
+    let mut chars = haystack.chars().flat_map(char::to_lowercase);
+    needle
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|c| chars.any(|h| h == c))
+}
+
+/// Selects agents by state, repository, branch and label
+///
+/// Text filters match case-insensitive substrings; the repository is matched
+/// against the repository root and its GitHub slug. Unset filters match all.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AgentFilter {
+    pub state: Option<StateFilter>,
+    pub repo: Option<String>,
+    pub branch: Option<String>,
+    pub label: Option<String>,
+    /// Words that must each fuzzy-match the session, window name, cwd,
+    /// branch, label or state
+    pub terms: Vec<String>,
+}
+
+impl AgentFilter {
+    /// Parses qualifiers like "state:idle repo:myapp branch:main label:api"
+    pub fn parse(query: &str) -> Result<Self> {
+        Self::parse_query(query, false)
+    }
+
+    /// Parses a pane search like "api state:waiting", where words without a
+    /// qualifier are matched fuzzily
+    pub fn parse_search(query: &str) -> Result<Self> {
+        Self::parse_query(query, true)
+    }
+
+    fn parse_query(query: &str, fuzzy: bool) -> Result<Self> {
+        let mut filter = Self::default();
+        for term in query.split_whitespace() {
+            let Some((key, value)) = term.split_once(':') else {
+                if fuzzy {
+                    filter.terms.push(term.to_string());
+                    continue;
+                }
+                bail!("expected key:value, got {:?}", term);
+            };
+            let value = value.to_string();
+            match key.to_lowercase().as_str() {
+                "state" => filter.state = Some(StateFilter::parse(&value)?),
+                "repo" => filter.repo = Some(value),
+                "branch" => filter.branch = Some(value),
+                "label" => filter.label = Some(value),
+                _ => bail!("unknown filter {:?} (state, repo, branch, label)", key),
+            }
+        }
+        Ok(filter)
+    }
+
+    pub fn matches(&self, agent: &MonitoredAgent) -> bool {
+        if self.state.is_some_and(|state| !state.matches(agent)) {
+            return false;
+        }
+        if let Some(repo) = &self.repo {
+            let slug = agent.git.as_ref().and_then(|g| g.repo_slug.as_deref());
+            if !contains(&agent.repo_root(), repo) && !slug.is_some_and(|s| contains(s, repo)) {
+                return false;
+            }
+        }
+        if let Some(branch) = &self.branch {
+            if !agent
+                .git
+                .as_ref()
+                .is_some_and(|g| contains(&g.branch, branch))
+            {
+                return false;
+            }
+        }
+        if let Some(label) = &self.label {
+            if !agent.label.as_deref().is_some_and(|l| contains(l, label)) {
+                return false;
+            }
+        }
+        if !self.terms.is_empty() {
+            let fields = [
+                Some(agent.session.as_str()),
+                Some(agent.window_name.as_str()),
+                Some(agent.path.as_str()),
+                agent.git.as_ref().map(|g| g.branch.as_str()),
+                agent.label.as_deref(),
+                agent.state.map(AgentState::label),
+            ];
+            let matched = |term: &String| {
+                fields
+                    .iter()
+                    .flatten()
+                    .any(|field| fuzzy_contains(field, term))
+            };
+            if !self.terms.iter().all(matched) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Finds the one agent `query` names: its pane target (server-qualified for
+/// other servers) or pane id, else its label, else the panes matching it as
+/// a search
+///
+/// Fails if nothing or several panes match.
+pub fn find_agent<'a>(agents: &'a [MonitoredAgent], query: &str) -> Result<&'a MonitoredAgent> {
+    match find_agents(agents, query)?.as_slice() {
+        [agent] => Ok(agent),
+        [] => bail!("no agent pane matches {:?}", query),
+        several => {
+            let keys: Vec<String> = several.iter().map(|a| a.key()).collect();
+            bail!("{:?} matches several panes: {}", query, keys.join(", "))
+        }
+    }
+}
+
+/// Returns the agents `query` names, looked up like [`find_agent`] does
+///
+/// Fails only on a search that can't be parsed.
+pub fn find_agents<'a>(
+    agents: &'a [MonitoredAgent],
+    query: &str,
+) -> Result<Vec<&'a MonitoredAgent>> {
+    let by_target =
+        |a: &&MonitoredAgent| a.key() == query || a.target == query || a.pane_id == query;
+    let by_label = |a: &&MonitoredAgent| {
+        a.label
+            .as_deref()
+            .is_some_and(|l| l.eq_ignore_ascii_case(query))
+    };
+    let mut found: Vec<&MonitoredAgent> = agents.iter().filter(by_target).collect();
+    if found.is_empty() {
+        found = agents.iter().filter(by_label).collect();
+    }
+    if found.is_empty() {
+        let search = AgentFilter::parse_search(query)?;
+        found = agents.iter().filter(|a| search.matches(a)).collect();
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::AgentType;
+    use crate::git::GitInfo;
+
+    #[test]
+    fn test_filter_matches() {
+        let mut agent = MonitoredAgent::new(
+            "%1-100".to_string(),
+            "main:0.0".to_string(),
+            "main".to_string(),
+            0,
+            "code".to_string(),
+            0,
+            "/home/me/code/MyApp".to_string(),
+            AgentType::ClaudeCode,
+            100,
+        );
+        agent.state = Some(AgentState::WaitingForInput);
+        agent.label = Some("api refactor".to_string());
+        agent.git = Some(GitInfo::parse_porcelain("feat/login".to_string(), ""));
+
+        let filter = AgentFilter::parse("state:waiting repo:myapp branch:login").unwrap();
+        assert!(filter.matches(&agent));
+        assert!(AgentFilter::default().matches(&agent));
+        assert!(AgentFilter::parse("label:API").unwrap().matches(&agent));
+        assert!(!AgentFilter::parse("state:idle").unwrap().matches(&agent));
+        assert!(!AgentFilter::parse("branch:main").unwrap().matches(&agent));
+
+        assert!(AgentFilter::parse("state:sleeping").is_err());
+        assert!(AgentFilter::parse("myapp").is_err());
+    }
+
+    #[test]
+    fn test_search_matches() {
+        let mut agent = MonitoredAgent::new(
+            "%1-100".to_string(),
+            "work:1.0".to_string(),
+            "work".to_string(),
+            1,
+            "backend".to_string(),
+            0,
+            "/home/me/code/MyApp".to_string(),
+            AgentType::ClaudeCode,
+            100,
+        );
+        agent.state = Some(AgentState::WaitingForPermission);
+        agent.git = Some(GitInfo::parse_porcelain("feat/login".to_string(), ""));
+
+        let search = |query: &str| AgentFilter::parse_search(query).unwrap().matches(&agent);
+        assert!(search("bkend"));
+        assert!(search("MYAPP flogin"));
+        assert!(search("perm"));
+        assert!(search("work state:waiting"));
+        assert!(!search("backend state:idle"));
+        assert!(!search("frontend"));
+        assert!(AgentFilter::parse_search("state:sleeping").is_err());
+    }
+
+    #[test]
+    fn test_find_agent() {
+        let agent = |target: &str, window: &str, label: Option<&str>| {
+            let mut agent = MonitoredAgent::new(
+                format!("{}-100", target),
+                target.to_string(),
+                "main".to_string(),
+                0,
+                window.to_string(),
+                0,
+                "/code/app".to_string(),
+                AgentType::ClaudeCode,
+                100,
+            );
+            agent.label = label.map(str::to_string);
+            agent
+        };
+        let agents = [
+            agent("main:0.0", "backend", Some("api")),
+            agent("main:1.0", "frontend", Some("api-v2")),
+            agent("main:2.0", "docs", None),
+        ];
+        let find = |query| find_agent(&agents, query).map(|a| a.target.as_str());
+        assert_eq!(find("main:1.0").unwrap(), "main:1.0");
+        // An exact label wins over the longer one it is part of
+        assert_eq!(find("API").unwrap(), "main:0.0");
+        assert_eq!(find("fntend").unwrap(), "main:1.0");
+        assert_eq!(find("dcs").unwrap(), "main:2.0");
+
+        let several = find("end").unwrap_err().to_string();
+        assert!(several.contains("main:0.0, main:1.0"), "{}", several);
+        assert!(find("nothing").is_err());
+
+        let all = |query| find_agents(&agents, query).unwrap().len();
+        assert_eq!(all("end"), 2);
+        assert_eq!(all("api"), 1);
+        assert_eq!(all("nothing"), 0);
+        assert!(find_agents(&agents, "colour:red").is_err());
+    }
+}
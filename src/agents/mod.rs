@@ -1,5 +1,9 @@
+mod filter;
 mod subagent;
 mod types;
 
+pub use filter::{find_agent, find_agents, AgentFilter, StateFilter};
 pub use subagent::{Subagent, SubagentStatus, SubagentType};
-pub use types::{AgentStatus, AgentType, ApprovalType, MonitoredAgent};
+pub use types::{
+    AgentState, AgentStatus, AgentType, ApprovalType, MonitoredAgent, PaneStats, Usage,
+};
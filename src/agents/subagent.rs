@@ -1,8 +1,9 @@
 use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// Types of subagents that can be spawned
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SubagentType {
     Explore,
     Plan,
@@ -45,7 +46,7 @@ impl fmt::Display for SubagentType {
 }
 
 /// Status of a subagent
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SubagentStatus {
     Running,
     Completed,
@@ -78,7 +79,7 @@ impl fmt::Display for SubagentStatus {
 }
 
 /// Represents a subagent spawned by a parent agent
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Subagent {
     /// Unique identifier
     pub id: String,
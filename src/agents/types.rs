@@ -1,15 +1,26 @@
+use chrono::{DateTime, Local};
 use std::fmt;
+use std::sync::Arc;
 use std::time::Instant;
 
+use serde::{Deserialize, Serialize};
+
 use super::subagent::Subagent;
+use crate::app::ProjectConfig;
+use crate::git::GitInfo;
+use crate::tmux::{LaunchCommand, TmuxServer};
 
 /// Types of AI agents that can be monitored
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AgentType {
     ClaudeCode,
     OpenCode,
     CodexCli,
     GeminiCli,
+    Aider,
+    Goose,
+    /// Agent defined in the config's `custom_agents`
+    Custom(String),
     Unknown,
 }
 
@@ -21,6 +32,9 @@ impl AgentType {
             AgentType::OpenCode => "OpenCode",
             AgentType::CodexCli => "Codex CLI",
             AgentType::GeminiCli => "Gemini CLI",
+            AgentType::Aider => "Aider",
+            AgentType::Goose => "Goose",
+            AgentType::Custom(name) => name,
             AgentType::Unknown => "Unknown",
         }
     }
@@ -32,6 +46,9 @@ impl AgentType {
             AgentType::OpenCode => "Open",
             AgentType::CodexCli => "Codex",
             AgentType::GeminiCli => "Gemini",
+            AgentType::Aider => "Aider",
+            AgentType::Goose => "Goose",
+            AgentType::Custom(name) => name,
             AgentType::Unknown => "???",
         }
     }
@@ -44,7 +61,7 @@ impl fmt::Display for AgentType {
 }
 
 /// Types of approvals that agents may request
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ApprovalType {
     FileEdit,
     FileCreate,
@@ -111,7 +128,7 @@ impl fmt::Display for ApprovalType {
 }
 
 /// Status of an AI agent
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AgentStatus {
     /// Agent is idle and ready for input
     Idle,
@@ -174,13 +191,195 @@ impl fmt::Display for AgentStatus {
     }
 }
 
+/// Stable state of an agent, kept by the monitor's state machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AgentState {
+    Idle,
+    Working,
+    /// Asking the user a question
+    WaitingForInput,
+    /// Asking for permission to edit, run or call something
+    WaitingForPermission,
+    Error,
+}
+
+impl AgentState {
+    /// Classifies a parsed status, `None` when it is unknown
+    pub fn from_status(status: &AgentStatus) -> Option<Self> {
+        match status {
+            AgentStatus::Idle => Some(AgentState::Idle),
+            AgentStatus::Processing { .. } => Some(AgentState::Working),
+            AgentStatus::AwaitingApproval {
+                approval_type: ApprovalType::UserQuestion { .. },
+                ..
+            } => Some(AgentState::WaitingForInput),
+            AgentStatus::AwaitingApproval { .. } => Some(AgentState::WaitingForPermission),
+            AgentStatus::Error { .. } => Some(AgentState::Error),
+            AgentStatus::Unknown => None,
+        }
+    }
+
+    /// Returns the badge text
+    pub fn label(self) -> &'static str {
+        match self {
+            AgentState::Idle => "IDLE",
+            AgentState::Working => "WORKING",
+            AgentState::WaitingForInput => "INPUT",
+            AgentState::WaitingForPermission => "PERMISSION",
+            AgentState::Error => "ERROR",
+        }
+    }
+}
+
+/// Token and cost totals
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub cost_usd: f64,
+}
+
+impl Usage {
+    /// Returns input and output tokens together
+    pub fn tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    /// Adds `other` to these totals
+    pub fn add(&mut self, other: &Usage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cost_usd += other.cost_usd;
+    }
+
+    /// Returns what was used since the cumulative report `baseline`
+    ///
+    /// A report lower than its baseline comes from a new session and counts
+    /// from zero.
+    pub fn since(&self, baseline: &Usage) -> Usage {
+        if self.input_tokens < baseline.input_tokens
+            || self.output_tokens < baseline.output_tokens
+            || self.cost_usd < baseline.cost_usd
+        {
+            return *self;
+        }
+        Usage {
+            input_tokens: self.input_tokens - baseline.input_tokens,
+            output_tokens: self.output_tokens - baseline.output_tokens,
+            cost_usd: self.cost_usd - baseline.cost_usd,
+        }
+    }
+
+    /// Returns the totals like "45.1k tokens · $1.23"
+    pub fn summary(&self) -> String {
+        let tokens = self.tokens();
+        let tokens = if tokens >= 1_000_000 {
+            format!("{:.1}M", tokens as f64 / 1_000_000.0)
+        } else if tokens >= 1000 {
+            format!("{:.1}k", tokens as f64 / 1000.0)
+        } else {
+            tokens.to_string()
+        };
+        format!("{} tokens · ${:.2}", tokens, self.cost_usd)
+    }
+}
+
+/// Statistics of an agent pane, kept across tmuxcc and tmux restarts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PaneStats {
+    /// Unix time the pane was first seen
+    #[serde(default)]
+    pub first_seen: u64,
+    /// Time spent processing, in milliseconds
+    #[serde(default)]
+    pub working_ms: u64,
+    /// Number of times the agent waited for approval or input
+    #[serde(default)]
+    pub prompts: u32,
+    /// Tokens and cost reported by the agent
+    #[serde(default)]
+    pub usage: Usage,
+}
+
+impl PaneStats {
+    /// Returns the working time like "1h05m", "12m" or "40s"
+    pub fn working_str(&self) -> String {
+        let secs = self.working_ms / 1000;
+        if secs < 60 {
+            format!("{}s", secs)
+        } else if secs < 3600 {
+            format!("{}m", secs / 60)
+        } else {
+            format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+        }
+    }
+}
+
+/// Formats a duration like "40s", "12m" or "1h05m"
+fn format_secs(secs: u64) -> String {
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
+/// Serializes an `Instant` as how long ago it was, in milliseconds, so an
+/// agent sent to another process keeps its ages
+mod instant {
+    use std::time::{Duration, Instant};
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    fn to_ms(at: &Instant) -> u64 {
+        at.elapsed().as_millis() as u64
+    }
+
+    fn from_ms(ms: u64) -> Instant {
+        let now = Instant::now();
+        now.checked_sub(Duration::from_millis(ms)).unwrap_or(now)
+    }
+
+    pub fn serialize<S: Serializer>(at: &Instant, serializer: S) -> Result<S::Ok, S::Error> {
+        to_ms(at).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Instant, D::Error> {
+        u64::deserialize(deserializer).map(from_ms)
+    }
+
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            at: &Option<Instant>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            at.as_ref().map(to_ms).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Instant>, D::Error> {
+            Option::<u64>::deserialize(deserializer).map(|ms| ms.map(from_ms))
+        }
+    }
+}
+
 /// Represents a monitored AI agent in a tmux pane
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MonitoredAgent {
     /// Unique identifier for this agent
     pub id: String,
     /// Tmux target (e.g., "main:0.1")
     pub target: String,
+    /// Tmux pane id (e.g., "%3"), set by the monitor
+    pub pane_id: String,
     /// Session name
     pub session: String,
     /// Window index
@@ -199,14 +398,58 @@ pub struct MonitoredAgent {
     pub subagents: Vec<Subagent>,
     /// Last captured pane content
     pub last_content: String,
+    /// Last captured pane content with SGR color sequences
+    pub last_content_styled: String,
     /// Process ID
     pub pid: u32,
     /// When this agent was first detected
+    #[serde(with = "instant")]
     pub started_at: Instant,
     /// When the pane content was last updated
+    #[serde(with = "instant")]
     pub last_updated: Instant,
     /// Context remaining percentage (0-100), if detectable
     pub context_remaining: Option<u8>,
+    /// Cumulative session usage last reported in the output, if any
+    pub reported_usage: Option<Usage>,
+    /// When the rate limit the agent hit resets, while it is limited
+    pub rate_limit_reset: Option<DateTime<Local>>,
+    /// Git repository information, if the working directory is inside a repository
+    pub git: Option<GitInfo>,
+    /// How the agent was started, if it could be determined
+    pub launch: Option<LaunchCommand>,
+    /// Version of the agent, when the pane's command shows it
+    pub version: Option<String>,
+    /// New output lines per minute over the last minutes, oldest first
+    pub activity: Vec<u32>,
+    /// tmux server the pane lives on
+    pub server: TmuxServer,
+    /// Whether the pane is zoomed to fill its window
+    pub zoomed: bool,
+    /// Whether status and usage come from a `tmuxcc headless` event stream
+    pub headless: bool,
+    /// Stable state, `None` until the status could be classified
+    pub state: Option<AgentState>,
+    /// When the current state was entered
+    #[serde(with = "instant")]
+    pub state_since: Instant,
+    /// One-line summary from `summarize_cmd`, while waiting for input
+    pub summary: Option<String>,
+    /// Last output of a working agent the watchdog considers stalled
+    #[serde(default, with = "instant::option")]
+    pub stalled_since: Option<Instant>,
+    /// Time spent working since the agent was first seen
+    pub working_time: std::time::Duration,
+    /// Time spent idle since the agent was first seen
+    pub idle_time: std::time::Duration,
+    /// User-given label, remembered by the pane registry
+    pub label: Option<String>,
+    /// User-given note, remembered by the pane registry
+    pub note: Option<String>,
+    /// Statistics remembered by the pane registry
+    pub stats: PaneStats,
+    /// `.tmuxcc.toml` of the pane's repository, if it has one
+    pub project: Option<Arc<ProjectConfig>>,
 }
 
 impl MonitoredAgent {
@@ -227,6 +470,7 @@ impl MonitoredAgent {
         Self {
             id,
             target,
+            pane_id: String::new(),
             session,
             window,
             window_name,
@@ -236,13 +480,38 @@ impl MonitoredAgent {
             status: AgentStatus::Unknown,
             subagents: Vec::new(),
             last_content: String::new(),
+            last_content_styled: String::new(),
             pid,
             started_at: now,
             last_updated: now,
             context_remaining: None,
+            reported_usage: None,
+            rate_limit_reset: None,
+            git: None,
+            launch: None,
+            version: None,
+            activity: Vec::new(),
+            server: TmuxServer::Default,
+            zoomed: false,
+            headless: false,
+            state: None,
+            state_since: now,
+            summary: None,
+            stalled_since: None,
+            working_time: std::time::Duration::ZERO,
+            idle_time: std::time::Duration::ZERO,
+            label: None,
+            note: None,
+            stats: PaneStats::default(),
+            project: None,
         }
     }
 
+    /// Returns the target qualified with the server, unique across servers
+    pub fn key(&self) -> String {
+        self.server.qualify(&self.target)
+    }
+
     /// Returns the duration since this agent was first detected
     pub fn uptime(&self) -> std::time::Duration {
         self.started_at.elapsed()
@@ -260,6 +529,39 @@ impl MonitoredAgent {
         }
     }
 
+    /// Returns the label of the agent's state, "STALLED" while the watchdog flags it
+    pub fn state_label(&self) -> &'static str {
+        if self.stalled_since.is_some() {
+            return "STALLED";
+        }
+        self.state.map_or("UNKNOWN", |s| s.label())
+    }
+
+    /// Returns how long the agent has been in its current state like "40s" or "1h05m"
+    pub fn state_age_str(&self) -> String {
+        format_secs(self.state_since.elapsed().as_secs())
+    }
+
+    /// Returns the session's working and idle totals like "W 1h05m · I 12m"
+    pub fn session_times_str(&self) -> String {
+        format!(
+            "W {} · I {}",
+            format_secs(self.working_time.as_secs()),
+            format_secs(self.idle_time.as_secs())
+        )
+    }
+
+    /// Returns the time until the rate limit resets like "1h05m" or "12m",
+    /// while the agent is limited
+    pub fn rate_limit_countdown(&self) -> Option<String> {
+        let secs = (self.rate_limit_reset? - Local::now()).num_seconds().max(0) as u64;
+        Some(if secs < 3600 {
+            format!("{}m", secs.div_ceil(60))
+        } else {
+            format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+        })
+    }
+
     /// Returns a human-readable last updated string
     pub fn last_updated_str(&self) -> String {
         let secs = self.last_updated.elapsed().as_secs();
@@ -314,6 +616,49 @@ impl MonitoredAgent {
         format!("/{}/{}", abbreviated.join("/"), parts.last().unwrap())
     }
 
+    /// Returns the branch with status indicators (e.g., "main* ↑1"), if in a repository
+    pub fn git_label(&self) -> Option<String> {
+        self.git.as_ref().map(|g| g.label())
+    }
+
+    /// Returns the repository root, or the pane path outside a repository
+    ///
+    /// Used to group panes working on the same project.
+    pub fn repo_root(&self) -> String {
+        match &self.git {
+            Some(git) if !git.root.as_os_str().is_empty() => git.root.to_string_lossy().to_string(),
+            _ => self.path.clone(),
+        }
+    }
+
+    /// Returns the last line of the pane that looks like output, skipping
+    /// blank lines and the agent's borders and prompt box
+    pub fn last_output_line(&self) -> Option<&str> {
+        self.last_content
+            .lines()
+            .rev()
+            .map(str::trim)
+            .filter(|line| line.chars().any(char::is_alphanumeric))
+            .find(|line| !line.starts_with(|c: char| ('\u{2500}'..='\u{257f}').contains(&c)))
+    }
+
+    /// Returns what the agent asks or says, for notifications: the pending
+    /// request, the error or the last output line, cut to 200 characters
+    pub fn snippet(&self) -> String {
+        let snippet = match &self.status {
+            AgentStatus::AwaitingApproval {
+                approval_type,
+                details,
+            } if !details.is_empty() => format!("{}: {}", approval_type, details),
+            AgentStatus::Error { message } if !message.is_empty() => message.clone(),
+            _ => self.last_output_line().unwrap_or_default().to_string(),
+        };
+        match snippet.char_indices().nth(200) {
+            Some((end, _)) => format!("{}…", &snippet[..end]),
+            None => snippet,
+        }
+    }
+
     /// Returns the number of active subagents
     pub fn active_subagent_count(&self) -> usize {
         use super::subagent::SubagentStatus;
@@ -374,4 +719,22 @@ mod tests {
         assert_eq!(agent.active_subagent_count(), 0);
         assert_eq!(agent.short_path(), "project");
     }
+
+    #[test]
+    fn test_last_output_line() {
+        let mut agent = MonitoredAgent::new(
+            "agent-1".to_string(),
+            "main:0.1".to_string(),
+            "main".to_string(),
+            0,
+            "code".to_string(),
+            1,
+            "/home/user/project".to_string(),
+            AgentType::ClaudeCode,
+            12345,
+        );
+        assert_eq!(agent.last_output_line(), None);
+        agent.last_content = "⏺ Updated 3 files\n\n╭────╮\n│ >  │\n╰────╯\n  ──\n".to_string();
+        assert_eq!(agent.last_output_line(), Some("⏺ Updated 3 files"));
+    }
 }
@@ -7,6 +7,10 @@ pub enum Action {
     NextAgent,
     /// Navigate to previous agent
     PrevAgent,
+    /// Navigate to the first agent
+    FirstAgent,
+    /// Navigate to the last agent
+    LastAgent,
     /// Toggle selection of current agent
     ToggleSelection,
     /// Select all agents
@@ -27,10 +31,30 @@ pub enum Action {
     ToggleSummaryDetail,
     /// Refresh agent list
     Refresh,
+    /// Silence the configured sounds, or turn them back on
+    ToggleMute,
+    /// Open the command palette listing every action
+    ShowPalette,
+    /// Type a character into the palette query
+    PaletteChar(char),
+    /// Delete the last character of the palette query
+    PaletteBackspace,
+    /// Highlight the next palette match
+    PaletteNext,
+    /// Highlight the previous palette match
+    PalettePrev,
+    /// Run the highlighted palette match
+    PaletteConfirm,
+    /// Close the palette
+    PaletteCancel,
     /// Show help
     ShowHelp,
     /// Hide help
     HideHelp,
+    /// Scroll the help up by N lines
+    HelpUp(usize),
+    /// Scroll the help down by N lines
+    HelpDown(usize),
     /// Focus on input panel
     FocusInput,
     /// Focus on sidebar
@@ -65,6 +89,125 @@ pub enum Action {
     ScrollUp,
     /// Scroll down in sidebar
     ScrollDown,
+    /// Scroll the preview back by a number of lines
+    ScrollPreviewUp(usize),
+    /// Scroll the preview forward by a number of lines
+    ScrollPreviewDown(usize),
+    /// Create a worktree for the selected agent's repository and launch an agent in it
+    NewWorktree,
+    /// Launch an agent in a new tmux window, split or session
+    NewAgent,
+    /// Switch the tmux client (or attach) to the selected agent's pane
+    JumpToPane,
+    /// Ask to kill the selected agent's pane
+    KillPane,
+    /// Ask to restart the selected agent with its original command
+    RespawnPane,
+    /// Stop monitoring the selected pane (sets its `@tmuxcc_ignore` option)
+    IgnorePane,
+    /// Carry out the action waiting for confirmation
+    ConfirmYes,
+    /// Drop the action waiting for confirmation
+    ConfirmNo,
+    /// Add character to the prompt popup
+    PromptChar(char),
+    /// Delete last character in the prompt popup
+    PromptBackspace,
+    /// Submit the prompt popup
+    PromptSubmit,
+    /// Close the prompt popup
+    PromptCancel,
+    /// Open the branch picker for the selected agent's repository
+    OpenBranchPicker,
+    /// Highlight the next picker item
+    PickerNext,
+    /// Highlight the previous picker item
+    PickerPrev,
+    /// Confirm the highlighted picker item (force skips safety checks)
+    PickerConfirm { force: bool },
+    /// Close the picker popup
+    PickerCancel,
+    /// Show recent commits of the selected agent's repository
+    ShowCommitLog,
+    /// Show unstaged and staged changes of the selected agent's repository
+    ShowDiff,
+    /// Scroll the viewer down by a number of lines
+    ViewerDown(usize),
+    /// Scroll the viewer up by a number of lines
+    ViewerUp(usize),
+    /// Jump to the top of the viewer
+    ViewerTop,
+    /// Jump to the bottom of the viewer
+    ViewerBottom,
+    /// Close the viewer
+    CloseViewer,
+    /// Start or clear a region selection in the viewer
+    ViewerMark,
+    /// Ask for text to find in the viewer
+    ViewerSearch,
+    /// Jump to the next (or previous) line matching the viewer search
+    ViewerFindNext { forward: bool },
+    /// Show the selected pane's scrollback history in a viewer
+    ShowScrollback,
+    /// Copy the viewer's selected region (or all of it) to the clipboard
+    ViewerYank,
+    /// Show the selected agent's captured output in the viewer
+    ShowOutput,
+    /// Copy the selected agent's captured output to the clipboard
+    YankOutput,
+    /// Toggle sending input to every selected pane instead of the current one
+    ToggleBroadcast,
+    /// Select the panes matching a filter and broadcast to them
+    BroadcastFilter,
+    /// Search the pane list, hiding panes that don't match
+    Search,
+    /// Show every pane again
+    ClearSearch,
+    /// Zoom or unzoom the selected agent's pane in its window
+    ToggleZoom,
+    /// Edit the selected agent's label
+    EditLabel,
+    /// Edit the selected agent's note
+    EditNote,
+    /// Exclude the selected agent from automatic answers, or include it again
+    ToggleAutomation,
+    /// Pause or resume all automatic answers
+    ToggleAutomationPause,
+    /// Show token and cost totals per repository, day and pane
+    ShowUsage,
+    /// Queue a prompt for the selected agent, sent once it is idle
+    QueuePrompt,
+    /// Show the selected agent's queued prompts for reordering
+    EditQueue,
+    /// Ask the selected agent to run the test command and fix failures,
+    /// once it is idle
+    RunTests,
+    /// Move the highlighted queued prompt later (true) or earlier
+    PickerMove { later: bool },
+    /// Remove the highlighted queued prompt
+    PickerRemove,
+    /// Mark the highlighted notification, or all of them, as read
+    PickerMarkRead { all: bool },
+    /// Show the history of notifications
+    ShowNotifications,
+    /// Show everything known about the selected pane
+    ShowDetails,
+    /// Switch the sidebar between session/window and repository grouping
+    ToggleGroupByRepo,
+    /// Collapse or expand the selected agent's session or repository group
+    ToggleCollapseGroup,
+    /// Set the sidebar width, in percent of the screen (mouse drag)
+    ResizeSidebar(u16),
+    /// Switch the pane list to the next sort order
+    CycleSort,
+    /// Pin the selected agent to the top of the list, or unpin it
+    TogglePin,
+    /// Show the panes of one server tab (1-based), or of all servers for 0
+    SelectServerTab(usize),
+    /// Switch the pane list between one line per pane and the detailed view
+    ToggleCompactList,
+    /// Open a GitHub pull request for the selected agent's branch
+    CreatePullRequest,
     /// No action (used for unbound keys)
     None,
 }
@@ -76,6 +219,8 @@ impl Action {
             Action::Quit => "Quit application",
             Action::NextAgent => "Select next agent",
             Action::PrevAgent => "Select previous agent",
+            Action::FirstAgent => "Select first agent",
+            Action::LastAgent => "Select last agent",
             Action::ToggleSelection => "Toggle selection",
             Action::SelectAll => "Select all agents",
             Action::ClearSelection => "Clear selection",
@@ -86,8 +231,18 @@ impl Action {
             Action::ToggleSubagentLog => "Toggle subagent log",
             Action::ToggleSummaryDetail => "Toggle TODO/Tools display",
             Action::Refresh => "Refresh agent list",
+            Action::ToggleMute => "Mute/unmute sounds",
+            Action::ShowPalette => "Command palette",
+            Action::PaletteChar(_) => "Type character",
+            Action::PaletteBackspace => "Delete character",
+            Action::PaletteNext => "Next match",
+            Action::PalettePrev => "Previous match",
+            Action::PaletteConfirm => "Run command",
+            Action::PaletteCancel => "Cancel",
             Action::ShowHelp => "Show help",
             Action::HideHelp => "Hide help",
+            Action::HelpUp(_) => "Scroll help up",
+            Action::HelpDown(_) => "Scroll help down",
             Action::FocusInput => "Focus input panel",
             Action::FocusSidebar => "Focus sidebar",
             Action::SendInput => "Send input",
@@ -105,6 +260,67 @@ impl Action {
             Action::SelectAgent(_) => "Select agent",
             Action::ScrollUp => "Scroll up",
             Action::ScrollDown => "Scroll down",
+            Action::ScrollPreviewUp(_) => "Scroll preview up",
+            Action::ScrollPreviewDown(_) => "Scroll preview down",
+            Action::NewWorktree => "Spawn agent in new worktree",
+            Action::NewAgent => "Launch agent in new pane",
+            Action::JumpToPane => "Jump to agent pane",
+            Action::KillPane => "Kill agent pane",
+            Action::RespawnPane => "Restart agent",
+            Action::IgnorePane => "Stop monitoring pane",
+            Action::ConfirmYes => "Confirm",
+            Action::ConfirmNo => "Cancel",
+            Action::PromptChar(_) => "Type character",
+            Action::PromptBackspace => "Delete character",
+            Action::PromptSubmit => "Submit",
+            Action::PromptCancel => "Cancel",
+            Action::OpenBranchPicker => "Checkout branch",
+            Action::PickerNext => "Next item",
+            Action::PickerPrev => "Previous item",
+            Action::PickerConfirm { .. } => "Confirm",
+            Action::PickerCancel => "Cancel",
+            Action::ShowCommitLog => "Show recent commits",
+            Action::ShowDiff => "Show working tree diff",
+            Action::ViewerDown(_) => "Scroll down",
+            Action::ViewerUp(_) => "Scroll up",
+            Action::ViewerTop => "Jump to top",
+            Action::ViewerBottom => "Jump to bottom",
+            Action::CloseViewer => "Close viewer",
+            Action::ViewerMark => "Start/clear selection",
+            Action::ViewerYank => "Copy selection",
+            Action::ViewerSearch => "Find in viewer",
+            Action::ViewerFindNext { .. } => "Find next match",
+            Action::ShowScrollback => "Show scrollback history",
+            Action::ShowOutput => "Show pane output",
+            Action::YankOutput => "Copy pane output",
+            Action::ToggleBroadcast => "Toggle broadcast input",
+            Action::BroadcastFilter => "Broadcast to matching panes",
+            Action::Search => "Search panes",
+            Action::ClearSearch => "Clear search",
+            Action::ToggleZoom => "Zoom/unzoom pane",
+            Action::EditLabel => "Edit label",
+            Action::EditNote => "Edit note",
+            Action::ToggleAutomation => "Toggle auto-answers for pane",
+            Action::ToggleAutomationPause => "Pause/resume all auto-answers",
+            Action::ShowUsage => "Show token usage and cost",
+            Action::QueuePrompt => "Queue prompt for when idle",
+            Action::EditQueue => "Reorder queued prompts",
+            Action::RunTests => "Ask agent to run the tests",
+            Action::PickerMove { later: true } => "Move item down",
+            Action::PickerMove { later: false } => "Move item up",
+            Action::PickerRemove => "Remove item",
+            Action::PickerMarkRead { all: false } => "Mark as read",
+            Action::PickerMarkRead { all: true } => "Mark all as read",
+            Action::ShowNotifications => "Show notification history",
+            Action::ShowDetails => "Show pane details",
+            Action::ToggleGroupByRepo => "Group by repository",
+            Action::ResizeSidebar(_) => "Resize sidebar",
+            Action::CycleSort => "Cycle sort order",
+            Action::TogglePin => "Pin/unpin pane",
+            Action::SelectServerTab(_) => "Select server tab",
+            Action::ToggleCompactList => "Toggle compact list",
+            Action::ToggleCollapseGroup => "Collapse/expand group",
+            Action::CreatePullRequest => "Create pull request",
             Action::None => "",
         }
     }
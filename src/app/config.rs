@@ -1,9 +1,22 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use super::icons::UseIcons;
+use super::keymap::KeysConfig;
+use super::theme::ThemeConfig;
+use crate::agents::AgentState;
+use crate::tmux::TmuxServer;
+
+/// The config `tmuxcc config init` writes: every key at its default value,
+/// with comments
+pub const DEFAULT_CONFIG: &str = include_str!("default_config.toml");
 
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     /// Polling interval in milliseconds
     #[serde(default = "default_poll_interval")]
@@ -13,9 +26,170 @@ pub struct Config {
     #[serde(default = "default_capture_lines")]
     pub capture_lines: u32,
 
+    /// Polling intervals of some panes, or panes not to monitor at all; the
+    /// first rule matching a pane decides
+    #[serde(default)]
+    pub pane_polling: Vec<PanePolling>,
+
     /// Custom agent patterns (command -> agent type mapping)
     #[serde(default)]
     pub agent_patterns: Vec<AgentPattern>,
+
+    /// Built-in agents to detect (claude, opencode, codex, gemini, aider,
+    /// goose); empty detects all of them
+    #[serde(default)]
+    pub enabled_agents: Vec<String>,
+
+    /// Additional agents recognized by process and output patterns
+    #[serde(default)]
+    pub custom_agents: Vec<CustomAgent>,
+
+    /// Extra output patterns mapped to states, checked before the built-in ones
+    #[serde(default)]
+    pub state_patterns: StatePatterns,
+
+    /// Rules for answering permission prompts automatically (off by default)
+    #[serde(default)]
+    pub auto_approve: AutoApproveConfig,
+
+    /// Replies sent automatically to questions matching a pattern
+    #[serde(default)]
+    pub auto_respond: Vec<AutoRespondRule>,
+
+    /// Warn when an agent's context left drops below this percentage (0 to never warn)
+    #[serde(default = "default_context_warning")]
+    pub context_warning: u8,
+
+    /// Sent to a rate limited agent once its limit resets; empty to only show
+    /// the countdown
+    #[serde(default)]
+    pub rate_limit_resume: String,
+
+    /// Flags working agents that stop producing output
+    #[serde(default)]
+    pub watchdog: WatchdogConfig,
+
+    /// Desktop notifications for agents that need attention
+    #[serde(default)]
+    pub desktop_notifications: DesktopNotifications,
+
+    /// tmux status line messages and window bells for agents that need
+    /// attention, for when tmuxcc isn't the pane in view
+    #[serde(default)]
+    pub tmux_alerts: TmuxAlerts,
+
+    /// Sounds played when agents change state
+    #[serde(default)]
+    pub sounds: SoundsConfig,
+
+    /// URLs a JSON payload is POSTed to when agents change state
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+
+    /// Slack messages for agents that need attention
+    #[serde(default)]
+    pub slack: SlackConfig,
+
+    /// Discord messages for agents that need attention
+    #[serde(default)]
+    pub discord: DiscordConfig,
+
+    /// Shell commands run on events, for custom integrations
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    /// Which notifiers hear about which panes; the first rule matching a pane
+    /// and event decides, events no rule matches go to every notifier
+    #[serde(default)]
+    pub notify_rules: Vec<NotifyRule>,
+
+    /// Seconds an alert identical to the last one about a pane (same event
+    /// and text) is not repeated; 0 repeats them
+    #[serde(default = "default_notify_dedup")]
+    pub notify_dedup_secs: u64,
+
+    /// Command the selected agent is asked to run and fix with `run_tests`;
+    /// a project's `.tmuxcc.toml` may set its own
+    #[serde(default)]
+    pub test_command: String,
+
+    /// Shell command summarizing a pane's output in one line when its agent
+    /// starts waiting for input; gets the last lines on stdin, empty to disable
+    #[serde(default)]
+    pub summarize_cmd: String,
+
+    /// Command used to launch an agent in newly created panes
+    #[serde(default = "default_agent_command")]
+    pub agent_command: String,
+
+    /// Use a tmux control mode connection to react to changes immediately
+    #[serde(default)]
+    pub control_mode: bool,
+
+    /// Install tmux hooks so pane lifecycle changes are picked up immediately
+    #[serde(default = "default_tmux_hooks")]
+    pub tmux_hooks: bool,
+
+    /// tmux key (in the prefix table) that switches back to tmuxcc after
+    /// jumping to an agent pane; empty to leave tmux's bindings alone
+    #[serde(default = "default_return_key")]
+    pub return_key: String,
+
+    /// Rename tmux windows and/or panes to show each agent's state
+    #[serde(default)]
+    pub status_titles: StatusTitles,
+
+    /// Named pane arrangements for `tmuxcc layout apply <name>`
+    #[serde(default)]
+    pub layouts: BTreeMap<String, LayoutConfig>,
+
+    /// tmux servers to monitor: "default", a socket name (`-L`) or a socket
+    /// path (`-S`, anything containing `/`); empty monitors the default server
+    #[serde(default)]
+    pub sockets: Vec<String>,
+
+    /// SSH hosts whose default tmux server is monitored alongside the local ones
+    #[serde(default)]
+    pub hosts: Vec<String>,
+
+    /// Canned replies offered by `tmuxcc popup` on keys 1-9
+    #[serde(default = "default_popup_replies")]
+    pub popup_replies: Vec<String>,
+
+    /// Width of the pane list in percent of the screen (15-70); the preview
+    /// takes the rest
+    #[serde(default = "default_sidebar_width")]
+    pub sidebar_width: u16,
+
+    /// Sidebar key bindings: a profile and per-action overrides
+    #[serde(default)]
+    pub keys: KeysConfig,
+
+    /// TUI colors: a built-in palette and per-role overrides
+    #[serde(default)]
+    pub theme: ThemeConfig,
+
+    /// Symbols drawn for states, branches and agents: "auto", "nerd",
+    /// "unicode" or "ascii"
+    #[serde(default)]
+    pub use_icons: UseIcons,
+
+    /// Named sets of keys laid over the rest of the config with `--profile`:
+    /// tables merge key by key, other values replace the top-level ones
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, toml::Table>,
+
+    /// File the config was read from, `None` when running on the defaults
+    #[serde(skip)]
+    pub source: Option<PathBuf>,
+
+    /// Profile laid over the config, if one was asked for
+    #[serde(skip)]
+    pub profile: Option<String>,
+
+    /// `TMUXCC_*` environment variables laid over the config file
+    #[serde(skip)]
+    pub env: Vec<String>,
 }
 
 fn default_poll_interval() -> u64 {
@@ -26,8 +200,108 @@ fn default_capture_lines() -> u32 {
     100
 }
 
+fn default_context_warning() -> u8 {
+    15
+}
+
+fn default_notify_dedup() -> u64 {
+    60
+}
+
+fn default_sidebar_width() -> u16 {
+    35
+}
+
+fn default_agent_command() -> String {
+    "claude".to_string()
+}
+
+fn default_tmux_hooks() -> bool {
+    true
+}
+
+fn default_popup_replies() -> Vec<String> {
+    vec![
+        "continue".to_string(),
+        "run the tests and fix any failures".to_string(),
+    ]
+}
+
+fn default_return_key() -> String {
+    "C-t".to_string()
+}
+
+/// Which tmux names are rewritten to reflect agent state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusTitles {
+    /// Leave window names and pane titles alone
+    #[default]
+    Off,
+    /// Rename windows (`rename-window`)
+    Window,
+    /// Set pane titles (`select-pane -T`)
+    Pane,
+    /// Rename windows and set pane titles
+    Both,
+}
+
+impl StatusTitles {
+    /// Returns true if window names are rewritten
+    pub fn windows(self) -> bool {
+        matches!(self, StatusTitles::Window | StatusTitles::Both)
+    }
+
+    /// Returns true if pane titles are rewritten
+    pub fn panes(self) -> bool {
+        matches!(self, StatusTitles::Pane | StatusTitles::Both)
+    }
+}
+
+/// A predefined arrangement of agent panes in one window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LayoutConfig {
+    /// Session to create the window in; created if it doesn't exist.
+    /// Defaults to tmux's current session.
+    #[serde(default)]
+    pub session: Option<String>,
+
+    /// Name of the created window
+    #[serde(default)]
+    pub window_name: Option<String>,
+
+    /// tmux layout applied once all panes exist
+    /// (tiled, even-horizontal, even-vertical, main-horizontal, main-vertical)
+    #[serde(default = "default_arrangement")]
+    pub arrangement: String,
+
+    /// Panes in creation order
+    #[serde(default)]
+    pub panes: Vec<LayoutSlot>,
+}
+
+/// One pane of a layout
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LayoutSlot {
+    /// Working directory (`~` expands to the home directory); defaults to
+    /// the directory `tmuxcc layout apply` runs in
+    #[serde(default)]
+    pub cwd: Option<String>,
+
+    /// Startup command; empty leaves a plain shell (e.g., for a monitor pane)
+    #[serde(default)]
+    pub command: String,
+}
+
+fn default_arrangement() -> String {
+    "tiled".to_string()
+}
+
 /// Pattern for detecting agent types
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct AgentPattern {
     /// Command pattern to match (regex)
     pub pattern: String,
@@ -35,42 +309,734 @@ pub struct AgentPattern {
     pub agent_type: String,
 }
 
+/// Regexes matched against an agent's recent output, each list mapping to a state
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StatePatterns {
+    /// Agents the patterns apply to, by name (e.g. "claude"); empty for all
+    #[serde(default)]
+    pub agents: Vec<String>,
+    /// The agent waits for approval or an answer
+    #[serde(default)]
+    pub waiting: Vec<String>,
+    /// The agent is working
+    #[serde(default)]
+    pub working: Vec<String>,
+    /// The agent is ready for input
+    #[serde(default)]
+    pub idle: Vec<String>,
+    /// The agent hit an error
+    #[serde(default)]
+    pub error: Vec<String>,
+}
+
+impl StatePatterns {
+    /// Returns true if no pattern is defined
+    pub fn is_empty(&self) -> bool {
+        self.waiting.is_empty()
+            && self.working.is_empty()
+            && self.idle.is_empty()
+            && self.error.is_empty()
+    }
+}
+
+/// Rules for answering Claude Code permission prompts without the user
+///
+/// Patterns are regexes matched against the request as `Tool(input)`, e.g.
+/// `Bash(git push origin main)` or `Read(src/main.rs)`. Deny wins over allow;
+/// prompts matching neither are left to the user.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AutoApproveConfig {
+    /// Whether prompts are answered at all
+    #[serde(default)]
+    pub enabled: bool,
+    /// Requests approved automatically
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Requests never approved automatically
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// Directories whose `.tmuxcc.toml` files may approve requests; the deny
+    /// rules of other projects' files are still honored
+    #[serde(default)]
+    pub trusted_projects: Vec<String>,
+    /// Where decisions are logged; defaults to `auto-approve.log` in the data directory
+    #[serde(default)]
+    pub log_file: Option<PathBuf>,
+}
+
+/// Stalled-agent detection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WatchdogConfig {
+    /// Seconds without new output before a working agent counts as stalled
+    /// (0 disables the watchdog)
+    #[serde(default = "default_stall_secs")]
+    pub stall_secs: u64,
+    /// Shell command run for each stall, with `TMUXCC_PANE`, `TMUXCC_PATH`
+    /// and `TMUXCC_QUIET_SECS` set
+    #[serde(default)]
+    pub hook: String,
+}
+
+fn default_stall_secs() -> u64 {
+    600
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            stall_secs: default_stall_secs(),
+            hook: String::new(),
+        }
+    }
+}
+
+/// Events the notifiers can be told about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyOn {
+    /// An agent waits for a permission prompt to be answered
+    Permission,
+    /// An agent asks a question
+    Input,
+    /// An agent failed
+    Error,
+    /// A working agent became idle
+    Finished,
+    /// The watchdog flagged a working agent
+    Stalled,
+}
+
+impl NotifyOn {
+    /// Returns the event of a state change, `None` for changes nobody is
+    /// called back for and for panes seen for the first time
+    pub fn from_transition(from: Option<AgentState>, to: AgentState) -> Option<Self> {
+        match (from?, to) {
+            (_, AgentState::WaitingForPermission) => Some(NotifyOn::Permission),
+            (_, AgentState::WaitingForInput) => Some(NotifyOn::Input),
+            (_, AgentState::Error) => Some(NotifyOn::Error),
+            (AgentState::Working, AgentState::Idle) => Some(NotifyOn::Finished),
+            _ => None,
+        }
+    }
+
+    /// Says what happened, after the pane's name
+    pub fn text(self) -> &'static str {
+        match self {
+            NotifyOn::Permission => "asks for permission",
+            NotifyOn::Input => "asks a question",
+            NotifyOn::Error => "failed",
+            NotifyOn::Finished => "finished",
+            NotifyOn::Stalled => "looks stuck",
+        }
+    }
+}
+
+/// Native desktop notifications: notify-send on Linux, terminal-notifier or
+/// osascript on macOS
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DesktopNotifications {
+    /// Whether notifications are shown at all
+    #[serde(default)]
+    pub enabled: bool,
+    /// Events shown
+    #[serde(default = "default_notify_on")]
+    pub on: Vec<NotifyOn>,
+}
+
+fn default_notify_on() -> Vec<NotifyOn> {
+    vec![NotifyOn::Permission, NotifyOn::Error]
+}
+
+impl Default for DesktopNotifications {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            on: default_notify_on(),
+        }
+    }
+}
+
+impl DesktopNotifications {
+    /// Returns true if notifications are on for `event`
+    pub fn wants(&self, event: NotifyOn) -> bool {
+        self.enabled && self.on.contains(&event)
+    }
+}
+
+/// Alerts shown through tmux itself: a `display-message` on the attached
+/// clients and a bell in the agent's window, which tmux flags in the
+/// window list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TmuxAlerts {
+    /// Whether alerts are shown at all
+    #[serde(default)]
+    pub enabled: bool,
+    /// Events shown
+    #[serde(default = "default_alert_on")]
+    pub on: Vec<NotifyOn>,
+    /// Also ring the bell in the agent's window
+    #[serde(default = "default_alert_bell")]
+    pub bell: bool,
+}
+
+fn default_alert_bell() -> bool {
+    true
+}
+
+fn default_alert_on() -> Vec<NotifyOn> {
+    vec![NotifyOn::Permission, NotifyOn::Input]
+}
+
+impl Default for TmuxAlerts {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            on: default_alert_on(),
+            bell: true,
+        }
+    }
+}
+
+impl TmuxAlerts {
+    /// Returns true if alerts are on for `event`
+    pub fn wants(&self, event: NotifyOn) -> bool {
+        self.enabled && self.on.contains(&event)
+    }
+}
+
+/// Sound of each event: "bell" rings the terminal bell, anything else is a
+/// sound file played with afplay on macOS and paplay or aplay on Linux;
+/// empty stays silent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SoundsConfig {
+    /// Whether sounds are played at all
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_bell")]
+    pub permission: String,
+    #[serde(default)]
+    pub input: String,
+    #[serde(default = "default_bell")]
+    pub error: String,
+    #[serde(default)]
+    pub finished: String,
+    #[serde(default)]
+    pub stalled: String,
+}
+
+fn default_bell() -> String {
+    "bell".to_string()
+}
+
+impl Default for SoundsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            permission: default_bell(),
+            input: String::new(),
+            error: default_bell(),
+            finished: String::new(),
+            stalled: String::new(),
+        }
+    }
+}
+
+impl SoundsConfig {
+    /// Returns the sound of `event`, `None` if it is silent or sounds are off
+    pub fn sound_for(&self, event: NotifyOn) -> Option<&str> {
+        let sound = match event {
+            NotifyOn::Permission => &self.permission,
+            NotifyOn::Input => &self.input,
+            NotifyOn::Error => &self.error,
+            NotifyOn::Finished => &self.finished,
+            NotifyOn::Stalled => &self.stalled,
+        };
+        (self.enabled && !sound.is_empty()).then_some(sound.as_str())
+    }
+}
+
+/// A URL notified of state changes with curl
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Events sent; empty sends all of them
+    #[serde(default)]
+    pub on: Vec<NotifyOn>,
+    /// Body template; `{pane}`, `{pane_id}`, `{label}`, `{path}`, `{repo}`,
+    /// `{branch}`, `{event}`, `{from}`, `{to}`, `{snippet}`, `{at}`,
+    /// `{message}` (e.g. "api asks for permission on app (main)") and
+    /// `{emoji}` are replaced by their JSON-escaped values. Empty sends every
+    /// field as a JSON object
+    #[serde(default)]
+    pub body: String,
+    /// Extra headers like "Authorization: Bearer ..."
+    #[serde(default)]
+    pub headers: Vec<String>,
+    /// Attempts after a failed one, with doubling pauses from one second
+    #[serde(default = "default_webhook_retries")]
+    pub retries: u32,
+    /// Least time between two requests; requests beyond a short queue are
+    /// dropped while waiting
+    #[serde(default)]
+    pub min_interval_ms: u64,
+}
+
+fn default_webhook_retries() -> u32 {
+    3
+}
+
+impl WebhookConfig {
+    /// Returns true if `event` is sent to this webhook
+    pub fn wants(&self, event: NotifyOn) -> bool {
+        self.on.is_empty() || self.on.contains(&event)
+    }
+}
+
+/// Where notifications can go
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Notifier {
+    Desktop,
+    /// `tmux_alerts`
+    Tmux,
+    Sound,
+    /// Every entry of `webhooks`
+    Webhook,
+    Slack,
+    Discord,
+}
+
+/// Routing of the notifications about some panes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotifyRule {
+    /// Panes the rule is about, in the broadcast filter syntax
+    /// ("repo:app label:api state:..."); empty matches every pane
+    #[serde(rename = "match", default)]
+    pub filter: String,
+    /// Events the rule is about; empty matches every event
+    #[serde(default)]
+    pub on: Vec<NotifyOn>,
+    /// Notifiers told, if set up; empty keeps the matching events quiet
+    #[serde(default)]
+    pub notify: Vec<Notifier>,
+    /// Slack channel instead of the configured one (bot token only)
+    #[serde(default)]
+    pub channel: String,
+}
+
+/// How often some panes are captured
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PanePolling {
+    /// Panes the rule is about, in the broadcast filter syntax; empty
+    /// matches every pane
+    #[serde(rename = "match", default)]
+    pub filter: String,
+    /// Milliseconds between captures of the panes; 0 keeps `poll_interval_ms`
+    #[serde(default)]
+    pub interval_ms: u64,
+    /// Stops monitoring the panes until they close or the config changes
+    #[serde(default)]
+    pub ignore: bool,
+}
+
+/// Slack messages, through an incoming webhook or a bot token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SlackConfig {
+    /// Incoming webhook URL
+    #[serde(default)]
+    pub webhook_url: String,
+    /// Bot token (`xoxb-...`) posting to `channel`, instead of a webhook
+    #[serde(default)]
+    pub token: String,
+    #[serde(default)]
+    pub channel: String,
+    /// Events sent
+    #[serde(default = "default_notify_on")]
+    pub on: Vec<NotifyOn>,
+}
+
+impl Default for SlackConfig {
+    fn default() -> Self {
+        Self {
+            webhook_url: String::new(),
+            token: String::new(),
+            channel: String::new(),
+            on: default_notify_on(),
+        }
+    }
+}
+
+/// Discord messages through a channel webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DiscordConfig {
+    #[serde(default)]
+    pub webhook_url: String,
+    /// Events sent
+    #[serde(default = "default_notify_on")]
+    pub on: Vec<NotifyOn>,
+}
+
+impl Default for DiscordConfig {
+    fn default() -> Self {
+        Self {
+            webhook_url: String::new(),
+            on: default_notify_on(),
+        }
+    }
+}
+
+/// `.tmuxcc.toml` of a repository, found from the working directory of its
+/// panes, overriding parts of the config for just those panes
+///
+/// ```toml
+/// label = "api"
+/// test_command = "cargo test"
+///
+/// [auto_approve]
+/// allow = ['^Bash\(cargo (build|test) ']
+///
+/// [[notify_rules]]
+/// notify = ["slack"]
+/// channel = "#api"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Label of the panes that weren't given one
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Replaces the configured `test_command`
+    #[serde(default)]
+    pub test_command: Option<String>,
+    /// Rules checked before the configured ones, though the configured deny
+    /// rules still have the last word; allow rules only count in a directory
+    /// of `auto_approve.trusted_projects`
+    #[serde(default)]
+    pub auto_approve: ProjectAutoApprove,
+    /// Rules checked before the configured `notify_rules`; an empty `match`
+    /// applies to every pane of the project
+    #[serde(default)]
+    pub notify_rules: Vec<NotifyRule>,
+    /// File the config was read from
+    #[serde(skip)]
+    pub source: PathBuf,
+}
+
+/// The `[auto_approve]` table of a `.tmuxcc.toml`; whether prompts are
+/// answered at all stays up to the config
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectAutoApprove {
+    #[serde(default)]
+    pub allow: Vec<String>,
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+/// Shell commands run with `sh -c` on events, empty to run nothing
+///
+/// Commands get the pane in `TMUXCC_*` variables: `EVENT`, `PANE`,
+/// `PANE_ID`, `LABEL`, `PATH`, `REPO`, `BRANCH`, `AGENT`, `FROM`, `TO`,
+/// `SNIPPET` and `COST`, plus `QUIET_SECS` for stalls.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Any state change of a pane seen before
+    #[serde(default)]
+    pub state_change: String,
+    #[serde(default)]
+    pub permission: String,
+    #[serde(default)]
+    pub input: String,
+    #[serde(default)]
+    pub error: String,
+    /// A working agent became idle
+    #[serde(default)]
+    pub finished: String,
+    #[serde(default)]
+    pub stalled: String,
+    /// A pane's cost reached `cost_threshold`
+    #[serde(default)]
+    pub cost: String,
+    /// Dollars per pane; 0 disables the cost hook
+    #[serde(default)]
+    pub cost_threshold: f64,
+}
+
+impl HooksConfig {
+    /// Returns the command run for `event`, empty for none
+    pub fn for_event(&self, event: NotifyOn) -> &str {
+        match event {
+            NotifyOn::Permission => &self.permission,
+            NotifyOn::Input => &self.input,
+            NotifyOn::Error => &self.error,
+            NotifyOn::Finished => &self.finished,
+            NotifyOn::Stalled => &self.stalled,
+        }
+    }
+
+    /// Returns true if no command is set
+    pub fn is_empty(&self) -> bool {
+        [
+            &self.state_change,
+            &self.permission,
+            &self.input,
+            &self.error,
+            &self.finished,
+            &self.stalled,
+            &self.cost,
+        ]
+        .iter()
+        .all(|command| command.is_empty())
+    }
+}
+
+/// A routine question answered automatically
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AutoRespondRule {
+    /// Regex matched against the last lines of the pane
+    pub pattern: String,
+    /// Text typed before Enter; empty only presses Enter (accepts the default choice)
+    #[serde(default)]
+    pub reply: String,
+}
+
+/// An agent defined in the config, detected and parsed with regexes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomAgent {
+    /// Name shown in the sidebar
+    pub name: String,
+    /// Matched against the pane's command, title and process command lines
+    #[serde(default)]
+    pub process: Vec<String>,
+    /// Matched against recent output: the agent waits for approval or an answer
+    #[serde(default)]
+    pub prompt: Vec<String>,
+    /// Matched against recent output: the agent is working
+    #[serde(default)]
+    pub processing: Vec<String>,
+    /// Matched against recent output: the agent is ready for input
+    #[serde(default)]
+    pub idle: Vec<String>,
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             poll_interval_ms: default_poll_interval(),
             capture_lines: default_capture_lines(),
+            pane_polling: Vec::new(),
             agent_patterns: Vec::new(),
+            enabled_agents: Vec::new(),
+            custom_agents: Vec::new(),
+            state_patterns: StatePatterns::default(),
+            auto_approve: AutoApproveConfig::default(),
+            auto_respond: Vec::new(),
+            context_warning: default_context_warning(),
+            rate_limit_resume: String::new(),
+            watchdog: WatchdogConfig::default(),
+            desktop_notifications: DesktopNotifications::default(),
+            tmux_alerts: TmuxAlerts::default(),
+            sounds: SoundsConfig::default(),
+            webhooks: Vec::new(),
+            slack: SlackConfig::default(),
+            discord: DiscordConfig::default(),
+            hooks: HooksConfig::default(),
+            notify_rules: Vec::new(),
+            notify_dedup_secs: default_notify_dedup(),
+            summarize_cmd: String::new(),
+            test_command: String::new(),
+            agent_command: default_agent_command(),
+            control_mode: false,
+            tmux_hooks: default_tmux_hooks(),
+            return_key: default_return_key(),
+            status_titles: StatusTitles::Off,
+            layouts: BTreeMap::new(),
+            sockets: Vec::new(),
+            hosts: Vec::new(),
+            popup_replies: default_popup_replies(),
+            sidebar_width: default_sidebar_width(),
+            keys: KeysConfig::default(),
+            theme: ThemeConfig::default(),
+            use_icons: UseIcons::default(),
+            profiles: BTreeMap::new(),
+            source: None,
+            profile: None,
+            env: Vec::new(),
         }
     }
 }
 
 impl Config {
+    /// Returns the local servers listed in `sockets` (just the default one
+    /// when empty) followed by the servers on `hosts`
+    pub fn servers(&self) -> Vec<TmuxServer> {
+        let local = if self.sockets.is_empty() {
+            vec![TmuxServer::Default]
+        } else {
+            self.sockets.iter().map(|s| TmuxServer::parse(s)).collect()
+        };
+        let remote = self
+            .hosts
+            .iter()
+            .map(|h| h.trim())
+            .filter(|h| !h.is_empty())
+            .map(|h| TmuxServer::Remote(h.to_string()));
+
+        let mut servers: Vec<TmuxServer> = Vec::new();
+        for server in local.into_iter().chain(remote) {
+            if !servers.contains(&server) {
+                servers.push(server);
+            }
+        }
+        servers
+    }
+
     /// Returns the default config file path
+    ///
+    /// That is `$XDG_CONFIG_HOME/tmuxcc/config.toml`, or
+    /// `~/.config/tmuxcc/config.toml` without it, on every platform. A file
+    /// at the platform's own config directory (`~/Library/Application
+    /// Support` on macOS) is still used if only it exists.
     pub fn default_path() -> Option<PathBuf> {
-        dirs::config_dir().map(|p| p.join("tmuxcc").join("config.toml"))
-    }
-
-    /// Loads config from the default path or returns defaults
-    pub fn load() -> Self {
-        Self::default_path()
-            .and_then(|path| {
-                if path.exists() {
-                    Self::load_from(&path).ok()
-                } else {
-                    None
-                }
-            })
-            .unwrap_or_default()
-    }
-
-    /// Loads config from a specific path
-    pub fn load_from(path: &PathBuf) -> Result<Self> {
-        let content = std::fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&content)?;
+        let xdg = xdg_path(std::env::var_os("XDG_CONFIG_HOME"), dirs::home_dir());
+        let platform = dirs::config_dir().map(|p| p.join("tmuxcc").join("config.toml"));
+        match (xdg, platform) {
+            (Some(xdg), Some(platform)) if !xdg.exists() && platform.exists() => Some(platform),
+            (xdg, platform) => xdg.or(platform),
+        }
+    }
+
+    /// Loads config from the default path with `profile` laid over it, or
+    /// returns defaults if there is no config file
+    ///
+    /// Fails on a config file that can't be read or parsed, rather than
+    /// silently running with defaults.
+    pub fn load(profile: Option<&str>) -> Result<Self> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load_from(&path, profile),
+            _ => match profile {
+                Some(name) => bail!("Unknown profile {:?} (no config file)", name),
+                None => Self::default().with_env(std::env::vars()),
+            },
+        }
+    }
+
+    /// Loads config from a specific path with `profile` laid over it
+    ///
+    /// Parse errors name the line, the column and the offending key, and
+    /// unknown keys (usually typos) are errors too.
+    pub fn load_from(path: &Path, profile: Option<&str>) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut config: Config = toml::from_str(&content)
+            .with_context(|| format!("Invalid config {}", path.display()))?;
+        if let Some(name) = profile {
+            config = config
+                .with_profile(&content, name)
+                .with_context(|| format!("Invalid config {}", path.display()))?;
+        }
+        config = config.with_env(std::env::vars())?;
+        config.source = Some(path.to_path_buf());
         Ok(config)
     }
 
+    /// Returns the config in `content` (this config, parsed) with the
+    /// profile `name` laid over it
+    pub(crate) fn with_profile(&self, content: &str, name: &str) -> Result<Self> {
+        let Some(profile) = self.profiles.get(name) else {
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            bail!("Unknown profile {:?} (known: {})", name, known.join(", "));
+        };
+        let mut table: toml::Table = toml::from_str(content)?;
+        merge_tables(&mut table, profile.clone());
+        let mut config = Config::deserialize(toml::Value::Table(table))
+            .with_context(|| format!("in [profiles.{}]", name))?;
+        config.profile = Some(name.to_string());
+        Ok(config)
+    }
+
+    /// Returns this config with the `TMUXCC_*` variables among `vars` laid
+    /// over it
+    ///
+    /// `TMUXCC_SOUNDS_ENABLED=1` sets `sounds.enabled`: the name is the key
+    /// path joined by underscores, an array takes a comma-separated list or
+    /// TOML, and `TMUXCC_POLL_MS`, `TMUXCC_SOCKET`, `TMUXCC_HOST` and
+    /// `TMUXCC_THEME` are short for common keys. Variables that name no key,
+    /// like the ones tmuxcc sets for hooks, are left alone.
+    pub fn with_env(self, vars: impl IntoIterator<Item = (String, String)>) -> Result<Self> {
+        let mut table = toml::Table::try_from(&self)?;
+        let mut overlay = toml::Table::new();
+        let mut names = Vec::new();
+        for (name, raw) in vars {
+            let Some(key) = name.strip_prefix("TMUXCC_") else {
+                continue;
+            };
+            let key = key.to_lowercase();
+            let key = match key.as_str() {
+                "poll_ms" => "poll_interval_ms",
+                "socket" => "sockets",
+                "host" => "hosts",
+                "theme" => "theme_name",
+                key => key,
+            };
+            let Some((path, current)) = env_key(&table, key) else {
+                continue;
+            };
+            let value =
+                env_value(&raw, current).with_context(|| format!("Invalid {}={}", name, raw))?;
+            let mut target = &mut overlay;
+            let (last, parents) = path.split_last().expect("key paths aren't empty");
+            for parent in parents {
+                target = target
+                    .entry(parent.clone())
+                    .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                    .as_table_mut()
+                    .expect("parents are tables");
+            }
+            target.insert(last.clone(), value);
+            names.push(name);
+        }
+        if names.is_empty() {
+            return Ok(self);
+        }
+
+        names.sort();
+        merge_tables(&mut table, overlay);
+        let mut config = Config::deserialize(toml::Value::Table(table))
+            .with_context(|| format!("Invalid environment ({})", names.join(", ")))?;
+        config.source = self.source;
+        config.profile = self.profile;
+        config.env = names;
+        Ok(config)
+    }
+
+    /// Writes the commented default config to `path`, refusing to replace an
+    /// existing file unless `force` is set
+    pub fn write_default(path: &Path, force: bool) -> Result<()> {
+        if path.exists() && !force {
+            bail!("{} already exists (--force replaces it)", path.display());
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, DEFAULT_CONFIG)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
     /// Saves config to the default path
     pub fn save(&self) -> Result<()> {
         if let Some(path) = Self::default_path() {
@@ -90,15 +1056,203 @@ impl Config {
     }
 }
 
+/// Lays `overlay` over `base`: tables merge key by key, other values
+/// (arrays included) replace those in `base`
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => {
+                merge_tables(base, overlay)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Returns the path of the key `name` (lowercase, without `TMUXCC_`) names
+/// in `table`, with its current value; `None` if it names no key
+///
+/// Keys are matched longest first, so `auto_approve_enabled` is
+/// `auto_approve.enabled`. Within a table, a rest that isn't one of its
+/// current keys (a theme color, a key binding) is taken as a new key.
+fn env_key<'a>(
+    table: &'a toml::Table,
+    name: &str,
+) -> Option<(Vec<String>, Option<&'a toml::Value>)> {
+    let mut keys: Vec<(&String, &toml::Value)> = table.iter().collect();
+    keys.sort_by_key(|(key, _)| std::cmp::Reverse(key.len()));
+    keys.into_iter().find_map(|(key, value)| {
+        if name == key {
+            return Some((vec![key.clone()], Some(value)));
+        }
+        let rest = name.strip_prefix(key.as_str())?.strip_prefix('_')?;
+        let inner = value.as_table()?;
+        let (mut path, current) =
+            env_key(inner, rest).unwrap_or_else(|| (vec![rest.to_string()], None));
+        path.insert(0, key.clone());
+        Some((path, current))
+    })
+}
+
+/// Parses the environment value `raw` for a key whose value is `current`
+/// (`None` for a key not set yet)
+fn env_value(raw: &str, current: Option<&toml::Value>) -> Result<toml::Value> {
+    let raw = raw.trim();
+    Ok(match current {
+        Some(toml::Value::Boolean(_)) => match raw.to_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => toml::Value::Boolean(true),
+            "0" | "false" | "no" | "off" | "" => toml::Value::Boolean(false),
+            _ => bail!("expected true or false"),
+        },
+        Some(toml::Value::Integer(_)) => {
+            toml::Value::Integer(raw.parse().context("expected an integer")?)
+        }
+        Some(toml::Value::Float(_)) => {
+            toml::Value::Float(raw.parse().context("expected a number")?)
+        }
+        Some(toml::Value::Array(_)) if !raw.starts_with('[') => toml::Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| toml::Value::String(item.to_string()))
+                .collect(),
+        ),
+        Some(toml::Value::Array(_) | toml::Value::Table(_)) => toml_value(raw)?,
+        None if raw.starts_with('[') || raw.starts_with('{') => toml_value(raw)?,
+        _ => toml::Value::String(raw.to_string()),
+    })
+}
+
+/// Parses `raw` as a TOML value, such as an inline table or an array
+fn toml_value(raw: &str) -> Result<toml::Value> {
+    let mut table: toml::Table = toml::from_str(&format!("value = {}", raw))?;
+    Ok(table
+        .remove("value")
+        .expect("parsed as a table with the key"))
+}
+
+/// Returns `tmuxcc/config.toml` in `XDG_CONFIG_HOME` if that is set to an
+/// absolute path (as the spec asks), in `~/.config` otherwise
+fn xdg_path(xdg_config_home: Option<OsString>, home: Option<PathBuf>) -> Option<PathBuf> {
+    let base = xdg_config_home
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| home.map(|home| home.join(".config")))?;
+    Some(base.join("tmuxcc").join("config.toml"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::app::ThemeName;
 
     #[test]
     fn test_default_config() {
         let config = Config::default();
         assert_eq!(config.poll_interval_ms, 500);
         assert_eq!(config.capture_lines, 100);
+        assert_eq!(config.return_key, "C-t");
+    }
+
+    #[test]
+    fn test_default_template() {
+        // The template must stay in step with the defaults
+        let template: Config = toml::from_str(DEFAULT_CONFIG).unwrap();
+        assert_eq!(
+            toml::to_string(&template).unwrap(),
+            toml::to_string(&Config::default()).unwrap()
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tmuxcc/config.toml");
+        Config::write_default(&path, false).unwrap();
+        assert!(Config::write_default(&path, false).is_err());
+        Config::write_default(&path, true).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), DEFAULT_CONFIG);
+    }
+
+    #[test]
+    fn test_sounds() {
+        let config = Config::default();
+        assert_eq!(config.sounds.sound_for(NotifyOn::Error), None);
+
+        let config: Config = toml::from_str(
+            r#"
+            [sounds]
+            enabled = true
+            finished = "/usr/share/sounds/done.oga"
+            error = ""
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.sounds.sound_for(NotifyOn::Permission), Some("bell"));
+        assert_eq!(
+            config.sounds.sound_for(NotifyOn::Finished),
+            Some("/usr/share/sounds/done.oga")
+        );
+        assert_eq!(config.sounds.sound_for(NotifyOn::Error), None);
+    }
+
+    #[test]
+    fn test_tmux_alerts() {
+        let config: Config = toml::from_str("[tmux_alerts]\nenabled = true").unwrap();
+        assert!(config.tmux_alerts.wants(NotifyOn::Input));
+        assert!(!config.tmux_alerts.wants(NotifyOn::Finished));
+        assert!(config.tmux_alerts.bell);
+        assert!(!Config::default().tmux_alerts.wants(NotifyOn::Permission));
+    }
+
+    #[test]
+    fn test_parse_layouts() {
+        let config: Config = toml::from_str(
+            r#"
+[layouts.quad]
+session = "agents"
+
+[[layouts.quad.panes]]
+cwd = "~/code/app"
+command = "claude"
+
+[[layouts.quad.panes]]
+command = "codex"
+"#,
+        )
+        .unwrap();
+        let quad = &config.layouts["quad"];
+        assert_eq!(quad.session.as_deref(), Some("agents"));
+        assert_eq!(quad.arrangement, "tiled");
+        assert_eq!(quad.panes.len(), 2);
+        assert_eq!(quad.panes[0].cwd.as_deref(), Some("~/code/app"));
+        assert_eq!(quad.panes[1].command, "codex");
+    }
+
+    #[test]
+    fn test_servers() {
+        assert_eq!(Config::default().servers(), vec![TmuxServer::Default]);
+
+        let config: Config =
+            toml::from_str(r#"sockets = ["default", "agents", "/tmp/tmux-1000/work", "agents"]"#)
+                .unwrap();
+        assert_eq!(
+            config.servers(),
+            vec![
+                TmuxServer::Default,
+                TmuxServer::Name("agents".to_string()),
+                TmuxServer::Path(PathBuf::from("/tmp/tmux-1000/work")),
+            ]
+        );
+
+        let config: Config = toml::from_str(r#"hosts = ["dev1", "gpu-box"]"#).unwrap();
+        assert_eq!(
+            config.servers(),
+            vec![
+                TmuxServer::Default,
+                TmuxServer::Remote("dev1".to_string()),
+                TmuxServer::Remote("gpu-box".to_string()),
+            ]
+        );
     }
 
     #[test]
@@ -108,4 +1262,140 @@ mod tests {
         let parsed: Config = toml::from_str(&toml_str).unwrap();
         assert_eq!(config.poll_interval_ms, parsed.poll_interval_ms);
     }
+
+    #[test]
+    fn test_config_source() {
+        assert_eq!(Config::default().source, None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "poll_interval_ms = 250").unwrap();
+        let config = Config::load_from(&path, None).unwrap();
+        assert_eq!(config.poll_interval_ms, 250);
+        assert_eq!(config.source, Some(path.clone()));
+        assert!(!toml::to_string(&config).unwrap().contains("source"));
+
+        // Typos are errors naming the key, not silently ignored
+        std::fs::write(&path, "[sounds]\nenable = true").unwrap();
+        let error = format!("{:#}", Config::load_from(&path, None).unwrap_err());
+        assert!(error.contains("unknown field `enable`"), "{}", error);
+        assert!(error.contains("line 2"), "{}", error);
+    }
+
+    #[test]
+    fn test_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            r#"
+            sockets = ["default"]
+            hosts = ["devbox"]
+
+            [theme]
+            name = "light"
+            accent = "red"
+
+            [profiles.work]
+            sockets = ["work"]
+            theme = { accent = "blue" }
+
+            [profiles.typo]
+            sokets = ["work"]
+            "#,
+        )
+        .unwrap();
+
+        let config = Config::load_from(&path, None).unwrap();
+        assert_eq!(config.sockets, vec!["default"]);
+        assert_eq!(config.profile, None);
+
+        // Arrays are replaced, tables merged
+        let config = Config::load_from(&path, Some("work")).unwrap();
+        assert_eq!(config.sockets, vec!["work"]);
+        assert_eq!(config.hosts, vec!["devbox"]);
+        assert_eq!(config.theme.colors["accent"], "blue");
+        assert_eq!(config.profile.as_deref(), Some("work"));
+
+        let error = format!("{:#}", Config::load_from(&path, Some("home")).unwrap_err());
+        assert!(error.contains("known: typo, work"), "{}", error);
+        let error = format!("{:#}", Config::load_from(&path, Some("typo")).unwrap_err());
+        assert!(error.contains("[profiles.typo]"), "{}", error);
+        assert!(error.contains("unknown field `sokets`"), "{}", error);
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let vars = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        let config = Config::default()
+            .with_env(vars(&[
+                ("TMUXCC_POLL_MS", "250"),
+                ("TMUXCC_SOCKET", "work, /tmp/tmux-1000/other"),
+                ("TMUXCC_SOUNDS_ENABLED", "yes"),
+                ("TMUXCC_AUTO_APPROVE_ALLOW", "['^Read\\(']"),
+                ("TMUXCC_HOOKS_COST_THRESHOLD", "2.5"),
+                ("TMUXCC_THEME", "light"),
+                ("TMUXCC_THEME_ACCENT", "red"),
+                ("TMUXCC_STATUS_TITLES", "both"),
+                // Set for hooks, not settings
+                ("TMUXCC_PANE_ID", "%3"),
+                ("TMUX", "/tmp/tmux-1000/default,1,0"),
+            ]))
+            .unwrap();
+        assert_eq!(config.poll_interval_ms, 250);
+        assert_eq!(config.sockets, vec!["work", "/tmp/tmux-1000/other"]);
+        assert!(config.sounds.enabled);
+        assert_eq!(config.auto_approve.allow, vec!["^Read\\("]);
+        assert_eq!(config.hooks.cost_threshold, 2.5);
+        assert_eq!(config.theme.name, ThemeName::Light);
+        assert_eq!(config.theme.colors["accent"], "red");
+        assert_eq!(config.status_titles, StatusTitles::Both);
+        assert_eq!(config.env.len(), 8);
+        // The rest stays as it was
+        assert_eq!(config.capture_lines, 100);
+
+        for bad in [
+            ("TMUXCC_POLL_MS", "fast"),
+            ("TMUXCC_SOUNDS_ENABLED", "maybe"),
+            ("TMUXCC_USE_ICONS", "emoji"),
+        ] {
+            let error = Config::default().with_env(vars(&[bad])).unwrap_err();
+            assert!(format!("{:#}", error).contains(bad.0), "{:#}", error);
+        }
+    }
+
+    #[test]
+    fn test_readme_example() {
+        let readme = include_str!("../../README.md");
+        let start = readme.find("```toml\n# Polling interval").unwrap() + "```toml\n".len();
+        let example = &readme[start..];
+        let example = &example[..example.find("```").unwrap()];
+        if let Err(e) = toml::from_str::<Config>(example) {
+            panic!("README config example: {}", e);
+        }
+    }
+
+    #[test]
+    fn test_xdg_path() {
+        let home = Some(PathBuf::from("/home/me"));
+        assert_eq!(
+            xdg_path(None, home.clone()),
+            Some(PathBuf::from("/home/me/.config/tmuxcc/config.toml"))
+        );
+        assert_eq!(
+            xdg_path(Some("/xdg".into()), home.clone()),
+            Some(PathBuf::from("/xdg/tmuxcc/config.toml"))
+        );
+        // Relative values are ignored, as the spec says
+        assert_eq!(
+            xdg_path(Some("xdg".into()), home),
+            Some(PathBuf::from("/home/me/.config/tmuxcc/config.toml"))
+        );
+        assert_eq!(xdg_path(None, None), None);
+    }
 }
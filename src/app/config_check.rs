@@ -0,0 +1,256 @@
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use super::{Config, Keymap, Theme};
+use crate::agents::AgentFilter;
+use crate::parsers::ParserRegistry;
+
+/// One problem found in a config file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Line and column (both from 1) of the offending value, if known
+    pub position: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some((line, column)) => write!(f, "{}:{}: {}", line, column, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Checks the config file at `path`; fails only if it can't be read
+pub fn check_file(path: &Path) -> Result<Vec<Diagnostic>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(check(&content))
+}
+
+/// Returns the problems of the config in `content`: TOML syntax, unknown
+/// keys and bad values first, then invalid patterns, filters, keys, colors
+/// and missing sound files
+pub fn check(content: &str) -> Vec<Diagnostic> {
+    let config: Config = match toml::from_str(content) {
+        Ok(config) => config,
+        Err(e) => {
+            let position = e.span().map(|span| position(content, span.start));
+            return vec![Diagnostic {
+                position,
+                message: e.message().trim().replace('\n', ": "),
+            }];
+        }
+    };
+    let mut checker = Checker {
+        content,
+        diagnostics: Vec::new(),
+    };
+    checker.config(&config);
+    for name in config.profiles.keys() {
+        if let Err(e) = config.with_profile(content, name) {
+            let header = format!("[profiles.{}]", name);
+            checker.report(&header, format!("{:#}", e));
+        }
+    }
+    checker.diagnostics
+}
+
+struct Checker<'a> {
+    content: &'a str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn config(&mut self, config: &Config) {
+        for pattern in &config.agent_patterns {
+            if let Err(e) = Regex::new(&pattern.pattern) {
+                self.bad_pattern(&pattern.pattern, "agent_patterns", &e);
+            }
+        }
+        for agent in &config.custom_agents {
+            let owner = format!("custom agent {}", agent.name);
+            if agent.process.is_empty() {
+                let name = toml_string(&agent.name);
+                self.report(&name, format!("{} has no process patterns", owner));
+            }
+            for patterns in [
+                &agent.process,
+                &agent.prompt,
+                &agent.processing,
+                &agent.idle,
+            ] {
+                self.output_patterns(patterns, &owner);
+            }
+        }
+        let states = &config.state_patterns;
+        for patterns in [
+            &states.waiting,
+            &states.working,
+            &states.idle,
+            &states.error,
+        ] {
+            self.output_patterns(patterns, "state_patterns");
+        }
+        self.output_patterns(&config.auto_approve.allow, "auto_approve.allow");
+        self.output_patterns(&config.auto_approve.deny, "auto_approve.deny");
+        for rule in &config.auto_respond {
+            self.output_patterns(std::slice::from_ref(&rule.pattern), "auto_respond");
+        }
+        for rule in &config.notify_rules {
+            if let Err(e) = AgentFilter::parse(&rule.filter) {
+                let message = format!("Invalid notify rule match {:?}: {}", rule.filter, e);
+                self.report(&toml_string(&rule.filter), message);
+            }
+        }
+
+        for rule in &config.pane_polling {
+            if let Err(e) = AgentFilter::parse(&rule.filter) {
+                let message = format!("Invalid pane polling match {:?}: {}", rule.filter, e);
+                self.report(&toml_string(&rule.filter), message);
+            }
+        }
+
+        let sounds = &config.sounds;
+        for sound in [
+            &sounds.permission,
+            &sounds.input,
+            &sounds.error,
+            &sounds.finished,
+            &sounds.stalled,
+        ] {
+            if !sound.is_empty() && sound != "bell" && !Path::new(sound).is_file() {
+                let mut message = format!("Sound file {} not found", sound);
+                if sound.starts_with('~') {
+                    message.push_str(" (~ isn't expanded; use the full path)");
+                }
+                self.report(&toml_string(sound), message);
+            }
+        }
+
+        if let Err(e) = Keymap::from_config(&config.keys) {
+            self.report("[keys]", format!("{:#}", e));
+        }
+        if let Err(e) = Theme::from_config(&config.theme) {
+            self.report("[theme]", format!("{:#}", e));
+        }
+        // Whatever the checks above don't cover, such as unknown agents
+        if self.diagnostics.is_empty() {
+            if let Err(e) = ParserRegistry::from_config(config) {
+                self.diagnostics.push(Diagnostic {
+                    position: None,
+                    message: format!("{:#}", e),
+                });
+            }
+        }
+    }
+
+    /// Checks patterns matched against output, which the monitor compiles
+    /// in multi-line mode
+    fn output_patterns(&mut self, patterns: &[String], owner: &str) {
+        for pattern in patterns {
+            if let Err(e) = Regex::new(&format!("(?m){}", pattern)) {
+                self.bad_pattern(pattern, owner, &e);
+            }
+        }
+    }
+
+    fn bad_pattern(&mut self, pattern: &str, owner: &str, error: &regex::Error) {
+        let message = format!(
+            "Invalid pattern {:?} in {}: {}",
+            pattern,
+            owner,
+            regex_error(error)
+        );
+        self.report(&toml_string(pattern), message);
+    }
+
+    /// Adds a diagnostic at the first occurrence of `needle`, without a
+    /// position if it doesn't occur
+    fn report(&mut self, needle: &str, message: String) {
+        let position = locate(self.content, needle).map(|offset| position(self.content, offset));
+        self.diagnostics.push(Diagnostic { position, message });
+    }
+}
+
+/// Returns the last line of a regex error, the one saying what is wrong
+fn regex_error(error: &regex::Error) -> String {
+    let text = error.to_string();
+    let last = text.lines().last().unwrap_or_default();
+    last.strip_prefix("error: ").unwrap_or(last).to_string()
+}
+
+/// Returns `value` as the literal string TOML would have for it
+fn toml_string(value: &str) -> String {
+    format!("'{}'", value)
+}
+
+/// Returns the offset of `needle` in `content`; a literal string is also
+/// looked for in its basic (double-quoted, escaped) form
+fn locate(content: &str, needle: &str) -> Option<usize> {
+    let found = content.find(needle);
+    let basic = needle
+        .strip_prefix('\'')
+        .and_then(|n| n.strip_suffix('\''))
+        .and_then(|value| {
+            let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+            content.find(&format!("\"{}\"", escaped))
+        });
+    match (found, basic) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Returns the line and column (both from 1) at byte `offset` of `content`
+fn position(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::DEFAULT_CONFIG;
+
+    #[test]
+    fn test_check() {
+        assert_eq!(check(DEFAULT_CONFIG), vec![]);
+
+        let typo = check("poll_interval_ms = 250\n[sounds]\nenable = true\n");
+        assert_eq!(typo.len(), 1);
+        assert_eq!(typo[0].position, Some((3, 1)));
+        assert!(typo[0].message.contains("unknown field `enable`"));
+
+        let problems = check(concat!(
+            "[auto_approve]\n",
+            "allow = ['^Read(', \"(x\\\\\"]\n",
+            "[sounds]\n",
+            "error = \"/no/such/sound.oga\"\n",
+            "[[notify_rules]]\n",
+            "match = \"colour:red\"\n",
+        ));
+        let found: Vec<String> = problems.iter().map(|d| d.to_string()).collect();
+        assert_eq!(found.len(), 4, "{:?}", found);
+        assert!(found[0].starts_with("2:10: Invalid pattern \"^Read(\""));
+        assert!(found[0].contains("unclosed group"), "{}", found[0]);
+        assert!(found[1].starts_with("2:20: Invalid pattern \"(x\\\\\""));
+        assert!(found[2].starts_with("6:9: Invalid notify rule match"));
+        assert!(found[3].starts_with("4:9: Sound file /no/such/sound.oga not found"));
+
+        // Problems only a profile has
+        let profile = check("[profiles.work]\nsokets = [\"work\"]\n");
+        assert_eq!(profile[0].position, Some((1, 1)));
+        assert!(profile[0].message.contains("unknown field `sokets`"));
+
+        let unknown = check("enabled_agents = [\"clod\"]");
+        assert_eq!(unknown[0].position, None);
+        assert!(unknown[0].message.contains("clod"));
+    }
+}
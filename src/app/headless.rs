@@ -0,0 +1,145 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result};
+
+use crate::agents::Usage;
+use crate::parsers::stream_json::{render_event, result_usage, session_id, usage_event};
+
+/// Arguments putting Claude Code in print mode with a structured event stream
+const STREAM_ARGS: &[&str] = &["-p", "--output-format", "stream-json", "--verbose"];
+
+/// Event recorded when the agent exits without reporting a result
+const EXITED_EVENT: &str = r#"{"type":"result","subtype":"exited","is_error":true}"#;
+
+/// Returns the default stream file of the tmux pane `pane_id` (e.g. "%12")
+pub fn default_stream_path(pane_id: &str) -> Option<PathBuf> {
+    let name = format!("{}.jsonl", pane_id.trim_start_matches('%'));
+    dirs::cache_dir().map(|p| p.join("tmuxcc").join("streams").join(name))
+}
+
+/// Runs `command` headless, one print mode run per prompt
+///
+/// Prompts after `prompt` are read from stdin, so they are sent to the pane
+/// like to any interactive agent; each run resumes the previous run's
+/// session. Events are appended to `stream` for the monitor, followed by the
+/// session's usage totals after every turn, and rendered to stdout.
+pub fn run(command: &[String], stream: &Path, prompt: Option<String>) -> Result<()> {
+    run_prompts(command, stream, prompt, std::io::stdin().lock())
+}
+
+/// Runs `command` for `prompt` and then for each line of `input`
+fn run_prompts(
+    command: &[String],
+    stream: &Path,
+    prompt: Option<String>,
+    mut input: impl BufRead,
+) -> Result<()> {
+    let (program, args) = command.split_first().context("No agent command")?;
+    if let Some(dir) = stream.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file =
+        File::create(stream).with_context(|| format!("Failed to create {}", stream.display()))?;
+
+    let mut pending = prompt;
+    let mut session: Option<String> = None;
+    let mut total = Usage::default();
+    loop {
+        let prompt = match pending.take() {
+            Some(prompt) => prompt,
+            None => {
+                print!("> ");
+                std::io::stdout().flush()?;
+                let mut line = String::new();
+                if input.read_line(&mut line)? == 0 {
+                    return Ok(());
+                }
+                line.trim().to_string()
+            }
+        };
+        if prompt.is_empty() {
+            continue;
+        }
+
+        let mut agent = Command::new(program);
+        agent.args(args).args(STREAM_ARGS);
+        if let Some(id) = &session {
+            agent.args(["--resume", id]);
+        }
+        let mut child = agent
+            .arg(&prompt)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to run {}", program))?;
+
+        let mut finished = false;
+        let stdout = child.stdout.take().context("No agent output")?;
+        for line in BufReader::new(stdout).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            writeln!(file, "{}", line)?;
+            session = session_id(&line).or(session);
+            if let Some(usage) = result_usage(&line) {
+                total.add(&usage);
+                writeln!(file, "{}", usage_event(&total))?;
+                finished = true;
+            }
+            for rendered in render_event(&line) {
+                println!("{}", rendered);
+            }
+        }
+        let status = child.wait()?;
+        if !finished {
+            // Without a result the monitor would show the turn as running forever
+            writeln!(file, "{}", EXITED_EVENT)?;
+            println!("── {} exited ({})", program, status);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::AgentStatus;
+    use crate::parsers::stream_json::read_stream;
+
+    #[test]
+    fn test_run_records_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        let agent = dir.path().join("agent.sh");
+        std::fs::write(
+            &agent,
+            r#"echo '{"type":"system","subtype":"init","session_id":"s1","model":"m"}'
+echo '{"type":"result","subtype":"success","is_error":false,"total_cost_usd":0.5,"usage":{"input_tokens":3,"output_tokens":4}}'
+"#,
+        )
+        .unwrap();
+        let stream = dir.path().join("streams").join("1.jsonl");
+        let command = vec!["sh".to_string(), agent.to_string_lossy().to_string()];
+
+        run_prompts(&command, &stream, Some("hello".to_string()), &b""[..]).unwrap();
+        let status = read_stream(&stream).unwrap();
+        assert_eq!(status.status, AgentStatus::Idle);
+        assert_eq!(
+            status.usage,
+            Some(Usage {
+                input_tokens: 3,
+                output_tokens: 4,
+                cost_usd: 0.5,
+            })
+        );
+
+        let failing = vec!["false".to_string()];
+        run_prompts(&failing, &stream, None, &b"\nhello\n"[..]).unwrap();
+        assert!(matches!(
+            read_stream(&stream).unwrap().status,
+            AgentStatus::Error { .. }
+        ));
+    }
+}
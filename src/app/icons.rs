@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+
+use crate::agents::{AgentState, AgentType};
+
+/// Which symbols the TUI draws, the `use_icons` setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UseIcons {
+    /// Detected from the terminal: see [`UseIcons::resolve`]
+    #[default]
+    Auto,
+    /// Nerd Font glyphs, for terminals using a patched font
+    Nerd,
+    /// Symbols found in ordinary fonts
+    Unicode,
+    /// Plain ASCII, for terminals without UTF-8
+    Ascii,
+}
+
+impl UseIcons {
+    /// Returns the icons to draw, detecting them from the environment for `Auto`
+    ///
+    /// Like tmux, a locale without UTF-8 (`LC_ALL`, `LC_CTYPE`, then `LANG`)
+    /// and the Linux console get ASCII. WezTerm bundles the Nerd Font
+    /// symbols, so it gets them; any other terminal gets Unicode.
+    pub fn resolve(self, env: impl Fn(&str) -> Option<String>) -> &'static Icons {
+        match self {
+            UseIcons::Nerd => &Icons::NERD,
+            UseIcons::Unicode => &Icons::UNICODE,
+            UseIcons::Ascii => &Icons::ASCII,
+            UseIcons::Auto => {
+                let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+                    .into_iter()
+                    .find_map(|name| env(name).filter(|v| !v.is_empty()))
+                    .unwrap_or_default()
+                    .to_lowercase();
+                if !(locale.contains("utf-8") || locale.contains("utf8"))
+                    || env("TERM").as_deref() == Some("linux")
+                {
+                    &Icons::ASCII
+                } else if env("TERM_PROGRAM").as_deref() == Some("WezTerm") {
+                    &Icons::NERD
+                } else {
+                    &Icons::UNICODE
+                }
+            }
+        }
+    }
+}
+
+/// Symbols of every part of the TUI that has one, by role
+#[derive(Debug, PartialEq, Eq)]
+pub struct Icons {
+    /// Name of the set, as in `use_icons`
+    pub name: &'static str,
+    pub idle: &'static str,
+    pub permission: &'static str,
+    pub input: &'static str,
+    pub error: &'static str,
+    pub unknown: &'static str,
+    pub stalled: &'static str,
+    /// Frames of the working animation
+    pub spinner: &'static [&'static str],
+    /// Completed and running subagents
+    pub done: &'static str,
+    pub running: &'static str,
+    /// Before the branch name
+    pub branch: &'static str,
+    /// Before the agent type, empty for none
+    pub agent: &'static str,
+    /// Unread notifications
+    pub alert: &'static str,
+    /// Title of the pinned group
+    pub pin: &'static str,
+    /// Sounds on and muted
+    pub sound: &'static str,
+    pub muted: &'static str,
+}
+
+const BRAILLE: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+impl Icons {
+    pub const NERD: Icons = Icons {
+        name: "nerd",
+        idle: "\u{f111}",
+        permission: "\u{f071}",
+        input: "\u{f059}",
+        error: "\u{f057}",
+        unknown: "\u{f10c}",
+        stalled: "\u{f252}",
+        spinner: BRAILLE,
+        done: "\u{f00c}",
+        running: "\u{f04b}",
+        branch: "\u{e725}",
+        agent: "\u{f06a9}",
+        alert: "\u{f0f3}",
+        pin: "\u{f08d}",
+        sound: "\u{f028}",
+        muted: "\u{f026}",
+    };
+
+    pub const UNICODE: Icons = Icons {
+        name: "unicode",
+        idle: "●",
+        permission: "⚠",
+        input: "?",
+        error: "✗",
+        unknown: "○",
+        stalled: "⏱",
+        spinner: BRAILLE,
+        done: "✓",
+        running: "▶",
+        branch: "⎇",
+        agent: "",
+        alert: "⚑",
+        pin: "★",
+        sound: "♪",
+        muted: "×",
+    };
+
+    pub const ASCII: Icons = Icons {
+        name: "ascii",
+        idle: "o",
+        permission: "!",
+        input: "?",
+        error: "x",
+        unknown: ".",
+        stalled: "z",
+        spinner: &["|", "/", "-", "\\"],
+        done: "+",
+        running: ">",
+        branch: "@",
+        agent: "",
+        alert: "*",
+        pin: "^",
+        sound: "<))",
+        muted: "<x",
+    };
+
+    /// Returns the working animation frame for animation tick `tick`
+    pub fn spinner_frame(&self, tick: usize) -> &'static str {
+        self.spinner[tick % self.spinner.len()]
+    }
+
+    /// Returns the icon of an agent state, the unknown one for `None`
+    pub fn state(&self, state: Option<AgentState>, tick: usize) -> &'static str {
+        match state {
+            Some(AgentState::Idle) => self.idle,
+            Some(AgentState::Working) => self.spinner_frame(tick),
+            Some(AgentState::WaitingForInput) => self.input,
+            Some(AgentState::WaitingForPermission) => self.permission,
+            Some(AgentState::Error) => self.error,
+            None => self.unknown,
+        }
+    }
+
+    /// Returns the agent type prefixed with the agent icon, if any
+    pub fn agent_name(&self, agent_type: &AgentType) -> String {
+        match self.agent {
+            "" => agent_type.short_name().to_string(),
+            icon => format!("{} {}", icon, agent_type.short_name()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_detect_icons() {
+        let detect = |vars: &[(&str, &str)]| {
+            let vars: HashMap<String, String> = vars
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            UseIcons::Auto.resolve(|name| vars.get(name).cloned())
+        };
+
+        assert_eq!(detect(&[]), &Icons::ASCII);
+        assert_eq!(detect(&[("LANG", "en_US.UTF-8")]), &Icons::UNICODE);
+        assert_eq!(
+            detect(&[("LC_ALL", "C"), ("LANG", "en_US.UTF-8")]),
+            &Icons::ASCII
+        );
+        assert_eq!(
+            detect(&[("LANG", "en_US.utf8"), ("TERM", "linux")]),
+            &Icons::ASCII
+        );
+        assert_eq!(
+            detect(&[("LANG", "C.UTF-8"), ("TERM_PROGRAM", "WezTerm")]),
+            &Icons::NERD
+        );
+        assert_eq!(UseIcons::Nerd.resolve(|_| None), &Icons::NERD);
+
+        // The fallback draws nothing a plain terminal can't show
+        let ascii = &Icons::ASCII;
+        let mut all = vec![ascii.idle, ascii.permission, ascii.input, ascii.error];
+        all.extend([ascii.unknown, ascii.stalled, ascii.done, ascii.running]);
+        all.extend([ascii.branch, ascii.agent, ascii.alert, ascii.pin]);
+        all.extend([ascii.sound, ascii.muted]);
+        all.extend(ascii.spinner);
+        assert!(all.iter().all(|s| s.is_ascii()));
+    }
+}
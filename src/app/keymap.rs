@@ -0,0 +1,477 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use anyhow::{bail, Context, Result};
+use crossterm::event::{KeyCode, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+use super::actions::Action;
+use super::palette::PaletteEntry;
+
+/// Built-in set of sidebar key bindings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyProfile {
+    /// hjkl, gg/G and `/`, with the keys they displace moved aside
+    #[default]
+    Vim,
+    /// The original single-key bindings
+    Classic,
+}
+
+/// The `[keys]` config section
+///
+/// ```toml
+/// [keys]
+/// profile = "vim"
+/// next_agent = ["j", "down", "ctrl+n"]
+/// show_diff = ["D"]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeysConfig {
+    /// Bindings the overrides start from
+    #[serde(default)]
+    pub profile: KeyProfile,
+    /// Keys per action name, replacing the profile's keys for that action
+    #[serde(flatten)]
+    pub bindings: BTreeMap<String, Vec<String>>,
+}
+
+/// One key press, e.g. `ctrl+a`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    /// Only Ctrl and Alt; Shift is part of the character
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    /// Creates the chord of a key event
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self {
+            code,
+            modifiers: modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT),
+        }
+    }
+
+    /// Parses a key like "j", "G", "ctrl+a", "enter" or "pagedown"
+    pub fn parse(key: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = key;
+        loop {
+            let lower = rest.to_lowercase();
+            if lower.starts_with("ctrl+") {
+                modifiers |= KeyModifiers::CONTROL;
+                rest = &rest[5..];
+            } else if lower.starts_with("alt+") {
+                modifiers |= KeyModifiers::ALT;
+                rest = &rest[4..];
+            } else {
+                break;
+            }
+        }
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_lowercase().as_str() {
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "space" => KeyCode::Char(' '),
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                _ => bail!("unknown key {:?}", key),
+            },
+        };
+        Ok(Self::new(code, modifiers))
+    }
+
+    fn is_plain_char(&self) -> bool {
+        matches!(self.code, KeyCode::Char(c) if c != ' ') && self.modifiers.is_empty()
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// Parses a binding: one key, or several separated by spaces ("g g")
+fn parse_sequence(keys: &str) -> Result<Vec<KeyChord>> {
+    let chords = keys
+        .split_whitespace()
+        .map(KeyChord::parse)
+        .collect::<Result<Vec<_>>>()?;
+    if chords.is_empty() {
+        bail!("empty key binding");
+    }
+    Ok(chords)
+}
+
+/// Formats a binding like "gg", "G" or "Ctrl+a"
+fn format_sequence(chords: &[KeyChord]) -> String {
+    let parts: Vec<String> = chords.iter().map(|c| c.to_string()).collect();
+    if chords.iter().all(KeyChord::is_plain_char) {
+        parts.concat()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Lines the preview scrolls per key press
+pub const PREVIEW_PAGE: usize = 10;
+
+/// Config names of the actions that can be bound, in help order
+const ACTION_NAMES: &[(&str, Action)] = &[
+    ("next_agent", Action::NextAgent),
+    ("prev_agent", Action::PrevAgent),
+    ("first_agent", Action::FirstAgent),
+    ("last_agent", Action::LastAgent),
+    ("focus_input", Action::FocusInput),
+    ("search", Action::Search),
+    ("toggle_selection", Action::ToggleSelection),
+    ("select_all", Action::SelectAll),
+    ("toggle_broadcast", Action::ToggleBroadcast),
+    ("broadcast_filter", Action::BroadcastFilter),
+    ("approve", Action::Approve),
+    ("reject", Action::Reject),
+    ("approve_all", Action::ApproveAll),
+    ("focus_pane", Action::FocusPane),
+    ("jump_to_pane", Action::JumpToPane),
+    ("toggle_zoom", Action::ToggleZoom),
+    ("new_agent", Action::NewAgent),
+    ("new_worktree", Action::NewWorktree),
+    ("kill_pane", Action::KillPane),
+    ("respawn_pane", Action::RespawnPane),
+    ("ignore_pane", Action::IgnorePane),
+    ("open_branch_picker", Action::OpenBranchPicker),
+    ("create_pull_request", Action::CreatePullRequest),
+    ("show_commit_log", Action::ShowCommitLog),
+    ("show_diff", Action::ShowDiff),
+    ("show_output", Action::ShowOutput),
+    ("yank_output", Action::YankOutput),
+    ("show_scrollback", Action::ShowScrollback),
+    ("edit_label", Action::EditLabel),
+    ("edit_note", Action::EditNote),
+    ("queue_prompt", Action::QueuePrompt),
+    ("edit_queue", Action::EditQueue),
+    ("run_tests", Action::RunTests),
+    ("toggle_automation", Action::ToggleAutomation),
+    ("toggle_automation_pause", Action::ToggleAutomationPause),
+    ("show_usage", Action::ShowUsage),
+    ("show_notifications", Action::ShowNotifications),
+    ("show_details", Action::ShowDetails),
+    ("scroll_preview_up", Action::ScrollPreviewUp(PREVIEW_PAGE)),
+    (
+        "scroll_preview_down",
+        Action::ScrollPreviewDown(PREVIEW_PAGE),
+    ),
+    ("toggle_subagent_log", Action::ToggleSubagentLog),
+    ("toggle_summary_detail", Action::ToggleSummaryDetail),
+    ("toggle_group_by_repo", Action::ToggleGroupByRepo),
+    ("toggle_collapse_group", Action::ToggleCollapseGroup),
+    ("cycle_sort", Action::CycleSort),
+    ("toggle_pin", Action::TogglePin),
+    ("toggle_compact_list", Action::ToggleCompactList),
+    ("sidebar_narrower", Action::SidebarNarrower),
+    ("sidebar_wider", Action::SidebarWider),
+    ("refresh", Action::Refresh),
+    ("toggle_mute", Action::ToggleMute),
+    ("show_palette", Action::ShowPalette),
+    ("show_help", Action::ShowHelp),
+    ("quit", Action::Quit),
+];
+
+/// Returns the keys of `profile` per action name
+fn profile_bindings(profile: KeyProfile) -> Vec<(&'static str, &'static [&'static str])> {
+    let mut bindings: Vec<(&str, &[&str])> = vec![
+        ("next_agent", &["j", "down", "tab"]),
+        ("prev_agent", &["k", "up"]),
+        ("first_agent", &["home"]),
+        ("last_agent", &["end"]),
+        ("focus_input", &["i", "right"]),
+        ("search", &["/"]),
+        ("toggle_selection", &["space"]),
+        ("select_all", &["ctrl+a"]),
+        ("toggle_broadcast", &["B"]),
+        ("broadcast_filter", &["*"]),
+        ("approve", &["y", "Y"]),
+        ("reject", &["n", "N"]),
+        ("approve_all", &["a", "A"]),
+        ("focus_pane", &["f", "F"]),
+        ("jump_to_pane", &["enter", "o"]),
+        ("toggle_zoom", &["Z"]),
+        ("new_agent", &["c"]),
+        ("new_worktree", &["w"]),
+        ("kill_pane", &["X"]),
+        ("respawn_pane", &["R"]),
+        ("ignore_pane", &["-"]),
+        ("open_branch_picker", &["b"]),
+        ("create_pull_request", &["P"]),
+        ("show_commit_log", &["l"]),
+        ("show_diff", &["d"]),
+        ("show_output", &["v"]),
+        ("yank_output", &["C"]),
+        ("show_scrollback", &["H"]),
+        ("edit_label", &["e"]),
+        ("edit_note", &["E"]),
+        ("queue_prompt", &["Q"]),
+        ("edit_queue", &["U"]),
+        ("run_tests", &["u"]),
+        ("toggle_automation", &["m"]),
+        ("toggle_automation_pause", &["M"]),
+        ("show_usage", &["$"]),
+        ("show_notifications", &["I"]),
+        ("show_details", &["K"]),
+        ("scroll_preview_up", &["ctrl+u", "pageup"]),
+        ("scroll_preview_down", &["ctrl+d", "pagedown"]),
+        ("toggle_subagent_log", &["s", "S"]),
+        ("toggle_summary_detail", &["t", "T"]),
+        ("toggle_group_by_repo", &["g"]),
+        ("toggle_collapse_group", &["z"]),
+        ("cycle_sort", &["O"]),
+        ("toggle_pin", &["p"]),
+        ("toggle_compact_list", &["V"]),
+        ("sidebar_narrower", &["<"]),
+        ("sidebar_wider", &[">"]),
+        ("refresh", &["r"]),
+        ("toggle_mute", &["!"]),
+        ("show_palette", &["ctrl+p", ":"]),
+        ("show_help", &["h", "?"]),
+        ("quit", &["q"]),
+    ];
+    if profile == KeyProfile::Vim {
+        let vim: &[(&str, &[&str])] = &[
+            ("first_agent", &["g g", "home"]),
+            ("last_agent", &["G", "end"]),
+            ("focus_input", &["i", "l", "right"]),
+            ("toggle_collapse_group", &["h", "z"]),
+            // Displaced by gg and l/h
+            ("toggle_group_by_repo", &["g r"]),
+            ("show_commit_log", &["L"]),
+            ("show_help", &["?"]),
+        ];
+        for (name, keys) in vim {
+            if let Some(binding) = bindings.iter_mut().find(|(n, _)| n == name) {
+                binding.1 = keys;
+            }
+        }
+    }
+    bindings
+}
+
+/// Result of feeding a key press to a [`Keymap`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyMatch {
+    /// A binding is complete
+    Action(Action),
+    /// The key starts a longer binding; waiting for the rest
+    Pending,
+    /// No binding uses the key
+    Unbound,
+}
+
+/// Sidebar key bindings built from a profile and config overrides
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(Vec<KeyChord>, Action)>,
+    /// Keys typed so far of a multi-key binding
+    pending: Vec<KeyChord>,
+}
+
+impl Keymap {
+    /// Builds the keymap described by `config`
+    ///
+    /// Each action listed in the config gets exactly the keys given there;
+    /// an empty list unbinds it.
+    pub fn from_config(config: &KeysConfig) -> Result<Self> {
+        let mut keys: BTreeMap<&str, Vec<String>> = profile_bindings(config.profile)
+            .into_iter()
+            .map(|(name, keys)| (name, keys.iter().map(|k| k.to_string()).collect()))
+            .collect();
+        for (name, overrides) in &config.bindings {
+            let Some((name, _)) = ACTION_NAMES.iter().find(|(n, _)| n == name) else {
+                let known: Vec<&str> = ACTION_NAMES.iter().map(|(n, _)| *n).collect();
+                bail!(
+                    "Unknown action {:?} in [keys] (known: {})",
+                    name,
+                    known.join(", ")
+                );
+            };
+            keys.insert(name, overrides.clone());
+        }
+
+        let mut bindings = Vec::new();
+        for (name, action) in ACTION_NAMES {
+            for key in keys.get(name).into_iter().flatten() {
+                let sequence = parse_sequence(key)
+                    .with_context(|| format!("Invalid key {:?} for {} in [keys]", key, name))?;
+                bindings.push((sequence, action.clone()));
+            }
+        }
+        Ok(Self {
+            bindings,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Feeds one key press, completing or extending a multi-key binding
+    ///
+    /// A key that continues no pending binding starts over on its own.
+    pub fn press(&mut self, code: KeyCode, modifiers: KeyModifiers) -> KeyMatch {
+        let chord = KeyChord::new(code, modifiers);
+        self.pending.push(chord);
+        if self.pending.len() > 1 && !self.is_prefix(&self.pending) {
+            self.pending = vec![chord];
+        }
+        if let Some((_, action)) = self.bindings.iter().find(|(seq, _)| *seq == self.pending) {
+            let action = action.clone();
+            self.pending.clear();
+            return KeyMatch::Action(action);
+        }
+        if self.is_prefix(&self.pending) {
+            return KeyMatch::Pending;
+        }
+        self.pending.clear();
+        KeyMatch::Unbound
+    }
+
+    /// Drops the keys typed so far of a multi-key binding
+    pub fn reset(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Returns true if some binding starts with `keys`
+    fn is_prefix(&self, keys: &[KeyChord]) -> bool {
+        self.bindings
+            .iter()
+            .any(|(seq, _)| seq.len() >= keys.len() && seq[..keys.len()] == *keys)
+    }
+
+    /// Returns the keys bound to `action` like "j / ↓", empty if unbound
+    pub fn keys_for(&self, action: &Action) -> String {
+        let keys: Vec<String> = self
+            .bindings
+            .iter()
+            .filter(|(_, a)| a == action)
+            .map(|(seq, _)| format_sequence(seq))
+            .collect();
+        keys.join(" / ")
+    }
+
+    /// Returns an entry for every action that has a name, bound or not, in
+    /// help order
+    pub fn palette_entries(&self) -> Vec<PaletteEntry> {
+        ACTION_NAMES
+            .iter()
+            .filter(|(_, action)| *action != Action::ShowPalette)
+            .map(|(name, action)| PaletteEntry {
+                action: action.clone(),
+                name,
+                keys: self.keys_for(action),
+            })
+            .collect()
+    }
+
+    /// Returns the bound actions in help order with their keys
+    pub fn help_entries(&self) -> Vec<(String, &'static str)> {
+        ACTION_NAMES
+            .iter()
+            .map(|(_, action)| (self.keys_for(action), action.description()))
+            .filter(|(keys, _)| !keys.is_empty())
+            .collect()
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_config(&KeysConfig::default()).expect("built-in profiles are valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(keymap: &mut Keymap, c: char) -> KeyMatch {
+        keymap.press(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_vim_profile() {
+        let mut keymap = Keymap::default();
+        assert_eq!(press(&mut keymap, 'j'), KeyMatch::Action(Action::NextAgent));
+        assert_eq!(press(&mut keymap, 'g'), KeyMatch::Pending);
+        assert_eq!(
+            press(&mut keymap, 'g'),
+            KeyMatch::Action(Action::FirstAgent)
+        );
+        assert_eq!(
+            keymap.press(KeyCode::Char('G'), KeyModifiers::SHIFT),
+            KeyMatch::Action(Action::LastAgent)
+        );
+        // A key that doesn't continue the binding counts on its own
+        assert_eq!(press(&mut keymap, 'g'), KeyMatch::Pending);
+        assert_eq!(press(&mut keymap, 'k'), KeyMatch::Action(Action::PrevAgent));
+        assert_eq!(press(&mut keymap, 'x'), KeyMatch::Unbound);
+        assert_eq!(keymap.keys_for(&Action::FirstAgent), "gg / Home");
+        assert_eq!(keymap.keys_for(&Action::SelectAll), "Ctrl+a");
+    }
+
+    #[test]
+    fn test_config_overrides() {
+        let config: KeysConfig = toml::from_str(
+            r#"
+profile = "classic"
+show_diff = ["D", "ctrl+d"]
+quit = []
+"#,
+        )
+        .unwrap();
+        let mut keymap = Keymap::from_config(&config).unwrap();
+        assert_eq!(
+            press(&mut keymap, 'g'),
+            KeyMatch::Action(Action::ToggleGroupByRepo)
+        );
+        assert_eq!(press(&mut keymap, 'd'), KeyMatch::Unbound);
+        assert_eq!(
+            keymap.press(KeyCode::Char('d'), KeyModifiers::CONTROL),
+            KeyMatch::Action(Action::ShowDiff)
+        );
+        assert_eq!(press(&mut keymap, 'q'), KeyMatch::Unbound);
+        assert!(!keymap
+            .help_entries()
+            .iter()
+            .any(|(_, d)| *d == "Quit application"));
+
+        let unknown: KeysConfig = toml::from_str(r#"fly = ["f"]"#).unwrap();
+        assert!(Keymap::from_config(&unknown).is_err());
+        let invalid: KeysConfig = toml::from_str(r#"quit = ["hyper+q"]"#).unwrap();
+        assert!(Keymap::from_config(&invalid).is_err());
+    }
+}
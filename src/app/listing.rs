@@ -0,0 +1,152 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::agents::MonitoredAgent;
+use crate::parsers::Json;
+
+/// Returns the fields `tmuxcc ls --json` prints for `agent`
+///
+/// Times are in seconds; `first_seen` is the Unix time tmuxcc first saw the
+/// pane, possibly in an earlier run.
+fn fields(agent: &MonitoredAgent, now: u64) -> Vec<(String, Json)> {
+    let stats = &agent.stats;
+    let text = |s: &str| Json::String(s.to_string());
+    let opt = |s: Option<&str>| s.map_or(Json::Null, text);
+    let num = |n: u64| Json::Number(n as f64);
+    let first_seen = (stats.first_seen > 0).then_some(stats.first_seen);
+    let fields = [
+        ("pane", text(&agent.key())),
+        ("pane_id", text(&agent.pane_id)),
+        ("session", text(&agent.session)),
+        ("window", num(agent.window.into())),
+        ("window_name", text(&agent.window_name)),
+        ("path", text(&agent.path)),
+        ("repo", text(&agent.repo_root())),
+        ("branch", opt(agent.git.as_ref().map(|g| g.branch.as_str()))),
+        ("agent", text(agent.agent_type.display_name())),
+        ("label", opt(agent.label.as_deref())),
+        ("state", text(agent.state_label())),
+        ("first_seen", first_seen.map_or(Json::Null, num)),
+        (
+            "age_secs",
+            first_seen.map_or(Json::Null, |t| num(now.saturating_sub(t))),
+        ),
+        ("working_secs", num(stats.working_ms / 1000)),
+        ("prompts", num(stats.prompts.into())),
+        ("input_tokens", num(stats.usage.input_tokens)),
+        ("output_tokens", num(stats.usage.output_tokens)),
+        (
+            "cost_usd",
+            Json::Number((stats.usage.cost_usd * 10_000.0).round() / 10_000.0),
+        ),
+        (
+            "context_remaining",
+            agent
+                .context_remaining
+                .map_or(Json::Null, |p| num(p.into())),
+        ),
+    ];
+    fields
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect()
+}
+
+/// Formats `agents` as a JSON array, one object per line
+pub fn json(agents: &[MonitoredAgent]) -> String {
+    let now = unix_now();
+    let objects: Vec<String> = agents
+        .iter()
+        .map(|agent| Json::Object(fields(agent, now)).to_string())
+        .collect();
+    if objects.is_empty() {
+        return "[]".to_string();
+    }
+    format!("[\n{}\n]", objects.join(",\n"))
+}
+
+/// Formats `agents` as a table with a header line, columns aligned
+pub fn table(agents: &[MonitoredAgent]) -> String {
+    let mut rows = vec![[
+        "PANE", "AGENT", "STATE", "LABEL", "BRANCH", "WORKING", "COST", "PATH",
+    ]
+    .map(String::from)];
+    for agent in agents {
+        rows.push([
+            agent.key(),
+            agent.agent_type.display_name().to_string(),
+            agent.state_label().to_string(),
+            agent.label.clone().unwrap_or_else(|| "-".to_string()),
+            agent.git_label().unwrap_or_else(|| "-".to_string()),
+            agent.stats.working_str(),
+            format!("${:.2}", agent.stats.usage.cost_usd),
+            agent.path.clone(),
+        ]);
+    }
+    let mut widths = [0; 8];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    rows.iter()
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                .collect();
+            cells.join("  ").trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::{AgentState, AgentType};
+
+    #[test]
+    fn test_listing() {
+        let mut agent = MonitoredAgent::new(
+            "main:1.0-42".to_string(),
+            "main:1.0".to_string(),
+            "main".to_string(),
+            1,
+            "api".to_string(),
+            0,
+            "/code/\"api\"".to_string(),
+            AgentType::ClaudeCode,
+            42,
+        );
+        agent.pane_id = "%3".to_string();
+        agent.state = Some(AgentState::WaitingForPermission);
+        agent.label = Some("review".to_string());
+        agent.stats.working_ms = 125_000;
+        agent.stats.usage.cost_usd = 1.5;
+
+        let json = json(std::slice::from_ref(&agent));
+        assert!(json.starts_with("[\n{\"pane\":\"main:1.0\",\"pane_id\":\"%3\","));
+        assert!(json.contains("\"path\":\"/code/\\\"api\\\"\""), "{}", json);
+        assert!(json.contains("\"branch\":null,\"agent\":\"Claude Code\",\"label\":\"review\""));
+        assert!(json.contains("\"state\":\"PERMISSION\",\"first_seen\":null"));
+        assert!(json.contains("\"working_secs\":125,"));
+        assert!(json.ends_with("\"cost_usd\":1.5,\"context_remaining\":null}\n]"));
+        assert_eq!(super::json(&[]), "[]");
+
+        assert_eq!(
+            table(&[agent]),
+            concat!(
+                "PANE      AGENT        STATE       LABEL   BRANCH  WORKING  COST   PATH\n",
+                "main:1.0  Claude Code  PERMISSION  review  -       2m       $1.50  /code/\"api\"",
+            )
+        );
+    }
+}
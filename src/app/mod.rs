@@ -1,7 +1,30 @@
 mod actions;
 mod config;
+pub mod config_check;
+pub mod headless;
+mod icons;
+mod keymap;
+pub mod listing;
+mod palette;
+mod prefs;
+pub mod script;
+pub mod spawn;
 mod state;
+mod theme;
 
 pub use actions::Action;
-pub use config::Config;
-pub use state::{AgentTree, AppState, FocusedPanel};
+pub use config::{
+    AgentPattern, AutoApproveConfig, AutoRespondRule, Config, CustomAgent, DesktopNotifications,
+    DiscordConfig, HooksConfig, LayoutConfig, LayoutSlot, Notifier, NotifyOn, NotifyRule,
+    PanePolling, ProjectAutoApprove, ProjectConfig, SlackConfig, SoundsConfig, StatePatterns,
+    StatusTitles, TmuxAlerts, WatchdogConfig, WebhookConfig, DEFAULT_CONFIG,
+};
+pub use icons::{Icons, UseIcons};
+pub use keymap::{KeyMatch, KeyProfile, Keymap, KeysConfig, PREVIEW_PAGE};
+pub use palette::{Palette, PaletteEntry};
+pub use prefs::UiPrefs;
+pub use state::{
+    AgentTree, AppState, Confirm, FocusedPanel, Notification, Picker, PickerKind, Prompt,
+    PromptKind, Search, SortMode, Viewer, ViewerKind, PINNED_GROUP,
+};
+pub use theme::{Theme, ThemeConfig, ThemeName};
@@ -0,0 +1,148 @@
+use super::actions::Action;
+
+/// One command listed in the palette
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteEntry {
+    pub action: Action,
+    /// Config name of the action (e.g. "cycle_sort"), also matched
+    pub name: &'static str,
+    /// Keys bound to it like "O", empty if unbound
+    pub keys: String,
+}
+
+/// Open command palette: every action, narrowed down by a fuzzy query
+#[derive(Debug, Clone)]
+pub struct Palette {
+    pub entries: Vec<PaletteEntry>,
+    pub query: String,
+    /// Index of the highlighted match
+    pub selected: usize,
+}
+
+impl Palette {
+    pub fn new(entries: Vec<PaletteEntry>) -> Self {
+        Self {
+            entries,
+            query: String::new(),
+            selected: 0,
+        }
+    }
+
+    /// Returns the entries matching the query, best first and in list order
+    /// for ties
+    pub fn matches(&self) -> Vec<&PaletteEntry> {
+        let mut scored: Vec<(i32, &PaletteEntry)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let text = format!("{} {}", entry.action.description(), entry.name);
+                fuzzy_score(&self.query, &text).map(|score| (score, entry))
+            })
+            .collect();
+        scored.sort_by_key(|(score, _)| -score);
+        scored.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    /// Types a character, highlighting the best match again
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.selected = 0;
+    }
+
+    /// Deletes the last character of the query
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+
+    /// Highlights the next match, wrapping around
+    pub fn select_next(&mut self) {
+        let len = self.matches().len();
+        if len > 0 {
+            self.selected = (self.selected + 1) % len;
+        }
+    }
+
+    /// Highlights the previous match, wrapping around
+    pub fn select_prev(&mut self) {
+        let len = self.matches().len();
+        if len > 0 {
+            self.selected = self.selected.checked_sub(1).unwrap_or(len - 1);
+        }
+    }
+
+    /// Returns the action of the highlighted match
+    pub fn selected_action(&self) -> Option<Action> {
+        self.matches()
+            .get(self.selected)
+            .map(|entry| entry.action.clone())
+    }
+}
+
+/// Scores how well `query` matches `text` as a subsequence, ignoring case
+/// and spaces in the query; `None` if it doesn't match
+///
+/// Characters at word starts and runs of consecutive characters score higher,
+/// skipped characters lower.
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut prev: Option<usize> = None;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let i = (pos..text.len()).find(|&i| text[i] == q)?;
+        if i == 0 || !text[i - 1].is_alphanumeric() {
+            score += 8;
+        }
+        if prev.is_some_and(|p| p + 1 == i) {
+            score += 5;
+        }
+        score -= (i - pos).min(10) as i32;
+        prev = Some(i);
+        pos = i + 1;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(action: Action, name: &'static str) -> PaletteEntry {
+        PaletteEntry {
+            action,
+            name,
+            keys: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_palette_matching() {
+        let mut palette = Palette::new(vec![
+            entry(Action::Approve, "approve"),
+            entry(Action::ApproveAll, "approve_all"),
+            entry(Action::CycleSort, "cycle_sort"),
+            entry(Action::KillPane, "kill_pane"),
+        ]);
+        assert_eq!(palette.matches().len(), 4);
+
+        for c in "sort".chars() {
+            palette.push(c);
+        }
+        assert_eq!(palette.selected_action(), Some(Action::CycleSort));
+
+        palette.query = "apall".to_string();
+        assert_eq!(palette.selected_action(), Some(Action::ApproveAll));
+
+        // Word starts beat letters in the middle of words
+        palette.query = "ap".to_string();
+        assert_eq!(palette.matches()[0].action, Action::Approve);
+        palette.select_next();
+        assert_eq!(palette.selected_action(), Some(Action::ApproveAll));
+
+        palette.query = "zzz".to_string();
+        assert!(palette.matches().is_empty());
+        assert_eq!(palette.selected_action(), None);
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::state::SortMode;
+
+/// Dashboard settings and layout changed from the UI and kept across runs
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UiPrefs {
+    /// Order of the pane list
+    #[serde(default)]
+    pub sort: SortMode,
+    /// One line per pane instead of the detailed list
+    #[serde(default)]
+    pub compact: bool,
+    /// Server-qualified targets of the panes pinned to the top
+    #[serde(default)]
+    pub pinned: BTreeSet<String>,
+    /// Sidebar width in percent, when resized away from the configured one
+    #[serde(default)]
+    pub sidebar_width: Option<u16>,
+    /// Whether the sidebar groups panes by repository
+    #[serde(default)]
+    pub group_by_repo: bool,
+    /// Collapsed sidebar groups
+    #[serde(default)]
+    pub collapsed: BTreeSet<String>,
+    /// Search filtering the pane list
+    #[serde(default)]
+    pub search: Option<String>,
+    /// Server-qualified target of the selected pane
+    #[serde(default)]
+    pub selected: Option<String>,
+    /// Whether configured sounds are silenced
+    #[serde(default)]
+    pub muted: bool,
+}
+
+impl UiPrefs {
+    /// Returns the default preferences file path
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|p| p.join("tmuxcc").join("ui.toml"))
+    }
+
+    /// Loads the preferences at `path`, the defaults if it is missing or invalid
+    pub fn load(path: Option<&Path>) -> Self {
+        path.and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|content| match toml::from_str(&content) {
+                Ok(prefs) => Some(prefs),
+                Err(e) => {
+                    debug!("Ignoring invalid UI preferences: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    /// Writes the preferences to `path`
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, toml::to_string(self)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefs_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tmuxcc").join("ui.toml");
+        assert_eq!(UiPrefs::load(Some(&path)), UiPrefs::default());
+
+        let prefs = UiPrefs {
+            sort: SortMode::Activity,
+            compact: true,
+            pinned: BTreeSet::from(["main:0.1".to_string()]),
+            sidebar_width: Some(40),
+            group_by_repo: true,
+            collapsed: BTreeSet::from(["/code/app".to_string()]),
+            search: Some("state:working".to_string()),
+            selected: Some("work:1.0".to_string()),
+            muted: true,
+        };
+        prefs.save(&path).unwrap();
+        assert_eq!(UiPrefs::load(Some(&path)), prefs);
+
+        std::fs::write(&path, "sort = 42").unwrap();
+        assert_eq!(UiPrefs::load(Some(&path)), UiPrefs::default());
+    }
+}
@@ -0,0 +1,232 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::agents::{AgentState, MonitoredAgent};
+
+/// A sequence of prompts sent to one agent, each once the previous one is done
+///
+/// ```toml
+/// steps = [
+///   { send = "implement the feature in TODO.md" },
+///   { send = "run the tests and fix any failures", timeout_secs = 1800 },
+///   { send = "commit the changes" },
+/// ]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskScript {
+    pub steps: Vec<ScriptStep>,
+}
+
+/// One prompt of a [`TaskScript`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScriptStep {
+    /// Text typed into the pane and submitted with Enter
+    pub send: String,
+    /// State that ends the step
+    #[serde(default)]
+    pub wait_for: WaitFor,
+    /// Seconds to wait before the script gives up (0 waits forever)
+    #[serde(default)]
+    pub timeout_secs: u64,
+}
+
+/// State a [`ScriptStep`] waits for after sending its prompt
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WaitFor {
+    /// The agent finished and is ready for the next prompt
+    #[default]
+    Idle,
+    /// The agent asks a question or for permission
+    Waiting,
+    /// Don't wait, send the next step right away
+    None,
+}
+
+impl WaitFor {
+    fn reached(self, state: Option<AgentState>) -> bool {
+        match self {
+            WaitFor::Idle => state == Some(AgentState::Idle),
+            WaitFor::Waiting => matches!(
+                state,
+                Some(AgentState::WaitingForInput | AgentState::WaitingForPermission)
+            ),
+            WaitFor::None => true,
+        }
+    }
+}
+
+impl TaskScript {
+    /// Reads a script from a TOML file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let script: TaskScript = toml::from_str(&content)?;
+        if script.steps.is_empty() {
+            bail!("{} has no steps", path.display());
+        }
+        Ok(script)
+    }
+}
+
+/// What a [`ScriptRun`] wants done next
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptEvent {
+    /// Send the prompt of step `step` (0-based)
+    Send { step: usize, text: String },
+    /// All steps are done
+    Finished,
+    /// Step `step` didn't end within its timeout
+    TimedOut { step: usize },
+    /// The agent reported an error during step `step`
+    Failed { step: usize },
+}
+
+/// Progress of a [`TaskScript`] driving one pane
+///
+/// Fed with the agent after every poll. The first prompt waits for the agent
+/// to be idle; a step ends once its state is entered after the prompt was sent,
+/// so the idle prompt still on screen right after sending doesn't count.
+pub struct ScriptRun {
+    script: TaskScript,
+    step: usize,
+    sent_at: Option<Instant>,
+}
+
+impl ScriptRun {
+    pub fn new(script: TaskScript) -> Self {
+        Self {
+            script,
+            step: 0,
+            sent_at: None,
+        }
+    }
+
+    /// Returns the number of steps
+    pub fn len(&self) -> usize {
+        self.script.steps.len()
+    }
+
+    /// Returns true if the script has no steps
+    pub fn is_empty(&self) -> bool {
+        self.script.steps.is_empty()
+    }
+
+    /// Advances the run with the agent's latest state
+    pub fn advance(&mut self, agent: &MonitoredAgent, now: Instant) -> Option<ScriptEvent> {
+        let step = self.script.steps.get(self.step)?;
+        let Some(sent_at) = self.sent_at else {
+            if self.step == 0 && agent.state != Some(AgentState::Idle) {
+                return None;
+            }
+            self.sent_at = Some(now);
+            return Some(ScriptEvent::Send {
+                step: self.step,
+                text: step.send.clone(),
+            });
+        };
+
+        let entered_since_sent = agent.state_since > sent_at;
+        if step.wait_for.reached(agent.state)
+            && (entered_since_sent || step.wait_for == WaitFor::None)
+        {
+            self.step += 1;
+            self.sent_at = None;
+            if self.step == self.script.steps.len() {
+                return Some(ScriptEvent::Finished);
+            }
+            return self.advance(agent, now);
+        }
+        if agent.state == Some(AgentState::Error) && entered_since_sent {
+            return Some(ScriptEvent::Failed { step: self.step });
+        }
+        if step.timeout_secs > 0
+            && now.duration_since(sent_at) >= Duration::from_secs(step.timeout_secs)
+        {
+            return Some(ScriptEvent::TimedOut { step: self.step });
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::AgentType;
+
+    #[test]
+    fn test_parse_script() {
+        let script: TaskScript = toml::from_str(
+            r#"steps = [
+                { send = "implement it" },
+                { send = "run the tests", wait_for = "waiting", timeout_secs = 60 },
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(script.steps.len(), 2);
+        assert_eq!(script.steps[0].wait_for, WaitFor::Idle);
+        assert_eq!(script.steps[1].wait_for, WaitFor::Waiting);
+        assert_eq!(script.steps[1].timeout_secs, 60);
+    }
+
+    #[test]
+    fn test_run_waits_for_each_step() {
+        let script: TaskScript =
+            toml::from_str(r#"steps = [{ send = "one" }, { send = "two", timeout_secs = 10 }]"#)
+                .unwrap();
+        let mut run = ScriptRun::new(script);
+        let mut agent = MonitoredAgent::new(
+            "%1-100".to_string(),
+            "main:0.0".to_string(),
+            "main".to_string(),
+            0,
+            "code".to_string(),
+            0,
+            "/repo".to_string(),
+            AgentType::ClaudeCode,
+            100,
+        );
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        // Not sent while the agent is busy
+        agent.state = Some(AgentState::Working);
+        assert_eq!(run.advance(&agent, start), None);
+
+        agent.state = Some(AgentState::Idle);
+        agent.state_since = start;
+        assert_eq!(
+            run.advance(&agent, at(1)),
+            Some(ScriptEvent::Send {
+                step: 0,
+                text: "one".to_string()
+            })
+        );
+        // Still the idle prompt from before sending
+        assert_eq!(run.advance(&agent, at(2)), None);
+
+        agent.state = Some(AgentState::Working);
+        agent.state_since = at(3);
+        assert_eq!(run.advance(&agent, at(3)), None);
+        agent.state = Some(AgentState::Idle);
+        agent.state_since = at(5);
+        assert_eq!(
+            run.advance(&agent, at(7)),
+            Some(ScriptEvent::Send {
+                step: 1,
+                text: "two".to_string()
+            })
+        );
+
+        assert_eq!(
+            run.advance(&agent, at(17)),
+            Some(ScriptEvent::TimedOut { step: 1 })
+        );
+        agent.state_since = at(18);
+        assert_eq!(run.advance(&agent, at(18)), Some(ScriptEvent::Finished));
+        assert_eq!(run.advance(&agent, at(19)), None);
+    }
+}
@@ -0,0 +1,230 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use super::config::LayoutConfig;
+use crate::git;
+use crate::tmux::{LaunchCommand, SendMode, TmuxClient, LABEL_OPTION};
+
+/// Where a newly launched agent's pane is created
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Placement {
+    /// A new window in `session` (tmux's current session if None)
+    Window {
+        session: Option<String>,
+        name: Option<String>,
+    },
+    /// A split of the pane at `target`, below it if `vertical`, else to the right
+    Split { target: String, vertical: bool },
+    /// A new detached session (named by tmux if None)
+    Session { name: Option<String> },
+}
+
+impl Placement {
+    /// Returns a short description for pickers and messages
+    pub fn label(&self) -> String {
+        match self {
+            Placement::Window {
+                session: Some(session),
+                ..
+            } => format!("New window in {}", session),
+            Placement::Window { session: None, .. } => "New window".to_string(),
+            Placement::Split {
+                target,
+                vertical: false,
+            } => format!("Split right of {}", target),
+            Placement::Split {
+                target,
+                vertical: true,
+            } => format!("Split below {}", target),
+            Placement::Session { name: Some(name) } => format!("New session {}", name),
+            Placement::Session { name: None } => "New session".to_string(),
+        }
+    }
+}
+
+/// Creates a pane at `placement` in `cwd` and starts `command` in it
+///
+/// The command is typed into the pane's shell rather than passed to tmux,
+/// so the pane survives the agent exiting.
+/// Returns the target of the new pane.
+pub fn launch_agent(
+    tmux: &TmuxClient,
+    placement: &Placement,
+    cwd: &str,
+    command: &str,
+) -> Result<String> {
+    let target = match placement {
+        Placement::Window { session, name } => {
+            tmux.new_window(session.as_deref(), cwd, name.as_deref())?
+        }
+        Placement::Split { target, vertical } => tmux.split_window(target, cwd, *vertical)?,
+        Placement::Session { name } => tmux.new_session(name.as_deref(), cwd)?,
+    };
+    if !command.trim().is_empty() {
+        tmux.send_text(&target, command, SendMode::Literal)?;
+        tmux.send_keys(&target, "Enter")?;
+    }
+    Ok(target)
+}
+
+/// Expands a leading `~` to the home directory
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            format!("{}{}", home.display(), rest)
+        }
+        _ => path.to_string(),
+    }
+}
+
+/// Creates a window with the panes of `layout` and starts their commands
+///
+/// The first pane opens a new window (or the session, if it doesn't exist
+/// yet) and the others are split off it. Slots without a cwd use `default_cwd`.
+/// Returns the targets of the created panes in slot order.
+pub fn apply_layout(
+    tmux: &TmuxClient,
+    layout: &LayoutConfig,
+    default_cwd: &str,
+) -> Result<Vec<String>> {
+    if layout.panes.is_empty() {
+        anyhow::bail!("Layout has no panes");
+    }
+
+    let mut targets: Vec<String> = Vec::new();
+    for slot in &layout.panes {
+        let cwd = slot
+            .cwd
+            .as_deref()
+            .map(expand_home)
+            .unwrap_or_else(|| default_cwd.to_string());
+        // Splitting the newest pane appends after it, so earlier pane
+        // indexes (and the returned targets) don't shift
+        let placement = match (targets.last(), &layout.session) {
+            (Some(last), _) => Placement::Split {
+                target: last.clone(),
+                vertical: false,
+            },
+            (None, Some(session)) if !tmux.has_session(session) => Placement::Session {
+                name: Some(session.clone()),
+            },
+            (None, session) => Placement::Window {
+                session: session.clone(),
+                name: layout.window_name.clone(),
+            },
+        };
+        let target = launch_agent(tmux, &placement, &cwd, &slot.command)?;
+        if let (Placement::Session { .. }, Some(name)) = (&placement, &layout.window_name) {
+            tmux.rename_window(&target, name)?;
+        }
+        // Re-tile after each split so the window never runs out of room
+        tmux.select_layout(&target, "tiled")?;
+        targets.push(target);
+    }
+    tmux.select_layout(&targets[0], &layout.arrangement)?;
+
+    Ok(targets)
+}
+
+/// Restarts the agent in the pane at `target` with its original command
+///
+/// A pane whose agent ran under a shell gets a fresh shell with the command
+/// typed into it again; otherwise the command becomes the pane's process.
+pub fn respawn_agent(
+    tmux: &TmuxClient,
+    target: &str,
+    cwd: &str,
+    launch: &LaunchCommand,
+) -> Result<()> {
+    if launch.in_shell {
+        tmux.respawn_pane(target, cwd, None)?;
+        tmux.send_text(target, &launch.command_line, SendMode::Literal)?;
+        tmux.send_keys(target, "Enter")?;
+    } else {
+        tmux.respawn_pane(target, cwd, Some(&launch.command_line))?;
+    }
+    Ok(())
+}
+
+/// Creates a work tree for `branch` next to the repository containing `repo`,
+/// opens a tmux window in it and launches the agent
+///
+/// Returns the work tree path and the target of the new pane.
+pub fn spawn_worktree_agent(
+    tmux: &TmuxClient,
+    repo: &str,
+    branch: &str,
+    session: Option<&str>,
+    agent_command: &str,
+) -> Result<(PathBuf, String)> {
+    let path = git::create_worktree(repo, branch)?;
+    let placement = Placement::Window {
+        session: session.map(|s| s.to_string()),
+        name: Some(branch.trim().to_string()),
+    };
+    let target = launch_agent(tmux, &placement, &path.to_string_lossy(), agent_command)?;
+    Ok((path, target))
+}
+
+/// Launches an agent in a new window for `tmuxcc run`: in a new work tree
+/// for `branch` if given, else in `repo`, labeled `label` through the pane's
+/// `@tmuxcc_label` option so a running tmuxcc picks the label up too
+///
+/// Returns the agent's working directory and the target of its pane.
+pub fn launch_worker(
+    tmux: &TmuxClient,
+    repo: &str,
+    branch: Option<&str>,
+    session: Option<&str>,
+    agent_command: &str,
+    label: Option<&str>,
+) -> Result<(PathBuf, String)> {
+    let (path, target) = match branch {
+        Some(branch) => spawn_worktree_agent(tmux, repo, branch, session, agent_command)?,
+        None => {
+            let placement = Placement::Window {
+                session: session.map(|s| s.to_string()),
+                name: None,
+            };
+            let target = launch_agent(tmux, &placement, repo, agent_command)?;
+            (PathBuf::from(repo), target)
+        }
+    };
+    if let Some(label) = label {
+        tmux.set_pane_option(&target, LABEL_OPTION, label)?;
+    }
+    Ok((path, target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_home() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(
+            expand_home("~/code/app"),
+            format!("{}/code/app", home.display())
+        );
+        assert_eq!(expand_home("~"), home.display().to_string());
+        assert_eq!(expand_home("~other/x"), "~other/x");
+        assert_eq!(expand_home("/srv/app"), "/srv/app");
+    }
+
+    #[test]
+    fn test_placement_label() {
+        let window = Placement::Window {
+            session: Some("main".to_string()),
+            name: None,
+        };
+        assert_eq!(window.label(), "New window in main");
+        let split = Placement::Split {
+            target: "main:0.1".to_string(),
+            vertical: true,
+        };
+        assert_eq!(split.label(), "Split below main:0.1");
+        assert_eq!(Placement::Session { name: None }.label(), "New session");
+    }
+}
@@ -1,6 +1,12 @@
-use crate::agents::MonitoredAgent;
-use crate::monitor::SystemStats;
-use std::collections::HashSet;
+use crate::agents::{AgentFilter, AgentState, MonitoredAgent};
+use crate::app::config::NotifyOn;
+use crate::app::palette::Palette;
+use crate::app::spawn::Placement;
+use crate::monitor::{StateTransition, SystemStats};
+use crate::tmux::{LaunchCommand, TmuxServer};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::time::Instant;
 
 /// Which panel is currently focused
@@ -13,8 +19,364 @@ pub enum FocusedPanel {
     Input,
 }
 
+/// Purpose of a single-line prompt popup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptKind {
+    /// Branch name for a new worktree in the selected agent's repository
+    WorktreeBranch,
+    /// Working directory for a new agent pane at `placement`
+    AgentCwd { placement: Placement },
+    /// Startup command for a new agent pane at `placement` in `cwd`
+    AgentCommand { placement: Placement, cwd: String },
+    /// Label of the agent `id`, empty to clear
+    Label { id: String },
+    /// Note of the agent `id`, empty to clear
+    Note { id: String },
+    /// Prompt queued for the agent at `key` until it is idle
+    Queue { key: String },
+    /// Filter selecting the panes to broadcast to
+    BroadcastFilter,
+    /// Search narrowing the pane list, applied while typing
+    Search,
+    /// Text to find in the open viewer
+    ViewerSearch,
+}
+
+impl PromptKind {
+    /// Returns the popup title
+    pub fn title(&self) -> &str {
+        match self {
+            PromptKind::WorktreeBranch => "New worktree branch",
+            PromptKind::AgentCwd { .. } => "Working directory",
+            PromptKind::AgentCommand { .. } => "Startup command",
+            PromptKind::Label { .. } => "Label (empty to clear)",
+            PromptKind::Note { .. } => "Note (empty to clear)",
+            PromptKind::Queue { .. } => "Queue prompt",
+            PromptKind::BroadcastFilter => "Broadcast to (state: repo: branch: label:)",
+            PromptKind::Search => "Search panes (words, state: repo: branch: label:)",
+            PromptKind::ViewerSearch => "Find (empty to clear)",
+        }
+    }
+}
+
+/// An open single-line prompt popup
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    /// What the entered text is for
+    pub kind: PromptKind,
+    /// Text entered so far
+    pub buffer: String,
+}
+
+/// Search narrowing the pane list
+#[derive(Debug, Clone)]
+pub struct Search {
+    /// Text as entered
+    pub query: String,
+    pub filter: AgentFilter,
+}
+
+/// Destructive action waiting for a y/n confirmation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Confirm {
+    /// Kill the pane at `target` on `server`
+    KillPane { server: TmuxServer, target: String },
+    /// Restart the agent in the pane at `target` on `server`
+    RespawnPane {
+        server: TmuxServer,
+        target: String,
+        cwd: String,
+        launch: LaunchCommand,
+    },
+}
+
+impl Confirm {
+    /// Returns the question shown in the popup
+    pub fn message(&self) -> String {
+        match self {
+            Confirm::KillPane { server, target } => {
+                format!("Kill pane {}?", server.qualify(target))
+            }
+            Confirm::RespawnPane {
+                server,
+                target,
+                launch,
+                ..
+            } => {
+                format!(
+                    "Restart {} with `{}`?",
+                    server.qualify(target),
+                    launch.command_line
+                )
+            }
+        }
+    }
+}
+
+/// Purpose of a list picker popup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PickerKind {
+    /// Local branch to check out in the repository at `repo`
+    Branch { repo: String },
+    /// Where to create a new agent pane; items are the placements' labels
+    NewAgent {
+        placements: Vec<Placement>,
+        cwd: String,
+    },
+    /// Prompts queued for the agent at `key`; items mirror the queue
+    Queue { key: String },
+    /// The notification history; items mirror it, newest first
+    Notifications,
+}
+
+impl PickerKind {
+    /// Returns the popup title
+    pub fn title(&self) -> &str {
+        match self {
+            PickerKind::Branch { .. } => "Checkout branch",
+            PickerKind::NewAgent { .. } => "New agent",
+            PickerKind::Queue { .. } => "Queued prompts (J/K: move, x: remove)",
+            PickerKind::Notifications => "Notifications (Enter: jump, r: read, R: all read)",
+        }
+    }
+}
+
+/// An open list picker popup
+#[derive(Debug, Clone)]
+pub struct Picker {
+    /// What the chosen item is for
+    pub kind: PickerKind,
+    /// Items to choose from
+    pub items: Vec<String>,
+    /// Index of the highlighted item
+    pub selected: usize,
+    /// Index of the item that is currently in effect (e.g., checked out branch)
+    pub current: Option<usize>,
+}
+
+impl Picker {
+    /// Creates a picker with the current item highlighted
+    pub fn new(kind: PickerKind, items: Vec<String>, current: Option<usize>) -> Self {
+        Self {
+            kind,
+            items,
+            selected: current.unwrap_or(0),
+            current,
+        }
+    }
+
+    /// Highlights the next item, wrapping around
+    pub fn select_next(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = (self.selected + 1) % self.items.len();
+        }
+    }
+
+    /// Highlights the previous item, wrapping around
+    pub fn select_prev(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = self.selected.checked_sub(1).unwrap_or(self.items.len() - 1);
+        }
+    }
+
+    /// Returns the highlighted item
+    pub fn selected_item(&self) -> Option<&str> {
+        self.items.get(self.selected).map(|s| s.as_str())
+    }
+}
+
+/// Content shown in a scrollable viewer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewerKind {
+    /// Recent commits, one per line as tab-separated hash, age, author and subject
+    CommitLog,
+    /// Unified diff output
+    Diff,
+    /// Captured pane output, shown as plain text
+    Output,
+    /// A pane's scrollback history, shown as plain text over the whole screen
+    Scrollback,
+}
+
+/// An open scrollable text viewer
+#[derive(Debug, Clone)]
+pub struct Viewer {
+    /// How lines are colored
+    pub kind: ViewerKind,
+    /// Viewer title
+    pub title: String,
+    /// Content lines
+    pub lines: Vec<String>,
+    /// Index of the first visible line
+    pub scroll: usize,
+    /// Line where the region being selected starts; the region extends to
+    /// the first visible line
+    pub mark: Option<usize>,
+    /// Text being searched for, highlighted in the content
+    pub search: Option<String>,
+}
+
+impl Viewer {
+    pub fn new(kind: ViewerKind, title: String, lines: Vec<String>) -> Self {
+        Self {
+            kind,
+            title,
+            lines,
+            scroll: 0,
+            mark: None,
+            search: None,
+        }
+    }
+
+    /// Starts selecting a region at the first visible line, or clears the
+    /// selection
+    pub fn toggle_mark(&mut self) {
+        self.mark = match self.mark {
+            Some(_) => None,
+            None => Some(self.scroll),
+        };
+    }
+
+    /// Returns the range of selected lines, if a region is being selected
+    pub fn selection(&self) -> Option<std::ops::RangeInclusive<usize>> {
+        let mark = self.mark?;
+        let last = self.lines.len().checked_sub(1)?;
+        Some(mark.min(self.scroll).min(last)..=mark.max(self.scroll).min(last))
+    }
+
+    /// Returns the selected region, or every line when nothing is selected
+    pub fn selected_text(&self) -> String {
+        match self.selection() {
+            Some(range) => self.lines[range].join("\n"),
+            None => self.lines.join("\n"),
+        }
+    }
+
+    /// Scrolls down by `n` lines, stopping at the last line
+    pub fn scroll_down(&mut self, n: usize) {
+        self.scroll = (self.scroll + n).min(self.lines.len().saturating_sub(1));
+    }
+
+    /// Scrolls up by `n` lines
+    pub fn scroll_up(&mut self, n: usize) {
+        self.scroll = self.scroll.saturating_sub(n);
+    }
+
+    /// Jumps to the last line
+    pub fn scroll_to_end(&mut self) {
+        self.scroll = self.lines.len().saturating_sub(1);
+    }
+
+    /// Searches for `text` (case-insensitive) from the first visible line on,
+    /// or clears the search when it is empty; returns whether a line matches
+    pub fn set_search(&mut self, text: &str) -> bool {
+        self.search = (!text.is_empty()).then(|| text.to_lowercase());
+        self.search.is_none() || self.find_from(self.scroll, true)
+    }
+
+    /// Scrolls to the next line matching the search after the first visible
+    /// one (before it when `forward` is false), wrapping around
+    pub fn find_next(&mut self, forward: bool) -> bool {
+        let len = self.lines.len();
+        if len == 0 {
+            return false;
+        }
+        let start = if forward {
+            (self.scroll + 1) % len
+        } else {
+            (self.scroll + len - 1) % len
+        };
+        self.find_from(start, forward)
+    }
+
+    fn find_from(&mut self, start: usize, forward: bool) -> bool {
+        let Some(search) = &self.search else {
+            return false;
+        };
+        let len = self.lines.len();
+        let found = (0..len)
+            .map(|i| {
+                if forward {
+                    (start + i) % len
+                } else {
+                    (start + len - i) % len
+                }
+            })
+            .find(|&i| self.lines[i].to_lowercase().contains(search.as_str()));
+        if let Some(line) = found {
+            self.scroll = line;
+        }
+        found.is_some()
+    }
+}
+
+/// Order of the pane list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortMode {
+    /// tmux session, window and pane order
+    #[default]
+    Tmux,
+    /// Panes waiting for an answer first, then errors, working and idle ones
+    State,
+    /// Most recently active first
+    Activity,
+    /// Most expensive first
+    Cost,
+    /// By label or window name
+    Name,
+}
+
+impl SortMode {
+    /// Returns the mode after this one, wrapping around
+    pub fn next(self) -> Self {
+        match self {
+            SortMode::Tmux => SortMode::State,
+            SortMode::State => SortMode::Activity,
+            SortMode::Activity => SortMode::Cost,
+            SortMode::Cost => SortMode::Name,
+            SortMode::Name => SortMode::Tmux,
+        }
+    }
+
+    /// Returns the name shown in the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            SortMode::Tmux => "tmux order",
+            SortMode::State => "state",
+            SortMode::Activity => "activity",
+            SortMode::Cost => "cost",
+            SortMode::Name => "name",
+        }
+    }
+}
+
+/// An entry of the notification history
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    /// Server-qualified target of the pane it is about
+    pub key: String,
+    pub text: String,
+    pub at: DateTime<Local>,
+    /// Whether it was seen, by opening it or visiting its pane
+    pub read: bool,
+}
+
+impl Notification {
+    /// Returns the history line like "● 14:02:31 main:0.1  asks for permission"
+    pub fn line(&self) -> String {
+        format!(
+            "{} {} {}  {}",
+            if self.read { " " } else { "●" },
+            self.at.format("%H:%M:%S"),
+            self.key,
+            self.text
+        )
+    }
+}
+
 /// Tree structure containing all monitored agents
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AgentTree {
     /// Root agents (directly in tmux panes)
     pub root_agents: Vec<MonitoredAgent>,
@@ -60,6 +422,37 @@ impl AgentTree {
             .count()
     }
 
+    /// Groups agent indices by repository root, ordered by root
+    pub fn repo_groups(&self) -> Vec<(String, Vec<usize>)> {
+        let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (idx, agent) in self.root_agents.iter().enumerate() {
+            groups.entry(agent.repo_root()).or_default().push(idx);
+        }
+        groups.into_iter().collect()
+    }
+
+    /// Groups agent indices by server-qualified session, in session, window
+    /// and pane order
+    pub fn session_groups(&self) -> Vec<(String, Vec<usize>)> {
+        let mut groups: BTreeMap<(&TmuxServer, &str), Vec<usize>> = BTreeMap::new();
+        for (idx, agent) in self.root_agents.iter().enumerate() {
+            groups
+                .entry((&agent.server, &agent.session))
+                .or_default()
+                .push(idx);
+        }
+        groups
+            .into_iter()
+            .map(|((server, session), mut indices)| {
+                indices.sort_by_key(|&i| {
+                    let agent = &self.root_agents[i];
+                    (agent.window, agent.window_name.as_str())
+                });
+                (server.qualify(session), indices)
+            })
+            .collect()
+    }
+
     /// Gets an agent by index (for selection)
     pub fn get_agent(&self, index: usize) -> Option<&MonitoredAgent> {
         self.root_agents.get(index)
@@ -69,10 +462,21 @@ impl AgentTree {
     pub fn get_agent_mut(&mut self, index: usize) -> Option<&mut MonitoredAgent> {
         self.root_agents.get_mut(index)
     }
+
+    /// Gets a mutable agent by its id
+    pub fn get_agent_by_id_mut(&mut self, id: &str) -> Option<&mut MonitoredAgent> {
+        self.root_agents.iter_mut().find(|a| a.id == id)
+    }
 }
 
-/// Spinner frames for animation
-const SPINNER_FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+/// State changes kept for the summary panel
+const MAX_TRANSITIONS: usize = 200;
+
+/// Entries kept in the notification history
+const MAX_NOTIFICATIONS: usize = 200;
+
+/// Key of the sidebar group holding the pinned panes
+pub const PINNED_GROUP: &str = "★ pinned";
 
 /// Main application state
 #[derive(Debug)]
@@ -89,8 +493,12 @@ pub struct AppState {
     pub input_buffer: String,
     /// Cursor position within input buffer (byte offset)
     pub cursor_position: usize,
+    /// Where the last input went and its text, shown until the next one is typed
+    pub last_sent: Option<(String, String)>,
     /// Whether help is being shown
     pub show_help: bool,
+    /// Lines the help is scrolled down
+    pub help_scroll: usize,
     /// Whether subagent log is shown
     pub show_subagent_log: bool,
     /// Whether summary detail (TODOs and Tools) is shown
@@ -99,8 +507,59 @@ pub struct AppState {
     pub should_quit: bool,
     /// Last error message (if any)
     pub last_error: Option<String>,
+    /// Last informational message (if any)
+    pub last_message: Option<String>,
+    /// Open prompt popup (if any)
+    pub prompt: Option<Prompt>,
+    /// Open command palette (if any)
+    pub palette: Option<Palette>,
+    /// Action waiting for confirmation (if any)
+    pub confirm: Option<Confirm>,
+    /// Open list picker popup (if any)
+    pub picker: Option<Picker>,
+    /// Whether typed input and number choices go to every selected pane
+    pub broadcast: bool,
+    /// Recent agent state changes, oldest first
+    pub transitions: VecDeque<StateTransition>,
+    /// State changes, automatic answers and warnings worth a look, newest first
+    pub notifications: VecDeque<Notification>,
+    /// Prompts waiting for their pane to become idle, next first, keyed by
+    /// server-qualified target
+    pub queues: HashMap<String, VecDeque<String>>,
+    /// Search hiding the panes that don't match it (if any)
+    pub search: Option<Search>,
+    /// Monitored tmux servers, one tab each when there are several
+    pub servers: Vec<TmuxServer>,
+    /// Server whose panes are shown, by index in `servers`; `None` shows all
+    pub server_tab: Option<usize>,
+    /// Whether the sidebar groups panes by repository instead of session/window
+    pub group_by_repo: bool,
+    /// Groups (repository roots and sessions) that are collapsed
+    pub collapsed_groups: HashSet<String>,
+    /// Order of the pane list
+    pub sort: SortMode,
+    /// Panes listed above all groups, by server-qualified target
+    pub pinned: BTreeSet<String>,
+    /// Whether the pane list shows one line per pane
+    pub compact_list: bool,
+    /// Whether any sound is configured, so muting means something
+    pub sounds: bool,
+    /// Whether sounds are silenced
+    pub muted: bool,
+    /// Pane selected in the last run, selected once the monitor lists it
+    pub pending_selection: Option<String>,
+    /// Open scrollable viewer (if any)
+    pub viewer: Option<Viewer>,
+    /// Kill switch for automatic answers (auto-approve and auto-respond)
+    pub automation_paused: bool,
+    /// Panes excluded from automatic answers, by server-qualified target
+    pub automation_disabled: HashSet<String>,
+    /// Context left (percent) below which gauges turn red, 0 to never warn
+    pub context_warning: u8,
     /// Sidebar width in percentage (15-70)
     pub sidebar_width: u16,
+    /// Lines the preview is scrolled back, with the agent index it applies to
+    preview_scroll: Option<(usize, usize)>,
     /// Animation tick counter
     pub tick: usize,
     /// Last tick time for animation throttling
@@ -119,12 +578,39 @@ impl AppState {
             focused_panel: FocusedPanel::Sidebar,
             input_buffer: String::new(),
             cursor_position: 0,
+            last_sent: None,
             show_help: false,
+            help_scroll: 0,
             show_subagent_log: false,
             show_summary_detail: true,
             should_quit: false,
             last_error: None,
+            last_message: None,
+            prompt: None,
+            palette: None,
+            confirm: None,
+            picker: None,
+            broadcast: false,
+            transitions: VecDeque::new(),
+            notifications: VecDeque::new(),
+            queues: HashMap::new(),
+            search: None,
+            servers: Vec::new(),
+            server_tab: None,
+            group_by_repo: false,
+            collapsed_groups: HashSet::new(),
+            sort: SortMode::Tmux,
+            pinned: BTreeSet::new(),
+            compact_list: false,
+            sounds: false,
+            muted: false,
+            pending_selection: None,
+            viewer: None,
+            automation_paused: false,
+            automation_disabled: HashSet::new(),
+            context_warning: 15,
             sidebar_width: 35,
+            preview_scroll: None,
             tick: 0,
             last_tick: Instant::now(),
             system_stats: SystemStats::new(),
@@ -140,11 +626,6 @@ impl AppState {
         }
     }
 
-    /// Get the current spinner frame
-    pub fn spinner_frame(&self) -> &'static str {
-        SPINNER_FRAMES[self.tick % SPINNER_FRAMES.len()]
-    }
-
     /// Check if input panel is focused
     pub fn is_input_focused(&self) -> bool {
         self.focused_panel == FocusedPanel::Input
@@ -252,21 +733,245 @@ impl AppState {
         self.agents.get_agent_mut(self.selected_index)
     }
 
+    /// Returns true if the agent at `index` is on the server tab shown and
+    /// matches the search, if any
+    pub fn is_visible(&self, index: usize) -> bool {
+        let tab = self.server_tab.and_then(|i| self.servers.get(i));
+        let agent = self
+            .agents
+            .get_agent(index)
+            .filter(|a| tab.is_none_or(|server| a.server == *server));
+        match &self.search {
+            Some(search) => agent.is_some_and(|a| search.filter.matches(a)),
+            None => agent.is_some(),
+        }
+    }
+
+    /// Returns true if the agent at `index` is pinned to the top of the list
+    pub fn is_pinned(&self, index: usize) -> bool {
+        self.agents
+            .get_agent(index)
+            .is_some_and(|a| self.pinned.contains(&a.key()))
+    }
+
+    /// Pins the selected agent to the top of the list, or unpins it
+    ///
+    /// Returns whether it is pinned now, or `None` without a selection.
+    pub fn toggle_pin(&mut self) -> Option<bool> {
+        let key = self.selected_agent()?.key();
+        if self.pinned.remove(&key) {
+            Some(false)
+        } else {
+            self.pinned.insert(key);
+            Some(true)
+        }
+    }
+
+    /// Orders agent indices by the sort mode, keeping tmux order for ties
+    fn sort_agents(&self, indices: &mut [usize]) {
+        let agents = &self.agents.root_agents;
+        match self.sort {
+            SortMode::Tmux => {}
+            SortMode::State => indices.sort_by_key(|&i| state_rank(&agents[i])),
+            SortMode::Activity => indices.sort_by_key(|&i| {
+                let agent = &agents[i];
+                match agent.state {
+                    Some(AgentState::Working) => std::time::Duration::ZERO,
+                    _ => agent.state_since.elapsed(),
+                }
+            }),
+            SortMode::Cost => indices.sort_by(|&a, &b| {
+                let cost = |i: usize| agents[i].stats.usage.cost_usd;
+                cost(b).total_cmp(&cost(a))
+            }),
+            SortMode::Name => indices.sort_by_cached_key(|&i| {
+                let agent = &agents[i];
+                agent
+                    .label
+                    .as_deref()
+                    .unwrap_or(&agent.window_name)
+                    .to_lowercase()
+            }),
+        }
+    }
+
+    /// Returns the key of the sidebar group `agent` belongs to: the pinned
+    /// group, its repository root or its server-qualified session
+    pub fn group_key(&self, agent: &MonitoredAgent) -> String {
+        if self.pinned.contains(&agent.key()) {
+            PINNED_GROUP.to_string()
+        } else if self.group_by_repo {
+            agent.repo_root()
+        } else {
+            agent.server.qualify(&agent.session)
+        }
+    }
+
+    /// Returns the sidebar groups (repositories or sessions) with the agents
+    /// matching the search, leaving out groups without any
+    ///
+    /// Agents are sorted within their group, and groups by their first agent.
+    /// Pinned agents leave their group for one listed first.
+    /// unless the list keeps tmux order.
+    pub fn visible_groups(&self) -> Vec<(String, Vec<usize>)> {
+        let groups = if self.group_by_repo {
+            self.agents.repo_groups()
+        } else {
+            self.agents.session_groups()
+        };
+        let mut groups: Vec<(String, Vec<usize>)> = groups
+            .into_iter()
+            .filter_map(|(root, indices)| {
+                let mut indices: Vec<usize> = indices
+                    .into_iter()
+                    .filter(|&i| self.is_visible(i) && !self.is_pinned(i))
+                    .collect();
+                self.sort_agents(&mut indices);
+                (!indices.is_empty()).then_some((root, indices))
+            })
+            .collect();
+        if self.sort != SortMode::Tmux {
+            let mut order: Vec<usize> = groups.iter().map(|(_, indices)| indices[0]).collect();
+            self.sort_agents(&mut order);
+            groups.sort_by_key(|(_, indices)| order.iter().position(|&i| i == indices[0]));
+        }
+        let mut pinned: Vec<usize> = (0..self.agents.root_agents.len())
+            .filter(|&i| self.is_visible(i) && self.is_pinned(i))
+            .collect();
+        if !pinned.is_empty() {
+            self.sort_agents(&mut pinned);
+            groups.insert(0, (PINNED_GROUP.to_string(), pinned));
+        }
+        groups
+    }
+
+    /// Returns the agent indices the cursor can visit, in display order
+    ///
+    /// A collapsed group is represented by its first agent.
+    pub fn navigable_agents(&self) -> Vec<usize> {
+        self.visible_groups()
+            .into_iter()
+            .flat_map(|(root, indices)| {
+                if self.collapsed_groups.contains(&root) {
+                    indices.into_iter().take(1).collect::<Vec<_>>()
+                } else {
+                    indices
+                }
+            })
+            .collect()
+    }
+
+    /// Shows the panes of server tab `tab` (1-based), or of all servers for 0
+    ///
+    /// Returns false if there is no such tab.
+    pub fn select_server_tab(&mut self, tab: usize) -> bool {
+        if tab > self.servers.len() {
+            return false;
+        }
+        self.server_tab = tab.checked_sub(1);
+        self.keep_cursor_visible();
+        true
+    }
+
+    /// Narrows the pane list to the agents matching `query`; empty shows all
+    ///
+    /// The cursor moves to the first match when its agent is hidden.
+    pub fn set_search(&mut self, query: &str) -> anyhow::Result<()> {
+        self.search = if query.trim().is_empty() {
+            None
+        } else {
+            Some(Search {
+                query: query.to_string(),
+                filter: AgentFilter::parse_search(query)?,
+            })
+        };
+        self.keep_cursor_visible();
+        Ok(())
+    }
+
+    /// Selects the pane selected in the last run, if the first monitor
+    /// update lists it
+    pub fn restore_selection(&mut self) {
+        let Some(key) = self.pending_selection.take() else {
+            return;
+        };
+        if let Some(index) = self.agents.root_agents.iter().position(|a| a.key() == key) {
+            self.selected_index = index;
+        }
+    }
+
+    /// Moves the cursor to the first navigable agent if its agent is hidden
+    pub fn keep_cursor_visible(&mut self) {
+        if !self.navigable_agents().contains(&self.selected_index) {
+            if let Some(&first) = self.navigable_agents().first() {
+                self.selected_index = first;
+            }
+        }
+    }
+
     /// Selects the next agent
     pub fn select_next(&mut self) {
-        if !self.agents.root_agents.is_empty() {
-            self.selected_index = (self.selected_index + 1) % self.agents.root_agents.len();
+        let order = self.navigable_agents();
+        if order.is_empty() {
+            return;
         }
+        let next = match order.iter().position(|&i| i == self.selected_index) {
+            Some(pos) => order[(pos + 1) % order.len()],
+            None => order[0],
+        };
+        self.selected_index = next;
     }
 
     /// Selects the previous agent
     pub fn select_prev(&mut self) {
-        if !self.agents.root_agents.is_empty() {
-            if self.selected_index == 0 {
-                self.selected_index = self.agents.root_agents.len() - 1;
-            } else {
-                self.selected_index -= 1;
+        let order = self.navigable_agents();
+        if order.is_empty() {
+            return;
+        }
+        let prev = match order.iter().position(|&i| i == self.selected_index) {
+            Some(0) | None => order[order.len() - 1],
+            Some(pos) => order[pos - 1],
+        };
+        self.selected_index = prev;
+    }
+
+    /// Selects the first agent in display order
+    pub fn select_first(&mut self) {
+        if let Some(&first) = self.navigable_agents().first() {
+            self.selected_index = first;
+        }
+    }
+
+    /// Selects the last agent in display order
+    pub fn select_last(&mut self) {
+        if let Some(&last) = self.navigable_agents().last() {
+            self.selected_index = last;
+        }
+    }
+
+    /// Switches to the next sort order of the pane list
+    pub fn cycle_sort(&mut self) {
+        self.sort = self.sort.next();
+    }
+
+    /// Switches the sidebar between session/window and repository grouping
+    pub fn toggle_group_by_repo(&mut self) {
+        self.group_by_repo = !self.group_by_repo;
+    }
+
+    /// Collapses or expands the group of the selected agent
+    ///
+    /// The cursor moves to the group's first agent, which stays visible.
+    pub fn toggle_collapse_selected_group(&mut self) {
+        let Some(root) = self.selected_agent().map(|a| self.group_key(a)) else {
+            return;
+        };
+        if !self.collapsed_groups.remove(&root) {
+            if let Some((_, indices)) = self.visible_groups().into_iter().find(|(r, _)| *r == root)
+            {
+                self.selected_index = indices[0];
             }
+            self.collapsed_groups.insert(root);
         }
     }
 
@@ -277,6 +982,39 @@ impl AppState {
         }
     }
 
+    /// Sets the sidebar width, kept between 15% and 70% of the screen
+    pub fn set_sidebar_width(&mut self, percent: u16) {
+        self.sidebar_width = Self::clamp_sidebar_width(percent);
+    }
+
+    /// Returns the sidebar width actually used for `percent`
+    pub fn clamp_sidebar_width(percent: u16) -> u16 {
+        percent.clamp(15, 70)
+    }
+
+    /// Returns how many lines the selected agent's preview is scrolled back
+    pub fn preview_scroll(&self) -> usize {
+        match self.preview_scroll {
+            Some((index, lines)) if index == self.selected_index => lines,
+            _ => 0,
+        }
+    }
+
+    /// Scrolls the preview back, at most to the first captured line
+    pub fn scroll_preview_up(&mut self, lines: usize) {
+        let captured = self
+            .selected_agent()
+            .map_or(0, |a| a.last_content.lines().count());
+        let scroll = (self.preview_scroll() + lines).min(captured.saturating_sub(1));
+        self.preview_scroll = Some((self.selected_index, scroll));
+    }
+
+    /// Scrolls the preview forward, back to following the output at the end
+    pub fn scroll_preview_down(&mut self, lines: usize) {
+        let scroll = self.preview_scroll().saturating_sub(lines);
+        self.preview_scroll = (scroll > 0).then_some((self.selected_index, scroll));
+    }
+
     /// Toggles selection of the current agent
     pub fn toggle_selection(&mut self) {
         if self.selected_agents.contains(&self.selected_index) {
@@ -286,10 +1024,12 @@ impl AppState {
         }
     }
 
-    /// Selects all agents
+    /// Selects all agents matching the search
     pub fn select_all(&mut self) {
         for i in 0..self.agents.root_agents.len() {
-            self.selected_agents.insert(i);
+            if self.is_visible(i) {
+                self.selected_agents.insert(i);
+            }
         }
     }
 
@@ -309,6 +1049,277 @@ impl AppState {
         }
     }
 
+    /// Records state changes reported by the monitor, keeping the most recent ones
+    ///
+    /// Panes that start waiting, fail or finish their work get a notification.
+    pub fn record_transitions(&mut self, transitions: Vec<StateTransition>) {
+        for transition in &transitions {
+            if let Some(event) = NotifyOn::from_transition(transition.from, transition.to) {
+                self.notify(transition.key.clone(), event.text().to_string());
+            }
+        }
+
+        self.transitions.extend(transitions);
+        let excess = self.transitions.len().saturating_sub(MAX_TRANSITIONS);
+        self.transitions.drain(..excess);
+    }
+
+    /// Adds a notification about the pane at `key`, already read if it is
+    /// the selected one
+    pub fn notify(&mut self, key: String, text: String) {
+        let read = self.selected_agent().is_some_and(|a| a.key() == key);
+        self.notifications.push_front(Notification {
+            key,
+            text,
+            at: Local::now(),
+            read,
+        });
+        self.notifications.truncate(MAX_NOTIFICATIONS);
+    }
+
+    /// Returns the number of notifications not seen yet
+    pub fn unread_count(&self) -> usize {
+        self.notifications.iter().filter(|n| !n.read).count()
+    }
+
+    /// Marks the notifications about the selected agent as read
+    pub fn acknowledge_selected(&mut self) {
+        if let Some(key) = self.selected_agent().map(|a| a.key()) {
+            for notification in self.notifications.iter_mut().filter(|n| n.key == key) {
+                notification.read = true;
+            }
+        }
+    }
+
+    /// Marks one notification, or all of them, as read
+    pub fn mark_read(&mut self, index: Option<usize>) {
+        match index {
+            Some(index) => {
+                if let Some(notification) = self.notifications.get_mut(index) {
+                    notification.read = true;
+                }
+            }
+            None => self.notifications.iter_mut().for_each(|n| n.read = true),
+        }
+    }
+
+    /// Marks a notification as read and selects its pane, which is returned
+    /// unless it is gone
+    pub fn open_notification(&mut self, index: usize) -> Option<&MonitoredAgent> {
+        self.mark_read(Some(index));
+        let key = &self.notifications.get(index)?.key;
+        let Some(agent) = self.agents.root_agents.iter().position(|a| a.key() == *key) else {
+            self.set_error(format!("{} is gone", key));
+            return None;
+        };
+        self.selected_index = agent;
+        self.selected_agent()
+    }
+
+    /// Returns the recorded state changes of the agent at `key`, newest first
+    pub fn transitions_for<'a>(
+        &'a self,
+        key: &'a str,
+    ) -> impl Iterator<Item = &'a StateTransition> + 'a {
+        self.transitions.iter().rev().filter(move |t| t.key == key)
+    }
+
+    /// Returns the lines of the detail view of `agent`: where and what it is,
+    /// its repository and cost, and its state history, queue and notifications
+    pub fn pane_details(&self, agent: &MonitoredAgent) -> Vec<String> {
+        let key = agent.key();
+        let field = |name: &str, value: String| format!("{:<12}{}", name, value);
+        let mut lines = vec![
+            field("Pane", format!("{} (window {})", key, agent.window_name)),
+            field("Directory", agent.path.clone()),
+            field(
+                "Agent",
+                match &agent.version {
+                    Some(version) => format!("{} {}", agent.agent_type.display_name(), version),
+                    None => agent.agent_type.display_name().to_string(),
+                },
+            ),
+            field("PID", agent.pid.to_string()),
+            field(
+                "Uptime",
+                format!("{} ({})", agent.uptime_str(), agent.session_times_str()),
+            ),
+            field(
+                "State",
+                format!("{} for {}", agent.state_label(), agent.state_age_str()),
+            ),
+        ];
+        if let Some(git) = &agent.git {
+            let status = &git.status;
+            lines.push(field(
+                "Git",
+                format!(
+                    "{} ({} staged, {} modified, {} untracked)",
+                    git.label(),
+                    status.staged,
+                    status.modified,
+                    status.untracked
+                ),
+            ));
+            if let Some(commit) = &git.last_commit {
+                lines.push(field(
+                    "Last commit",
+                    format!("{} {}", commit.short_hash, commit.subject),
+                ));
+            }
+        }
+        lines.push(field("Cost", agent.stats.usage.summary()));
+        if !agent.activity.is_empty() {
+            let total: u32 = agent.activity.iter().sum();
+            let minutes = agent.activity.len();
+            lines.push(field(
+                "Output",
+                format!("{} lines in the last {}m", total, minutes),
+            ));
+        }
+        if let Some(ctx) = agent.context_remaining {
+            lines.push(field("Context", format!("{}% left", ctx)));
+        }
+        if let Some(project) = &agent.project {
+            lines.push(field("Project", project.source.display().to_string()));
+        }
+        for (name, value) in [("Label", &agent.label), ("Note", &agent.note)] {
+            if let Some(value) = value {
+                lines.push(field(name, value.clone()));
+            }
+        }
+
+        let transitions: Vec<String> = self
+            .transitions_for(&key)
+            .map(|t| {
+                let from = t.from.map_or("NEW", AgentState::label);
+                format!("  {} {} → {}", t.at.format("%H:%M:%S"), from, t.to.label())
+            })
+            .collect();
+        let queued: Vec<String> = self
+            .queued(&key)
+            .enumerate()
+            .map(|(i, text)| format!("  {}. {}", i + 1, text))
+            .collect();
+        let notifications: Vec<String> = self
+            .notifications
+            .iter()
+            .filter(|n| n.key == key)
+            .map(|n| format!("  {}", n.line()))
+            .collect();
+        for (title, section) in [
+            ("State history", transitions),
+            ("Queued prompts", queued),
+            ("Notifications", notifications),
+        ] {
+            lines.push(String::new());
+            lines.push(title.to_string());
+            if section.is_empty() {
+                lines.push("  none".to_string());
+            }
+            lines.extend(section);
+        }
+        lines
+    }
+
+    /// Queues `text` for the agent at `key`
+    pub fn enqueue_prompt(&mut self, key: String, text: String) {
+        self.queues.entry(key).or_default().push_back(text);
+    }
+
+    /// Returns the prompts queued for the agent at `key`, next first
+    pub fn queued(&self, key: &str) -> impl Iterator<Item = &String> {
+        self.queues.get(key).into_iter().flatten()
+    }
+
+    /// Moves the queued prompt at `index` one place later (or earlier when
+    /// `later` is false), returning its new index
+    pub fn move_queued(&mut self, key: &str, index: usize, later: bool) -> Option<usize> {
+        let queue = self.queues.get_mut(key)?;
+        let other = if later {
+            index.checked_add(1).filter(|&i| i < queue.len())?
+        } else {
+            index.checked_sub(1)?
+        };
+        queue.swap(index, other);
+        Some(other)
+    }
+
+    /// Removes the queued prompt at `index`
+    pub fn remove_queued(&mut self, key: &str, index: usize) {
+        if let Some(queue) = self.queues.get_mut(key) {
+            queue.remove(index);
+            if queue.is_empty() {
+                self.queues.remove(key);
+            }
+        }
+    }
+
+    /// Takes the next queued prompt of every agent that just became idle,
+    /// as `(key, prompt)` pairs
+    pub fn take_due_prompts(&mut self, transitions: &[StateTransition]) -> Vec<(String, String)> {
+        let mut due = Vec::new();
+        for transition in transitions {
+            if transition.to != AgentState::Idle {
+                continue;
+            }
+            if let Some(queue) = self.queues.get_mut(&transition.key) {
+                if let Some(text) = queue.pop_front() {
+                    due.push((transition.key.clone(), text));
+                }
+                if queue.is_empty() {
+                    self.queues.remove(&transition.key);
+                }
+            }
+        }
+        due
+    }
+
+    /// Excludes the agent at `key` from automatic answers, or includes it again
+    pub fn toggle_automation(&mut self, key: &str) {
+        if !self.automation_disabled.remove(key) {
+            self.automation_disabled.insert(key.to_string());
+        }
+    }
+
+    /// Selects exactly the agents matching `filter` and turns broadcast on
+    ///
+    /// Returns the number of selected agents; nothing changes if none match.
+    pub fn select_matching(&mut self, filter: &AgentFilter) -> usize {
+        let matching: HashSet<usize> = self
+            .agents
+            .root_agents
+            .iter()
+            .enumerate()
+            .filter(|(_, agent)| filter.matches(agent))
+            .map(|(i, _)| i)
+            .collect();
+        let count = matching.len();
+        if count > 0 {
+            self.selected_agents = matching;
+            self.broadcast = true;
+        }
+        count
+    }
+
+    /// Turns broadcast input on or off
+    pub fn toggle_broadcast(&mut self) {
+        self.broadcast = !self.broadcast;
+    }
+
+    /// Returns the indices input is sent to: every selected agent in
+    /// broadcast mode, otherwise the agent under the cursor
+    pub fn input_targets(&self) -> Vec<usize> {
+        if self.broadcast && !self.selected_agents.is_empty() {
+            return self.get_operation_indices();
+        }
+        if self.selected_agent().is_some() {
+            vec![self.selected_index]
+        } else {
+            Vec::new()
+        }
+    }
+
     /// Check if an agent is in multi-selection
     pub fn is_multi_selected(&self, index: usize) -> bool {
         self.selected_agents.contains(&index)
@@ -317,6 +1328,7 @@ impl AppState {
     /// Toggles help display
     pub fn toggle_help(&mut self) {
         self.show_help = !self.show_help;
+        self.help_scroll = 0;
     }
 
     /// Toggles subagent log display
@@ -331,12 +1343,73 @@ impl AppState {
 
     /// Sets an error message
     pub fn set_error(&mut self, message: String) {
+        self.last_message = None;
         self.last_error = Some(message);
     }
 
     /// Clears the error message
     pub fn clear_error(&mut self) {
         self.last_error = None;
+        self.last_message = None;
+    }
+
+    /// Sets an informational message (replaces any error)
+    pub fn set_message(&mut self, message: String) {
+        self.last_error = None;
+        self.last_message = Some(message);
+    }
+
+    /// Opens a prompt popup
+    pub fn open_prompt(&mut self, kind: PromptKind) {
+        self.open_prompt_with(kind, String::new());
+    }
+
+    /// Opens a prompt popup with `initial` already entered
+    pub fn open_prompt_with(&mut self, kind: PromptKind, initial: String) {
+        self.prompt = Some(Prompt {
+            kind,
+            buffer: initial,
+        });
+    }
+
+    /// Closes the prompt popup, returning it
+    pub fn take_prompt(&mut self) -> Option<Prompt> {
+        self.prompt.take()
+    }
+
+    /// Opens a list picker popup
+    pub fn open_picker(&mut self, picker: Picker) {
+        self.picker = Some(picker);
+    }
+
+    /// Closes the list picker popup, returning it
+    pub fn take_picker(&mut self) -> Option<Picker> {
+        self.picker.take()
+    }
+
+    /// Opens a scrollable viewer
+    pub fn open_viewer(&mut self, viewer: Viewer) {
+        self.viewer = Some(viewer);
+    }
+
+    /// Closes the scrollable viewer
+    pub fn close_viewer(&mut self) {
+        self.viewer = None;
+    }
+}
+
+/// Position of an agent in state order, agents waiting for an answer first
+fn state_rank(agent: &MonitoredAgent) -> u8 {
+    if agent.stalled_since.is_some() {
+        return 2;
+    }
+    match agent.state {
+        Some(AgentState::WaitingForPermission) => 0,
+        Some(AgentState::WaitingForInput) => 1,
+        Some(AgentState::Error) => 2,
+        Some(AgentState::Working) => 3,
+        Some(AgentState::Idle) => 4,
+        None => 5,
     }
 }
 
@@ -386,5 +1459,521 @@ mod tests {
         assert_eq!(state.selected_index, 0); // Wraps around
         state.select_prev();
         assert_eq!(state.selected_index, 1); // Wraps around
+        state.select_first();
+        assert_eq!(state.selected_index, 0);
+        state.select_last();
+        assert_eq!(state.selected_index, 1);
+    }
+
+    #[test]
+    fn test_input_targets() {
+        let mut state = AppState::new();
+        assert!(state.input_targets().is_empty());
+        for i in 0..3 {
+            state.agents.root_agents.push(MonitoredAgent::new(
+                i.to_string(),
+                format!("main:0.{}", i),
+                "main".to_string(),
+                0,
+                "code".to_string(),
+                i as u32,
+                "/home/user/project".to_string(),
+                AgentType::ClaudeCode,
+                1000 + i as u32,
+            ));
+        }
+        state.selected_agents.extend([2, 0]);
+        assert_eq!(state.input_targets(), vec![0]);
+
+        state.toggle_broadcast();
+        assert_eq!(state.input_targets(), vec![0, 2]);
+
+        state.clear_selection();
+        assert_eq!(state.input_targets(), vec![0]);
+    }
+
+    #[test]
+    fn test_confirm_message() {
+        let confirm = Confirm::RespawnPane {
+            server: TmuxServer::Default,
+            target: "main:0.1".to_string(),
+            cwd: "/home/user/app".to_string(),
+            launch: LaunchCommand {
+                command_line: "claude --resume".to_string(),
+                in_shell: true,
+            },
+        };
+        assert_eq!(
+            confirm.message(),
+            "Restart main:0.1 with `claude --resume`?"
+        );
+        let kill = Confirm::KillPane {
+            server: TmuxServer::Name("agents".to_string()),
+            target: "main:0.1".to_string(),
+        };
+        assert_eq!(kill.message(), "Kill pane agents/main:0.1?");
+    }
+
+    #[test]
+    fn test_picker_navigation() {
+        let mut picker = Picker::new(
+            PickerKind::Branch {
+                repo: "/repo".to_string(),
+            },
+            vec!["feat".to_string(), "main".to_string(), "topic".to_string()],
+            Some(1),
+        );
+        assert_eq!(picker.selected_item(), Some("main"));
+        picker.select_next();
+        picker.select_next();
+        assert_eq!(picker.selected_item(), Some("feat")); // Wraps around
+        picker.select_prev();
+        assert_eq!(picker.selected_item(), Some("topic")); // Wraps around
+    }
+
+    #[test]
+    fn test_prompt_queue() {
+        let mut state = AppState::new();
+        for text in ["first", "second", "third"] {
+            state.enqueue_prompt("main:0.0".to_string(), text.to_string());
+        }
+        assert_eq!(state.move_queued("main:0.0", 2, false), Some(1));
+        assert_eq!(state.move_queued("main:0.0", 0, false), None);
+        assert_eq!(state.move_queued("main:0.0", 2, true), None);
+        assert_eq!(
+            state.queued("main:0.0").collect::<Vec<_>>(),
+            ["first", "third", "second"]
+        );
+
+        let transition = |key: &str, to| StateTransition {
+            key: key.to_string(),
+            from: Some(AgentState::Working),
+            to,
+            at: chrono::Local::now(),
+        };
+        // Only becoming idle delivers, one prompt at a time
+        let transitions = [
+            transition("main:0.0", AgentState::WaitingForPermission),
+            transition("main:0.1", AgentState::Idle),
+        ];
+        assert!(state.take_due_prompts(&transitions).is_empty());
+        let transitions = [transition("main:0.0", AgentState::Idle)];
+        assert_eq!(
+            state.take_due_prompts(&transitions),
+            [("main:0.0".to_string(), "first".to_string())]
+        );
+
+        state.remove_queued("main:0.0", 0);
+        state.remove_queued("main:0.0", 0);
+        assert!(state.queues.is_empty());
+    }
+
+    #[test]
+    fn test_notifications() {
+        let mut state = AppState::new();
+        for i in 0..2 {
+            state.agents.root_agents.push(MonitoredAgent::new(
+                i.to_string(),
+                format!("main:0.{}", i),
+                "main".to_string(),
+                0,
+                "code".to_string(),
+                i as u32,
+                "/code".to_string(),
+                AgentType::ClaudeCode,
+                1000 + i as u32,
+            ));
+        }
+        let transition = |key: &str, from, to| StateTransition {
+            key: key.to_string(),
+            from,
+            to,
+            at: chrono::Local::now(),
+        };
+
+        // Newly seen panes and dull changes are left out; the selected pane's are read
+        state.record_transitions(vec![
+            transition("main:0.0", Some(AgentState::Working), AgentState::Error),
+            transition("main:0.1", None, AgentState::WaitingForInput),
+            transition("main:0.1", Some(AgentState::Idle), AgentState::Working),
+        ]);
+        assert_eq!(state.notifications.len(), 1);
+        assert_eq!(state.unread_count(), 0);
+
+        let waiting = AgentState::WaitingForPermission;
+        state.record_transitions(vec![
+            transition("main:0.1", Some(AgentState::Working), waiting),
+            transition("main:0.1", Some(waiting), AgentState::Working),
+            transition("main:0.1", Some(AgentState::Working), AgentState::Idle),
+        ]);
+        assert_eq!(state.unread_count(), 2);
+        assert_eq!(state.notifications[0].text, "finished");
+        assert!(state.notifications[1].line().starts_with("● "));
+
+        state.mark_read(Some(0));
+        assert_eq!(state.unread_count(), 1);
+        assert_eq!(
+            state.open_notification(1).map(|a| a.key()),
+            Some("main:0.1".to_string())
+        );
+        assert_eq!(state.selected_index, 1);
+        assert_eq!(state.unread_count(), 0);
+
+        state.agents.root_agents.pop();
+        state.selected_index = 0;
+        assert!(state.open_notification(0).is_none());
+        assert!(state.last_error.is_some());
+    }
+
+    #[test]
+    fn test_viewer_scroll() {
+        let lines = (0..5).map(|i| i.to_string()).collect();
+        let mut viewer = Viewer::new(ViewerKind::CommitLog, "Log".to_string(), lines);
+        viewer.scroll_up(3);
+        assert_eq!(viewer.scroll, 0);
+        viewer.scroll_down(10);
+        assert_eq!(viewer.scroll, 4);
+        viewer.scroll_up(2);
+        assert_eq!(viewer.scroll, 2);
+    }
+
+    #[test]
+    fn test_viewer_selection() {
+        let lines = (0..5).map(|i| i.to_string()).collect();
+        let mut viewer = Viewer::new(ViewerKind::Output, "Output".to_string(), lines);
+        assert_eq!(viewer.selected_text(), "0\n1\n2\n3\n4");
+
+        viewer.scroll_down(3);
+        viewer.toggle_mark();
+        viewer.scroll_up(2);
+        assert_eq!(viewer.selection(), Some(1..=3));
+        assert_eq!(viewer.selected_text(), "1\n2\n3");
+
+        viewer.toggle_mark();
+        assert_eq!(viewer.selection(), None);
+    }
+
+    #[test]
+    fn test_viewer_search() {
+        let lines = ["cargo build", "error: E0425", "warning", "Error again"]
+            .map(str::to_string)
+            .to_vec();
+        let mut viewer = Viewer::new(ViewerKind::Scrollback, "History".to_string(), lines);
+        assert!(viewer.set_search("ERROR"));
+        assert_eq!(viewer.scroll, 1);
+        assert!(viewer.find_next(true));
+        assert_eq!(viewer.scroll, 3);
+        assert!(viewer.find_next(true));
+        assert_eq!(viewer.scroll, 1);
+        assert!(viewer.find_next(false));
+        assert_eq!(viewer.scroll, 3);
+
+        assert!(!viewer.set_search("panic"));
+        assert_eq!(viewer.scroll, 3);
+        assert!(viewer.set_search(""));
+        assert_eq!(viewer.search, None);
+        assert!(!viewer.find_next(true));
+    }
+
+    #[test]
+    fn test_repo_grouping_navigation() {
+        let mut state = AppState::new();
+        for (i, path) in ["/code/app", "/code/lib", "/code/app"].iter().enumerate() {
+            state.agents.root_agents.push(MonitoredAgent::new(
+                i.to_string(),
+                format!("main:0.{}", i),
+                "main".to_string(),
+                0,
+                "code".to_string(),
+                i as u32,
+                path.to_string(),
+                AgentType::ClaudeCode,
+                1000 + i as u32,
+            ));
+        }
+
+        state.toggle_group_by_repo();
+        assert_eq!(state.navigable_agents(), vec![0, 2, 1]);
+        state.select_next();
+        assert_eq!(state.selected_index, 2);
+
+        // Collapsing moves the cursor to the group's first agent and hides the rest
+        state.toggle_collapse_selected_group();
+        assert_eq!(state.selected_index, 0);
+        assert_eq!(state.navigable_agents(), vec![0, 1]);
+        state.select_next();
+        assert_eq!(state.selected_index, 1);
+
+        state.select_prev();
+        state.toggle_collapse_selected_group();
+        assert_eq!(state.navigable_agents(), vec![0, 2, 1]);
+    }
+
+    #[test]
+    fn test_session_grouping_navigation() {
+        let mut state = AppState::new();
+        for (i, (session, window)) in [("work", 1), ("api", 0), ("work", 0)].iter().enumerate() {
+            state.agents.root_agents.push(MonitoredAgent::new(
+                i.to_string(),
+                format!("{}:{}.0", session, window),
+                session.to_string(),
+                *window,
+                "code".to_string(),
+                0,
+                "/code".to_string(),
+                AgentType::ClaudeCode,
+                1000 + i as u32,
+            ));
+        }
+
+        // Sessions by name, windows in order within each
+        assert_eq!(state.navigable_agents(), vec![1, 2, 0]);
+        state.select_agent(0);
+        state.toggle_collapse_selected_group();
+        assert!(state.collapsed_groups.contains("work"));
+        assert_eq!(state.selected_index, 2);
+        assert_eq!(state.navigable_agents(), vec![1, 2]);
+
+        state.toggle_collapse_selected_group();
+        assert_eq!(state.navigable_agents(), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn test_server_tabs() {
+        let mut state = AppState::new();
+        state.servers = vec![TmuxServer::Default, TmuxServer::Remote("build".to_string())];
+        for (i, server) in state.servers.clone().into_iter().enumerate() {
+            let mut agent = MonitoredAgent::new(
+                i.to_string(),
+                "main:0.0".to_string(),
+                "main".to_string(),
+                0,
+                "code".to_string(),
+                0,
+                "/code".to_string(),
+                AgentType::ClaudeCode,
+                1000 + i as u32,
+            );
+            agent.server = server;
+            state.agents.root_agents.push(agent);
+        }
+
+        assert!(state.select_server_tab(2));
+        assert_eq!(state.navigable_agents(), vec![1]);
+        assert_eq!(state.selected_index, 1);
+        assert!(!state.select_server_tab(3));
+        assert!(state.select_server_tab(0));
+        assert_eq!(state.server_tab, None);
+        assert_eq!(state.navigable_agents(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_sort_modes() {
+        let mut state = AppState::new();
+        let agents = [
+            ("zeta", Some(AgentState::Idle), 0.5),
+            ("Alpha", Some(AgentState::Working), 2.0),
+            ("beta", Some(AgentState::WaitingForInput), 1.0),
+        ];
+        for (i, (name, agent_state, cost)) in agents.into_iter().enumerate() {
+            let mut agent = MonitoredAgent::new(
+                i.to_string(),
+                format!("main:{}.0", i),
+                "main".to_string(),
+                i as u32,
+                name.to_string(),
+                0,
+                "/code".to_string(),
+                AgentType::ClaudeCode,
+                1000 + i as u32,
+            );
+            agent.state = agent_state;
+            agent.stats.usage.cost_usd = cost;
+            state.agents.root_agents.push(agent);
+        }
+
+        assert_eq!(state.navigable_agents(), vec![0, 1, 2]);
+        state.cycle_sort();
+        assert_eq!(state.sort, SortMode::State);
+        assert_eq!(state.navigable_agents(), vec![2, 1, 0]);
+        state.cycle_sort();
+        assert_eq!(state.navigable_agents()[0], 1);
+        state.cycle_sort();
+        assert_eq!(state.navigable_agents(), vec![1, 2, 0]);
+        state.cycle_sort();
+        assert_eq!(state.navigable_agents(), vec![1, 2, 0]);
+        state.select_first();
+        assert_eq!(state.selected_index, 1);
+        state.cycle_sort();
+        assert_eq!(state.sort, SortMode::Tmux);
+    }
+
+    #[test]
+    fn test_restore_selection() {
+        let mut state = AppState::new();
+        state.pending_selection = Some("main:0.1".to_string());
+        for i in 0..2 {
+            state.agents.root_agents.push(MonitoredAgent::new(
+                i.to_string(),
+                format!("main:0.{}", i),
+                "main".to_string(),
+                0,
+                "code".to_string(),
+                i as u32,
+                "/code".to_string(),
+                AgentType::ClaudeCode,
+                1000 + i as u32,
+            ));
+        }
+
+        state.restore_selection();
+        assert_eq!(state.selected_index, 1);
+        // Only the first update restores it
+        state.selected_index = 0;
+        state.restore_selection();
+        assert_eq!(state.selected_index, 0);
+        assert_eq!(AppState::clamp_sidebar_width(90), 70);
+    }
+
+    #[test]
+    fn test_pane_details() {
+        let mut state = AppState::new();
+        let mut agent = MonitoredAgent::new(
+            "1".to_string(),
+            "main:0.1".to_string(),
+            "main".to_string(),
+            0,
+            "code".to_string(),
+            1,
+            "/code/app".to_string(),
+            AgentType::ClaudeCode,
+            4242,
+        );
+        agent.version = Some("2.1.11".to_string());
+        state.record_transitions(vec![StateTransition {
+            key: "main:0.1".to_string(),
+            from: Some(AgentState::Working),
+            to: AgentState::Idle,
+            at: chrono::Local::now(),
+        }]);
+        state.enqueue_prompt("main:0.1".to_string(), "run the tests".to_string());
+
+        let lines = state.pane_details(&agent);
+        assert!(lines.contains(&"Agent       Claude Code 2.1.11".to_string()));
+        assert!(lines.contains(&"PID         4242".to_string()));
+        assert!(lines.iter().any(|l| l.ends_with("WORKING → IDLE")));
+        assert!(lines.contains(&"  1. run the tests".to_string()));
+        assert!(lines.iter().any(|l| l.ends_with("main:0.1  finished")));
+        assert!(!lines.iter().any(|l| l.starts_with("Git")));
+    }
+
+    #[test]
+    fn test_pinned_panes() {
+        let mut state = AppState::new();
+        for (i, session) in ["api", "api", "web"].iter().enumerate() {
+            state.agents.root_agents.push(MonitoredAgent::new(
+                i.to_string(),
+                format!("{}:{}.0", session, i),
+                session.to_string(),
+                i as u32,
+                "code".to_string(),
+                0,
+                "/code".to_string(),
+                AgentType::ClaudeCode,
+                1000 + i as u32,
+            ));
+        }
+
+        state.select_agent(2);
+        assert_eq!(state.toggle_pin(), Some(true));
+        state.select_agent(1);
+        assert_eq!(state.toggle_pin(), Some(true));
+        let groups = state.visible_groups();
+        assert_eq!(groups[0], (PINNED_GROUP.to_string(), vec![1, 2]));
+        assert_eq!(groups[1], ("api".to_string(), vec![0]));
+        assert_eq!(groups.len(), 2);
+
+        // Pins stay on top whatever the order
+        state.agents.root_agents[0].state = Some(AgentState::WaitingForPermission);
+        state.sort = SortMode::State;
+        assert_eq!(state.navigable_agents(), vec![1, 2, 0]);
+
+        // Collapsing follows the pins
+        state.toggle_collapse_selected_group();
+        assert!(state.collapsed_groups.contains(PINNED_GROUP));
+        assert_eq!(state.navigable_agents(), vec![1, 0]);
+
+        assert_eq!(state.toggle_pin(), Some(false));
+        assert_eq!(state.pinned.len(), 1);
+    }
+
+    #[test]
+    fn test_search_navigation() {
+        let mut state = AppState::new();
+        for (i, window) in ["api", "web", "api-tests"].iter().enumerate() {
+            state.agents.root_agents.push(MonitoredAgent::new(
+                i.to_string(),
+                format!("main:{}.0", i),
+                "main".to_string(),
+                i as u32,
+                window.to_string(),
+                0,
+                "/code/app".to_string(),
+                AgentType::ClaudeCode,
+                1000 + i as u32,
+            ));
+        }
+        state.select_agent(1);
+
+        // The cursor leaves the hidden agent for the first match
+        state.set_search("api").unwrap();
+        assert_eq!(state.navigable_agents(), vec![0, 2]);
+        assert_eq!(state.selected_index, 0);
+        state.select_next();
+        assert_eq!(state.selected_index, 2);
+        state.select_all();
+        assert_eq!(state.get_operation_indices(), vec![0, 2]);
+
+        state.toggle_group_by_repo();
+        assert_eq!(state.visible_groups()[0].1, vec![0, 2]);
+
+        assert!(state.set_search("state:sleeping").is_err());
+        state.set_search("").unwrap();
+        assert!(state.search.is_none());
+        assert_eq!(state.navigable_agents(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_preview_scroll() {
+        let mut state = AppState::new();
+        for i in 0..2 {
+            let mut agent = MonitoredAgent::new(
+                i.to_string(),
+                format!("main:0.{}", i),
+                "main".to_string(),
+                0,
+                "code".to_string(),
+                i,
+                "/code/app".to_string(),
+                AgentType::ClaudeCode,
+                1000 + i,
+            );
+            agent.last_content = "1\n2\n3\n4\n5".to_string();
+            state.agents.root_agents.push(agent);
+        }
+
+        state.scroll_preview_up(3);
+        assert_eq!(state.preview_scroll(), 3);
+        state.scroll_preview_up(3);
+        assert_eq!(state.preview_scroll(), 4);
+        state.scroll_preview_down(1);
+        assert_eq!(state.preview_scroll(), 3);
+
+        // Scrolling applies to the agent it was done on
+        state.select_next();
+        assert_eq!(state.preview_scroll(), 0);
+        state.select_prev();
+        state.scroll_preview_down(10);
+        assert_eq!(state.preview_scroll(), 0);
     }
 }
@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+/// Built-in color palette
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    /// Bright colors for dark terminal backgrounds
+    #[default]
+    Dark,
+    /// Darker colors readable on light terminal backgrounds
+    Light,
+    /// Bright, saturated colors only, with a strong selection
+    HighContrast,
+}
+
+/// The `[theme]` config section
+///
+/// ```toml
+/// [theme]
+/// name = "light"
+/// accent = "#005f87"
+/// waiting = "33"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThemeConfig {
+    /// Palette the overrides start from
+    #[serde(default)]
+    pub name: ThemeName,
+    /// Colors per role: a name ("red", "light-blue"), "#rrggbb" or a
+    /// 0-255 palette index
+    #[serde(flatten)]
+    pub colors: BTreeMap<String, String>,
+}
+
+/// Colors of every part of the TUI, by role
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Regular text
+    pub text: Color,
+    /// Secondary details, separators and placeholders
+    pub dim: Color,
+    /// Unfocused borders and less important text
+    pub muted: Color,
+    /// Background of popups
+    pub background: Color,
+    /// Text on colored backgrounds (badges, selection)
+    pub inverse: Color,
+    /// Titles, focused borders and the selection bar
+    pub accent: Color,
+    /// Keys, numbers and activity
+    pub highlight: Color,
+    /// Passed checks, clean trees and completed work
+    pub success: Color,
+    /// Failures and requests needing attention
+    pub danger: Color,
+    /// Branches, notes and remote hosts
+    pub special: Color,
+    /// Links, queued prompts and other information
+    pub info: Color,
+    /// Warnings that are not failures
+    pub alert: Color,
+    /// Background of the cursor row in lists and viewers
+    pub cursor: Color,
+    /// Background of selected rows
+    pub selection: Color,
+    /// State badge of idle agents
+    pub idle: Color,
+    /// State badge of working agents
+    pub working: Color,
+    /// State badge of agents asking a question
+    pub waiting: Color,
+    /// State badge of agents asking for permission
+    pub permission: Color,
+    /// State badge of agents in error
+    pub error: Color,
+    /// State badge of agents in an unknown state
+    pub unknown: Color,
+    /// State badge of agents flagged by the watchdog
+    pub stalled: Color,
+}
+
+/// Role names accepted in `[theme]`
+const ROLES: &[&str] = &[
+    "text",
+    "dim",
+    "muted",
+    "background",
+    "inverse",
+    "accent",
+    "highlight",
+    "success",
+    "danger",
+    "special",
+    "info",
+    "alert",
+    "cursor",
+    "selection",
+    "idle",
+    "working",
+    "waiting",
+    "permission",
+    "error",
+    "unknown",
+    "stalled",
+];
+
+impl Theme {
+    /// Returns a built-in palette
+    pub fn builtin(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Dark => Self {
+                text: Color::White,
+                dim: Color::DarkGray,
+                muted: Color::Gray,
+                background: Color::Black,
+                inverse: Color::Black,
+                accent: Color::Cyan,
+                highlight: Color::Yellow,
+                success: Color::Green,
+                danger: Color::Red,
+                special: Color::Magenta,
+                info: Color::Blue,
+                alert: Color::LightRed,
+                cursor: Color::Rgb(50, 50, 70),
+                selection: Color::Rgb(35, 35, 50),
+                idle: Color::Green,
+                working: Color::Yellow,
+                waiting: Color::Cyan,
+                permission: Color::Red,
+                error: Color::Magenta,
+                unknown: Color::DarkGray,
+                stalled: Color::LightRed,
+            },
+            ThemeName::Light => Self {
+                text: Color::Black,
+                dim: Color::Indexed(245),
+                muted: Color::Indexed(240),
+                background: Color::White,
+                inverse: Color::White,
+                accent: Color::Indexed(31),
+                highlight: Color::Indexed(130),
+                success: Color::Indexed(28),
+                danger: Color::Indexed(160),
+                special: Color::Indexed(127),
+                info: Color::Indexed(25),
+                alert: Color::Indexed(166),
+                cursor: Color::Indexed(252),
+                selection: Color::Indexed(255),
+                idle: Color::Indexed(28),
+                working: Color::Indexed(136),
+                waiting: Color::Indexed(31),
+                permission: Color::Indexed(160),
+                error: Color::Indexed(127),
+                unknown: Color::Indexed(245),
+                stalled: Color::Indexed(166),
+            },
+            ThemeName::HighContrast => Self {
+                text: Color::White,
+                dim: Color::Gray,
+                muted: Color::White,
+                background: Color::Black,
+                inverse: Color::Black,
+                accent: Color::LightCyan,
+                highlight: Color::LightYellow,
+                success: Color::LightGreen,
+                danger: Color::LightRed,
+                special: Color::LightMagenta,
+                info: Color::LightBlue,
+                alert: Color::Indexed(208),
+                cursor: Color::Blue,
+                selection: Color::Indexed(238),
+                idle: Color::LightGreen,
+                working: Color::LightYellow,
+                waiting: Color::LightCyan,
+                permission: Color::LightRed,
+                error: Color::LightMagenta,
+                unknown: Color::Gray,
+                stalled: Color::Indexed(208),
+            },
+        }
+    }
+
+    /// Builds the theme of the `[theme]` config section
+    pub fn from_config(config: &ThemeConfig) -> Result<Self> {
+        let mut theme = Self::builtin(config.name);
+        for (role, value) in &config.colors {
+            let Some(color) = theme.role_mut(role) else {
+                bail!(
+                    "Unknown theme color '{}' (expected one of: {})",
+                    role,
+                    ROLES.join(", ")
+                );
+            };
+            let Ok(parsed) = Color::from_str(value) else {
+                bail!("Invalid color '{}' for theme color '{}'", value, role);
+            };
+            *color = parsed;
+        }
+        Ok(theme)
+    }
+
+    fn role_mut(&mut self, role: &str) -> Option<&mut Color> {
+        let color = match role {
+            "text" => &mut self.text,
+            "dim" => &mut self.dim,
+            "muted" => &mut self.muted,
+            "background" => &mut self.background,
+            "inverse" => &mut self.inverse,
+            "accent" => &mut self.accent,
+            "highlight" => &mut self.highlight,
+            "success" => &mut self.success,
+            "danger" => &mut self.danger,
+            "special" => &mut self.special,
+            "info" => &mut self.info,
+            "alert" => &mut self.alert,
+            "cursor" => &mut self.cursor,
+            "selection" => &mut self.selection,
+            "idle" => &mut self.idle,
+            "working" => &mut self.working,
+            "waiting" => &mut self.waiting,
+            "permission" => &mut self.permission,
+            "error" => &mut self.error,
+            "unknown" => &mut self.unknown,
+            "stalled" => &mut self.stalled,
+            _ => return None,
+        };
+        Some(color)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::builtin(ThemeName::Dark)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_themes() {
+        let config: ThemeConfig = toml::from_str("").unwrap();
+        assert_eq!(Theme::from_config(&config).unwrap(), Theme::default());
+
+        let config: ThemeConfig = toml::from_str(r#"name = "high-contrast""#).unwrap();
+        assert_eq!(config.name, ThemeName::HighContrast);
+        assert_eq!(
+            Theme::from_config(&config).unwrap().accent,
+            Color::LightCyan
+        );
+
+        let light = Theme::builtin(ThemeName::Light);
+        assert_ne!(light.text, Theme::default().text);
+        let mut theme = light.clone();
+        for role in ROLES {
+            assert!(theme.role_mut(role).is_some(), "{}", role);
+        }
+    }
+
+    #[test]
+    fn test_color_overrides() {
+        let config: ThemeConfig = toml::from_str(
+            r##"
+name = "light"
+accent = "#005f87"
+waiting = "33"
+danger = "light-red"
+"##,
+        )
+        .unwrap();
+        let theme = Theme::from_config(&config).unwrap();
+        assert_eq!(theme.accent, Color::Rgb(0, 95, 135));
+        assert_eq!(theme.waiting, Color::Indexed(33));
+        assert_eq!(theme.danger, Color::LightRed);
+        assert_eq!(theme.text, Color::Black);
+
+        let unknown: ThemeConfig = toml::from_str(r#"acent = "red""#).unwrap();
+        let err = Theme::from_config(&unknown).unwrap_err().to_string();
+        assert!(err.contains("acent"), "{}", err);
+
+        let invalid: ThemeConfig = toml::from_str(r#"accent = "reddish""#).unwrap();
+        assert!(Theme::from_config(&invalid).is_err());
+    }
+}
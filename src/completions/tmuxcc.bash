@@ -0,0 +1,18 @@
+
+# Pane targets, labels and session names of the running tmux servers
+_tmuxcc_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" prev="${COMP_WORDS[COMP_CWORD-1]}"
+    local kind=""
+    case "${COMP_WORDS[1]} $prev" in
+        "new -s" | "new --session") kind=sessions ;;
+        "new --split") kind=targets ;;
+        send\ * | wait\ * | jump\ * | logs\ * | kill\ * | restart\ *) [[ $COMP_CWORD -eq 2 ]] && kind=targets ;;
+    esac
+    if [[ -n $kind ]]; then
+        local IFS=$'\n'
+        COMPREPLY=($(compgen -W "$(tmuxcc complete "$kind" 2>/dev/null)" -- "$cur"))
+        return 0
+    fi
+    _tmuxcc "$@"
+}
+complete -F _tmuxcc_dynamic -o nosort -o bashdefault -o default tmuxcc
@@ -0,0 +1,5 @@
+
+# Pane targets, labels and session names of the running tmux servers
+complete -c tmuxcc -n "__fish_seen_subcommand_from send wait jump logs kill restart; and test (count (commandline -opc)) -eq 2" -f -a "(tmuxcc complete targets 2>/dev/null)"
+complete -c tmuxcc -n "__fish_seen_subcommand_from new" -s s -l session -x -a "(tmuxcc complete sessions 2>/dev/null)"
+complete -c tmuxcc -n "__fish_seen_subcommand_from new" -l split -x -a "(tmuxcc complete targets 2>/dev/null)"
@@ -0,0 +1,104 @@
+use std::io::BufReader;
+use std::os::unix::net::UnixStream;
+
+use anyhow::{bail, Context, Result};
+use tokio::sync::mpsc;
+
+use super::protocol::{read_message, write_message, Reply, Request, Settings, Source};
+use super::{owned, socket_path};
+use crate::app::{AgentTree, Config};
+use crate::monitor::{MonitorUpdate, StateTransition};
+
+/// Whether a daemon of the user listens on the socket
+pub fn is_running() -> bool {
+    connect().is_ok()
+}
+
+/// A running daemon as seen by a client
+pub enum Availability {
+    NotRunning,
+    /// The daemon monitors what the client's config describes
+    Serves,
+    /// The daemon runs with another config, profile or servers
+    Elsewhere(Source),
+}
+
+/// Returns whether a daemon runs for the config file, profile and servers
+/// of `config`
+pub fn availability(config: &Config) -> Availability {
+    if !is_running() {
+        return Availability::NotRunning;
+    }
+    match request(&Request::Source) {
+        Ok(Reply::Source(source)) if source == Source::of(config) => Availability::Serves,
+        Ok(Reply::Source(source)) => Availability::Elsewhere(source),
+        _ => Availability::NotRunning,
+    }
+}
+
+/// Connects to the daemon, refusing a socket or directory of another user
+fn connect() -> Result<UnixStream> {
+    let path = socket_path();
+    let stream = UnixStream::connect(&path)
+        .with_context(|| format!("tmuxcc daemon isn't running ({})", path.display()))?;
+    if !path.parent().is_some_and(owned) || !owned(&path) {
+        bail!("{} belongs to another user", path.display());
+    }
+    Ok(stream)
+}
+
+/// Sends `request` to the daemon and returns its reply
+pub fn request(request: &Request) -> Result<Reply> {
+    let mut stream = connect()?;
+    write_message(&mut stream, request)?;
+    match read_message(&mut BufReader::new(stream))? {
+        Some(Reply::Error(message)) => bail!("tmuxcc daemon: {}", message),
+        Some(reply) => Ok(reply),
+        None => bail!("tmuxcc daemon closed the connection"),
+    }
+}
+
+/// Returns the agents as of the daemon's last poll
+pub fn snapshot() -> Result<AgentTree> {
+    match request(&Request::Snapshot)? {
+        Reply::Agents(tree) => Ok(tree),
+        reply => bail!("Unexpected reply from tmuxcc daemon: {:?}", reply),
+    }
+}
+
+/// The daemon's monitor as seen by a subscriber
+pub struct Subscription {
+    /// Recent state transitions, oldest first
+    pub transitions: Vec<StateTransition>,
+    pub settings: Settings,
+    /// The monitor updates, until the daemon stops
+    pub updates: mpsc::Receiver<MonitorUpdate>,
+}
+
+/// Subscribes to the daemon's monitor updates
+pub fn subscribe() -> Result<Subscription> {
+    let mut stream = connect()?;
+    write_message(&mut stream, &Request::Subscribe)?;
+    let mut reader = BufReader::new(stream);
+    let Some(Reply::Welcome {
+        transitions,
+        settings,
+    }) = read_message(&mut reader)?
+    else {
+        bail!("tmuxcc daemon didn't accept the subscription");
+    };
+
+    let (tx, updates) = mpsc::channel(32);
+    std::thread::spawn(move || {
+        while let Ok(Some(Reply::Update(update))) = read_message(&mut reader) {
+            if tx.blocking_send(*update).is_err() {
+                return;
+            }
+        }
+    });
+    Ok(Subscription {
+        transitions,
+        settings,
+        updates,
+    })
+}
@@ -0,0 +1,80 @@
+//! `tmuxcc daemon`: the monitor on its own, with the dashboard and commands
+//! as clients of its Unix socket
+//!
+//! The daemon polls, approves, answers and notifies whether or not a
+//! dashboard is open, so closing one loses no state or statistics.
+
+mod client;
+mod protocol;
+mod server;
+
+use std::fs::{DirBuilder, Permissions};
+use std::io::ErrorKind;
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+pub use client::{
+    availability, is_running, request, snapshot, subscribe, Availability, Subscription,
+};
+pub use protocol::{Reply, Request, Settings, Source};
+pub use server::run_daemon;
+
+/// Returns the id of the user running tmuxcc
+fn current_uid() -> u32 {
+    // SAFETY: getuid has no preconditions and can't fail
+    unsafe { libc::getuid() }
+}
+
+/// Returns the directory of tmuxcc's sockets (the daemon's, the tmux hook
+/// listener's and ssh's shared connections), one per user like tmux's own,
+/// so users sharing a temporary directory don't meet
+pub(crate) fn socket_dir() -> PathBuf {
+    dirs::runtime_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(format!("tmuxcc-{}", current_uid()))
+}
+
+/// Returns the path of the daemon's socket
+pub fn socket_path() -> PathBuf {
+    socket_dir().join("daemon.sock")
+}
+
+/// Returns true if `path` itself (not a link to it) belongs to the user
+fn owned(path: &Path) -> bool {
+    std::fs::symlink_metadata(path).is_ok_and(|m| m.uid() == current_uid())
+}
+
+/// Creates the socket's directory, or takes the existing one if it belongs
+/// to the user, accessible to nobody else
+pub(crate) fn create_socket_dir(dir: &Path) -> Result<()> {
+    match DirBuilder::new().mode(0o700).create(dir) {
+        Err(e) if e.kind() != ErrorKind::AlreadyExists => {
+            return Err(e).with_context(|| format!("Failed to create {}", dir.display()));
+        }
+        _ => {}
+    }
+    if !dir.is_dir() || !owned(dir) {
+        bail!("{} isn't a directory of yours", dir.display());
+    }
+    std::fs::set_permissions(dir, Permissions::from_mode(0o700))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_socket_path() {
+        let path = socket_path();
+        let dir = path.parent().unwrap();
+        assert_eq!(dir, socket_dir());
+        assert!(dir.ends_with(format!("tmuxcc-{}", current_uid())));
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        assert!(owned(file.path()));
+        assert!(!owned(&file.path().with_extension("missing")));
+    }
+}
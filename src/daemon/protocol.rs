@@ -0,0 +1,231 @@
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::app::{AgentTree, Config};
+use crate::monitor::{MonitorUpdate, StateTransition};
+use crate::tmux::TmuxServer;
+
+/// What a client asks the daemon, one reply per request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Request {
+    /// A welcome, then every monitor update until the client hangs up
+    Subscribe,
+    /// The agents as of the last poll
+    Snapshot,
+    SetLabel {
+        id: String,
+        label: Option<String>,
+    },
+    SetNote {
+        id: String,
+        note: Option<String>,
+    },
+    /// Replaces the settings the dashboard changes
+    Settings(Settings),
+    /// The token usage and cost report of the pane registry
+    UsageReport,
+    /// What the daemon monitors
+    Source,
+    /// Stops the daemon
+    Stop,
+}
+
+/// Dashboard settings the daemon acts on, kept while no dashboard runs
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Settings {
+    /// Nothing is answered automatically while set
+    pub automation_paused: bool,
+    /// Panes excluded from automatic answers, by server-qualified target
+    pub automation_disabled: Vec<String>,
+    /// No sounds are played while set
+    pub muted: bool,
+}
+
+/// The config a daemon was started with, as far as it decides which panes
+/// are monitored and how
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Source {
+    /// `None` when running on the defaults
+    pub config: Option<PathBuf>,
+    pub profile: Option<String>,
+    pub servers: Vec<TmuxServer>,
+}
+
+impl Source {
+    pub fn of(config: &Config) -> Self {
+        Self {
+            config: config.source.clone(),
+            profile: config.profile.clone(),
+            servers: config.servers(),
+        }
+    }
+
+    /// Describes the source for messages, e.g. `config.toml, profile work,
+    /// servers default, agents`
+    pub fn describe(&self) -> String {
+        let config = self
+            .config
+            .as_ref()
+            .map_or("defaults".to_string(), |path| path.display().to_string());
+        let profile = self
+            .profile
+            .as_ref()
+            .map(|profile| format!(", profile {}", profile))
+            .unwrap_or_default();
+        let servers: Vec<String> = self.servers.iter().map(TmuxServer::label).collect();
+        format!("{}{}, servers {}", config, profile, servers.join(", "))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Reply {
+    /// First reply to a subscription: the recent state transitions, oldest
+    /// first, and the settings
+    Welcome {
+        transitions: Vec<StateTransition>,
+        settings: Settings,
+    },
+    Update(Box<MonitorUpdate>),
+    Agents(AgentTree),
+    Lines(Vec<String>),
+    Source(Source),
+    Done,
+    /// The request couldn't be answered
+    Error(String),
+}
+
+/// TOML documents are tables, so messages are wrapped in one
+#[derive(Serialize, Deserialize)]
+struct Frame<T> {
+    message: T,
+}
+
+/// Encodes `message` as a frame: its length in bytes on a line, then the
+/// message as TOML
+pub fn encode<T: Serialize>(message: &T) -> Result<Vec<u8>> {
+    let body = toml::to_string(&Frame { message }).context("Failed to encode message")?;
+    let mut frame = format!("{}\n", body.len()).into_bytes();
+    frame.extend_from_slice(body.as_bytes());
+    Ok(frame)
+}
+
+pub fn write_message<T: Serialize>(writer: &mut impl Write, message: &T) -> Result<()> {
+    writer.write_all(&encode(message)?)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads the next message, `None` if the other side hung up
+pub fn read_message<T: DeserializeOwned>(reader: &mut impl BufRead) -> Result<Option<T>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let len: usize = line
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid frame length {:?}", line.trim()))?;
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8(body).context("Message isn't UTF-8")?;
+    let frame: Frame<T> = toml::from_str(&body).context("Failed to decode message")?;
+    Ok(Some(frame.message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::{AgentState, AgentStatus, AgentType, MonitoredAgent};
+    use crate::monitor::PaneChanged;
+
+    #[test]
+    fn test_message_roundtrip() {
+        let mut agent = MonitoredAgent::new(
+            "main:0.0-42".to_string(),
+            "main:0.0".to_string(),
+            "main".to_string(),
+            0,
+            "code".to_string(),
+            0,
+            "/code/app".to_string(),
+            AgentType::ClaudeCode,
+            42,
+        );
+        agent.status = AgentStatus::Processing {
+            activity: "Reading".to_string(),
+        };
+        agent.state = Some(AgentState::Working);
+        agent.last_content_styled = "\x1b[1m> \x1b[0m".to_string();
+        let update = MonitorUpdate {
+            agents: AgentTree {
+                root_agents: vec![agent],
+            },
+            changes: vec![PaneChanged {
+                target: "main:0.0".to_string(),
+                new_lines: vec!["> ".to_string(), String::new()],
+            }],
+            transitions: vec![StateTransition {
+                key: "main:0.0".to_string(),
+                from: None,
+                to: AgentState::Working,
+                at: chrono::Local::now(),
+            }],
+            approvals: Vec::new(),
+            context_warnings: Vec::new(),
+            responses: Vec::new(),
+            stalls: Vec::new(),
+        };
+
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &Reply::Update(Box::new(update.clone()))).unwrap();
+        let request = Request::SetLabel {
+            id: "main:0.0-42".to_string(),
+            label: None,
+        };
+        write_message(&mut buffer, &request).unwrap();
+        write_message(&mut buffer, &Reply::Error("No poll".to_string())).unwrap();
+
+        let mut reader = buffer.as_slice();
+        let Some(Reply::Update(decoded)) = read_message(&mut reader).unwrap() else {
+            panic!("not an update");
+        };
+        let agent = &decoded.agents.root_agents[0];
+        let sent = &update.agents.root_agents[0];
+        assert_eq!(agent.id, sent.id);
+        assert_eq!(agent.status, sent.status);
+        assert_eq!(agent.state, Some(AgentState::Working));
+        assert_eq!(agent.last_content_styled, sent.last_content_styled);
+        assert!(agent.started_at.elapsed() < std::time::Duration::from_secs(5));
+        assert_eq!(decoded.changes[0].new_lines, update.changes[0].new_lines);
+        assert_eq!(decoded.transitions, update.transitions);
+
+        assert_eq!(read_message::<Request>(&mut reader).unwrap(), Some(request));
+        assert!(matches!(
+            read_message(&mut reader).unwrap(),
+            Some(Reply::Error(message)) if message == "No poll"
+        ));
+        assert_eq!(read_message::<Request>(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn test_source() {
+        let mut config = Config {
+            sockets: vec!["default".to_string(), "agents".to_string()],
+            ..Config::default()
+        };
+        let source = Source::of(&config);
+        assert_eq!(source.describe(), "defaults, servers default, agents");
+
+        config.source = Some(PathBuf::from("/home/u/.config/tmuxcc/config.toml"));
+        config.profile = Some("work".to_string());
+        assert_ne!(Source::of(&config), source);
+        assert_eq!(
+            Source::of(&config).describe(),
+            "/home/u/.config/tmuxcc/config.toml, profile work, servers default, agents"
+        );
+    }
+}
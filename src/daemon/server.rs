@@ -0,0 +1,233 @@
+use std::collections::VecDeque;
+use std::fs::Permissions;
+use std::io::{BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use parking_lot::Mutex;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, warn};
+
+use super::protocol::{encode, read_message, write_message, Reply, Request, Settings, Source};
+use super::{create_socket_dir, is_running, socket_path};
+use crate::app::{AgentTree, Config, UiPrefs};
+use crate::monitor::{SharedRegistry, SharedSwitches, StateTransition};
+use crate::tmux::TmuxClient;
+use crate::ui::monitoring::{reload_config, ConfigWatch, Monitoring, CONFIG_CHECK_INTERVAL};
+
+/// State transitions a new dashboard gets for its history, as many as it keeps
+const HISTORY: usize = 200;
+
+/// How long a snapshot waits for the first poll of a daemon just started
+const FIRST_POLL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What the daemon knows beyond the monitor
+#[derive(Default)]
+struct Shared {
+    /// `None` until the first poll
+    agents: Option<AgentTree>,
+    transitions: VecDeque<StateTransition>,
+    settings: Settings,
+}
+
+/// What connections reach
+struct Daemon {
+    shared: Mutex<Shared>,
+    registry: SharedRegistry,
+    switches: SharedSwitches,
+    source: Source,
+    /// Encoded [`Reply::Update`] frames for subscribers
+    updates: broadcast::Sender<Arc<Vec<u8>>>,
+    stop: mpsc::UnboundedSender<()>,
+}
+
+/// Removes the socket when the daemon stops
+struct Socket(PathBuf);
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Runs the monitor `config` describes and serves it on the daemon socket
+/// until interrupted, terminated or asked to stop
+pub async fn run_daemon(mut config: Config) -> Result<()> {
+    let path = socket_path();
+    if is_running() {
+        bail!("tmuxcc daemon is already running ({})", path.display());
+    }
+    let tmux_client = Arc::new(TmuxClient::with_capture_lines(config.capture_lines));
+    let (tx, mut rx) = mpsc::channel(32);
+    let mut monitoring = Monitoring::start(&config, tmux_client.clone(), tx)?;
+    if let Some(error) = &monitoring.hook_error {
+        warn!("{}", error);
+        eprintln!("{}", error);
+    }
+
+    if let Some(dir) = path.parent() {
+        create_socket_dir(dir)?;
+    }
+    // Left behind by a daemon that didn't stop cleanly
+    let _ = std::fs::remove_file(&path);
+    let listener =
+        UnixListener::bind(&path).with_context(|| format!("Failed to bind {}", path.display()))?;
+    let _socket = Socket(path.clone());
+    std::fs::set_permissions(&path, Permissions::from_mode(0o600))?;
+
+    let (stop, mut stop_rx) = mpsc::unbounded_channel();
+    let settings = Settings {
+        muted: UiPrefs::load(UiPrefs::default_path().as_deref()).muted,
+        ..Settings::default()
+    };
+    let daemon = Arc::new(Daemon {
+        shared: Mutex::new(Shared {
+            settings,
+            ..Shared::default()
+        }),
+        registry: monitoring.registry.clone(),
+        switches: monitoring.switches.clone(),
+        source: Source::of(&config),
+        updates: broadcast::channel(16).0,
+        stop,
+    });
+    let accepting = daemon.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let daemon = accepting.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = serve(stream, &daemon) {
+                    debug!("Daemon connection failed: {:#}", e);
+                }
+            });
+        }
+    });
+    eprintln!("tmuxcc daemon: {}", path.display());
+
+    let mut watch = ConfigWatch::new(&config);
+    let mut checks = tokio::time::interval(CONFIG_CHECK_INTERVAL);
+    let mut terminate = signal(SignalKind::terminate())?;
+    loop {
+        tokio::select! {
+            update = rx.recv() => {
+                let Some(update) = update else { break };
+                let muted = daemon.shared.lock().settings.muted;
+                monitoring.alert(&update, &config, &tmux_client, muted);
+                {
+                    let mut shared = daemon.shared.lock();
+                    shared.agents = Some(update.agents.clone());
+                    shared.transitions.extend(update.transitions.iter().cloned());
+                    let excess = shared.transitions.len().saturating_sub(HISTORY);
+                    shared.transitions.drain(..excess);
+                }
+                if daemon.updates.receiver_count() > 0 {
+                    match encode(&Reply::Update(Box::new(update))) {
+                        Ok(frame) => {
+                            let _ = daemon.updates.send(Arc::new(frame));
+                        }
+                        Err(e) => warn!("{:#}", e),
+                    }
+                }
+            }
+            _ = checks.tick() => {
+                let Some(path) = watch.changed() else { continue };
+                match reload_config(&path, &config) {
+                    Ok((new_config, reload)) => {
+                        config = new_config;
+                        monitoring.reload(reload);
+                        eprintln!("Reloaded {}", path.display());
+                    }
+                    Err(e) => eprintln!("Config not reloaded: {:#}", e),
+                }
+            }
+            _ = stop_rx.recv() => break,
+            _ = tokio::signal::ctrl_c() => break,
+            _ = terminate.recv() => break,
+        }
+    }
+    daemon.registry.lock().save()?;
+    Ok(())
+}
+
+/// Answers the requests of one client
+fn serve(stream: UnixStream, daemon: &Daemon) -> Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    while let Some(request) = read_message(&mut reader)? {
+        let reply = match request {
+            Request::Subscribe => return subscribe(writer, daemon),
+            Request::Snapshot => match agents(daemon) {
+                Some(agents) => Reply::Agents(agents),
+                None => Reply::Error(format!(
+                    "No poll finished within {}s",
+                    FIRST_POLL_TIMEOUT.as_secs()
+                )),
+            },
+            Request::SetLabel { id, label } => {
+                daemon.registry.lock().set_label(&id, label);
+                Reply::Done
+            }
+            Request::SetNote { id, note } => {
+                daemon.registry.lock().set_note(&id, note);
+                Reply::Done
+            }
+            Request::Settings(settings) => {
+                let mut switches = daemon.switches.lock();
+                switches.paused = settings.automation_paused;
+                switches.disabled = settings.automation_disabled.iter().cloned().collect();
+                daemon.shared.lock().settings = settings;
+                Reply::Done
+            }
+            Request::UsageReport => Reply::Lines(daemon.registry.lock().usage_report()),
+            Request::Source => Reply::Source(daemon.source.clone()),
+            Request::Stop => {
+                let _ = daemon.stop.send(());
+                Reply::Done
+            }
+        };
+        write_message(&mut writer, &reply)?;
+    }
+    Ok(())
+}
+
+/// Returns the agents of the last poll, waiting up to [`FIRST_POLL_TIMEOUT`]
+/// for the first one
+fn agents(daemon: &Daemon) -> Option<AgentTree> {
+    let started = Instant::now();
+    loop {
+        if let Some(agents) = &daemon.shared.lock().agents {
+            return Some(agents.clone());
+        }
+        if started.elapsed() >= FIRST_POLL_TIMEOUT {
+            return None;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// Sends the welcome and then the updates until the client hangs up
+fn subscribe(mut writer: UnixStream, daemon: &Daemon) -> Result<()> {
+    // Before the welcome, so no update falls in between
+    let mut updates = daemon.updates.subscribe();
+    let welcome = {
+        let shared = daemon.shared.lock();
+        Reply::Welcome {
+            transitions: shared.transitions.iter().cloned().collect(),
+            settings: shared.settings.clone(),
+        }
+    };
+    write_message(&mut writer, &welcome)?;
+    loop {
+        match updates.blocking_recv() {
+            Ok(frame) => writer.write_all(&frame)?,
+            // A slow client misses updates rather than holding up the others
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => return Ok(()),
+        }
+    }
+}
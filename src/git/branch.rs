@@ -0,0 +1,91 @@
+use std::process::Command;
+
+use anyhow::{Context, Result};
+
+/// Parses `git for-each-ref --format=%(HEAD)%(refname:short)` output
+///
+/// Returns the branch names and the index of the checked out one.
+pub fn parse_branch_list(output: &str) -> (Vec<String>, Option<usize>) {
+    let mut branches = Vec::new();
+    let mut current = None;
+    for line in output.lines() {
+        let (is_head, name) = match line.strip_prefix('*') {
+            Some(name) => (true, name),
+            None => (false, line.strip_prefix(' ').unwrap_or(line)),
+        };
+        let name = name.trim();
+        if name.is_empty() {
+            continue;
+        }
+        if is_head {
+            current = Some(branches.len());
+        }
+        branches.push(name.to_string());
+    }
+    (branches, current)
+}
+
+/// Lists local branches of the repository containing `path`
+///
+/// Returns the branch names and the index of the checked out one.
+pub fn list_branches(path: &str) -> Result<(Vec<String>, Option<usize>)> {
+    let output = Command::new("git")
+        .args([
+            "-C",
+            path,
+            "for-each-ref",
+            "--format=%(HEAD)%(refname:short)",
+            "refs/heads",
+        ])
+        .output()
+        .context("Failed to execute git for-each-ref")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git for-each-ref failed: {}", stderr.trim());
+    }
+
+    Ok(parse_branch_list(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Checks out `branch` in the work tree containing `path`
+///
+/// Refuses when the work tree has uncommitted changes unless `force` is set.
+/// Forcing only skips that check: local changes are carried over to the
+/// branch, and git still refuses if they would be overwritten.
+pub fn checkout_branch(path: &str, branch: &str, force: bool) -> Result<()> {
+    if !force {
+        let status = super::get_git_status(path).context("Not inside a git repository")?;
+        if status.is_dirty() {
+            anyhow::bail!("Working tree has uncommitted changes (press F to force)");
+        }
+    }
+
+    let output = Command::new("git")
+        .args(["-C", path, "checkout", branch])
+        .output()
+        .context("Failed to execute git checkout")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git checkout failed: {}", stderr.trim());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_branch_list() {
+        let (branches, current) = parse_branch_list(" feat/x\n*main\n topic\n");
+        assert_eq!(branches, vec!["feat/x", "main", "topic"]);
+        assert_eq!(current, Some(1));
+
+        let (branches, current) = parse_branch_list("");
+        assert!(branches.is_empty());
+        assert_eq!(current, None);
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// How long PR information is reused before `gh` is queried again
+const PR_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// jq program turning `gh pr view --json` output into one tab-separated line:
+/// number, state, review decision, combined CI state
+const PR_JQ: &str = r#"[.number, .state, (.reviewDecision // ""), ([.statusCheckRollup[]? | (.conclusion // .state // "")] | if length == 0 then "NONE" elif any(. == "FAILURE" or . == "ERROR" or . == "CANCELLED" or . == "TIMED_OUT" or . == "ACTION_REQUIRED" or . == "STARTUP_FAILURE") then "FAILURE" elif any(. == "" or . == "PENDING" or . == "EXPECTED" or . == "IN_PROGRESS" or . == "QUEUED") then "PENDING" else "SUCCESS" end)] | @tsv"#;
+
+/// Review decision of a pull request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReviewState {
+    Approved,
+    ChangesRequested,
+    ReviewRequired,
+    None,
+}
+
+impl ReviewState {
+    fn parse(s: &str) -> Self {
+        match s {
+            "APPROVED" => ReviewState::Approved,
+            "CHANGES_REQUESTED" => ReviewState::ChangesRequested,
+            "REVIEW_REQUIRED" => ReviewState::ReviewRequired,
+            _ => ReviewState::None,
+        }
+    }
+
+    /// Returns a short label, empty when no review is required
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReviewState::Approved => "approved",
+            ReviewState::ChangesRequested => "changes requested",
+            ReviewState::ReviewRequired => "review required",
+            ReviewState::None => "",
+        }
+    }
+}
+
+/// Combined state of the checks on a pull request's head commit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CiState {
+    Success,
+    Failure,
+    Pending,
+    None,
+}
+
+impl CiState {
+    fn parse(s: &str) -> Self {
+        match s {
+            "SUCCESS" => CiState::Success,
+            "FAILURE" => CiState::Failure,
+            "PENDING" => CiState::Pending,
+            _ => CiState::None,
+        }
+    }
+
+    /// Returns the status icon shown next to the PR number
+    pub fn icon(&self) -> &'static str {
+        match self {
+            CiState::Success => "✓",
+            CiState::Failure => "✗",
+            CiState::Pending => "●",
+            CiState::None => "",
+        }
+    }
+}
+
+/// Pull request opened from a branch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PullRequest {
+    pub number: u64,
+    /// "OPEN", "MERGED" or "CLOSED"
+    pub state: String,
+    pub review: ReviewState,
+    pub ci: CiState,
+}
+
+impl PullRequest {
+    /// Parses the tab-separated line produced by [`PR_JQ`]
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut parts = line.trim_end_matches(['\r', '\n']).split('\t');
+        let number = parts.next()?.parse().ok()?;
+        let state = parts.next()?.to_string();
+        let review = ReviewState::parse(parts.next().unwrap_or(""));
+        let ci = CiState::parse(parts.next().unwrap_or(""));
+        Some(Self {
+            number,
+            state,
+            review,
+            ci,
+        })
+    }
+
+    /// Returns true if the pull request is still open
+    pub fn is_open(&self) -> bool {
+        self.state == "OPEN"
+    }
+
+    /// Returns a compact label like "#42✓"
+    pub fn label(&self) -> String {
+        format!("#{}{}", self.number, self.ci.icon())
+    }
+}
+
+/// Looks up the pull request for `branch` with the GitHub CLI, run from `dir`
+///
+/// Returns None if `gh` is missing, not authenticated, or no PR exists.
+pub fn fetch_pull_request(dir: &str, branch: &str) -> Option<PullRequest> {
+    let output = Command::new("gh")
+        .current_dir(dir)
+        .args([
+            "pr",
+            "view",
+            branch,
+            "--json",
+            "number,state,reviewDecision,statusCheckRollup",
+            "--jq",
+            PR_JQ,
+        ])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    PullRequest::parse(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Builds a pull request title and body from commit subjects (oldest first)
+///
+/// The title is the first commit's subject; the body lists every commit.
+pub fn pr_template(subjects: &[String]) -> (String, String) {
+    let title = subjects.first().cloned().unwrap_or_default();
+    let body = subjects
+        .iter()
+        .map(|s| format!("- {}", s))
+        .collect::<Vec<_>>()
+        .join("\n");
+    (title, body)
+}
+
+/// Runs git in `dir` and returns stdout, failing with stderr
+fn git_output(dir: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("Failed to execute git")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git {} failed: {}", args[0], stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Opens a pull request for `branch` against `base` with the GitHub CLI
+///
+/// Title and body are templated from the commits on `branch` that are not on
+/// `origin/<base>`. The branch is pushed to `origin` first, since `gh` can only
+/// open PRs for branches that exist on the remote. Returns the PR URL.
+pub fn create_pull_request(dir: &str, branch: &str, base: &str) -> Result<String> {
+    let range = format!("origin/{}..{}", base, branch);
+    let log = git_output(dir, &["log", "--reverse", "--format=%s", &range])?;
+    let subjects: Vec<String> = log.lines().map(|l| l.to_string()).collect();
+    if subjects.is_empty() {
+        anyhow::bail!("No commits on {} ahead of origin/{}", branch, base);
+    }
+    let (title, body) = pr_template(&subjects);
+
+    git_output(dir, &["push", "--set-upstream", "origin", branch])?;
+
+    let output = Command::new("gh")
+        .current_dir(dir)
+        .args([
+            "pr", "create", "--base", base, "--head", branch, "--title", &title, "--body", &body,
+        ])
+        .output()
+        .context("Failed to execute gh (is the GitHub CLI installed?)")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("gh pr create failed: {}", stderr.trim());
+    }
+
+    // gh prints the new PR's URL as the last line
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .rev()
+        .find(|l| l.starts_with("http"))
+        .map(|l| l.trim().to_string())
+        .context("gh pr create did not print a URL")
+}
+
+struct PrEntry {
+    fetched_at: Option<Instant>,
+    pr: Option<PullRequest>,
+    in_flight: bool,
+}
+
+/// Pull request lookups cached per repository and branch
+///
+/// `gh` talks to the network, so lookups run on background threads and
+/// [`PrCache::get`] only ever returns what is already cached.
+#[derive(Clone, Default)]
+pub struct PrCache {
+    entries: Arc<Mutex<HashMap<(String, String), PrEntry>>>,
+}
+
+impl PrCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached PR for `branch` of `repo` (an "owner/repo" slug),
+    /// starting a refresh from `dir` when the entry is missing or stale
+    pub fn get(&self, repo: &str, branch: &str, dir: &str) -> Option<PullRequest> {
+        let key = (repo.to_string(), branch.to_string());
+        let mut entries = self.entries.lock();
+        let entry = entries.entry(key.clone()).or_insert(PrEntry {
+            fetched_at: None,
+            pr: None,
+            in_flight: false,
+        });
+
+        let stale = match entry.fetched_at {
+            Some(fetched) => fetched.elapsed() >= PR_CACHE_TTL,
+            None => true,
+        };
+        if stale && !entry.in_flight {
+            entry.in_flight = true;
+            let entries = Arc::clone(&self.entries);
+            let dir = dir.to_string();
+            thread::spawn(move || {
+                let pr = fetch_pull_request(&dir, &key.1);
+                if let Some(entry) = entries.lock().get_mut(&key) {
+                    entry.pr = pr;
+                    entry.fetched_at = Some(Instant::now());
+                    entry.in_flight = false;
+                }
+            });
+        }
+
+        entry.pr.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pull_request() {
+        let pr = PullRequest::parse("42\tOPEN\tAPPROVED\tSUCCESS\n").unwrap();
+        assert_eq!(pr.number, 42);
+        assert!(pr.is_open());
+        assert_eq!(pr.review, ReviewState::Approved);
+        assert_eq!(pr.ci, CiState::Success);
+        assert_eq!(pr.label(), "#42✓");
+
+        let pr = PullRequest::parse("7\tMERGED\t\tNONE").unwrap();
+        assert!(!pr.is_open());
+        assert_eq!(pr.review, ReviewState::None);
+        assert_eq!(pr.label(), "#7");
+
+        assert!(PullRequest::parse("").is_none());
+    }
+
+    #[test]
+    fn test_pr_template() {
+        let subjects = vec!["Add parser".to_string(), "Fix tests".to_string()];
+        let (title, body) = pr_template(&subjects);
+        assert_eq!(title, "Add parser");
+        assert_eq!(body, "- Add parser\n- Fix tests");
+    }
+}
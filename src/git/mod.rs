@@ -0,0 +1,1345 @@
+mod branch;
+mod github;
+mod worktree;
+
+pub use branch::{checkout_branch, list_branches, parse_branch_list};
+pub use github::{
+    create_pull_request, fetch_pull_request, pr_template, CiState, PrCache, PullRequest,
+    ReviewState,
+};
+pub use worktree::{create_worktree, default_worktree_path, list_worktrees, Worktree};
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Repository location overrides taken from a process environment
+///
+/// Mirrors how git itself treats `GIT_DIR` and `GIT_WORK_TREE`: relative
+/// values are resolved against the process's working directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitEnv {
+    /// Value of `GIT_DIR`
+    pub git_dir: Option<PathBuf>,
+    /// Value of `GIT_WORK_TREE`
+    pub work_tree: Option<PathBuf>,
+}
+
+impl GitEnv {
+    /// Environment variables that affect repository discovery
+    pub const VARS: &'static [&'static str] = &["GIT_DIR", "GIT_WORK_TREE"];
+
+    /// Builds overrides from `(name, value)` pairs, ignoring unrelated and empty variables
+    pub fn from_vars<I: IntoIterator<Item = (String, String)>>(vars: I) -> Self {
+        let mut env = Self::default();
+        for (name, value) in vars {
+            if value.is_empty() {
+                continue;
+            }
+            match name.as_str() {
+                "GIT_DIR" => env.git_dir = Some(PathBuf::from(value)),
+                "GIT_WORK_TREE" => env.work_tree = Some(PathBuf::from(value)),
+                _ => {}
+            }
+        }
+        env
+    }
+
+    /// Returns true if neither variable is set
+    pub fn is_empty(&self) -> bool {
+        self.git_dir.is_none() && self.work_tree.is_none()
+    }
+}
+
+/// A discovered repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Repo {
+    /// Work tree root, or the git directory itself for bare repositories
+    root: PathBuf,
+    /// Git directory (per-worktree for linked worktrees)
+    git_dir: PathBuf,
+    /// Whether the repository has no work tree
+    bare: bool,
+    /// Whether the location came from `GIT_DIR`/`GIT_WORK_TREE` and has to be passed on to git
+    explicit: bool,
+}
+
+impl Repo {
+    /// Returns a `git` command running in this repository
+    ///
+    /// Our own `GIT_DIR`/`GIT_WORK_TREE` are never inherited, since they would
+    /// point every pane at tmuxcc's repository.
+    fn git(&self) -> Command {
+        let mut cmd = Command::new("git");
+        cmd.arg("-C").arg(&self.root);
+        cmd.env_remove("GIT_DIR").env_remove("GIT_WORK_TREE");
+        if self.explicit {
+            cmd.env("GIT_DIR", &self.git_dir);
+            if !self.bare {
+                cmd.env("GIT_WORK_TREE", &self.root);
+            }
+        }
+        cmd
+    }
+}
+
+/// Finds the repository for a path by walking up the tree
+///
+/// Handles both regular repositories (`.git` directory) and worktrees/submodules
+/// (`.git` file containing `gitdir: <path>`, which may be relative to the file),
+/// as well as paths inside a bare repository.
+fn discover(path: &Path) -> Option<Repo> {
+    let mut current = Some(path);
+    while let Some(dir) = current {
+        let dot_git = dir.join(".git");
+        if dot_git.is_dir() {
+            return Some(Repo {
+                root: dir.to_path_buf(),
+                git_dir: dot_git,
+                bare: false,
+                explicit: false,
+            });
+        }
+        if dot_git.is_file() {
+            let content = std::fs::read_to_string(&dot_git).ok()?;
+            let gitdir = content.trim().strip_prefix("gitdir:")?.trim();
+            return Some(Repo {
+                root: dir.to_path_buf(),
+                git_dir: dir.join(gitdir),
+                bare: false,
+                explicit: false,
+            });
+        }
+        if is_git_dir(dir) && is_bare(dir) {
+            return Some(Repo {
+                root: dir.to_path_buf(),
+                git_dir: dir.to_path_buf(),
+                bare: true,
+                explicit: false,
+            });
+        }
+        current = dir.parent();
+    }
+    None
+}
+
+/// Finds the repository for a path, honoring `GIT_DIR`/`GIT_WORK_TREE` overrides
+fn discover_with_env(path: &Path, env: &GitEnv) -> Option<Repo> {
+    if env.is_empty() {
+        return discover(path);
+    }
+
+    let git_dir = match &env.git_dir {
+        Some(git_dir) => path.join(git_dir),
+        // GIT_WORK_TREE alone only moves the work tree; the git dir is discovered as usual
+        None => discover(path)?.git_dir,
+    };
+    if !is_git_dir(&git_dir) {
+        return None;
+    }
+
+    let (root, bare) = match &env.work_tree {
+        Some(work_tree) => (path.join(work_tree), false),
+        None if is_bare(&git_dir) => (git_dir.clone(), true),
+        // Without GIT_WORK_TREE git uses the current directory as the work tree
+        None => (path.to_path_buf(), false),
+    };
+    Some(Repo {
+        root,
+        git_dir,
+        bare,
+        explicit: true,
+    })
+}
+
+/// Returns true if `dir` looks like a git directory (`HEAD`, `objects/` and `refs/`)
+fn is_git_dir(dir: &Path) -> bool {
+    dir.join("HEAD").is_file() && dir.join("objects").is_dir() && dir.join("refs").is_dir()
+}
+
+/// Returns true if the repository config sets `core.bare = true`
+fn is_bare(git_dir: &Path) -> bool {
+    std::fs::read_to_string(common_dir(git_dir).join("config"))
+        .map(|config| parse_is_bare(&config))
+        .unwrap_or(false)
+}
+
+/// Reads `core.bare` from git config text
+fn parse_is_bare(config: &str) -> bool {
+    let mut in_core = false;
+    for line in config.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_core = line.eq_ignore_ascii_case("[core]");
+            continue;
+        }
+        if !in_core {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            if key.trim().eq_ignore_ascii_case("bare") {
+                return value.trim().eq_ignore_ascii_case("true");
+            }
+        }
+    }
+    false
+}
+
+/// Finds the git directory for a path
+fn find_git_dir(path: &Path) -> Option<PathBuf> {
+    discover(path).map(|repo| repo.git_dir)
+}
+
+/// Returns the root of the work tree containing `path`
+///
+/// For a bare repository this is the git directory itself.
+pub fn get_repo_root(path: &str) -> Option<PathBuf> {
+    if path.is_empty() {
+        return None;
+    }
+    discover(Path::new(path)).map(|repo| repo.root)
+}
+
+/// Resolves the common directory shared by all worktrees of a repository
+///
+/// Linked worktrees keep HEAD and index in their own git directory, but refs,
+/// packed-refs, stashes and config live in the directory named by `commondir`.
+fn common_dir(git_dir: &Path) -> PathBuf {
+    std::fs::read_to_string(git_dir.join("commondir"))
+        .ok()
+        .map(|content| git_dir.join(content.trim()))
+        .unwrap_or_else(|| git_dir.to_path_buf())
+}
+
+/// What HEAD points at
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Head {
+    /// Symbolic ref to a branch (name without `refs/heads/`)
+    Branch(String),
+    /// Detached at a commit (full hash)
+    Detached(String),
+}
+
+/// Returns the current branch name for the repository containing `path`
+///
+/// A detached HEAD is reported as the abbreviated commit hash.
+pub fn get_git_branch(path: &str) -> Option<String> {
+    if path.is_empty() {
+        return None;
+    }
+    match read_head(&find_git_dir(Path::new(path))?)? {
+        Head::Branch(name) => Some(name),
+        Head::Detached(hash) => Some(short_hash(&hash)),
+    }
+}
+
+/// Reads HEAD from a git directory
+fn read_head(git_dir: &Path) -> Option<Head> {
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+
+    if let Some(reference) = head.strip_prefix("ref:") {
+        let reference = reference.trim();
+        let branch = reference.strip_prefix("refs/heads/").unwrap_or(reference);
+        Some(Head::Branch(branch.to_string()))
+    } else if head.len() >= 7 {
+        Some(Head::Detached(head.to_string()))
+    } else {
+        None
+    }
+}
+
+fn short_hash(hash: &str) -> String {
+    hash.chars().take(7).collect()
+}
+
+/// Finds a tag pointing exactly at `commit` in loose refs or packed-refs
+///
+/// Annotated tags are matched through the peeled (`^`) lines of packed-refs;
+/// loose annotated tags point at tag objects and are left to `git describe`.
+fn find_exact_tag(common_dir: &Path, commit: &str) -> Option<String> {
+    if let Ok(packed) = std::fs::read_to_string(common_dir.join("packed-refs")) {
+        let mut last_tag: Option<&str> = None;
+        for line in packed.lines() {
+            if line.starts_with('#') {
+                continue;
+            }
+            if let Some(peeled) = line.strip_prefix('^') {
+                if peeled.trim() == commit {
+                    if let Some(tag) = last_tag {
+                        return Some(tag.to_string());
+                    }
+                }
+                continue;
+            }
+            last_tag = None;
+            if let Some((hash, name)) = line.split_once(' ') {
+                if let Some(tag) = name.trim().strip_prefix("refs/tags/") {
+                    if hash == commit {
+                        return Some(tag.to_string());
+                    }
+                    last_tag = Some(tag);
+                }
+            }
+        }
+    }
+
+    find_loose_tag(&common_dir.join("refs/tags"), "", commit)
+}
+
+fn find_loose_tag(dir: &Path, prefix: &str, commit: &str) -> Option<String> {
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+        let path = entry.path();
+        if path.is_dir() {
+            if let Some(tag) = find_loose_tag(&path, &format!("{}/", name), commit) {
+                return Some(tag);
+            }
+        } else if std::fs::read_to_string(&path).is_ok_and(|hash| hash.trim() == commit) {
+            return Some(name);
+        }
+    }
+    None
+}
+
+/// Converts `git describe --tags --long` output ("v1.2.3-2-gabc1234") to "v1.2.3~2"
+fn parse_describe(output: &str) -> Option<String> {
+    let mut parts = output.trim().rsplitn(3, '-');
+    let hash = parts.next()?;
+    let distance: u32 = parts.next()?.parse().ok()?;
+    let tag = parts.next()?;
+    if !hash.starts_with('g') || tag.is_empty() {
+        return None;
+    }
+    if distance == 0 {
+        Some(tag.to_string())
+    } else {
+        Some(format!("{}~{}", tag, distance))
+    }
+}
+
+/// Describes a detached commit relative to the nearest tag
+fn describe_commit(repo: &Repo, common_dir: &Path, commit: &str) -> Option<String> {
+    if let Some(tag) = find_exact_tag(common_dir, commit) {
+        return Some(tag);
+    }
+
+    let output = repo
+        .git()
+        .args(["describe", "--tags", "--long", commit])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_describe(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Returns the number of stash entries for the repository containing `path`
+pub fn get_stash_count(path: &str) -> usize {
+    if path.is_empty() {
+        return 0;
+    }
+    find_git_dir(Path::new(path))
+        .map(|git_dir| read_stash_count(&common_dir(&git_dir)))
+        .unwrap_or(0)
+}
+
+/// Counts stash entries from the stash reflog (one line per entry)
+fn read_stash_count(common_dir: &Path) -> usize {
+    match std::fs::read_to_string(common_dir.join("logs/refs/stash")) {
+        Ok(log) => log.lines().filter(|l| !l.trim().is_empty()).count(),
+        // refs/stash without a reflog still means at least one stash
+        Err(_) if common_dir.join("refs/stash").is_file() => 1,
+        Err(_) => 0,
+    }
+}
+
+/// Working tree status of a repository
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitStatus {
+    /// Number of files with changes staged in the index
+    pub staged: usize,
+    /// Number of files with unstaged changes in the working tree
+    pub modified: usize,
+    /// Number of untracked files
+    pub untracked: usize,
+    /// Number of files with unresolved merge conflicts
+    pub conflicted: usize,
+}
+
+impl GitStatus {
+    /// Parses the output of `git status --porcelain=v2`
+    pub fn parse_porcelain(output: &str) -> Self {
+        let mut status = Self::default();
+
+        for line in output.lines() {
+            // Changed entries: "1 XY ...", renamed/copied: "2 XY ...",
+            // unmerged: "u XY ...", untracked: "? path"
+            let mut fields = line.splitn(3, ' ');
+            let kind = fields.next().unwrap_or("");
+            match kind {
+                "?" => {
+                    status.untracked += 1;
+                    continue;
+                }
+                "u" => {
+                    status.conflicted += 1;
+                    continue;
+                }
+                "1" | "2" => {}
+                _ => continue,
+            }
+            let xy: Vec<char> = fields.next().unwrap_or("").chars().collect();
+            if xy.len() != 2 {
+                continue;
+            }
+            // '.' means unmodified on that side
+            if xy[0] != '.' {
+                status.staged += 1;
+            }
+            if xy[1] != '.' {
+                status.modified += 1;
+            }
+        }
+
+        status
+    }
+
+    /// Returns true if there are staged, unstaged or conflicted changes
+    pub fn is_dirty(&self) -> bool {
+        self.staged > 0 || self.modified > 0 || self.conflicted > 0
+    }
+
+    /// Returns a compact indicator like git's prompt: `*` unstaged, `+` staged
+    pub fn indicator(&self) -> String {
+        let mut s = String::new();
+        if self.modified > 0 {
+            s.push('*');
+        }
+        if self.staged > 0 {
+            s.push('+');
+        }
+        s
+    }
+
+    /// Returns file counters for badges like `~3 ?2 !1`
+    /// (modified, untracked, conflicted), omitting zero counts
+    pub fn badges(&self) -> Vec<(char, usize)> {
+        [
+            ('~', self.modified),
+            ('?', self.untracked),
+            ('!', self.conflicted),
+        ]
+        .into_iter()
+        .filter(|(_, n)| *n > 0)
+        .collect()
+    }
+}
+
+/// Runs `git status --porcelain=v2 --branch` in a repository
+fn run_status(repo: &Repo) -> Option<String> {
+    if repo.bare {
+        return None;
+    }
+
+    // Don't take optional locks: agents may be running git in the same repo
+    let output = repo
+        .git()
+        .args([
+            "--no-optional-locks",
+            "status",
+            "--porcelain=v2",
+            "--branch",
+        ])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Returns the working tree status for the repository containing `path`
+pub fn get_git_status(path: &str) -> Option<GitStatus> {
+    if path.is_empty() {
+        return None;
+    }
+    run_status(&discover(Path::new(path))?).map(|output| GitStatus::parse_porcelain(&output))
+}
+
+/// Branch and upstream tracking information for a repository
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitInfo {
+    /// Current branch, or when detached the nearest tag ("v1.2.3~2") or abbreviated hash
+    pub branch: String,
+    /// Whether HEAD is detached
+    pub detached: bool,
+    /// Tracked upstream branch (e.g., "origin/main"), if any
+    pub upstream: Option<String>,
+    /// Whether the tracked upstream branch no longer exists (e.g., deleted after merge)
+    pub upstream_gone: bool,
+    /// Commits on the local branch not on the upstream
+    pub ahead: u32,
+    /// Commits on the upstream not on the local branch
+    pub behind: u32,
+    /// Working tree status
+    pub status: GitStatus,
+    /// Number of stash entries
+    pub stash_count: usize,
+    /// Most recent commit on HEAD
+    pub last_commit: Option<CommitInfo>,
+    /// URL of the `origin` remote (or the first remote configured)
+    pub remote_url: Option<String>,
+    /// "owner/repo" derived from the remote URL
+    pub repo_slug: Option<String>,
+    /// Multi-step operation left in progress, if any
+    pub operation: Option<RepoOperation>,
+    /// Work trees of the repository (main and linked)
+    pub worktrees: Vec<Worktree>,
+    /// Whether the repository is bare (no work tree, so no status)
+    pub bare: bool,
+    /// Work tree root (the git directory itself for bare repositories)
+    pub root: PathBuf,
+    /// Default branch of `origin` (from `refs/remotes/origin/HEAD`), if known
+    pub default_branch: Option<String>,
+    /// GitHub pull request for the branch, filled in by the monitor
+    pub pull_request: Option<PullRequest>,
+}
+
+impl GitInfo {
+    /// Parses the output of `git status --porcelain=v2 --branch`
+    pub fn parse_porcelain(branch: String, output: &str) -> Self {
+        let mut info = Self {
+            branch,
+            status: GitStatus::parse_porcelain(output),
+            ..Self::default()
+        };
+
+        let mut has_ab = false;
+        for line in output.lines() {
+            if let Some(upstream) = line.strip_prefix("# branch.upstream ") {
+                info.upstream = Some(upstream.trim().to_string());
+            } else if let Some(ab) = line.strip_prefix("# branch.ab ") {
+                has_ab = true;
+                // Format: "+<ahead> -<behind>"
+                for part in ab.split_whitespace() {
+                    if let Some(n) = part.strip_prefix('+') {
+                        info.ahead = n.parse().unwrap_or(0);
+                    } else if let Some(n) = part.strip_prefix('-') {
+                        info.behind = n.parse().unwrap_or(0);
+                    }
+                }
+            }
+        }
+        // git omits branch.ab when the configured upstream ref is missing
+        info.upstream_gone = info.upstream.is_some() && !has_ab;
+
+        info
+    }
+
+    /// Returns true if a pull request can be opened from the current branch:
+    /// a named GitHub branch other than the default one, without an open PR
+    pub fn can_open_pr(&self) -> bool {
+        let default = self.default_branch.as_deref().unwrap_or("main");
+        !self.detached
+            && !self.bare
+            && self.repo_slug.is_some()
+            && self.branch != default
+            && self.branch != "master"
+            && !self.pull_request.as_ref().is_some_and(|pr| pr.is_open())
+    }
+
+    /// Returns true if the branch has both local and upstream-only commits
+    pub fn is_diverged(&self) -> bool {
+        self.ahead > 0 && self.behind > 0
+    }
+
+    /// Returns a compact label like "main*+ ↑2↓1"
+    pub fn label(&self) -> String {
+        let mut label = format!("{}{}", self.branch, self.status.indicator());
+        if self.ahead > 0 || self.behind > 0 {
+            label.push(' ');
+        }
+        if self.ahead > 0 {
+            label.push_str(&format!("↑{}", self.ahead));
+        }
+        if self.behind > 0 {
+            label.push_str(&format!("↓{}", self.behind));
+        }
+        label
+    }
+}
+
+/// Returns branch, upstream and working tree information for `path`
+pub fn get_git_info(path: &str) -> Option<GitInfo> {
+    if path.is_empty() {
+        return None;
+    }
+    read_info(&discover(Path::new(path))?)
+}
+
+/// Returns git information for `path` as seen by a process with the given environment
+pub fn get_git_info_with_env(path: &str, env: &GitEnv) -> Option<GitInfo> {
+    if path.is_empty() {
+        return None;
+    }
+    read_info(&discover_with_env(Path::new(path), env)?)
+}
+
+/// Reads repository information for a discovered repository
+fn read_info(repo: &Repo) -> Option<GitInfo> {
+    let git_dir = &repo.git_dir;
+    let common = common_dir(git_dir);
+    let (branch, detached) = match read_head(git_dir)? {
+        Head::Branch(name) => (name, false),
+        Head::Detached(hash) => (
+            describe_commit(repo, &common, &hash).unwrap_or_else(|| short_hash(&hash)),
+            true,
+        ),
+    };
+    let output = run_status(repo).unwrap_or_default();
+    let mut info = GitInfo::parse_porcelain(branch, &output);
+    info.detached = detached;
+    info.bare = repo.bare;
+    info.root = repo.root.clone();
+    info.stash_count = read_stash_count(&common);
+    info.last_commit = last_commit(repo);
+    info.remote_url = read_remote_url(&common);
+    info.default_branch = read_default_branch(&common);
+    info.repo_slug = info.remote_url.as_deref().and_then(parse_repo_slug);
+    info.operation = RepoOperation::detect(git_dir);
+    info.worktrees = worktree::run_worktree_list(repo.git()).unwrap_or_default();
+    Some(info)
+}
+
+/// A multi-step git operation in progress in a work tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RepoOperation {
+    Merge,
+    Rebase,
+    CherryPick,
+    Revert,
+}
+
+impl RepoOperation {
+    /// Detects an in-progress operation from the state files in a git directory
+    pub fn detect(git_dir: &Path) -> Option<Self> {
+        if git_dir.join("rebase-merge").is_dir() || git_dir.join("rebase-apply").is_dir() {
+            Some(RepoOperation::Rebase)
+        } else if git_dir.join("MERGE_HEAD").is_file() {
+            Some(RepoOperation::Merge)
+        } else if git_dir.join("CHERRY_PICK_HEAD").is_file() {
+            Some(RepoOperation::CherryPick)
+        } else if git_dir.join("REVERT_HEAD").is_file() {
+            Some(RepoOperation::Revert)
+        } else {
+            None
+        }
+    }
+
+    /// Returns an uppercase label like git's prompt (e.g., "REBASING")
+    pub fn label(&self) -> &'static str {
+        match self {
+            RepoOperation::Merge => "MERGING",
+            RepoOperation::Rebase => "REBASING",
+            RepoOperation::CherryPick => "CHERRY-PICKING",
+            RepoOperation::Revert => "REVERTING",
+        }
+    }
+}
+
+/// Reads the remote URL from the repository config, preferring `origin`
+fn read_remote_url(common_dir: &Path) -> Option<String> {
+    let config = std::fs::read_to_string(common_dir.join("config")).ok()?;
+    parse_remote_url(&config)
+}
+
+/// Reads the default branch of `origin` from the `refs/remotes/origin/HEAD` symref
+fn read_default_branch(common_dir: &Path) -> Option<String> {
+    let head = std::fs::read_to_string(common_dir.join("refs/remotes/origin/HEAD")).ok()?;
+    head.trim()
+        .strip_prefix("ref:")?
+        .trim()
+        .strip_prefix("refs/remotes/origin/")
+        .map(|b| b.to_string())
+}
+
+/// Extracts the `origin` URL (or the first remote's URL) from git config text
+fn parse_remote_url(config: &str) -> Option<String> {
+    let mut current_remote: Option<String> = None;
+    let mut first_url: Option<String> = None;
+
+    for line in config.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            // Section header: [remote "origin"]
+            current_remote = line
+                .strip_prefix("[remote")
+                .and_then(|rest| rest.trim().strip_suffix(']'))
+                .map(|name| name.trim().trim_matches('"').to_string());
+            continue;
+        }
+        let Some(remote) = &current_remote else {
+            continue;
+        };
+        if let Some((key, value)) = line.split_once('=') {
+            if key.trim() == "url" {
+                let url = value.trim().to_string();
+                if remote == "origin" {
+                    return Some(url);
+                }
+                first_url.get_or_insert(url);
+            }
+        }
+    }
+
+    first_url
+}
+
+/// Derives "owner/repo" from a remote URL
+///
+/// Supports scp-like (`git@github.com:owner/repo.git`), `https://` and `ssh://` URLs.
+pub fn parse_repo_slug(url: &str) -> Option<String> {
+    let url = url.trim().trim_end_matches('/');
+    let path = if let Some((_, rest)) = url.split_once("://") {
+        // Strip host (and optional user/port)
+        rest.split_once('/')?.1
+    } else if let Some((_, rest)) = url.split_once(':') {
+        rest
+    } else {
+        return None;
+    };
+
+    let path = path.trim_end_matches(".git");
+    let mut parts = path.rsplit('/').filter(|s| !s.is_empty());
+    let repo = parts.next()?;
+    let owner = parts.next()?;
+    Some(format!("{}/{}", owner, repo))
+}
+
+/// Maximum age of a cached entry before it is re-read regardless of changes
+///
+/// Edits to tracked files don't touch HEAD or the index, so this bounds how
+/// long a dirty working tree can go unnoticed.
+const GIT_CACHE_TTL: Duration = Duration::from_secs(10);
+
+/// Files whose metadata changes invalidate a cached entry
+/// (checkout, staging, and commits/resets via the HEAD reflog)
+const WATCHED_FILES: &[&str] = &["HEAD", "index", "logs/HEAD"];
+
+/// Snapshot of the watched files' modification time and size
+type Fingerprint = Vec<Option<(SystemTime, u64)>>;
+
+struct CacheEntry {
+    fingerprint: Fingerprint,
+    fetched_at: Instant,
+    info: Option<GitInfo>,
+    /// Whether the entry was looked up since the last prune
+    used: bool,
+}
+
+/// Cache of git information keyed by repository root
+///
+/// Panes sharing a repository share one entry, and an entry is only re-read
+/// when `.git/HEAD`, `.git/index` or the HEAD reflog change (or the TTL expires),
+/// so many monitored panes don't spawn git on every poll.
+#[derive(Default)]
+pub struct GitCache {
+    entries: HashMap<PathBuf, CacheEntry>,
+}
+
+impl GitCache {
+    /// Creates an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns git information for `path`, re-reading only if the repository changed
+    pub fn get(&mut self, path: &str) -> Option<GitInfo> {
+        self.get_with_env(path, &GitEnv::default())
+    }
+
+    /// Like [`GitCache::get`], honoring `GIT_DIR`/`GIT_WORK_TREE` from a pane's process
+    pub fn get_with_env(&mut self, path: &str, env: &GitEnv) -> Option<GitInfo> {
+        if path.is_empty() {
+            return None;
+        }
+        let repo = discover_with_env(Path::new(path), env)?;
+        let fingerprint = fingerprint(&repo.git_dir);
+
+        if let Some(entry) = self.entries.get_mut(&repo.root) {
+            if entry.fingerprint == fingerprint && entry.fetched_at.elapsed() < GIT_CACHE_TTL {
+                entry.used = true;
+                return entry.info.clone();
+            }
+        }
+
+        let info = read_info(&repo);
+        self.entries.insert(
+            repo.root,
+            CacheEntry {
+                fingerprint,
+                fetched_at: Instant::now(),
+                info: info.clone(),
+                used: true,
+            },
+        );
+        info
+    }
+
+    /// Drops entries not looked up since the previous prune (call once per poll)
+    pub fn prune_unused(&mut self) {
+        self.entries
+            .retain(|_, entry| std::mem::take(&mut entry.used));
+    }
+
+    /// Returns the number of cached repositories
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if nothing is cached
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+fn fingerprint(git_dir: &Path) -> Fingerprint {
+    WATCHED_FILES
+        .iter()
+        .map(|name| {
+            std::fs::metadata(git_dir.join(name))
+                .ok()
+                .and_then(|m| Some((m.modified().ok()?, m.len())))
+        })
+        .collect()
+}
+
+/// `git log` format parsed by [`CommitInfo::parse`]: hash, time, author, subject
+const COMMIT_FORMAT: &str = "--format=%h%x09%ct%x09%an%x09%s";
+
+/// Summary of a single commit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitInfo {
+    /// Abbreviated commit hash
+    pub short_hash: String,
+    /// Author name
+    pub author: String,
+    /// First line of the commit message
+    pub subject: String,
+    /// Committer timestamp (Unix seconds)
+    pub timestamp: i64,
+}
+
+impl CommitInfo {
+    /// Parses a line formatted with [`COMMIT_FORMAT`]
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut parts = line.splitn(4, '\t');
+        let short_hash = parts.next()?.trim().to_string();
+        let timestamp = parts.next()?.trim().parse().ok()?;
+        let author = parts.next().unwrap_or("").trim().to_string();
+        let subject = parts.next().unwrap_or("").trim().to_string();
+        if short_hash.is_empty() {
+            return None;
+        }
+        Some(Self {
+            short_hash,
+            author,
+            subject,
+            timestamp,
+        })
+    }
+
+    /// Returns a human-readable age like "5m ago"
+    pub fn age_str(&self) -> String {
+        let secs = (chrono::Local::now().timestamp() - self.timestamp).max(0);
+        format_age(secs as u64)
+    }
+}
+
+/// Formats an age in seconds as a short relative string
+fn format_age(secs: u64) -> String {
+    if secs < 60 {
+        "just now".to_string()
+    } else if secs < 3600 {
+        format!("{}m ago", secs / 60)
+    } else if secs < 86400 {
+        format!("{}h ago", secs / 3600)
+    } else {
+        format!("{}d ago", secs / 86400)
+    }
+}
+
+/// Returns the most recent commit for the repository containing `path`
+pub fn get_last_commit(path: &str) -> Option<CommitInfo> {
+    if path.is_empty() {
+        return None;
+    }
+    last_commit(&discover(Path::new(path))?)
+}
+
+/// Reads the most recent commit of a repository
+fn last_commit(repo: &Repo) -> Option<CommitInfo> {
+    let output = repo
+        .git()
+        .args(["log", "-1", COMMIT_FORMAT])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    CommitInfo::parse(String::from_utf8_lossy(&output.stdout).trim_end())
+}
+
+/// Discovers the repository containing `path`, failing outside of one
+fn open(path: &str) -> Result<Repo> {
+    discover(Path::new(path)).with_context(|| format!("{} is not in a git repository", path))
+}
+
+/// Returns up to `limit` most recent commits on HEAD, newest first
+pub fn get_recent_commits(path: &str, limit: usize) -> Result<Vec<CommitInfo>> {
+    let output = open(path)?
+        .git()
+        .args(["log", &format!("-{}", limit), COMMIT_FORMAT])
+        .output()
+        .context("Failed to execute git log")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git log failed: {}", stderr.trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(CommitInfo::parse)
+        .collect())
+}
+
+/// Returns `git diff` output for the work tree containing `path`
+///
+/// With `staged`, returns the changes in the index (`git diff --staged`) instead.
+pub fn get_diff(path: &str, staged: bool) -> Result<String> {
+    let mut args = vec!["--no-optional-locks", "diff", "--no-color"];
+    if staged {
+        args.push("--staged");
+    }
+
+    let output = open(path)?
+        .git()
+        .args(&args)
+        .output()
+        .context("Failed to execute git diff")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git diff failed: {}", stderr.trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_git_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let git_dir = dir.path().join(".git");
+        std::fs::create_dir(&git_dir).unwrap();
+        std::fs::write(git_dir.join("HEAD"), "ref: refs/heads/feature/x\n").unwrap();
+
+        let sub = dir.path().join("src");
+        std::fs::create_dir(&sub).unwrap();
+
+        let root = dir.path().to_str().unwrap();
+        assert_eq!(get_git_branch(root), Some("feature/x".to_string()));
+        assert_eq!(
+            get_git_branch(sub.to_str().unwrap()),
+            Some("feature/x".to_string())
+        );
+    }
+
+    #[test]
+    fn test_get_git_branch_detached() {
+        let dir = tempfile::tempdir().unwrap();
+        let git_dir = dir.path().join(".git");
+        std::fs::create_dir(&git_dir).unwrap();
+        std::fs::write(
+            git_dir.join("HEAD"),
+            "3f2a9c1d8e7b6a5f4e3d2c1b0a9f8e7d6c5b4a39\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            get_git_branch(dir.path().to_str().unwrap()),
+            Some("3f2a9c1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_porcelain() {
+        let output = "\
+1 M. N... 100644 100644 100644 abc abc src/main.rs
+1 .M N... 100644 100644 100644 abc abc src/lib.rs
+1 MM N... 100644 100644 100644 abc abc src/git.rs
+2 R. N... 100644 100644 100644 abc abc R100 new.rs\told.rs
+? untracked.txt
+";
+        let status = GitStatus::parse_porcelain(output);
+        assert_eq!(status.staged, 3);
+        assert_eq!(status.modified, 2);
+        assert_eq!(status.untracked, 1);
+        assert_eq!(status.conflicted, 0);
+        assert!(status.is_dirty());
+        assert_eq!(status.indicator(), "*+");
+        assert_eq!(status.badges(), vec![('~', 2), ('?', 1)]);
+
+        let conflicted = GitStatus::parse_porcelain(
+            "u UU N... 100644 100644 100644 100644 a b c src/a.rs\n? new.rs\n",
+        );
+        assert_eq!(conflicted.conflicted, 1);
+        assert_eq!(conflicted.staged, 0);
+        assert!(conflicted.is_dirty());
+        assert_eq!(conflicted.badges(), vec![('?', 1), ('!', 1)]);
+
+        let clean = GitStatus::parse_porcelain("");
+        assert!(!clean.is_dirty());
+        assert_eq!(clean.indicator(), "");
+    }
+
+    #[test]
+    fn test_parse_branch_headers() {
+        let output = "\
+# branch.oid 3f2a9c1d8e7b6a5f4e3d2c1b0a9f8e7d6c5b4a39
+# branch.head main
+# branch.upstream origin/main
+# branch.ab +2 -1
+1 .M N... 100644 100644 100644 abc abc src/lib.rs
+";
+        let info = GitInfo::parse_porcelain("main".to_string(), output);
+        assert_eq!(info.upstream.as_deref(), Some("origin/main"));
+        assert_eq!(info.ahead, 2);
+        assert_eq!(info.behind, 1);
+        assert!(info.is_diverged());
+        assert_eq!(info.label(), "main* ↑2↓1");
+        assert!(!info.upstream_gone);
+
+        let gone = GitInfo::parse_porcelain(
+            "feat".to_string(),
+            "# branch.head feat\n# branch.upstream origin/feat\n",
+        );
+        assert_eq!(gone.upstream.as_deref(), Some("origin/feat"));
+        assert!(gone.upstream_gone);
+
+        let no_upstream = GitInfo::parse_porcelain("wip".to_string(), "# branch.head wip\n");
+        assert_eq!(no_upstream.upstream, None);
+        assert!(!no_upstream.is_diverged());
+        assert_eq!(no_upstream.label(), "wip");
+    }
+
+    #[test]
+    fn test_stash_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let git_dir = dir.path().join(".git");
+        std::fs::create_dir_all(git_dir.join("logs/refs")).unwrap();
+        std::fs::write(git_dir.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        let path = dir.path().to_str().unwrap();
+
+        assert_eq!(get_stash_count(path), 0);
+
+        std::fs::write(
+            git_dir.join("logs/refs/stash"),
+            "0000 1111 A <a@b> 1700000000 +0000\tWIP on main: one\n\
+             1111 2222 A <a@b> 1700000001 +0000\tWIP on main: two\n",
+        )
+        .unwrap();
+        assert_eq!(get_stash_count(path), 2);
+    }
+
+    #[test]
+    fn test_parse_commit_info() {
+        let commit =
+            CommitInfo::parse("3f2a9c1\t1700000000\tJane Doe\tFix parser: handle tabs\tin subject")
+                .unwrap();
+        assert_eq!(commit.short_hash, "3f2a9c1");
+        assert_eq!(commit.timestamp, 1700000000);
+        assert_eq!(commit.author, "Jane Doe");
+        assert_eq!(commit.subject, "Fix parser: handle tabs\tin subject");
+
+        assert!(CommitInfo::parse("").is_none());
+        assert!(CommitInfo::parse("abc\tnot-a-number\tsubject").is_none());
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(30), "just now");
+        assert_eq!(format_age(300), "5m ago");
+        assert_eq!(format_age(7200), "2h ago");
+        assert_eq!(format_age(3 * 86400), "3d ago");
+    }
+
+    /// Creates the minimal layout recognized as a git directory
+    fn make_git_dir(dir: &Path, config: &str) {
+        std::fs::create_dir_all(dir.join("objects")).unwrap();
+        std::fs::create_dir_all(dir.join("refs/heads")).unwrap();
+        std::fs::write(dir.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        std::fs::write(dir.join("config"), config).unwrap();
+    }
+
+    #[test]
+    fn test_parse_is_bare() {
+        assert!(parse_is_bare("[core]\n\tbare = true\n"));
+        assert!(!parse_is_bare("[core]\n\tbare = false\n"));
+        assert!(!parse_is_bare("[remote \"origin\"]\n\tbare = true\n"));
+    }
+
+    #[test]
+    fn test_discover_bare_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        let bare = dir.path().join("repo.git");
+        make_git_dir(&bare, "[core]\n\tbare = true\n");
+        std::fs::create_dir_all(bare.join("hooks")).unwrap();
+
+        let repo = discover(&bare.join("hooks")).unwrap();
+        assert!(repo.bare);
+        assert_eq!(repo.root, bare);
+        assert_eq!(repo.git_dir, bare);
+    }
+
+    #[test]
+    fn test_discover_with_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let git_dir = dir.path().join("store/app.git");
+        make_git_dir(&git_dir, "[core]\n\tbare = false\n");
+        let work = dir.path().join("checkout");
+        std::fs::create_dir_all(&work).unwrap();
+
+        // No .git next to the work tree: only found through GIT_DIR
+        assert!(discover(&work).is_none());
+
+        let env = GitEnv::from_vars([
+            ("GIT_DIR".to_string(), "../store/app.git".to_string()),
+            ("PATH".to_string(), "/usr/bin".to_string()),
+        ]);
+        let repo = discover_with_env(&work, &env).unwrap();
+        assert!(repo.explicit);
+        assert!(!repo.bare);
+        assert_eq!(repo.root, work);
+        assert_eq!(repo.git_dir, work.join("../store/app.git"));
+
+        let env = GitEnv::from_vars([
+            ("GIT_DIR".to_string(), git_dir.to_string_lossy().to_string()),
+            ("GIT_WORK_TREE".to_string(), "/elsewhere".to_string()),
+        ]);
+        let repo = discover_with_env(&work, &env).unwrap();
+        assert_eq!(repo.root, PathBuf::from("/elsewhere"));
+    }
+
+    /// Creates a main repository with a linked worktree at `<root>/wt`
+    /// using a relative `gitdir:` and a relative `commondir`
+    fn make_worktree(root: &Path) -> PathBuf {
+        let main_git = root.join("main/.git");
+        let wt_git = main_git.join("worktrees/wt");
+        std::fs::create_dir_all(&wt_git).unwrap();
+        std::fs::create_dir_all(main_git.join("logs/refs")).unwrap();
+        std::fs::write(main_git.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        std::fs::write(wt_git.join("HEAD"), "ref: refs/heads/feature\n").unwrap();
+        std::fs::write(wt_git.join("commondir"), "../..\n").unwrap();
+
+        let wt = root.join("wt");
+        std::fs::create_dir_all(wt.join("src")).unwrap();
+        std::fs::write(wt.join(".git"), "gitdir: ../main/.git/worktrees/wt\n").unwrap();
+        wt
+    }
+
+    #[test]
+    fn test_worktree_relative_gitdir() {
+        let dir = tempfile::tempdir().unwrap();
+        let wt = make_worktree(dir.path());
+
+        assert_eq!(
+            get_git_branch(wt.to_str().unwrap()),
+            Some("feature".to_string())
+        );
+        assert_eq!(
+            get_git_branch(wt.join("src").to_str().unwrap()),
+            Some("feature".to_string())
+        );
+    }
+
+    #[test]
+    fn test_worktree_commondir() {
+        let dir = tempfile::tempdir().unwrap();
+        let wt = make_worktree(dir.path());
+        std::fs::write(
+            dir.path().join("main/.git/logs/refs/stash"),
+            "0000 1111 A <a@b> 1700000000 +0000\tWIP on main: one\n",
+        )
+        .unwrap();
+
+        let git_dir = find_git_dir(&wt).unwrap();
+        let common = common_dir(&git_dir);
+        assert_eq!(
+            common.canonicalize().unwrap(),
+            dir.path().join("main/.git").canonicalize().unwrap()
+        );
+        assert_eq!(get_stash_count(wt.to_str().unwrap()), 1);
+    }
+
+    #[test]
+    fn test_worktree_absolute_gitdir() {
+        let dir = tempfile::tempdir().unwrap();
+        let wt = make_worktree(dir.path());
+        let absolute = dir.path().join("main/.git/worktrees/wt");
+        std::fs::write(wt.join(".git"), format!("gitdir: {}\n", absolute.display())).unwrap();
+
+        assert_eq!(
+            get_git_branch(wt.to_str().unwrap()),
+            Some("feature".to_string())
+        );
+    }
+
+    #[test]
+    fn test_git_cache_invalidates_on_head_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let git_dir = dir.path().join(".git");
+        std::fs::create_dir(&git_dir).unwrap();
+        std::fs::write(git_dir.join("HEAD"), "ref: refs/heads/a\n").unwrap();
+        let sub = dir.path().join("src");
+        std::fs::create_dir(&sub).unwrap();
+
+        let mut cache = GitCache::new();
+        let root = dir.path().to_str().unwrap();
+        assert_eq!(cache.get(root).unwrap().branch, "a");
+        // Subdirectories share the repository entry
+        assert_eq!(cache.get(sub.to_str().unwrap()).unwrap().branch, "a");
+        assert_eq!(cache.len(), 1);
+
+        std::fs::write(git_dir.join("HEAD"), "ref: refs/heads/bb\n").unwrap();
+        assert_eq!(cache.get(root).unwrap().branch, "bb");
+
+        cache.prune_unused();
+        assert_eq!(cache.len(), 1);
+        cache.prune_unused();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_find_exact_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let common = dir.path();
+        std::fs::create_dir_all(common.join("refs/tags/release")).unwrap();
+        std::fs::write(common.join("refs/tags/release/v2.0"), "bbbb\n").unwrap();
+        std::fs::write(
+            common.join("packed-refs"),
+            "# pack-refs with: peeled fully-peeled sorted\n\
+             aaaa refs/heads/main\n\
+             cccc refs/tags/v1.0\n\
+             dddd refs/tags/v1.1\n\
+             ^eeee\n",
+        )
+        .unwrap();
+
+        assert_eq!(find_exact_tag(common, "cccc"), Some("v1.0".to_string()));
+        // Annotated tag matched via its peeled commit
+        assert_eq!(find_exact_tag(common, "eeee"), Some("v1.1".to_string()));
+        assert_eq!(
+            find_exact_tag(common, "bbbb"),
+            Some("release/v2.0".to_string())
+        );
+        assert_eq!(find_exact_tag(common, "aaaa"), None);
+    }
+
+    #[test]
+    fn test_parse_describe() {
+        assert_eq!(
+            parse_describe("v1.2.3-2-gabc1234\n"),
+            Some("v1.2.3~2".to_string())
+        );
+        assert_eq!(
+            parse_describe("v1.2.3-0-gabc1234"),
+            Some("v1.2.3".to_string())
+        );
+        assert_eq!(
+            parse_describe("release-candidate-1-5-g0123456"),
+            Some("release-candidate-1~5".to_string())
+        );
+        assert_eq!(parse_describe("garbage"), None);
+    }
+
+    #[test]
+    fn test_parse_remote_url() {
+        let config = r#"
+[core]
+	bare = false
+[remote "upstream"]
+	url = https://github.com/upstream/app.git
+	fetch = +refs/heads/*:refs/remotes/upstream/*
+[remote "origin"]
+	url = git@github.com:me/app.git
+"#;
+        assert_eq!(
+            parse_remote_url(config),
+            Some("git@github.com:me/app.git".to_string())
+        );
+
+        let only_fork = "[remote \"fork\"]\n\turl = https://example.com/x/y\n";
+        assert_eq!(
+            parse_remote_url(only_fork),
+            Some("https://example.com/x/y".to_string())
+        );
+        assert_eq!(parse_remote_url("[core]\n\tbare = false\n"), None);
+    }
+
+    #[test]
+    fn test_parse_repo_slug() {
+        for url in [
+            "git@github.com:owner/repo.git",
+            "https://github.com/owner/repo",
+            "https://github.com/owner/repo.git/",
+            "ssh://git@github.com:22/owner/repo.git",
+        ] {
+            assert_eq!(
+                parse_repo_slug(url),
+                Some("owner/repo".to_string()),
+                "{}",
+                url
+            );
+        }
+        assert_eq!(
+            parse_repo_slug("https://gitlab.com/group/sub/repo.git"),
+            Some("sub/repo".to_string())
+        );
+        assert_eq!(parse_repo_slug("/local/path/repo"), None);
+    }
+
+    #[test]
+    fn test_detect_repo_operation() {
+        let dir = tempfile::tempdir().unwrap();
+        let git_dir = dir.path();
+        assert_eq!(RepoOperation::detect(git_dir), None);
+
+        std::fs::write(git_dir.join("CHERRY_PICK_HEAD"), "abc\n").unwrap();
+        assert_eq!(
+            RepoOperation::detect(git_dir),
+            Some(RepoOperation::CherryPick)
+        );
+
+        std::fs::write(git_dir.join("MERGE_HEAD"), "abc\n").unwrap();
+        assert_eq!(RepoOperation::detect(git_dir), Some(RepoOperation::Merge));
+
+        std::fs::create_dir(git_dir.join("rebase-merge")).unwrap();
+        assert_eq!(RepoOperation::detect(git_dir), Some(RepoOperation::Rebase));
+        assert_eq!(RepoOperation::Rebase.label(), "REBASING");
+    }
+}
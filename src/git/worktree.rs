@@ -0,0 +1,219 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// A work tree attached to a repository (the main one or a linked one)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Worktree {
+    /// Absolute path of the work tree
+    pub path: PathBuf,
+    /// Checked out branch (without `refs/heads/`), None when detached or bare
+    pub branch: Option<String>,
+    /// Commit checked out in the work tree
+    pub head: String,
+    /// Whether this is the bare repository entry
+    pub bare: bool,
+    /// Whether the work tree is locked
+    pub locked: bool,
+}
+
+impl Worktree {
+    /// Returns the branch name or the abbreviated HEAD for display
+    pub fn display_branch(&self) -> String {
+        match &self.branch {
+            Some(branch) => branch.clone(),
+            None if self.bare => "(bare)".to_string(),
+            None => self.head.chars().take(7).collect(),
+        }
+    }
+}
+
+/// Parses the output of `git worktree list --porcelain`
+///
+/// Entries are separated by blank lines and start with a `worktree <path>` line.
+pub fn parse_worktree_list(output: &str) -> Vec<Worktree> {
+    let mut worktrees = Vec::new();
+    let mut current: Option<Worktree> = None;
+
+    for line in output.lines() {
+        if let Some(path) = line.strip_prefix("worktree ") {
+            worktrees.extend(current.take());
+            current = Some(Worktree {
+                path: PathBuf::from(path),
+                branch: None,
+                head: String::new(),
+                bare: false,
+                locked: false,
+            });
+            continue;
+        }
+
+        let Some(wt) = current.as_mut() else {
+            continue;
+        };
+        if let Some(head) = line.strip_prefix("HEAD ") {
+            wt.head = head.to_string();
+        } else if let Some(branch) = line.strip_prefix("branch ") {
+            wt.branch = Some(
+                branch
+                    .strip_prefix("refs/heads/")
+                    .unwrap_or(branch)
+                    .to_string(),
+            );
+        } else if line == "bare" {
+            wt.bare = true;
+        } else if line == "locked" || line.starts_with("locked ") {
+            wt.locked = true;
+        }
+    }
+
+    worktrees.extend(current);
+    worktrees
+}
+
+/// Lists the work trees of the repository containing `path`
+pub fn list_worktrees(path: &str) -> Result<Vec<Worktree>> {
+    let mut cmd = Command::new("git");
+    cmd.args(["-C", path]);
+    run_worktree_list(cmd)
+}
+
+/// Runs `worktree list --porcelain` with a prepared `git` command
+pub(super) fn run_worktree_list(mut git: Command) -> Result<Vec<Worktree>> {
+    let output = git
+        .args(["worktree", "list", "--porcelain"])
+        .output()
+        .context("Failed to execute git worktree list")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git worktree list failed: {}", stderr.trim());
+    }
+
+    Ok(parse_worktree_list(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Returns the default location for a new work tree of `branch`
+///
+/// Work trees are created next to the main work tree as `<repo>-<branch>`,
+/// with `/` in branch names replaced so `feat/x` becomes `<repo>-feat-x`.
+pub fn default_worktree_path(repo_root: &Path, branch: &str) -> PathBuf {
+    let repo_name = repo_root
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "repo".to_string());
+    let slug: String = branch
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let name = format!("{}-{}", repo_name, slug);
+    match repo_root.parent() {
+        Some(parent) => parent.join(name),
+        None => PathBuf::from(name),
+    }
+}
+
+/// Returns true if a local branch named `branch` exists
+fn branch_exists(repo: &str, branch: &str) -> bool {
+    Command::new("git")
+        .args([
+            "-C",
+            repo,
+            "show-ref",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{}", branch),
+        ])
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+/// Creates a work tree for `branch` in the repository containing `repo`
+///
+/// Checks out the branch if it exists, otherwise creates it from HEAD.
+/// Returns the path of the new work tree.
+pub fn create_worktree(repo: &str, branch: &str) -> Result<PathBuf> {
+    let branch = branch.trim();
+    if branch.is_empty() {
+        anyhow::bail!("Branch name is empty");
+    }
+
+    // Place linked work trees relative to the main work tree, not a linked one
+    let main_root = list_worktrees(repo)?
+        .into_iter()
+        .find(|wt| !wt.bare)
+        .map(|wt| wt.path)
+        .or_else(|| super::get_repo_root(repo))
+        .context("Not inside a git repository")?;
+    let path = default_worktree_path(&main_root, branch);
+    let path_str = path.to_string_lossy().to_string();
+
+    let mut args = vec!["-C", repo, "worktree", "add"];
+    if branch_exists(repo, branch) {
+        args.extend([path_str.as_str(), branch]);
+    } else {
+        args.extend(["-b", branch, path_str.as_str()]);
+    }
+
+    let output = Command::new("git")
+        .args(&args)
+        .output()
+        .context("Failed to execute git worktree add")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git worktree add failed: {}", stderr.trim());
+    }
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_worktree_list() {
+        let output = "\
+worktree /home/user/app
+HEAD 3f2a9c1d8e7b6a5f4e3d2c1b0a9f8e7d6c5b4a39
+branch refs/heads/main
+
+worktree /home/user/app-feat-x
+HEAD 1111111d8e7b6a5f4e3d2c1b0a9f8e7d6c5b4a39
+branch refs/heads/feat/x
+locked
+
+worktree /home/user/app-detached
+HEAD 2222222d8e7b6a5f4e3d2c1b0a9f8e7d6c5b4a39
+detached
+";
+        let worktrees = parse_worktree_list(output);
+        assert_eq!(worktrees.len(), 3);
+        assert_eq!(worktrees[0].path, PathBuf::from("/home/user/app"));
+        assert_eq!(worktrees[0].branch.as_deref(), Some("main"));
+        assert_eq!(worktrees[1].branch.as_deref(), Some("feat/x"));
+        assert!(worktrees[1].locked);
+        assert_eq!(worktrees[2].branch, None);
+        assert_eq!(worktrees[2].display_branch(), "2222222");
+    }
+
+    #[test]
+    fn test_default_worktree_path() {
+        assert_eq!(
+            default_worktree_path(Path::new("/home/user/app"), "feat/x"),
+            PathBuf::from("/home/user/app-feat-x")
+        );
+    }
+}
@@ -1,5 +1,7 @@
 pub mod agents;
 pub mod app;
+pub mod daemon;
+pub mod git;
 pub mod monitor;
 pub mod parsers;
 pub mod tmux;
@@ -1,10 +1,26 @@
 use anyhow::Result;
-use clap::Parser;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use std::os::unix::process::CommandExt;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use tmuxcc::app::Config;
-use tmuxcc::ui::run_app;
+use tmuxcc::agents::{
+    find_agent, find_agents, AgentFilter, AgentState, MonitoredAgent, StateFilter,
+};
+use tmuxcc::app::script::{ScriptEvent, ScriptRun, TaskScript};
+use tmuxcc::app::{config_check, headless, listing, spawn, spawn::Placement, AgentTree, Config};
+use tmuxcc::daemon::{self, Availability, Request};
+use tmuxcc::monitor::{
+    CaptureStore, EventStream, MonitorTask, MonitorUpdate, PaneRegistry, StatusSummary,
+    SummaryCache, SUMMARY_MAX_AGE,
+};
+use tmuxcc::parsers::stream_json::STREAM_VAR;
+use tmuxcc::parsers::ParserRegistry;
+use tmuxcc::tmux::{send_hook_event, HookEvent, SendMode, TmuxClient};
+use tmuxcc::ui::{run_app, run_popup};
 
 #[derive(Parser)]
 #[command(name = "tmuxcc")]
@@ -13,22 +29,30 @@ use tmuxcc::ui::run_app;
     about = "AI Agent Dashboard for tmux - Claude Code, OpenCode, Codex CLI, Gemini CLI を一元管理"
 )]
 struct Cli {
-    /// ポーリング間隔（ミリ秒）
-    #[arg(short, long, default_value = "500", value_name = "MS")]
-    poll_interval: u64,
+    /// ポーリング間隔（ミリ秒、設定ファイルより優先）[デフォルト: 500]
+    #[arg(short, long, value_name = "MS")]
+    poll_interval: Option<u64>,
 
-    /// ペインからキャプチャする行数
-    #[arg(short, long, default_value = "100", value_name = "LINES")]
-    capture_lines: u32,
+    /// ペインからキャプチャする行数（設定ファイルより優先）[デフォルト: 100]
+    #[arg(short, long, value_name = "LINES")]
+    capture_lines: Option<u32>,
 
-    /// 設定ファイルのパス
+    /// 設定ファイルのパス（環境変数 TMUXCC_CONFIG、デフォルト: $XDG_CONFIG_HOME/tmuxcc/config.toml）
     #[arg(short = 'f', long, value_name = "FILE")]
     config: Option<PathBuf>,
 
+    /// 設定ファイルの [profiles.<NAME>] を重ねて使う（環境変数 TMUXCC_PROFILE）
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
     /// デバッグログを tmuxcc.log に出力
     #[arg(short, long)]
     debug: bool,
 
+    /// tmux コントロールモードでイベント駆動の監視を行う（実験的）
+    #[arg(long)]
+    control_mode: bool,
+
     /// 設定ファイルのパスを表示
     #[arg(long)]
     show_config_path: bool,
@@ -36,31 +60,337 @@ struct Cli {
     /// デフォルト設定ファイルを生成
     #[arg(long)]
     init_config: bool,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// 新しい tmux ウィンドウ・分割ペイン・セッションでエージェントを起動
+    New {
+        /// 作業ディレクトリ（デフォルト: カレントディレクトリ）
+        #[arg(short = 'c', long, value_name = "DIR")]
+        cwd: Option<PathBuf>,
+
+        /// ウィンドウを作成するセッション（デフォルト: 現在のセッション）
+        #[arg(short, long, value_name = "NAME", conflicts_with_all = ["split", "new_session"])]
+        session: Option<String>,
+
+        /// 指定したペインを分割して起動（例: main:0.1）
+        #[arg(long, value_name = "TARGET", conflicts_with = "new_session")]
+        split: Option<String>,
+
+        /// 上下に分割（デフォルト: 左右）
+        #[arg(long, requires = "split")]
+        vertical: bool,
+
+        /// 新しいセッションを作成して起動
+        #[arg(long, value_name = "NAME")]
+        new_session: Option<String>,
+
+        /// 起動するエージェントのコマンド（デフォルト: 設定の agent_command）
+        #[arg(long, value_name = "CMD")]
+        agent: Option<String>,
+
+        /// エージェントを tmuxcc headless 経由で起動（画面ではなくイベントストリームで監視）
+        #[arg(long)]
+        headless: bool,
+    },
+    /// Claude Code を -p --output-format stream-json で実行し、プロンプトごとにセッションを再開
+    Headless {
+        /// 最初のプロンプト（以降のプロンプトは標準入力から1行ずつ読む）
+        #[arg(short, long, value_name = "TEXT")]
+        prompt: Option<String>,
+
+        /// エージェントのコマンドと引数（例: claude --model sonnet）
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+    /// tmux の status-right 用に状態の概要を1行で表示
+    Statusline,
+
+    /// 状態の概要を表示し、承認待ちなら 2、エラーなら 3 で終了（cron や CI の監視向け）
+    Status,
+
+    /// 監視中のペインを一覧表示（セッション・ブランチ・状態・作業時間・コスト）
+    Ls {
+        /// JSON で出力（スクリプトや他のツール向け）
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// display-popup 向けのコンパクトな選択画面（ジャンプ・定型返信して終了）
+    Popup,
+
+    /// 条件に一致するすべてのエージェントにプロンプトを送信
+    Broadcast {
+        /// 状態（idle, working, waiting, input, permission, error, stalled）
+        #[arg(long, value_name = "STATE")]
+        state: Option<String>,
+
+        /// リポジトリ（ルートのパスまたは GitHub の owner/name の部分一致）
+        #[arg(long, value_name = "NAME")]
+        repo: Option<String>,
+
+        /// ブランチ名（部分一致）
+        #[arg(long, value_name = "NAME")]
+        branch: Option<String>,
+
+        /// ラベル（部分一致）
+        #[arg(long, value_name = "TEXT")]
+        label: Option<String>,
+
+        /// 送信するプロンプト
+        prompt: String,
+    },
+
+    /// 1つのエージェントにプロンプトを送信（git フックや他のツール向け）
+    Send {
+        /// 送信先（ペイン ID %3、ターゲット main:0.1、ラベル、またはあいまい検索）
+        target: String,
+
+        /// 送信するテキスト（- で標準入力から読む）
+        text: String,
+
+        /// エージェントが作業を始めるまで待つ
+        #[arg(long)]
+        wait: bool,
+
+        /// --wait で待つ最大秒数
+        #[arg(long, value_name = "SECS", default_value_t = 30, requires = "wait")]
+        timeout: u64,
+    },
+
+    /// エージェントのペインの出力を表示（--follow で追いかける）
+    Logs {
+        /// 対象（ペイン ID %3、ターゲット main:0.1、ラベル、またはあいまい検索）
+        target: String,
+
+        /// 表示する行数
+        #[arg(short = 'n', long, value_name = "N", default_value_t = 50)]
+        lines: usize,
+
+        /// 新しい出力を表示し続ける
+        #[arg(short, long)]
+        follow: bool,
+    },
+
+    /// 監視イベントを1行1つの JSON（NDJSON）で出力し続ける（jq などのツール向け）
+    Events,
+
+    /// 監視・通知・統計をバックグラウンドで続けるデーモンを起動（ダッシュボードや各コマンドはデーモンに接続する）
+    Daemon {
+        /// 動作中のデーモンを停止
+        #[arg(long)]
+        stop: bool,
+    },
+
+    /// 検索に一致するエージェントのペインへ表示を切り替える（tmux のキーに割り当てる用）
+    ///
+    /// 複数のペインが一致するときは、繰り返すたびに次のペインへ移る
+    Jump {
+        /// 検索（ペイン ID %3、ターゲット、ラベル、または "api state:waiting" のようなあいまい検索）
+        #[arg(required = true)]
+        query: Vec<String>,
+    },
+
+    /// エージェントを終了してペインを閉じる（まず Ctrl-C で割り込む）
+    Kill {
+        /// 対象（ペイン ID %3、ターゲット main:0.1、ラベル、またはあいまい検索）
+        target: String,
+
+        /// 割り込んでから終了を待つ秒数（過ぎたら強制終了、0: 割り込まない）
+        #[arg(long, value_name = "SECS", default_value_t = 5)]
+        grace: u64,
+    },
+
+    /// エージェントを元のコマンドで再起動する（まず Ctrl-C で割り込む）
+    Restart {
+        /// 対象（ペイン ID %3、ターゲット main:0.1、ラベル、またはあいまい検索）
+        target: String,
+
+        /// 割り込んでから終了を待つ秒数（過ぎたら強制終了、0: 割り込まない）
+        #[arg(long, value_name = "SECS", default_value_t = 5)]
+        grace: u64,
+    },
+
+    /// エージェントが指定した状態になるまで待つ（0: 到達, 1: ペインなし, 2: タイムアウト, 3: エラー）
+    Wait {
+        /// 対象（ペイン ID %3、ターゲット main:0.1、ラベル、またはあいまい検索）
+        target: String,
+
+        /// 待つ状態（idle, working, waiting, input, permission, error, stalled）
+        #[arg(long, value_name = "STATE", default_value = "idle")]
+        state: String,
+
+        /// 待つ最大秒数（0: 無制限）
+        #[arg(long, value_name = "SECS", default_value_t = 0)]
+        timeout: u64,
+    },
+
+    /// タスクスクリプトのプロンプトを、完了を待ちながら順番にペインへ送信
+    ///
+    /// --pane がなければ新しいウィンドウ（--branch なら新しい worktree）でエージェントを起動し、
+    /// スクリプトがあればそのペインで実行する
+    Run {
+        /// スクリプトファイル（TOML の steps = [{ send = "...", wait_for = "idle" }, ...]）
+        #[arg(required_unless_present_any = ["repo", "branch", "agent", "label", "session"])]
+        script: Option<PathBuf>,
+
+        /// 対象のペイン（例: main:0.1）
+        #[arg(
+            short,
+            long,
+            value_name = "TARGET",
+            requires = "script",
+            conflicts_with_all = ["repo", "branch", "agent", "label", "session"]
+        )]
+        pane: Option<String>,
+
+        /// 起動するリポジトリ（デフォルト: カレントディレクトリ）
+        #[arg(long, value_name = "DIR")]
+        repo: Option<PathBuf>,
+
+        /// このブランチの worktree を作成して起動
+        #[arg(long, value_name = "BRANCH")]
+        branch: Option<String>,
+
+        /// 起動するエージェントのコマンド（デフォルト: 設定の agent_command）
+        #[arg(long, value_name = "CMD")]
+        agent: Option<String>,
+
+        /// ペインのラベル
+        #[arg(long, value_name = "TEXT")]
+        label: Option<String>,
+
+        /// ウィンドウを作成するセッション（デフォルト: 現在のセッション）
+        #[arg(short, long, value_name = "NAME")]
+        session: Option<String>,
+    },
+
+    /// tmux フックから呼ばれ、実行中の tmuxcc にイベントを通知（内部用）
+    #[command(hide = true)]
+    NotifyEvent {
+        /// 通知先のソケット
+        #[arg(long, value_name = "PATH")]
+        socket: PathBuf,
+
+        /// フック名（例: pane-exited）
+        event: String,
+
+        /// フックの引数
+        args: Vec<String>,
+    },
+
+    /// 設定のレイアウトテンプレートの操作
+    Layout {
+        #[command(subcommand)]
+        action: LayoutCommand,
+    },
+
+    /// git worktree の操作
+    Worktree {
+        #[command(subcommand)]
+        action: WorktreeCommand,
+    },
+
+    /// 設定ファイルの生成と検証
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+
+    /// シェル補完スクリプトを出力（bash, zsh, fish ではペインのラベルやセッション名も補完）
+    Completions {
+        /// シェル
+        shell: Shell,
+    },
+
+    /// 補完候補を1行に1つ出力（補完スクリプト用）
+    #[command(hide = true)]
+    Complete { kind: CompleteKind },
+}
+
+/// Values the completion scripts ask `tmuxcc complete` for
+#[derive(Clone, Copy, ValueEnum)]
+enum CompleteKind {
+    /// Labels and targets of agent panes
+    Targets,
+    /// tmux session names
+    Sessions,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// コメント付きのデフォルト設定ファイルを生成（-f のパスまたはデフォルトのパス）
+    Init {
+        /// 既存の設定ファイルを上書き
+        #[arg(long)]
+        force: bool,
+    },
+    /// 設定ファイルを検証し、問題を行と列つきで表示
+    Check,
+}
+
+#[derive(Subcommand)]
+enum LayoutCommand {
+    /// レイアウトのペインを作成し、各ペインのコマンドを起動
+    Apply {
+        /// 設定の [layouts.<NAME>] の名前
+        name: String,
+    },
+    /// 設定済みのレイアウトを一覧表示
+    List,
+}
+
+#[derive(Subcommand)]
+enum WorktreeCommand {
+    /// worktree を作成し、新しい tmux ウィンドウでエージェントを起動
+    New {
+        /// ブランチ名（存在しなければ HEAD から作成）
+        branch: String,
+
+        /// 対象リポジトリのパス（デフォルト: カレントディレクトリ）
+        #[arg(long, value_name = "DIR")]
+        repo: Option<PathBuf>,
+
+        /// 起動するエージェントのコマンド（デフォルト: 設定の agent_command）
+        #[arg(long, value_name = "CMD")]
+        agent: Option<String>,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+    // For containers and scripts, where options are as awkward as files
+    if cli.config.is_none() {
+        cli.config = std::env::var_os("TMUXCC_CONFIG").map(PathBuf::from);
+    }
+    if cli.profile.is_none() {
+        cli.profile = std::env::var("TMUXCC_PROFILE").ok();
+    }
 
-    // Show config path and exit
-    if cli.show_config_path {
-        if let Some(path) = Config::default_path() {
-            println!("{}", path.display());
-        } else {
-            println!("設定ディレクトリが見つかりません");
-        }
+    if let Some(Commands::Completions { shell }) = cli.command {
+        print_completions(shell);
         return Ok(());
     }
 
-    // Initialize config file and exit
-    if cli.init_config {
-        let config = Config::default();
-        if let Err(e) = config.save() {
-            eprintln!("設定ファイルの作成に失敗: {}", e);
+    // Write or check the config file and exit
+    let config_command = match &cli.command {
+        Some(Commands::Config { action }) => Some(action),
+        _ if cli.init_config => Some(&ConfigCommand::Init { force: false }),
+        _ => None,
+    };
+    if let Some(action) = config_command {
+        let Some(path) = cli.config.clone().or_else(Config::default_path) else {
+            eprintln!("設定ディレクトリが見つかりません");
             std::process::exit(1);
-        }
-        if let Some(path) = Config::default_path() {
-            println!("設定ファイルを作成しました: {}", path.display());
+        };
+        match action {
+            ConfigCommand::Init { force } => init_config(&path, *force),
+            ConfigCommand::Check => check_config(&path),
         }
         return Ok(());
     }
@@ -79,19 +409,914 @@ async fn main() -> Result<()> {
     }
 
     // Load config (from file or CLI args)
-    let mut config = if let Some(config_path) = &cli.config {
-        Config::load_from(config_path).unwrap_or_else(|e| {
-            eprintln!("設定ファイルの読み込みに失敗: {}", e);
-            std::process::exit(1);
-        })
-    } else {
-        Config::load()
+    let loaded = match &cli.config {
+        Some(config_path) => Config::load_from(config_path, cli.profile.as_deref()),
+        None => Config::load(cli.profile.as_deref()),
     };
+    let mut config = loaded.unwrap_or_else(|e| {
+        eprintln!("設定ファイルの読み込みに失敗: {:#}", e);
+        std::process::exit(1);
+    });
+
+    // Show the config path (the file read, else where one would be) and exit
+    if cli.show_config_path {
+        if let Some(path) = config.source.clone().or_else(Config::default_path) {
+            println!("{}", path.display());
+        } else {
+            println!("設定ディレクトリが見つかりません");
+        }
+        return Ok(());
+    }
 
     // CLI args override config file
-    config.poll_interval_ms = cli.poll_interval;
-    config.capture_lines = cli.capture_lines;
+    if let Some(poll_interval) = cli.poll_interval {
+        config.poll_interval_ms = poll_interval;
+    }
+    if let Some(capture_lines) = cli.capture_lines {
+        config.capture_lines = capture_lines;
+    }
+    if cli.control_mode {
+        config.control_mode = true;
+    }
+
+    // Run a subcommand and exit
+    match cli.command {
+        Some(Commands::Statusline) => return print_statusline(&config).await,
+        Some(Commands::Status) => return print_status(&config).await,
+        Some(Commands::Events) => return stream_events(&config).await,
+        Some(Commands::Daemon { stop }) => return run_daemon(config, stop).await,
+        Some(Commands::Complete { kind }) => return print_candidates(&config, kind).await,
+        Some(Commands::Ls { json }) => return list_panes(&config, json).await,
+        Some(Commands::Popup) => return run_popup(config).await,
+        Some(Commands::Run {
+            script,
+            pane,
+            repo,
+            branch,
+            agent,
+            label,
+            session,
+        }) => {
+            let (pane, startup) = match pane {
+                Some(pane) => (pane, Duration::ZERO),
+                None => {
+                    let repo = match repo {
+                        Some(repo) => repo,
+                        None => std::env::current_dir()?,
+                    };
+                    let agent = agent.unwrap_or_else(|| config.agent_command.clone());
+                    let launched = spawn::launch_worker(
+                        &TmuxClient::new(),
+                        &repo.to_string_lossy(),
+                        branch.as_deref(),
+                        session.as_deref(),
+                        &agent,
+                        label.as_deref(),
+                    );
+                    let (path, target) = launched.unwrap_or_else(|e| {
+                        eprintln!("エージェントの起動に失敗: {:#}", e);
+                        std::process::exit(1);
+                    });
+                    println!(
+                        "エージェントを起動しました: {} ({})",
+                        target,
+                        path.display()
+                    );
+                    (target, AGENT_STARTUP)
+                }
+            };
+            return match script {
+                Some(script) => run_script(&config, &script, &pane, startup).await,
+                None => Ok(()),
+            };
+        }
+        Some(Commands::Jump { query }) => return jump_to_agent(&config, &query.join(" ")).await,
+        Some(Commands::Kill { target, grace }) => {
+            return stop_agent(&config, &target, Duration::from_secs(grace), false).await
+        }
+        Some(Commands::Restart { target, grace }) => {
+            return stop_agent(&config, &target, Duration::from_secs(grace), true).await
+        }
+        Some(Commands::Logs {
+            target,
+            lines,
+            follow,
+        }) => return tail_logs(&config, &target, lines, follow).await,
+        Some(Commands::Wait {
+            target,
+            state,
+            timeout,
+        }) => {
+            let state = StateFilter::parse(&state).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            });
+            let timeout = (timeout > 0).then(|| Duration::from_secs(timeout));
+            return wait_for_state(&config, &target, state, timeout).await;
+        }
+        Some(Commands::Send {
+            target,
+            text,
+            wait,
+            timeout,
+        }) => {
+            let timeout = wait.then(|| Duration::from_secs(timeout));
+            return send_prompt(&config, &target, &text, timeout).await;
+        }
+        Some(Commands::Broadcast {
+            state,
+            repo,
+            branch,
+            label,
+            prompt,
+        }) => {
+            let state = state.as_deref().map(StateFilter::parse).transpose();
+            let state = state.unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            });
+            let filter = AgentFilter {
+                state,
+                repo,
+                branch,
+                label,
+                terms: Vec::new(),
+            };
+            return broadcast(&config, &filter, &prompt).await;
+        }
+        Some(command) => return run_command(command, &config),
+        None => {}
+    }
 
     // Run the application
     run_app(config).await
 }
+
+/// Prints the completion script for `shell`, with dynamic completion of
+/// pane targets, labels and sessions where the shell supports it
+fn print_completions(shell: Shell) {
+    let mut stdout = std::io::stdout();
+    clap_complete::generate(shell, &mut Cli::command(), "tmuxcc", &mut stdout);
+    let dynamic = match shell {
+        Shell::Bash => include_str!("completions/tmuxcc.bash"),
+        Shell::Zsh => include_str!("completions/tmuxcc.zsh"),
+        Shell::Fish => include_str!("completions/tmuxcc.fish"),
+        _ => "",
+    };
+    print!("{}", dynamic);
+}
+
+/// Prints the completion candidates of `kind`, one per line
+async fn print_candidates(config: &Config, kind: CompleteKind) -> Result<()> {
+    let tmux_client = Arc::new(TmuxClient::with_capture_lines(config.capture_lines));
+    match kind {
+        CompleteKind::Targets => {
+            let tree = command_monitor(config, tmux_client)?.poll_once().await?;
+            for agent in &tree.root_agents {
+                if let Some(label) = &agent.label {
+                    println!("{}", label);
+                }
+                println!("{}", agent.key());
+            }
+        }
+        CompleteKind::Sessions => {
+            for server in config.servers() {
+                let Ok(tree) = tmux_client.for_server(&server).list_tree() else {
+                    continue;
+                };
+                for session in &tree.sessions {
+                    println!("{}", session.name);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes the commented default config to `path`
+fn init_config(path: &std::path::Path, force: bool) {
+    match Config::write_default(path, force) {
+        Ok(()) => println!("設定ファイルを作成しました: {}", path.display()),
+        Err(e) => {
+            eprintln!("設定ファイルの作成に失敗: {:#}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Prints the problems of the config at `path` as `path:line:column:
+/// message`, exiting with an error if there are any
+fn check_config(path: &std::path::Path) {
+    let diagnostics = config_check::check_file(path).unwrap_or_else(|e| {
+        eprintln!("{:#}", e);
+        std::process::exit(1);
+    });
+    if diagnostics.is_empty() {
+        println!("{}: OK", path.display());
+        return;
+    }
+    for diagnostic in &diagnostics {
+        eprintln!("{}:{}", path.display(), diagnostic);
+    }
+    std::process::exit(1);
+}
+
+/// Prints the agent summary for tmux's status line
+///
+/// Uses the counts published by a running tmuxcc when they are fresh, and
+/// polls tmux once (publishing the result for the next call) otherwise.
+async fn print_statusline(config: &Config) -> Result<()> {
+    let path = SummaryCache::default_path();
+    let cached = path
+        .as_deref()
+        .and_then(|p| SummaryCache::load(p, SUMMARY_MAX_AGE));
+    let summary = match cached {
+        Some(summary) => summary,
+        None => {
+            let (tx, _rx) = tokio::sync::mpsc::channel(1);
+            let mut monitor = MonitorTask::new(
+                Arc::new(TmuxClient::with_capture_lines(config.capture_lines)),
+                Arc::new(ParserRegistry::from_config(config)?),
+                tx,
+                Duration::from_millis(config.poll_interval_ms),
+            )
+            .with_servers(config.servers());
+            if let Some(path) = path {
+                monitor = monitor.with_summary_cache(path);
+            }
+            let tree = monitor.poll_once().await?;
+            StatusSummary::from_agents(&tree.root_agents)
+        }
+    };
+    println!("{}", summary.tmux_format());
+    Ok(())
+}
+
+/// Where commands get the agents from
+enum CommandMonitor {
+    /// A running `tmuxcc daemon`, which knows how long agents have been in
+    /// their states
+    Daemon,
+    /// Polling tmux
+    Local(Box<MonitorTask>),
+}
+
+impl CommandMonitor {
+    async fn poll_once(&mut self) -> Result<AgentTree> {
+        match self {
+            CommandMonitor::Daemon => daemon::snapshot(),
+            CommandMonitor::Local(monitor) => monitor.poll_once().await,
+        }
+    }
+}
+
+/// Returns true if a daemon runs for `config`; one running with another
+/// config, profile or servers is left alone with a warning
+fn use_daemon(config: &Config) -> bool {
+    match daemon::availability(config) {
+        Availability::Serves => true,
+        Availability::Elsewhere(source) => {
+            eprintln!(
+                "デーモンは別の設定で動作しているため、tmux を直接監視します（{}）",
+                source.describe()
+            );
+            false
+        }
+        Availability::NotRunning => false,
+    }
+}
+
+/// Returns the agents of a daemon running for `config` or, without one, a
+/// monitor for commands polling tmux themselves, with labels and statistics
+/// from the pane registry (which it doesn't write)
+fn command_monitor(config: &Config, tmux_client: Arc<TmuxClient>) -> Result<CommandMonitor> {
+    if use_daemon(config) {
+        return Ok(CommandMonitor::Daemon);
+    }
+    let (tx, _rx) = tokio::sync::mpsc::channel(1);
+    passive_monitor(config, tmux_client, tx).map(|monitor| CommandMonitor::Local(Box::new(monitor)))
+}
+
+/// Returns a monitor sending its updates to `tx` that, like the one of
+/// [`command_monitor`], only watches: it approves and answers nothing
+fn passive_monitor(
+    config: &Config,
+    tmux_client: Arc<TmuxClient>,
+    tx: tokio::sync::mpsc::Sender<MonitorUpdate>,
+) -> Result<MonitorTask> {
+    let registry = PaneRegistry::load(PaneRegistry::default_path()).read_only();
+    Ok(MonitorTask::new(
+        tmux_client,
+        Arc::new(ParserRegistry::from_config(config)?),
+        tx,
+        Duration::from_millis(config.poll_interval_ms),
+    )
+    .with_servers(config.servers())
+    .with_registry(registry.shared()))
+}
+
+/// Returns the agent a command's `target` names, exiting if it names none
+/// or several
+fn find_target<'a>(
+    config: &Config,
+    tmux_client: &TmuxClient,
+    agents: &'a [MonitoredAgent],
+    target: &str,
+) -> &'a MonitoredAgent {
+    // tmux resolves pane ids, on whichever server has the pane
+    let pane_key = target.starts_with('%').then(|| {
+        config.servers().iter().find_map(|server| {
+            let tree = tmux_client.for_server(server).list_tree().ok()?;
+            Some(server.qualify(&tree.find_pane(target)?.info.target()))
+        })
+    });
+    let query = pane_key.flatten().unwrap_or_else(|| target.to_string());
+    find_agent(agents, &query).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Prints the state counts and the agents needing attention
+///
+/// Exits with 2 if an agent waits for permission and 3 if one reports an
+/// error, so health checks can tell them apart from a failure to poll (1).
+async fn print_status(config: &Config) -> Result<()> {
+    let tmux_client = Arc::new(TmuxClient::with_capture_lines(config.capture_lines));
+    let tree = command_monitor(config, tmux_client)?.poll_once().await?;
+    println!(
+        "{}",
+        StatusSummary::from_agents(&tree.root_agents).plain_format()
+    );
+    let mut code = 0;
+    for agent in &tree.root_agents {
+        code = match agent.state {
+            Some(AgentState::Error) => 3,
+            Some(AgentState::WaitingForPermission) => code.max(2),
+            _ => continue,
+        };
+        println!(
+            "{}  {}  {}",
+            agent.key(),
+            agent.state_label(),
+            agent.snippet()
+        );
+    }
+    if code != 0 {
+        std::process::exit(code);
+    }
+    Ok(())
+}
+
+/// Prints every agent pane as a table, or as JSON with `json`
+async fn list_panes(config: &Config, json: bool) -> Result<()> {
+    let tmux_client = Arc::new(TmuxClient::with_capture_lines(config.capture_lines));
+    let tree = command_monitor(config, tmux_client)?.poll_once().await?;
+    if json {
+        println!("{}", listing::json(&tree.root_agents));
+    } else {
+        println!("{}", listing::table(&tree.root_agents));
+    }
+    Ok(())
+}
+
+/// Sends `prompt` to every agent matching `filter`
+async fn broadcast(config: &Config, filter: &AgentFilter, prompt: &str) -> Result<()> {
+    let tmux_client = Arc::new(TmuxClient::with_capture_lines(config.capture_lines));
+    let tree = command_monitor(config, tmux_client.clone())?
+        .poll_once()
+        .await?;
+
+    let matching: Vec<_> = tree
+        .root_agents
+        .iter()
+        .filter(|a| filter.matches(a))
+        .collect();
+    if matching.is_empty() {
+        eprintln!("条件に一致するエージェントがありません");
+        std::process::exit(1);
+    }
+    let mut failed = false;
+    for agent in matching {
+        let client = tmux_client.for_server(&agent.server);
+        let sent = client
+            .send_text(&agent.target, prompt, SendMode::for_text(prompt))
+            .and_then(|()| client.send_keys(&agent.target, "Enter"));
+        match sent {
+            Ok(()) => println!("送信しました: {}", agent.key()),
+            Err(e) => {
+                eprintln!("送信に失敗: {}: {}", agent.key(), e);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Sends `text` ("-" reads it from stdin) to the agent `target` names,
+/// then with a `timeout` waits for the agent to start on it
+///
+/// The agent acknowledged the prompt once it is working or has changed state
+/// since it was sent.
+async fn send_prompt(
+    config: &Config,
+    target: &str,
+    text: &str,
+    timeout: Option<Duration>,
+) -> Result<()> {
+    let text = if text == "-" {
+        let mut input = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut input)?;
+        input.trim_end().to_string()
+    } else {
+        text.to_string()
+    };
+    let tmux_client = Arc::new(TmuxClient::with_capture_lines(config.capture_lines));
+    let mut monitor = command_monitor(config, tmux_client.clone())?;
+    let tree = monitor.poll_once().await?;
+    let agent = find_target(config, &tmux_client, &tree.root_agents, target);
+    let key = agent.key();
+    let client = tmux_client.for_server(&agent.server);
+    client.send_text(&agent.target, &text, SendMode::for_text(&text))?;
+    client.send_keys(&agent.target, "Enter")?;
+    println!("送信しました: {}", key);
+
+    let Some(timeout) = timeout else {
+        return Ok(());
+    };
+    let sent_at = std::time::Instant::now();
+    loop {
+        tokio::time::sleep(Duration::from_millis(config.poll_interval_ms)).await;
+        let tree = monitor.poll_once().await?;
+        let Some(agent) = tree.root_agents.iter().find(|a| a.key() == key) else {
+            eprintln!("エージェントのペインが見つかりません: {}", key);
+            std::process::exit(1);
+        };
+        if agent.state == Some(AgentState::Working) || agent.state_since > sent_at {
+            println!("受け付けました: {} ({})", key, agent.state_label());
+            return Ok(());
+        }
+        if sent_at.elapsed() >= timeout {
+            eprintln!(
+                "{} 秒以内に応答がありませんでした: {}",
+                timeout.as_secs(),
+                key
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Prints the last `lines` lines of the pane of the agent `target` names,
+/// then with `follow` its new output as it appears
+async fn tail_logs(config: &Config, target: &str, lines: usize, follow: bool) -> Result<()> {
+    let tmux_client = Arc::new(TmuxClient::with_capture_lines(config.capture_lines));
+    let tree = command_monitor(config, tmux_client.clone())?
+        .poll_once()
+        .await?;
+    let agent = find_target(config, &tmux_client, &tree.root_agents, target);
+    let client = tmux_client.for_server(&agent.server);
+    let mut captures = CaptureStore::new();
+    let mut shown = false;
+    loop {
+        let content = match client.capture_history(&agent.target, lines) {
+            Ok(content) => content,
+            Err(_) if follow => {
+                eprintln!("ペインが閉じられました: {}", agent.key());
+                std::process::exit(1);
+            }
+            Err(e) => return Err(e),
+        };
+        // The unused rows below the output aren't output
+        if let Some(change) = captures.update(&agent.target, content.trim_end()) {
+            let new_lines = &change.new_lines;
+            let skip = if shown {
+                0
+            } else {
+                new_lines.len().saturating_sub(lines)
+            };
+            for line in &new_lines[skip..] {
+                println!("{}", line);
+            }
+        }
+        if !follow {
+            return Ok(());
+        }
+        shown = true;
+        tokio::time::sleep(Duration::from_millis(config.poll_interval_ms)).await;
+    }
+}
+
+/// Prints the monitor's events, those of the daemon if it runs for `config`,
+/// as JSON lines until interrupted or the monitor stops
+async fn stream_events(config: &Config) -> Result<()> {
+    use std::io::Write;
+
+    let mut rx = if use_daemon(config) {
+        daemon::subscribe()?.updates
+    } else {
+        let tmux_client = Arc::new(TmuxClient::with_capture_lines(config.capture_lines));
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        let monitor = passive_monitor(config, tmux_client, tx)?;
+        tokio::spawn(monitor.run());
+        rx
+    };
+    let mut stream = EventStream::new();
+    let mut stdout = std::io::stdout().lock();
+    while let Some(update) = rx.recv().await {
+        for line in stream.lines(&update, chrono::Local::now()) {
+            // A closed pipe (`tmuxcc events | head`) ends the stream
+            if writeln!(stdout, "{}", line).is_err() {
+                return Ok(());
+            }
+        }
+        if stdout.flush().is_err() {
+            return Ok(());
+        }
+    }
+    Ok(())
+}
+
+/// Runs `tmuxcc daemon` in the foreground, or stops the running one
+async fn run_daemon(config: Config, stop: bool) -> Result<()> {
+    if !stop {
+        return daemon::run_daemon(config).await;
+    }
+    if !daemon::is_running() {
+        eprintln!("デーモンは動作していません");
+        std::process::exit(1);
+    }
+    daemon::request(&Request::Stop)?;
+    println!("デーモンを停止しました");
+    Ok(())
+}
+
+/// Switches the current tmux client to the agent `query` names, attaching
+/// to its session when run outside tmux or for a pane on another server
+///
+/// When several agents match, the one after the current pane is picked, so
+/// pressing the same key again cycles through them. Failures are also shown
+/// in the status line, `run-shell` hiding the output of a key binding.
+async fn jump_to_agent(config: &Config, query: &str) -> Result<()> {
+    let tmux_client = Arc::new(TmuxClient::with_capture_lines(config.capture_lines));
+    let tree = command_monitor(config, tmux_client.clone())?
+        .poll_once()
+        .await?;
+    let inside = TmuxClient::is_inside_tmux();
+    let fail = |message: String| -> ! {
+        if inside {
+            let _ = tmux_client.display_message(&format!("tmuxcc: {}", message));
+        }
+        eprintln!("{}", message);
+        std::process::exit(1);
+    };
+    let found = find_agents(&tree.root_agents, query)
+        .unwrap_or_else(|e| fail(format!("検索条件が正しくありません: {:#}", e)));
+    if found.is_empty() {
+        fail(format!(
+            "{:?} に一致するエージェントのペインがありません",
+            query
+        ));
+    }
+    let current = inside.then(|| tmux_client.current_pane()).flatten();
+    let next = found
+        .iter()
+        .position(|a| a.server.is_default() && current.as_ref() == Some(&a.pane_id))
+        .map_or(0, |i| (i + 1) % found.len());
+    let agent = found[next];
+    let client = tmux_client.for_server(&agent.server);
+    if inside && agent.server.is_default() {
+        return client.switch_client(&agent.target);
+    }
+    client.focus_pane(&agent.target)?;
+    client.attach_session(&agent.target)
+}
+
+/// Stops the agent `target` names, then kills its pane or, with `restart`,
+/// starts the agent again with its original command
+///
+/// The agent gets Ctrl-C twice (most agents quit on the second) and up to
+/// `grace` to exit before its processes are killed.
+async fn stop_agent(config: &Config, target: &str, grace: Duration, restart: bool) -> Result<()> {
+    let tmux_client = Arc::new(TmuxClient::with_capture_lines(config.capture_lines));
+    let mut monitor = command_monitor(config, tmux_client.clone())?;
+    let tree = monitor.poll_once().await?;
+    let agent = find_target(config, &tmux_client, &tree.root_agents, target).clone();
+    let key = agent.key();
+    let launch = match (restart, agent.launch.clone()) {
+        (true, None) => {
+            eprintln!("エージェントの起動コマンドがわかりません: {}", key);
+            std::process::exit(1);
+        }
+        (_, launch) => launch,
+    };
+    let client = tmux_client.for_server(&agent.server);
+    // Pane indexes shift when a pane closes; the pane id stays
+    let pane = match client.list_tree()?.find_pane(&agent.target) {
+        Some(pane) => pane.id.clone(),
+        None => anyhow::bail!("pane {} is gone", key),
+    };
+
+    // An agent that is the pane's process would take the pane with it
+    let keep_pane = restart && launch.as_ref().is_some_and(|l| !l.in_shell);
+    if keep_pane {
+        client.set_pane_option(&pane, "remain-on-exit", "on")?;
+    }
+    if !grace.is_zero() {
+        let started = std::time::Instant::now();
+        client.send_keys(&pane, "C-c")?;
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        // The first one may already have closed the pane
+        let _ = client.send_keys(&pane, "C-c");
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        loop {
+            let agents = monitor.poll_once().await?.root_agents;
+            if !agents.iter().any(|a| a.key() == key && a.pid == agent.pid) {
+                break;
+            }
+            if started.elapsed() >= grace {
+                eprintln!("終了しないため強制終了します: {}", key);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(config.poll_interval_ms)).await;
+        }
+    }
+
+    match launch.filter(|_| restart) {
+        Some(launch) => {
+            let respawned = spawn::respawn_agent(&client, &pane, &agent.path, &launch);
+            if keep_pane {
+                client.unset_pane_option(&pane, "remain-on-exit")?;
+            }
+            respawned?;
+            println!("再起動しました: {}", key);
+        }
+        None => {
+            // Gone already if the agent was the pane's process
+            if client.list_tree()?.find_pane(&pane).is_some() {
+                client.kill_pane(&pane)?;
+            }
+            println!("終了しました: {}", key);
+        }
+    }
+    Ok(())
+}
+
+/// Polls the agent `target` names until it is in `state`
+///
+/// Exits with 1 if the pane goes away, 2 once `timeout` passed and 3 if the
+/// agent reports an error meanwhile (unless that is the awaited state).
+async fn wait_for_state(
+    config: &Config,
+    target: &str,
+    state: StateFilter,
+    timeout: Option<Duration>,
+) -> Result<()> {
+    let tmux_client = Arc::new(TmuxClient::with_capture_lines(config.capture_lines));
+    let mut monitor = command_monitor(config, tmux_client.clone())?;
+    let tree = monitor.poll_once().await?;
+    let key = find_target(config, &tmux_client, &tree.root_agents, target).key();
+    let started = std::time::Instant::now();
+    let mut agents = tree.root_agents;
+    loop {
+        let Some(agent) = agents.iter().find(|a| a.key() == key) else {
+            eprintln!("エージェントのペインが見つかりません: {}", key);
+            std::process::exit(1);
+        };
+        if state.matches(agent) {
+            println!("{}: {}", key, agent.state_label());
+            return Ok(());
+        }
+        if agent.state == Some(AgentState::Error) && agent.state_since > started {
+            eprintln!("エージェントがエラーを報告しました: {}", key);
+            std::process::exit(3);
+        }
+        if timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+            eprintln!("タイムアウトしました: {} ({})", key, agent.state_label());
+            std::process::exit(2);
+        }
+        tokio::time::sleep(Duration::from_millis(config.poll_interval_ms)).await;
+        agents = monitor.poll_once().await?.root_agents;
+    }
+}
+
+/// How long `tmuxcc run` waits for an agent it launched to show up
+const AGENT_STARTUP: Duration = Duration::from_secs(30);
+
+/// Drives the agent in `pane` through the steps of the script at `path`
+///
+/// tmux is polled at the configured interval; exits with an error when a step
+/// times out, the agent reports an error or the pane goes away. The pane may
+/// go without a detected agent for `startup` first, while one is starting.
+async fn run_script(
+    config: &Config,
+    path: &std::path::Path,
+    pane: &str,
+    startup: Duration,
+) -> Result<()> {
+    let script = TaskScript::load(path).unwrap_or_else(|e| {
+        eprintln!("スクリプトの読み込みに失敗: {}", e);
+        std::process::exit(1);
+    });
+    let tmux_client = Arc::new(TmuxClient::with_capture_lines(config.capture_lines));
+    let (tx, _rx) = tokio::sync::mpsc::channel(1);
+    let mut monitor = MonitorTask::new(
+        tmux_client.clone(),
+        Arc::new(ParserRegistry::from_config(config)?),
+        tx,
+        Duration::from_millis(config.poll_interval_ms),
+    )
+    .with_servers(config.servers());
+    let mut run = ScriptRun::new(script);
+    let started = std::time::Instant::now();
+
+    loop {
+        let tree = monitor.poll_once().await?;
+        let Some(agent) = tree
+            .root_agents
+            .iter()
+            .find(|a| a.key() == pane || a.target == pane)
+        else {
+            if started.elapsed() < startup {
+                tokio::time::sleep(Duration::from_millis(config.poll_interval_ms)).await;
+                continue;
+            }
+            eprintln!("エージェントのペインが見つかりません: {}", pane);
+            std::process::exit(1);
+        };
+        match run.advance(agent, std::time::Instant::now()) {
+            Some(ScriptEvent::Send { step, text }) => {
+                println!("[{}/{}] {}", step + 1, run.len(), text);
+                let client = tmux_client.for_server(&agent.server);
+                client.send_text(&agent.target, &text, SendMode::for_text(&text))?;
+                client.send_keys(&agent.target, "Enter")?;
+            }
+            Some(ScriptEvent::Finished) => {
+                println!("スクリプトが完了しました");
+                return Ok(());
+            }
+            Some(ScriptEvent::TimedOut { step }) => {
+                eprintln!("手順 {} がタイムアウトしました", step + 1);
+                std::process::exit(1);
+            }
+            Some(ScriptEvent::Failed { step }) => {
+                eprintln!("手順 {} でエージェントがエラーを報告しました", step + 1);
+                std::process::exit(1);
+            }
+            None => {}
+        }
+        tokio::time::sleep(Duration::from_millis(config.poll_interval_ms)).await;
+    }
+}
+
+fn run_command(command: Commands, config: &Config) -> Result<()> {
+    match command {
+        Commands::Statusline
+        | Commands::Status
+        | Commands::Events
+        | Commands::Daemon { .. }
+        | Commands::Ls { .. }
+        | Commands::Popup
+        | Commands::Run { .. }
+        | Commands::Send { .. }
+        | Commands::Wait { .. }
+        | Commands::Logs { .. }
+        | Commands::Jump { .. }
+        | Commands::Kill { .. }
+        | Commands::Restart { .. }
+        | Commands::Broadcast { .. }
+        | Commands::Config { .. }
+        | Commands::Completions { .. }
+        | Commands::Complete { .. } => {
+            unreachable!("handled in main")
+        }
+        Commands::Headless { prompt, command } => {
+            let Some(stream) = std::env::var_os(STREAM_VAR) else {
+                // The monitor finds the stream in the process environment, so
+                // restart with it set
+                let pane_id = std::env::var("TMUX_PANE")
+                    .unwrap_or_else(|_| format!("pid-{}", std::process::id()));
+                let Some(stream) = headless::default_stream_path(&pane_id) else {
+                    eprintln!("キャッシュディレクトリが見つかりません");
+                    std::process::exit(1);
+                };
+                let error = std::process::Command::new(std::env::current_exe()?)
+                    .args(std::env::args_os().skip(1))
+                    .env(STREAM_VAR, stream)
+                    .exec();
+                eprintln!("tmuxcc headless の起動に失敗: {}", error);
+                std::process::exit(1);
+            };
+            if let Err(e) = headless::run(&command, std::path::Path::new(&stream), prompt) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::NotifyEvent {
+            socket,
+            event,
+            args,
+        } => {
+            // Stale hooks of an exited tmuxcc must not make tmux show errors
+            let _ = send_hook_event(&socket, &HookEvent { name: event, args });
+        }
+        Commands::New {
+            cwd,
+            session,
+            split,
+            vertical,
+            new_session,
+            agent,
+            headless,
+        } => {
+            let cwd = match cwd {
+                Some(cwd) => cwd,
+                None => std::env::current_dir()?,
+            };
+            let placement = match (split, new_session) {
+                (Some(target), _) => Placement::Split { target, vertical },
+                (None, Some(name)) => Placement::Session { name: Some(name) },
+                (None, None) => Placement::Window {
+                    session,
+                    name: None,
+                },
+            };
+            let mut agent = agent.unwrap_or_else(|| config.agent_command.clone());
+            if headless {
+                agent = format!("tmuxcc headless {}", agent);
+            }
+            let tmux = TmuxClient::new();
+            match spawn::launch_agent(&tmux, &placement, &cwd.to_string_lossy(), &agent) {
+                Ok(target) => println!("エージェントを起動しました: {}", target),
+                Err(e) => {
+                    eprintln!("エージェントの起動に失敗: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Layout {
+            action: LayoutCommand::Apply { name },
+        } => {
+            let Some(layout) = config.layouts.get(&name) else {
+                eprintln!("レイアウトが見つかりません: {}", name);
+                std::process::exit(1);
+            };
+            let cwd = std::env::current_dir()?;
+            let tmux = TmuxClient::new();
+            match spawn::apply_layout(&tmux, layout, &cwd.to_string_lossy()) {
+                Ok(targets) => {
+                    println!("レイアウト {} を作成しました: {}", name, targets.join(", "));
+                }
+                Err(e) => {
+                    eprintln!("レイアウトの作成に失敗: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Layout {
+            action: LayoutCommand::List,
+        } => {
+            if config.layouts.is_empty() {
+                println!("レイアウトが設定されていません");
+            }
+            for (name, layout) in &config.layouts {
+                println!(
+                    "{}\t{} ペイン ({})",
+                    name,
+                    layout.panes.len(),
+                    layout.arrangement
+                );
+            }
+        }
+        Commands::Worktree {
+            action:
+                WorktreeCommand::New {
+                    branch,
+                    repo,
+                    agent,
+                },
+        } => {
+            let repo = match repo {
+                Some(repo) => repo,
+                None => std::env::current_dir()?,
+            };
+            let agent = agent.unwrap_or_else(|| config.agent_command.clone());
+            let tmux = TmuxClient::new();
+            match spawn::spawn_worktree_agent(&tmux, &repo.to_string_lossy(), &branch, None, &agent)
+            {
+                Ok((path, target)) => {
+                    println!("worktree を作成しました: {}", path.display());
+                    println!("エージェントを起動しました: {}", target);
+                }
+                Err(e) => {
+                    eprintln!("worktree の作成に失敗: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+    Ok(())
+}
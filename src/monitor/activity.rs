@@ -0,0 +1,88 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Minutes of output counts kept per pane
+pub const ACTIVITY_MINUTES: usize = 10;
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+/// New output lines of one pane, per minute
+struct PaneActivity {
+    /// When the current minute started
+    minute_start: Instant,
+    /// Oldest first; the last one is the current minute
+    counts: VecDeque<u32>,
+}
+
+/// Counts the new output lines of each pane per minute
+#[derive(Default)]
+pub struct ActivityTracker {
+    panes: HashMap<String, PaneActivity>,
+}
+
+impl ActivityTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `new_lines` to the current minute of the pane at `key` and returns
+    /// its counts for the last [`ACTIVITY_MINUTES`] minutes, oldest first
+    ///
+    /// The first capture of a pane reports its whole screen as new, so the
+    /// lines of a pane seen for the first time aren't counted.
+    pub fn record(&mut self, key: &str, new_lines: usize, now: Instant) -> Vec<u32> {
+        let pane = match self.panes.get_mut(key) {
+            Some(pane) => {
+                let minutes = (now.duration_since(pane.minute_start).as_secs() / 60) as usize;
+                for _ in 0..minutes.min(ACTIVITY_MINUTES) {
+                    pane.counts.pop_front();
+                    pane.counts.push_back(0);
+                }
+                pane.minute_start += MINUTE * minutes as u32;
+                if let Some(current) = pane.counts.back_mut() {
+                    *current = current.saturating_add(new_lines as u32);
+                }
+                pane
+            }
+            None => self
+                .panes
+                .entry(key.to_string())
+                .or_insert_with(|| PaneActivity {
+                    minute_start: now,
+                    counts: VecDeque::from(vec![0; ACTIVITY_MINUTES]),
+                }),
+        };
+        pane.counts.iter().copied().collect()
+    }
+
+    /// Forgets panes whose keys are not in `live`
+    pub fn retain(&mut self, live: &HashSet<String>) {
+        self.panes.retain(|key, _| live.contains(key));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_per_minute() {
+        let mut tracker = ActivityTracker::new();
+        let start = Instant::now();
+
+        // The first screen isn't new output
+        assert_eq!(tracker.record("main:0.1", 40, start), vec![0; 10]);
+        tracker.record("main:0.1", 3, start + Duration::from_secs(10));
+        let counts = tracker.record("main:0.1", 2, start + Duration::from_secs(50));
+        assert_eq!(counts[9], 5);
+
+        let counts = tracker.record("main:0.1", 1, start + Duration::from_secs(185));
+        assert_eq!(&counts[6..], &[5, 0, 0, 1]);
+
+        let counts = tracker.record("main:0.1", 0, start + Duration::from_secs(3600));
+        assert_eq!(counts, vec![0; 10]);
+
+        tracker.retain(&HashSet::new());
+        assert!(tracker.panes.is_empty());
+    }
+}
@@ -0,0 +1,488 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use regex::Regex;
+use tracing::{debug, info};
+
+use crate::agents::{AgentStatus, AgentType, ApprovalType, MonitoredAgent};
+use crate::app::{AutoApproveConfig, ProjectConfig};
+use crate::parsers::compile_patterns;
+use crate::tmux::TmuxClient;
+use serde::{Deserialize, Serialize};
+
+/// Headers of Claude Code's permission dialogs and the tools they belong to
+const DIALOG_TOOLS: &[(&str, &str)] = &[
+    ("Bash command", "Bash"),
+    ("Read file", "Read"),
+    ("Edit file", "Edit"),
+    ("Create file", "Write"),
+    ("Write file", "Write"),
+    ("Fetch", "WebFetch"),
+    ("Tool use", "MCP"),
+];
+
+/// Lines above the dialog's question searched for its header
+const DIALOG_LINES: usize = 20;
+
+/// What an agent asks permission for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionRequest {
+    pub tool: String,
+    /// Command, path or tool arguments, as shown in the dialog; the lines
+    /// of a command continued over several are joined by spaces
+    pub input: String,
+    /// Every line between the dialog's header and its question
+    pub body: Vec<String>,
+}
+
+impl PermissionRequest {
+    /// Returns the request as rules see it, e.g. `Bash(git status)`
+    pub fn describe(&self) -> String {
+        format!("{}({})", self.tool, self.input)
+    }
+
+    /// Returns what deny rules are checked against: the request, then each
+    /// line of the dialog as if it were the input, since a command running
+    /// on after the first line may not look continued
+    fn deny_candidates(&self) -> Vec<String> {
+        std::iter::once(self.describe())
+            .chain(self.body.iter().map(|l| format!("{}({})", self.tool, l)))
+            .collect()
+    }
+}
+
+/// Strips the dialog's box drawing from a captured line
+fn dialog_text(line: &str) -> &str {
+    line.trim_matches(|c: char| c.is_whitespace() || matches!(c, '│' | '┃' | '|'))
+}
+
+/// Returns true for a dialog border line, e.g. `╭─────╮`
+fn is_border(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty()
+        && line
+            .chars()
+            .all(|c| matches!(c, '─' | '━' | '╭' | '╮' | '┌' | '┐'))
+}
+
+/// Returns true if a command line goes on in the next one, e.g. `make &&`
+fn continues(line: &str) -> bool {
+    ["\\", "&&", "||", "|"]
+        .iter()
+        .any(|end| line.ends_with(end))
+}
+
+/// Extracts the pending permission request from a Claude Code pane's output
+///
+/// The dialog is found from its question ("Do you want to ...") upwards to
+/// the top border: the first line below it names the tool, the next ones
+/// hold the command or path. `None` if the dialog isn't complete within
+/// [`DIALOG_LINES`] or its command runs into the question.
+pub fn permission_request(content: &str) -> Option<PermissionRequest> {
+    let lines: Vec<&str> = content.trim_end().lines().collect();
+    let question = lines.iter().rposition(|l| l.contains("Do you want to"))?;
+    let top = lines[..question]
+        .iter()
+        .rev()
+        .take(DIALOG_LINES)
+        .position(|l| is_border(l))
+        .map(|offset| question - 1 - offset)?;
+
+    let mut body = lines[top + 1..question]
+        .iter()
+        .map(|l| dialog_text(l))
+        .filter(|l| !l.is_empty());
+    let header = body.next()?;
+    let body: Vec<&str> = body.collect();
+    let mut command = body.len().min(1);
+    while command < body.len() && continues(body[command - 1]) {
+        command += 1;
+    }
+    if body[..command].last().is_some_and(|l| continues(l)) {
+        return None;
+    }
+
+    let tool = DIALOG_TOOLS
+        .iter()
+        .find(|(prefix, _)| header.starts_with(prefix))
+        .map_or(header, |(_, tool)| tool);
+    Some(PermissionRequest {
+        tool: tool.to_string(),
+        input: body[..command].join(" "),
+        body: body.iter().map(|l| l.to_string()).collect(),
+    })
+}
+
+/// An automated answer to a permission prompt
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalDecision {
+    /// Server-qualified pane target (see [`MonitoredAgent::key`])
+    pub key: String,
+    /// The request, as matched by the rules
+    pub request: String,
+    /// True if the prompt was approved, false if a deny rule kept it for the user
+    pub approved: bool,
+    /// The rule that decided
+    pub rule: String,
+    pub at: DateTime<Local>,
+}
+
+impl ApprovalDecision {
+    /// Returns the line written to the decision log
+    pub fn log_line(&self) -> String {
+        format!(
+            "{} {} {} {} [{}]",
+            self.at.format("%Y-%m-%d %H:%M:%S"),
+            if self.approved { "approve" } else { "deny" },
+            self.key,
+            self.request,
+            self.rule
+        )
+    }
+}
+
+/// Returns the pattern of the first rule matching `request`
+fn first_match<'a>(rules: &'a [(String, Regex)], request: &str) -> Option<&'a str> {
+    rules
+        .iter()
+        .find(|(_, regex)| regex.is_match(request))
+        .map(|(pattern, _)| pattern.as_str())
+}
+
+/// Returns the first of the uncompiled `patterns` matching `request`
+fn first_pattern<'a>(patterns: &'a [String], request: &str) -> Option<&'a str> {
+    // Checked when the project file was read
+    let regexes = compile_patterns(patterns, "auto_approve").ok()?;
+    patterns
+        .iter()
+        .zip(regexes)
+        .find(|(_, regex)| regex.is_match(request))
+        .map(|(pattern, _)| pattern.as_str())
+}
+
+/// Answers Claude Code permission prompts according to allow/deny rules
+pub struct AutoApprover {
+    allow: Vec<(String, Regex)>,
+    deny: Vec<(String, Regex)>,
+    /// Directories whose project files' allow rules apply
+    trusted: Vec<PathBuf>,
+    log_path: Option<PathBuf>,
+    /// Request last decided per pane, so a prompt still on screen isn't answered twice
+    decided: HashMap<String, String>,
+}
+
+impl AutoApprover {
+    /// Returns the default decision log path
+    pub fn default_log_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|p| p.join("tmuxcc").join("auto-approve.log"))
+    }
+
+    pub fn new(config: &AutoApproveConfig) -> Result<Self> {
+        let rules = |patterns: &[String], owner| -> Result<Vec<(String, Regex)>> {
+            let regexes = compile_patterns(patterns, owner)?;
+            Ok(patterns.iter().cloned().zip(regexes).collect())
+        };
+        Ok(Self {
+            allow: rules(&config.allow, "auto_approve.allow")?,
+            deny: rules(&config.deny, "auto_approve.deny")?,
+            trusted: config
+                .trusted_projects
+                .iter()
+                .map(|dir| match (dir.strip_prefix("~/"), dirs::home_dir()) {
+                    (Some(rest), Some(home)) => home.join(rest),
+                    _ => PathBuf::from(dir),
+                })
+                .collect(),
+            log_path: config.log_file.clone().or_else(Self::default_log_path),
+            decided: HashMap::new(),
+        })
+    }
+
+    /// Returns whether `request` is approved and the rule that says so, or
+    /// `None` when no rule applies
+    ///
+    /// Deny rules also see each line of the dialog on its own. The rules of
+    /// the pane's `project` come after the configured deny rules, so a
+    /// repository can't approve what the config denies, and before the
+    /// configured allow rules. Its allow rules only count if the project
+    /// file is in a trusted directory.
+    pub fn decide<'a>(
+        &'a self,
+        request: &PermissionRequest,
+        project: Option<&'a ProjectConfig>,
+    ) -> Option<(bool, &'a str)> {
+        let described = request.describe();
+        let candidates = request.deny_candidates();
+        candidates
+            .iter()
+            .find_map(|c| first_match(&self.deny, c))
+            .map(|rule| (false, rule))
+            .or_else(|| {
+                let deny = &project?.auto_approve.deny;
+                let rule = candidates.iter().find_map(|c| first_pattern(deny, c))?;
+                Some((false, rule))
+            })
+            .or_else(|| {
+                let project = project.filter(|p| self.trusts(&p.source))?;
+                let rule = first_pattern(&project.auto_approve.allow, &described)?;
+                Some((true, rule))
+            })
+            .or_else(|| first_match(&self.allow, &described).map(|rule| (true, rule)))
+    }
+
+    /// Returns true if the project file at `source` may approve requests
+    fn trusts(&self, source: &Path) -> bool {
+        self.trusted.iter().any(|dir| source.starts_with(dir))
+    }
+
+    /// Returns true if the pane at `key` showed a permission prompt at the last
+    /// [`AutoApprover::apply`], whether a rule approved it, denied it or left
+    /// it to the user; other automation keeps away from it
+    pub fn handles(&self, key: &str) -> bool {
+        self.decided.contains_key(key)
+    }
+
+    /// Answers the permission prompts of `agents` that a rule decides
+    ///
+    /// Each decision is made once per request and logged.
+    pub fn apply<'a>(
+        &mut self,
+        tmux_client: &TmuxClient,
+        agents: impl IntoIterator<Item = &'a MonitoredAgent>,
+    ) -> Vec<ApprovalDecision> {
+        let mut decisions = Vec::new();
+        let mut pending = HashMap::new();
+
+        for agent in agents {
+            let AgentStatus::AwaitingApproval { approval_type, .. } = &agent.status else {
+                continue;
+            };
+            // Questions need a real answer, other agents' prompts look different
+            if agent.agent_type != AgentType::ClaudeCode
+                || matches!(approval_type, ApprovalType::UserQuestion { .. })
+            {
+                continue;
+            }
+            let Some(request) = permission_request(&agent.last_content) else {
+                continue;
+            };
+            let key = agent.key();
+            let described = request.describe();
+            if self.decided.get(&key) == Some(&described) {
+                pending.insert(key, described);
+                continue;
+            }
+
+            if let Some((approved, rule)) = self.decide(&request, agent.project.as_deref()) {
+                let decision = ApprovalDecision {
+                    key: key.clone(),
+                    request: described.clone(),
+                    approved,
+                    rule: rule.to_string(),
+                    at: Local::now(),
+                };
+                if approved {
+                    let client = tmux_client.for_server(&agent.server);
+                    if let Err(e) = client
+                        .send_keys(&agent.target, "y")
+                        .and_then(|_| client.send_keys(&agent.target, "Enter"))
+                    {
+                        debug!("Failed to auto-approve {}: {}", key, e);
+                        continue;
+                    }
+                }
+                info!("Auto-approve: {}", decision.log_line());
+                self.log(&decision);
+                decisions.push(decision);
+            }
+            pending.insert(key, described);
+        }
+
+        // Panes no longer waiting may ask the same again later
+        self.decided = pending;
+        decisions
+    }
+
+    fn log(&self, decision: &ApprovalDecision) {
+        let Some(path) = self.log_path.as_ref() else {
+            return;
+        };
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| {
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+            })
+            .and_then(|mut file| writeln!(file, "{}", decision.log_line()));
+        if let Err(e) = result {
+            debug!("Failed to write auto-approve log: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASH_PROMPT: &str = "\
+⏺ Pushing the branch.
+
+╭──────────────────────────────────────────╮
+│ Bash command                             │
+│                                          │
+│   git push origin main                   │
+│   Push the branch to origin              │
+│                                          │
+│ Do you want to proceed?                  │
+│ ❯ 1. Yes                                 │
+│   2. No, and tell Claude what to do differently (esc) │
+╰──────────────────────────────────────────╯
+";
+
+    #[test]
+    fn test_permission_request() {
+        assert_eq!(
+            permission_request(BASH_PROMPT),
+            Some(PermissionRequest {
+                tool: "Bash".to_string(),
+                input: "git push origin main".to_string(),
+                body: vec![
+                    "git push origin main".to_string(),
+                    "Push the branch to origin".to_string(),
+                ],
+            })
+        );
+        let continued = BASH_PROMPT.replace(
+            "│   git push origin main                   │",
+            "│   git status && \\                        │\n│   rm -rf ~                               │",
+        );
+        assert_eq!(
+            permission_request(&continued).map(|r| r.describe()),
+            Some("Bash(git status && \\ rm -rf ~)".to_string())
+        );
+        // A command still going on at the question was cut off
+        let cut = "────────────\n Bash command\n\n  git status &&\n Do you want to proceed?\n";
+        assert_eq!(permission_request(cut), None);
+
+        let read =
+            "────────────\n Read file\n\n  src/main.rs\n\n Do you want to proceed?\n ❯ 1. Yes\n";
+        assert_eq!(
+            permission_request(read).map(|r| r.describe()),
+            Some("Read(src/main.rs)".to_string())
+        );
+        assert_eq!(permission_request("Do you want to proceed?\n"), None);
+    }
+
+    #[test]
+    fn test_decide() {
+        let approver = AutoApprover::new(&AutoApproveConfig {
+            enabled: true,
+            allow: vec![r"^(Read|Grep)\(".to_string(), r"^Bash\(git ".to_string()],
+            deny: vec![r"\brm\b".to_string(), "git push".to_string()],
+            trusted_projects: vec!["/code/trusted".to_string()],
+            log_file: None,
+        })
+        .unwrap();
+        let request = |tool: &str, input: &str| PermissionRequest {
+            tool: tool.to_string(),
+            input: input.to_string(),
+            body: vec![input.to_string()],
+        };
+
+        assert_eq!(
+            approver.decide(&request("Read", "src/main.rs"), None),
+            Some((true, r"^(Read|Grep)\("))
+        );
+        assert_eq!(
+            approver.decide(&request("Bash", "git status"), None),
+            Some((true, r"^Bash\(git "))
+        );
+        assert_eq!(
+            approver.decide(&request("Bash", "git push origin main"), None),
+            Some((false, "git push"))
+        );
+        assert_eq!(approver.decide(&request("Bash", "cargo build"), None), None);
+        // A line the allowed command doesn't seem to continue into is still denied
+        let mut chained = request("Bash", "git status");
+        chained.body.push("rm -rf ~".to_string());
+        assert_eq!(approver.decide(&chained, None), Some((false, r"\brm\b")));
+
+        let mut project = ProjectConfig {
+            auto_approve: crate::app::ProjectAutoApprove {
+                allow: vec![r"^Bash\(cargo ".to_string(), "git push".to_string()],
+                deny: vec![r"^Read\(\.env".to_string()],
+            },
+            source: PathBuf::from("/code/cloned/.tmuxcc.toml"),
+            ..ProjectConfig::default()
+        };
+        // Only the deny rules of an untrusted project count
+        assert_eq!(
+            approver.decide(&request("Bash", "cargo build"), Some(&project)),
+            None
+        );
+        assert_eq!(
+            approver.decide(&request("Read", ".env"), Some(&project)),
+            Some((false, r"^Read\(\.env"))
+        );
+        project.source = PathBuf::from("/code/trusted/api/.tmuxcc.toml");
+        assert_eq!(
+            approver.decide(&request("Bash", "cargo build"), Some(&project)),
+            Some((true, r"^Bash\(cargo "))
+        );
+        assert_eq!(
+            approver.decide(&request("Read", ".env"), Some(&project)),
+            Some((false, r"^Read\(\.env"))
+        );
+        // The configured deny rules still win
+        assert_eq!(
+            approver.decide(&request("Bash", "git push origin main"), Some(&project)),
+            Some((false, "git push"))
+        );
+    }
+
+    #[test]
+    fn test_apply_denied() {
+        let mut approver = AutoApprover::new(&AutoApproveConfig {
+            enabled: true,
+            deny: vec!["git push".to_string()],
+            ..AutoApproveConfig::default()
+        })
+        .unwrap();
+        approver.log_path = None;
+        let mut agent = MonitoredAgent::new(
+            "main:0.1-42".to_string(),
+            "main:0.1".to_string(),
+            "main".to_string(),
+            0,
+            "editor".to_string(),
+            1,
+            "/code/app".to_string(),
+            AgentType::ClaudeCode,
+            42,
+        );
+        agent.status = AgentStatus::AwaitingApproval {
+            approval_type: ApprovalType::ShellCommand,
+            details: String::new(),
+        };
+        agent.last_content = BASH_PROMPT.to_string();
+
+        // Denying types nothing, so no tmux server is needed
+        let decisions = approver.apply(&TmuxClient::new(), [&agent]);
+        assert_eq!(decisions.len(), 1);
+        assert!(!decisions[0].approved);
+        assert!(approver.handles("main:0.1"));
+        // Still handled while the prompt stays on screen
+        assert!(approver.apply(&TmuxClient::new(), [&agent]).is_empty());
+        assert!(approver.handles("main:0.1"));
+
+        agent.status = AgentStatus::Idle;
+        approver.apply(&TmuxClient::new(), [&agent]);
+        assert!(!approver.handles("main:0.1"));
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::Result;
+use parking_lot::Mutex;
+use regex::Regex;
+use tracing::{debug, info};
+
+use crate::agents::{AgentStatus, ApprovalType, MonitoredAgent};
+use crate::app::AutoRespondRule;
+use crate::parsers::compile_patterns;
+use crate::tmux::{SendMode, TmuxClient};
+use serde::{Deserialize, Serialize};
+
+/// Lines at the bottom of a pane a question must appear in to be answered
+const QUESTION_LINES: usize = 8;
+
+/// Switches shared by the UI, which flips them, and the monitor, which obeys them
+pub type SharedSwitches = Arc<Mutex<AutomationSwitches>>;
+
+/// Whether prompts may be answered without the user (auto-approve and auto-respond)
+#[derive(Debug, Default)]
+pub struct AutomationSwitches {
+    /// Kill switch: nothing is answered while set
+    pub paused: bool,
+    /// Panes excluded from automatic answers, by server-qualified target
+    pub disabled: HashSet<String>,
+}
+
+impl AutomationSwitches {
+    /// Wraps the switches for sharing between the monitor and the UI
+    pub fn shared(self) -> SharedSwitches {
+        Arc::new(Mutex::new(self))
+    }
+
+    /// Returns true if the agent at `key` may be answered automatically
+    pub fn allows(&self, key: &str) -> bool {
+        !self.paused && !self.disabled.contains(key)
+    }
+}
+
+/// An automatic reply sent to a pane
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutoResponse {
+    /// Server-qualified pane target (see [`MonitoredAgent::key`])
+    pub key: String,
+    /// The line that matched
+    pub question: String,
+    pub reply: String,
+}
+
+/// Replies to routine questions according to the config's `auto_respond` rules
+pub struct AutoResponder {
+    rules: Vec<(Regex, String)>,
+    /// Question last answered per pane, kept while it is on screen so it
+    /// isn't answered again
+    answered: HashMap<String, String>,
+}
+
+impl AutoResponder {
+    pub fn new(rules: &[AutoRespondRule]) -> Result<Self> {
+        let patterns: Vec<String> = rules.iter().map(|r| r.pattern.clone()).collect();
+        let regexes = compile_patterns(&patterns, "auto_respond")?;
+        Ok(Self {
+            rules: regexes
+                .into_iter()
+                .zip(rules.iter().map(|r| r.reply.clone()))
+                .collect(),
+            answered: HashMap::new(),
+        })
+    }
+
+    /// Returns the question line at the bottom of `content` matching a rule,
+    /// with that rule's reply
+    pub fn find_question<'a>(&'a self, content: &'a str) -> Option<(&'a str, &'a str)> {
+        let lines: Vec<&str> = content.trim_end().lines().collect();
+        let recent = &lines[lines.len().saturating_sub(QUESTION_LINES)..];
+        recent.iter().rev().find_map(|line| {
+            self.rules
+                .iter()
+                .find(|(regex, _)| regex.is_match(line))
+                .map(|(_, reply)| {
+                    (
+                        line.trim_matches(|c: char| c.is_whitespace() || c == '│'),
+                        reply.as_str(),
+                    )
+                })
+        })
+    }
+
+    /// Returns the question `agent` waits on and its reply, `None` if it
+    /// doesn't wait or the question was answered already
+    fn due<'a>(&'a self, agent: &'a MonitoredAgent) -> Option<(&'a str, &'a str)> {
+        // Permission prompts are left to the approver and the user
+        if !matches!(
+            agent.status,
+            AgentStatus::AwaitingApproval {
+                approval_type: ApprovalType::UserQuestion { .. } | ApprovalType::Other(_),
+                ..
+            }
+        ) {
+            return None;
+        }
+        let (question, reply) = self.find_question(&agent.last_content)?;
+        let answered = self.answered.get(&agent.key()).map(String::as_str);
+        (answered != Some(question)).then_some((question, reply))
+    }
+
+    /// Answers the questions of the waiting `agents`, each once while it
+    /// stays on screen
+    ///
+    /// The reply is typed and submitted with Enter; an empty reply only presses Enter.
+    pub fn apply<'a>(
+        &mut self,
+        tmux_client: &TmuxClient,
+        agents: impl IntoIterator<Item = &'a MonitoredAgent>,
+    ) -> Vec<AutoResponse> {
+        let mut responses = Vec::new();
+        let mut answered = HashMap::new();
+
+        for agent in agents {
+            let key = agent.key();
+            let Some((question, reply)) = self.due(agent) else {
+                // Remembered until the question scrolls away, whatever the
+                // agent does meanwhile
+                if let Some(previous) = self.answered.get(&key) {
+                    if self.find_question(&agent.last_content).is_some() {
+                        answered.insert(key, previous.clone());
+                    }
+                }
+                continue;
+            };
+            let client = tmux_client.for_server(&agent.server);
+            let result = if reply.is_empty() {
+                Ok(())
+            } else {
+                client.send_text(&agent.target, reply, SendMode::for_text(reply))
+            }
+            .and_then(|()| client.send_keys(&agent.target, "Enter"));
+            if let Err(e) = result {
+                debug!("Failed to auto-respond to {}: {}", key, e);
+                continue;
+            }
+            info!("Auto-respond: {} {:?} → {:?}", key, question, reply);
+            responses.push(AutoResponse {
+                key: key.clone(),
+                question: question.to_string(),
+                reply: reply.to_string(),
+            });
+            answered.insert(key, question.to_string());
+        }
+
+        self.answered = answered;
+        responses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::AgentType;
+
+    #[test]
+    fn test_find_question() {
+        let responder = AutoResponder::new(&[
+            AutoRespondRule {
+                pattern: r"Continue\?".to_string(),
+                reply: "yes".to_string(),
+            },
+            AutoRespondRule {
+                pattern: "Do you trust the files in this folder".to_string(),
+                reply: String::new(),
+            },
+        ])
+        .unwrap();
+
+        let content = "Step 1 done.\nContinue? (yes/no)\n> ";
+        assert_eq!(
+            responder.find_question(content),
+            Some(("Continue? (yes/no)", "yes"))
+        );
+        let trust = "╭────╮\n│ Do you trust the files in this folder?\n│ ❯ 1. Yes, proceed\n│   2. No, exit\n╰────╯\n";
+        assert_eq!(
+            responder.find_question(trust),
+            Some(("Do you trust the files in this folder?", ""))
+        );
+
+        // Questions scrolled up have been dealt with already
+        let old = format!("Continue?\n{}", "output\n".repeat(QUESTION_LINES));
+        assert_eq!(responder.find_question(&old), None);
+    }
+
+    #[test]
+    fn test_due() {
+        let mut responder = AutoResponder::new(&[AutoRespondRule {
+            pattern: r"Continue\?".to_string(),
+            reply: "yes".to_string(),
+        }])
+        .unwrap();
+        let mut agent = MonitoredAgent::new(
+            "main:0.1-42".to_string(),
+            "main:0.1".to_string(),
+            "main".to_string(),
+            0,
+            "editor".to_string(),
+            1,
+            "/code/app".to_string(),
+            AgentType::ClaudeCode,
+            42,
+        );
+        agent.last_content = "Continue? (yes/no)\n> ".to_string();
+
+        // Output mentioning the question while working isn't answered
+        agent.status = AgentStatus::Processing {
+            activity: String::new(),
+        };
+        assert_eq!(responder.due(&agent), None);
+        agent.status = AgentStatus::AwaitingApproval {
+            approval_type: ApprovalType::UserQuestion {
+                choices: Vec::new(),
+                multi_select: false,
+            },
+            details: String::new(),
+        };
+        assert_eq!(responder.due(&agent), Some(("Continue? (yes/no)", "yes")));
+        responder
+            .answered
+            .insert(agent.key(), "Continue? (yes/no)".to_string());
+        assert_eq!(responder.due(&agent), None);
+
+        // A permission prompt, e.g. one a deny rule kept for the user, gets
+        // no reply even if a rule matches it
+        let mut responder = AutoResponder::new(&[AutoRespondRule {
+            pattern: "Do you want to proceed".to_string(),
+            reply: String::new(),
+        }])
+        .unwrap();
+        agent.last_content =
+            "Bash command\n  git push\nDo you want to proceed?\n❯ 1. Yes".to_string();
+        agent.status = AgentStatus::AwaitingApproval {
+            approval_type: ApprovalType::ShellCommand,
+            details: String::new(),
+        };
+        assert_eq!(responder.due(&agent), None);
+        responder.answered.clear();
+        agent.status = AgentStatus::AwaitingApproval {
+            approval_type: ApprovalType::Other("Continue".to_string()),
+            details: String::new(),
+        };
+        assert_eq!(responder.due(&agent), Some(("Do you want to proceed?", "")));
+    }
+
+    #[test]
+    fn test_switches() {
+        let mut switches = AutomationSwitches::default();
+        switches.disabled.insert("main:0.1".to_string());
+        assert!(switches.allows("main:0.0"));
+        assert!(!switches.allows("main:0.1"));
+        switches.paused = true;
+        assert!(!switches.allows("main:0.0"));
+    }
+}
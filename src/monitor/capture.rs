@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Output that appeared in a pane since the previous capture
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaneChanged {
+    /// Pane target (e.g., "main:0.1")
+    pub target: String,
+    /// Lines not present in the previous capture
+    pub new_lines: Vec<String>,
+}
+
+struct CapturedPane {
+    hash: u64,
+    lines: Vec<String>,
+    /// Whether the pane was captured since the last prune
+    used: bool,
+}
+
+/// Last captured content per pane, used to detect and extract new output
+#[derive(Default)]
+pub struct CaptureStore {
+    panes: HashMap<String, CapturedPane>,
+}
+
+impl CaptureStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a capture of `target`, returning the change if the content differs
+    ///
+    /// The first capture of a pane reports all of its lines as new.
+    pub fn update(&mut self, target: &str, content: &str) -> Option<PaneChanged> {
+        let hash = hash_content(content);
+
+        if let Some(pane) = self.panes.get_mut(target) {
+            pane.used = true;
+            if pane.hash == hash {
+                return None;
+            }
+            let lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+            let new_lines = new_lines(&pane.lines, &lines);
+            pane.hash = hash;
+            pane.lines = lines;
+            return Some(PaneChanged {
+                target: target.to_string(),
+                new_lines,
+            });
+        }
+
+        let lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+        self.panes.insert(
+            target.to_string(),
+            CapturedPane {
+                hash,
+                lines: lines.clone(),
+                used: true,
+            },
+        );
+        Some(PaneChanged {
+            target: target.to_string(),
+            new_lines: lines,
+        })
+    }
+
+    /// Keeps the last capture of `target` through the next prune without
+    /// capturing it again
+    pub fn keep(&mut self, target: &str) {
+        if let Some(pane) = self.panes.get_mut(target) {
+            pane.used = true;
+        }
+    }
+
+    /// Drops panes not captured since the previous prune (call once per poll)
+    pub fn prune_unused(&mut self) {
+        self.panes.retain(|_, pane| std::mem::take(&mut pane.used));
+    }
+}
+
+fn hash_content(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns the lines of `new` that follow the part it shares with `old`
+///
+/// A capture is a fixed-size window over the scrollback, so new output shows
+/// up as `old` shifted up with lines appended. The last old line is often a
+/// prompt or progress line that gets rewritten, so if no exact overlap exists
+/// the overlap is retried without it. Without any overlap everything is new.
+pub fn new_lines(old: &[String], new: &[String]) -> Vec<String> {
+    if let Some(start) = overlap_end(old, new) {
+        return new[start..].to_vec();
+    }
+    if let Some((_, head)) = old.split_last() {
+        if let Some(start) = overlap_end(head, new) {
+            return new[start..].to_vec();
+        }
+    }
+    new.to_vec()
+}
+
+/// Finds the longest suffix of `old` that is a prefix of `new`, returning its length
+fn overlap_end(old: &[String], new: &[String]) -> Option<usize> {
+    (0..old.len()).find_map(|shift| {
+        let overlap = old.len() - shift;
+        (overlap <= new.len() && old[shift..] == new[..overlap]).then_some(overlap)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(s: &[&str]) -> Vec<String> {
+        s.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_new_lines_scrolled() {
+        let old = lines(&["a", "b", "c"]);
+        assert_eq!(new_lines(&old, &lines(&["b", "c", "d"])), lines(&["d"]));
+        assert_eq!(
+            new_lines(&old, &lines(&["a", "b", "c", "d", "e"])),
+            lines(&["d", "e"])
+        );
+        assert_eq!(new_lines(&old, &lines(&["x", "y"])), lines(&["x", "y"]));
+    }
+
+    #[test]
+    fn test_new_lines_rewritten_last_line() {
+        let old = lines(&["a", "b", "> typing"]);
+        let new = lines(&["a", "b", "> typing done", "output"]);
+        assert_eq!(new_lines(&old, &new), lines(&["> typing done", "output"]));
+    }
+
+    #[test]
+    fn test_capture_store() {
+        let mut store = CaptureStore::new();
+        let first = store.update("main:0.0", "a\nb").unwrap();
+        assert_eq!(first.new_lines, lines(&["a", "b"]));
+        assert!(store.update("main:0.0", "a\nb").is_none());
+        let change = store.update("main:0.0", "a\nb\nc").unwrap();
+        assert_eq!(change.new_lines, lines(&["c"]));
+
+        store.prune_unused();
+        store.prune_unused();
+        // Pruned panes are reported in full again
+        assert_eq!(
+            store.update("main:0.0", "a\nb\nc").unwrap().new_lines.len(),
+            3
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+
+use crate::agents::MonitoredAgent;
+use serde::{Deserialize, Serialize};
+
+/// An agent's context left dropped below the warning threshold
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextWarning {
+    /// Server-qualified pane target (see [`MonitoredAgent::key`])
+    pub key: String,
+    /// Context left, in percent
+    pub remaining: u8,
+}
+
+impl ContextWarning {
+    /// Returns the message shown to the user
+    pub fn message(&self) -> String {
+        format!(
+            "{}: {}% context left, auto-compact is near",
+            self.key, self.remaining
+        )
+    }
+}
+
+/// Reports each agent once when its context left crosses below a threshold
+///
+/// A pane is warned about again only after its context went back above the
+/// threshold, e.g. after a compaction or a new session.
+pub struct ContextWatch {
+    threshold: u8,
+    warned: HashSet<String>,
+}
+
+impl ContextWatch {
+    /// Warns below `threshold` percent of context left
+    pub fn new(threshold: u8) -> Self {
+        Self {
+            threshold,
+            warned: HashSet::new(),
+        }
+    }
+
+    pub fn check(&mut self, agents: &[MonitoredAgent]) -> Vec<ContextWarning> {
+        let mut warnings = Vec::new();
+        let mut low = HashSet::new();
+        for agent in agents {
+            let key = agent.key();
+            match agent.context_remaining {
+                Some(remaining) if remaining < self.threshold => {
+                    if !self.warned.contains(&key) {
+                        warnings.push(ContextWarning {
+                            key: key.clone(),
+                            remaining,
+                        });
+                    }
+                    low.insert(key);
+                }
+                // The percentage isn't always on screen; still low until shown otherwise
+                None if self.warned.contains(&key) => {
+                    low.insert(key);
+                }
+                _ => {}
+            }
+        }
+        self.warned = low;
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::AgentType;
+
+    #[test]
+    fn test_warns_once_per_crossing() {
+        let mut agent = MonitoredAgent::new(
+            "%1-100".to_string(),
+            "main:0.0".to_string(),
+            "main".to_string(),
+            0,
+            "api".to_string(),
+            0,
+            "/repo".to_string(),
+            AgentType::ClaudeCode,
+            100,
+        );
+        let mut watch = ContextWatch::new(15);
+        let mut check = |remaining| {
+            agent.context_remaining = remaining;
+            watch.check(std::slice::from_ref(&agent)).len()
+        };
+
+        assert_eq!(check(Some(40)), 0);
+        assert_eq!(check(Some(12)), 1);
+        assert_eq!(check(Some(9)), 0);
+        // The percentage isn't shown while the agent works
+        assert_eq!(check(None), 0);
+        assert_eq!(check(Some(8)), 0);
+        // Compacted, then low again
+        assert_eq!(check(Some(90)), 0);
+        assert_eq!(check(Some(14)), 1);
+    }
+}
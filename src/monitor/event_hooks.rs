@@ -0,0 +1,239 @@
+use std::collections::HashSet;
+use std::process::{Command, Stdio};
+
+use tracing::{info, warn};
+
+use super::state_machine::StateTransition;
+use super::watchdog::Stall;
+use crate::agents::{AgentState, MonitoredAgent};
+use crate::app::{HooksConfig, NotifyOn};
+
+/// Returns the `TMUXCC_*` variables describing `agent` to a hook
+fn pane_env(agent: &MonitoredAgent) -> Vec<(&'static str, String)> {
+    vec![
+        ("TMUXCC_PANE", agent.key()),
+        ("TMUXCC_PANE_ID", agent.pane_id.clone()),
+        ("TMUXCC_LABEL", agent.label.clone().unwrap_or_default()),
+        ("TMUXCC_PATH", agent.path.clone()),
+        ("TMUXCC_REPO", agent.repo_root()),
+        (
+            "TMUXCC_BRANCH",
+            agent
+                .git
+                .as_ref()
+                .map(|g| g.branch.clone())
+                .unwrap_or_default(),
+        ),
+        ("TMUXCC_AGENT", agent.agent_type.short_name().to_string()),
+        ("TMUXCC_SNIPPET", agent.snippet()),
+        ("TMUXCC_COST", format!("{:.2}", agent.stats.usage.cost_usd)),
+    ]
+}
+
+/// Returns the command running `hook` for `event` in `agent`
+fn hook_command(
+    hook: &str,
+    event: &str,
+    agent: &MonitoredAgent,
+    extra: &[(&'static str, String)],
+) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(hook).env("TMUXCC_EVENT", event);
+    command.envs(pane_env(agent)).envs(extra.iter().cloned());
+    command
+}
+
+/// Runs the configured shell hooks on state changes, stalls and panes
+/// reaching the cost threshold
+///
+/// Hooks run in the background; the cost hook runs once per pane until its
+/// cost drops below the threshold again (a new session).
+pub struct EventHooks {
+    config: HooksConfig,
+    /// Panes whose cost reached the threshold
+    over_cost: HashSet<String>,
+}
+
+impl EventHooks {
+    pub fn new(config: HooksConfig) -> Self {
+        Self {
+            config,
+            over_cost: HashSet::new(),
+        }
+    }
+
+    /// Switches to the commands of a reloaded config, keeping track of the
+    /// panes already over the cost threshold
+    pub fn set_config(&mut self, config: HooksConfig) {
+        self.config = config;
+    }
+
+    /// Runs the hooks of this poll's transitions, stalls and costs
+    pub fn apply(
+        &mut self,
+        transitions: &[StateTransition],
+        stalls: &[Stall],
+        agents: &[MonitoredAgent],
+    ) {
+        let find = |key: &str| agents.iter().find(|a| a.key() == key);
+        let states = |t: &StateTransition| {
+            vec![
+                (
+                    "TMUXCC_FROM",
+                    t.from.map_or("", AgentState::label).to_string(),
+                ),
+                ("TMUXCC_TO", t.to.label().to_string()),
+            ]
+        };
+
+        for transition in transitions.iter().filter(|t| t.from.is_some()) {
+            let Some(agent) = find(&transition.key) else {
+                continue;
+            };
+            let state_change = &self.config.state_change;
+            if !state_change.is_empty() {
+                run(hook_command(
+                    state_change,
+                    "state_change",
+                    agent,
+                    &states(transition),
+                ));
+            }
+            if let Some(event) = NotifyOn::from_transition(transition.from, transition.to) {
+                let hook = self.config.for_event(event);
+                if !hook.is_empty() {
+                    run(hook_command(
+                        hook,
+                        event_name(event),
+                        agent,
+                        &states(transition),
+                    ));
+                }
+            }
+        }
+
+        if !self.config.stalled.is_empty() {
+            for stall in stalls {
+                if let Some(agent) = find(&stall.key) {
+                    let quiet = [("TMUXCC_QUIET_SECS", stall.quiet.as_secs().to_string())];
+                    run(hook_command(&self.config.stalled, "stalled", agent, &quiet));
+                }
+            }
+        }
+
+        if self.config.cost_threshold > 0.0 {
+            let mut over = HashSet::new();
+            for agent in agents {
+                if agent.stats.usage.cost_usd < self.config.cost_threshold {
+                    continue;
+                }
+                let key = agent.key();
+                if !self.over_cost.contains(&key) && !self.config.cost.is_empty() {
+                    info!("{} reached ${:.2}", key, self.config.cost_threshold);
+                    run(hook_command(&self.config.cost, "cost", agent, &[]));
+                }
+                over.insert(key);
+            }
+            self.over_cost = over;
+        }
+    }
+}
+
+/// Returns the `TMUXCC_EVENT` value of `event`
+fn event_name(event: NotifyOn) -> &'static str {
+    match event {
+        NotifyOn::Permission => "permission",
+        NotifyOn::Input => "input",
+        NotifyOn::Error => "error",
+        NotifyOn::Finished => "finished",
+        NotifyOn::Stalled => "stalled",
+    }
+}
+
+/// Starts a hook without waiting for it
+fn run(mut command: Command) {
+    let spawned = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match spawned {
+        // Reap the hook without holding up the poll
+        Ok(mut child) => {
+            std::thread::spawn(move || child.wait());
+        }
+        Err(e) => warn!("Failed to run hook: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::AgentType;
+
+    #[test]
+    fn test_hook_environment() {
+        let mut agent = MonitoredAgent::new(
+            "main:0.1-42".to_string(),
+            "main:0.1".to_string(),
+            "main".to_string(),
+            0,
+            "editor".to_string(),
+            1,
+            "/code/app".to_string(),
+            AgentType::ClaudeCode,
+            42,
+        );
+        agent.pane_id = "%3".to_string();
+        agent.stats.usage.cost_usd = 1.5;
+        let command = hook_command(
+            "echo $TMUXCC_PANE",
+            "error",
+            &agent,
+            &[("TMUXCC_TO", "ERROR".to_string())],
+        );
+        let env: Vec<(String, String)> = command
+            .get_envs()
+            .map(|(k, v)| {
+                let v = v.map(|v| v.to_string_lossy().into_owned());
+                (k.to_string_lossy().into_owned(), v.unwrap_or_default())
+            })
+            .collect();
+        let get = |name: &str| env.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str());
+        assert_eq!(get("TMUXCC_EVENT"), Some("error"));
+        assert_eq!(get("TMUXCC_PANE"), Some("main:0.1"));
+        assert_eq!(get("TMUXCC_PANE_ID"), Some("%3"));
+        assert_eq!(get("TMUXCC_REPO"), Some("/code/app"));
+        assert_eq!(get("TMUXCC_COST"), Some("1.50"));
+        assert_eq!(get("TMUXCC_TO"), Some("ERROR"));
+    }
+
+    #[test]
+    fn test_cost_threshold() {
+        let mut agent = MonitoredAgent::new(
+            "%3".to_string(),
+            "main:0.1".to_string(),
+            "main".to_string(),
+            0,
+            "editor".to_string(),
+            1,
+            "/code/app".to_string(),
+            AgentType::ClaudeCode,
+            42,
+        );
+        let mut hooks = EventHooks::new(HooksConfig {
+            cost: "true".to_string(),
+            cost_threshold: 5.0,
+            ..HooksConfig::default()
+        });
+        let mut check = |cost| {
+            agent.stats.usage.cost_usd = cost;
+            hooks.apply(&[], &[], std::slice::from_ref(&agent));
+            hooks.over_cost.len()
+        };
+        assert_eq!(check(4.0), 0);
+        assert_eq!(check(5.2), 1);
+        assert_eq!(check(6.0), 1);
+        assert_eq!(check(0.1), 0);
+    }
+}
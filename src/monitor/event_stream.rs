@@ -0,0 +1,218 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Local};
+
+use super::task::MonitorUpdate;
+use crate::agents::{AgentState, MonitoredAgent};
+use crate::app::NotifyOn;
+use crate::parsers::Json;
+
+/// Turns monitor updates into the JSON lines of `tmuxcc events`
+///
+/// Every line is an object with the `event` (`pane_added`, `pane_removed`,
+/// `state`, `output` or `notification`), the time `at` and the `pane` key.
+/// Panes seen for the first time are reported as added with their state,
+/// not with their whole capture as output.
+#[derive(Default)]
+pub struct EventStream {
+    panes: HashSet<String>,
+}
+
+impl EventStream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the lines for `update`, received at `now`
+    pub fn lines(&mut self, update: &MonitorUpdate, now: DateTime<Local>) -> Vec<String> {
+        let agents = &update.agents.root_agents;
+        let mut events = Vec::new();
+
+        let live: HashSet<String> = agents.iter().map(MonitoredAgent::key).collect();
+        for agent in agents.iter().filter(|a| !self.panes.contains(&a.key())) {
+            events.push(event("pane_added", &agent.key(), now, pane_fields(agent)));
+        }
+        let mut gone: Vec<&String> = self.panes.difference(&live).collect();
+        gone.sort();
+        for key in gone {
+            events.push(event("pane_removed", key, now, Vec::new()));
+        }
+
+        for transition in &update.transitions {
+            let fields = vec![
+                ("from", transition.from.map_or(Json::Null, state)),
+                ("to", state(transition.to)),
+            ];
+            events.push(event("state", &transition.key, transition.at, fields));
+        }
+        for change in &update.changes {
+            // The unused rows below the output aren't output
+            let end = change
+                .new_lines
+                .iter()
+                .rposition(|line| !line.trim().is_empty())
+                .map_or(0, |i| i + 1);
+            if !self.panes.contains(&change.target) || end == 0 {
+                continue;
+            }
+            let lines = change.new_lines[..end]
+                .iter()
+                .cloned()
+                .map(Json::String)
+                .collect();
+            let fields = vec![("lines", Json::Array(lines))];
+            events.push(event("output", &change.target, now, fields));
+        }
+
+        let notification = |kind: &str, message: String| {
+            vec![
+                ("kind", Json::String(kind.to_string())),
+                ("message", Json::String(message)),
+            ]
+        };
+        for transition in &update.transitions {
+            let Some(kind) = NotifyOn::from_transition(transition.from, transition.to) else {
+                continue;
+            };
+            let message = format!("{} {}", transition.key, kind.text());
+            let fields = notification(&format!("{:?}", kind).to_lowercase(), message);
+            events.push(event(
+                "notification",
+                &transition.key,
+                transition.at,
+                fields,
+            ));
+        }
+        for stall in &update.stalls {
+            let fields = notification("stalled", stall.message());
+            events.push(event("notification", &stall.key, now, fields));
+        }
+        for warning in &update.context_warnings {
+            let fields = notification("context", warning.message());
+            events.push(event("notification", &warning.key, now, fields));
+        }
+
+        self.panes = live;
+        events
+    }
+}
+
+/// Returns an event line: `event`, `at` and `pane` followed by `fields`
+fn event(name: &str, pane: &str, at: DateTime<Local>, fields: Vec<(&'static str, Json)>) -> String {
+    let mut members = vec![
+        ("event".to_string(), Json::String(name.to_string())),
+        ("at".to_string(), Json::String(at.to_rfc3339())),
+        ("pane".to_string(), Json::String(pane.to_string())),
+    ];
+    members.extend(fields.into_iter().map(|(k, v)| (k.to_string(), v)));
+    Json::Object(members).to_string()
+}
+
+fn state(state: AgentState) -> Json {
+    Json::String(state.label().to_string())
+}
+
+/// Returns the fields describing a newly seen pane
+fn pane_fields(agent: &MonitoredAgent) -> Vec<(&'static str, Json)> {
+    let text = |s: &str| Json::String(s.to_string());
+    vec![
+        ("agent", text(agent.agent_type.display_name())),
+        ("state", text(agent.state_label())),
+        ("path", text(&agent.path)),
+        ("label", agent.label.as_deref().map_or(Json::Null, text)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::AgentType;
+    use crate::app::AgentTree;
+    use crate::monitor::{PaneChanged, StateTransition};
+
+    #[test]
+    fn test_event_stream() {
+        let agent = MonitoredAgent::new(
+            "main:0.0-100".to_string(),
+            "main:0.0".to_string(),
+            "main".to_string(),
+            0,
+            "code".to_string(),
+            0,
+            "/code/app".to_string(),
+            AgentType::ClaudeCode,
+            100,
+        );
+        let now = Local::now();
+        let update = |transitions: Vec<StateTransition>, lines: &[&str]| MonitorUpdate {
+            agents: AgentTree {
+                root_agents: vec![agent.clone()],
+            },
+            changes: vec![PaneChanged {
+                target: "main:0.0".to_string(),
+                new_lines: lines.iter().map(|l| l.to_string()).collect(),
+            }],
+            transitions,
+            approvals: Vec::new(),
+            context_warnings: Vec::new(),
+            responses: Vec::new(),
+            stalls: Vec::new(),
+        };
+        let transition = |from, to| StateTransition {
+            key: "main:0.0".to_string(),
+            from,
+            to,
+            at: now,
+        };
+        let at = now.to_rfc3339();
+
+        let mut stream = EventStream::new();
+        let first = stream.lines(
+            &update(
+                vec![transition(None, AgentState::Working)],
+                &["whole", "capture"],
+            ),
+            now,
+        );
+        assert_eq!(
+            first,
+            vec![
+                format!(
+                    r#"{{"event":"pane_added","at":"{at}","pane":"main:0.0","agent":"Claude Code","state":"UNKNOWN","path":"/code/app","label":null}}"#
+                ),
+                format!(
+                    r#"{{"event":"state","at":"{at}","pane":"main:0.0","from":null,"to":"WORKING"}}"#
+                ),
+            ]
+        );
+
+        let next = stream.lines(
+            &update(
+                vec![transition(
+                    Some(AgentState::Working),
+                    AgentState::WaitingForPermission,
+                )],
+                &["Allow \"rm\"?", "", ""],
+            ),
+            now,
+        );
+        assert_eq!(next.len(), 3);
+        assert!(
+            next[1].ends_with(r#""lines":["Allow \"rm\"?"]}"#),
+            "{}",
+            next[1]
+        );
+        assert!(
+            next[2].contains(r#""kind":"permission","message":"main:0.0 asks for permission"}"#)
+        );
+
+        let mut empty = update(Vec::new(), &[]);
+        empty.agents.root_agents.clear();
+        assert_eq!(
+            stream.lines(&empty, now),
+            vec![format!(
+                r#"{{"event":"pane_removed","at":"{at}","pane":"main:0.0"}}"#
+            )]
+        );
+    }
+}
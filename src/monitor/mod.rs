@@ -1,5 +1,41 @@
+mod activity;
+mod auto_approve;
+mod auto_respond;
+mod capture;
+mod context;
+mod event_hooks;
+mod event_stream;
+mod notify_rules;
+mod project;
+mod rate_limit;
+mod registry;
+mod schedule;
+mod state_machine;
+mod summarizer;
+mod summary;
 mod system_stats;
 mod task;
+mod titles;
+mod watchdog;
+mod webhook;
 
+pub use activity::ActivityTracker;
+pub use auto_approve::{permission_request, ApprovalDecision, AutoApprover, PermissionRequest};
+pub use auto_respond::{AutoResponder, AutoResponse, AutomationSwitches, SharedSwitches};
+pub use capture::{new_lines, CaptureStore, PaneChanged};
+pub use context::{ContextWarning, ContextWatch};
+pub use event_hooks::EventHooks;
+pub use event_stream::EventStream;
+pub use notify_rules::{NotifyRouter, Route};
+pub use project::{ProjectCache, PROJECT_FILE};
+pub use rate_limit::{RateLimit, RateLimitTracker};
+pub use registry::{PaneRegistry, SharedRegistry};
+pub use schedule::{PaneTurn, PollSchedule};
+pub use state_machine::{StateTracker, StateTransition};
+pub use summarizer::Summarizer;
+pub use summary::{StatusSummary, SummaryCache, SUMMARY_MAX_AGE};
 pub use system_stats::{SystemStats, SystemStatsCollector};
-pub use task::{MonitorTask, MonitorUpdate};
+pub use task::{MonitorReload, MonitorTask, MonitorUpdate};
+pub use titles::{status_title, TitleUpdater};
+pub use watchdog::{Stall, Watchdog};
+pub use webhook::{webhook_targets, WebhookTarget, Webhooks};
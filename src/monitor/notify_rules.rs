@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+
+use crate::agents::{AgentFilter, MonitoredAgent};
+use crate::app::{Config, Notifier, NotifyOn, NotifyRule};
+
+/// Where one notification goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route<'a> {
+    /// Identical to the last alert about the pane, sent nowhere
+    Duplicate,
+    /// No rule matches; every notifier decides by its own `on`
+    Default,
+    /// The first matching rule decides
+    Rule(&'a NotifyRule),
+}
+
+impl Route<'_> {
+    /// Returns true if `notifier` gets the notification; `enabled` says
+    /// whether it is set up at all, `wants` whether its own `on` asks for it
+    pub fn sends_to(&self, notifier: Notifier, enabled: bool, wants: bool) -> bool {
+        match self {
+            Route::Duplicate => false,
+            Route::Default => enabled && wants,
+            Route::Rule(rule) => enabled && rule.notify.contains(&notifier),
+        }
+    }
+
+    /// Returns the Slack channel the rule asks for, if any
+    pub fn channel(&self) -> Option<&str> {
+        match self {
+            Route::Rule(rule) if !rule.channel.is_empty() => Some(&rule.channel),
+            _ => None,
+        }
+    }
+}
+
+/// Picks the notifiers of each notification by the `notify_rules` and drops
+/// repeats of identical alerts
+///
+/// The monitor and the UI each route their own notifiers with a copy; the
+/// copies see the same events, so they agree on what is a repeat.
+#[derive(Debug, Clone)]
+pub struct NotifyRouter {
+    rules: Vec<(AgentFilter, NotifyRule)>,
+    dedup: Duration,
+    /// Last alert per pane and event: its text and when it was sent
+    last: HashMap<(String, NotifyOn), (String, Instant)>,
+}
+
+impl NotifyRouter {
+    /// Fails on a rule whose `match` isn't a valid filter
+    pub fn new(config: &Config) -> Result<Self> {
+        let rules = config
+            .notify_rules
+            .iter()
+            .map(|rule| {
+                AgentFilter::parse(&rule.filter)
+                    .with_context(|| format!("invalid notify rule match {:?}", rule.filter))
+                    .map(|filter| (filter, rule.clone()))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            rules,
+            dedup: Duration::from_secs(config.notify_dedup_secs),
+            last: HashMap::new(),
+        })
+    }
+
+    /// Returns where the notification about `event` in `agent` goes
+    ///
+    /// The rules of the pane's `.tmuxcc.toml` come before the configured ones.
+    pub fn route<'a>(
+        &'a mut self,
+        agent: &'a MonitoredAgent,
+        event: NotifyOn,
+        now: Instant,
+    ) -> Route<'a> {
+        let text = agent.snippet();
+        let key = (agent.key(), event);
+        if let Some((last, at)) = self.last.get(&key) {
+            if *last == text && now.duration_since(*at) < self.dedup {
+                return Route::Duplicate;
+            }
+        }
+        self.last.insert(key, (text, now));
+
+        let fits = |rule: &NotifyRule| rule.on.is_empty() || rule.on.contains(&event);
+        let mut project = agent.project.iter().flat_map(|p| &p.notify_rules);
+        // The filters were checked when the project file was read
+        let project_rule = project.find(|rule| {
+            fits(rule) && AgentFilter::parse(&rule.filter).is_ok_and(|f| f.matches(agent))
+        });
+        project_rule
+            .or_else(|| {
+                self.rules
+                    .iter()
+                    .find(|(filter, rule)| fits(rule) && filter.matches(agent))
+                    .map(|(_, rule)| rule)
+            })
+            .map_or(Route::Default, Route::Rule)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::AgentType;
+
+    fn agent(path: &str) -> MonitoredAgent {
+        MonitoredAgent::new(
+            "%1".to_string(),
+            format!("main:0.{}", path.len()),
+            "main".to_string(),
+            0,
+            "editor".to_string(),
+            path.len() as u32,
+            path.to_string(),
+            AgentType::ClaudeCode,
+            42,
+        )
+    }
+
+    #[test]
+    fn test_routing() {
+        let config: Config = toml::from_str(
+            r##"
+            [[notify_rules]]
+            match = "repo:alpha"
+            notify = ["slack"]
+            channel = "#a"
+
+            [[notify_rules]]
+            match = "repo:beta"
+            on = ["permission", "error"]
+            notify = ["desktop"]
+
+            [[notify_rules]]
+            on = ["finished"]
+            "##,
+        )
+        .unwrap();
+        let mut router = NotifyRouter::new(&config).unwrap();
+        let now = Instant::now();
+
+        let alpha = agent("/code/alpha");
+        let route = router.route(&alpha, NotifyOn::Error, now);
+        assert!(route.sends_to(Notifier::Slack, true, false));
+        assert!(!route.sends_to(Notifier::Desktop, true, true));
+        assert_eq!(route.channel(), Some("#a"));
+
+        let beta = agent("/code/beta");
+        let route = router.route(&beta, NotifyOn::Permission, now);
+        assert!(route.sends_to(Notifier::Desktop, true, false));
+        assert!(!route.sends_to(Notifier::Desktop, false, false));
+        assert!(!route.sends_to(Notifier::Slack, true, true));
+
+        // Quiet: a rule without notifiers
+        let route = router.route(&beta, NotifyOn::Finished, now);
+        assert!(!route.sends_to(Notifier::Sound, true, true));
+
+        let gamma = agent("/code/gamma");
+        let route = router.route(&gamma, NotifyOn::Input, now);
+        assert_eq!(route, Route::Default);
+        assert!(route.sends_to(Notifier::Webhook, true, true));
+
+        // Repeats are dropped until the window passes or the text changes
+        let later = now + Duration::from_secs(30);
+        assert_eq!(
+            router.route(&gamma, NotifyOn::Input, later),
+            Route::Duplicate
+        );
+        let much_later = now + Duration::from_secs(120);
+        assert_eq!(
+            router.route(&gamma, NotifyOn::Input, much_later),
+            Route::Default
+        );
+
+        // A project's rules come first
+        let mut alpha = agent("/code/alpha");
+        alpha.project = Some(std::sync::Arc::new(
+            toml::from_str("[[notify_rules]]\nnotify = [\"discord\"]").unwrap(),
+        ));
+        let route = router.route(&alpha, NotifyOn::Finished, now);
+        assert!(route.sends_to(Notifier::Discord, true, false));
+        assert!(!route.sends_to(Notifier::Slack, true, true));
+
+        let bad: Config = toml::from_str("[[notify_rules]]\nmatch = \"colour:red\"").unwrap();
+        assert!(NotifyRouter::new(&bad).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use tracing::warn;
+
+use crate::agents::AgentFilter;
+use crate::app::ProjectConfig;
+use crate::parsers::compile_patterns;
+
+/// Name of the per-project config file
+pub const PROJECT_FILE: &str = ".tmuxcc.toml";
+
+/// Returns the `.tmuxcc.toml` nearest to `path`, looking up to the
+/// repository `root`; only in `path` itself outside a repository
+fn find_project_file(path: &Path, root: Option<&Path>) -> Option<PathBuf> {
+    let Some(root) = root.filter(|root| path.starts_with(root)) else {
+        let file = path.join(PROJECT_FILE);
+        return file.is_file().then_some(file);
+    };
+    for dir in path.ancestors() {
+        let file = dir.join(PROJECT_FILE);
+        if file.is_file() {
+            return Some(file);
+        }
+        if dir == root {
+            break;
+        }
+    }
+    None
+}
+
+/// Reads the project config at `file`, checking its patterns and filters
+fn load(file: &Path) -> Result<ProjectConfig> {
+    let content = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read {}", file.display()))?;
+    let mut project: ProjectConfig =
+        toml::from_str(&content).with_context(|| format!("Invalid {}", file.display()))?;
+    compile_patterns(&project.auto_approve.allow, "auto_approve.allow")
+        .and_then(|_| compile_patterns(&project.auto_approve.deny, "auto_approve.deny"))
+        .with_context(|| format!("Invalid {}", file.display()))?;
+    for rule in &project.notify_rules {
+        AgentFilter::parse(&rule.filter).with_context(|| {
+            format!(
+                "Invalid notify rule match {:?} in {}",
+                rule.filter,
+                file.display()
+            )
+        })?;
+    }
+    project.source = file.to_path_buf();
+    Ok(project)
+}
+
+struct CacheEntry {
+    modified: Option<SystemTime>,
+    /// `None` if the file is invalid
+    project: Option<Arc<ProjectConfig>>,
+}
+
+/// Per-project configs of the panes' repositories
+///
+/// A file is read again only when its modification time changes, and an
+/// invalid one is reported once and ignored until it is fixed.
+#[derive(Default)]
+pub struct ProjectCache {
+    entries: HashMap<PathBuf, CacheEntry>,
+}
+
+impl ProjectCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the project config of a pane working in `path`, inside the
+    /// repository at `root` if it is in one
+    pub fn get(&mut self, path: &str, root: Option<&Path>) -> Option<Arc<ProjectConfig>> {
+        if path.is_empty() {
+            return None;
+        }
+        let file = find_project_file(Path::new(path), root)?;
+        let modified = std::fs::metadata(&file).and_then(|m| m.modified()).ok();
+        if let Some(entry) = self.entries.get(&file) {
+            if entry.modified == modified {
+                return entry.project.clone();
+            }
+        }
+        let project = match load(&file) {
+            Ok(project) => Some(Arc::new(project)),
+            Err(e) => {
+                warn!("Ignoring {}: {:#}", PROJECT_FILE, e);
+                None
+            }
+        };
+        self.entries.insert(
+            file,
+            CacheEntry {
+                modified,
+                project: project.clone(),
+            },
+        );
+        project
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_lookup() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("repo");
+        let nested = root.join("crates/api");
+        std::fs::create_dir_all(&nested).unwrap();
+        let path = nested.to_str().unwrap();
+        let mut cache = ProjectCache::new();
+        assert!(cache.get(path, Some(&root)).is_none());
+
+        // Found from a subdirectory, but not above the repository
+        std::fs::write(dir.path().join(PROJECT_FILE), "label = \"outside\"").unwrap();
+        assert!(cache.get(path, Some(&root)).is_none());
+        std::fs::write(root.join(PROJECT_FILE), "label = \"api\"").unwrap();
+        let project = cache.get(path, Some(&root)).unwrap();
+        assert_eq!(project.label.as_deref(), Some("api"));
+        assert_eq!(project.source, root.join(PROJECT_FILE));
+        // Outside a repository only the directory itself counts
+        assert!(cache.get(path, None).is_none());
+
+        let invalid = root.join("bad");
+        std::fs::create_dir_all(&invalid).unwrap();
+        let file = invalid.join(PROJECT_FILE);
+        std::fs::write(&file, "[auto_approve]\nallow = [\"(\"]").unwrap();
+        assert!(cache.get(invalid.to_str().unwrap(), Some(&root)).is_none());
+        std::fs::write(&file, "[[notify_rules]]\nmatch = \"colour:red\"").unwrap();
+        assert!(load(&file).is_err());
+        std::fs::write(&file, "lable = \"typo\"").unwrap();
+        assert!(load(&file).is_err());
+    }
+}
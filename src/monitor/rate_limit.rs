@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveTime, TimeZone};
+use parking_lot::Mutex;
+use regex::Regex;
+use tracing::{debug, info};
+
+use crate::agents::MonitoredAgent;
+use crate::tmux::{SendMode, TmuxClient};
+
+/// Lines at the bottom of a pane searched for a rate limit message
+const RECENT_LINES: usize = 15;
+
+/// Wait after the announced reset before resuming, in case clocks disagree
+const RESUME_GRACE: Duration = Duration::minutes(1);
+
+/// Time zone lookups kept before the cache is cleared
+const ZONE_CACHE_LEN: usize = 64;
+
+/// A rate limit message found in a pane's output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The line announcing the limit
+    pub line: String,
+    /// When the limit resets
+    pub resets_at: DateTime<Local>,
+}
+
+struct Limited {
+    limit: RateLimit,
+    resumed: bool,
+}
+
+/// Tracks rate limited panes and optionally resumes them once the limit resets
+///
+/// A message stays on screen after its reset, so each one is scheduled once:
+/// while the same line is visible its reset time is kept.
+pub struct RateLimitTracker {
+    /// Sent to a pane once its limit reset; empty to only show the countdown
+    resume: String,
+    panes: HashMap<String, Limited>,
+    limit_pattern: Regex,
+    reset_pattern: Regex,
+    /// UTC offsets the time zones messages named have at given times, `None`
+    /// for unknown zones
+    zones: Mutex<HashMap<(String, i64), Option<FixedOffset>>>,
+}
+
+impl RateLimitTracker {
+    pub fn new(resume: String) -> Self {
+        Self {
+            resume,
+            panes: HashMap::new(),
+            // e.g. "Claude usage limit reached. Your limit will reset at 5pm
+            // (Europe/Paris)" or "5-hour limit reached ∙ resets 3:30pm"
+            limit_pattern: Regex::new(r"(?i)limit reached|rate limit|rate_limit_error").unwrap(),
+            reset_pattern: Regex::new(
+                r"(?i)resets?\s+(?:at\s+)?(\d{1,2})(?::(\d{2}))?\s*(am|pm)?(?:\s*\(([A-Za-z_]+(?:/[A-Za-z0-9_+-]+)*)\))?",
+            )
+            .unwrap(),
+            zones: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the UTC offset the IANA time zone `zone` (e.g. "Asia/Tokyo")
+    /// has at the Unix time `at`, `None` if the system doesn't know it
+    fn zone_offset(&self, zone: &str, at: i64) -> Option<FixedOffset> {
+        let mut zones = self.zones.lock();
+        // Only a few lookups a day; forget them all now and then
+        if zones.len() > ZONE_CACHE_LEN {
+            zones.clear();
+        }
+        *zones
+            .entry((zone.to_string(), at))
+            .or_insert_with(|| zone_offset(zone, at))
+    }
+
+    /// Returns the next `time` of day in `zone` after `now`
+    ///
+    /// The offset of today's start guesses when that is, the offset at the
+    /// guess settles it, so a reset past a DST change isn't an hour off.
+    fn next_zone_time(
+        &self,
+        zone: &str,
+        time: NaiveTime,
+        now: DateTime<Local>,
+    ) -> Option<DateTime<Local>> {
+        let day_start = now.timestamp() - now.timestamp().rem_euclid(86400);
+        let guess = next_time(&self.zone_offset(zone, day_start)?, time, now)?;
+        let offset = self.zone_offset(zone, guess.timestamp())?;
+        next_time(&offset, time, now)
+    }
+
+    /// Finds a rate limit message with a reset time at the bottom of `content`
+    ///
+    /// The reset time is a time of day, in the time zone named after it if
+    /// any, else the local one; it is taken as its next occurrence after
+    /// `now`. With a time zone the system doesn't know, the reset time is
+    /// unknown and no limit is reported.
+    pub fn parse(&self, content: &str, now: DateTime<Local>) -> Option<RateLimit> {
+        let lines: Vec<&str> = content.trim_end().lines().collect();
+        let recent = &lines[lines.len().saturating_sub(RECENT_LINES)..];
+        recent.iter().rev().find_map(|line| {
+            if !self.limit_pattern.is_match(line) {
+                return None;
+            }
+            let caps = self.reset_pattern.captures(line)?;
+            let mut hour: u32 = caps[1].parse().ok()?;
+            let minute: u32 = caps.get(2).map_or(Some(0), |m| m.as_str().parse().ok())?;
+            match caps.get(3).map(|m| m.as_str().to_lowercase()).as_deref() {
+                Some("pm") if hour < 12 => hour += 12,
+                Some("am") if hour == 12 => hour = 0,
+                _ => {}
+            }
+            let time = NaiveTime::from_hms_opt(hour, minute, 0)?;
+            let resets_at = match caps.get(4) {
+                Some(zone) => self.next_zone_time(zone.as_str(), time, now)?,
+                None => next_time(&Local, time, now)?,
+            };
+            Some(RateLimit {
+                line: line.trim().to_string(),
+                resets_at,
+            })
+        })
+    }
+
+    /// Sets `rate_limit_reset` of limited agents and resumes those whose limit reset
+    ///
+    /// Only agents `allowed` to be automated are resumed; a limit resetting
+    /// while automation is off is left to the user.
+    pub fn apply(
+        &mut self,
+        tmux_client: &TmuxClient,
+        agents: &mut [MonitoredAgent],
+        allowed: impl Fn(&MonitoredAgent) -> bool,
+    ) {
+        let now = Local::now();
+        let mut panes = HashMap::new();
+
+        for agent in agents.iter_mut() {
+            let key = agent.key();
+            let Some(found) = self.parse(&agent.last_content, now) else {
+                continue;
+            };
+            let mut limited = match self.panes.remove(&key) {
+                Some(known) if known.limit.line == found.line => known,
+                _ => Limited {
+                    limit: found,
+                    resumed: false,
+                },
+            };
+
+            if now < limited.limit.resets_at {
+                agent.rate_limit_reset = Some(limited.limit.resets_at);
+            } else if !limited.resumed && now >= limited.limit.resets_at + RESUME_GRACE {
+                limited.resumed = true;
+                if !self.resume.is_empty() && allowed(agent) {
+                    let client = tmux_client.for_server(&agent.server);
+                    let result = client
+                        .send_text(
+                            &agent.target,
+                            &self.resume,
+                            SendMode::for_text(&self.resume),
+                        )
+                        .and_then(|()| client.send_keys(&agent.target, "Enter"));
+                    match result {
+                        Ok(()) => info!("Resumed {} after its rate limit reset", key),
+                        Err(e) => debug!("Failed to resume {}: {}", key, e),
+                    }
+                }
+            }
+            panes.insert(key, limited);
+        }
+
+        self.panes = panes;
+    }
+}
+
+/// Returns the next `time` of day in `tz` after `now`
+fn next_time<Tz: TimeZone>(
+    tz: &Tz,
+    time: NaiveTime,
+    now: DateTime<Local>,
+) -> Option<DateTime<Local>> {
+    let today = now.with_timezone(tz).date_naive();
+    let mut at = tz
+        .from_local_datetime(&today.and_time(time))
+        .earliest()?
+        .with_timezone(&Local);
+    if at <= now {
+        at += Duration::days(1);
+    }
+    Some(at)
+}
+
+/// Looks up the UTC offset of `zone` at the Unix time `at` with `date`, which
+/// reads the system's time zone database
+fn zone_offset(zone: &str, at: i64) -> Option<FixedOffset> {
+    let database = std::env::var_os("TZDIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/usr/share/zoneinfo"));
+    // `date` falls back to UTC for zones it doesn't find
+    if zone.split('/').any(|part| part.starts_with('.')) || !database.join(zone).is_file() {
+        return None;
+    }
+    // GNU date takes the time with -d, BSD date with -r
+    let output = [
+        ["-d".to_string(), format!("@{}", at)],
+        ["-r".to_string(), at.to_string()],
+    ]
+    .into_iter()
+    .filter_map(|time| {
+        Command::new("date")
+            .args(time)
+            .arg("+%z")
+            .env("TZ", zone)
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+    })
+    .find(|output| output.status.success())?;
+    let offset = String::from_utf8_lossy(&output.stdout);
+    let offset = offset.trim();
+    let sign = match offset.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let hours: i32 = offset.get(1..3)?.parse().ok()?;
+    let minutes: i32 = offset.get(3..5)?.parse().ok()?;
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_parse_rate_limit() {
+        let tracker = RateLimitTracker::new(String::new());
+        let parse_rate_limit = |content, now| tracker.parse(content, now);
+        let now = Local.with_ymd_and_hms(2026, 10, 14, 13, 20, 0).unwrap();
+        let at = |d, h, m| Local.with_ymd_and_hms(2026, 10, d, h, m, 0).unwrap();
+
+        let limit = parse_rate_limit("5-hour limit reached ∙ resets 1:05pm\n", now).unwrap();
+        assert_eq!(limit.resets_at, at(15, 13, 5)); // Already past today
+        let limit = parse_rate_limit("Rate limit reached, resets at 12am\n", now).unwrap();
+        assert_eq!(limit.resets_at, at(15, 0, 0));
+
+        // Time zones need the system's time zone database
+        if zone_offset("Europe/Paris", 0).is_some() {
+            let content = "⏺ Working on it\n\n  ⎿  Claude usage limit reached. Your limit will reset at 5pm (Europe/Paris).\n\n> ";
+            let limit = parse_rate_limit(content, now).unwrap();
+            // 5pm in Paris (then UTC+2), whatever the local time zone
+            let paris = FixedOffset::east_opt(2 * 3600).unwrap();
+            assert_eq!(
+                limit.resets_at.with_timezone(&paris).time(),
+                NaiveTime::from_hms_opt(17, 0, 0).unwrap()
+            );
+            assert!(limit.line.starts_with("⎿  Claude usage limit reached"));
+
+            // In the time zone of the message: 15:00 in Tokyo is 06:00 UTC
+            let tokyo = FixedOffset::east_opt(9 * 3600).unwrap();
+            let now_in_tokyo = tokyo.with_ymd_and_hms(2026, 10, 14, 13, 20, 0).unwrap();
+            let limit = parse_rate_limit(
+                "5-hour limit reached ∙ resets 3pm (Asia/Tokyo)\n",
+                now_in_tokyo.with_timezone(&Local),
+            )
+            .unwrap();
+            assert_eq!(
+                limit.resets_at,
+                tokyo.with_ymd_and_hms(2026, 10, 14, 15, 0, 0).unwrap()
+            );
+            // Paris leaves summer time at 3am on Oct 25: that 3am is 02:00 UTC
+            let now = Utc.with_ymd_and_hms(2026, 10, 24, 20, 0, 0).unwrap();
+            let limit = parse_rate_limit(
+                "Limit reached ∙ resets 3am (Europe/Paris)\n",
+                now.with_timezone(&Local),
+            )
+            .unwrap();
+            assert_eq!(
+                limit.resets_at,
+                Utc.with_ymd_and_hms(2026, 10, 25, 2, 0, 0).unwrap()
+            );
+        }
+
+        assert_eq!(
+            parse_rate_limit("Limit reached ∙ resets 3pm (Mars/Olympus)\n", now),
+            None
+        );
+        assert_eq!(parse_rate_limit("resets at 5pm\n", now), None);
+        assert_eq!(parse_rate_limit("limit reached, try later\n", now), None);
+    }
+}
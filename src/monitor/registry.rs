@@ -0,0 +1,425 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::agents::{AgentStatus, MonitoredAgent, PaneStats, Usage};
+
+/// Write pending changes at most this often
+const SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Records kept on disk, least recently seen ones are dropped first
+const MAX_RECORDS: usize = 200;
+
+/// Days of per-repository usage kept on disk
+const USAGE_DAYS: usize = 90;
+
+/// Registry shared by the monitor, which tracks panes, and the UI, which edits them
+pub type SharedRegistry = Arc<Mutex<PaneRegistry>>;
+
+/// What tmuxcc remembers about one agent pane
+///
+/// Pane ids and pids don't survive a tmux-resurrect restore, so a record is
+/// identified by where the agent runs instead: working directory, agent type
+/// and window name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct PaneRecord {
+    cwd: String,
+    command: String,
+    window_name: String,
+    #[serde(default)]
+    label: Option<String>,
+    #[serde(default)]
+    note: Option<String>,
+    #[serde(default)]
+    stats: PaneStats,
+    /// Cumulative usage the agent last reported, already counted in `stats`
+    #[serde(default)]
+    reported: Option<Usage>,
+    /// Unix time the pane was last seen
+    #[serde(default)]
+    last_seen: u64,
+}
+
+impl PaneRecord {
+    fn matches(&self, agent: &MonitoredAgent) -> bool {
+        self.cwd == agent.path
+            && self.command == agent.agent_type.short_name()
+            && self.window_name == agent.window_name
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistryFile {
+    #[serde(default)]
+    panes: Vec<PaneRecord>,
+    /// Usage per day ("2026-10-14") and repository root
+    #[serde(default)]
+    usage: BTreeMap<String, BTreeMap<String, Usage>>,
+}
+
+/// Persistent labels, notes and statistics of agent panes
+pub struct PaneRegistry {
+    path: Option<PathBuf>,
+    records: Vec<PaneRecord>,
+    usage: BTreeMap<String, BTreeMap<String, Usage>>,
+    /// Record index of each live agent id, with whether it was waiting at the last poll
+    live: HashMap<String, (usize, bool)>,
+    last_update: Option<Instant>,
+    last_save: Instant,
+    dirty: bool,
+}
+
+impl PaneRegistry {
+    /// Returns the default registry file path
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|p| p.join("tmuxcc").join("panes.toml"))
+    }
+
+    /// Loads the registry at `path`, starting empty if it is missing or invalid
+    ///
+    /// Without a path nothing is persisted.
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let file = path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|content| match toml::from_str::<RegistryFile>(&content) {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    debug!("Ignoring invalid pane registry: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self {
+            path,
+            records: file.panes,
+            usage: file.usage,
+            live: HashMap::new(),
+            last_update: None,
+            last_save: Instant::now(),
+            dirty: false,
+        }
+    }
+
+    /// Keeps the registry from writing its file, for a one-off look such as
+    /// `tmuxcc ls` while another tmuxcc may be keeping it up to date
+    pub fn read_only(mut self) -> Self {
+        self.path = None;
+        self
+    }
+
+    /// Wraps the registry for sharing between the monitor and the UI
+    pub fn shared(self) -> SharedRegistry {
+        Arc::new(Mutex::new(self))
+    }
+
+    /// Associates `agents` with their records and fills in labels, notes and statistics
+    ///
+    /// Usage reported by an agent is added to its pane and to today's total
+    /// of its repository once per change of the report.
+    ///
+    /// An agent id seen for the first time takes over an unclaimed record with
+    /// the same identity, so panes restored by tmux-resurrect or restarted in
+    /// place keep their history.
+    pub fn update(&mut self, agents: &mut [MonitoredAgent]) {
+        let now = unix_now();
+        let elapsed = self.last_update.map(|t| t.elapsed()).unwrap_or_default();
+        self.last_update = Some(Instant::now());
+
+        // Release records of panes that are gone before new panes claim them
+        let ids: HashSet<&str> = agents.iter().map(|a| a.id.as_str()).collect();
+        self.live.retain(|id, _| ids.contains(id.as_str()));
+
+        for agent in agents.iter_mut() {
+            let (index, was_waiting) = match self.live.get(&agent.id) {
+                Some(&entry) => entry,
+                None => (self.claim(agent, now), false),
+            };
+            let waiting = matches!(agent.status, AgentStatus::AwaitingApproval { .. });
+
+            let record = &mut self.records[index];
+            if matches!(agent.status, AgentStatus::Processing { .. }) {
+                record.stats.working_ms += elapsed.as_millis() as u64;
+            }
+            if waiting && !was_waiting {
+                record.stats.prompts += 1;
+            }
+            if let Some(reported) = agent.reported_usage {
+                if record.reported != Some(reported) {
+                    let delta = reported.since(&record.reported.unwrap_or_default());
+                    record.stats.usage.add(&delta);
+                    record.reported = Some(reported);
+                    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+                    self.usage
+                        .entry(today)
+                        .or_default()
+                        .entry(agent.repo_root())
+                        .or_default()
+                        .add(&delta);
+                }
+            }
+            // Follow renames so the next restore matches the current identity
+            record.cwd.clone_from(&agent.path);
+            record.window_name.clone_from(&agent.window_name);
+            record.last_seen = now;
+
+            agent.label.clone_from(&record.label);
+            agent.note.clone_from(&record.note);
+            agent.stats = record.stats.clone();
+            self.live.insert(agent.id.clone(), (index, waiting));
+        }
+
+        if !agents.is_empty() {
+            self.dirty = true;
+        }
+        if self.dirty && self.last_save.elapsed() >= SAVE_INTERVAL {
+            if let Err(e) = self.save() {
+                debug!("Failed to save pane registry: {}", e);
+            }
+        }
+    }
+
+    /// Returns the record index for a newly seen agent, creating one if needed
+    fn claim(&mut self, agent: &MonitoredAgent, now: u64) -> usize {
+        let claimed: HashSet<usize> = self.live.values().map(|&(i, _)| i).collect();
+        let candidate = self
+            .records
+            .iter()
+            .enumerate()
+            .filter(|(i, r)| !claimed.contains(i) && r.matches(agent))
+            .min_by_key(|(i, r)| (std::cmp::Reverse(r.last_seen), *i))
+            .map(|(i, _)| i);
+        if let Some(index) = candidate {
+            return index;
+        }
+
+        self.records.push(PaneRecord {
+            cwd: agent.path.clone(),
+            command: agent.agent_type.short_name().to_string(),
+            window_name: agent.window_name.clone(),
+            label: None,
+            note: None,
+            stats: PaneStats {
+                first_seen: now,
+                ..Default::default()
+            },
+            reported: None,
+            last_seen: now,
+        });
+        self.records.len() - 1
+    }
+
+    /// Sets or clears (`None`) the label of the live agent `id`
+    pub fn set_label(&mut self, id: &str, label: Option<String>) {
+        if let Some(&(index, _)) = self.live.get(id) {
+            self.records[index].label = label;
+            self.save_edit();
+        }
+    }
+
+    /// Sets or clears (`None`) the note of the live agent `id`
+    pub fn set_note(&mut self, id: &str, note: Option<String>) {
+        if let Some(&(index, _)) = self.live.get(id) {
+            self.records[index].note = note;
+            self.save_edit();
+        }
+    }
+
+    /// Returns the usage report shown by the stats viewer: today per
+    /// repository, the last days, all-time per repository and live panes
+    pub fn usage_report(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let line = |name: &str, usage: &Usage| format!("  {:<40} {}", name, usage.summary());
+
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        lines.push(format!("Today ({})", today));
+        match self.usage.get(&today) {
+            Some(repos) => lines.extend(repos.iter().map(|(repo, u)| line(repo, u))),
+            None => lines.push("  no usage reported yet".to_string()),
+        }
+
+        lines.push(String::new());
+        lines.push("By day".to_string());
+        for (day, repos) in self.usage.iter().rev().take(14) {
+            let mut total = Usage::default();
+            repos.values().for_each(|u| total.add(u));
+            lines.push(line(day, &total));
+        }
+
+        lines.push(String::new());
+        lines.push(format!("By repository (last {} days)", USAGE_DAYS));
+        let mut repos: BTreeMap<&str, Usage> = BTreeMap::new();
+        for (repo, usage) in self.usage.values().flatten() {
+            repos.entry(repo).or_default().add(usage);
+        }
+        let mut repos: Vec<_> = repos.into_iter().collect();
+        repos.sort_by(|a, b| b.1.cost_usd.total_cmp(&a.1.cost_usd));
+        lines.extend(repos.iter().map(|(repo, u)| line(repo, u)));
+
+        lines.push(String::new());
+        lines.push("By pane".to_string());
+        let mut live: Vec<_> = self.live.iter().collect();
+        live.sort();
+        for (id, &(index, _)) in live {
+            let record = &self.records[index];
+            let name = record.label.as_deref().unwrap_or(id);
+            lines.push(line(name, &record.stats.usage));
+        }
+        lines
+    }
+
+    /// Saves right away, edits shouldn't wait for the next interval
+    fn save_edit(&mut self) {
+        self.dirty = true;
+        if let Err(e) = self.save() {
+            debug!("Failed to save pane registry: {}", e);
+        }
+    }
+
+    /// Writes the registry file, dropping the oldest records beyond the limit
+    pub fn save(&mut self) -> Result<()> {
+        self.last_save = Instant::now();
+        self.dirty = false;
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+
+        let mut panes = self.records.clone();
+        panes.sort_by_key(|r| std::cmp::Reverse(r.last_seen));
+        panes.truncate(MAX_RECORDS);
+        while self.usage.len() > USAGE_DAYS {
+            self.usage.pop_first();
+        }
+        let content = toml::to_string(&RegistryFile {
+            panes,
+            usage: self.usage.clone(),
+        })?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Write then rename so a crash never leaves a truncated file
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+impl Drop for PaneRegistry {
+    fn drop(&mut self) {
+        if self.dirty {
+            let _ = self.save();
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::AgentType;
+
+    fn agent(id: &str, path: &str, window_name: &str) -> MonitoredAgent {
+        MonitoredAgent::new(
+            id.to_string(),
+            "main:0.0".to_string(),
+            "main".to_string(),
+            0,
+            window_name.to_string(),
+            0,
+            path.to_string(),
+            AgentType::ClaudeCode,
+            1000,
+        )
+    }
+
+    #[test]
+    fn test_restored_pane_keeps_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("panes.toml");
+
+        let mut registry = PaneRegistry::load(Some(path.clone()));
+        let mut agents = vec![agent("%1-100", "/repo", "api")];
+        agents[0].status = AgentStatus::AwaitingApproval {
+            approval_type: crate::agents::ApprovalType::FileEdit,
+            details: String::new(),
+        };
+        registry.update(&mut agents);
+        registry.set_label("%1-100", Some("backend".to_string()));
+        registry.set_note("%1-100", Some("waiting on review".to_string()));
+        drop(registry);
+
+        // After a restore the pane id and pid differ, the identity doesn't
+        let mut registry = PaneRegistry::load(Some(path));
+        let mut agents = vec![
+            agent("%7-200", "/repo", "api"),
+            agent("%8-201", "/other", "api"),
+        ];
+        registry.update(&mut agents);
+        assert_eq!(agents[0].label.as_deref(), Some("backend"));
+        assert_eq!(agents[0].note.as_deref(), Some("waiting on review"));
+        assert_eq!(agents[0].stats.prompts, 1);
+        assert_eq!(agents[1].label, None);
+    }
+
+    #[test]
+    fn test_usage_counted_once() {
+        let mut registry = PaneRegistry::load(None);
+        let mut agents = vec![agent("%1-100", "/repo", "api")];
+        let reported = |cost_usd, output_tokens| Usage {
+            input_tokens: 0,
+            output_tokens,
+            cost_usd,
+        };
+
+        agents[0].reported_usage = Some(reported(0.5, 1000));
+        registry.update(&mut agents);
+        registry.update(&mut agents); // The same report is still on screen
+        agents[0].reported_usage = Some(reported(0.75, 1500));
+        registry.update(&mut agents);
+        assert_eq!(agents[0].stats.usage, reported(0.75, 1500));
+
+        // A new session reports from zero again
+        agents[0].reported_usage = Some(reported(0.25, 200));
+        registry.update(&mut agents);
+        assert_eq!(agents[0].stats.usage, reported(1.0, 1700));
+
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        assert_eq!(registry.usage[&today]["/repo"], reported(1.0, 1700));
+    }
+
+    #[test]
+    fn test_record_claimed_once() {
+        let mut registry = PaneRegistry::load(None);
+        let mut agents = vec![agent("%1-100", "/repo", "api")];
+        registry.update(&mut agents);
+        registry.set_label("%1-100", Some("first".to_string()));
+
+        // A second pane with the same identity gets its own record
+        let mut agents = vec![
+            agent("%1-100", "/repo", "api"),
+            agent("%2-101", "/repo", "api"),
+        ];
+        registry.update(&mut agents);
+        assert_eq!(agents[0].label.as_deref(), Some("first"));
+        assert_eq!(agents[1].label, None);
+
+        // A restarted agent in the same pane takes over the record
+        let mut agents = vec![agent("%1-300", "/repo", "api")];
+        registry.update(&mut agents);
+        assert_eq!(agents[0].label.as_deref(), Some("first"));
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+
+use crate::agents::{AgentFilter, MonitoredAgent};
+use crate::app::PanePolling;
+
+/// What the monitor does with a pane this poll
+#[derive(Debug, Clone)]
+pub enum PaneTurn {
+    /// Capture and parse it
+    Poll,
+    /// Not due yet: show it as it was at its last capture
+    Wait(Box<MonitoredAgent>),
+    /// Leave it alone
+    Ignore,
+}
+
+enum Entry {
+    /// The agent at its last capture and when it is due again
+    Polled {
+        agent: Box<MonitoredAgent>,
+        due: Instant,
+    },
+    /// Matched an `ignore` rule
+    Ignored,
+}
+
+/// When each agent pane is captured, by the `pane_polling` rules
+///
+/// The monitor wakes at the shortest interval of the rules (or the global
+/// one, if that is shorter) and captures the panes that are due. Rules are
+/// matched with what the pane showed at its last capture, so a rule on
+/// `state:working` speeds a pane up from the poll after it starts working.
+pub struct PollSchedule {
+    interval: Duration,
+    rules: Vec<(AgentFilter, PanePolling)>,
+    panes: HashMap<String, Entry>,
+}
+
+impl PollSchedule {
+    /// Captures every pane every `interval`
+    pub fn uniform(interval: Duration) -> Self {
+        Self {
+            interval,
+            rules: Vec::new(),
+            panes: HashMap::new(),
+        }
+    }
+
+    /// Fails on a rule whose `match` isn't a valid filter
+    pub fn new(interval: Duration, rules: &[PanePolling]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                AgentFilter::parse(&rule.filter)
+                    .with_context(|| format!("invalid pane polling match {:?}", rule.filter))
+                    .map(|filter| (filter, rule.clone()))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            rules,
+            ..Self::uniform(interval)
+        })
+    }
+
+    /// Returns how long the monitor sleeps between polls
+    pub fn tick(&self) -> Duration {
+        self.rules
+            .iter()
+            .filter(|(_, rule)| !rule.ignore && rule.interval_ms > 0)
+            .map(|(_, rule)| Duration::from_millis(rule.interval_ms))
+            .fold(self.interval, Duration::min)
+    }
+
+    /// Returns what to do with the pane `key` at `now`
+    pub fn turn(&self, key: &str, now: Instant) -> PaneTurn {
+        match self.panes.get(key) {
+            Some(Entry::Ignored) => PaneTurn::Ignore,
+            // A little early is on time, or a pane as slow as the tick
+            // would only be captured every other poll
+            Some(Entry::Polled { agent, due }) if *due > now + self.tick() / 2 => {
+                PaneTurn::Wait(agent.clone())
+            }
+            _ => PaneTurn::Poll,
+        }
+    }
+
+    /// Schedules the next capture of `agent`, just captured at `now`
+    pub fn polled(&mut self, agent: &MonitoredAgent, now: Instant) {
+        let rule = self
+            .rules
+            .iter()
+            .find(|(filter, _)| filter.matches(agent))
+            .map(|(_, rule)| rule);
+        let entry = match rule {
+            Some(rule) if rule.ignore => Entry::Ignored,
+            _ => {
+                let interval = rule
+                    .map(|rule| Duration::from_millis(rule.interval_ms))
+                    .filter(|interval| !interval.is_zero())
+                    .unwrap_or(self.interval);
+                Entry::Polled {
+                    agent: Box::new(agent.clone()),
+                    due: now + interval,
+                }
+            }
+        };
+        self.panes.insert(agent.key(), entry);
+    }
+
+    /// Forgets the panes that are gone
+    pub fn retain(&mut self, live: &HashSet<String>) {
+        self.panes.retain(|key, _| live.contains(key));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::AgentType;
+
+    fn agent(pane: u32, label: &str) -> MonitoredAgent {
+        let mut agent = MonitoredAgent::new(
+            format!("%{}", pane),
+            format!("main:0.{}", pane),
+            "main".to_string(),
+            0,
+            "editor".to_string(),
+            pane,
+            "/code/app".to_string(),
+            AgentType::ClaudeCode,
+            42,
+        );
+        agent.label = Some(label.to_string());
+        agent
+    }
+
+    #[test]
+    fn test_schedule() {
+        let rules = [
+            PanePolling {
+                filter: "label:fast".to_string(),
+                interval_ms: 100,
+                ignore: false,
+            },
+            PanePolling {
+                filter: "label:slow".to_string(),
+                interval_ms: 5000,
+                ignore: false,
+            },
+            PanePolling {
+                filter: "label:shell".to_string(),
+                interval_ms: 0,
+                ignore: true,
+            },
+        ];
+        let mut schedule = PollSchedule::new(Duration::from_millis(500), &rules).unwrap();
+        assert_eq!(schedule.tick(), Duration::from_millis(100));
+
+        let now = Instant::now();
+        let (fast, slow, other, shell) = (
+            agent(0, "fast"),
+            agent(1, "slow"),
+            agent(2, "other"),
+            agent(3, "shell"),
+        );
+        for agent in [&fast, &slow, &other, &shell] {
+            assert!(matches!(schedule.turn(&agent.key(), now), PaneTurn::Poll));
+            schedule.polled(agent, now);
+        }
+
+        let later = now + Duration::from_millis(600);
+        let turn = |schedule: &PollSchedule, agent: &MonitoredAgent, at| match schedule
+            .turn(&agent.key(), at)
+        {
+            PaneTurn::Poll => "poll",
+            PaneTurn::Wait(last) if last.label == agent.label => "wait",
+            PaneTurn::Wait(_) => "wait for another pane",
+            PaneTurn::Ignore => "ignore",
+        };
+        assert_eq!(turn(&schedule, &fast, later), "poll");
+        assert_eq!(turn(&schedule, &other, later), "poll");
+        assert_eq!(turn(&schedule, &slow, later), "wait");
+        assert_eq!(turn(&schedule, &shell, later), "ignore");
+        let much_later = now + Duration::from_secs(5);
+        assert_eq!(turn(&schedule, &slow, much_later), "poll");
+
+        // Gone panes start over
+        schedule.retain(&HashSet::from([fast.key()]));
+        assert_eq!(turn(&schedule, &shell, later), "poll");
+
+        let bad = PanePolling {
+            filter: "colour:red".to_string(),
+            ..PanePolling::default()
+        };
+        assert!(PollSchedule::new(Duration::from_millis(500), &[bad]).is_err());
+    }
+}
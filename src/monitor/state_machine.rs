@@ -0,0 +1,268 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+
+use crate::agents::{AgentState, AgentStatus, MonitoredAgent};
+use serde::{Deserialize, Serialize};
+
+/// How long a pane must look idle before it leaves its current state
+///
+/// Agents briefly show an idle prompt between tool calls and after a prompt
+/// is answered; without this the state would flap on every poll.
+const IDLE_SETTLE: Duration = Duration::from_millis(2000);
+
+/// A change of an agent's state, reported to the UI with each update
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateTransition {
+    /// Server-qualified pane target (see [`MonitoredAgent::key`])
+    pub key: String,
+    /// State before the change, `None` for a newly seen pane
+    pub from: Option<AgentState>,
+    pub to: AgentState,
+    /// When the new state was entered
+    pub at: DateTime<Local>,
+}
+
+struct PaneState {
+    state: AgentState,
+    /// Status of the last observation that matched `state`
+    status: AgentStatus,
+    since: Instant,
+    /// When the pane started to look idle, while that isn't settled yet
+    idle_since: Option<Instant>,
+    /// Agent id the totals belong to; they restart with a new agent process
+    session: String,
+    /// When the pane was last observed
+    seen: Instant,
+    working: Duration,
+    idle: Duration,
+}
+
+impl PaneState {
+    /// Returns the running total kept for `state`, if any
+    fn total(&mut self, state: AgentState) -> Option<&mut Duration> {
+        match state {
+            AgentState::Working => Some(&mut self.working),
+            AgentState::Idle => Some(&mut self.idle),
+            _ => None,
+        }
+    }
+}
+
+/// Per-pane state machine turning one-shot status classifications into stable states
+///
+/// Entering Working, WaitingForInput, WaitingForPermission or Error happens on
+/// the first observation; falling back to Idle only once the pane has looked
+/// idle for [`IDLE_SETTLE`]. Unknown observations keep the current state.
+#[derive(Default)]
+pub struct StateTracker {
+    panes: HashMap<String, PaneState>,
+}
+
+impl StateTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds the status parsed for `agent` and replaces it with the stable one
+    ///
+    /// Sets `agent.state`, `agent.state_since` and the session's working and
+    /// idle totals; returns the transition if the state changed.
+    pub fn apply(&mut self, agent: &mut MonitoredAgent, now: Instant) -> Option<StateTransition> {
+        let key = agent.key();
+        let observed = AgentState::from_status(&agent.status);
+
+        let Some(pane) = self.panes.get_mut(&key) else {
+            let state = observed?;
+            self.panes.insert(
+                key.clone(),
+                PaneState {
+                    state,
+                    status: agent.status.clone(),
+                    since: now,
+                    idle_since: None,
+                    session: agent.id.clone(),
+                    seen: now,
+                    working: Duration::ZERO,
+                    idle: Duration::ZERO,
+                },
+            );
+            agent.state = Some(state);
+            agent.state_since = now;
+            return Some(StateTransition {
+                key,
+                from: None,
+                to: state,
+                at: Local::now(),
+            });
+        };
+
+        if pane.session != agent.id {
+            pane.session = agent.id.clone();
+            pane.working = Duration::ZERO;
+            pane.idle = Duration::ZERO;
+        }
+        let elapsed = now.saturating_duration_since(pane.seen);
+        pane.seen = now;
+        if let Some(total) = pane.total(pane.state) {
+            *total += elapsed;
+        }
+
+        let entered = match observed {
+            Some(state) if state == pane.state => {
+                pane.status = agent.status.clone();
+                pane.idle_since = None;
+                None
+            }
+            Some(AgentState::Idle) => {
+                let idle_since = *pane.idle_since.get_or_insert(now);
+                (now.duration_since(idle_since) >= IDLE_SETTLE)
+                    .then_some((AgentState::Idle, idle_since))
+            }
+            Some(state) => Some((state, now)),
+            None => None,
+        };
+
+        let transition = entered.map(|(state, since)| {
+            let from = pane.state;
+            // Idle is entered in the past, give the settle time to it
+            let backdated = now.saturating_duration_since(since);
+            if let Some(total) = pane.total(from) {
+                *total = total.saturating_sub(backdated);
+            }
+            if let Some(total) = pane.total(state) {
+                *total += backdated;
+            }
+            pane.state = state;
+            pane.status = agent.status.clone();
+            pane.since = since;
+            pane.idle_since = None;
+            StateTransition {
+                key,
+                from: Some(from),
+                to: state,
+                at: Local::now() - chrono::Duration::from_std(now - since).unwrap_or_default(),
+            }
+        });
+
+        // Hold the previous status while a change isn't settled
+        agent.status = pane.status.clone();
+        agent.state = Some(pane.state);
+        agent.state_since = pane.since;
+        agent.working_time = pane.working;
+        agent.idle_time = pane.idle;
+        transition
+    }
+
+    /// Forgets panes other than `live` (call once per poll)
+    pub fn retain(&mut self, live: &HashSet<String>) {
+        self.panes.retain(|key, _| live.contains(key));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::{AgentType, ApprovalType};
+
+    fn observe(
+        tracker: &mut StateTracker,
+        status: AgentStatus,
+        at: Instant,
+    ) -> (MonitoredAgent, Option<StateTransition>) {
+        let mut agent = MonitoredAgent::new(
+            "main:0.0-1".to_string(),
+            "main:0.0".to_string(),
+            "main".to_string(),
+            0,
+            "code".to_string(),
+            0,
+            "/repo".to_string(),
+            AgentType::ClaudeCode,
+            1,
+        );
+        agent.status = status;
+        let transition = tracker.apply(&mut agent, at);
+        (agent, transition)
+    }
+
+    fn working() -> AgentStatus {
+        AgentStatus::Processing {
+            activity: "Reading".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_idle_settles() {
+        let mut tracker = StateTracker::new();
+        let start = Instant::now();
+
+        let (agent, transition) = observe(&mut tracker, working(), start);
+        assert_eq!(agent.state, Some(AgentState::Working));
+        assert_eq!(transition.unwrap().from, None);
+
+        // A short idle flicker keeps the agent working, with its last status
+        let t = start + Duration::from_millis(500);
+        let (agent, transition) = observe(&mut tracker, AgentStatus::Idle, t);
+        assert_eq!(agent.state, Some(AgentState::Working));
+        assert_eq!(agent.status, working());
+        assert!(transition.is_none());
+
+        let (_, transition) = observe(&mut tracker, AgentStatus::Unknown, t);
+        assert!(transition.is_none());
+
+        let t = start + Duration::from_millis(2600);
+        let (agent, transition) = observe(&mut tracker, AgentStatus::Idle, t);
+        assert_eq!(agent.state, Some(AgentState::Idle));
+        assert_eq!(agent.state_since, start + Duration::from_millis(500));
+        let transition = transition.unwrap();
+        assert_eq!(transition.from, Some(AgentState::Working));
+        assert_eq!(transition.to, AgentState::Idle);
+    }
+
+    #[test]
+    fn test_prompts_enter_immediately() {
+        let mut tracker = StateTracker::new();
+        let start = Instant::now();
+        observe(&mut tracker, AgentStatus::Idle, start);
+
+        let question = AgentStatus::AwaitingApproval {
+            approval_type: ApprovalType::UserQuestion {
+                choices: vec!["A".to_string()],
+                multi_select: false,
+            },
+            details: String::new(),
+        };
+        let (agent, transition) = observe(&mut tracker, question, start);
+        assert_eq!(agent.state, Some(AgentState::WaitingForInput));
+        assert_eq!(transition.unwrap().from, Some(AgentState::Idle));
+
+        let edit = AgentStatus::AwaitingApproval {
+            approval_type: ApprovalType::FileEdit,
+            details: String::new(),
+        };
+        let (agent, _) = observe(&mut tracker, edit, start);
+        assert_eq!(agent.state, Some(AgentState::WaitingForPermission));
+    }
+
+    #[test]
+    fn test_session_totals() {
+        let mut tracker = StateTracker::new();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        observe(&mut tracker, working(), start);
+        observe(&mut tracker, working(), at(10_000));
+        observe(&mut tracker, AgentStatus::Idle, at(11_000));
+        let (agent, _) = observe(&mut tracker, AgentStatus::Idle, at(14_000));
+        assert_eq!(agent.state, Some(AgentState::Idle));
+        // The settle time counts as idle
+        assert_eq!(agent.working_time, Duration::from_millis(11_000));
+        assert_eq!(agent.idle_time, Duration::from_millis(3_000));
+
+        let (agent, _) = observe(&mut tracker, working(), at(20_000));
+        assert_eq!(agent.idle_time, Duration::from_millis(9_000));
+        assert_eq!(agent.working_time, Duration::from_millis(11_000));
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tracing::{debug, warn};
+
+use super::state_machine::StateTransition;
+use crate::agents::{AgentState, MonitoredAgent};
+
+/// Lines of output piped to the summarize command
+const SUMMARY_LINES: usize = 60;
+
+/// How long the summarize command may run before it is killed
+const SUMMARY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Summary of a pane's wait, `None` while the command still runs
+struct Pending {
+    /// Bumped for every wait so results of an older one are dropped
+    generation: u64,
+    summary: Option<String>,
+}
+
+/// Runs `summarize_cmd` on the output of agents that start waiting for input
+///
+/// The command gets the last lines of the pane on stdin and its first
+/// non-empty line of output becomes `agent.summary` until the agent stops
+/// waiting. Commands run on their own threads, so slow ones (e.g. an LLM call)
+/// don't hold up polling, one at a time per pane and for at most
+/// [`SUMMARY_TIMEOUT`].
+pub struct Summarizer {
+    command: String,
+    summaries: Arc<Mutex<HashMap<String, Pending>>>,
+    /// Panes whose command still runs
+    running: Arc<Mutex<HashSet<String>>>,
+    generation: u64,
+}
+
+impl Summarizer {
+    pub fn new(command: String) -> Self {
+        Self {
+            command,
+            summaries: Arc::default(),
+            running: Arc::default(),
+            generation: 0,
+        }
+    }
+
+    /// Starts summaries for panes that just started waiting and sets
+    /// `summary` of waiting agents whose summary is ready
+    pub fn apply(&mut self, transitions: &[StateTransition], agents: &mut [MonitoredAgent]) {
+        let mut summaries = self.summaries.lock();
+        summaries.retain(|key, _| {
+            agents
+                .iter()
+                .any(|a| a.key() == *key && a.state == Some(AgentState::WaitingForInput))
+        });
+
+        for transition in transitions {
+            if transition.to != AgentState::WaitingForInput {
+                continue;
+            }
+            let Some(agent) = agents.iter().find(|a| a.key() == transition.key) else {
+                continue;
+            };
+            // A pane asking again before the last summary came back gets none
+            if !self.running.lock().insert(transition.key.clone()) {
+                continue;
+            }
+            self.generation += 1;
+            summaries.insert(
+                transition.key.clone(),
+                Pending {
+                    generation: self.generation,
+                    summary: None,
+                },
+            );
+            self.spawn(
+                transition.key.clone(),
+                self.generation,
+                tail(&agent.last_content),
+            );
+        }
+
+        for agent in agents.iter_mut() {
+            agent.summary = summaries
+                .get(&agent.key())
+                .and_then(|pending| pending.summary.clone());
+        }
+    }
+
+    fn spawn(&self, key: String, generation: u64, input: String) {
+        let command = self.command.clone();
+        let summaries = self.summaries.clone();
+        let running = self.running.clone();
+        std::thread::spawn(move || {
+            let result = run(&command, &key, &input, SUMMARY_TIMEOUT);
+            running.lock().remove(&key);
+            let summary = match result {
+                Ok(summary) => summary,
+                Err(e) => {
+                    warn!("summarize_cmd failed for {}: {}", key, e);
+                    return;
+                }
+            };
+            debug!("Summary for {}: {}", key, summary);
+            if let Some(pending) = summaries.lock().get_mut(&key) {
+                if pending.generation == generation {
+                    pending.summary = Some(summary);
+                }
+            }
+        });
+    }
+}
+
+/// Returns the last [`SUMMARY_LINES`] lines of `content`
+fn tail(content: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    lines[lines.len().saturating_sub(SUMMARY_LINES)..].join("\n")
+}
+
+/// Runs `command` with `input` on stdin and returns its first non-empty
+/// line, killing it and whatever it started after `timeout`
+fn run(command: &str, key: &str, input: &str, timeout: Duration) -> anyhow::Result<String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("TMUXCC_PANE", key)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let input = input.to_string();
+    // A command that doesn't read all of its input is fine, and one that
+    // reads none doesn't block this thread
+    std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let mut output = Vec::new();
+        let _ = stdout.read_to_end(&mut output);
+        let _ = tx.send(output);
+    });
+
+    let Ok(output) = rx.recv_timeout(timeout) else {
+        // SAFETY: kill has no memory preconditions; the group is the child's own
+        unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) };
+        let _ = child.wait();
+        anyhow::bail!("killed after {}s", timeout.as_secs_f32());
+    };
+    let status = child.wait()?;
+    let stdout = String::from_utf8_lossy(&output);
+    let line = stdout.lines().map(str::trim).find(|l| !l.is_empty());
+    match line {
+        Some(line) => Ok(line.to_string()),
+        None => anyhow::bail!("no output (exit status {})", status),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::AgentType;
+
+    #[test]
+    fn test_summarizes_waiting_agents() {
+        let mut agents = vec![MonitoredAgent::new(
+            "%1-100".to_string(),
+            "main:0.0".to_string(),
+            "main".to_string(),
+            0,
+            "code".to_string(),
+            0,
+            "/repo".to_string(),
+            AgentType::ClaudeCode,
+            100,
+        )];
+        agents[0].state = Some(AgentState::WaitingForInput);
+        agents[0].last_content = "Which database?\n❯ 1. Postgres\n  2. SQLite".to_string();
+        let transition = StateTransition {
+            key: agents[0].key(),
+            from: Some(AgentState::Working),
+            to: AgentState::WaitingForInput,
+            at: chrono::Local::now(),
+        };
+
+        let mut summarizer = Summarizer::new("grep -m1 '?' | tr a-z A-Z".to_string());
+        summarizer.apply(std::slice::from_ref(&transition), &mut agents);
+        for _ in 0..100 {
+            if agents[0].summary.is_some() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+            summarizer.apply(&[], &mut agents);
+        }
+        assert_eq!(agents[0].summary.as_deref(), Some("WHICH DATABASE?"));
+
+        // Dropped once the agent moves on
+        agents[0].state = Some(AgentState::Working);
+        summarizer.apply(&[], &mut agents);
+        assert_eq!(agents[0].summary, None);
+
+        // One command at a time per pane
+        agents[0].state = Some(AgentState::WaitingForInput);
+        let mut summarizer = Summarizer::new("sleep 1; echo late".to_string());
+        summarizer.apply(std::slice::from_ref(&transition), &mut agents);
+        summarizer.apply(&[transition], &mut agents);
+        assert_eq!(summarizer.generation, 1);
+    }
+
+    #[test]
+    fn test_kills_slow_commands() {
+        let started = std::time::Instant::now();
+        let result = run(
+            "sleep 5; echo late",
+            "main:0.0",
+            "",
+            Duration::from_millis(200),
+        );
+        assert!(result.unwrap_err().to_string().starts_with("killed after"));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
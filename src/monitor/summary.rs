@@ -0,0 +1,186 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+
+use crate::agents::{AgentStatus, MonitoredAgent};
+
+/// How old the cache may be before `tmuxcc statusline` polls tmux itself
+pub const SUMMARY_MAX_AGE: Duration = Duration::from_secs(10);
+
+/// Rewrite an unchanged cache this often so readers can tell it's fresh
+const SUMMARY_REFRESH: Duration = Duration::from_secs(2);
+
+/// Counts of agents per state, as shown in the tmux status line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatusSummary {
+    pub working: usize,
+    pub waiting: usize,
+    pub error: usize,
+    pub idle: usize,
+}
+
+impl StatusSummary {
+    /// Counts the states of `agents`
+    pub fn from_agents(agents: &[MonitoredAgent]) -> Self {
+        let mut summary = Self::default();
+        for agent in agents {
+            match agent.status {
+                AgentStatus::Processing { .. } => summary.working += 1,
+                AgentStatus::AwaitingApproval { .. } => summary.waiting += 1,
+                AgentStatus::Error { .. } => summary.error += 1,
+                AgentStatus::Idle => summary.idle += 1,
+                AgentStatus::Unknown => {}
+            }
+        }
+        summary
+    }
+
+    /// Formats the summary with tmux style directives for `status-right`
+    ///
+    /// Non-zero waiting and error counts are highlighted.
+    pub fn tmux_format(&self) -> String {
+        let part = |count: usize, label: &str, color: &str| {
+            if count > 0 {
+                format!("#[fg={}]{} {}#[default]", color, count, label)
+            } else {
+                format!("{} {}", count, label)
+            }
+        };
+        [
+            part(self.working, "working", "blue"),
+            part(self.waiting, "waiting", "yellow"),
+            part(self.error, "error", "red"),
+        ]
+        .join(" · ")
+    }
+
+    /// Formats the summary as plain text, idle agents included
+    pub fn plain_format(&self) -> String {
+        format!(
+            "{} working · {} waiting · {} error · {} idle",
+            self.working, self.waiting, self.error, self.idle
+        )
+    }
+
+    /// Serializes the summary for the cache file
+    fn to_cache(self) -> String {
+        format!(
+            "working={}\nwaiting={}\nerror={}\nidle={}\n",
+            self.working, self.waiting, self.error, self.idle
+        )
+    }
+
+    /// Parses the cache file written by [`SummaryCache`]
+    fn parse_cache(content: &str) -> Self {
+        let mut summary = Self::default();
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim().parse().unwrap_or(0);
+            match key.trim() {
+                "working" => summary.working = value,
+                "waiting" => summary.waiting = value,
+                "error" => summary.error = value,
+                "idle" => summary.idle = value,
+                _ => {}
+            }
+        }
+        summary
+    }
+}
+
+/// File where the running monitor publishes the latest [`StatusSummary`]
+pub struct SummaryCache {
+    path: PathBuf,
+    last: Option<(StatusSummary, SystemTime)>,
+}
+
+impl SummaryCache {
+    /// Returns the default cache file path
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|p| p.join("tmuxcc").join("status"))
+    }
+
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, last: None }
+    }
+
+    /// Writes `summary` if it changed or the file is due for a refresh
+    pub fn store(&mut self, summary: StatusSummary) -> Result<()> {
+        let now = SystemTime::now();
+        if let Some((last, written)) = self.last {
+            let age = now.duration_since(written).unwrap_or_default();
+            if last == summary && age < SUMMARY_REFRESH {
+                return Ok(());
+            }
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Write then rename so readers never see a partial file
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, summary.to_cache())?;
+        std::fs::rename(&tmp, &self.path)?;
+        self.last = Some((summary, now));
+        Ok(())
+    }
+
+    /// Reads the summary at `path` if it was written within `max_age`
+    pub fn load(path: &Path, max_age: Duration) -> Option<StatusSummary> {
+        let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+        if SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default()
+            > max_age
+        {
+            return None;
+        }
+        let content = std::fs::read_to_string(path).ok()?;
+        Some(StatusSummary::parse_cache(&content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tmux_format() {
+        let summary = StatusSummary {
+            working: 3,
+            waiting: 1,
+            error: 0,
+            idle: 2,
+        };
+        assert_eq!(
+            summary.tmux_format(),
+            "#[fg=blue]3 working#[default] · #[fg=yellow]1 waiting#[default] · 0 error"
+        );
+        assert_eq!(
+            summary.plain_format(),
+            "3 working · 1 waiting · 0 error · 2 idle"
+        );
+    }
+
+    #[test]
+    fn test_cache_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tmuxcc").join("status");
+        let summary = StatusSummary {
+            working: 1,
+            waiting: 2,
+            error: 3,
+            idle: 4,
+        };
+
+        let mut cache = SummaryCache::new(path.clone());
+        cache.store(summary).unwrap();
+        assert_eq!(SummaryCache::load(&path, SUMMARY_MAX_AGE), Some(summary));
+        assert_eq!(
+            SummaryCache::load(&dir.path().join("missing"), SUMMARY_MAX_AGE),
+            None
+        );
+    }
+}
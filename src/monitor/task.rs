@@ -1,22 +1,111 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use tokio::sync::mpsc;
 use tracing::{debug, error, warn};
 
-use crate::agents::{AgentStatus, MonitoredAgent};
-use crate::app::AgentTree;
-use crate::parsers::ParserRegistry;
-use crate::tmux::{refresh_process_cache, TmuxClient};
+use crate::agents::{AgentStatus, MonitoredAgent, Subagent, Usage};
+use crate::app::{AgentTree, HooksConfig, StatusTitles};
+use crate::git::{GitCache, GitEnv, PrCache};
+use crate::parsers::stream_json::{read_stream, STREAM_VAR};
+use crate::parsers::{is_version_like, ParserRegistry};
+use crate::tmux::{
+    refresh_process_cache, strip_ansi, ControlClient, ControlEvent, HookEvent, TmuxClient,
+    TmuxServer, TmuxTree,
+};
+use serde::{Deserialize, Serialize};
 
-/// Hysteresis duration - keep "Processing" status for this long after last active detection
-const STATUS_HYSTERESIS_MS: u64 = 2000;
+/// Delay after a control mode notification before polling, so bursts of output coalesce
+const CONTROL_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Fallback poll interval in control mode, for changes tmux doesn't notify about
+const CONTROL_FALLBACK_INTERVAL: Duration = Duration::from_secs(2);
+
+use super::activity::ActivityTracker;
+use super::auto_approve::{ApprovalDecision, AutoApprover};
+use super::auto_respond::{AutoResponder, AutoResponse, SharedSwitches};
+use super::capture::{CaptureStore, PaneChanged};
+use super::context::{ContextWarning, ContextWatch};
+use super::event_hooks::EventHooks;
+use super::notify_rules::NotifyRouter;
+use super::project::ProjectCache;
+use super::rate_limit::RateLimitTracker;
+use super::registry::SharedRegistry;
+use super::schedule::{PaneTurn, PollSchedule};
+use super::state_machine::{StateTracker, StateTransition};
+use super::summarizer::Summarizer;
+use super::summary::{StatusSummary, SummaryCache};
+use super::titles::TitleUpdater;
+use super::watchdog::{Stall, Watchdog};
+use super::webhook::{WebhookTarget, Webhooks};
+
+/// Parts of the monitor rebuilt from a reloaded config
+///
+/// Servers, the poll interval and other settings fixed at startup still
+/// need a restart.
+pub struct MonitorReload {
+    pub parser_registry: Arc<ParserRegistry>,
+    pub auto_approver: Option<AutoApprover>,
+    pub auto_responder: Option<AutoResponder>,
+    pub webhook_targets: Vec<WebhookTarget>,
+    pub router: NotifyRouter,
+    pub hooks: HooksConfig,
+    pub schedule: PollSchedule,
+}
 
 /// Update message sent from monitor to UI
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MonitorUpdate {
     pub agents: AgentTree,
+    /// Agent panes whose content changed since the previous update
+    pub changes: Vec<PaneChanged>,
+    /// Agent state changes since the previous update
+    pub transitions: Vec<StateTransition>,
+    /// Permission prompts answered or held by the auto-approve rules
+    pub approvals: Vec<ApprovalDecision>,
+    /// Agents whose context left just dropped below the warning threshold
+    pub context_warnings: Vec<ContextWarning>,
+    /// Questions answered by the auto-respond rules
+    pub responses: Vec<AutoResponse>,
+    /// Working agents that just went quiet for too long
+    pub stalls: Vec<Stall>,
+}
+
+/// Result of one poll of all servers
+struct Poll {
+    tree: AgentTree,
+    changes: Vec<PaneChanged>,
+    transitions: Vec<StateTransition>,
+    approvals: Vec<ApprovalDecision>,
+    context_warnings: Vec<ContextWarning>,
+    responses: Vec<AutoResponse>,
+    stalls: Vec<Stall>,
+}
+
+/// Parser results for a pane, reused while its content is unchanged
+struct ParsedPane {
+    status: AgentStatus,
+    subagents: Vec<Subagent>,
+    context_remaining: Option<u8>,
+    usage: Option<Usage>,
+}
+
+/// Why [`MonitorTask::wait_for_next_poll`] returned
+enum Wake {
+    Interval,
+    Event,
+    ControlClosed,
+    HooksClosed,
+}
+
+/// Receives from `rx`, or waits forever when there is no receiver
+async fn recv_optional<T>(rx: &mut Option<mpsc::Receiver<T>>) -> Option<T> {
+    match rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
 }
 
 /// Background task that monitors tmux panes for AI agents
@@ -24,10 +113,55 @@ pub struct MonitorTask {
     tmux_client: Arc<TmuxClient>,
     parser_registry: Arc<ParserRegistry>,
     tx: mpsc::Sender<MonitorUpdate>,
-    poll_interval: Duration,
-    /// Track when each agent was last seen as "active" (Processing/AwaitingApproval)
-    /// Key: agent target string
-    last_active: HashMap<String, Instant>,
+    /// When each pane is captured
+    schedule: PollSchedule,
+    /// tmux servers to monitor
+    servers: Vec<TmuxServer>,
+    /// Stable per-pane states, keyed by server-qualified agent target
+    states: StateTracker,
+    /// Git information cached per repository root
+    git_cache: GitCache,
+    /// GitHub pull requests cached per repository and branch
+    pr_cache: PrCache,
+    /// `.tmuxcc.toml` files of the panes' repositories
+    projects: ProjectCache,
+    /// Last captured content per pane
+    captures: CaptureStore,
+    /// Parser results of the last changed capture per pane
+    parsed: HashMap<String, ParsedPane>,
+    /// New output lines per pane and minute
+    activity: ActivityTracker,
+    /// tmux control mode connection, when event-driven monitoring is enabled
+    control: Option<ControlClient>,
+    /// Notifications from the control mode connection
+    events: Option<mpsc::Receiver<ControlEvent>>,
+    /// Pane lifecycle notifications from tmux hooks
+    hook_events: Option<mpsc::Receiver<HookEvent>>,
+    /// Rebuilt parts from config reloads, applied before the next poll
+    reloads: Option<mpsc::UnboundedReceiver<MonitorReload>>,
+    /// Rewrites tmux window/pane names to show agent state, when enabled
+    titles: Option<TitleUpdater>,
+    /// Where the per-state counts are published for `tmuxcc statusline`
+    summary_cache: Option<SummaryCache>,
+    registry: Option<SharedRegistry>,
+    /// Answers permission prompts by rule, when enabled
+    auto_approver: Option<AutoApprover>,
+    /// Answers routine questions by rule, when configured
+    auto_responder: Option<AutoResponder>,
+    /// Kill switch and per-pane exclusions for automatic answers
+    switches: Option<SharedSwitches>,
+    /// Warns when an agent's context runs low, when enabled
+    context_watch: Option<ContextWatch>,
+    /// Rate limited panes and their reset times
+    rate_limits: RateLimitTracker,
+    /// Flags working agents without new output, when enabled
+    watchdog: Option<Watchdog>,
+    /// Summarizes the output of agents waiting for input, when configured
+    summarizer: Option<Summarizer>,
+    /// POSTs state changes to webhooks, when configured
+    webhooks: Option<Webhooks>,
+    /// Runs shell hooks on events, when configured
+    event_hooks: Option<EventHooks>,
 }
 
 impl MonitorTask {
@@ -41,17 +175,206 @@ impl MonitorTask {
             tmux_client,
             parser_registry,
             tx,
-            poll_interval,
-            last_active: HashMap::new(),
+            schedule: PollSchedule::uniform(poll_interval),
+            servers: vec![TmuxServer::Default],
+            states: StateTracker::new(),
+            git_cache: GitCache::new(),
+            pr_cache: PrCache::new(),
+            projects: ProjectCache::new(),
+            captures: CaptureStore::new(),
+            parsed: HashMap::new(),
+            activity: ActivityTracker::new(),
+            control: None,
+            events: None,
+            hook_events: None,
+            reloads: None,
+            titles: None,
+            summary_cache: None,
+            registry: None,
+            auto_approver: None,
+            auto_responder: None,
+            switches: None,
+            context_watch: None,
+            rate_limits: RateLimitTracker::new(String::new()),
+            watchdog: None,
+            summarizer: None,
+            webhooks: None,
+            event_hooks: None,
+        }
+    }
+
+    /// Enables event-driven monitoring through a tmux control mode connection
+    ///
+    /// Polls run as soon as tmux reports output or layout changes instead of
+    /// waiting for the next interval. Falls back to interval polling if the
+    /// connection can't be established.
+    pub fn with_control_mode(mut self) -> Self {
+        let (tx, rx) = mpsc::channel(256);
+        match ControlClient::spawn_for_attached_session(tx) {
+            Ok(client) => {
+                self.control = Some(client);
+                self.events = Some(rx);
+            }
+            Err(e) => warn!("Control mode unavailable, polling instead: {}", e),
+        }
+        self
+    }
+
+    /// Monitors the panes of every server in `servers` instead of only the
+    /// default one
+    ///
+    /// All panes of additional servers are monitored, since agents isolated
+    /// there usually run in detached sessions. An empty list keeps the default.
+    pub fn with_servers(mut self, servers: Vec<TmuxServer>) -> Self {
+        if !servers.is_empty() {
+            self.servers = servers;
+        }
+        self
+    }
+
+    /// Polls immediately when a tmux hook reports a pane lifecycle change
+    pub fn with_hook_events(mut self, hook_events: mpsc::Receiver<HookEvent>) -> Self {
+        self.hook_events = Some(hook_events);
+        self
+    }
+
+    /// Renames tmux windows and/or panes after each poll to show agent state
+    pub fn with_status_titles(mut self, mode: StatusTitles) -> Self {
+        if mode != StatusTitles::Off {
+            self.titles = Some(TitleUpdater::new(self.tmux_client.clone(), mode));
+        }
+        self
+    }
+
+    /// Publishes per-state agent counts to `path` after each poll
+    pub fn with_summary_cache(mut self, path: PathBuf) -> Self {
+        self.summary_cache = Some(SummaryCache::new(path));
+        self
+    }
+
+    /// Remembers labels, notes and statistics of panes in `registry`
+    pub fn with_registry(mut self, registry: SharedRegistry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Answers permission prompts according to the rules of `approver`
+    pub fn with_auto_approver(mut self, approver: AutoApprover) -> Self {
+        self.auto_approver = Some(approver);
+        self
+    }
+
+    /// Replies to questions according to the rules of `responder`
+    pub fn with_auto_responder(mut self, responder: AutoResponder) -> Self {
+        self.auto_responder = Some(responder);
+        self
+    }
+
+    /// Skips automatic answers while `switches` say so
+    pub fn with_switches(mut self, switches: SharedSwitches) -> Self {
+        self.switches = Some(switches);
+        self
+    }
+
+    /// Reports agents whose context left drops below `threshold` percent;
+    /// 0 never warns
+    pub fn with_context_warning(mut self, threshold: u8) -> Self {
+        if threshold > 0 {
+            self.context_watch = Some(ContextWatch::new(threshold));
         }
+        self
+    }
+
+    /// Sends `message` to rate limited agents once their limit resets
+    pub fn with_rate_limit_resume(mut self, message: String) -> Self {
+        self.rate_limits = RateLimitTracker::new(message);
+        self
+    }
+
+    /// Flags working agents quiet for `after` as stalled and runs `hook`
+    /// for each; a zero duration disables the watchdog
+    pub fn with_watchdog(mut self, after: Duration, hook: String) -> Self {
+        if !after.is_zero() {
+            self.watchdog = Some(Watchdog::new(after, hook));
+        }
+        self
+    }
+
+    /// Pipes the output of agents that start waiting for input through
+    /// `command` and shows its first line; empty disables summaries
+    pub fn with_summarize_cmd(mut self, command: String) -> Self {
+        if !command.is_empty() {
+            self.summarizer = Some(Summarizer::new(command));
+        }
+        self
+    }
+
+    /// POSTs state changes to `targets` as `router` says; no targets
+    /// disables webhooks
+    pub fn with_webhooks(mut self, targets: Vec<WebhookTarget>, router: NotifyRouter) -> Self {
+        if !targets.is_empty() {
+            self.webhooks = Some(Webhooks::new(targets, router));
+        }
+        self
+    }
+
+    /// Runs the shell commands of `hooks` on events; none set disables hooks
+    pub fn with_event_hooks(mut self, hooks: HooksConfig) -> Self {
+        if !hooks.is_empty() {
+            self.event_hooks = Some(EventHooks::new(hooks));
+        }
+        self
+    }
+
+    /// Captures panes as often as `schedule` says instead of every poll
+    /// interval
+    pub fn with_poll_schedule(mut self, schedule: PollSchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Applies the configs sent to `reloads` before the following poll
+    pub fn with_reloads(mut self, reloads: mpsc::UnboundedReceiver<MonitorReload>) -> Self {
+        self.reloads = Some(reloads);
+        self
+    }
+
+    /// Switches to the parsers, rules and notifiers of a reloaded config
+    fn apply_reload(&mut self, reload: MonitorReload) {
+        self.parser_registry = reload.parser_registry;
+        // Parse every pane again with the new patterns
+        self.parsed.clear();
+        self.auto_approver = reload.auto_approver;
+        self.auto_responder = reload.auto_responder;
+        self.schedule = reload.schedule;
+        self.webhooks = (!reload.webhook_targets.is_empty())
+            .then(|| Webhooks::new(reload.webhook_targets, reload.router));
+        match self.event_hooks.as_mut() {
+            _ if reload.hooks.is_empty() => self.event_hooks = None,
+            Some(hooks) => hooks.set_config(reload.hooks),
+            None => self.event_hooks = Some(EventHooks::new(reload.hooks)),
+        }
+    }
+
+    /// Polls tmux once and returns the detected agents
+    pub async fn poll_once(&mut self) -> anyhow::Result<AgentTree> {
+        self.poll_agents().await.map(|poll| poll.tree)
     }
 
     /// Runs the monitoring loop
     pub async fn run(mut self) {
         loop {
             match self.poll_agents().await {
-                Ok(tree) => {
-                    let update = MonitorUpdate { agents: tree };
+                Ok(poll) => {
+                    let update = MonitorUpdate {
+                        agents: poll.tree,
+                        changes: poll.changes,
+                        transitions: poll.transitions,
+                        approvals: poll.approvals,
+                        context_warnings: poll.context_warnings,
+                        responses: poll.responses,
+                        stalls: poll.stalls,
+                    };
                     if self.tx.send(update).await.is_err() {
                         debug!("Monitor channel closed, stopping");
                         break;
@@ -62,121 +385,357 @@ impl MonitorTask {
                 }
             }
 
-            tokio::time::sleep(self.poll_interval).await;
+            self.wait_for_next_poll().await;
+        }
+    }
+
+    /// Waits for the poll interval, or until tmux reports a change through
+    /// control mode or a hook
+    async fn wait_for_next_poll(&mut self) {
+        let interval = if self.events.is_some() {
+            self.schedule.tick().max(CONTROL_FALLBACK_INTERVAL)
+        } else {
+            self.schedule.tick()
+        };
+
+        let woken = tokio::select! {
+            _ = tokio::time::sleep(interval) => Wake::Interval,
+            event = recv_optional(&mut self.events) => match event {
+                Some(ControlEvent::Exit { reason }) => {
+                    warn!("Control mode client exited: {}", reason.unwrap_or_default());
+                    Wake::ControlClosed
+                }
+                None => Wake::ControlClosed,
+                Some(_) => Wake::Event,
+            },
+            event = recv_optional(&mut self.hook_events) => match event {
+                Some(event) => {
+                    debug!("tmux hook {} {:?}", event.name, event.args);
+                    Wake::Event
+                }
+                None => Wake::HooksClosed,
+            },
+        };
+
+        match woken {
+            Wake::Interval => {}
+            Wake::Event => {
+                // Let bursts of notifications coalesce into one poll
+                tokio::time::sleep(CONTROL_DEBOUNCE).await;
+                if let Some(events) = self.events.as_mut() {
+                    while events.try_recv().is_ok() {}
+                }
+                if let Some(hook_events) = self.hook_events.as_mut() {
+                    while hook_events.try_recv().is_ok() {}
+                }
+            }
+            Wake::ControlClosed => {
+                // Fall back to interval polling for the rest of the session
+                self.events = None;
+                self.control = None;
+            }
+            Wake::HooksClosed => self.hook_events = None,
         }
     }
 
-    async fn poll_agents(&mut self) -> anyhow::Result<AgentTree> {
+    async fn poll_agents(&mut self) -> anyhow::Result<Poll> {
+        // Only the latest of several reloads matters
+        let reload = self
+            .reloads
+            .as_mut()
+            .and_then(|reloads| std::iter::from_fn(|| reloads.try_recv().ok()).last());
+        if let Some(reload) = reload {
+            self.apply_reload(reload);
+        }
+
         // Refresh process cache once per poll cycle (much faster than per-pane)
         refresh_process_cache();
 
-        let panes = self.tmux_client.list_panes()?;
+        let mut trees: Vec<(TmuxClient, TmuxTree)> = Vec::new();
+        let mut last_error = None;
+        for server in &self.servers {
+            let client = self.tmux_client.for_server(server);
+            match client.list_tree() {
+                Ok(tmux_tree) => trees.push((client, tmux_tree)),
+                Err(e) => {
+                    debug!("Failed to list panes of server {}: {}", server.label(), e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        if trees.is_empty() {
+            if let Some(e) = last_error {
+                return Err(e);
+            }
+        }
+
         let mut tree = AgentTree::new();
+        let mut changes = Vec::new();
+        let mut transitions = Vec::new();
+        let now = Instant::now();
+        // Panes the schedule knows about and the agents captured this time
+        let mut seen = std::collections::HashSet::new();
+        let mut captured = std::collections::HashSet::new();
+        // Labels set on the panes with the @tmuxcc_label option
+        let mut pane_labels = std::collections::HashMap::new();
 
-        for pane in panes {
-            // Try to find a matching parser for the pane (checks command, title, cmdline)
-            if let Some(parser) = self.parser_registry.find_parser_for_pane(&pane) {
+        for (client, tmux_tree) in &trees {
+            let panes: Vec<_> = if client.server().is_default() {
+                tmux_tree.attached_panes().collect()
+            } else {
+                tmux_tree.panes().collect()
+            };
+            for tmux_pane in panes {
+                let pane = &tmux_pane.info;
+                // A dead pane only shows what its process left behind
+                if tmux_pane.ignored || tmux_pane.dead {
+                    continue;
+                }
                 let target = pane.target();
-
-                // Capture pane content
-                let content = match self.tmux_client.capture_pane(&target) {
-                    Ok(c) => c,
-                    Err(e) => {
-                        error!("Failed to capture pane {}: {}", target, e);
+                let key = tmux_pane.server.qualify(&target);
+                seen.insert(key.clone());
+                if let Some(label) = &tmux_pane.label {
+                    pane_labels.insert(key.clone(), label.clone());
+                }
+                match self.schedule.turn(&key, now) {
+                    PaneTurn::Poll => {}
+                    PaneTurn::Wait(agent) => {
+                        self.captures.keep(&key);
+                        tree.root_agents.push(*agent);
                         continue;
                     }
-                };
-
-                // Parse status from content
-                let mut status = parser.parse_status(&content);
-
-                // Check pane title for spinner (Claude Code specific)
-                // Spinners like ⠐⠇⠋⠙⠸ in title indicate processing
-                let title_has_spinner = pane.title.chars().any(|c| {
-                    matches!(
-                        c,
-                        '⠿' | '⠇'
-                            | '⠋'
-                            | '⠙'
-                            | '⠸'
-                            | '⠴'
-                            | '⠦'
-                            | '⠧'
-                            | '⠖'
-                            | '⠏'
-                            | '⠹'
-                            | '⠼'
-                            | '⠷'
-                            | '⠾'
-                            | '⠽'
-                            | '⠻'
-                            | '⠐'
-                            | '⠑'
-                            | '⠒'
-                            | '⠓'
-                    )
-                });
-
-                // If title has spinner, override to Processing
-                if title_has_spinner && matches!(status, AgentStatus::Idle | AgentStatus::Unknown) {
-                    status = AgentStatus::Processing {
-                        activity: "Working...".to_string(),
-                    };
+                    PaneTurn::Ignore => continue,
                 }
 
-                // Apply hysteresis: if status is now Idle but was recently active, keep as Processing
-                let now = Instant::now();
-                let is_active = matches!(
-                    status,
-                    AgentStatus::Processing { .. } | AgentStatus::AwaitingApproval { .. }
-                );
-
-                if is_active {
-                    // Update last active time
-                    self.last_active.insert(target.clone(), now);
-                } else if matches!(status, AgentStatus::Idle) {
-                    // Check if we were recently active
-                    if let Some(last) = self.last_active.get(&target) {
-                        if now.duration_since(*last) < Duration::from_millis(STATUS_HYSTERESIS_MS) {
-                            // Keep as Processing to avoid flicker
-                            status = AgentStatus::Processing {
-                                activity: "Working...".to_string(),
-                            };
+                // Try to find a matching parser for the pane (checks command, title, cmdline)
+                if let Some(parser) = self.parser_registry.find_parser_for_pane(pane) {
+                    // Capture pane content; parsers work on the plain text
+                    let styled = match client.capture_pane_styled(&target) {
+                        Ok(c) => c,
+                        Err(e) => {
+                            error!("Failed to capture pane {}: {}", key, e);
+                            continue;
                         }
+                    };
+                    let content = strip_ansi(&styled);
+
+                    // Only re-run the parsers when the capture changed
+                    let change = self.captures.update(&key, &content);
+                    let reuse = change.is_none() && self.parsed.contains_key(&key);
+                    if !reuse {
+                        self.parsed.insert(
+                            key.clone(),
+                            ParsedPane {
+                                status: parser.parse_status(&content),
+                                subagents: parser.parse_subagents(&content),
+                                context_remaining: parser.parse_context_remaining(&content),
+                                usage: parser.parse_usage(&content),
+                            },
+                        );
                     }
-                }
+                    let new_lines = change.as_ref().map_or(0, |c| c.new_lines.len());
+                    changes.extend(change);
+                    let parsed = &self.parsed[&key];
+
+                    let mut status = parsed.status.clone();
+                    let mut usage = parsed.usage;
+
+                    // Processes of remote panes aren't on this machine
+                    let local = tmux_pane.server.host().is_none();
+                    let env = if local {
+                        pane.process_env(&[GitEnv::VARS, &[STREAM_VAR]].concat())
+                    } else {
+                        Vec::new()
+                    };
+                    // Headless agents report exact turns and usage in their event stream
+                    let stream = env
+                        .iter()
+                        .find(|(name, _)| name == STREAM_VAR)
+                        .and_then(|(_, path)| read_stream(std::path::Path::new(path)));
+                    if let Some(stream) = &stream {
+                        if stream.status != AgentStatus::Unknown {
+                            status = stream.status.clone();
+                        }
+                        usage = stream.usage.or(usage);
+                    }
+
+                    // Check pane title for spinner (Claude Code specific)
+                    // Spinners like ⠐⠇⠋⠙⠸ in title indicate processing
+                    let title_has_spinner = pane.title.chars().any(|c| {
+                        matches!(
+                            c,
+                            '⠿' | '⠇'
+                                | '⠋'
+                                | '⠙'
+                                | '⠸'
+                                | '⠴'
+                                | '⠦'
+                                | '⠧'
+                                | '⠖'
+                                | '⠏'
+                                | '⠹'
+                                | '⠼'
+                                | '⠷'
+                                | '⠾'
+                                | '⠽'
+                                | '⠻'
+                                | '⠐'
+                                | '⠑'
+                                | '⠒'
+                                | '⠓'
+                        )
+                    });
+
+                    // If title has spinner, override to Processing
+                    if title_has_spinner
+                        && matches!(status, AgentStatus::Idle | AgentStatus::Unknown)
+                    {
+                        status = AgentStatus::Processing {
+                            activity: "Working...".to_string(),
+                        };
+                    }
+
+                    let subagents = parsed.subagents.clone();
+                    let context_remaining = parsed.context_remaining;
+
+                    // Create monitored agent
+                    let mut agent = MonitoredAgent::new(
+                        format!("{}-{}", key, pane.pid),
+                        target,
+                        pane.session.clone(),
+                        pane.window,
+                        pane.window_name.clone(),
+                        pane.pane,
+                        pane.path.clone(),
+                        parser.agent_type(),
+                        pane.pid,
+                    );
+                    agent.pane_id = tmux_pane.id.clone();
+                    agent.status = status;
+                    agent.subagents = subagents;
+                    agent.last_content = content;
+                    agent.last_content_styled = styled;
+                    agent.context_remaining = context_remaining;
+                    agent.reported_usage = usage;
+                    agent.headless = stream.is_some();
+                    agent.launch = pane.launch_command();
+                    agent.activity = self.activity.record(&key, new_lines, Instant::now());
+                    agent.version = is_version_like(&pane.command).then(|| pane.command.clone());
+                    agent.server = tmux_pane.server.clone();
+                    agent.zoomed = tmux_pane.zoomed;
+                    // Repositories of remote panes aren't on this machine
+                    if local {
+                        let git_env = GitEnv::from_vars(env);
+                        agent.git = self.git_cache.get_with_env(&pane.path, &git_env);
+                        if let Some(git) = agent.git.as_mut() {
+                            if let (Some(slug), false) = (&git.repo_slug, git.detached) {
+                                git.pull_request = self.pr_cache.get(slug, &git.branch, &pane.path);
+                            }
+                        }
+                        let root = agent.git.as_ref().map(|g| g.root.as_path());
+                        let root = root.filter(|root| !root.as_os_str().is_empty());
+                        agent.project = self.projects.get(&pane.path, root);
+                    }
+                    agent.touch(); // Update last_updated
+                    transitions.extend(self.states.apply(&mut agent, Instant::now()));
+                    captured.insert(key);
 
-                // Parse subagents
-                let subagents = parser.parse_subagents(&content);
-
-                // Parse context remaining
-                let context_remaining = parser.parse_context_remaining(&content);
-
-                // Create monitored agent
-                let mut agent = MonitoredAgent::new(
-                    format!("{}-{}", target, pane.pid),
-                    target,
-                    pane.session.clone(),
-                    pane.window,
-                    pane.window_name.clone(),
-                    pane.pane,
-                    pane.path.clone(),
-                    parser.agent_type(),
-                    pane.pid,
-                );
-                agent.status = status;
-                agent.subagents = subagents;
-                agent.last_content = content;
-                agent.context_remaining = context_remaining;
-                agent.touch(); // Update last_updated
-
-                tree.root_agents.push(agent);
+                    tree.root_agents.push(agent);
+                }
             }
         }
 
-        // Sort agents by target for consistent ordering
-        tree.root_agents.sort_by(|a, b| a.target.cmp(&b.target));
+        // Forget repositories and panes no longer open in any agent pane
+        self.git_cache.prune_unused();
+        self.captures.prune_unused();
+        let live: std::collections::HashSet<String> =
+            tree.root_agents.iter().map(|a| a.key()).collect();
+        self.parsed.retain(|key, _| live.contains(key));
+        self.states.retain(&live);
+        self.activity.retain(&live);
+
+        // Sort agents by server and target for consistent ordering
+        tree.root_agents
+            .sort_by(|a, b| (&a.server, &a.target).cmp(&(&b.server, &b.target)));
+
+        let (approvals, responses) = {
+            let switches = self.switches.as_ref().map(|s| s.lock());
+            let allowed = |agent: &MonitoredAgent| {
+                switches
+                    .as_ref()
+                    .is_none_or(|switches| switches.allows(&agent.key()))
+            };
+            self.rate_limits
+                .apply(&self.tmux_client, &mut tree.root_agents, allowed);
+            let agents = tree.root_agents.iter().filter(|a| allowed(a));
+            let approvals = match self.auto_approver.as_mut() {
+                Some(approver) => approver.apply(&self.tmux_client, agents.clone()),
+                None => Vec::new(),
+            };
+            // A permission prompt, approved, denied or left to the user, is
+            // never answered by a reply rule
+            let approver = self.auto_approver.as_ref();
+            let unanswered =
+                agents.filter(|a| approver.is_none_or(|approver| !approver.handles(&a.key())));
+            let responses = match self.auto_responder.as_mut() {
+                Some(responder) => responder.apply(&self.tmux_client, unanswered),
+                None => Vec::new(),
+            };
+            (approvals, responses)
+        };
+        let context_warnings = match self.context_watch.as_mut() {
+            Some(watch) => watch.check(&tree.root_agents),
+            None => Vec::new(),
+        };
+        if let Some(summarizer) = self.summarizer.as_mut() {
+            summarizer.apply(&transitions, &mut tree.root_agents);
+        }
+        let stalls = match self.watchdog.as_mut() {
+            Some(watchdog) => watchdog.check(&mut tree.root_agents, Instant::now()),
+            None => Vec::new(),
+        };
+        if let Some(registry) = self.registry.as_ref() {
+            registry.lock().update(&mut tree.root_agents);
+        }
+        for agent in &mut tree.root_agents {
+            if agent.label.is_none() {
+                agent.label = pane_labels.remove(&agent.key());
+            }
+            if let (None, Some(project)) = (&agent.label, &agent.project) {
+                agent.label.clone_from(&project.label);
+            }
+        }
+        for agent in tree
+            .root_agents
+            .iter()
+            .filter(|a| captured.contains(&a.key()))
+        {
+            self.schedule.polled(agent, now);
+        }
+        self.schedule.retain(&seen);
+        if let Some(titles) = self.titles.as_mut() {
+            titles.apply(&tree.root_agents);
+        }
+        if let Some(webhooks) = self.webhooks.as_mut() {
+            webhooks.send(&transitions, &stalls, &tree.root_agents);
+        }
+        if let Some(hooks) = self.event_hooks.as_mut() {
+            hooks.apply(&transitions, &stalls, &tree.root_agents);
+        }
+        if let Some(cache) = self.summary_cache.as_mut() {
+            if let Err(e) = cache.store(StatusSummary::from_agents(&tree.root_agents)) {
+                debug!("Failed to write status summary: {}", e);
+            }
+        }
 
-        Ok(tree)
+        Ok(Poll {
+            tree,
+            changes,
+            transitions,
+            approvals,
+            context_warnings,
+            responses,
+            stalls,
+        })
     }
 }
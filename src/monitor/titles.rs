@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::agents::{AgentStatus, MonitoredAgent};
+use crate::app::StatusTitles;
+use crate::tmux::{TmuxClient, TmuxServer};
+
+/// Returns the icon, state word and urgency (higher is more urgent) of a status
+fn status_parts(status: &AgentStatus) -> (&'static str, &'static str, u8) {
+    match status {
+        AgentStatus::AwaitingApproval { .. } => ("🟠", "waiting", 4),
+        AgentStatus::Error { .. } => ("🔴", "error", 3),
+        AgentStatus::Processing { .. } => ("🔵", "working", 2),
+        AgentStatus::Idle => ("🟢", "idle", 1),
+        AgentStatus::Unknown => ("⚪", "unknown", 0),
+    }
+}
+
+/// Formats the tmux title for an agent (e.g., "🟢 claude: idle")
+pub fn status_title(agent: &MonitoredAgent) -> String {
+    let (icon, state, _) = status_parts(&agent.status);
+    format!(
+        "{} {}: {}",
+        icon,
+        agent.agent_type.short_name().to_lowercase(),
+        state
+    )
+}
+
+/// Keeps tmux window names and pane titles in sync with agent state
+///
+/// Only names that changed since the last update are sent to tmux. Windows
+/// with several agents show the most urgent one. Renamed windows get
+/// automatic renaming back when the updater is dropped.
+pub struct TitleUpdater {
+    tmux_client: Arc<TmuxClient>,
+    mode: StatusTitles,
+    /// Last title set per window ("session:window") of each server
+    windows: HashMap<(TmuxServer, String), String>,
+    /// Last title set per server-qualified pane target
+    panes: HashMap<String, String>,
+}
+
+impl TitleUpdater {
+    pub fn new(tmux_client: Arc<TmuxClient>, mode: StatusTitles) -> Self {
+        Self {
+            tmux_client,
+            mode,
+            windows: HashMap::new(),
+            panes: HashMap::new(),
+        }
+    }
+
+    /// Applies the titles for `agents`
+    pub fn apply(&mut self, agents: &[MonitoredAgent]) {
+        // Forget panes that are gone so a reused target gets its title again
+        self.panes
+            .retain(|key, _| agents.iter().any(|a| a.key() == *key));
+
+        if self.mode.panes() {
+            for agent in agents {
+                let title = status_title(agent);
+                let key = agent.key();
+                if self.panes.get(&key) != Some(&title)
+                    && self
+                        .tmux_client
+                        .for_server(&agent.server)
+                        .set_pane_title(&agent.target, &title)
+                        .is_ok()
+                {
+                    self.panes.insert(key, title);
+                }
+            }
+        }
+
+        if self.mode.windows() {
+            let mut most_urgent: HashMap<(TmuxServer, String), &MonitoredAgent> = HashMap::new();
+            for agent in agents {
+                let window = format!("{}:{}", agent.session, agent.window);
+                let urgency = status_parts(&agent.status).2;
+                let entry = most_urgent
+                    .entry((agent.server.clone(), window))
+                    .or_insert(agent);
+                if urgency > status_parts(&entry.status).2 {
+                    *entry = agent;
+                }
+            }
+            for (key, agent) in most_urgent {
+                let title = status_title(agent);
+                if self.windows.get(&key) != Some(&title)
+                    && self
+                        .tmux_client
+                        .for_server(&key.0)
+                        .rename_window(&key.1, &title)
+                        .is_ok()
+                {
+                    self.windows.insert(key, title);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for TitleUpdater {
+    fn drop(&mut self) {
+        for (server, window) in self.windows.keys() {
+            let _ = self
+                .tmux_client
+                .for_server(server)
+                .set_automatic_rename(window, true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::AgentType;
+
+    #[test]
+    fn test_status_title() {
+        let mut agent = MonitoredAgent::new(
+            "main:0.0-1".to_string(),
+            "main:0.0".to_string(),
+            "main".to_string(),
+            0,
+            "code".to_string(),
+            0,
+            "/home/user/app".to_string(),
+            AgentType::ClaudeCode,
+            1,
+        );
+        agent.status = AgentStatus::Idle;
+        assert_eq!(status_title(&agent), "🟢 claude: idle");
+        agent.status = AgentStatus::AwaitingApproval {
+            approval_type: crate::agents::ApprovalType::ShellCommand,
+            details: String::new(),
+        };
+        assert_eq!(status_title(&agent), "🟠 claude: waiting");
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use tracing::{info, warn};
+
+use crate::agents::{AgentState, MonitoredAgent};
+use serde::{Deserialize, Serialize};
+
+/// A working agent stopped producing output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stall {
+    /// Server-qualified pane target (see [`MonitoredAgent::key`])
+    pub key: String,
+    /// How long the pane has been quiet
+    pub quiet: Duration,
+}
+
+impl Stall {
+    /// Returns the message shown to the user
+    pub fn message(&self) -> String {
+        format!(
+            "{}: working but no new output for {}m, it may be stuck",
+            self.key,
+            self.quiet.as_secs() / 60
+        )
+    }
+}
+
+/// Last output seen in a pane
+struct Output {
+    fingerprint: u64,
+    changed: Instant,
+    reported: bool,
+}
+
+/// Hashes the letters of `content`
+///
+/// Spinners, elapsed-time counters and token counts keep changing while an
+/// agent hangs on a request, so only letters count as new output.
+fn fingerprint(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for c in content.chars().filter(|c| c.is_alphabetic()) {
+        c.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Flags agents that report Working without new output for a while
+///
+/// Each stall is reported once and runs the hook command, if any, with
+/// `TMUXCC_PANE`, `TMUXCC_PATH` and `TMUXCC_QUIET_SECS` set. A pane counts
+/// again once it produced output or left the Working state.
+pub struct Watchdog {
+    after: Duration,
+    hook: String,
+    outputs: HashMap<String, Output>,
+}
+
+impl Watchdog {
+    /// Flags agents quiet for `after`; `hook` is run with `sh -c` unless empty
+    pub fn new(after: Duration, hook: String) -> Self {
+        Self {
+            after,
+            hook,
+            outputs: HashMap::new(),
+        }
+    }
+
+    /// Sets `stalled_since` of stalled agents and returns the new stalls
+    pub fn check(&mut self, agents: &mut [MonitoredAgent], now: Instant) -> Vec<Stall> {
+        let mut stalls = Vec::new();
+        let mut outputs = HashMap::new();
+        for agent in agents.iter_mut() {
+            let key = agent.key();
+            let fingerprint = fingerprint(&agent.last_content);
+            let mut output = match self.outputs.remove(&key) {
+                Some(output)
+                    if output.fingerprint == fingerprint
+                        && agent.state == Some(AgentState::Working) =>
+                {
+                    output
+                }
+                _ => Output {
+                    fingerprint,
+                    changed: now,
+                    reported: false,
+                },
+            };
+
+            let quiet = now.duration_since(output.changed);
+            if quiet >= self.after {
+                agent.stalled_since = Some(output.changed);
+                if !output.reported {
+                    output.reported = true;
+                    let stall = Stall {
+                        key: key.clone(),
+                        quiet,
+                    };
+                    info!("{}", stall.message());
+                    self.run_hook(agent, &stall);
+                    stalls.push(stall);
+                }
+            }
+            outputs.insert(key, output);
+        }
+        self.outputs = outputs;
+        stalls
+    }
+
+    fn run_hook(&self, agent: &MonitoredAgent, stall: &Stall) {
+        if self.hook.is_empty() {
+            return;
+        }
+        let spawned = Command::new("sh")
+            .arg("-c")
+            .arg(&self.hook)
+            .env("TMUXCC_PANE", &stall.key)
+            .env("TMUXCC_PATH", &agent.path)
+            .env("TMUXCC_QUIET_SECS", stall.quiet.as_secs().to_string())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        match spawned {
+            // Reap the hook without holding up the poll
+            Ok(mut child) => {
+                std::thread::spawn(move || child.wait());
+            }
+            Err(e) => warn!("Failed to run stall hook: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::AgentType;
+
+    #[test]
+    fn test_reports_stall_once() {
+        let mut agents = vec![MonitoredAgent::new(
+            "%1-100".to_string(),
+            "main:0.0".to_string(),
+            "main".to_string(),
+            0,
+            "code".to_string(),
+            0,
+            "/repo".to_string(),
+            AgentType::ClaudeCode,
+            100,
+        )];
+        agents[0].state = Some(AgentState::Working);
+        agents[0].last_content = "✻ Thinking… (12s · esc to interrupt)".to_string();
+        let mut watchdog = Watchdog::new(Duration::from_secs(60), String::new());
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        assert!(watchdog.check(&mut agents, start).is_empty());
+        // Only the counter moved
+        agents[0].last_content = "✶ Thinking… (71s · esc to interrupt)".to_string();
+        let stalls = watchdog.check(&mut agents, at(61));
+        assert_eq!(stalls.len(), 1);
+        assert_eq!(stalls[0].quiet, Duration::from_secs(61));
+        assert_eq!(agents[0].stalled_since, Some(start));
+        assert!(watchdog.check(&mut agents, at(90)).is_empty());
+
+        // New output re-arms the watchdog
+        agents[0].stalled_since = None;
+        agents[0].last_content = "⏺ Read(src/main.rs)".to_string();
+        assert!(watchdog.check(&mut agents, at(100)).is_empty());
+        assert_eq!(agents[0].stalled_since, None);
+        assert_eq!(watchdog.check(&mut agents, at(160)).len(), 1);
+
+        // Idle agents are never stalled
+        agents[0].stalled_since = None;
+        agents[0].state = Some(AgentState::Idle);
+        assert!(watchdog.check(&mut agents, at(300)).is_empty());
+        assert_eq!(agents[0].stalled_since, None);
+    }
+}
@@ -0,0 +1,572 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use tracing::{debug, warn};
+
+use super::notify_rules::{NotifyRouter, Route};
+use super::state_machine::StateTransition;
+use super::watchdog::Stall;
+use crate::agents::{AgentState, MonitoredAgent};
+use crate::app::{Config, DiscordConfig, Notifier, NotifyOn, SlackConfig, WebhookConfig};
+use crate::parsers::json_escape;
+
+/// Seconds curl may take for one attempt
+const TIMEOUT_SECS: &str = "10";
+
+/// Requests waiting for their turn per webhook before new ones are dropped
+const QUEUE_LEN: usize = 20;
+
+/// Slack accepts about one message per second per webhook or channel
+const SLACK_INTERVAL_MS: u64 = 1000;
+
+/// Discord allows 30 requests per minute per webhook
+const DISCORD_INTERVAL_MS: u64 = 2000;
+
+/// Slack and Discord message: emoji, what happened in bold, the snippet quoted
+const SLACK_TEXT: &str = "{emoji} *{message}*\\n>{snippet}";
+const DISCORD_TEXT: &str = "{emoji} **{message}**\\n> {snippet}";
+
+/// A webhook and the notifier it belongs to
+#[derive(Debug, Clone)]
+pub struct WebhookTarget {
+    pub notifier: Notifier,
+    pub hook: WebhookConfig,
+    /// Channel put in for `{channel}` unless a rule names another one
+    pub channel: Option<String>,
+}
+
+impl WebhookTarget {
+    /// Returns the request body about `event` sent the way `route` says
+    fn body(&self, event: &Event, route: &Route) -> String {
+        match &self.channel {
+            Some(channel) => {
+                let channel = route.channel().unwrap_or(channel);
+                event.render(&self.hook.body, &[("channel", channel.to_string())])
+            }
+            None => event.body(&self.hook.body),
+        }
+    }
+}
+
+/// Returns the configured webhooks followed by the Slack and Discord ones
+pub fn webhook_targets(config: &Config) -> Vec<WebhookTarget> {
+    let mut targets: Vec<WebhookTarget> = config
+        .webhooks
+        .iter()
+        .map(|hook| WebhookTarget {
+            notifier: Notifier::Webhook,
+            hook: hook.clone(),
+            channel: None,
+        })
+        .collect();
+    targets.extend(slack_webhook(&config.slack).map(|hook| WebhookTarget {
+        notifier: Notifier::Slack,
+        hook,
+        // Incoming webhooks post to the channel they were made for
+        channel: (config.slack.webhook_url.is_empty()).then(|| config.slack.channel.clone()),
+    }));
+    targets.extend(discord_webhook(&config.discord).map(|hook| WebhookTarget {
+        notifier: Notifier::Discord,
+        hook,
+        channel: None,
+    }));
+    targets
+}
+
+/// Returns the webhook posting Slack messages, `None` if Slack isn't set up
+fn slack_webhook(slack: &SlackConfig) -> Option<WebhookConfig> {
+    let (url, headers, body) = if !slack.webhook_url.is_empty() {
+        let body = format!("{{\"text\":\"{}\"}}", SLACK_TEXT);
+        (slack.webhook_url.clone(), Vec::new(), body)
+    } else if !slack.token.is_empty() && !slack.channel.is_empty() {
+        let body = format!(
+            "{{\"channel\":\"{{channel}}\",\"text\":\"{}\"}}",
+            SLACK_TEXT
+        );
+        let headers = vec![format!("Authorization: Bearer {}", slack.token)];
+        let url = "https://slack.com/api/chat.postMessage".to_string();
+        (url, headers, body)
+    } else {
+        return None;
+    };
+    Some(WebhookConfig {
+        url,
+        on: slack.on.clone(),
+        body,
+        headers,
+        retries: 3,
+        min_interval_ms: SLACK_INTERVAL_MS,
+    })
+}
+
+/// Returns the webhook posting Discord messages, `None` without a URL
+fn discord_webhook(discord: &DiscordConfig) -> Option<WebhookConfig> {
+    if discord.webhook_url.is_empty() {
+        return None;
+    }
+    Some(WebhookConfig {
+        url: discord.webhook_url.clone(),
+        on: discord.on.clone(),
+        body: format!(
+            "{{\"username\":\"tmuxcc\",\"content\":\"{}\"}}",
+            DISCORD_TEXT
+        ),
+        headers: Vec::new(),
+        retries: 3,
+        min_interval_ms: DISCORD_INTERVAL_MS,
+    })
+}
+
+/// Returns the emoji starting chat messages about `event`
+fn emoji(event: NotifyOn) -> &'static str {
+    match event {
+        NotifyOn::Permission => "⚠️",
+        NotifyOn::Input => "❓",
+        NotifyOn::Error => "❌",
+        NotifyOn::Finished => "✅",
+        NotifyOn::Stalled => "⏱️",
+    }
+}
+
+/// One state change as sent to webhooks
+struct Event<'a> {
+    agent: &'a MonitoredAgent,
+    event: NotifyOn,
+    from: Option<AgentState>,
+    to: Option<AgentState>,
+    at: DateTime<Local>,
+}
+
+impl Event<'_> {
+    /// Returns the placeholders of body templates with their values
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let git = self.agent.git.as_ref();
+        let repo = self.agent.repo_root();
+        let repo_name = repo.rsplit('/').find(|s| !s.is_empty()).unwrap_or("/");
+        let name = self.agent.label.clone().unwrap_or_else(|| self.agent.key());
+        let message = match git {
+            Some(git) => format!(
+                "{} {} on {} ({})",
+                name,
+                self.event.text(),
+                repo_name,
+                git.branch
+            ),
+            None => format!("{} {} in {}", name, self.event.text(), repo_name),
+        };
+        vec![
+            ("pane", self.agent.key()),
+            ("pane_id", self.agent.pane_id.clone()),
+            ("label", self.agent.label.clone().unwrap_or_default()),
+            ("path", self.agent.path.clone()),
+            ("repo", self.agent.repo_root()),
+            ("branch", git.map(|g| g.branch.clone()).unwrap_or_default()),
+            ("event", format!("{:?}", self.event).to_lowercase()),
+            ("from", self.from.map_or("", AgentState::label).to_string()),
+            ("to", self.to.map_or("", AgentState::label).to_string()),
+            ("snippet", self.agent.snippet()),
+            ("at", self.at.to_rfc3339()),
+            ("message", message),
+            ("emoji", emoji(self.event).to_string()),
+        ]
+    }
+
+    /// Returns the body for `template`, every field as an object if empty
+    fn body(&self, template: &str) -> String {
+        self.render(template, &[])
+    }
+
+    /// Returns the body for `template` with the placeholders of `extra` also
+    /// filled in
+    ///
+    /// The template is read once, so a value containing a placeholder (a
+    /// snippet quoting `{state}`) is left as it is. Unknown placeholders
+    /// stay in the body.
+    fn render(&self, template: &str, extra: &[(&'static str, String)]) -> String {
+        let fields = self.fields();
+        if template.is_empty() {
+            let members: Vec<String> = fields
+                .iter()
+                .map(|(name, value)| format!("\"{}\":\"{}\"", name, json_escape(value)))
+                .collect();
+            return format!("{{{}}}", members.join(","));
+        }
+        let mut body = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            body.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let value = rest.find('}').and_then(|end| {
+                let name = &rest[1..end];
+                let (_, value) = fields.iter().chain(extra).find(|(n, _)| *n == name)?;
+                Some((end, value))
+            });
+            match value {
+                Some((end, value)) => {
+                    body.push_str(&json_escape(value));
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    body.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        body.push_str(rest);
+        body
+    }
+}
+
+/// A webhook and the queue of its sender thread
+struct Target {
+    target: WebhookTarget,
+    queue: SyncSender<String>,
+}
+
+/// POSTs state changes to the configured webhooks
+///
+/// Each webhook has a thread sending its requests in order with curl, so a
+/// slow or unreachable endpoint doesn't hold up polling. Failed requests are
+/// retried with doubling pauses and requests are spaced `min_interval_ms`
+/// apart; ones that don't fit in the queue meanwhile are dropped.
+pub struct Webhooks {
+    targets: Vec<Target>,
+    router: NotifyRouter,
+}
+
+impl Webhooks {
+    pub fn new(targets: Vec<WebhookTarget>, router: NotifyRouter) -> Self {
+        let targets = targets
+            .into_iter()
+            .map(|target| {
+                let (queue, requests) = mpsc::sync_channel::<String>(QUEUE_LEN);
+                let sender = target.hook.clone();
+                std::thread::spawn(move || {
+                    let interval = Duration::from_millis(sender.min_interval_ms);
+                    // Ends when the monitor drops the queue
+                    while let Ok(body) = requests.recv() {
+                        post(&sender, &body);
+                        std::thread::sleep(interval);
+                    }
+                });
+                Target { target, queue }
+            })
+            .collect();
+        Self { targets, router }
+    }
+
+    /// Sends the transitions and stalls the webhooks or the notify rules
+    /// ask for
+    pub fn send(
+        &mut self,
+        transitions: &[StateTransition],
+        stalls: &[Stall],
+        agents: &[MonitoredAgent],
+    ) {
+        let find = |key: &str| agents.iter().find(|a| a.key() == key);
+        let mut events = Vec::new();
+        for transition in transitions {
+            let event = NotifyOn::from_transition(transition.from, transition.to);
+            if let (Some(event), Some(agent)) = (event, find(&transition.key)) {
+                events.push(Event {
+                    agent,
+                    event,
+                    from: transition.from,
+                    to: Some(transition.to),
+                    at: transition.at,
+                });
+            }
+        }
+        for stall in stalls {
+            if let Some(agent) = find(&stall.key) {
+                events.push(Event {
+                    agent,
+                    event: NotifyOn::Stalled,
+                    from: agent.state,
+                    to: agent.state,
+                    at: Local::now(),
+                });
+            }
+        }
+
+        for event in &events {
+            let route = self.router.route(event.agent, event.event, Instant::now());
+            for Target { target, queue } in &self.targets {
+                if !route.sends_to(target.notifier, true, target.hook.wants(event.event)) {
+                    continue;
+                }
+                match queue.try_send(target.body(event, &route)) {
+                    Ok(()) => {}
+                    Err(TrySendError::Full(_)) => {
+                        warn!("Dropped webhook to {}: too many queued", target.hook.url)
+                    }
+                    Err(TrySendError::Disconnected(_)) => {}
+                }
+            }
+        }
+    }
+}
+
+/// Returns the curl command POSTing the request its stdin describes
+///
+/// The URL and headers go through stdin with the body, so tokens never show
+/// up on the command line for other users to read.
+fn curl() -> Command {
+    let mut command = Command::new("curl");
+    command.args([
+        "--silent",
+        "--show-error",
+        "--fail",
+        "--max-time",
+        TIMEOUT_SECS,
+        "--config",
+        "-",
+    ]);
+    command
+}
+
+/// Returns the curl config POSTing `body` to the webhook
+fn curl_config(hook: &WebhookConfig, body: &str) -> String {
+    let mut config = String::new();
+    let mut option = |name: &str, value: &str| {
+        let value = value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r");
+        config.push_str(&format!("{} = \"{}\"\n", name, value));
+    };
+    option("header", "Content-Type: application/json");
+    for header in &hook.headers {
+        option("header", header);
+    }
+    option("data-raw", body);
+    option("url", &hook.url);
+    config
+}
+
+/// Returns the error the Slack API answered with
+///
+/// `chat.postMessage` reports failures as `"ok":false` with status 200, which
+/// `--fail` doesn't catch.
+fn slack_error(hook: &WebhookConfig, response: &str) -> Option<String> {
+    if !hook.url.starts_with("https://slack.com/api/") {
+        return None;
+    }
+    let response: String = response.chars().filter(|c| !c.is_whitespace()).collect();
+    if !response.contains("\"ok\":false") {
+        return None;
+    }
+    let error = response
+        .split_once("\"error\":\"")
+        .and_then(|(_, rest)| rest.split('"').next())
+        .unwrap_or("unknown error");
+    Some(error.to_string())
+}
+
+/// Sends `body` to the webhook, retrying failures
+fn post(hook: &WebhookConfig, body: &str) {
+    let config = curl_config(hook, body);
+    let mut pause = Duration::from_secs(1);
+    for attempt in 0..=hook.retries {
+        if attempt > 0 {
+            std::thread::sleep(pause);
+            pause *= 2;
+        }
+        let result = curl()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                if let Some(mut stdin) = child.stdin.take() {
+                    stdin.write_all(config.as_bytes())?;
+                }
+                child.wait_with_output()
+            });
+        match result {
+            Ok(output) if output.status.success() => {
+                // An API error won't go away on retry
+                match slack_error(hook, &String::from_utf8_lossy(&output.stdout)) {
+                    Some(error) => warn!("Slack rejected the message to {}: {}", hook.url, error),
+                    None => debug!("Sent webhook to {}", hook.url),
+                }
+                return;
+            }
+            Ok(output) => warn!(
+                "Webhook {} failed (attempt {}): {}",
+                hook.url,
+                attempt + 1,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(e) => warn!("Failed to run curl for webhook {}: {}", hook.url, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::{AgentStatus, AgentType};
+    use crate::app::NotifyRule;
+
+    #[test]
+    fn test_webhook_body() {
+        assert_eq!(
+            json_escape("a \"b\"\n\\\u{1b}"),
+            "a \\\"b\\\"\\n\\\\\\u001b"
+        );
+
+        let mut agent = MonitoredAgent::new(
+            "main:0.1-42".to_string(),
+            "main:0.1".to_string(),
+            "main".to_string(),
+            0,
+            "editor".to_string(),
+            1,
+            "/code/app".to_string(),
+            AgentType::ClaudeCode,
+            42,
+        );
+        agent.pane_id = "%3".to_string();
+        agent.status = AgentStatus::Error {
+            message: "API \"overloaded\"".to_string(),
+        };
+        let at = Local::now();
+        let event = Event {
+            agent: &agent,
+            event: NotifyOn::Error,
+            from: Some(AgentState::Working),
+            to: Some(AgentState::Error),
+            at,
+        };
+
+        let body = event.body("");
+        assert!(body.starts_with("{\"pane\":\"main:0.1\",\"pane_id\":\"%3\","));
+        assert!(body.contains("\"repo\":\"/code/app\",\"branch\":\"\",\"event\":\"error\""));
+        assert!(body.contains("\"from\":\"WORKING\",\"to\":\"ERROR\""));
+        assert!(body.contains("\"snippet\":\"API \\\"overloaded\\\"\""));
+
+        assert_eq!(
+            event.body(r#"{"text": "{pane} {event}: {snippet}", "x": "{unknown}"}"#),
+            r#"{"text": "main:0.1 error: API \"overloaded\"", "x": "{unknown}"}"#
+        );
+        // Placeholders in the agent's output aren't filled in
+        agent.status = AgentStatus::Error {
+            message: "no {pane} here".to_string(),
+        };
+        let event = Event {
+            agent: &agent,
+            event: NotifyOn::Error,
+            from: Some(AgentState::Working),
+            to: Some(AgentState::Error),
+            at,
+        };
+        assert_eq!(event.body("{snippet} {to}"), "no {pane} here ERROR");
+    }
+
+    #[test]
+    fn test_curl_command() {
+        let hook = WebhookConfig {
+            url: "https://example.com/hook".to_string(),
+            headers: vec!["Authorization: Bearer t".to_string()],
+            ..WebhookConfig::default()
+        };
+        let args: Vec<_> = curl()
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect();
+        assert!(!args.iter().any(|a| a.contains("Bearer")));
+        assert_eq!(&args[args.len() - 2..], ["--config", "-"]);
+        assert_eq!(
+            curl_config(&hook, "{\"text\":\"a\\nb\"}\n"),
+            concat!(
+                "header = \"Content-Type: application/json\"\n",
+                "header = \"Authorization: Bearer t\"\n",
+                r#"data-raw = "{\"text\":\"a\\nb\"}\n""#,
+                "\n",
+                "url = \"https://example.com/hook\"\n",
+            )
+        );
+
+        let slack = WebhookConfig {
+            url: "https://slack.com/api/chat.postMessage".to_string(),
+            ..WebhookConfig::default()
+        };
+        assert_eq!(
+            slack_error(&slack, "{\"ok\": false, \"error\": \"channel_not_found\"}"),
+            Some("channel_not_found".to_string())
+        );
+        assert_eq!(slack_error(&slack, "{\"ok\":true}"), None);
+        assert_eq!(slack_error(&hook, "{\"ok\":false}"), None);
+        assert!(hook.wants(NotifyOn::Finished));
+    }
+
+    #[test]
+    fn test_chat_webhooks() {
+        assert!(slack_webhook(&SlackConfig::default()).is_none());
+        assert!(discord_webhook(&DiscordConfig::default()).is_none());
+
+        let config = Config {
+            slack: SlackConfig {
+                token: "xoxb-1".to_string(),
+                channel: "#agents".to_string(),
+                ..SlackConfig::default()
+            },
+            ..Config::default()
+        };
+        let targets = webhook_targets(&config);
+        let slack = &targets[0];
+        assert_eq!(slack.notifier, Notifier::Slack);
+        assert_eq!(slack.hook.url, "https://slack.com/api/chat.postMessage");
+        assert_eq!(slack.hook.headers, ["Authorization: Bearer xoxb-1"]);
+        assert!(slack.hook.wants(NotifyOn::Permission));
+        assert!(!slack.hook.wants(NotifyOn::Finished));
+
+        let mut agent = MonitoredAgent::new(
+            "%3".to_string(),
+            "main:0.1".to_string(),
+            "main".to_string(),
+            0,
+            "editor".to_string(),
+            1,
+            "/code/app".to_string(),
+            AgentType::ClaudeCode,
+            42,
+        );
+        agent.label = Some("api".to_string());
+        agent.last_content = "Deploy to production?".to_string();
+        let event = Event {
+            agent: &agent,
+            event: NotifyOn::Input,
+            from: Some(AgentState::Working),
+            to: Some(AgentState::WaitingForInput),
+            at: Local::now(),
+        };
+        assert_eq!(
+            slack.body(&event, &Route::Default),
+            "{\"channel\":\"#agents\",\"text\":\"❓ *api asks a question in app*\\n>Deploy to production?\"}"
+        );
+        let rule = NotifyRule {
+            channel: "#api".to_string(),
+            ..NotifyRule::default()
+        };
+        assert!(slack
+            .body(&event, &Route::Rule(&rule))
+            .starts_with("{\"channel\":\"#api\","));
+
+        let discord = DiscordConfig {
+            webhook_url: "https://discord.com/api/webhooks/1/x".to_string(),
+            ..DiscordConfig::default()
+        };
+        let hook = discord_webhook(&discord).unwrap();
+        assert_eq!(hook.min_interval_ms, 2000);
+        assert_eq!(
+            event.body(&hook.body),
+            "{\"username\":\"tmuxcc\",\"content\":\"❓ **api asks a question in app**\\n> Deploy to production?\"}"
+        );
+    }
+}
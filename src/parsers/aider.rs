@@ -0,0 +1,91 @@
+use regex::Regex;
+
+use crate::agents::{AgentStatus, AgentType, ApprovalType};
+
+use super::{safe_tail, AgentParser};
+
+/// Parser for Aider output
+pub struct AiderParser {
+    approval_pattern: Regex,
+    processing_pattern: Regex,
+    idle_pattern: Regex,
+}
+
+impl AiderParser {
+    pub fn new() -> Self {
+        Self {
+            // e.g. "Add file to the chat? (Y)es/(N)o/(D)on't ask again [Yes]:"
+            approval_pattern: Regex::new(r"\(Y\)es/\(N\)o.*\]:\s*$").unwrap(),
+            processing_pattern: Regex::new(r"(?i)(waiting for|thinking|applying edit)").unwrap(),
+            idle_pattern: Regex::new(r"(?m)^\S*>\s*$").unwrap(),
+        }
+    }
+}
+
+impl Default for AiderParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AgentParser for AiderParser {
+    fn agent_name(&self) -> &str {
+        "Aider"
+    }
+
+    fn agent_type(&self) -> AgentType {
+        AgentType::Aider
+    }
+
+    fn matches(&self, detection_strings: &[&str]) -> bool {
+        detection_strings
+            .iter()
+            .any(|s| s.to_lowercase().contains("aider"))
+    }
+
+    fn parse_status(&self, content: &str) -> AgentStatus {
+        let recent = safe_tail(content.trim_end(), 500);
+
+        if self.approval_pattern.is_match(recent) {
+            let details = recent.lines().last().unwrap_or_default().trim().to_string();
+            return AgentStatus::AwaitingApproval {
+                approval_type: ApprovalType::Other("Confirm".to_string()),
+                details,
+            };
+        }
+
+        if self.idle_pattern.is_match(safe_tail(recent, 80)) {
+            return AgentStatus::Idle;
+        }
+
+        if self.processing_pattern.is_match(recent) {
+            return AgentStatus::Processing {
+                activity: "Working...".to_string(),
+            };
+        }
+
+        AgentStatus::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let parser = AiderParser::new();
+        assert!(parser.matches(&["aider", "", ""]));
+        assert!(!parser.matches(&["claude", "", ""]));
+
+        let content = "Add src/main.rs to the chat? (Y)es/(N)o/(D)on't ask again [Yes]: ";
+        assert!(matches!(
+            parser.parse_status(content),
+            AgentStatus::AwaitingApproval { .. }
+        ));
+        assert_eq!(
+            parser.parse_status("Tokens: 2k sent\n\ndiff> "),
+            AgentStatus::Idle
+        );
+    }
+}
@@ -1,12 +1,14 @@
 use regex::Regex;
 
-use crate::agents::{AgentStatus, AgentType, ApprovalType, Subagent, SubagentStatus, SubagentType};
+use crate::agents::{
+    AgentStatus, AgentType, ApprovalType, Subagent, SubagentStatus, SubagentType, Usage,
+};
 
 use super::{safe_tail, AgentParser};
 
 /// Check if a string looks like a version number (e.g., "2.1.11")
 /// Claude Code's pane_current_command often shows version number
-fn is_version_like(s: &str) -> bool {
+pub(crate) fn is_version_like(s: &str) -> bool {
     if s.is_empty() {
         return false;
     }
@@ -38,6 +40,23 @@ pub struct ClaudeCodeParser {
 
     // Context remaining pattern
     context_pattern: Regex,
+
+    // `/cost` summary patterns
+    cost_pattern: Regex,
+    model_usage_pattern: Regex,
+}
+
+/// Parses a token count like "830", "12.4k" or "1.2m"
+fn parse_count(s: &str) -> Option<u64> {
+    let (number, scale) = match s.chars().last()? {
+        'k' | 'K' => (&s[..s.len() - 1], 1_000.0),
+        'm' | 'M' => (&s[..s.len() - 1], 1_000_000.0),
+        _ => (s, 1.0),
+    };
+    number
+        .parse::<f64>()
+        .ok()
+        .map(|n| (n * scale).round() as u64)
 }
 
 impl ClaudeCodeParser {
@@ -78,9 +97,17 @@ impl ClaudeCodeParser {
                 r"(?m)[✓✔]\s*(\w+).*?(?:completed|finished|done|returned)"
             ).unwrap(),
 
-            // Context remaining pattern (e.g., "Context left until auto-compact: 42%")
+            // Context remaining pattern (e.g., "Context left until auto-compact: 42%",
+            // "Context low (8% remaining)" or "12% context left")
             context_pattern: Regex::new(
-                r"(?i)Context\s+(?:left|remaining).*?(\d+)%"
+                r"(?i)Context\s+(?:left|remaining).*?(\d+)%|Context low\s*\((\d+)%|(\d+)%\s+context\s+(?:left|remaining)"
+            ).unwrap(),
+
+            // `/cost` and exit summary, e.g. "Total cost:  $0.55" followed by
+            // "claude-sonnet-4:  12.4k input, 1.2k output, ..." per model
+            cost_pattern: Regex::new(r"Total cost:\s*\$(\d+(?:\.\d+)?)").unwrap(),
+            model_usage_pattern: Regex::new(
+                r"(?m)^\s*[\w.-]+:\s+([\d.]+[kKmM]?) input, ([\d.]+[kKmM]?) output"
             ).unwrap(),
         }
     }
@@ -460,12 +487,29 @@ impl AgentParser for ClaudeCodeParser {
     fn parse_context_remaining(&self, content: &str) -> Option<u8> {
         // Look for context percentage in the last portion of content
         let recent = safe_tail(content, 1000);
+        // The latest message is the current one
         self.context_pattern
-            .captures(recent)
-            .and_then(|cap| cap.get(1))
+            .captures_iter(recent)
+            .last()
+            .and_then(|cap| cap.iter().skip(1).flatten().next())
             .and_then(|m| m.as_str().parse::<u8>().ok())
     }
 
+    fn parse_usage(&self, content: &str) -> Option<Usage> {
+        // Only the latest summary is current
+        let cost = self.cost_pattern.captures_iter(content).last()?;
+        let mut usage = Usage {
+            cost_usd: cost[1].parse().ok()?,
+            ..Default::default()
+        };
+        let after = &content[cost.get(0)?.end()..];
+        for model in self.model_usage_pattern.captures_iter(after) {
+            usage.input_tokens += parse_count(&model[1]).unwrap_or(0);
+            usage.output_tokens += parse_count(&model[2]).unwrap_or(0);
+        }
+        Some(usage)
+    }
+
     fn approval_keys(&self) -> &str {
         "y"
     }
@@ -525,6 +569,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_context_remaining() {
+        let parser = ClaudeCodeParser::new();
+        let content = "Context left until auto-compact: 42%\n> next\nContext low (8% remaining) · Run /compact to compact & continue\n";
+        assert_eq!(parser.parse_context_remaining(content), Some(8));
+        assert_eq!(
+            parser.parse_context_remaining("  ⏵⏵ accept edits · 15% context left\n"),
+            Some(15)
+        );
+        assert_eq!(parser.parse_context_remaining("> hello\n"), None);
+    }
+
+    #[test]
+    fn test_parse_usage() {
+        let parser = ClaudeCodeParser::new();
+        let content = r#"
+> /cost
+  ⎿  Total cost:            $0.55
+     Total duration (API):  6m 19.7s
+     Total duration (wall): 6h 33m 10.2s
+     Total code changes:    12 lines added, 3 lines removed
+     Usage by model:
+         claude-3-5-haiku:  12.4k input, 830 output, 0 cache read, 0 cache write
+            claude-sonnet:  1.2k input, 3.5k output, 45.1k cache read, 2.1k cache write
+"#;
+        assert_eq!(
+            parser.parse_usage(content),
+            Some(Usage {
+                input_tokens: 13_600,
+                output_tokens: 4_330,
+                cost_usd: 0.55,
+            })
+        );
+        assert_eq!(parser.parse_usage("> hello"), None);
+    }
+
     #[test]
     fn test_parse_thinking() {
         // Note: parse_status returns Idle for non-approval content.
@@ -0,0 +1,103 @@
+use anyhow::Result;
+use regex::Regex;
+
+use crate::agents::{AgentStatus, AgentType, ApprovalType};
+use crate::app::CustomAgent;
+
+use super::{compile_patterns, safe_tail, AgentParser};
+
+/// Parser for an agent defined in the config's `custom_agents`
+pub struct CustomParser {
+    name: String,
+    process: Vec<Regex>,
+    prompt: Vec<Regex>,
+    processing: Vec<Regex>,
+    idle: Vec<Regex>,
+}
+
+impl CustomParser {
+    /// Compiles the patterns of `agent`
+    pub fn new(agent: &CustomAgent) -> Result<Self> {
+        let owner = format!("custom agent {}", agent.name);
+        let compile = |patterns: &[String]| compile_patterns(patterns, &owner);
+        if agent.process.is_empty() {
+            anyhow::bail!("Agent {} has no process patterns", agent.name);
+        }
+        Ok(Self {
+            name: agent.name.clone(),
+            process: compile(&agent.process)?,
+            prompt: compile(&agent.prompt)?,
+            processing: compile(&agent.processing)?,
+            idle: compile(&agent.idle)?,
+        })
+    }
+}
+
+impl AgentParser for CustomParser {
+    fn agent_name(&self) -> &str {
+        &self.name
+    }
+
+    fn agent_type(&self) -> AgentType {
+        AgentType::Custom(self.name.clone())
+    }
+
+    fn matches(&self, detection_strings: &[&str]) -> bool {
+        detection_strings
+            .iter()
+            .any(|s| self.process.iter().any(|p| p.is_match(s)))
+    }
+
+    fn parse_status(&self, content: &str) -> AgentStatus {
+        let recent = safe_tail(content.trim_end(), 500);
+        let any = |patterns: &[Regex]| patterns.iter().any(|p| p.is_match(recent));
+
+        if any(&self.prompt) {
+            return AgentStatus::AwaitingApproval {
+                approval_type: ApprovalType::Other("Pending".to_string()),
+                details: String::new(),
+            };
+        }
+        if any(&self.processing) {
+            return AgentStatus::Processing {
+                activity: "Processing...".to_string(),
+            };
+        }
+        if any(&self.idle) {
+            return AgentStatus::Idle;
+        }
+        AgentStatus::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_parser() {
+        let parser = CustomParser::new(&CustomAgent {
+            name: "Cursor".to_string(),
+            process: vec!["cursor-agent".to_string()],
+            prompt: vec![r"Run this command\?".to_string()],
+            processing: vec!["Generating".to_string()],
+            idle: vec![r"^→\s*$".to_string()],
+        })
+        .unwrap();
+        assert_eq!(parser.agent_type(), AgentType::Custom("Cursor".to_string()));
+        assert!(parser.matches(&["zsh", "", "cursor-agent --resume"]));
+        assert!(!parser.matches(&["zsh", "", "claude"]));
+        assert!(matches!(
+            parser.parse_status("ls -la\nRun this command?\n"),
+            AgentStatus::AwaitingApproval { .. }
+        ));
+        assert_eq!(parser.parse_status("done\n→ "), AgentStatus::Idle);
+
+        let invalid = CustomAgent {
+            name: "Broken".to_string(),
+            process: vec!["(".to_string()],
+            ..Default::default()
+        };
+        assert!(CustomParser::new(&invalid).is_err());
+    }
+}
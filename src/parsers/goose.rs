@@ -0,0 +1,85 @@
+use regex::Regex;
+
+use crate::agents::{AgentStatus, AgentType, ApprovalType};
+
+use super::{safe_tail, AgentParser};
+
+/// Parser for Goose output
+pub struct GooseParser {
+    approval_pattern: Regex,
+    processing_pattern: Regex,
+    idle_pattern: Regex,
+}
+
+impl GooseParser {
+    pub fn new() -> Self {
+        Self {
+            // e.g. "Goose would like to call the above tool, do you allow?"
+            approval_pattern: Regex::new(r"(?i)do you allow|allow once|\[y/n\]").unwrap(),
+            processing_pattern: Regex::new(r"(?i)(thinking|working on it|running)").unwrap(),
+            idle_pattern: Regex::new(r"\( O\)>\s*$").unwrap(),
+        }
+    }
+}
+
+impl Default for GooseParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AgentParser for GooseParser {
+    fn agent_name(&self) -> &str {
+        "Goose"
+    }
+
+    fn agent_type(&self) -> AgentType {
+        AgentType::Goose
+    }
+
+    fn matches(&self, detection_strings: &[&str]) -> bool {
+        detection_strings
+            .iter()
+            .any(|s| s.to_lowercase().contains("goose"))
+    }
+
+    fn parse_status(&self, content: &str) -> AgentStatus {
+        let recent = safe_tail(content.trim_end(), 500);
+
+        if self.approval_pattern.is_match(recent) {
+            return AgentStatus::AwaitingApproval {
+                approval_type: ApprovalType::Other("Tool call".to_string()),
+                details: String::new(),
+            };
+        }
+
+        if self.idle_pattern.is_match(recent) {
+            return AgentStatus::Idle;
+        }
+
+        if self.processing_pattern.is_match(recent) {
+            return AgentStatus::Processing {
+                activity: "Working...".to_string(),
+            };
+        }
+
+        AgentStatus::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let parser = GooseParser::new();
+        assert!(parser.matches(&["goose", "", ""]));
+        assert_eq!(parser.parse_status("done.\n( O)> "), AgentStatus::Idle);
+        assert!(matches!(
+            parser
+                .parse_status("shell: ls\nGoose would like to call the above tool, do you allow?"),
+            AgentStatus::AwaitingApproval { .. }
+        ));
+    }
+}
@@ -0,0 +1,288 @@
+use std::fmt;
+
+/// A parsed JSON value
+///
+/// Just enough JSON for reading agent event streams line by line and for
+/// writing tmuxcc's own; objects keep their keys in order and numbers are
+/// f64.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parses one JSON document, `None` if it isn't valid JSON
+    pub(crate) fn parse(input: &str) -> Option<Json> {
+        let mut reader = Reader {
+            bytes: input.as_bytes(),
+            pos: 0,
+        };
+        let value = reader.value()?;
+        reader.skip_whitespace();
+        (reader.pos == reader.bytes.len()).then_some(value)
+    }
+
+    /// Returns the member `key` of an object
+    pub(crate) fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> &[Json] {
+        match self {
+            Json::Array(items) => items,
+            _ => &[],
+        }
+    }
+
+    /// Returns the string member `key` of an object
+    pub(crate) fn str_at(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(Json::as_str)
+    }
+
+    /// Returns the numeric member `key` of an object
+    pub(crate) fn f64_at(&self, key: &str) -> Option<f64> {
+        self.get(key).and_then(Json::as_f64)
+    }
+}
+
+impl fmt::Display for Json {
+    /// Writes the value as compact JSON
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) if !n.is_finite() => f.write_str("null"),
+            Json::Number(n) => write!(f, "{}", n),
+            Json::String(s) => write!(f, "\"{}\"", json_escape(s)),
+            Json::Array(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str("]")
+            }
+            Json::Object(members) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "\"{}\":{}", json_escape(key), value)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// Quotes `text` as a JSON string, without the quotes
+pub(crate) fn json_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn skip_whitespace(&mut self) {
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| b.is_ascii_whitespace())
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, expected: &[u8]) -> Option<()> {
+        let end = self.pos + expected.len();
+        (self.bytes.get(self.pos..end)? == expected).then(|| self.pos = end)
+    }
+
+    fn value(&mut self) -> Option<Json> {
+        self.skip_whitespace();
+        match *self.bytes.get(self.pos)? {
+            b'n' => self.eat(b"null").map(|()| Json::Null),
+            b't' => self.eat(b"true").map(|()| Json::Bool(true)),
+            b'f' => self.eat(b"false").map(|()| Json::Bool(false)),
+            b'"' => self.string().map(Json::String),
+            b'[' => self.array(),
+            b'{' => self.object(),
+            _ => self.number(),
+        }
+    }
+
+    fn array(&mut self) -> Option<Json> {
+        self.eat(b"[")?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.eat(b"]").is_some() {
+            return Some(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            if self.eat(b"]").is_some() {
+                return Some(Json::Array(items));
+            }
+            self.eat(b",")?;
+        }
+    }
+
+    fn object(&mut self) -> Option<Json> {
+        self.eat(b"{")?;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.eat(b"}").is_some() {
+            return Some(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.eat(b":")?;
+            members.push((key, self.value()?));
+            self.skip_whitespace();
+            if self.eat(b"}").is_some() {
+                return Some(Json::Object(members));
+            }
+            self.eat(b",")?;
+        }
+    }
+
+    fn number(&mut self) -> Option<Json> {
+        let start = self.pos;
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| b.is_ascii_digit() || matches!(b, b'-' | b'+' | b'.' | b'e' | b'E'))
+        {
+            self.pos += 1;
+        }
+        let text = std::str::from_utf8(&self.bytes[start..self.pos]).ok()?;
+        text.parse().ok().map(Json::Number)
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let digits = std::str::from_utf8(self.bytes.get(self.pos..self.pos + 4)?).ok()?;
+        self.pos += 4;
+        u32::from_str_radix(digits, 16).ok()
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.eat(b"\"")?;
+        let mut out = Vec::new();
+        loop {
+            let byte = *self.bytes.get(self.pos)?;
+            self.pos += 1;
+            match byte {
+                b'"' => return String::from_utf8(out).ok(),
+                b'\\' => {
+                    let escape = *self.bytes.get(self.pos)?;
+                    self.pos += 1;
+                    let c = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let mut code = self.hex4()?;
+                            // A high surrogate is followed by the low half
+                            if (0xD800..0xDC00).contains(&code) {
+                                self.eat(b"\\u")?;
+                                let low = self.hex4()?;
+                                code = 0x10000
+                                    + ((code - 0xD800) << 10)
+                                    + (low.checked_sub(0xDC00)?);
+                            }
+                            char::from_u32(code).unwrap_or('\u{FFFD}')
+                        }
+                        _ => return None,
+                    };
+                    let mut buf = [0; 4];
+                    out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                _ => out.push(byte),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json() {
+        let json = Json::parse(
+            r#"{"type":"assistant","n":-1.5e2,"ok":true,"list":[1, null, "a\"b\u00e9\ud83d\ude00"],"empty":{}}"#,
+        )
+        .unwrap();
+        assert_eq!(json.str_at("type"), Some("assistant"));
+        assert_eq!(json.f64_at("n"), Some(-150.0));
+        assert_eq!(json.get("ok").and_then(Json::as_bool), Some(true));
+        let list = json.get("list").unwrap().as_array();
+        assert_eq!(list[1], Json::Null);
+        assert_eq!(list[2].as_str(), Some("a\"bé😀"));
+        assert_eq!(json.get("empty"), Some(&Json::Object(Vec::new())));
+
+        assert!(Json::parse(r#"{"a":1"#).is_none());
+        assert!(Json::parse("[1,]").is_none());
+        assert!(Json::parse("{} x").is_none());
+
+        // Written back as it was read
+        let text = r#"{"a":[1,-1.5,null,true],"b":"x\"y\n","c":{}}"#;
+        assert_eq!(Json::parse(text).unwrap().to_string(), text);
+        assert_eq!(Json::Number(f64::NAN).to_string(), "null");
+    }
+}
@@ -1,16 +1,50 @@
+mod aider;
 mod claude_code;
 mod codex_cli;
+mod custom;
 mod gemini_cli;
+mod goose;
+mod json;
 mod opencode;
+mod patterns;
+pub mod stream_json;
 
+pub use aider::AiderParser;
+pub(crate) use claude_code::is_version_like;
 pub use claude_code::ClaudeCodeParser;
 pub use codex_cli::CodexCliParser;
+pub use custom::CustomParser;
 pub use gemini_cli::GeminiCliParser;
+pub use goose::GooseParser;
+pub(crate) use json::{json_escape, Json};
 pub use opencode::OpenCodeParser;
+pub use patterns::PatternParser;
 
-use crate::agents::{AgentStatus, AgentType, Subagent};
+use anyhow::{Context, Result};
+use regex::Regex;
+use tracing::warn;
+
+use crate::agents::{AgentStatus, AgentType, Subagent, Usage};
+use crate::app::Config;
 use crate::tmux::PaneInfo;
 
+/// Config names of the built-in parsers, in detection order
+pub const BUILTIN_AGENTS: &[&str] = &["claude", "opencode", "codex", "gemini", "aider", "goose"];
+
+/// Creates the built-in parser named `name` in [`BUILTIN_AGENTS`]
+fn builtin_parser(name: &str) -> Option<Box<dyn AgentParser>> {
+    let parser: Box<dyn AgentParser> = match name {
+        "claude" => Box::new(ClaudeCodeParser::new()),
+        "opencode" => Box::new(OpenCodeParser::new()),
+        "codex" => Box::new(CodexCliParser::new()),
+        "gemini" => Box::new(GeminiCliParser::new()),
+        "aider" => Box::new(AiderParser::new()),
+        "goose" => Box::new(GooseParser::new()),
+        _ => return None,
+    };
+    Some(parser)
+}
+
 /// Safely get the last N characters of a string (handles multi-byte chars)
 pub(crate) fn safe_tail(s: &str, max_chars: usize) -> &str {
     let char_count = s.chars().count();
@@ -22,6 +56,25 @@ pub(crate) fn safe_tail(s: &str, max_chars: usize) -> &str {
     &s[byte_idx..]
 }
 
+/// Compiles config regexes for multi-line output, naming `owner` in errors
+pub(crate) fn compile_patterns(patterns: &[String], owner: &str) -> Result<Vec<Regex>> {
+    patterns
+        .iter()
+        .map(|p| {
+            // Output patterns are checked against many lines at once
+            Regex::new(&format!("(?m){}", p))
+                .with_context(|| format!("Invalid pattern {:?} in {}", p, owner))
+        })
+        .collect()
+}
+
+/// Returns true if `parser` goes by `name` (its name or short name, any case)
+fn parser_named(parser: &dyn AgentParser, name: &str) -> bool {
+    let name = name.to_lowercase();
+    parser.agent_name().to_lowercase() == name
+        || parser.agent_type().short_name().to_lowercase() == name
+}
+
 /// Trait for parsing agent output
 pub trait AgentParser: Send + Sync {
     /// Returns the name of the agent
@@ -48,6 +101,12 @@ pub trait AgentParser: Send + Sync {
         None
     }
 
+    /// Parses the cumulative session usage the agent last reported (default: None)
+    fn parse_usage(&self, content: &str) -> Option<Usage> {
+        let _ = content;
+        None
+    }
+
     /// Returns the key(s) to send for approval
     fn approval_keys(&self) -> &str {
         "y"
@@ -62,24 +121,108 @@ pub trait AgentParser: Send + Sync {
 /// Registry of all available parsers
 pub struct ParserRegistry {
     parsers: Vec<Box<dyn AgentParser>>,
+    /// `agent_patterns` from the config: panes matching the regex use the parser at the index
+    overrides: Vec<(Regex, usize)>,
 }
 
 impl ParserRegistry {
     /// Creates a new registry with all default parsers
     pub fn new() -> Self {
         Self {
-            parsers: vec![
-                Box::new(ClaudeCodeParser::new()),
-                Box::new(OpenCodeParser::new()),
-                Box::new(CodexCliParser::new()),
-                Box::new(GeminiCliParser::new()),
-            ],
+            parsers: BUILTIN_AGENTS
+                .iter()
+                .filter_map(|name| builtin_parser(name))
+                .collect(),
+            overrides: Vec::new(),
         }
     }
 
+    /// Creates the registry described by `config`
+    ///
+    /// Custom agents come first so their patterns can claim panes a built-in
+    /// parser would also match; `enabled_agents` selects the built-ins.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let mut parsers: Vec<Box<dyn AgentParser>> = Vec::new();
+        for agent in &config.custom_agents {
+            parsers.push(Box::new(CustomParser::new(agent)?));
+        }
+        if config.enabled_agents.is_empty() {
+            parsers.extend(
+                BUILTIN_AGENTS
+                    .iter()
+                    .filter_map(|name| builtin_parser(name)),
+            );
+        } else {
+            for name in &config.enabled_agents {
+                let parser = builtin_parser(&name.to_lowercase()).with_context(|| {
+                    format!(
+                        "Unknown agent {:?} in enabled_agents (known: {})",
+                        name,
+                        BUILTIN_AGENTS.join(", ")
+                    )
+                })?;
+                parsers.push(parser);
+            }
+        }
+
+        let patterns = &config.state_patterns;
+        if !patterns.is_empty() {
+            parsers = parsers
+                .into_iter()
+                .map(|parser| -> Result<Box<dyn AgentParser>> {
+                    let applies = patterns.agents.is_empty()
+                        || patterns
+                            .agents
+                            .iter()
+                            .any(|name| parser_named(parser.as_ref(), name));
+                    Ok(if applies {
+                        Box::new(PatternParser::new(parser, patterns)?)
+                    } else {
+                        parser
+                    })
+                })
+                .collect::<Result<_>>()?;
+        }
+
+        let mut overrides = Vec::new();
+        for pattern in &config.agent_patterns {
+            let regex = Regex::new(&pattern.pattern)
+                .with_context(|| format!("Invalid agent pattern {:?}", pattern.pattern))?;
+            let index = parsers
+                .iter()
+                .position(|p| parser_named(p.as_ref(), &pattern.agent_type));
+            // Older configs name agent types that never existed; keep them loading
+            match index {
+                Some(index) => overrides.push((regex, index)),
+                None => warn!(
+                    "Ignoring agent pattern {:?}: unknown agent type {:?}",
+                    pattern.pattern, pattern.agent_type
+                ),
+            }
+        }
+
+        Ok(Self { parsers, overrides })
+    }
+
     /// Finds a parser that matches the given pane info
+    ///
+    /// An agent found in the pane's process tree decides first, so a wrapper
+    /// script or a title mentioning another agent can't misclassify it.
     pub fn find_parser_for_pane(&self, pane: &PaneInfo) -> Option<&dyn AgentParser> {
         let detection_strings = pane.detection_strings();
+        // Explicit mappings from the config beat any detection
+        if let Some((_, index)) = self
+            .overrides
+            .iter()
+            .find(|(regex, _)| detection_strings.iter().any(|s| regex.is_match(s)))
+        {
+            return Some(self.parsers[*index].as_ref());
+        }
+        if let Some(binary) = pane.agent_binary.as_deref() {
+            if let Some(parser) = self.parsers.iter().find(|p| p.matches(&[binary])) {
+                return Some(parser.as_ref());
+            }
+        }
         self.parsers
             .iter()
             .find(|p| p.matches(&detection_strings))
@@ -118,6 +261,7 @@ mod tests {
             pid: 1234,
             cmdline: "/usr/bin/claude".to_string(),
             child_commands: Vec::new(),
+            agent_binary: None,
         };
         assert!(registry.find_parser_for_pane(&claude_pane).is_some());
 
@@ -132,6 +276,7 @@ mod tests {
             pid: 1235,
             cmdline: "opencode".to_string(),
             child_commands: Vec::new(),
+            agent_binary: None,
         };
         assert!(registry.find_parser_for_pane(&opencode_pane).is_some());
 
@@ -147,7 +292,55 @@ mod tests {
             pid: 1236,
             cmdline: "-zsh".to_string(),
             child_commands: vec!["claude -c".to_string(), "claude".to_string()],
+            agent_binary: Some("claude".to_string()),
         };
         assert!(registry.find_parser_for_pane(&child_claude_pane).is_some());
     }
+
+    #[test]
+    fn test_from_config() {
+        let config: Config = toml::from_str(
+            r#"
+enabled_agents = ["claude", "codex"]
+
+[[agent_patterns]]
+pattern = "^my-claude-wrapper"
+agent_type = "claude"
+
+[[custom_agents]]
+name = "Cursor"
+process = ["cursor-agent"]
+"#,
+        )
+        .unwrap();
+        let registry = ParserRegistry::from_config(&config).unwrap();
+        let names: Vec<&str> = registry.all_parsers().map(|p| p.agent_name()).collect();
+        assert_eq!(names, vec!["Cursor", "Claude Code", "Codex CLI"]);
+
+        let mut pane = PaneInfo {
+            session: "main".to_string(),
+            window: 0,
+            window_name: "code".to_string(),
+            pane: 0,
+            command: "my-claude-wrapper".to_string(),
+            title: "codex".to_string(),
+            path: "/home/user/project".to_string(),
+            pid: 1237,
+            cmdline: "my-claude-wrapper".to_string(),
+            child_commands: Vec::new(),
+            agent_binary: None,
+        };
+        let parser = registry.find_parser_for_pane(&pane).unwrap();
+        assert_eq!(parser.agent_type(), AgentType::ClaudeCode);
+
+        // Disabled built-ins aren't detected
+        pane.command = "aider".to_string();
+        pane.cmdline = "aider".to_string();
+        pane.title = String::new();
+        pane.agent_binary = Some("aider".to_string());
+        assert!(registry.find_parser_for_pane(&pane).is_none());
+
+        let unknown: Config = toml::from_str(r#"enabled_agents = ["cursor"]"#).unwrap();
+        assert!(ParserRegistry::from_config(&unknown).is_err());
+    }
 }
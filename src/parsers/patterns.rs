@@ -0,0 +1,129 @@
+use anyhow::Result;
+use regex::Regex;
+
+use crate::agents::{AgentStatus, AgentType, ApprovalType, Subagent, Usage};
+use crate::app::StatePatterns;
+
+use super::{compile_patterns, safe_tail, AgentParser};
+
+/// Wraps a parser with the config's `state_patterns`
+///
+/// The user's patterns are checked first, in the order waiting → error →
+/// working → idle; when none matches the wrapped parser decides.
+pub struct PatternParser {
+    inner: Box<dyn AgentParser>,
+    waiting: Vec<Regex>,
+    error: Vec<Regex>,
+    working: Vec<Regex>,
+    idle: Vec<Regex>,
+}
+
+impl PatternParser {
+    pub fn new(inner: Box<dyn AgentParser>, patterns: &StatePatterns) -> Result<Self> {
+        let owner = "state_patterns";
+        Ok(Self {
+            inner,
+            waiting: compile_patterns(&patterns.waiting, owner)?,
+            error: compile_patterns(&patterns.error, owner)?,
+            working: compile_patterns(&patterns.working, owner)?,
+            idle: compile_patterns(&patterns.idle, owner)?,
+        })
+    }
+}
+
+/// Returns the line of `text` containing the first match of any of `patterns`
+fn matching_line<'a>(patterns: &[Regex], text: &'a str) -> Option<&'a str> {
+    let m = patterns.iter().find_map(|p| p.find(text))?;
+    let start = text[..m.start()].rfind('\n').map_or(0, |i| i + 1);
+    let end = text[m.end()..]
+        .find('\n')
+        .map_or(text.len(), |i| m.end() + i);
+    Some(text[start..end].trim())
+}
+
+impl AgentParser for PatternParser {
+    fn agent_name(&self) -> &str {
+        self.inner.agent_name()
+    }
+
+    fn agent_type(&self) -> AgentType {
+        self.inner.agent_type()
+    }
+
+    fn matches(&self, detection_strings: &[&str]) -> bool {
+        self.inner.matches(detection_strings)
+    }
+
+    fn parse_status(&self, content: &str) -> AgentStatus {
+        let recent = safe_tail(content.trim_end(), 500);
+
+        if let Some(line) = matching_line(&self.waiting, recent) {
+            return AgentStatus::AwaitingApproval {
+                approval_type: ApprovalType::Other("Pending".to_string()),
+                details: line.to_string(),
+            };
+        }
+        if let Some(line) = matching_line(&self.error, recent) {
+            return AgentStatus::Error {
+                message: line.to_string(),
+            };
+        }
+        if matching_line(&self.working, recent).is_some() {
+            return AgentStatus::Processing {
+                activity: "Working...".to_string(),
+            };
+        }
+        if matching_line(&self.idle, recent).is_some() {
+            return AgentStatus::Idle;
+        }
+        self.inner.parse_status(content)
+    }
+
+    fn parse_subagents(&self, content: &str) -> Vec<Subagent> {
+        self.inner.parse_subagents(content)
+    }
+
+    fn parse_context_remaining(&self, content: &str) -> Option<u8> {
+        self.inner.parse_context_remaining(content)
+    }
+
+    fn parse_usage(&self, content: &str) -> Option<Usage> {
+        self.inner.parse_usage(content)
+    }
+
+    fn approval_keys(&self) -> &str {
+        self.inner.approval_keys()
+    }
+
+    fn rejection_keys(&self) -> &str {
+        self.inner.rejection_keys()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::ClaudeCodeParser;
+
+    #[test]
+    fn test_pattern_parser() {
+        let patterns = StatePatterns {
+            waiting: vec![r"❯ 1\. Yes".to_string()],
+            error: vec!["API Error".to_string(), "overloaded".to_string()],
+            ..Default::default()
+        };
+        let parser = PatternParser::new(Box::new(ClaudeCodeParser::new()), &patterns).unwrap();
+        assert_eq!(parser.agent_type(), AgentType::ClaudeCode);
+
+        match parser.parse_status("Write to foo.rs?\n❯ 1. Yes\n  2. No\n") {
+            AgentStatus::AwaitingApproval { details, .. } => assert_eq!(details, "❯ 1. Yes"),
+            other => panic!("unexpected status {:?}", other),
+        }
+        assert_eq!(
+            parser.parse_status("⏺ Reading\n  ⎿ API Error: 529 overloaded\n"),
+            AgentStatus::Error {
+                message: "⎿ API Error: 529 overloaded".to_string()
+            }
+        );
+    }
+}
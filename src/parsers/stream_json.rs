@@ -0,0 +1,243 @@
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use super::json::Json;
+use crate::agents::{AgentStatus, Usage};
+
+/// Environment variable holding the event stream file of a headless agent
+pub const STREAM_VAR: &str = "TMUXCC_STREAM";
+
+/// Event appended by `tmuxcc headless` after each turn with the running totals
+pub const USAGE_EVENT: &str = "tmuxcc_usage";
+
+/// How much of the end of a stream file is read per poll
+const STREAM_TAIL_BYTES: u64 = 64 * 1024;
+
+/// What the latest events of a `--output-format stream-json` stream say
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamStatus {
+    pub status: AgentStatus,
+    /// Totals of the session, from the last usage event
+    pub usage: Option<Usage>,
+}
+
+/// Returns a short description of a `tool_use` content block like "Bash(cargo test)"
+fn tool_label(block: &Json) -> Option<String> {
+    let name = block.str_at("name")?;
+    let input = block.get("input");
+    let arg = [
+        "command",
+        "file_path",
+        "path",
+        "pattern",
+        "url",
+        "description",
+    ]
+    .iter()
+    .find_map(|key| input.and_then(|i| i.str_at(key)));
+    Some(match arg {
+        Some(arg) => {
+            let arg = arg.lines().next().unwrap_or_default();
+            format!("{}({})", name, arg)
+        }
+        None => name.to_string(),
+    })
+}
+
+/// Returns the token and cost totals a `result` event reports for its run
+pub fn result_usage(line: &str) -> Option<Usage> {
+    let event = Json::parse(line)?;
+    if event.str_at("type") != Some("result") {
+        return None;
+    }
+    let usage = event.get("usage");
+    let count = |key: &str| usage.and_then(|u| u.f64_at(key)).unwrap_or(0.0) as u64;
+    Some(Usage {
+        input_tokens: count("input_tokens")
+            + count("cache_creation_input_tokens")
+            + count("cache_read_input_tokens"),
+        output_tokens: count("output_tokens"),
+        cost_usd: event.f64_at("total_cost_usd").unwrap_or(0.0),
+    })
+}
+
+/// Returns the session id carried by an event, if any
+pub fn session_id(line: &str) -> Option<String> {
+    Json::parse(line)?.str_at("session_id").map(str::to_string)
+}
+
+/// Renders an event as the lines shown in a headless agent's pane
+pub fn render_event(line: &str) -> Vec<String> {
+    let Some(event) = Json::parse(line) else {
+        return vec![line.to_string()];
+    };
+    let blocks = || {
+        event
+            .get("message")
+            .and_then(|m| m.get("content"))
+            .map_or(&[][..], Json::as_array)
+    };
+    match event.str_at("type") {
+        Some("system") if event.str_at("subtype") == Some("init") => vec![format!(
+            "● session {} ({})",
+            event.str_at("session_id").unwrap_or("?"),
+            event.str_at("model").unwrap_or("?")
+        )],
+        Some("assistant") => blocks()
+            .iter()
+            .flat_map(|block| match block.str_at("type") {
+                Some("text") => block
+                    .str_at("text")
+                    .unwrap_or_default()
+                    .lines()
+                    .map(str::to_string)
+                    .collect(),
+                Some("tool_use") => vec![format!("⏺ {}", tool_label(block).unwrap_or_default())],
+                _ => Vec::new(),
+            })
+            .collect(),
+        Some("user") => blocks()
+            .iter()
+            .filter(|b| b.get("is_error").and_then(Json::as_bool) == Some(true))
+            .map(|_| "  ⎿ tool error".to_string())
+            .collect(),
+        Some("result") => {
+            let mut lines = vec![format!(
+                "── {} · {} turns · ${:.2} · {}s",
+                event.str_at("subtype").unwrap_or("done"),
+                event.f64_at("num_turns").unwrap_or(0.0),
+                event.f64_at("total_cost_usd").unwrap_or(0.0),
+                (event.f64_at("duration_ms").unwrap_or(0.0) / 1000.0).round()
+            )];
+            if event.get("is_error").and_then(Json::as_bool) == Some(true) {
+                lines.extend(event.str_at("result").map(str::to_string));
+            }
+            lines
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Formats the usage event `tmuxcc headless` appends to a stream
+pub fn usage_event(usage: &Usage) -> String {
+    format!(
+        r#"{{"type":"{}","input_tokens":{},"output_tokens":{},"cost_usd":{}}}"#,
+        USAGE_EVENT, usage.input_tokens, usage.output_tokens, usage.cost_usd
+    )
+}
+
+/// Derives the agent's status from the last events of a stream
+///
+/// A `result` event ends a turn (Idle, or Error if it failed); anything
+/// after it means the agent is working on the next one.
+pub fn parse_stream(content: &str) -> StreamStatus {
+    let mut status = AgentStatus::Unknown;
+    let mut usage = None;
+    for event in content.lines().filter_map(Json::parse) {
+        let working = |activity: &str| AgentStatus::Processing {
+            activity: activity.to_string(),
+        };
+        match event.str_at("type") {
+            Some("system") => status = working("Starting"),
+            Some("user") => status = working("Thinking"),
+            Some("assistant") => {
+                let content = event.get("message").and_then(|m| m.get("content"));
+                let last = content.map(Json::as_array).and_then(<[Json]>::last);
+                status = match last.filter(|b| b.str_at("type") == Some("tool_use")) {
+                    Some(block) => working(&tool_label(block).unwrap_or_default()),
+                    None => working("Thinking"),
+                };
+            }
+            Some("result") => {
+                let failed = event.get("is_error").and_then(Json::as_bool) == Some(true)
+                    || event.str_at("subtype").is_some_and(|s| s != "success");
+                status = if failed {
+                    let message = event
+                        .str_at("result")
+                        .or(event.str_at("subtype"))
+                        .unwrap_or("error");
+                    AgentStatus::Error {
+                        message: message.to_string(),
+                    }
+                } else {
+                    AgentStatus::Idle
+                };
+            }
+            Some(USAGE_EVENT) => {
+                let count = |key: &str| event.f64_at(key).unwrap_or(0.0) as u64;
+                usage = Some(Usage {
+                    input_tokens: count("input_tokens"),
+                    output_tokens: count("output_tokens"),
+                    cost_usd: event.f64_at("cost_usd").unwrap_or(0.0),
+                });
+            }
+            _ => {}
+        }
+    }
+    StreamStatus { status, usage }
+}
+
+/// Reads the end of the stream file at `path` and parses it
+pub fn read_stream(path: &Path) -> Option<StreamStatus> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let start = len.saturating_sub(STREAM_TAIL_BYTES);
+    file.seek(SeekFrom::Start(start)).ok()?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).ok()?;
+    let content = String::from_utf8_lossy(&bytes);
+    // The first line may be cut off
+    let content = if start > 0 {
+        content.split_once('\n').map_or("", |(_, rest)| rest)
+    } else {
+        &content
+    };
+    Some(parse_stream(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TURN: &str = r#"{"type":"system","subtype":"init","session_id":"abc","model":"claude-sonnet-4"}
+{"type":"assistant","message":{"content":[{"type":"text","text":"Running tests"},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"cargo test\necho done"}}]}}
+"#;
+
+    #[test]
+    fn test_parse_stream() {
+        let status = parse_stream(TURN);
+        assert_eq!(
+            status.status,
+            AgentStatus::Processing {
+                activity: "Bash(cargo test)".to_string()
+            }
+        );
+        assert_eq!(status.usage, None);
+        assert_eq!(
+            session_id(TURN.lines().next().unwrap()).as_deref(),
+            Some("abc")
+        );
+        assert_eq!(
+            render_event(TURN.lines().nth(1).unwrap()),
+            vec!["Running tests", "⏺ Bash(cargo test)"]
+        );
+
+        let result = r#"{"type":"result","subtype":"success","is_error":false,"total_cost_usd":0.25,"usage":{"input_tokens":10,"cache_read_input_tokens":1000,"output_tokens":200},"session_id":"abc"}"#;
+        let usage = result_usage(result).unwrap();
+        assert_eq!(usage.input_tokens, 1010);
+        assert_eq!(usage.output_tokens, 200);
+
+        let content = format!("{}{}\n{}\n", TURN, result, usage_event(&usage));
+        let status = parse_stream(&content);
+        assert_eq!(status.status, AgentStatus::Idle);
+        assert_eq!(status.usage, Some(usage));
+
+        let failed = r#"{"type":"result","subtype":"error_max_turns","is_error":true}"#;
+        assert_eq!(
+            parse_stream(failed).status,
+            AgentStatus::Error {
+                message: "error_max_turns".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,133 @@
+/// A piece of terminal output captured with `capture-pane -e`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnsiSegment<'a> {
+    /// Printable text
+    Text(&'a str),
+    /// Parameters of an SGR (`ESC [ ... m`) sequence, e.g. "1;38;5;208"
+    Sgr(&'a str),
+}
+
+/// Splits `text` into printable text and SGR sequences
+///
+/// Other escape sequences (cursor movement, OSC titles and hyperlinks) are
+/// dropped, so the text segments are exactly what the terminal would show.
+pub fn ansi_segments(text: &str) -> AnsiSegments<'_> {
+    AnsiSegments { rest: text }
+}
+
+/// Iterator returned by [`ansi_segments`]
+pub struct AnsiSegments<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for AnsiSegments<'a> {
+    type Item = AnsiSegment<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+            let Some(esc) = self.rest.find('\x1b') else {
+                let text = self.rest;
+                self.rest = "";
+                return Some(AnsiSegment::Text(text));
+            };
+            if esc > 0 {
+                let text = &self.rest[..esc];
+                self.rest = &self.rest[esc..];
+                return Some(AnsiSegment::Text(text));
+            }
+
+            let bytes = self.rest.as_bytes();
+            match bytes.get(1) {
+                Some(b'[') => {
+                    // CSI: parameter and intermediate bytes, then one final byte
+                    let end = bytes[2..]
+                        .iter()
+                        .position(|b| (0x40..=0x7e).contains(b))
+                        .map(|i| i + 2);
+                    let Some(end) = end else {
+                        self.rest = "";
+                        return None;
+                    };
+                    let params = &self.rest[2..end];
+                    let is_sgr = bytes[end] == b'm';
+                    self.rest = &self.rest[end + 1..];
+                    if is_sgr {
+                        return Some(AnsiSegment::Sgr(params));
+                    }
+                }
+                Some(b']') => {
+                    // OSC: terminated by BEL or ST (ESC \)
+                    let body = &self.rest[2..];
+                    self.rest = match (body.find('\x07'), body.find("\x1b\\")) {
+                        (Some(bel), Some(st)) if st < bel => &body[st + 2..],
+                        (Some(bel), _) => &body[bel + 1..],
+                        (None, Some(st)) => &body[st + 2..],
+                        (None, None) => "",
+                    };
+                }
+                Some(b'(' | b')' | b'*' | b'+') => {
+                    // Character set designation such as ESC ( B
+                    self.rest = self.rest.get(3..).unwrap_or("");
+                }
+                Some(_) => {
+                    // Two-byte sequence such as ESC =
+                    let skip = self.rest[1..]
+                        .chars()
+                        .next()
+                        .map_or(1, |c| 1 + c.len_utf8());
+                    self.rest = &self.rest[skip..];
+                }
+                None => {
+                    self.rest = "";
+                }
+            }
+        }
+    }
+}
+
+/// Removes all escape sequences from `text`
+pub fn strip_ansi(text: &str) -> String {
+    if !text.contains('\x1b') {
+        return text.to_string();
+    }
+    ansi_segments(text)
+        .filter_map(|segment| match segment {
+            AnsiSegment::Text(text) => Some(text),
+            AnsiSegment::Sgr(_) => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ansi_segments() {
+        let segments: Vec<_> = ansi_segments("a\x1b[1;31mred\x1b[0m\x1b[2Kb").collect();
+        assert_eq!(
+            segments,
+            vec![
+                AnsiSegment::Text("a"),
+                AnsiSegment::Sgr("1;31"),
+                AnsiSegment::Text("red"),
+                AnsiSegment::Sgr("0"),
+                AnsiSegment::Text("b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi("plain"), "plain");
+        assert_eq!(strip_ansi("\x1b[38;5;208m✻ Thinking\x1b[39m"), "✻ Thinking");
+        assert_eq!(
+            strip_ansi("\x1b]8;;https://x.test\x1b\\link\x1b]8;;\x1b\\ done"),
+            "link done"
+        );
+        assert_eq!(strip_ansi("\x1b(Bok\x1b["), "ok");
+    }
+}
@@ -1,45 +1,106 @@
 use anyhow::{Context, Result};
-use std::process::Command;
+use std::io::Write;
+use std::process::{Command, Stdio};
 
-use super::pane::PaneInfo;
+use super::server::TmuxServer;
+use super::tree::TmuxTree;
+
+/// How text is delivered to a pane by [`TmuxClient::send_text`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendMode {
+    /// Types the text verbatim (`send-keys -l`), no key names are interpreted
+    Literal,
+    /// Pastes the text through a tmux buffer, wrapped in bracketed paste
+    /// sequences when the application requested them
+    Paste,
+    /// Interprets whitespace-separated words as tmux key names (e.g. `C-c Enter`)
+    Keys,
+}
+
+impl SendMode {
+    /// Picks the mode for typing user text: paste for multi-line text so
+    /// embedded newlines don't submit each line, literal otherwise
+    pub fn for_text(text: &str) -> Self {
+        if text.contains('\n') {
+            SendMode::Paste
+        } else {
+            SendMode::Literal
+        }
+    }
+}
+
+/// Format that makes commands creating panes print the new pane's target
+const NEW_PANE_FORMAT: &str = "#{session_name}:#{window_index}.#{pane_index}";
+
+/// Escapes an argument so tmux doesn't treat it as a command separator
+///
+/// tmux splits commands on an argument ending in `;` even when it is passed
+/// through exec, so a trailing semicolon is escaped as `\;`. Quotes need no
+/// escaping because no shell is involved.
+fn escape_tmux_arg(arg: &str) -> String {
+    match arg.strip_suffix(';') {
+        Some(rest) => format!("{}\\;", rest),
+        None => arg.to_string(),
+    }
+}
 
 /// Client for interacting with tmux
+#[derive(Debug, Clone)]
 pub struct TmuxClient {
     /// Number of lines to capture from pane
     capture_lines: u32,
+    /// Server every command is sent to
+    server: TmuxServer,
 }
 
 impl TmuxClient {
     /// Creates a new TmuxClient with default settings
     pub fn new() -> Self {
-        Self { capture_lines: 100 }
+        Self::with_capture_lines(100)
     }
 
     /// Creates a new TmuxClient with custom capture lines
     pub fn with_capture_lines(capture_lines: u32) -> Self {
-        Self { capture_lines }
+        Self {
+            capture_lines,
+            server: TmuxServer::Default,
+        }
+    }
+
+    /// Returns a client with the same settings that talks to `server`
+    pub fn for_server(&self, server: &TmuxServer) -> Self {
+        Self {
+            capture_lines: self.capture_lines,
+            server: server.clone(),
+        }
+    }
+
+    /// Server this client talks to
+    pub fn server(&self) -> &TmuxServer {
+        &self.server
+    }
+
+    /// Returns a `tmux` command with `args` for this client's server
+    fn tmux<I, S>(&self, args: I) -> Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.server.command(args)
     }
 
     /// Check if tmux is available and running
     pub fn is_available(&self) -> bool {
-        Command::new("tmux")
-            .arg("list-sessions")
+        self.tmux(["list-sessions"])
             .output()
             .map(|o| o.status.success())
             .unwrap_or(false)
     }
 
-    /// Lists all panes across all attached sessions
-    pub fn list_panes(&self) -> Result<Vec<PaneInfo>> {
-        // Use tab separator to handle spaces in titles/paths
-        // Include session_attached to filter out detached sessions
-        let output = Command::new("tmux")
-            .args([
-                "list-panes",
-                "-a",
-                "-F",
-                "#{session_attached}\t#{session_name}:#{window_index}.#{pane_index}\t#{window_name}\t#{pane_current_command}\t#{pane_pid}\t#{pane_title}\t#{pane_current_path}",
-            ])
+    /// Lists all sessions, windows and panes in one `list-panes -a` call
+    pub fn list_tree(&self) -> Result<TmuxTree> {
+        let output = self
+            .tmux(["list-panes", "-a", "-F", TmuxTree::FORMAT])
             .output()
             .context("Failed to execute tmux list-panes")?;
 
@@ -48,31 +109,43 @@ impl TmuxClient {
             anyhow::bail!("tmux list-panes failed: {}", stderr);
         }
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let panes: Vec<PaneInfo> = stdout
-            .lines()
-            .filter_map(|line| {
-                // First field is session_attached (0 or 1)
-                let (attached, rest) = line.split_once('\t')?;
-
-                // Only include panes from attached sessions
-                if attached == "1" {
-                    PaneInfo::parse(rest)
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        Ok(panes)
+        let mut tree = TmuxTree::parse(&String::from_utf8_lossy(&output.stdout));
+        tree.set_server(&self.server);
+        Ok(tree)
     }
 
     /// Captures the content of a specific pane
     pub fn capture_pane(&self, target: &str) -> Result<String> {
+        self.capture(target, false)
+    }
+
+    /// Captures the content of a pane with its colors and attributes as SGR sequences
+    pub fn capture_pane_styled(&self, target: &str) -> Result<String> {
+        self.capture(target, true)
+    }
+
+    /// Captures up to `lines` lines of a pane's history and its visible
+    /// content, with wrapped lines joined
+    pub fn capture_history(&self, target: &str, lines: usize) -> Result<String> {
+        let start_line = format!("-{}", lines);
+        self.run_capture(target, &["-S", &start_line, "-J"])
+    }
+
+    fn capture(&self, target: &str, escapes: bool) -> Result<String> {
         let start_line = format!("-{}", self.capture_lines);
+        let mut args = vec!["-S", start_line.as_str()];
+        if escapes {
+            args.push("-e");
+        }
+        self.run_capture(target, &args)
+    }
 
-        let output = Command::new("tmux")
-            .args(["capture-pane", "-p", "-t", target, "-S", &start_line])
+    fn run_capture(&self, target: &str, options: &[&str]) -> Result<String> {
+        let mut args = vec!["capture-pane", "-p", "-t", target];
+        args.extend_from_slice(options);
+
+        let output = self
+            .tmux(args)
             .output()
             .context("Failed to execute tmux capture-pane")?;
 
@@ -86,8 +159,8 @@ impl TmuxClient {
 
     /// Sends keys to a specific pane
     pub fn send_keys(&self, target: &str, keys: &str) -> Result<()> {
-        let output = Command::new("tmux")
-            .args(["send-keys", "-t", target, keys])
+        let output = self
+            .tmux(["send-keys", "-t", target, keys])
             .output()
             .context("Failed to execute tmux send-keys")?;
 
@@ -99,10 +172,155 @@ impl TmuxClient {
         Ok(())
     }
 
+    /// Shows `message` in the status line of the attached clients
+    pub fn display_message(&self, message: &str) -> Result<()> {
+        // The message is a format; `##` keeps a `#` from agent output literal
+        let output = self
+            .tmux(["display-message", &message.replace('#', "##")])
+            .output()
+            .context("Failed to execute tmux display-message")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux display-message failed: {}", stderr);
+        }
+
+        Ok(())
+    }
+
+    /// Rings the bell in the pane at `target`, which tmux flags on its window
+    /// in the status line (`monitor-bell`)
+    ///
+    /// The bell is written to the pane's terminal by the tmux server, so
+    /// this works for remote servers too.
+    pub fn ring_bell(&self, target: &str) -> Result<()> {
+        let output = self
+            .tmux([
+                "run-shell",
+                "-b",
+                "-t",
+                target,
+                "printf '\\a' > #{pane_tty}",
+            ])
+            .output()
+            .context("Failed to execute tmux run-shell")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux run-shell failed for {}: {}", target, stderr);
+        }
+
+        Ok(())
+    }
+
+    /// Sends `text` to a specific pane using the given mode
+    pub fn send_text(&self, target: &str, text: &str, mode: SendMode) -> Result<()> {
+        match mode {
+            SendMode::Literal => {
+                let text = escape_tmux_arg(text);
+                self.run_send_keys(target, &["-l", "-t", target, "--", &text])
+            }
+            SendMode::Keys => {
+                let keys: Vec<String> = text.split_whitespace().map(escape_tmux_arg).collect();
+                if keys.is_empty() {
+                    return Ok(());
+                }
+                let mut args = vec!["-t", target, "--"];
+                args.extend(keys.iter().map(String::as_str));
+                self.run_send_keys(target, &args)
+            }
+            SendMode::Paste => self.paste_text(target, text),
+        }
+    }
+
+    /// Runs `send-keys` with the given arguments
+    fn run_send_keys(&self, target: &str, args: &[&str]) -> Result<()> {
+        let output = self
+            .tmux(std::iter::once("send-keys").chain(args.iter().copied()))
+            .output()
+            .context("Failed to execute tmux send-keys")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux send-keys failed for {}: {}", target, stderr);
+        }
+
+        Ok(())
+    }
+
+    /// Loads `text` into a temporary buffer and pastes it into the pane
+    ///
+    /// The text travels over stdin, so it is never parsed by tmux. `-p` uses
+    /// bracketed paste when the application enabled it, `-r` keeps newlines
+    /// as LF and `-d` deletes the buffer afterwards.
+    fn paste_text(&self, target: &str, text: &str) -> Result<()> {
+        let buffer = format!("tmuxcc-{}", std::process::id());
+
+        self.load_buffer(&["load-buffer", "-b", &buffer, "-"], text)?;
+
+        let output = self
+            .tmux([
+                "paste-buffer",
+                "-p",
+                "-r",
+                "-d",
+                "-b",
+                &buffer,
+                "-t",
+                target,
+            ])
+            .output()
+            .context("Failed to execute tmux paste-buffer")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux paste-buffer failed for {}: {}", target, stderr);
+        }
+
+        Ok(())
+    }
+
+    /// Loads `text` into a new automatic buffer, the one `prefix ]` pastes
+    ///
+    /// With `clipboard`, tmux also forwards the text to the terminal's
+    /// clipboard (`load-buffer -w`, subject to its `set-clipboard` option).
+    pub fn set_buffer(&self, text: &str, clipboard: bool) -> Result<()> {
+        let mut args = vec!["load-buffer"];
+        if clipboard {
+            args.push("-w");
+        }
+        args.push("-");
+        self.load_buffer(&args, text)
+    }
+
+    /// Runs a `load-buffer ... -` command with `text` on stdin
+    fn load_buffer(&self, args: &[&str], text: &str) -> Result<()> {
+        let mut child = self
+            .tmux(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to execute tmux load-buffer")?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .context("Failed to write to tmux load-buffer")?;
+        }
+        let output = child
+            .wait_with_output()
+            .context("Failed to execute tmux load-buffer")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux load-buffer failed: {}", stderr);
+        }
+        Ok(())
+    }
+
     /// Selects (focuses) a specific pane
     pub fn select_pane(&self, target: &str) -> Result<()> {
-        let output = Command::new("tmux")
-            .args(["select-pane", "-t", target])
+        let output = self
+            .tmux(["select-pane", "-t", target])
             .output()
             .context("Failed to execute tmux select-pane")?;
 
@@ -123,8 +341,8 @@ impl TmuxClient {
             target
         };
 
-        let output = Command::new("tmux")
-            .args(["select-window", "-t", window_target])
+        let output = self
+            .tmux(["select-window", "-t", window_target])
             .output()
             .context("Failed to execute tmux select-window")?;
 
@@ -140,6 +358,334 @@ impl TmuxClient {
         Ok(())
     }
 
+    /// Opens a new window with the given working directory
+    ///
+    /// Targets `session` if given, otherwise tmux's current session.
+    /// Returns the target of the new pane (e.g., "main:3.0").
+    pub fn new_window(
+        &self,
+        session: Option<&str>,
+        cwd: &str,
+        name: Option<&str>,
+    ) -> Result<String> {
+        let mut args = vec![
+            "new-window".to_string(),
+            "-P".to_string(),
+            "-F".to_string(),
+            NEW_PANE_FORMAT.to_string(),
+            "-c".to_string(),
+            cwd.to_string(),
+        ];
+        if let Some(session) = session {
+            // Trailing colon targets the session rather than a window index
+            args.push("-t".to_string());
+            args.push(format!("{}:", session));
+        }
+        if let Some(name) = name {
+            args.push("-n".to_string());
+            args.push(name.to_string());
+        }
+
+        let output = self
+            .tmux(&args)
+            .output()
+            .context("Failed to execute tmux new-window")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux new-window failed: {}", stderr);
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Splits the pane at `target`, starting the new pane in `cwd`
+    ///
+    /// The new pane is placed to the right, or below when `vertical` is set.
+    /// Returns the target of the new pane.
+    pub fn split_window(&self, target: &str, cwd: &str, vertical: bool) -> Result<String> {
+        let direction = if vertical { "-v" } else { "-h" };
+        let output = self
+            .tmux([
+                "split-window",
+                direction,
+                "-P",
+                "-F",
+                NEW_PANE_FORMAT,
+                "-t",
+                target,
+                "-c",
+                cwd,
+            ])
+            .output()
+            .context("Failed to execute tmux split-window")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux split-window failed for {}: {}", target, stderr);
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Creates a detached session with its first window in `cwd`
+    ///
+    /// tmux picks the session name when `name` is None.
+    /// Returns the target of the session's first pane.
+    pub fn new_session(&self, name: Option<&str>, cwd: &str) -> Result<String> {
+        let mut args = vec!["new-session", "-d", "-P", "-F", NEW_PANE_FORMAT, "-c", cwd];
+        if let Some(name) = name {
+            args.extend(["-s", name]);
+        }
+
+        let output = self
+            .tmux(&args)
+            .output()
+            .context("Failed to execute tmux new-session")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux new-session failed: {}", stderr);
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Renames the window containing `target`
+    pub fn rename_window(&self, target: &str, name: &str) -> Result<()> {
+        let output = self
+            .tmux(["rename-window", "-t", target, name])
+            .output()
+            .context("Failed to execute tmux rename-window")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux rename-window failed for {}: {}", target, stderr);
+        }
+
+        Ok(())
+    }
+
+    /// Turns automatic renaming of the window containing `target` on or off
+    pub fn set_automatic_rename(&self, target: &str, on: bool) -> Result<()> {
+        let value = if on { "on" } else { "off" };
+        let output = self
+            .tmux(["set-option", "-w", "-t", target, "automatic-rename", value])
+            .output()
+            .context("Failed to execute tmux set-option")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux set-option failed for {}: {}", target, stderr);
+        }
+
+        Ok(())
+    }
+
+    /// Sets the option `name` of the pane at `target` itself, not its window
+    pub fn set_pane_option(&self, target: &str, name: &str, value: &str) -> Result<()> {
+        let output = self
+            .tmux(["set-option", "-p", "-t", target, name, value])
+            .output()
+            .context("Failed to execute tmux set-option")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux set-option failed for {}: {}", target, stderr);
+        }
+
+        Ok(())
+    }
+
+    /// Unsets the option `name` of the pane at `target`, so the pane follows
+    /// its window's (or the global) value again
+    pub fn unset_pane_option(&self, target: &str, name: &str) -> Result<()> {
+        let output = self
+            .tmux(["set-option", "-p", "-u", "-t", target, name])
+            .output()
+            .context("Failed to execute tmux set-option")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux set-option failed for {}: {}", target, stderr);
+        }
+
+        Ok(())
+    }
+
+    /// Sets the title of the pane at `target`
+    pub fn set_pane_title(&self, target: &str, title: &str) -> Result<()> {
+        let output = self
+            .tmux(["select-pane", "-t", target, "-T", title])
+            .output()
+            .context("Failed to execute tmux select-pane")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux select-pane failed for {}: {}", target, stderr);
+        }
+
+        Ok(())
+    }
+
+    /// Arranges the panes of the window containing `target` with a preset layout
+    pub fn select_layout(&self, target: &str, layout: &str) -> Result<()> {
+        let output = self
+            .tmux(["select-layout", "-t", target, layout])
+            .output()
+            .context("Failed to execute tmux select-layout")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux select-layout failed for {}: {}", target, stderr);
+        }
+
+        Ok(())
+    }
+
+    /// Zooms the pane at `target` to fill its window, or unzooms it
+    pub fn toggle_zoom(&self, target: &str) -> Result<()> {
+        let output = self
+            .tmux(["resize-pane", "-Z", "-t", target])
+            .output()
+            .context("Failed to execute tmux resize-pane")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux resize-pane failed for {}: {}", target, stderr);
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if a session named `name` exists
+    pub fn has_session(&self, name: &str) -> bool {
+        // `=` requests an exact match instead of a name prefix
+        self.tmux(["has-session", "-t", &format!("={}", name)])
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    /// Kills the pane at `target` and the processes running in it
+    pub fn kill_pane(&self, target: &str) -> Result<()> {
+        let output = self
+            .tmux(["kill-pane", "-t", target])
+            .output()
+            .context("Failed to execute tmux kill-pane")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux kill-pane failed for {}: {}", target, stderr);
+        }
+
+        Ok(())
+    }
+
+    /// Kills the processes in the pane at `target` and starts it again in `cwd`
+    ///
+    /// Runs `command` if given, otherwise the pane's original command
+    /// (usually the default shell).
+    pub fn respawn_pane(&self, target: &str, cwd: &str, command: Option<&str>) -> Result<()> {
+        let mut args = vec!["respawn-pane", "-k", "-t", target, "-c", cwd];
+        args.extend(command);
+        let output = self
+            .tmux(&args)
+            .output()
+            .context("Failed to execute tmux respawn-pane")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux respawn-pane failed for {}: {}", target, stderr);
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if tmuxcc itself runs inside a tmux client
+    pub fn is_inside_tmux() -> bool {
+        std::env::var_os("TMUX").is_some()
+    }
+
+    /// Returns the id of the current client's pane, the one tmuxcc runs in
+    /// or, under `run-shell`, the one the key was pressed in
+    pub fn current_pane(&self) -> Option<String> {
+        if let Some(pane) = std::env::var("TMUX_PANE").ok().filter(|p| !p.is_empty()) {
+            return Some(pane);
+        }
+        let output = self
+            .tmux(["display-message", "-p", "#{pane_id}"])
+            .output()
+            .ok()?;
+        let pane = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !pane.is_empty()).then_some(pane)
+    }
+
+    /// Switches the current client to the session, window and pane of `target`
+    pub fn switch_client(&self, target: &str) -> Result<()> {
+        let output = self
+            .tmux(["switch-client", "-t", target])
+            .output()
+            .context("Failed to execute tmux switch-client")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux switch-client failed for {}: {}", target, stderr);
+        }
+
+        Ok(())
+    }
+
+    /// Attaches the terminal to the session of `target`, blocking until detach
+    ///
+    /// stdin and stdout are inherited, so the caller must release the
+    /// terminal first. `$TMUX` is cleared so a pane on another server can be
+    /// attached from inside tmux as a nested client.
+    pub fn attach_session(&self, target: &str) -> Result<()> {
+        let status = self
+            .server
+            .interactive_command(["attach-session", "-t", target])
+            .env_remove("TMUX")
+            .status()
+            .context("Failed to execute tmux attach-session")?;
+
+        if !status.success() {
+            anyhow::bail!("tmux attach-session failed for {}", target);
+        }
+
+        Ok(())
+    }
+
+    /// Binds `key` in the prefix table to `command`
+    pub fn bind_key(&self, key: &str, command: &[&str]) -> Result<()> {
+        let output = self
+            .tmux(["bind-key", key].iter().chain(command))
+            .output()
+            .context("Failed to execute tmux bind-key")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux bind-key failed for {}: {}", key, stderr);
+        }
+
+        Ok(())
+    }
+
+    /// Removes the binding of `key` from the prefix table
+    pub fn unbind_key(&self, key: &str) -> Result<()> {
+        let output = self
+            .tmux(["unbind-key", key])
+            .output()
+            .context("Failed to execute tmux unbind-key")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux unbind-key failed for {}: {}", key, stderr);
+        }
+
+        Ok(())
+    }
+
     /// Focuses on a pane by selecting its window and pane
     pub fn focus_pane(&self, target: &str) -> Result<()> {
         self.select_window(target)?;
@@ -165,5 +711,24 @@ mod tests {
 
         let custom_client = TmuxClient::with_capture_lines(200);
         assert_eq!(custom_client.capture_lines, 200);
+
+        let server = TmuxServer::Name("agents".to_string());
+        let remote = custom_client.for_server(&server);
+        assert_eq!(remote.capture_lines, 200);
+        assert_eq!(remote.server(), &server);
+    }
+
+    #[test]
+    fn test_escape_tmux_arg() {
+        assert_eq!(escape_tmux_arg("echo a; echo b"), "echo a; echo b");
+        assert_eq!(escape_tmux_arg("let x = 1;"), "let x = 1\\;");
+        assert_eq!(escape_tmux_arg("say \"hi\" 'there'"), "say \"hi\" 'there'");
+        assert_eq!(escape_tmux_arg(";"), "\\;");
+    }
+
+    #[test]
+    fn test_send_mode_for_text() {
+        assert_eq!(SendMode::for_text("fix the bug"), SendMode::Literal);
+        assert_eq!(SendMode::for_text("fn main() {\n}\n"), SendMode::Paste);
     }
 }
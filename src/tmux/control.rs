@@ -0,0 +1,208 @@
+use std::process::Stdio;
+
+use anyhow::{Context, Result};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use super::client::TmuxClient;
+
+/// Notification received from a tmux control mode client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlEvent {
+    /// A pane produced output (`%output %<pane> <data>`)
+    Output { pane_id: String, data: String },
+    /// A window was created (`%window-add @<window>`)
+    WindowAdd { window_id: String },
+    /// A window was closed (`%window-close` / `%unlinked-window-close`)
+    WindowClose { window_id: String },
+    /// Panes were split, closed or resized (`%layout-change @<window> ...`)
+    LayoutChange { window_id: String },
+    /// A session was created, renamed or destroyed
+    SessionsChanged,
+    /// The control client exited (`%exit [reason]`)
+    Exit { reason: Option<String> },
+}
+
+impl ControlEvent {
+    /// Returns true if panes may have appeared or disappeared
+    pub fn is_structural(&self) -> bool {
+        !matches!(self, ControlEvent::Output { .. })
+    }
+}
+
+/// Parses one line of control mode output into an event
+///
+/// Command replies (`%begin` ... `%end`) and unknown notifications are ignored.
+pub fn parse_notification(line: &str) -> Option<ControlEvent> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let (name, rest) = line.split_once(' ').unwrap_or((line, ""));
+    let first_arg = || rest.split(' ').next().unwrap_or("").to_string();
+
+    match name {
+        "%output" => {
+            let (pane_id, data) = rest.split_once(' ').unwrap_or((rest, ""));
+            Some(ControlEvent::Output {
+                pane_id: pane_id.to_string(),
+                data: unescape_output(data),
+            })
+        }
+        "%window-add" => Some(ControlEvent::WindowAdd {
+            window_id: first_arg(),
+        }),
+        "%window-close" | "%unlinked-window-close" => Some(ControlEvent::WindowClose {
+            window_id: first_arg(),
+        }),
+        "%layout-change" => Some(ControlEvent::LayoutChange {
+            window_id: first_arg(),
+        }),
+        "%sessions-changed" | "%session-renamed" | "%session-changed" => {
+            Some(ControlEvent::SessionsChanged)
+        }
+        "%exit" => Some(ControlEvent::Exit {
+            reason: (!rest.is_empty()).then(|| rest.to_string()),
+        }),
+        _ => None,
+    }
+}
+
+/// Decodes the octal escapes (`\ooo`) tmux uses for bytes below 32 and `\`
+pub fn unescape_output(data: &str) -> String {
+    let bytes = data.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 3 < bytes.len() {
+            let digits = &bytes[i + 1..i + 4];
+            if digits.iter().all(|b| (b'0'..=b'7').contains(b)) {
+                let value = digits
+                    .iter()
+                    .fold(0u32, |acc, b| acc * 8 + (b - b'0') as u32);
+                out.push(value as u8);
+                i += 4;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Long-lived `tmux -C` connection forwarding notifications as [`ControlEvent`]s
+///
+/// The client attaches read-only to a session that already has a client, so
+/// it doesn't change which sessions count as attached. The tmux process is
+/// killed when the client is dropped.
+pub struct ControlClient {
+    child: Child,
+}
+
+impl ControlClient {
+    /// Attaches to `session` and forwards its notifications to `tx`
+    pub fn spawn(session: &str, tx: mpsc::Sender<ControlEvent>) -> Result<Self> {
+        let mut child = Command::new("tmux")
+            .args(["-C", "attach-session", "-r", "-t", session])
+            // Control mode exits when stdin closes, so keep it piped and open
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to start tmux control mode client")?;
+
+        let stdout = child
+            .stdout
+            .take()
+            .context("tmux control mode client has no stdout")?;
+
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            loop {
+                match lines.next_line().await {
+                    Ok(Some(line)) => {
+                        let Some(event) = parse_notification(&line) else {
+                            continue;
+                        };
+                        let exit = matches!(event, ControlEvent::Exit { .. });
+                        if tx.send(event).await.is_err() || exit {
+                            break;
+                        }
+                    }
+                    Ok(None) => {
+                        let _ = tx.send(ControlEvent::Exit { reason: None }).await;
+                        break;
+                    }
+                    Err(e) => {
+                        warn!("tmux control mode read error: {}", e);
+                        let _ = tx
+                            .send(ControlEvent::Exit {
+                                reason: Some(e.to_string()),
+                            })
+                            .await;
+                        break;
+                    }
+                }
+            }
+            debug!("tmux control mode reader stopped");
+        });
+
+        Ok(Self { child })
+    }
+
+    /// Attaches to the first session that already has a client attached
+    pub fn spawn_for_attached_session(tx: mpsc::Sender<ControlEvent>) -> Result<Self> {
+        let session = TmuxClient::new()
+            .list_tree()?
+            .attached_session()
+            .map(|s| s.name.clone())
+            .context("No attached tmux session for control mode")?;
+        Self::spawn(&session, tx)
+    }
+
+    /// Returns true while the tmux process is running
+    pub fn is_running(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_notification() {
+        assert_eq!(
+            parse_notification("%output %3 hello\\015\\012"),
+            Some(ControlEvent::Output {
+                pane_id: "%3".to_string(),
+                data: "hello\r\n".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_notification("%window-add @7"),
+            Some(ControlEvent::WindowAdd {
+                window_id: "@7".to_string()
+            })
+        );
+        assert_eq!(
+            parse_notification("%layout-change @1 b25d,80x24,0,0,0 b25d,80x24,0,0,0 *"),
+            Some(ControlEvent::LayoutChange {
+                window_id: "@1".to_string()
+            })
+        );
+        assert_eq!(
+            parse_notification("%exit"),
+            Some(ControlEvent::Exit { reason: None })
+        );
+        assert_eq!(parse_notification("%begin 1700000000 12 0"), None);
+    }
+
+    #[test]
+    fn test_unescape_output() {
+        assert_eq!(unescape_output("a\\134b"), "a\\b");
+        assert_eq!(unescape_output("tab\\011end"), "tab\tend");
+        assert_eq!(unescape_output("trailing\\01"), "trailing\\01");
+    }
+}
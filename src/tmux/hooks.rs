@@ -0,0 +1,253 @@
+use std::io::Write;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::net::UnixListener;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use super::server::{shell_quote, TmuxServer};
+use crate::daemon::{create_socket_dir, socket_dir};
+
+/// tmux hooks that fire when panes, windows or sessions come and go
+pub const HOOK_EVENTS: &[&str] = &[
+    "session-created",
+    "session-closed",
+    "after-new-window",
+    "after-split-window",
+    "after-kill-pane",
+    "pane-exited",
+    "pane-died",
+    "window-unlinked",
+];
+
+/// Marks hook commands installed by tmuxcc, followed by the socket path
+const HOOK_MARKER: &str = "notify-event --socket ";
+
+/// A tmux hook invocation forwarded by `tmuxcc notify-event`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookEvent {
+    /// Hook name (e.g., "pane-exited")
+    pub name: String,
+    /// Extra arguments, such as the pane id
+    pub args: Vec<String>,
+}
+
+impl HookEvent {
+    /// Parses a line sent over the notify socket
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace();
+        let name = words.next()?.to_string();
+        Some(Self {
+            name,
+            args: words.map(|w| w.to_string()).collect(),
+        })
+    }
+
+    /// Formats the event as a line for the notify socket
+    pub fn to_line(&self) -> String {
+        let mut line = self.name.clone();
+        for arg in &self.args {
+            line.push(' ');
+            line.push_str(arg);
+        }
+        line.push('\n');
+        line
+    }
+}
+
+/// Sends `event` to the tmuxcc instance listening on `socket`
+pub fn send_hook_event(socket: &Path, event: &HookEvent) -> Result<()> {
+    let mut stream = UnixStream::connect(socket)
+        .with_context(|| format!("Failed to connect to {}", socket.display()))?;
+    stream.write_all(event.to_line().as_bytes())?;
+    Ok(())
+}
+
+/// Builds the hook command that reports `event` to the socket
+fn hook_command(exe: &Path, socket: &Path, event: &str) -> String {
+    format!(
+        "run-shell -b \"{} {}{} {} '#{{pane_id}}'\"",
+        shell_quote(&exe.to_string_lossy()),
+        HOOK_MARKER,
+        shell_quote(&socket.to_string_lossy()),
+        event
+    )
+}
+
+/// Parses `show-hooks -g <name>` output into the indexed names and socket
+/// paths of hooks installed by tmuxcc (e.g., `("pane-exited[1]", "/run/user/1000/tmuxcc-1000/hooks-1.sock")`)
+fn parse_installed_hooks(output: &str) -> Vec<(String, PathBuf)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, command) = line.split_once(' ')?;
+            let rest = &command[command.find(HOOK_MARKER)? + HOOK_MARKER.len()..];
+            let path = rest.strip_prefix('\'')?.split('\'').next()?;
+            Some((name.to_string(), PathBuf::from(path)))
+        })
+        .collect()
+}
+
+/// Lists the tmuxcc hooks currently set for `event` on `server`
+fn installed_hooks(server: &TmuxServer, event: &str) -> Vec<(String, PathBuf)> {
+    server
+        .command(["show-hooks", "-g", event])
+        .output()
+        .map(|o| parse_installed_hooks(&String::from_utf8_lossy(&o.stdout)))
+        .unwrap_or_default()
+}
+
+/// Unsets the array entry `indexed_name` (e.g., "pane-exited[1]") on `server`
+fn unset_hook(server: &TmuxServer, indexed_name: &str) {
+    let _ = server.command(["set-hook", "-gu", indexed_name]).output();
+}
+
+/// Listens for `tmuxcc notify-event` calls made by tmux hooks
+///
+/// Starting the listener appends hooks for [`HOOK_EVENTS`] to the global
+/// hook arrays of each local tmux server, leaving the user's own hooks in
+/// place, and removes hooks left behind by instances that are no longer
+/// running. Remote servers get no hooks: they couldn't reach the socket.
+/// Dropping it removes its hooks and the socket.
+pub struct HookListener {
+    socket: PathBuf,
+    /// Servers the hooks were installed on
+    servers: Vec<TmuxServer>,
+}
+
+impl HookListener {
+    /// Returns the notify socket path for this process
+    pub fn default_socket() -> PathBuf {
+        socket_dir().join(format!("hooks-{}.sock", std::process::id()))
+    }
+
+    /// Binds the socket, installs the hooks on `servers` and forwards events
+    /// to `tx`
+    pub fn start(servers: &[TmuxServer], tx: mpsc::Sender<HookEvent>) -> Result<Self> {
+        let socket = Self::default_socket();
+        if let Some(dir) = socket.parent() {
+            create_socket_dir(dir)?;
+        }
+        let _ = std::fs::remove_file(&socket);
+        let listener = UnixListener::bind(&socket)
+            .with_context(|| format!("Failed to bind {}", socket.display()))?;
+        let exe = std::env::current_exe().context("Failed to locate the tmuxcc binary")?;
+
+        let servers: Vec<TmuxServer> = servers
+            .iter()
+            .filter(|server| server.host().is_none())
+            .cloned()
+            .collect();
+        for server in &servers {
+            for event in HOOK_EVENTS {
+                for (name, path) in installed_hooks(server, event).into_iter().rev() {
+                    // Nobody listens on it: the tmuxcc that installed it
+                    // is gone, even if it left its socket behind
+                    if UnixStream::connect(&path).is_err() {
+                        unset_hook(server, &name);
+                    }
+                }
+                let output = server
+                    .command([
+                        "set-hook",
+                        "-ga",
+                        event,
+                        &hook_command(&exe, &socket, event),
+                    ])
+                    .output()
+                    .context("Failed to execute tmux set-hook")?;
+                if !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    warn!(
+                        "tmux set-hook {} failed on {}: {}",
+                        event,
+                        server.label(),
+                        stderr.trim()
+                    );
+                }
+            }
+        }
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    let mut lines = BufReader::new(stream).lines();
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        if let Some(event) = HookEvent::parse(&line) {
+                            if tx.send(event).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                });
+            }
+            debug!("tmux hook listener stopped");
+        });
+
+        Ok(Self { socket, servers })
+    }
+}
+
+impl Drop for HookListener {
+    fn drop(&mut self) {
+        for server in &self.servers {
+            for event in HOOK_EVENTS {
+                for (name, path) in installed_hooks(server, event).into_iter().rev() {
+                    if path == self.socket {
+                        unset_hook(server, &name);
+                    }
+                }
+            }
+        }
+        let _ = std::fs::remove_file(&self.socket);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_event_roundtrip() {
+        let event = HookEvent {
+            name: "pane-exited".to_string(),
+            args: vec!["%3".to_string()],
+        };
+        assert_eq!(event.to_line(), "pane-exited %3\n");
+        assert_eq!(HookEvent::parse(&event.to_line()), Some(event));
+        assert_eq!(HookEvent::parse("  "), None);
+    }
+
+    #[test]
+    fn test_hook_command() {
+        let command = hook_command(
+            Path::new("/usr/bin/tmuxcc"),
+            Path::new("/run/user/1000/tmuxcc-1000/hooks-42.sock"),
+            "pane-exited",
+        );
+        assert_eq!(
+            command,
+            "run-shell -b \"'/usr/bin/tmuxcc' notify-event --socket '/run/user/1000/tmuxcc-1000/hooks-42.sock' pane-exited '#{pane_id}'\""
+        );
+    }
+
+    #[test]
+    fn test_parse_installed_hooks() {
+        let output = "\
+pane-exited[0] run-shell -b \"echo user hook\"
+pane-exited[1] run-shell -b \"'/usr/bin/tmuxcc' notify-event --socket '/tmp/tmuxcc 7.sock' pane-exited '#{pane_id}'\"
+";
+        assert_eq!(
+            parse_installed_hooks(output),
+            vec![(
+                "pane-exited[1]".to_string(),
+                PathBuf::from("/tmp/tmuxcc 7.sock")
+            )]
+        );
+        assert!(parse_installed_hooks("after-split-window\n").is_empty());
+    }
+}
@@ -1,5 +1,15 @@
+mod ansi;
 mod client;
+mod control;
+mod hooks;
 mod pane;
+mod server;
+mod tree;
 
-pub use client::TmuxClient;
-pub use pane::{refresh_process_cache, PaneInfo};
+pub use ansi::{ansi_segments, strip_ansi, AnsiSegment};
+pub use client::{SendMode, TmuxClient};
+pub use control::{parse_notification, unescape_output, ControlClient, ControlEvent};
+pub use hooks::{send_hook_event, HookEvent, HookListener, HOOK_EVENTS};
+pub use pane::{refresh_process_cache, LaunchCommand, PaneInfo};
+pub use server::TmuxServer;
+pub use tree::{TmuxPane, TmuxSession, TmuxTree, TmuxWindow, IGNORE_OPTION, LABEL_OPTION};
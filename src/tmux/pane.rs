@@ -1,4 +1,5 @@
 use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
 use std::process::Command;
@@ -96,6 +97,51 @@ impl ProcessTreeCache {
         }
     }
 
+    fn collect_descendant_pids(&self, pid: u32, pids: &mut Vec<u32>, depth: u32, max_depth: u32) {
+        if depth >= max_depth {
+            return;
+        }
+        for (&child_pid, info) in &self.processes {
+            if info.parent_pid == Some(pid) {
+                pids.push(child_pid);
+                self.collect_descendant_pids(child_pid, pids, depth + 1, max_depth);
+            }
+        }
+    }
+
+    /// Finds the first known agent binary in the process tree rooted at `pid`
+    ///
+    /// The tree is searched breadth-first so the process closest to the pane
+    /// wins, e.g. `claude` over a `codex` it happens to run as a tool.
+    fn find_agent_binary(&self, pid: u32, max_depth: u32) -> Option<&'static str> {
+        let mut level = vec![pid];
+        for _ in 0..=max_depth {
+            let mut next = Vec::new();
+            for &pid in &level {
+                if let Some(name) = self
+                    .processes
+                    .get(&pid)
+                    .and_then(|info| agent_binary(&info.command))
+                {
+                    return Some(name);
+                }
+                next.extend(
+                    self.processes
+                        .iter()
+                        .filter(|(_, info)| info.parent_pid == Some(pid))
+                        .map(|(&child, _)| child),
+                );
+            }
+            if next.is_empty() {
+                break;
+            }
+            // Order siblings by pid so the result doesn't depend on map order
+            next.sort_unstable();
+            level = next;
+        }
+        None
+    }
+
     fn get_cmdline(&self, pid: u32) -> Option<String> {
         self.processes.get(&pid).map(|info| info.command.clone())
     }
@@ -115,6 +161,93 @@ pub fn refresh_process_cache() {
     }
 }
 
+/// Reads the environment of a process from `/proc/<pid>/environ`
+///
+/// Only available on Linux; returns an empty list elsewhere or when the
+/// process belongs to another user.
+fn read_process_environ(pid: u32) -> Vec<(String, String)> {
+    let Ok(data) = std::fs::read(format!("/proc/{}/environ", pid)) else {
+        return Vec::new();
+    };
+    parse_environ(&data)
+}
+
+/// Parses NUL-separated `NAME=value` entries
+fn parse_environ(data: &[u8]) -> Vec<(String, String)> {
+    data.split(|&b| b == 0)
+        .filter_map(|entry| {
+            let entry = String::from_utf8_lossy(entry);
+            let (name, value) = entry.split_once('=')?;
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Shells that agents are commonly started from
+const SHELLS: &[&str] = &["bash", "zsh", "fish", "sh", "dash", "ksh", "tcsh", "nu"];
+
+/// Executable names of the agents tmuxcc knows
+const AGENT_BINARIES: &[&str] = &["claude", "aider", "codex", "goose", "opencode", "gemini"];
+
+/// Programs that run an agent given as one of their arguments
+const LAUNCHERS: &[&str] = &[
+    "node", "npx", "npm", "pnpm", "pnpx", "yarn", "bun", "bunx", "deno", "python", "python3", "uv",
+    "uvx", "pipx", "env", "exec",
+];
+
+/// How deep below the pane's process to look for an agent
+const AGENT_SEARCH_DEPTH: u32 = 6;
+
+/// Returns the known agent a process command line runs, if any
+///
+/// Matches the program itself (`/usr/local/bin/claude`, `aider-chat`) and,
+/// for interpreters and package runners, the script or package it runs
+/// (`node .../@anthropic-ai/claude-code/cli.js`, `npx @openai/codex`,
+/// `python -m aider`).
+fn agent_binary(command: &str) -> Option<&'static str> {
+    let mut words = command.split_whitespace();
+    let program = words.next()?;
+    let base = program
+        .rsplit('/')
+        .next()
+        .unwrap_or(program)
+        .trim_start_matches('-');
+    if let Some(name) = agent_name(base) {
+        return Some(name);
+    }
+
+    let launcher = base.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    if !LAUNCHERS.contains(&base) && !LAUNCHERS.contains(&launcher) {
+        return None;
+    }
+    words
+        .filter(|w| !w.starts_with('-') && !w.contains('='))
+        .take(3)
+        .flat_map(|w| w.split('/'))
+        .find_map(agent_name)
+}
+
+/// Matches one path component against the known agent names
+fn agent_name(component: &str) -> Option<&'static str> {
+    let lower = component.to_lowercase();
+    let stem = lower.split('@').next().unwrap_or(&lower);
+    AGENT_BINARIES.iter().copied().find(|name| {
+        stem == *name
+            || stem
+                .strip_prefix(name)
+                .is_some_and(|rest| rest.starts_with(['-', '.', '_']))
+    })
+}
+
+/// How the process running in a pane was started, used to restart it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaunchCommand {
+    /// Full command line of the agent process
+    pub command_line: String,
+    /// Whether the agent runs as a child of the pane's shell
+    pub in_shell: bool,
+}
+
 /// Represents a tmux pane with its identifying information
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PaneInfo {
@@ -138,6 +271,8 @@ pub struct PaneInfo {
     pub cmdline: String,
     /// Child process commands (for detecting running agents)
     pub child_commands: Vec<String>,
+    /// Known agent found in the pane's process tree (e.g. "claude")
+    pub agent_binary: Option<String>,
 }
 
 impl PaneInfo {
@@ -172,6 +307,9 @@ impl PaneInfo {
         let cache = get_process_cache().lock();
         let cmdline = cache.get_cmdline(pid).unwrap_or_default();
         let child_commands = cache.get_child_commands(pid, 2); // Reduced depth to 2
+        let agent_binary = cache
+            .find_agent_binary(pid, AGENT_SEARCH_DEPTH)
+            .map(str::to_string);
 
         Some(Self {
             session: session.to_string(),
@@ -184,16 +322,60 @@ impl PaneInfo {
             pid,
             cmdline,
             child_commands,
+            agent_binary,
+        })
+    }
+
+    /// Returns the values of `names` in the environment of the pane's processes
+    ///
+    /// The pane's shell is read first and its descendants after, so a variable
+    /// exported by a wrapper script that launched the agent wins over the shell's.
+    pub fn process_env(&self, names: &[&str]) -> Vec<(String, String)> {
+        let mut pids = vec![self.pid];
+        get_process_cache()
+            .lock()
+            .collect_descendant_pids(self.pid, &mut pids, 0, 2);
+
+        let mut found: Vec<(String, String)> = Vec::new();
+        for pid in pids {
+            for (name, value) in read_process_environ(pid) {
+                if !names.contains(&name.as_str()) {
+                    continue;
+                }
+                found.retain(|(n, _)| *n != name);
+                found.push((name, value));
+            }
+        }
+        found
+    }
+
+    /// Returns how the agent in this pane was started
+    ///
+    /// When the pane's own process is a shell, the agent is its first child;
+    /// otherwise the pane was created with the agent as its command.
+    pub fn launch_command(&self) -> Option<LaunchCommand> {
+        let program = self.cmdline.split_whitespace().next()?;
+        let base = program.rsplit('/').next().unwrap_or(program);
+        let in_shell = SHELLS.contains(&base.trim_start_matches('-'));
+        let command_line = if in_shell {
+            self.child_commands.first()?.clone()
+        } else {
+            self.cmdline.clone()
+        };
+        Some(LaunchCommand {
+            command_line,
+            in_shell,
         })
     }
 
     /// Returns all detection strings (command, title, cmdline, child commands) for agent matching
     pub fn detection_strings(&self) -> Vec<&str> {
-        let mut strings = vec![
+        let mut strings: Vec<&str> = self.agent_binary.iter().map(String::as_str).collect();
+        strings.extend([
             self.command.as_str(),
             self.title.as_str(),
             self.cmdline.as_str(),
-        ];
+        ]);
 
         // Add child command strings
         for cmd in &self.child_commands {
@@ -227,10 +409,105 @@ mod tests {
             pid: 99999,
             cmdline: "".to_string(),
             child_commands: Vec::new(),
+            agent_binary: None,
         };
         assert_eq!(pane.target(), "dev:2.3");
     }
 
+    #[test]
+    fn test_parse_environ() {
+        let env = parse_environ(b"HOME=/home/user\0GIT_DIR=/srv/repo.git\0EMPTY=\0junk\0");
+        assert_eq!(
+            env,
+            vec![
+                ("HOME".to_string(), "/home/user".to_string()),
+                ("GIT_DIR".to_string(), "/srv/repo.git".to_string()),
+                ("EMPTY".to_string(), String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_launch_command() {
+        let mut pane = PaneInfo {
+            session: "main".to_string(),
+            window: 0,
+            window_name: "code".to_string(),
+            pane: 0,
+            command: "node".to_string(),
+            title: "".to_string(),
+            path: "/home/user".to_string(),
+            pid: 1234,
+            cmdline: "-zsh".to_string(),
+            child_commands: vec!["claude --resume".to_string(), "claude".to_string()],
+            agent_binary: Some("claude".to_string()),
+        };
+        assert_eq!(
+            pane.launch_command(),
+            Some(LaunchCommand {
+                command_line: "claude --resume".to_string(),
+                in_shell: true,
+            })
+        );
+
+        pane.cmdline = "/usr/bin/codex --full-auto".to_string();
+        pane.child_commands.clear();
+        assert_eq!(
+            pane.launch_command(),
+            Some(LaunchCommand {
+                command_line: "/usr/bin/codex --full-auto".to_string(),
+                in_shell: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_agent_binary() {
+        assert_eq!(
+            agent_binary("/usr/local/bin/claude --resume"),
+            Some("claude")
+        );
+        assert_eq!(agent_binary("aider-chat --model sonnet"), Some("aider"));
+        assert_eq!(
+            agent_binary("node /usr/lib/node_modules/@anthropic-ai/claude-code/cli.js"),
+            Some("claude")
+        );
+        assert_eq!(agent_binary("npx -y @openai/codex@latest"), Some("codex"));
+        assert_eq!(agent_binary("python3.12 -m aider"), Some("aider"));
+        assert_eq!(agent_binary("goose session"), Some("goose"));
+        assert_eq!(agent_binary("-zsh"), None);
+        assert_eq!(agent_binary("vim claude.md"), None);
+        assert_eq!(agent_binary("node server.js"), None);
+    }
+
+    #[test]
+    fn test_find_agent_binary() {
+        let mut cache = ProcessTreeCache::new();
+        for (pid, ppid, command) in [
+            (10, 1, "-zsh"),
+            (11, 10, "bash ./run-agent.sh"),
+            (12, 11, "npm exec @anthropic-ai/claude-code"),
+            (13, 12, "node /tmp/x/cli.js"),
+            (14, 13, "codex exec"),
+            (20, 1, "-zsh"),
+            (21, 20, "vim"),
+        ] {
+            cache.processes.insert(
+                pid,
+                ProcessInfo {
+                    command: command.to_string(),
+                    parent_pid: Some(ppid),
+                },
+            );
+        }
+        assert_eq!(
+            cache.find_agent_binary(10, AGENT_SEARCH_DEPTH),
+            Some("claude")
+        );
+        assert_eq!(cache.find_agent_binary(20, AGENT_SEARCH_DEPTH), None);
+        assert_eq!(cache.find_agent_binary(10, 1), None);
+    }
+
     #[test]
     fn test_parse_invalid() {
         assert!(PaneInfo::parse("invalid").is_none());
@@ -250,6 +527,7 @@ mod tests {
             pid: 1234,
             cmdline: "-zsh".to_string(),
             child_commands: vec!["claude -c".to_string(), "claude".to_string()],
+            agent_binary: Some("claude".to_string()),
         };
         let strings = pane.detection_strings();
         assert!(strings.contains(&"zsh"));
@@ -0,0 +1,245 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+use tracing::warn;
+
+use crate::daemon::{create_socket_dir, socket_dir};
+
+/// Quotes `s` for `sh`
+pub(crate) fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// A tmux server, selected by socket the same way `tmux -L`/`-S` do, or
+/// the default server of a remote host reached over SSH
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TmuxServer {
+    /// The server tmux uses without socket options
+    #[default]
+    Default,
+    /// A named socket in tmux's socket directory (`-L name`)
+    Name(String),
+    /// A socket at an explicit path (`-S path`)
+    Path(PathBuf),
+    /// The default server on an SSH host (e.g., "dev1" from `~/.ssh/config`)
+    Remote(String),
+}
+
+impl TmuxServer {
+    /// Parses a `sockets` config entry
+    ///
+    /// Entries containing `/` are socket paths, "default" or an empty string
+    /// is the default server and anything else is a socket name.
+    pub fn parse(spec: &str) -> Self {
+        let spec = spec.trim();
+        if spec.is_empty() || spec == "default" {
+            TmuxServer::Default
+        } else if spec.contains('/') {
+            match (spec.strip_prefix("~/"), dirs::home_dir()) {
+                (Some(rest), Some(home)) => TmuxServer::Path(home.join(rest)),
+                _ => TmuxServer::Path(PathBuf::from(spec)),
+            }
+        } else {
+            TmuxServer::Name(spec.to_string())
+        }
+    }
+
+    /// Returns true for the server tmux uses without socket options
+    pub fn is_default(&self) -> bool {
+        *self == TmuxServer::Default
+    }
+
+    /// Returns the SSH host for remote servers
+    pub fn host(&self) -> Option<&str> {
+        match self {
+            TmuxServer::Remote(host) => Some(host),
+            _ => None,
+        }
+    }
+
+    /// Short name shown next to panes of this server (e.g., "agents", or
+    /// "@dev1" for a remote host so it never reads as a socket name)
+    pub fn label(&self) -> String {
+        match self {
+            TmuxServer::Default => "default".to_string(),
+            TmuxServer::Name(name) => name.clone(),
+            TmuxServer::Remote(host) => format!("@{}", host),
+            TmuxServer::Path(path) => path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.display().to_string()),
+        }
+    }
+
+    /// Prefixes `target` with the server label unless this is the default
+    /// server, so panes of different servers never share a key
+    pub fn qualify(&self, target: &str) -> String {
+        if self.is_default() {
+            target.to_string()
+        } else {
+            format!("{}/{}", self.label(), target)
+        }
+    }
+
+    /// Returns a `tmux` command with `args` talking to this server
+    ///
+    /// Remote servers run tmux through `ssh` with the arguments quoted for
+    /// the remote shell. Connections are multiplexed so polling doesn't pay
+    /// for a new SSH handshake on every command.
+    pub fn command<I, S>(&self, args: I) -> Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.build_command(args, false)
+    }
+
+    /// Like [`TmuxServer::command`], but allocates a terminal on remote hosts
+    /// (for `attach-session`)
+    pub fn interactive_command<I, S>(&self, args: I) -> Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.build_command(args, true)
+    }
+
+    fn build_command<I, S>(&self, args: I, interactive: bool) -> Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        if let TmuxServer::Remote(host) = self {
+            let remote: Vec<String> = std::iter::once("tmux".to_string())
+                .chain(args.into_iter().map(|a| shell_quote(a.as_ref())))
+                .collect();
+            let mut command = Command::new("ssh");
+            command
+                .args(ssh_options(interactive))
+                .arg(host)
+                .arg(remote.join(" "));
+            return command;
+        }
+
+        let mut command = Command::new("tmux");
+        match self {
+            TmuxServer::Name(name) => {
+                command.args(["-L", name]);
+            }
+            TmuxServer::Path(path) => {
+                command.arg("-S").arg(path);
+            }
+            TmuxServer::Default | TmuxServer::Remote(_) => {}
+        }
+        for arg in args {
+            command.arg(arg.as_ref());
+        }
+        command
+    }
+}
+
+/// Returns the directory of shared ssh connections, the user's own socket
+/// directory, `None` if it can't be created
+fn control_dir() -> Option<&'static Path> {
+    static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let dir = socket_dir();
+        match create_socket_dir(&dir) {
+            Ok(()) => Some(dir),
+            Err(e) => {
+                warn!("Not sharing ssh connections: {:#}", e);
+                None
+            }
+        }
+    })
+    .as_deref()
+}
+
+/// Options for the `ssh` commands of remote servers
+fn ssh_options(interactive: bool) -> Vec<String> {
+    // Never stop on a password prompt behind the TUI
+    let mut options = vec!["-o".to_string(), "BatchMode=yes".to_string()];
+    if let Some(dir) = control_dir() {
+        options.extend([
+            "-o".to_string(),
+            "ControlMaster=auto".to_string(),
+            "-o".to_string(),
+            format!("ControlPath={}", dir.join("ssh-%C").display()),
+            "-o".to_string(),
+            "ControlPersist=60".to_string(),
+        ]);
+    }
+    options.push(if interactive { "-t" } else { "-T" }.to_string());
+    options
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("a b"), "'a b'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn test_parse_server() {
+        assert_eq!(TmuxServer::parse("default"), TmuxServer::Default);
+        assert_eq!(TmuxServer::parse(""), TmuxServer::Default);
+        assert_eq!(
+            TmuxServer::parse("agents"),
+            TmuxServer::Name("agents".to_string())
+        );
+        assert_eq!(
+            TmuxServer::parse("/tmp/tmux-1000/work"),
+            TmuxServer::Path(PathBuf::from("/tmp/tmux-1000/work"))
+        );
+    }
+
+    #[test]
+    fn test_command_args() {
+        let args = |command: &Command| -> Vec<String> {
+            command
+                .get_args()
+                .map(|a| a.to_string_lossy().to_string())
+                .collect()
+        };
+
+        let local =
+            TmuxServer::Name("agents".to_string()).command(["list-panes", "-F", "#{pane_id} x"]);
+        assert_eq!(local.get_program(), "tmux");
+        assert_eq!(
+            args(&local),
+            ["-L", "agents", "list-panes", "-F", "#{pane_id} x"]
+        );
+
+        let remote = TmuxServer::Remote("dev1".to_string()).command(["send-keys", "-l", "it's"]);
+        assert_eq!(remote.get_program(), "ssh");
+        let remote_args = args(&remote);
+        assert_eq!(remote_args[remote_args.len() - 2], "dev1");
+        assert_eq!(
+            remote_args.last().unwrap(),
+            r"tmux 'send-keys' '-l' 'it'\''s'"
+        );
+    }
+
+    #[test]
+    fn test_qualify() {
+        assert_eq!(TmuxServer::Default.qualify("main:0.1"), "main:0.1");
+        assert_eq!(
+            TmuxServer::parse("agents").qualify("main:0.1"),
+            "agents/main:0.1"
+        );
+        assert_eq!(
+            TmuxServer::parse("/tmp/tmux-1000/work").qualify("main:0.1"),
+            "work/main:0.1"
+        );
+        // A host never shares keys with a socket of the same name
+        assert_eq!(
+            TmuxServer::Remote("agents".to_string()).qualify("main:0.1"),
+            "@agents/main:0.1"
+        );
+    }
+}
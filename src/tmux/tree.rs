@@ -0,0 +1,291 @@
+use super::pane::PaneInfo;
+use super::server::TmuxServer;
+
+/// tmux option that, set to anything but "" or "0" on a pane, window or
+/// session, keeps tmuxcc from capturing its panes
+pub const IGNORE_OPTION: &str = "@tmuxcc_ignore";
+
+/// tmux pane option labeling the pane's agent when tmuxcc has no label of its
+/// own for it, so scripts can label the panes they create
+pub const LABEL_OPTION: &str = "@tmuxcc_label";
+
+/// A pane in the tmux tree with the layout details tmux reports for it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TmuxPane {
+    /// Unique pane id (e.g., "%12"), stable across window moves
+    pub id: String,
+    /// Whether this is the active pane of its window
+    pub active: bool,
+    /// Pane width in cells
+    pub width: u16,
+    /// Pane height in cells
+    pub height: u16,
+    /// Whether the pane is zoomed to fill its window (`resize-pane -Z`)
+    pub zoomed: bool,
+    /// Whether the `@tmuxcc_ignore` option of the pane (or its window,
+    /// session or the global options) asks tmuxcc to leave it alone
+    pub ignored: bool,
+    /// The `@tmuxcc_label` option of the pane, if set
+    pub label: Option<String>,
+    /// Whether the pane's process exited and `remain-on-exit` kept the pane
+    pub dead: bool,
+    /// Target, title, cwd, command and process details
+    pub info: PaneInfo,
+    /// Server the pane lives on
+    pub server: TmuxServer,
+}
+
+/// A window and its panes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TmuxWindow {
+    /// Unique window id (e.g., "@3")
+    pub id: String,
+    /// Window index within the session
+    pub index: u32,
+    /// Window name
+    pub name: String,
+    /// Whether this is the current window of its session
+    pub active: bool,
+    /// Whether the window's active pane is zoomed
+    pub zoomed: bool,
+    /// Panes in index order
+    pub panes: Vec<TmuxPane>,
+}
+
+/// A session and its windows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TmuxSession {
+    /// Unique session id (e.g., "$0")
+    pub id: String,
+    /// Session name
+    pub name: String,
+    /// Number of attached clients
+    pub attached: u32,
+    /// Windows in index order
+    pub windows: Vec<TmuxWindow>,
+}
+
+impl TmuxSession {
+    /// Returns true if at least one client is attached
+    pub fn is_attached(&self) -> bool {
+        self.attached > 0
+    }
+}
+
+/// Snapshot of all tmux sessions → windows → panes
+///
+/// Built from a single `list-panes -a` call so every part of the app sees
+/// the same consistent view of the server.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TmuxTree {
+    /// Sessions in the order tmux listed them
+    pub sessions: Vec<TmuxSession>,
+}
+
+impl TmuxTree {
+    /// Format passed to `list-panes -a -F`
+    ///
+    /// The thirteen layout fields come first; the remaining six are the fields
+    /// [`PaneInfo::parse`] expects. Tabs separate fields so that spaces in
+    /// titles and paths survive.
+    pub const FORMAT: &'static str = "#{session_id}\t#{session_name}\t#{session_attached}\t#{window_id}\t#{window_active}\t#{window_zoomed_flag}\t#{pane_id}\t#{pane_active}\t#{pane_width}\t#{pane_height}\t#{@tmuxcc_ignore}\t#{@tmuxcc_label}\t#{pane_dead}\t#{session_name}:#{window_index}.#{pane_index}\t#{window_name}\t#{pane_current_command}\t#{pane_pid}\t#{pane_title}\t#{pane_current_path}";
+
+    /// Builds the tree from `list-panes -a -F FORMAT` output
+    ///
+    /// Lines that can't be parsed are skipped.
+    pub fn parse(output: &str) -> Self {
+        let mut tree = TmuxTree::default();
+
+        for line in output.lines() {
+            let fields: Vec<&str> = line.splitn(14, '\t').collect();
+            let [sid, name, attached, wid, win_active, zoomed, pane_id, active, width, height, ignore, label, dead, rest] =
+                fields[..]
+            else {
+                continue;
+            };
+            let Some(info) = PaneInfo::parse(rest) else {
+                continue;
+            };
+
+            let session = match tree.sessions.iter().position(|s| s.id == sid) {
+                Some(i) => &mut tree.sessions[i],
+                None => {
+                    tree.sessions.push(TmuxSession {
+                        id: sid.to_string(),
+                        name: name.to_string(),
+                        attached: attached.parse().unwrap_or(0),
+                        windows: Vec::new(),
+                    });
+                    tree.sessions.last_mut().unwrap()
+                }
+            };
+
+            let window = match session.windows.iter().position(|w| w.id == wid) {
+                Some(i) => &mut session.windows[i],
+                None => {
+                    session.windows.push(TmuxWindow {
+                        id: wid.to_string(),
+                        index: info.window,
+                        name: info.window_name.clone(),
+                        active: win_active == "1",
+                        zoomed: zoomed == "1",
+                        panes: Vec::new(),
+                    });
+                    session.windows.last_mut().unwrap()
+                }
+            };
+
+            window.panes.push(TmuxPane {
+                id: pane_id.to_string(),
+                active: active == "1",
+                width: width.parse().unwrap_or(0),
+                height: height.parse().unwrap_or(0),
+                zoomed: window.zoomed && active == "1",
+                ignored: !matches!(ignore, "" | "0"),
+                label: (!label.is_empty()).then(|| label.to_string()),
+                dead: dead == "1",
+                info,
+                server: TmuxServer::Default,
+            });
+        }
+
+        tree
+    }
+
+    /// Tags every pane with the server the tree was listed from
+    ///
+    /// Panes of remote servers lose their process details: those were looked
+    /// up by pid in the local process table and belong to unrelated processes.
+    pub fn set_server(&mut self, server: &TmuxServer) {
+        for pane in self
+            .sessions
+            .iter_mut()
+            .flat_map(|s| s.windows.iter_mut())
+            .flat_map(|w| w.panes.iter_mut())
+        {
+            pane.server = server.clone();
+            if server.host().is_some() {
+                pane.info.cmdline.clear();
+                pane.info.child_commands.clear();
+                pane.info.agent_binary = None;
+            }
+        }
+    }
+
+    /// Iterates over every pane in every session
+    pub fn panes(&self) -> impl Iterator<Item = &TmuxPane> {
+        self.sessions
+            .iter()
+            .flat_map(|s| s.windows.iter())
+            .flat_map(|w| w.panes.iter())
+    }
+
+    /// Iterates over the panes of sessions with an attached client
+    pub fn attached_panes(&self) -> impl Iterator<Item = &TmuxPane> {
+        self.sessions
+            .iter()
+            .filter(|s| s.is_attached())
+            .flat_map(|s| s.windows.iter())
+            .flat_map(|w| w.panes.iter())
+    }
+
+    /// Returns the first session with an attached client
+    pub fn attached_session(&self) -> Option<&TmuxSession> {
+        self.sessions.iter().find(|s| s.is_attached())
+    }
+
+    /// Looks up a session by name
+    pub fn session(&self, name: &str) -> Option<&TmuxSession> {
+        self.sessions.iter().find(|s| s.name == name)
+    }
+
+    /// Looks up a pane by its target (e.g., "main:0.1") or pane id (e.g., "%3")
+    pub fn find_pane(&self, target: &str) -> Option<&TmuxPane> {
+        self.panes()
+            .find(|p| p.id == target || p.info.target() == target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTPUT: &str = "\
+$0\tmain\t1\t@0\t1\t0\t%0\t1\t120\t40\t\tapi\t0\tmain:0.0\tcode\tclaude\t1001\tClaude Code\t/home/user/app
+$0\tmain\t1\t@0\t1\t0\t%1\t0\t120\t20\t1\t\t0\tmain:0.1\tcode\tzsh\t1002\t~\t/home/user/app
+$0\tmain\t1\t@2\t0\t1\t%4\t1\t240\t60\t0\t\t1\tmain:1.0\tlogs\ttail\t1003\tlogs\t/var/log
+$1\tbg\t0\t@5\t1\t0\t%7\t1\t80\t24\t\t\t0\tbg:0.0\tjob\tcodex\t1004\tcodex\t/srv/job
+garbage line
+";
+
+    #[test]
+    fn test_parse_tree() {
+        let tree = TmuxTree::parse(OUTPUT);
+        assert_eq!(tree.sessions.len(), 2);
+
+        let main = &tree.sessions[0];
+        assert_eq!(main.id, "$0");
+        assert!(main.is_attached());
+        assert_eq!(main.windows.len(), 2);
+        assert_eq!(main.windows[0].name, "code");
+        assert!(main.windows[0].active);
+        assert_eq!(main.windows[0].panes.len(), 2);
+        assert_eq!(main.windows[1].index, 1);
+
+        let pane = &main.windows[0].panes[1];
+        assert_eq!(pane.id, "%1");
+        assert!(!pane.active);
+        assert_eq!((pane.width, pane.height), (120, 20));
+        assert_eq!(pane.info.target(), "main:0.1");
+        assert_eq!(pane.info.path, "/home/user/app");
+        assert!(!pane.zoomed);
+        assert!(pane.ignored);
+        assert!(!main.windows[0].panes[0].ignored);
+        assert_eq!(main.windows[0].panes[0].label.as_deref(), Some("api"));
+        assert_eq!(pane.label, None);
+        assert!(!main.windows[1].panes[0].ignored);
+        assert!(main.windows[1].zoomed);
+        assert!(main.windows[1].panes[0].zoomed);
+        assert!(main.windows[1].panes[0].dead);
+        assert!(!pane.dead);
+
+        assert!(!tree.sessions[1].is_attached());
+    }
+
+    #[test]
+    fn test_set_server() {
+        let mut tree = TmuxTree::parse(OUTPUT);
+        assert!(tree.panes().all(|p| p.server.is_default()));
+
+        let server = TmuxServer::Name("agents".to_string());
+        tree.set_server(&server);
+        assert!(tree.panes().all(|p| p.server == server));
+
+        let remote = TmuxServer::Remote("dev1".to_string());
+        tree.set_server(&remote);
+        assert!(tree
+            .panes()
+            .all(|p| p.server == remote && p.info.cmdline.is_empty()));
+    }
+
+    #[test]
+    fn test_tree_queries() {
+        let tree = TmuxTree::parse(OUTPUT);
+        assert_eq!(tree.panes().count(), 4);
+        assert_eq!(tree.attached_panes().count(), 3);
+        assert_eq!(
+            tree.attached_session().map(|s| s.name.as_str()),
+            Some("main")
+        );
+        assert!(tree.session("bg").is_some());
+        assert_eq!(
+            tree.find_pane("%7").map(|p| p.info.command.as_str()),
+            Some("codex")
+        );
+        assert_eq!(
+            tree.find_pane("main:1.0").map(|p| p.id.as_str()),
+            Some("%4")
+        );
+        assert!(tree.find_pane("missing:0.0").is_none());
+    }
+}
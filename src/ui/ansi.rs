@@ -0,0 +1,113 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+use crate::tmux::{ansi_segments, AnsiSegment};
+
+/// Converts text captured with `capture-pane -e` into styled lines
+///
+/// tmux only emits attribute changes, so the style carries over from one
+/// line to the next until it is reset.
+pub fn styled_lines(text: &str) -> Vec<Line<'static>> {
+    let mut style = Style::default();
+    text.lines()
+        .map(|line| {
+            let mut spans = Vec::new();
+            for segment in ansi_segments(line) {
+                match segment {
+                    AnsiSegment::Text(text) => spans.push(Span::styled(text.to_string(), style)),
+                    AnsiSegment::Sgr(params) => style = apply_sgr(style, params),
+                }
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// Applies the SGR parameters `params` (e.g. "1;38;5;208") to `style`
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    let codes: Vec<u16> = params
+        .split([';', ':'])
+        .map(|p| p.parse().unwrap_or(0))
+        .collect();
+    let mut i = 0;
+    while i < codes.len() {
+        match codes[i] {
+            0 => style = Style::default(),
+            1 => style = style.add_modifier(Modifier::BOLD),
+            2 => style = style.add_modifier(Modifier::DIM),
+            3 => style = style.add_modifier(Modifier::ITALIC),
+            4 => style = style.add_modifier(Modifier::UNDERLINED),
+            5 | 6 => style = style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style = style.add_modifier(Modifier::REVERSED),
+            8 => style = style.add_modifier(Modifier::HIDDEN),
+            9 => style = style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style = style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style = style.remove_modifier(Modifier::ITALIC),
+            24 => style = style.remove_modifier(Modifier::UNDERLINED),
+            25 => style = style.remove_modifier(Modifier::SLOW_BLINK),
+            27 => style = style.remove_modifier(Modifier::REVERSED),
+            28 => style = style.remove_modifier(Modifier::HIDDEN),
+            29 => style = style.remove_modifier(Modifier::CROSSED_OUT),
+            code @ 30..=37 => style.fg = Some(Color::Indexed((code - 30) as u8)),
+            code @ 40..=47 => style.bg = Some(Color::Indexed((code - 40) as u8)),
+            code @ 90..=97 => style.fg = Some(Color::Indexed((code - 90 + 8) as u8)),
+            code @ 100..=107 => style.bg = Some(Color::Indexed((code - 100 + 8) as u8)),
+            39 => style.fg = None,
+            49 => style.bg = None,
+            code @ (38 | 48) => {
+                let (color, used) = extended_color(&codes[i + 1..]);
+                if code == 38 {
+                    style.fg = color.or(style.fg);
+                } else {
+                    style.bg = color.or(style.bg);
+                }
+                i += used;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    style
+}
+
+/// Parses the color after a 38/48 code, returning it and the parameters used
+fn extended_color(codes: &[u16]) -> (Option<Color>, usize) {
+    match codes {
+        [5, n, ..] => (Some(Color::Indexed(*n as u8)), 2),
+        [2, r, g, b, ..] => (Some(Color::Rgb(*r as u8, *g as u8, *b as u8)), 4),
+        _ => (None, codes.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_styled_lines() {
+        let lines = styled_lines("\x1b[1;32m+ added\n+ more\x1b[0m plain\nnext");
+        assert_eq!(lines.len(), 3);
+        let green = Style::default()
+            .fg(Color::Indexed(2))
+            .add_modifier(Modifier::BOLD);
+        assert_eq!(lines[0].spans[0], Span::styled("+ added", green));
+        // The style carries over to the next line until reset
+        assert_eq!(lines[1].spans[0], Span::styled("+ more", green));
+        assert_eq!(lines[1].spans[1], Span::raw(" plain"));
+        assert_eq!(lines[2].spans[0], Span::raw("next"));
+    }
+
+    #[test]
+    fn test_apply_sgr() {
+        let style = apply_sgr(Style::default(), "38;5;208;48;2;10;20;30");
+        assert_eq!(style.fg, Some(Color::Indexed(208)));
+        assert_eq!(style.bg, Some(Color::Rgb(10, 20, 30)));
+        assert_eq!(apply_sgr(style, "39;49"), Style::default());
+        assert_eq!(
+            apply_sgr(Style::default(), "91").fg,
+            Some(Color::Indexed(9))
+        );
+    }
+}
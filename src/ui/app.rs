@@ -1,32 +1,152 @@
 use std::io;
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use crossterm::{
-    event::{
-        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseButton,
-        MouseEventKind,
-    },
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 use tokio::sync::mpsc;
 
-use crate::app::{Action, AppState, Config};
-use crate::monitor::{MonitorTask, SystemStatsCollector};
-use crate::parsers::ParserRegistry;
-use crate::tmux::TmuxClient;
+use crate::agents::{AgentFilter, AgentState, AgentStatus, MonitoredAgent};
+use crate::app::{
+    spawn, spawn::Placement, Action, AppState, Config, Confirm, KeyMatch, Keymap, Palette, Picker,
+    PickerKind, PromptKind, Theme, UiPrefs, Viewer, ViewerKind,
+};
+use crate::daemon::{self, Availability, Reply, Request, Settings};
+use crate::git;
+use crate::monitor::{MonitorReload, SystemStatsCollector};
+use crate::tmux::{SendMode, TmuxClient, IGNORE_OPTION};
 
+use super::clipboard;
 use super::components::{
-    AgentTreeWidget, FooterWidget, HeaderWidget, HelpWidget, InputWidget, PanePreviewWidget,
-    SubagentLogWidget,
+    AgentTreeWidget, ConfirmWidget, FooterWidget, HeaderWidget, HelpWidget, InputWidget,
+    PaletteWidget, PanePreviewWidget, PickerWidget, PromptWidget, StatusBarWidget,
+    SubagentLogWidget, ViewerWidget,
 };
-use super::Layout;
+use super::monitoring::{self, ConfigWatch, Monitoring};
+use super::mouse::{map_mouse_to_action, Clicks, ScreenMap};
+use super::{set_icons, set_theme, Layout};
+
+/// Number of commits shown in the commit log viewer
+const COMMIT_LOG_LIMIT: usize = 20;
+
+/// Lines scrolled per page in the viewer
+const VIEWER_PAGE: usize = 10;
+
+/// Lines of history captured for the scrollback viewer
+const SCROLLBACK_LINES: usize = 5000;
+
+/// Where the dashboard's monitor runs
+enum Monitor {
+    /// In the dashboard, stopped when it quits
+    Local(Monitoring),
+    /// In `tmuxcc daemon`, reached through its socket
+    Daemon,
+}
+
+impl Monitor {
+    fn set_label(&self, id: &str, label: Option<String>) -> Result<()> {
+        match self {
+            Monitor::Local(monitoring) => monitoring.registry.lock().set_label(id, label),
+            Monitor::Daemon => {
+                let id = id.to_string();
+                daemon::request(&Request::SetLabel { id, label })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn set_note(&self, id: &str, note: Option<String>) -> Result<()> {
+        match self {
+            Monitor::Local(monitoring) => monitoring.registry.lock().set_note(id, note),
+            Monitor::Daemon => {
+                let id = id.to_string();
+                daemon::request(&Request::SetNote { id, note })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Hands the monitor the automation switches and mute of `state`
+    fn sync_settings(&self, state: &mut AppState) {
+        match self {
+            Monitor::Local(monitoring) => {
+                let mut switches = monitoring.switches.lock();
+                switches.paused = state.automation_paused;
+                switches.disabled = state.automation_disabled.clone();
+            }
+            Monitor::Daemon => {
+                let settings = Settings {
+                    automation_paused: state.automation_paused,
+                    automation_disabled: state.automation_disabled.iter().cloned().collect(),
+                    muted: state.muted,
+                };
+                if let Err(e) = daemon::request(&Request::Settings(settings)) {
+                    state.set_error(format!("Failed to update tmuxcc daemon: {}", e));
+                }
+            }
+        }
+    }
+
+    fn usage_report(&self) -> Result<Vec<String>> {
+        match self {
+            Monitor::Local(monitoring) => Ok(monitoring.registry.lock().usage_report()),
+            Monitor::Daemon => match daemon::request(&Request::UsageReport)? {
+                Reply::Lines(lines) => Ok(lines),
+                reply => anyhow::bail!("Unexpected reply from tmuxcc daemon: {:?}", reply),
+            },
+        }
+    }
+
+    /// Hands a local monitor the parts of a reloaded config; the daemon
+    /// watches the file itself
+    fn reload(&mut self, reload: MonitorReload) {
+        if let Monitor::Local(monitoring) = self {
+            monitoring.reload(reload);
+        }
+    }
+}
 
 /// Runs the main application loop
-pub async fn run_app(config: Config) -> Result<()> {
+///
+/// Runs the monitor itself unless `tmuxcc daemon` does; the dashboard then
+/// shows the daemon's agents and history and changes labels, notes and
+/// switches through it.
+pub async fn run_app(mut config: Config) -> Result<()> {
+    // Checked before touching the terminal so config errors print normally
+    let mut keymap = Keymap::from_config(&config.keys)?;
+    set_theme(Theme::from_config(&config.theme)?);
+    set_icons(config.use_icons.resolve(|name| std::env::var(name).ok()));
+
+    // Create tmux client
+    let tmux_client = Arc::new(TmuxClient::with_capture_lines(config.capture_lines));
+
+    // Start the monitor, or subscribe to the daemon's if it runs for this
+    // config. A second monitor next to a daemon running for another one
+    // would answer the same prompts twice and race it saving the registry
+    let (mut monitor, mut rx, welcome) = match daemon::availability(&config) {
+        Availability::Serves => {
+            let subscription = daemon::subscribe()?;
+            let welcome = (subscription.transitions, subscription.settings);
+            (Monitor::Daemon, subscription.updates, Some(welcome))
+        }
+        Availability::Elsewhere(source) => bail!(
+            "tmuxcc daemon runs for {}; stop it with `tmuxcc daemon --stop` or start the \
+             dashboard with the same config, profile and sockets",
+            source.describe()
+        ),
+        Availability::NotRunning => {
+            let (tx, rx) = mpsc::channel(32);
+            let monitoring = Monitoring::start(&config, tmux_client.clone(), tx)?;
+            (Monitor::Local(monitoring), rx, None)
+        }
+    };
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -36,29 +156,49 @@ pub async fn run_app(config: Config) -> Result<()> {
 
     // Initialize state
     let mut state = AppState::new();
+    state.context_warning = config.context_warning;
+    state.set_sidebar_width(config.sidebar_width);
+    state.servers = config.servers();
+    let prefs = UiPrefs::load(UiPrefs::default_path().as_deref());
+    state.sort = prefs.sort;
+    state.compact_list = prefs.compact;
+    state.pinned = prefs.pinned;
+    if let Some(width) = prefs.sidebar_width {
+        state.set_sidebar_width(width);
+    }
+    state.group_by_repo = prefs.group_by_repo;
+    state.collapsed_groups = prefs.collapsed.into_iter().collect();
+    if let Some(query) = &prefs.search {
+        // A query the filter syntax no longer accepts just isn't restored
+        let _ = state.set_search(query);
+    }
+    state.pending_selection = prefs.selected;
+    state.sounds = config.sounds.enabled;
+    state.muted = prefs.muted;
+    if let Monitor::Local(monitoring) = &monitor {
+        if let Some(error) = &monitoring.hook_error {
+            state.set_error(error.clone());
+        }
+    }
+    if let Some((transitions, settings)) = welcome {
+        state.transitions = transitions.into();
+        state.automation_paused = settings.automation_paused;
+        state.automation_disabled = settings.automation_disabled.into_iter().collect();
+        state.muted = settings.muted;
+        state.set_message("Connected to tmuxcc daemon".to_string());
+    }
 
-    // Create tmux client and parser registry
-    let tmux_client = Arc::new(TmuxClient::with_capture_lines(config.capture_lines));
-    let parser_registry = Arc::new(ParserRegistry::new());
-
-    // Check if tmux is available
-    if !tmux_client.is_available() {
+    // Check if tmux is available on any monitored server
+    if !config
+        .servers()
+        .iter()
+        .any(|s| tmux_client.for_server(s).is_available())
+    {
         state.set_error("tmux is not running".to_string());
     }
 
-    // Create channel for monitor updates
-    let (tx, mut rx) = mpsc::channel(32);
-
-    // Start monitor task
-    let monitor = MonitorTask::new(
-        tmux_client.clone(),
-        parser_registry.clone(),
-        tx,
-        Duration::from_millis(config.poll_interval_ms),
-    );
-    let monitor_handle = tokio::spawn(async move {
-        monitor.run().await;
-    });
+    // Let the user come back from a pane jumped to with Enter / o
+    let return_key = install_return_key(&tmux_client, &config);
 
     // Create system stats collector
     let mut system_stats = SystemStatsCollector::new();
@@ -70,11 +210,18 @@ pub async fn run_app(config: Config) -> Result<()> {
         &mut rx,
         &tmux_client,
         &mut system_stats,
+        &mut config,
+        &mut monitor,
+        &mut keymap,
     )
     .await;
 
     // Cleanup
-    monitor_handle.abort();
+    drop(monitor);
+    save_prefs(&mut state, &config);
+    if let Some(key) = return_key {
+        let _ = tmux_client.unbind_key(&key);
+    }
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
@@ -86,20 +233,49 @@ pub async fn run_app(config: Config) -> Result<()> {
     result
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn run_loop(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     state: &mut AppState,
     rx: &mut mpsc::Receiver<crate::monitor::MonitorUpdate>,
     tmux_client: &TmuxClient,
     system_stats: &mut SystemStatsCollector,
+    config: &mut Config,
+    monitor: &mut Monitor,
+    keymap: &mut Keymap,
 ) -> Result<()> {
+    // Results of slow actions run off the UI thread (Ok: message, Err: error)
+    let (bg_tx, mut bg_rx) = mpsc::unbounded_channel::<std::result::Result<String, String>>();
+    // Where the last frame put what mouse clicks land on
+    let mut screen = ScreenMap::default();
+    let mut clicks = Clicks::default();
+    // Furthest the help can scroll, as of the last frame that showed it
+    let mut help_max_scroll = 0;
+    let mut config_watch = ConfigWatch::new(config);
+
     loop {
         // Advance animation tick
         state.tick();
 
+        if let Some(path) = config_watch.changed() {
+            match reload_config(&path, config) {
+                Ok((new_config, new_keymap, new_theme, reload)) => {
+                    *config = new_config;
+                    *keymap = new_keymap;
+                    set_theme(new_theme);
+                    monitor.reload(reload);
+                    state.context_warning = config.context_warning;
+                    state.sounds = config.sounds.enabled;
+                    state.set_message(format!("Reloaded {}", path.display()));
+                }
+                Err(e) => state.set_error(format!("Config not reloaded: {}", one_line(&e))),
+            }
+        }
+
         // Update system stats
         system_stats.refresh();
         state.system_stats = system_stats.stats().clone();
+        state.acknowledge_selected();
 
         // Draw UI
         terminal.draw(|frame| {
@@ -116,7 +292,9 @@ async fn run_loop(
                 // With subagent log: sidebar | summary+preview+input | subagent_log
                 let (left, preview, subagent_log) =
                     Layout::content_layout_with_log(main_chunks[1], state.sidebar_width);
-                AgentTreeWidget::render(frame, left, state);
+                screen.tree_rows = AgentTreeWidget::render(frame, left, state);
+                screen.sidebar = left;
+                screen.content = main_chunks[1];
 
                 // Split preview area for summary, preview, and input
                 let preview_chunks = ratatui::layout::Layout::default()
@@ -130,6 +308,8 @@ async fn run_loop(
                 PanePreviewWidget::render_summary(frame, preview_chunks[0], state);
                 PanePreviewWidget::render_detailed(frame, preview_chunks[1], state);
                 InputWidget::render(frame, preview_chunks[2], state);
+                screen.preview = preview_chunks[1];
+                screen.input = preview_chunks[2];
                 SubagentLogWidget::render(frame, subagent_log, state);
             } else {
                 // Normal: sidebar | summary+preview+input
@@ -139,20 +319,42 @@ async fn run_loop(
                     input_height,
                     state.show_summary_detail,
                 );
-                AgentTreeWidget::render(frame, left, state);
+                screen.tree_rows = AgentTreeWidget::render(frame, left, state);
+                screen.sidebar = left;
+                screen.content = main_chunks[1];
                 if state.show_summary_detail {
                     PanePreviewWidget::render_summary(frame, summary, state);
                 }
                 PanePreviewWidget::render_detailed(frame, preview, state);
                 InputWidget::render(frame, input_area, state);
+                screen.preview = preview;
+                screen.input = input_area;
             }
 
             // Footer
             FooterWidget::render(frame, main_chunks[2], state);
+            screen.footer = main_chunks[2];
+            screen.mute = StatusBarWidget::render(frame, main_chunks[3], state);
+
+            // Viewer over the content area, or everything above the status bar for history
+            let viewer_area = match &state.viewer {
+                Some(viewer) if viewer.kind == ViewerKind::Scrollback => ratatui::layout::Rect {
+                    height: main_chunks[3].y - size.y,
+                    ..size
+                },
+                _ => main_chunks[1],
+            };
+            ViewerWidget::render(frame, viewer_area, state);
+
+            // Popups
+            PromptWidget::render(frame, size, state);
+            PickerWidget::render(frame, size, state);
+            PaletteWidget::render(frame, size, state);
+            ConfirmWidget::render(frame, size, state);
 
             // Help overlay
             if state.show_help {
-                HelpWidget::render(frame, size);
+                help_max_scroll = HelpWidget::render(frame, size, state, keymap, config);
             }
         })?;
 
@@ -162,299 +364,781 @@ async fn run_loop(
         tokio::select! {
             // Handle monitor updates
             Some(update) = rx.recv() => {
+                if let Monitor::Local(monitoring) = monitor {
+                    monitoring.alert(&update, config, tmux_client, state.muted);
+                }
                 state.agents = update.agents;
+                state.restore_selection();
+                for (key, text) in state.take_due_prompts(&update.transitions) {
+                    if let Some(agent) = state.agents.root_agents.iter().find(|a| a.key() == key) {
+                        match send_prompt(tmux_client, agent, &text) {
+                            Ok(()) => state.set_message(format!("Sent queued prompt to {}", key)),
+                            Err(e) => state.set_error(format!("Failed to send queued prompt to {}: {}", key, e)),
+                        }
+                    }
+                }
+                state.record_transitions(update.transitions);
+                for response in &update.responses {
+                    let text = format!("Auto-responded {:?} to \"{}\"", response.reply, response.question);
+                    state.set_message(format!("{} in {}", text, response.key));
+                    state.notify(response.key.clone(), text);
+                }
+                for decision in &update.approvals {
+                    let verb = if decision.approved { "Auto-approved" } else { "Held for review" };
+                    let text = format!("{} {}", verb, decision.request);
+                    state.set_message(format!("{} in {}", text, decision.key));
+                    state.notify(decision.key.clone(), text);
+                }
+                let warnings = update.context_warnings.iter().map(|w| (&w.key, w.message()));
+                for (key, message) in warnings.chain(update.stalls.iter().map(|s| (&s.key, s.message()))) {
+                    state.notify(key.clone(), message.clone());
+                    state.set_error(message);
+                }
                 // Ensure selected index is valid
                 if state.selected_index >= state.agents.root_agents.len() {
                     state.selected_index = state.agents.root_agents.len().saturating_sub(1);
                 }
+                state.keep_cursor_visible();
                 // Clean up invalid selections
                 let max_idx = state.agents.root_agents.len();
                 state.selected_agents.retain(|&idx| idx < max_idx);
             }
 
+            // Handle results of background actions
+            Some(result) = bg_rx.recv() => {
+                match result {
+                    Ok(message) => state.set_message(message),
+                    Err(error) => state.set_error(error),
+                }
+            }
+
             // Handle keyboard and mouse events
             _ = tokio::time::sleep(timeout) => {
                 // Process all pending events to avoid input lag
                 while event::poll(Duration::from_millis(0))? {
                     let event = event::read()?;
 
-                    // Handle mouse events
-                    if let Event::Mouse(mouse) = event {
-                        let size = terminal.size()?;
-                        let area = ratatui::layout::Rect::new(0, 0, size.width, size.height);
-                        let main_chunks = Layout::main_layout(area);
-                        let footer_area = main_chunks[2];
-                        let (sidebar, _, _, input_area) = Layout::content_layout_with_input(
-                            main_chunks[1], state.sidebar_width, 3, state.show_summary_detail
-                        );
-
-                        match mouse.kind {
-                            MouseEventKind::Down(MouseButton::Left) => {
-                                let x = mouse.column;
-                                let y = mouse.row;
-
-                                // Check footer button clicks first
-                                if let Some(button) = FooterWidget::hit_test(x, y, footer_area, state) {
-                                    use super::components::FooterButton;
-                                    match button {
-                                        FooterButton::Approve => {
-                                            let indices = state.get_operation_indices();
-                                            for idx in indices {
-                                                if let Some(agent) = state.agents.get_agent(idx) {
-                                                    if agent.status.needs_attention() {
-                                                        let target = agent.target.clone();
-                                                        let _ = tmux_client.send_keys(&target, "y");
-                                                        let _ = tmux_client.send_keys(&target, "Enter");
-                                                    }
-                                                }
-                                            }
-                                            state.clear_selection();
-                                        }
-                                        FooterButton::Reject => {
-                                            let indices = state.get_operation_indices();
-                                            for idx in indices {
-                                                if let Some(agent) = state.agents.get_agent(idx) {
-                                                    if agent.status.needs_attention() {
-                                                        let target = agent.target.clone();
-                                                        let _ = tmux_client.send_keys(&target, "n");
-                                                        let _ = tmux_client.send_keys(&target, "Enter");
-                                                    }
-                                                }
-                                            }
-                                            state.clear_selection();
-                                        }
-                                        FooterButton::ApproveAll => {
-                                            for agent in &state.agents.root_agents {
-                                                if agent.status.needs_attention() {
-                                                    let _ = tmux_client.send_keys(&agent.target, "y");
-                                                    let _ = tmux_client.send_keys(&agent.target, "Enter");
-                                                }
-                                            }
-                                        }
-                                        FooterButton::ToggleSelect => {
-                                            state.toggle_selection();
+                    let action = match event {
+                        Event::Mouse(mouse) => map_mouse_to_action(mouse, state, &screen, &mut clicks),
+                        Event::Key(key) => map_key_to_action(key.code, key.modifiers, state, keymap),
+                        _ => continue,
+                    };
+                    // A command chosen in the palette runs as if its keys were pressed
+                    let action = match action {
+                        Action::PaletteConfirm => {
+                            match state.palette.take().and_then(|p| p.selected_action()) {
+                                Some(action) => action,
+                                None => continue,
+                            }
+                        }
+                        action => action,
+                    };
+
+                    match action {
+                        Action::Quit => {
+                            state.should_quit = true;
+                        }
+                        Action::NextAgent => {
+                            state.select_next();
+                        }
+                        Action::PrevAgent => {
+                            state.select_prev();
+                        }
+                        Action::FirstAgent => {
+                            state.select_first();
+                        }
+                        Action::LastAgent => {
+                            state.select_last();
+                        }
+                        Action::ToggleSelection => {
+                            state.toggle_selection();
+                        }
+                        Action::SelectAll => {
+                            state.select_all();
+                        }
+                        Action::ClearSelection => {
+                            state.clear_selection();
+                        }
+                        Action::Approve => {
+                            let indices = state.get_operation_indices();
+                            for idx in indices {
+                                if let Some(agent) = state.agents.get_agent(idx) {
+                                    if agent.status.needs_attention() {
+                                        let target = agent.target.clone();
+                                        let client = tmux_client.for_server(&agent.server);
+                                        if let Err(e) = client.send_keys(&target, "y") {
+                                            state.set_error(format!("Failed to approve: {}", e));
+                                            break;
                                         }
-                                        FooterButton::Focus => {
-                                            if let Some(agent) = state.selected_agent() {
-                                                let target = agent.target.clone();
-                                                let _ = tmux_client.focus_pane(&target);
-                                            }
+                                        if let Err(e) = client.send_keys(&target, "Enter") {
+                                            state.set_error(format!("Failed to send Enter: {}", e));
+                                            break;
                                         }
-                                        FooterButton::Help => {
-                                            state.toggle_help();
+                                    }
+                                }
+                            }
+                            state.clear_selection();
+                        }
+                        Action::Reject => {
+                            let indices = state.get_operation_indices();
+                            for idx in indices {
+                                if let Some(agent) = state.agents.get_agent(idx) {
+                                    if agent.status.needs_attention() {
+                                        let target = agent.target.clone();
+                                        let client = tmux_client.for_server(&agent.server);
+                                        if let Err(e) = client.send_keys(&target, "n") {
+                                            state.set_error(format!("Failed to reject: {}", e));
+                                            break;
                                         }
-                                        FooterButton::Quit => {
-                                            state.should_quit = true;
+                                        if let Err(e) = client.send_keys(&target, "Enter") {
+                                            state.set_error(format!("Failed to send Enter: {}", e));
+                                            break;
                                         }
                                     }
                                 }
-                                // Check if click is in sidebar - try to select agent
-                                else if x >= sidebar.x && x < sidebar.x + sidebar.width
-                                    && y >= sidebar.y && y < sidebar.y + sidebar.height
-                                {
-                                    state.focus_sidebar();
-                                    // Calculate which agent was clicked based on row
-                                    // Each agent takes ~4 lines in the tree view (varies)
-                                    // Simple heuristic: use relative row position
-                                    let rel_y = (y - sidebar.y).saturating_sub(1) as usize;
-                                    let agents_count = state.agents.root_agents.len();
-                                    if agents_count > 0 {
-                                        // Estimate ~4 lines per agent (header + info + status)
-                                        let estimated_idx = rel_y / 4;
-                                        if estimated_idx < agents_count {
-                                            state.select_agent(estimated_idx);
-                                        }
+                            }
+                            state.clear_selection();
+                        }
+                        Action::ApproveAll => {
+                            for agent in &state.agents.root_agents {
+                                if agent.status.needs_attention() {
+                                    let client = tmux_client.for_server(&agent.server);
+                                    if let Err(e) = client.send_keys(&agent.target, "y") {
+                                        state.set_error(format!("Failed to approve {}: {}", agent.target, e));
+                                        break;
+                                    }
+                                    if let Err(e) = client.send_keys(&agent.target, "Enter") {
+                                        state.set_error(format!("Failed to send Enter to {}: {}", agent.target, e));
+                                        break;
                                     }
                                 }
-                                // Check if click is in input area
-                                else if x >= input_area.x && x < input_area.x + input_area.width
-                                    && y >= input_area.y && y < input_area.y + input_area.height
-                                {
-                                    state.focus_input();
+                            }
+                        }
+                        Action::FocusPane => {
+                            if let Some(agent) = state.selected_agent() {
+                                let target = agent.target.clone();
+                                let client = tmux_client.for_server(&agent.server);
+                                if let Err(e) = client.focus_pane(&target) {
+                                    state.set_error(format!("Failed to focus: {}", e));
                                 }
                             }
-                            MouseEventKind::ScrollUp => {
-                                state.select_prev();
+                        }
+                        Action::JumpToPane => {
+                            if let Some(agent) = state.selected_agent() {
+                                let target = agent.target.clone();
+                                let client = tmux_client.for_server(&agent.server);
+                                if let Err(e) = jump_to_pane(terminal, &client, &target) {
+                                    state.set_error(format!("Failed to jump to pane: {}", e));
+                                }
                             }
-                            MouseEventKind::ScrollDown => {
-                                state.select_next();
+                        }
+                        Action::KillPane => {
+                            if let Some(agent) = state.selected_agent() {
+                                state.confirm = Some(Confirm::KillPane {
+                                    server: agent.server.clone(),
+                                    target: agent.target.clone(),
+                                });
                             }
-                            _ => {}
                         }
-                        continue;
-                    }
-
-                    // Handle keyboard events
-                    if let Event::Key(key) = event {
-                        let action = map_key_to_action(key.code, key.modifiers, state);
-
-                        match action {
-                            Action::Quit => {
-                                state.should_quit = true;
-                            }
-                            Action::NextAgent => {
-                                state.select_next();
-                            }
-                            Action::PrevAgent => {
-                                state.select_prev();
-                            }
-                            Action::ToggleSelection => {
-                                state.toggle_selection();
-                            }
-                            Action::SelectAll => {
-                                state.select_all();
-                            }
-                            Action::ClearSelection => {
-                                state.clear_selection();
-                            }
-                            Action::Approve => {
-                                let indices = state.get_operation_indices();
-                                for idx in indices {
-                                    if let Some(agent) = state.agents.get_agent(idx) {
-                                        if agent.status.needs_attention() {
-                                            let target = agent.target.clone();
-                                            if let Err(e) = tmux_client.send_keys(&target, "y") {
-                                                state.set_error(format!("Failed to approve: {}", e));
-                                                break;
-                                            }
-                                            if let Err(e) = tmux_client.send_keys(&target, "Enter") {
-                                                state.set_error(format!("Failed to send Enter: {}", e));
-                                                break;
-                                            }
-                                        }
+                        Action::RespawnPane => {
+                            if let Some(agent) = state.selected_agent() {
+                                match agent.launch.clone() {
+                                    Some(launch) => {
+                                        state.confirm = Some(Confirm::RespawnPane {
+                                            server: agent.server.clone(),
+                                            target: agent.target.clone(),
+                                            cwd: agent.path.clone(),
+                                            launch,
+                                        });
                                     }
+                                    None => state.set_error(
+                                        "Could not determine the agent's command".to_string(),
+                                    ),
                                 }
-                                state.clear_selection();
-                            }
-                            Action::Reject => {
-                                let indices = state.get_operation_indices();
-                                for idx in indices {
-                                    if let Some(agent) = state.agents.get_agent(idx) {
-                                        if agent.status.needs_attention() {
-                                            let target = agent.target.clone();
-                                            if let Err(e) = tmux_client.send_keys(&target, "n") {
-                                                state.set_error(format!("Failed to reject: {}", e));
-                                                break;
-                                            }
-                                            if let Err(e) = tmux_client.send_keys(&target, "Enter") {
-                                                state.set_error(format!("Failed to send Enter: {}", e));
-                                                break;
-                                            }
-                                        }
-                                    }
+                            }
+                        }
+                        Action::ConfirmYes => {
+                            if let Some(confirm) = state.confirm.take() {
+                                run_confirmed(state, confirm, tmux_client);
+                            }
+                        }
+                        Action::ConfirmNo => {
+                            state.confirm = None;
+                        }
+                        Action::ToggleSubagentLog => {
+                            state.toggle_subagent_log();
+                        }
+                        Action::ToggleSummaryDetail => {
+                            state.toggle_summary_detail();
+                        }
+                        Action::Refresh => {
+                            state.clear_error();
+                        }
+                        Action::ToggleMute => {
+                            if state.sounds {
+                                state.muted = !state.muted;
+                                let verb = if state.muted { "muted" } else { "on" };
+                                state.set_message(format!("Sounds {}", verb));
+                                monitor.sync_settings(state);
+                                save_prefs(state, config);
+                            } else {
+                                state.set_message("No sounds configured ([sounds] in the config)".to_string());
+                            }
+                        }
+                        Action::ShowPalette => {
+                            state.palette = Some(Palette::new(keymap.palette_entries()));
+                        }
+                        Action::PaletteChar(c) => {
+                            if let Some(palette) = &mut state.palette {
+                                palette.push(c);
+                            }
+                        }
+                        Action::PaletteBackspace => {
+                            if let Some(palette) = &mut state.palette {
+                                palette.pop();
+                            }
+                        }
+                        Action::PaletteNext => {
+                            if let Some(palette) = &mut state.palette {
+                                palette.select_next();
+                            }
+                        }
+                        Action::PalettePrev => {
+                            if let Some(palette) = &mut state.palette {
+                                palette.select_prev();
+                            }
+                        }
+                        Action::PaletteCancel => {
+                            state.palette = None;
+                        }
+                        Action::ShowHelp => {
+                            state.toggle_help();
+                        }
+                        Action::HideHelp => {
+                            state.show_help = false;
+                        }
+                        Action::HelpUp(lines) => {
+                            state.help_scroll = state.help_scroll.saturating_sub(lines);
+                        }
+                        Action::HelpDown(lines) => {
+                            state.help_scroll = (state.help_scroll + lines).min(help_max_scroll);
+                        }
+                        Action::FocusInput => {
+                            state.focus_input();
+                        }
+                        Action::FocusSidebar => {
+                            state.focus_sidebar();
+                        }
+                        Action::ClearInput => {
+                            state.take_input();
+                        }
+                        Action::InputChar(c) => {
+                            state.input_char(c);
+                        }
+                        Action::InputNewline => {
+                            state.input_newline();
+                        }
+                        Action::InputBackspace => {
+                            state.input_backspace();
+                        }
+                        Action::CursorLeft => {
+                            state.cursor_left();
+                        }
+                        Action::CursorRight => {
+                            state.cursor_right();
+                        }
+                        Action::CursorHome => {
+                            state.cursor_home();
+                        }
+                        Action::CursorEnd => {
+                            state.cursor_end();
+                        }
+                        Action::SendInput => {
+                            let input = state.take_input();
+                            if !input.is_empty() {
+                                let mode = SendMode::for_text(&input);
+                                if send_to_targets(state, tmux_client, &input, mode) {
+                                    let targets = state.input_targets();
+                                    let target = match (targets.len(), state.selected_agent()) {
+                                        (1, Some(agent)) => agent.key(),
+                                        (n, _) => format!("{} panes", n),
+                                    };
+                                    state.last_sent = Some((target, input));
                                 }
-                                state.clear_selection();
                             }
-                            Action::ApproveAll => {
-                                for agent in &state.agents.root_agents {
-                                    if agent.status.needs_attention() {
-                                        if let Err(e) = tmux_client.send_keys(&agent.target, "y") {
-                                            state.set_error(format!("Failed to approve {}: {}", agent.target, e));
-                                            break;
-                                        }
-                                        if let Err(e) = tmux_client.send_keys(&agent.target, "Enter") {
-                                            state.set_error(format!("Failed to send Enter to {}: {}", agent.target, e));
-                                            break;
-                                        }
-                                    }
+                            // Stay in input mode for consecutive inputs
+                        }
+                        Action::SendNumber(num) => {
+                            send_to_targets(state, tmux_client, &num.to_string(), SendMode::Keys);
+                        }
+                        Action::IgnorePane => {
+                            if let Some(agent) = state.selected_agent() {
+                                let (key, target) = (agent.key(), agent.target.clone());
+                                let client = tmux_client.for_server(&agent.server);
+                                match client.set_pane_option(&target, IGNORE_OPTION, "1") {
+                                    Ok(()) => state.set_message(format!(
+                                        "No longer monitoring {} (tmux set -pu -t {} {} to undo)",
+                                        key, target, IGNORE_OPTION
+                                    )),
+                                    Err(e) => state.set_error(format!("Failed to ignore pane: {}", e)),
                                 }
                             }
-                            Action::FocusPane => {
-                                if let Some(agent) = state.selected_agent() {
-                                    let target = agent.target.clone();
-                                    if let Err(e) = tmux_client.focus_pane(&target) {
-                                        state.set_error(format!("Failed to focus: {}", e));
+                        }
+                        Action::ToggleZoom => {
+                            if let Some(agent) = state.selected_agent() {
+                                let client = tmux_client.for_server(&agent.server);
+                                match client.toggle_zoom(&agent.target) {
+                                    Ok(()) => {
+                                        // Shown right away; the next poll confirms it
+                                        if let Some(agent) = state.selected_agent_mut() {
+                                            agent.zoomed = !agent.zoomed;
+                                        }
                                     }
+                                    Err(e) => state.set_error(format!("Failed to zoom: {}", e)),
                                 }
                             }
-                            Action::ToggleSubagentLog => {
-                                state.toggle_subagent_log();
+                        }
+                        Action::EditLabel | Action::EditNote => {
+                            if let Some(agent) = state.selected_agent() {
+                                let id = agent.id.clone();
+                                let (kind, current) = if action == Action::EditLabel {
+                                    (PromptKind::Label { id }, agent.label.clone())
+                                } else {
+                                    (PromptKind::Note { id }, agent.note.clone())
+                                };
+                                state.open_prompt_with(kind, current.unwrap_or_default());
                             }
-                            Action::ToggleSummaryDetail => {
-                                state.toggle_summary_detail();
+                        }
+                        Action::QueuePrompt => {
+                            if let Some(agent) = state.selected_agent() {
+                                let key = agent.key();
+                                state.open_prompt(PromptKind::Queue { key });
                             }
-                            Action::Refresh => {
-                                state.clear_error();
+                        }
+                        Action::EditQueue => {
+                            if let Some(agent) = state.selected_agent() {
+                                let key = agent.key();
+                                let items: Vec<String> = state.queued(&key).cloned().collect();
+                                if items.is_empty() {
+                                    state.set_message("No prompts queued (Q to add one)".to_string());
+                                } else {
+                                    state.open_picker(Picker::new(PickerKind::Queue { key }, items, None));
+                                }
                             }
-                            Action::ShowHelp => {
-                                state.toggle_help();
+                        }
+                        Action::RunTests => {
+                            if let Some(agent) = state.selected_agent() {
+                                let key = agent.key();
+                                let project = agent.project.as_ref().and_then(|p| p.test_command.clone());
+                                let command = project.unwrap_or_else(|| config.test_command.clone());
+                                if command.is_empty() {
+                                    state.set_message("No test command (test_command in the config or .tmuxcc.toml)".to_string());
+                                } else {
+                                    let text = format!("Run the tests with `{}` and fix any failures", command);
+                                    queue_prompt(state, tmux_client, &key, &text);
+                                }
                             }
-                            Action::HideHelp => {
-                                state.show_help = false;
+                        }
+                        Action::PickerMove { later } => {
+                            if let Some(PickerKind::Queue { key }) = state.picker.as_ref().map(|p| p.kind.clone()) {
+                                let index = state.picker.as_ref().map_or(0, |p| p.selected);
+                                if let Some(moved) = state.move_queued(&key, index, later) {
+                                    sync_queue_picker(state, &key, moved);
+                                }
                             }
-                            Action::FocusInput => {
-                                state.focus_input();
+                        }
+                        Action::PickerRemove => {
+                            if let Some(PickerKind::Queue { key }) = state.picker.as_ref().map(|p| p.kind.clone()) {
+                                let index = state.picker.as_ref().map_or(0, |p| p.selected);
+                                state.remove_queued(&key, index);
+                                sync_queue_picker(state, &key, index);
                             }
-                            Action::FocusSidebar => {
-                                state.focus_sidebar();
+                        }
+                        Action::ToggleBroadcast => {
+                            state.toggle_broadcast();
+                            if state.broadcast && state.selected_agents.is_empty() {
+                                state.set_message("Broadcast on: select panes with Space".to_string());
                             }
-                            Action::ClearInput => {
-                                state.take_input();
+                        }
+                        Action::BroadcastFilter => {
+                            state.open_prompt(PromptKind::BroadcastFilter);
+                        }
+                        Action::Search => {
+                            let query = state.search.as_ref().map(|s| s.query.clone());
+                            state.open_prompt_with(PromptKind::Search, query.unwrap_or_default());
+                        }
+                        Action::ClearSearch => {
+                            let _ = state.set_search("");
+                        }
+                        Action::SidebarWider => {
+                            state.set_sidebar_width(state.sidebar_width + 5);
+                        }
+                        Action::SidebarNarrower => {
+                            state.set_sidebar_width(state.sidebar_width.saturating_sub(5));
+                        }
+                        Action::ResizeSidebar(percent) => {
+                            state.set_sidebar_width(percent);
+                        }
+                        Action::SelectAgent(idx) => {
+                            state.focus_sidebar();
+                            state.select_agent(idx);
+                        }
+                        Action::ScrollUp => {
+                            state.select_prev();
+                        }
+                        Action::ScrollDown => {
+                            state.select_next();
+                        }
+                        Action::ScrollPreviewUp(lines) => {
+                            state.scroll_preview_up(lines);
+                        }
+                        Action::ScrollPreviewDown(lines) => {
+                            state.scroll_preview_down(lines);
+                        }
+                        Action::NewWorktree => {
+                            if state.selected_agent().and_then(|a| a.git.as_ref()).is_some() {
+                                state.open_prompt(PromptKind::WorktreeBranch);
+                            } else {
+                                state.set_error("Selected agent is not in a git repository".to_string());
                             }
-                            Action::InputChar(c) => {
-                                state.input_char(c);
+                        }
+                        Action::NewAgent => match new_agent_picker(state, tmux_client) {
+                            Ok(picker) => state.open_picker(picker),
+                            Err(e) => state.set_error(format!("Failed to list tmux sessions: {}", e)),
+                        },
+                        Action::PromptChar(c) => {
+                            if let Some(prompt) = &mut state.prompt {
+                                prompt.buffer.push(c);
                             }
-                            Action::InputNewline => {
-                                state.input_newline();
+                            update_search(state);
+                        }
+                        Action::PromptBackspace => {
+                            if let Some(prompt) = &mut state.prompt {
+                                prompt.buffer.pop();
                             }
-                            Action::InputBackspace => {
-                                state.input_backspace();
+                            update_search(state);
+                        }
+                        Action::PromptCancel => {
+                            if state.take_prompt().is_some_and(|p| p.kind == PromptKind::Search) {
+                                let _ = state.set_search("");
                             }
-                            Action::CursorLeft => {
-                                state.cursor_left();
+                        }
+                        Action::PromptSubmit => {
+                            if let Some(prompt) = state.take_prompt() {
+                                submit_prompt(state, prompt.kind, prompt.buffer.trim(), tmux_client, config, monitor);
                             }
-                            Action::CursorRight => {
-                                state.cursor_right();
+                        }
+                        Action::OpenBranchPicker => {
+                            let repo = state
+                                .selected_agent()
+                                .filter(|a| a.git.is_some())
+                                .map(|a| a.path.clone());
+                            match repo {
+                                Some(repo) => match git::list_branches(&repo) {
+                                    Ok((branches, current)) => {
+                                        state.open_picker(Picker::new(
+                                            PickerKind::Branch { repo },
+                                            branches,
+                                            current,
+                                        ));
+                                    }
+                                    Err(e) => state.set_error(format!("Failed to list branches: {}", e)),
+                                },
+                                None => state.set_error("Selected agent is not in a git repository".to_string()),
                             }
-                            Action::CursorHome => {
-                                state.cursor_home();
+                        }
+                        Action::PickerNext => {
+                            if let Some(picker) = &mut state.picker {
+                                picker.select_next();
                             }
-                            Action::CursorEnd => {
-                                state.cursor_end();
+                        }
+                        Action::PickerPrev => {
+                            if let Some(picker) = &mut state.picker {
+                                picker.select_prev();
                             }
-                            Action::SendInput => {
-                                let input = state.take_input();
-                                if !input.is_empty() {
-                                    if let Some(agent) = state.selected_agent() {
+                        }
+                        Action::PickerCancel => {
+                            state.take_picker();
+                        }
+                        Action::PickerConfirm { force } => {
+                            if let Some(picker) = state.take_picker() {
+                                if picker.kind == PickerKind::Notifications {
+                                    let agent = state.open_notification(picker.selected);
+                                    if let Some(agent) = agent {
                                         let target = agent.target.clone();
-                                        // Send the input text
-                                        if let Err(e) = tmux_client.send_keys(&target, &input) {
-                                            state.set_error(format!("Failed to send input: {}", e));
-                                        } else if let Err(e) = tmux_client.send_keys(&target, "Enter") {
-                                            state.set_error(format!("Failed to send Enter: {}", e));
+                                        let client = tmux_client.for_server(&agent.server);
+                                        if let Err(e) = jump_to_pane(terminal, &client, &target) {
+                                            state.set_error(format!("Failed to jump to pane: {}", e));
                                         }
                                     }
+                                } else {
+                                    confirm_picker(state, picker, force);
+                                }
+                            }
+                        }
+                        Action::PickerMarkRead { all } => {
+                            if let Some(PickerKind::Notifications) = state.picker.as_ref().map(|p| &p.kind) {
+                                let index = state.picker.as_ref().map(|p| p.selected);
+                                state.mark_read(if all { None } else { index });
+                                let items = state.notifications.iter().map(|n| n.line()).collect();
+                                if let Some(picker) = state.picker.as_mut() {
+                                    picker.items = items;
+                                }
+                            }
+                        }
+                        Action::ShowDetails => {
+                            if let Some(agent) = state.selected_agent() {
+                                let title = format!("Details of {}", agent.key());
+                                let lines = state.pane_details(agent);
+                                state.open_viewer(Viewer::new(ViewerKind::Output, title, lines));
+                            }
+                        }
+                        Action::ShowNotifications => {
+                            if state.notifications.is_empty() {
+                                state.set_message("No notifications yet".to_string());
+                            } else {
+                                let items = state.notifications.iter().map(|n| n.line()).collect();
+                                state.open_picker(Picker::new(PickerKind::Notifications, items, None));
+                            }
+                        }
+                        Action::ShowCommitLog => {
+                            let repo = state
+                                .selected_agent()
+                                .filter(|a| a.git.is_some())
+                                .map(|a| a.path.clone());
+                            match repo {
+                                Some(repo) => match git::get_recent_commits(&repo, COMMIT_LOG_LIMIT) {
+                                    Ok(commits) => {
+                                        let lines = commits
+                                            .iter()
+                                            .map(|c| {
+                                                format!(
+                                                    "{}\t{}\t{}\t{}",
+                                                    c.short_hash,
+                                                    c.age_str(),
+                                                    c.author,
+                                                    c.subject
+                                                )
+                                            })
+                                            .collect();
+                                        state.open_viewer(Viewer::new(
+                                            ViewerKind::CommitLog,
+                                            format!("Recent commits: {}", repo),
+                                            lines,
+                                        ));
+                                    }
+                                    Err(e) => state.set_error(format!("Failed to read log: {}", e)),
+                                },
+                                None => state.set_error("Selected agent is not in a git repository".to_string()),
+                            }
+                        }
+                        Action::ShowDiff => {
+                            let repo = state
+                                .selected_agent()
+                                .filter(|a| a.git.is_some())
+                                .map(|a| a.path.clone());
+                            match repo {
+                                Some(repo) => match diff_lines(&repo) {
+                                    Ok(lines) => {
+                                        state.open_viewer(Viewer::new(
+                                            ViewerKind::Diff,
+                                            format!("Diff: {}", repo),
+                                            lines,
+                                        ));
+                                    }
+                                    Err(e) => state.set_error(format!("Failed to read diff: {}", e)),
+                                },
+                                None => state.set_error("Selected agent is not in a git repository".to_string()),
+                            }
+                        }
+                        Action::ViewerDown(n) => {
+                            if let Some(viewer) = &mut state.viewer {
+                                viewer.scroll_down(n);
+                            }
+                        }
+                        Action::ViewerUp(n) => {
+                            if let Some(viewer) = &mut state.viewer {
+                                viewer.scroll_up(n);
+                            }
+                        }
+                        Action::ViewerTop => {
+                            if let Some(viewer) = &mut state.viewer {
+                                viewer.scroll = 0;
+                            }
+                        }
+                        Action::ViewerBottom => {
+                            if let Some(viewer) = &mut state.viewer {
+                                viewer.scroll_to_end();
+                            }
+                        }
+                        Action::CloseViewer => {
+                            state.close_viewer();
+                        }
+                        Action::ViewerMark => {
+                            if let Some(viewer) = &mut state.viewer {
+                                viewer.toggle_mark();
+                            }
+                        }
+                        Action::ViewerYank => {
+                            if let Some(viewer) = &mut state.viewer {
+                                let text = viewer.selected_text();
+                                let count = text.lines().count();
+                                viewer.mark = None;
+                                match clipboard::copy(tmux_client, &text) {
+                                    Ok(()) => state.set_message(format!("Copied {} lines", count)),
+                                    Err(e) => state.set_error(format!("Failed to copy: {}", e)),
+                                }
+                            }
+                        }
+                        Action::ViewerSearch => {
+                            let query = state.viewer.as_ref().and_then(|v| v.search.clone());
+                            state.open_prompt_with(PromptKind::ViewerSearch, query.unwrap_or_default());
+                        }
+                        Action::ViewerFindNext { forward } => {
+                            if let Some(viewer) = &mut state.viewer {
+                                if let Some(search) = viewer.search.clone() {
+                                    if !viewer.find_next(forward) {
+                                        state.set_message(format!("No match for {:?}", search));
+                                    }
                                 }
-                                // Stay in input mode for consecutive inputs
-                            }
-                            Action::SendNumber(num) => {
-                                if let Some(agent) = state.selected_agent() {
-                                    let target = agent.target.clone();
-                                    let num_str = num.to_string();
-                                    if let Err(e) = tmux_client.send_keys(&target, &num_str) {
-                                        state.set_error(format!("Failed to send number: {}", e));
-                                    } else if let Err(e) = tmux_client.send_keys(&target, "Enter") {
-                                        state.set_error(format!("Failed to send Enter: {}", e));
+                            }
+                        }
+                        Action::ShowScrollback => {
+                            if let Some(agent) = state.selected_agent() {
+                                let client = tmux_client.for_server(&agent.server);
+                                match client.capture_history(&agent.target, SCROLLBACK_LINES) {
+                                    Ok(history) => {
+                                        let mut lines: Vec<String> =
+                                            history.lines().map(str::to_string).collect();
+                                        // The unused rest of the screen
+                                        while lines.last().is_some_and(|l| l.trim().is_empty()) {
+                                            lines.pop();
+                                        }
+                                        let viewer = Viewer::new(
+                                            ViewerKind::Scrollback,
+                                            format!("History: {} ({} lines)", agent.key(), lines.len()),
+                                            lines,
+                                        );
+                                        state.open_viewer(viewer);
+                                        if let Some(viewer) = &mut state.viewer {
+                                            viewer.scroll_to_end();
+                                        }
                                     }
+                                    Err(e) => state.set_error(format!("Failed to capture history: {}", e)),
                                 }
                             }
-                            Action::SidebarWider => {
-                                state.sidebar_width = (state.sidebar_width + 5).min(70);
+                        }
+                        Action::ShowOutput => {
+                            if let Some(agent) = state.selected_agent() {
+                                let viewer = Viewer::new(
+                                    ViewerKind::Output,
+                                    format!("Output: {}", agent.key()),
+                                    agent.last_content.lines().map(str::to_string).collect(),
+                                );
+                                state.open_viewer(viewer);
+                                if let Some(viewer) = &mut state.viewer {
+                                    viewer.scroll_to_end();
+                                }
                             }
-                            Action::SidebarNarrower => {
-                                state.sidebar_width = state.sidebar_width.saturating_sub(5).max(15);
+                        }
+                        Action::ToggleAutomation => {
+                            if let Some(agent) = state.selected_agent() {
+                                let key = agent.key();
+                                state.toggle_automation(&key);
+                                let off = state.automation_disabled.contains(&key);
+                                monitor.sync_settings(state);
+                                let status = if off { "off" } else { "on" };
+                                state.set_message(format!("Auto-answers {} for {}", status, key));
                             }
-                            Action::SelectAgent(idx) => {
-                                state.select_agent(idx);
+                        }
+                        Action::ToggleAutomationPause => {
+                            state.automation_paused = !state.automation_paused;
+                            monitor.sync_settings(state);
+                            let status = if state.automation_paused { "paused" } else { "resumed" };
+                            state.set_message(format!("Auto-answers {}", status));
+                        }
+                        Action::ShowUsage => {
+                            let lines = match monitor.usage_report() {
+                                Ok(lines) => lines,
+                                Err(e) => {
+                                    state.set_error(format!("Failed to get usage: {}", e));
+                                    continue;
+                                }
+                            };
+                            state.open_viewer(Viewer::new(
+                                ViewerKind::Output,
+                                "Token usage and cost".to_string(),
+                                lines,
+                            ));
+                        }
+                        Action::YankOutput => {
+                            if let Some(agent) = state.selected_agent() {
+                                let text = agent.last_content.trim_end().to_string();
+                                let key = agent.key();
+                                match clipboard::copy(tmux_client, &text) {
+                                    Ok(()) => state.set_message(format!("Copied output of {}", key)),
+                                    Err(e) => state.set_error(format!("Failed to copy: {}", e)),
+                                }
                             }
-                            Action::ScrollUp => {
-                                state.select_prev();
+                        }
+                        Action::ToggleGroupByRepo => {
+                            state.toggle_group_by_repo();
+                        }
+                        Action::ToggleCollapseGroup => {
+                            state.toggle_collapse_selected_group();
+                        }
+                        Action::CycleSort => {
+                            state.cycle_sort();
+                            state.set_message(format!("Sorted by {}", state.sort.label()));
+                            save_prefs(state, config);
+                        }
+                        Action::TogglePin => {
+                            if let Some(pinned) = state.toggle_pin() {
+                                let key = state.selected_agent().map(|a| a.key()).unwrap_or_default();
+                                let verb = if pinned { "Pinned" } else { "Unpinned" };
+                                state.set_message(format!("{} {}", verb, key));
+                                save_prefs(state, config);
                             }
-                            Action::ScrollDown => {
-                                state.select_next();
+                        }
+                        Action::SelectServerTab(tab) => {
+                            state.select_server_tab(tab);
+                        }
+                        Action::ToggleCompactList => {
+                            state.compact_list = !state.compact_list;
+                            save_prefs(state, config);
+                        }
+                        Action::CreatePullRequest => {
+                            let target = state.selected_agent().and_then(|agent| {
+                                let git = agent.git.as_ref()?;
+                                Some((agent.status.clone(), agent.path.clone(), git.clone()))
+                            });
+                            match target {
+                                Some((status, path, git)) if git.can_open_pr() => {
+                                    if !matches!(status, AgentStatus::Idle) {
+                                        state.set_error("Agent is still working".to_string());
+                                    } else {
+                                        let base = git
+                                            .default_branch
+                                            .clone()
+                                            .unwrap_or_else(|| "main".to_string());
+                                        state.set_message(format!("Creating PR for {}...", git.branch));
+                                        let tx = bg_tx.clone();
+                                        tokio::task::spawn_blocking(move || {
+                                            let result = git::create_pull_request(&path, &git.branch, &base)
+                                                .map(|url| format!("Opened {}", url))
+                                                .map_err(|e| format!("{}", e));
+                                            let _ = tx.send(result);
+                                        });
+                                    }
+                                }
+                                Some(_) => state.set_error(
+                                    "No PR can be opened from this branch".to_string(),
+                                ),
+                                None => state.set_error("Selected agent is not in a git repository".to_string()),
                             }
-                            Action::None => {}
                         }
+                        // Resolved to the chosen action above
+                        Action::PaletteConfirm | Action::None => {}
                     }
                 }
             }
@@ -468,10 +1152,490 @@ async fn run_loop(
     Ok(())
 }
 
-fn map_key_to_action(code: KeyCode, modifiers: KeyModifiers, state: &AppState) -> Action {
-    // If help is shown, any key closes it
+/// Reads the config at `path` again with its keymap and theme; nothing of
+/// it is used if any part is invalid
+fn reload_config(path: &Path, current: &Config) -> Result<(Config, Keymap, Theme, MonitorReload)> {
+    let (config, reload) = monitoring::reload_config(path, current)?;
+    let keymap = Keymap::from_config(&config.keys)?;
+    let theme = Theme::from_config(&config.theme)?;
+    Ok((config, keymap, theme, reload))
+}
+
+/// Returns `error` and its causes on one line for the status bar, without
+/// the source excerpt of TOML errors
+fn one_line(error: &anyhow::Error) -> String {
+    let text = format!("{:#}", error);
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.contains('|'))
+        .collect();
+    lines.join(" ")
+}
+
+/// Binds the configured return key to switch back to this pane
+///
+/// Only applies when tmuxcc runs inside tmux. Returns the bound key so it
+/// can be unbound on exit.
+fn install_return_key(tmux_client: &TmuxClient, config: &Config) -> Option<String> {
+    let key = config.return_key.trim();
+    if key.is_empty() || !TmuxClient::is_inside_tmux() {
+        return None;
+    }
+    let pane = std::env::var("TMUX_PANE").ok()?;
+    tmux_client
+        .bind_key(key, &["switch-client", "-t", &pane])
+        .ok()
+        .map(|()| key.to_string())
+}
+
+/// Brings the pane at `target` into view
+///
+/// Inside tmux the current client is switched to the pane. Outside tmux, or
+/// for panes on another server, the dashboard hands the terminal to
+/// `tmux attach-session` and resumes once the user detaches.
+fn jump_to_pane(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    tmux_client: &TmuxClient,
+    target: &str,
+) -> Result<()> {
+    if TmuxClient::is_inside_tmux() && tmux_client.server().is_default() {
+        return tmux_client.switch_client(target);
+    }
+
+    tmux_client.focus_pane(target)?;
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    let result = tokio::task::block_in_place(|| tmux_client.attach_session(target));
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
+    terminal.clear()?;
+    result
+}
+
+/// Types `text` into the agent's pane and submits it
+fn send_prompt(tmux_client: &TmuxClient, agent: &MonitoredAgent, text: &str) -> Result<()> {
+    let client = tmux_client.for_server(&agent.server);
+    client.send_text(&agent.target, text, SendMode::for_text(text))?;
+    client.send_keys(&agent.target, "Enter")
+}
+
+/// Sends `text` to the agent at `key` once it is idle, right away if it is
+/// idle with nothing queued
+fn queue_prompt(state: &mut AppState, tmux_client: &TmuxClient, key: &str, text: &str) {
+    let Some(agent) = state.agents.root_agents.iter().find(|a| a.key() == key) else {
+        return;
+    };
+    // An idle agent won't report becoming idle, so it gets the prompt now
+    if agent.state == Some(AgentState::Idle) && state.queued(key).next().is_none() {
+        if let Err(e) = send_prompt(tmux_client, agent, text) {
+            state.set_error(format!("Failed to send: {}", e));
+        }
+    } else {
+        state.enqueue_prompt(key.to_string(), text.to_string());
+        let count = state.queued(key).count();
+        state.set_message(format!("Queued ({} waiting)", count));
+    }
+}
+
+/// Refreshes an open queue picker after its queue changed, highlighting `selected`
+fn sync_queue_picker(state: &mut AppState, key: &str, selected: usize) {
+    let items: Vec<String> = state.queued(key).cloned().collect();
+    if items.is_empty() {
+        state.take_picker();
+    } else if let Some(picker) = state.picker.as_mut() {
+        picker.selected = selected.min(items.len() - 1);
+        picker.items = items;
+    }
+}
+
+/// Sends `text` followed by Enter to the input targets
+///
+/// In broadcast mode every selected pane gets the text; failures are
+/// collected so one dead pane doesn't stop the rest. Returns true if every
+/// target got it.
+fn send_to_targets(
+    state: &mut AppState,
+    tmux_client: &TmuxClient,
+    text: &str,
+    mode: SendMode,
+) -> bool {
+    let targets = state.input_targets();
+    let mut failed = Vec::new();
+    for idx in &targets {
+        let Some(agent) = state.agents.get_agent(*idx) else {
+            continue;
+        };
+        let client = tmux_client.for_server(&agent.server);
+        let result = client
+            .send_text(&agent.target, text, mode)
+            .and_then(|()| client.send_keys(&agent.target, "Enter"));
+        if let Err(e) = result {
+            failed.push(format!("{}: {}", agent.key(), e));
+        }
+    }
+    if !failed.is_empty() {
+        state.set_error(format!("Failed to send to {}", failed.join(", ")));
+    } else if targets.len() > 1 {
+        state.set_message(format!("Sent to {} panes", targets.len()));
+    }
+    failed.is_empty() && !targets.is_empty()
+}
+
+/// Carries out a confirmed destructive action
+fn run_confirmed(state: &mut AppState, confirm: Confirm, tmux_client: &TmuxClient) {
+    match confirm {
+        Confirm::KillPane { server, target } => {
+            match tmux_client.for_server(&server).kill_pane(&target) {
+                Ok(()) => state.set_message(format!("Killed {}", target)),
+                Err(e) => state.set_error(format!("Failed to kill pane: {}", e)),
+            }
+        }
+        Confirm::RespawnPane {
+            server,
+            target,
+            cwd,
+            launch,
+        } => match spawn::respawn_agent(&tmux_client.for_server(&server), &target, &cwd, &launch) {
+            Ok(()) => state.set_message(format!("Restarted {}", target)),
+            Err(e) => state.set_error(format!("Failed to restart agent: {}", e)),
+        },
+    }
+}
+
+/// Applies the search being typed, keeping the last valid one while a
+/// qualifier is incomplete
+fn update_search(state: &mut AppState) {
+    let query = match &state.prompt {
+        Some(prompt) if prompt.kind == PromptKind::Search => prompt.buffer.clone(),
+        _ => return,
+    };
+    let _ = state.set_search(&query);
+}
+
+/// Handles the text entered in a prompt popup
+fn submit_prompt(
+    state: &mut AppState,
+    kind: PromptKind,
+    text: &str,
+    tmux_client: &TmuxClient,
+    config: &Config,
+    monitor: &Monitor,
+) {
+    let clear = text.is_empty();
+    if clear
+        && !matches!(
+            kind,
+            PromptKind::Label { .. }
+                | PromptKind::Note { .. }
+                | PromptKind::Search
+                | PromptKind::ViewerSearch
+        )
+    {
+        return;
+    }
+    let value = (!clear).then(|| text.to_string());
+    match kind {
+        PromptKind::Label { id } => {
+            if let Err(e) = monitor.set_label(&id, value.clone()) {
+                state.set_error(format!("Failed to set the label: {}", e));
+            }
+            // Shown right away; the next poll reads it back from the registry
+            if let Some(agent) = state.agents.get_agent_by_id_mut(&id) {
+                agent.label = value;
+            }
+        }
+        PromptKind::Queue { key } => queue_prompt(state, tmux_client, &key, text),
+        PromptKind::BroadcastFilter => match AgentFilter::parse(text) {
+            Ok(filter) => match state.select_matching(&filter) {
+                0 => state.set_message(format!("No panes match {:?}", text)),
+                count => {
+                    state.focus_input();
+                    state.set_message(format!("Broadcasting to {} panes", count));
+                }
+            },
+            Err(e) => state.set_error(format!("Invalid filter: {}", e)),
+        },
+        PromptKind::Search => {
+            if let Err(e) = state.set_search(text) {
+                state.set_error(format!("Invalid search: {}", e));
+            }
+        }
+        PromptKind::ViewerSearch => {
+            if let Some(viewer) = &mut state.viewer {
+                if !viewer.set_search(text) {
+                    state.set_message(format!("No match for {:?}", text));
+                }
+            }
+        }
+        PromptKind::Note { id } => {
+            if let Err(e) = monitor.set_note(&id, value.clone()) {
+                state.set_error(format!("Failed to set the note: {}", e));
+            }
+            if let Some(agent) = state.agents.get_agent_by_id_mut(&id) {
+                agent.note = value;
+            }
+        }
+        PromptKind::WorktreeBranch => {
+            let Some(agent) = state.selected_agent() else {
+                return;
+            };
+            let repo = agent.path.clone();
+            let session = agent.session.clone();
+            match spawn::spawn_worktree_agent(
+                &tmux_client.for_server(&agent.server),
+                &repo,
+                text,
+                Some(&session),
+                &config.agent_command,
+            ) {
+                Ok((path, target)) => {
+                    state.set_message(format!("Worktree {} → {}", path.display(), target));
+                }
+                Err(e) => state.set_error(format!("Failed to create worktree: {}", e)),
+            }
+        }
+        PromptKind::AgentCwd { placement } => {
+            let kind = PromptKind::AgentCommand {
+                placement,
+                cwd: text.to_string(),
+            };
+            state.open_prompt_with(kind, config.agent_command.clone());
+        }
+        PromptKind::AgentCommand { placement, cwd } => {
+            match spawn::launch_agent(tmux_client, &placement, &cwd, text) {
+                Ok(target) => state.set_message(format!("Launched {} in {}", text, target)),
+                Err(e) => state.set_error(format!("Failed to launch agent: {}", e)),
+            }
+        }
+    }
+}
+
+/// Builds the placement picker for a new agent
+///
+/// Offers a window in every session, splits of the selected agent's pane
+/// and a new session, all on the default server; splits are only offered
+/// for agents on that server. The working directory defaults to the
+/// selected agent's, or tmuxcc's own when nothing is selected.
+fn new_agent_picker(state: &AppState, tmux_client: &TmuxClient) -> Result<Picker> {
+    let tmux_tree = tmux_client.list_tree()?;
+    let selected = state.selected_agent();
+
+    let mut placements: Vec<Placement> = tmux_tree
+        .sessions
+        .iter()
+        .map(|s| Placement::Window {
+            session: Some(s.name.clone()),
+            name: None,
+        })
+        .collect();
+    // Put the selected agent's session first
+    if let Some(agent) = selected.filter(|a| a.server.is_default()) {
+        if let Some(pos) = placements.iter().position(
+            |p| matches!(p, Placement::Window { session: Some(s), .. } if *s == agent.session),
+        ) {
+            let placement = placements.remove(pos);
+            placements.insert(0, placement);
+        }
+        for vertical in [false, true] {
+            placements.push(Placement::Split {
+                target: agent.target.clone(),
+                vertical,
+            });
+        }
+    }
+    placements.push(Placement::Session { name: None });
+
+    let cwd = match selected {
+        Some(agent) => agent.path.clone(),
+        None => std::env::current_dir()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    };
+    let items = placements.iter().map(Placement::label).collect();
+    Ok(Picker::new(
+        PickerKind::NewAgent { placements, cwd },
+        items,
+        None,
+    ))
+}
+
+/// Builds the diff viewer content: unstaged changes followed by staged ones
+fn diff_lines(repo: &str) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+    for (staged, header) in [
+        (false, "══ Unstaged changes ══"),
+        (true, "══ Staged changes ══"),
+    ] {
+        let diff = git::get_diff(repo, staged)?;
+        if diff.trim().is_empty() {
+            continue;
+        }
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(header.to_string());
+        lines.extend(diff.lines().map(|l| l.to_string()));
+    }
+    if lines.is_empty() {
+        lines.push("No changes".to_string());
+    }
+    Ok(lines)
+}
+
+/// Applies the item chosen in a picker popup
+fn confirm_picker(state: &mut AppState, picker: Picker, force: bool) {
+    let Some(item) = picker.selected_item() else {
+        return;
+    };
+    match &picker.kind {
+        PickerKind::Branch { repo } => {
+            if picker.current == Some(picker.selected) {
+                return;
+            }
+            match git::checkout_branch(repo, item, force) {
+                Ok(()) => state.set_message(format!("Checked out {}", item)),
+                Err(e) => state.set_error(format!("{}", e)),
+            }
+        }
+        // Reordering happens in place, confirming just closes the picker
+        PickerKind::Queue { .. } => {}
+        // Opened entries are handled by the caller, which can jump to the pane
+        PickerKind::Notifications => {}
+        PickerKind::NewAgent { placements, cwd } => {
+            if let Some(placement) = placements.get(picker.selected) {
+                let kind = PromptKind::AgentCwd {
+                    placement: placement.clone(),
+                };
+                state.open_prompt_with(kind, cwd.clone());
+            }
+        }
+    }
+}
+
+/// Remembers the pane list settings and the layout for the next run
+fn save_prefs(state: &mut AppState, config: &Config) {
+    let Some(path) = UiPrefs::default_path() else {
+        return;
+    };
+    let prefs = UiPrefs {
+        sort: state.sort,
+        compact: state.compact_list,
+        pinned: state.pinned.clone(),
+        // An unchanged width keeps following the config
+        sidebar_width: (state.sidebar_width != AppState::clamp_sidebar_width(config.sidebar_width))
+            .then_some(state.sidebar_width),
+        group_by_repo: state.group_by_repo,
+        collapsed: state.collapsed_groups.iter().cloned().collect(),
+        search: state.search.as_ref().map(|s| s.query.clone()),
+        selected: state.selected_agent().map(|a| a.key()),
+        muted: state.muted,
+    };
+    if let Err(e) = prefs.save(&path) {
+        state.set_error(format!("Failed to save list settings: {}", e));
+    }
+}
+
+fn map_key_to_action(
+    code: KeyCode,
+    modifiers: KeyModifiers,
+    state: &AppState,
+    keymap: &mut Keymap,
+) -> Action {
+    // If help is shown, scroll keys move it and any other key closes it
     if state.show_help {
-        return Action::HideHelp;
+        return match code {
+            KeyCode::Up | KeyCode::Char('k') => Action::HelpUp(1),
+            KeyCode::Down | KeyCode::Char('j') => Action::HelpDown(1),
+            KeyCode::PageUp => Action::HelpUp(VIEWER_PAGE),
+            KeyCode::PageDown | KeyCode::Char(' ') => Action::HelpDown(VIEWER_PAGE),
+            _ => Action::HideHelp,
+        };
+    }
+
+    // Confirmation popup: y confirms, anything else cancels
+    if state.confirm.is_some() {
+        return match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => Action::ConfirmYes,
+            _ => Action::ConfirmNo,
+        };
+    }
+
+    // Command palette captures all keys while open
+    if state.palette.is_some() {
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        return match code {
+            KeyCode::Esc => Action::PaletteCancel,
+            KeyCode::Enter => Action::PaletteConfirm,
+            KeyCode::Backspace => Action::PaletteBackspace,
+            KeyCode::Down | KeyCode::Tab => Action::PaletteNext,
+            KeyCode::Up | KeyCode::BackTab => Action::PalettePrev,
+            KeyCode::Char('n') if ctrl => Action::PaletteNext,
+            KeyCode::Char('p') if ctrl => Action::PalettePrev,
+            KeyCode::Char(c) if !ctrl => Action::PaletteChar(c),
+            _ => Action::None,
+        };
+    }
+
+    // Prompt popup captures all keys while open
+    if state.prompt.is_some() {
+        return match code {
+            KeyCode::Esc => Action::PromptCancel,
+            KeyCode::Enter => Action::PromptSubmit,
+            KeyCode::Backspace => Action::PromptBackspace,
+            KeyCode::Char(c) => Action::PromptChar(c),
+            _ => Action::None,
+        };
+    }
+
+    // Picker popup captures all keys while open
+    if state.picker.is_some() {
+        return match code {
+            KeyCode::Esc | KeyCode::Char('q') => Action::PickerCancel,
+            KeyCode::Enter => Action::PickerConfirm { force: false },
+            KeyCode::Char('F') => Action::PickerConfirm { force: true },
+            KeyCode::Char('j') | KeyCode::Down | KeyCode::Tab => Action::PickerNext,
+            KeyCode::Char('k') | KeyCode::Up => Action::PickerPrev,
+            KeyCode::Char('J') => Action::PickerMove { later: true },
+            KeyCode::Char('K') => Action::PickerMove { later: false },
+            KeyCode::Char('x') | KeyCode::Delete => Action::PickerRemove,
+            KeyCode::Char('r') => Action::PickerMarkRead { all: false },
+            KeyCode::Char('R') => Action::PickerMarkRead { all: true },
+            _ => Action::None,
+        };
+    }
+
+    // Viewer captures all keys while open
+    if state.viewer.is_some() {
+        return match code {
+            KeyCode::Esc | KeyCode::Char('q') => Action::CloseViewer,
+            KeyCode::Char('j') | KeyCode::Down => Action::ViewerDown(1),
+            KeyCode::Char('k') | KeyCode::Up => Action::ViewerUp(1),
+            KeyCode::Char('d') if modifiers.contains(KeyModifiers::CONTROL) => {
+                Action::ViewerDown(VIEWER_PAGE)
+            }
+            KeyCode::Char('u') if modifiers.contains(KeyModifiers::CONTROL) => {
+                Action::ViewerUp(VIEWER_PAGE)
+            }
+            KeyCode::PageDown | KeyCode::Char(' ') => Action::ViewerDown(VIEWER_PAGE),
+            KeyCode::PageUp => Action::ViewerUp(VIEWER_PAGE),
+            KeyCode::Char('g') | KeyCode::Home => Action::ViewerTop,
+            KeyCode::Char('G') | KeyCode::End => Action::ViewerBottom,
+            KeyCode::Char('v') => Action::ViewerMark,
+            KeyCode::Char('y') => Action::ViewerYank,
+            KeyCode::Char('/') => Action::ViewerSearch,
+            KeyCode::Char('n') => Action::ViewerFindNext { forward: true },
+            KeyCode::Char('N') => Action::ViewerFindNext { forward: false },
+            _ => Action::None,
+        };
     }
 
     // If input panel is focused, handle input-specific keys
@@ -494,57 +1658,42 @@ fn map_key_to_action(code: KeyCode, modifiers: KeyModifiers, state: &AppState) -
         };
     }
 
-    // Sidebar focused
+    // Sidebar focused: keys with fixed meanings first, then the keymap
     match code {
-        KeyCode::Char('q') => Action::Quit,
-        KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => Action::Quit,
-
-        KeyCode::Char('j') | KeyCode::Down => Action::NextAgent,
-        KeyCode::Char('k') | KeyCode::Up => Action::PrevAgent,
-        KeyCode::Tab => Action::NextAgent,
-
-        // Left/Right arrows for focus navigation
-        KeyCode::Right => Action::FocusInput,
-        KeyCode::Left => Action::None, // Already on sidebar
-
-        // Multi-selection
-        KeyCode::Char(' ') => Action::ToggleSelection,
-        KeyCode::Char('a') if modifiers.contains(KeyModifiers::CONTROL) => Action::SelectAll,
-
-        // Approval
-        KeyCode::Char('y') | KeyCode::Char('Y') => Action::Approve,
-        KeyCode::Char('n') | KeyCode::Char('N') => Action::Reject,
-        KeyCode::Char('a') | KeyCode::Char('A') => Action::ApproveAll,
+        KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return Action::Quit,
 
         // Number keys for quick choice selection (1-9)
-        KeyCode::Char(c @ '1'..='9') => {
+        KeyCode::Char(c @ '1'..='9') if modifiers.is_empty() => {
+            keymap.reset();
             let num = c.to_digit(10).unwrap() as u8;
-            Action::SendNumber(num)
+            return Action::SendNumber(num);
         }
 
-        // Focus pane with 'f'
-        KeyCode::Char('f') | KeyCode::Char('F') => Action::FocusPane,
-
-        KeyCode::Char('s') | KeyCode::Char('S') => Action::ToggleSubagentLog,
-        KeyCode::Char('t') | KeyCode::Char('T') => Action::ToggleSummaryDetail,
-        KeyCode::Char('r') => Action::Refresh,
-
-        // Sidebar resize (only < and >)
-        KeyCode::Char('<') => Action::SidebarNarrower,
-        KeyCode::Char('>') => Action::SidebarWider,
-
-        KeyCode::Char('h') | KeyCode::Char('?') => Action::ShowHelp,
+        // Alt+number switches server tabs, Alt+0 shows all servers
+        KeyCode::Char(c @ '0'..='9')
+            if modifiers == KeyModifiers::ALT && state.servers.len() > 1 =>
+        {
+            keymap.reset();
+            return Action::SelectServerTab(c.to_digit(10).unwrap() as usize);
+        }
 
         KeyCode::Esc => {
-            if !state.selected_agents.is_empty() {
+            keymap.reset();
+            return if !state.selected_agents.is_empty() {
                 Action::ClearSelection
             } else if state.show_subagent_log {
                 Action::ToggleSubagentLog
+            } else if state.search.is_some() {
+                Action::ClearSearch
             } else {
                 Action::None
-            }
+            };
         }
 
-        _ => Action::None,
+        _ => {}
+    }
+    match keymap.press(code, modifiers) {
+        KeyMatch::Action(action) => action,
+        KeyMatch::Pending | KeyMatch::Unbound => Action::None,
     }
 }
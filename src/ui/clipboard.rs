@@ -0,0 +1,74 @@
+use std::io::{self, Write};
+
+use anyhow::Result;
+
+use crate::tmux::TmuxClient;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes `data` as standard padded base64
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Builds the OSC 52 sequence that sets the terminal's clipboard to `text`
+fn osc52_sequence(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64_encode(text.as_bytes()))
+}
+
+/// Copies `text` into a tmux paste buffer and the system clipboard
+///
+/// Inside tmux, tmux forwards the buffer to the outer terminal itself
+/// (`load-buffer -w`), older versions without `-w` only get the buffer.
+/// Outside tmux the OSC 52 sequence is written to the terminal directly, which
+/// also reaches the local clipboard over SSH; the buffer is then best effort.
+pub fn copy(tmux_client: &TmuxClient, text: &str) -> Result<()> {
+    if TmuxClient::is_inside_tmux() {
+        return tmux_client
+            .set_buffer(text, true)
+            .or_else(|_| tmux_client.set_buffer(text, false));
+    }
+
+    let _ = tmux_client.set_buffer(text, false);
+    let mut stdout = io::stdout();
+    stdout.write_all(osc52_sequence(text).as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_encode("✓\n".as_bytes()), "4pyTCg==");
+    }
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(osc52_sequence("hi"), "\x1b]52;c;aGk=\x07");
+    }
+}
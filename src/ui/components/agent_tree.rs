@@ -2,18 +2,31 @@ use std::collections::BTreeMap;
 
 use ratatui::{
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, List, ListItem, ListState},
     Frame,
 };
 
 use crate::agents::{AgentStatus, AgentType, ApprovalType, MonitoredAgent, SubagentStatus};
-use crate::app::AppState;
+use crate::app::{AppState, SortMode, PINNED_GROUP};
+use crate::git::CiState;
+use crate::tmux::TmuxServer;
+use crate::ui::{icons, theme, Styles};
 
 /// Widget for displaying agents in a tree organized by session/window
 pub struct AgentTreeWidget;
 
+/// What a line of the rendered agent list belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeRow {
+    /// A line of the agent at this index
+    Agent(usize),
+    /// The header of a session or repository group, with the index of its
+    /// first agent
+    Group(usize),
+}
+
 /// Type alias for window key (window number, window name)
 type WindowKey<'a> = (u32, &'a str);
 
@@ -23,8 +36,8 @@ type WindowAgents<'a> = Vec<(usize, &'a MonitoredAgent)>;
 /// Type alias for windows map
 type WindowsMap<'a> = BTreeMap<WindowKey<'a>, WindowAgents<'a>>;
 
-/// Type alias for sessions map
-type SessionsMap<'a> = BTreeMap<&'a str, WindowsMap<'a>>;
+/// Type alias for sessions map, keyed by server and session name
+type SessionsMap<'a> = BTreeMap<(&'a TmuxServer, &'a str), WindowsMap<'a>>;
 
 /// Represents the hierarchical structure: Session -> Window -> Agents
 struct SessionWindowTree<'a> {
@@ -32,12 +45,16 @@ struct SessionWindowTree<'a> {
 }
 
 impl<'a> SessionWindowTree<'a> {
-    fn new(agents: &'a [MonitoredAgent]) -> Self {
+    /// Builds the tree of the agents matching the search
+    fn new(state: &'a AppState) -> Self {
         let mut sessions: SessionsMap<'a> = BTreeMap::new();
 
-        for (idx, agent) in agents.iter().enumerate() {
+        let agents = state.agents.root_agents.iter().enumerate();
+        // Pinned agents are listed above the tree
+        let listed = |idx: usize| state.is_visible(idx) && !state.is_pinned(idx);
+        for (idx, agent) in agents.filter(|(idx, _)| listed(*idx)) {
             sessions
-                .entry(&agent.session)
+                .entry((&agent.server, &agent.session))
                 .or_default()
                 .entry((agent.window, &agent.window_name))
                 .or_default()
@@ -49,14 +66,19 @@ impl<'a> SessionWindowTree<'a> {
 }
 
 impl AgentTreeWidget {
-    pub fn render(frame: &mut Frame, area: Rect, state: &AppState) {
+    /// Renders the agent list and returns the screen row of each visible
+    /// line that belongs to an agent or a repository group
+    pub fn render(frame: &mut Frame, area: Rect, state: &AppState) -> Vec<(u16, TreeRow)> {
         let agents = &state.agents.root_agents;
         let active_count = state.agents.active_count();
         let subagent_count = state.agents.running_subagent_count();
         let selected_count = state.selected_agents.len();
 
         // Build title
-        let title = if selected_count > 0 {
+        let title = if let Some(search) = &state.search {
+            let matching = (0..agents.len()).filter(|&i| state.is_visible(i)).count();
+            format!(" /{} │ {} of {} ", search.query, matching, agents.len())
+        } else if selected_count > 0 {
             format!(" {} sel │ {} pending ", selected_count, active_count)
         } else if subagent_count > 0 {
             format!(" {} pending │ {} subs ", active_count, subagent_count)
@@ -65,11 +87,16 @@ impl AgentTreeWidget {
         } else {
             format!(" {} agents ", agents.len())
         };
+        let title = if state.sort == SortMode::Tmux {
+            title
+        } else {
+            format!("{}│ by {} ", title, state.sort.label())
+        };
 
         let border_color = if !state.is_input_focused() {
-            Color::Cyan
+            theme().accent
         } else {
-            Color::Gray
+            theme().muted
         };
 
         let block = Block::default()
@@ -81,29 +108,76 @@ impl AgentTreeWidget {
         if agents.is_empty() {
             let empty_text = List::new(vec![ListItem::new(Line::from(vec![Span::styled(
                 "  No agents detected",
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme().dim),
             )]))])
             .block(block);
             frame.render_widget(empty_text, area);
-            return;
+            return Vec::new();
+        }
+
+        if state.navigable_agents().is_empty() {
+            let empty_text = List::new(vec![ListItem::new(Line::from(vec![Span::styled(
+                "  No panes match the search",
+                Style::default().fg(theme().dim),
+            )]))])
+            .block(block);
+            frame.render_widget(empty_text, area);
+            return Vec::new();
         }
 
-        let tree = SessionWindowTree::new(agents);
-        let mut items: Vec<ListItem> = Vec::new();
         let available_width = area.width.saturating_sub(4) as usize;
+        // Sorted sessions have no window order to show, so they list like repositories
+        if state.group_by_repo || state.sort != SortMode::Tmux {
+            let (items, rows, cursor_item) = Self::group_items(state, available_width);
+            let list = List::new(items).block(block);
+            // Selecting the cursor's first line keeps it scrolled into view
+            let mut list_state = ListState::default().with_selected(cursor_item);
+            frame.render_stateful_widget(list, area, &mut list_state);
+            return screen_rows(area, &rows, list_state.offset());
+        }
 
-        for (session, windows) in tree.sessions.iter() {
-            // Session header
-            let session_line = Line::from(vec![
-                Span::styled("▼ ", Style::default().fg(Color::Cyan)),
-                Span::styled(
-                    *session,
-                    Style::default()
-                        .fg(Color::Cyan)
-                        .add_modifier(Modifier::BOLD),
-                ),
-            ]);
-            items.push(ListItem::new(session_line));
+        let tree = SessionWindowTree::new(state);
+        let mut items: Vec<ListItem> = Vec::new();
+        let mut rows: Vec<Option<TreeRow>> = Vec::new();
+        let mut cursor_item = None;
+
+        if let Some((key, indices)) = state
+            .visible_groups()
+            .into_iter()
+            .find(|(key, _)| key == PINNED_GROUP)
+        {
+            Self::push_group(
+                &mut items,
+                &mut rows,
+                &mut cursor_item,
+                state,
+                key,
+                &indices,
+                available_width,
+            );
+        }
+
+        for ((server, session), windows) in tree.sessions.iter() {
+            // Session header, prefixed with the server unless it's the default
+            let key = server.qualify(session);
+            let indices: Vec<usize> = windows.values().flatten().map(|(i, _)| *i).collect();
+            let collapsed = state.collapsed_groups.contains(&key);
+            let has_cursor = indices.contains(&state.selected_index);
+            if collapsed && has_cursor {
+                cursor_item = Some(items.len());
+            }
+            items.push(group_header(
+                state,
+                key,
+                &indices,
+                collapsed,
+                collapsed && has_cursor,
+            ));
+            rows.push(Some(TreeRow::Group(indices[0])));
+
+            if collapsed {
+                continue;
+            }
 
             for (window_idx, ((window_num, window_name), window_agents)) in
                 windows.iter().enumerate()
@@ -115,308 +189,706 @@ impl AgentTreeWidget {
                 let window_line = Line::from(vec![
                     Span::styled(
                         format!(" {} ", window_prefix),
-                        Style::default().fg(Color::DarkGray),
+                        Style::default().fg(theme().dim),
                     ),
                     Span::styled(
                         format!("{}: {}", window_num, window_name),
-                        Style::default().fg(Color::White),
+                        Style::default().fg(theme().text),
                     ),
                 ]);
                 items.push(ListItem::new(window_line));
 
                 for (agent_idx, (original_idx, agent)) in window_agents.iter().enumerate() {
-                    let is_cursor = *original_idx == state.selected_index;
-                    let is_selected = state.is_multi_selected(*original_idx);
                     let is_last_agent = agent_idx == window_agents.len() - 1;
 
                     let cont_prefix = if is_last_window { "    " } else { " │  " };
+                    let ends_branch = is_last_agent && !shows_subagents(state, agent);
 
                     let tree_prefix = if is_last_window {
-                        if is_last_agent && agent.subagents.is_empty() {
+                        if ends_branch {
                             "    └─"
                         } else {
                             "    ├─"
                         }
-                    } else if is_last_agent && agent.subagents.is_empty() {
+                    } else if ends_branch {
                         " │  └─"
                     } else {
                         " │  ├─"
                     };
 
-                    let select_indicator = if is_selected && is_cursor {
-                        "┃☑" // カーソル+選択: 縦線とチェック
-                    } else if is_selected {
-                        " ☑" // 選択のみ: チェック
-                    } else if is_cursor {
-                        "┃ " // カーソルのみ: 縦線
-                    } else {
-                        "  "
-                    };
+                    if *original_idx == state.selected_index {
+                        cursor_item = Some(items.len());
+                    }
+                    rows.resize(items.len(), None);
+                    push_agent_items(
+                        &mut items,
+                        state,
+                        *original_idx,
+                        agent,
+                        tree_prefix,
+                        cont_prefix,
+                        agent.abbreviated_path(),
+                        available_width,
+                    );
+                    rows.resize(items.len(), Some(TreeRow::Agent(*original_idx)));
+                }
+            }
+        }
 
-                    // Status indicator and text
-                    let (status_char, status_text, status_style) = match &agent.status {
-                        AgentStatus::Idle => ("●", "Idle", Style::default().fg(Color::Green)),
-                        AgentStatus::Processing { .. } => (
-                            state.spinner_frame(),
-                            "Working",
-                            Style::default().fg(Color::Yellow),
-                        ),
-                        AgentStatus::AwaitingApproval { .. } => (
-                            "⚠",
-                            "Waiting",
-                            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-                        ),
-                        AgentStatus::Error { .. } => {
-                            ("✗", "Error", Style::default().fg(Color::Red))
-                        }
-                        AgentStatus::Unknown => {
-                            ("○", "Unknown", Style::default().fg(Color::DarkGray))
-                        }
-                    };
+        let list = List::new(items).block(block);
+        let mut list_state = ListState::default().with_selected(cursor_item);
+        frame.render_stateful_widget(list, area, &mut list_state);
+        screen_rows(area, &rows, list_state.offset())
+    }
+}
 
-                    let type_style = match agent.agent_type {
-                        AgentType::ClaudeCode => Style::default().fg(Color::Magenta),
-                        AgentType::OpenCode => Style::default().fg(Color::Blue),
-                        AgentType::CodexCli => Style::default().fg(Color::Green),
-                        AgentType::GeminiCli => Style::default().fg(Color::Yellow),
-                        AgentType::Unknown => Style::default().fg(Color::DarkGray),
-                    };
+/// Returns true if the list shows subagent lines under `agent`
+fn shows_subagents(state: &AppState, agent: &MonitoredAgent) -> bool {
+    !state.compact_list && !agent.subagents.is_empty()
+}
 
-                    let item_style = if is_cursor {
-                        Style::default().bg(Color::Rgb(50, 50, 70)) // より濃い紫がかった背景
-                    } else if is_selected {
-                        Style::default().bg(Color::Rgb(35, 35, 50)) // 薄めの選択背景
-                    } else {
-                        Style::default()
-                    };
+/// Maps the list lines shown from `offset` on to rows inside the border of `area`
+fn screen_rows(area: Rect, rows: &[Option<TreeRow>], offset: usize) -> Vec<(u16, TreeRow)> {
+    let height = area.height.saturating_sub(2) as usize;
+    (area.y + 1..)
+        .zip(rows.iter().skip(offset).take(height))
+        .filter_map(|(y, row)| row.map(|row| (y, row)))
+        .collect()
+}
 
-                    // Main line: status + path
-                    let line = Line::from(vec![
+/// Appends the list items for one agent (main line, info line, status details, subagents)
+#[allow(clippy::too_many_arguments)]
+fn push_agent_items<'a>(
+    items: &mut Vec<ListItem<'a>>,
+    state: &'a AppState,
+    index: usize,
+    agent: &'a MonitoredAgent,
+    tree_prefix: &'a str,
+    cont_prefix: &'a str,
+    location: String,
+    available_width: usize,
+) {
+    let is_cursor = index == state.selected_index;
+    let is_selected = state.is_multi_selected(index);
+
+    let select_indicator = if is_selected && is_cursor {
+        "┃☑" // カーソル+選択: 縦線とチェック
+    } else if is_selected {
+        " ☑" // 選択のみ: チェック
+    } else if is_cursor {
+        "┃ " // カーソルのみ: 縦線
+    } else {
+        "  "
+    };
+
+    // Status indicator
+    let (status_char, status_style) = match &agent.status {
+        AgentStatus::Idle => (icons().idle, Styles::idle()),
+        AgentStatus::Processing { .. } => (icons().spinner_frame(state.tick), Styles::processing()),
+        AgentStatus::AwaitingApproval { .. } => (icons().permission, Styles::awaiting_approval()),
+        AgentStatus::Error { .. } => (icons().error, Styles::error()),
+        AgentStatus::Unknown => (icons().unknown, Styles::unknown()),
+    };
+
+    let type_style = match agent.agent_type {
+        AgentType::ClaudeCode => Style::default().fg(theme().special),
+        AgentType::OpenCode => Style::default().fg(theme().info),
+        AgentType::CodexCli => Style::default().fg(theme().success),
+        AgentType::GeminiCli => Style::default().fg(theme().highlight),
+        AgentType::Aider => Style::default().fg(theme().accent),
+        AgentType::Goose => Style::default().fg(theme().alert),
+        AgentType::Custom(_) => Style::default().fg(theme().text),
+        AgentType::Unknown => Style::default().fg(theme().dim),
+    };
+
+    let item_style = if is_cursor {
+        Style::default().bg(theme().cursor) // より濃い紫がかった背景
+    } else if is_selected {
+        Style::default().bg(theme().selection) // 薄めの選択背景
+    } else {
+        Style::default()
+    };
+
+    // Main line: status + path + branch
+    let mut main_spans = vec![
+        Span::styled(
+            select_indicator,
+            if is_selected {
+                Style::default().fg(theme().accent)
+            } else {
+                Style::default().fg(theme().text)
+            },
+        ),
+        Span::styled(tree_prefix, Style::default().fg(theme().dim)),
+        Span::styled(status_char, status_style),
+        Span::raw(" "),
+    ];
+    if state.compact_list {
+        // The state age stands in for the info line
+        main_spans.push(Span::styled(
+            format!("{:>5} ", agent.state_age_str()),
+            Style::default().fg(theme().muted),
+        ));
+        if !agent.activity.is_empty() {
+            main_spans.push(activity_span(agent));
+            main_spans.push(Span::raw(" "));
+        }
+    }
+    // Host column, shown once any monitored agent is on a remote host
+    let host_width = state
+        .agents
+        .root_agents
+        .iter()
+        .filter_map(|a| a.server.host())
+        .map(|h| h.chars().count())
+        .max();
+    if let Some(width) = host_width {
+        let (host, style) = match agent.server.host() {
+            Some(host) => (host, Style::default().fg(theme().special)),
+            None => ("local", Style::default().fg(theme().dim)),
+        };
+        main_spans.push(Span::styled(
+            format!("{:<width$} ", host, width = width.max(5)),
+            style,
+        ));
+    }
+    main_spans.push(Span::styled(location, Style::default().fg(theme().accent)));
+    if let Some(label) = &agent.label {
+        main_spans.push(Span::styled(
+            format!(" {}", label),
+            Style::default()
+                .fg(theme().text)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    if agent.zoomed {
+        main_spans.push(Span::styled(" [Z]", Style::default().fg(theme().highlight)));
+    }
+    if agent.headless {
+        main_spans.push(Span::styled(
+            " [headless]",
+            Style::default().fg(theme().info),
+        ));
+    }
+    if state.automation_disabled.contains(&agent.key()) {
+        main_spans.push(Span::styled(" [manual]", Style::default().fg(theme().dim)));
+    }
+    if let Some(git) = &agent.git {
+        let branch_color = if git.is_diverged() {
+            theme().danger
+        } else if git.status.is_dirty() {
+            theme().highlight
+        } else {
+            theme().success
+        };
+        main_spans.push(Span::styled(
+            format!(" {} {}", icons().branch, git.label()),
+            Style::default().fg(branch_color),
+        ));
+        if git.upstream_gone {
+            main_spans.push(Span::styled(
+                " ⊘gone",
+                Style::default()
+                    .fg(theme().danger)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+        for (symbol, count) in git.status.badges() {
+            let badge_color = match symbol {
+                '!' => theme().danger,
+                '?' => theme().success,
+                _ => theme().highlight,
+            };
+            main_spans.push(Span::styled(
+                format!(" {}{}", symbol, count),
+                Style::default().fg(badge_color),
+            ));
+        }
+        if let Some(pr) = git.pull_request.as_ref().filter(|pr| pr.is_open()) {
+            let pr_color = match pr.ci {
+                CiState::Success => theme().success,
+                CiState::Failure => theme().danger,
+                CiState::Pending => theme().highlight,
+                CiState::None => theme().info,
+            };
+            main_spans.push(Span::styled(
+                format!(" {}", pr.label()),
+                Style::default().fg(pr_color),
+            ));
+        }
+        if let Some(op) = git.operation {
+            main_spans.push(Span::raw(" "));
+            main_spans.push(Span::styled(
+                format!(" {} ", op.label()),
+                Style::default()
+                    .fg(theme().text)
+                    .bg(theme().danger)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+    }
+    if state.compact_list {
+        if !agent.subagents.is_empty() {
+            main_spans.push(Span::styled(
+                format!(" +{} subs", agent.subagents.len()),
+                Style::default().fg(theme().dim),
+            ));
+        }
+        items.push(ListItem::new(Line::from(main_spans)).style(item_style));
+        return;
+    }
+    items.push(ListItem::new(Line::from(main_spans)).style(item_style));
+
+    // Info line: type | status and time in it | working/idle | pid | uptime | context
+    let mut info_parts = vec![
+        Span::raw("  "),
+        Span::styled(
+            format!("{}│  ", cont_prefix),
+            Style::default().fg(theme().dim),
+        ),
+        Span::styled(icons().agent_name(&agent.agent_type), type_style),
+        Span::styled(" │ ", Style::default().fg(theme().dim)),
+        Span::styled(
+            format!(" {} ", agent.state_label()),
+            Styles::agent_badge(agent),
+        ),
+        Span::styled(
+            format!(" {}", agent.state_age_str()),
+            Style::default().fg(theme().muted),
+        ),
+        Span::styled(" │ ", Style::default().fg(theme().dim)),
+        Span::styled(agent.session_times_str(), Style::default().fg(theme().dim)),
+        Span::styled(" │ ", Style::default().fg(theme().dim)),
+        Span::styled(
+            format!("pid:{}", agent.pid),
+            Style::default().fg(theme().dim),
+        ),
+        Span::styled(" │ ", Style::default().fg(theme().dim)),
+        Span::styled(agent.uptime_str(), Style::default().fg(theme().dim)),
+    ];
+
+    // Output per minute over the last minutes
+    if !agent.activity.is_empty() {
+        info_parts.push(Span::styled(" │ ", Style::default().fg(theme().dim)));
+        info_parts.push(activity_span(agent));
+    }
+
+    // Countdown to the end of a rate limit
+    if let Some(countdown) = agent.rate_limit_countdown() {
+        info_parts.push(Span::styled(" │ ", Style::default().fg(theme().dim)));
+        info_parts.push(Span::styled(
+            format!("⏳ {}", countdown),
+            Style::default().fg(theme().highlight),
+        ));
+    }
+
+    // Context bar if available
+    if let Some(ctx) = agent.context_remaining {
+        let bar_color = if ctx > 50 {
+            theme().success
+        } else if ctx > 20 {
+            theme().highlight
+        } else {
+            theme().danger
+        };
+        info_parts.push(Span::styled(" │ ", Style::default().fg(theme().dim)));
+        // Below the warning threshold an auto-compact is close
+        if ctx < state.context_warning {
+            info_parts.push(Span::styled(
+                "⚠ ",
+                Style::default()
+                    .fg(theme().danger)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+        info_parts.push(Span::styled(
+            context_bar(ctx),
+            Style::default().fg(bar_color),
+        ));
+    }
+
+    items.push(ListItem::new(Line::from(info_parts)).style(item_style));
+
+    // Summary of what the agent waits for, from `summarize_cmd`
+    if let Some(summary) = &agent.summary {
+        let summary_line = Line::from(vec![
+            Span::raw("  "),
+            Span::styled(
+                format!("{}│  ", cont_prefix),
+                Style::default().fg(theme().dim),
+            ),
+            Span::styled("≡ ", Style::default().fg(theme().accent)),
+            Span::styled(
+                truncate_str(summary, available_width.saturating_sub(12)),
+                Style::default().fg(theme().accent),
+            ),
+        ]);
+        items.push(ListItem::new(summary_line).style(item_style));
+    }
+
+    // Status details
+    match &agent.status {
+        AgentStatus::AwaitingApproval {
+            approval_type,
+            details,
+        } => {
+            let approval_line = Line::from(vec![
+                Span::raw("  "),
+                Span::styled(
+                    format!("{}│  ", cont_prefix),
+                    Style::default().fg(theme().dim),
+                ),
+                Span::styled("⚠ ", Style::default().fg(theme().danger)),
+                Span::styled(
+                    format!("{}", approval_type),
+                    Style::default()
+                        .fg(theme().danger)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]);
+            items.push(ListItem::new(approval_line).style(item_style));
+
+            if !details.is_empty() {
+                let detail_text = truncate_str(details, available_width.saturating_sub(14));
+                let detail_line = Line::from(vec![
+                    Span::raw("  "),
+                    Span::styled(
+                        format!("{}│  ", cont_prefix),
+                        Style::default().fg(theme().dim),
+                    ),
+                    Span::styled("  → ", Style::default().fg(theme().dim)),
+                    Span::styled(detail_text, Style::default().fg(theme().text)),
+                ]);
+                items.push(ListItem::new(detail_line).style(item_style));
+            }
+
+            if let ApprovalType::UserQuestion { choices, .. } = approval_type {
+                for (i, choice) in choices.iter().take(4).enumerate() {
+                    let choice_text = truncate_str(choice, available_width.saturating_sub(14));
+                    let choice_line = Line::from(vec![
+                        Span::raw("  "),
                         Span::styled(
-                            select_indicator,
-                            if is_selected {
-                                Style::default().fg(Color::Cyan)
-                            } else {
-                                Style::default().fg(Color::White)
-                            },
+                            format!("{}│  ", cont_prefix),
+                            Style::default().fg(theme().dim),
                         ),
-                        Span::styled(tree_prefix, Style::default().fg(Color::DarkGray)),
-                        Span::styled(status_char, status_style),
-                        Span::raw(" "),
-                        Span::styled(agent.abbreviated_path(), Style::default().fg(Color::Cyan)),
+                        Span::styled(
+                            format!("  {}. ", i + 1),
+                            Style::default().fg(theme().highlight),
+                        ),
+                        Span::styled(choice_text, Style::default().fg(theme().text)),
                     ]);
-                    items.push(ListItem::new(line).style(item_style));
-
-                    // Info line: type | status | pid | uptime | context
-                    let mut info_parts = vec![
+                    items.push(ListItem::new(choice_line).style(item_style));
+                }
+                if choices.len() > 4 {
+                    let more_line = Line::from(vec![
                         Span::raw("  "),
                         Span::styled(
                             format!("{}│  ", cont_prefix),
-                            Style::default().fg(Color::DarkGray),
+                            Style::default().fg(theme().dim),
                         ),
-                        Span::styled(agent.agent_type.short_name(), type_style),
-                        Span::styled(" │ ", Style::default().fg(Color::DarkGray)),
-                        Span::styled(status_text, status_style),
-                        Span::styled(" │ ", Style::default().fg(Color::DarkGray)),
                         Span::styled(
-                            format!("pid:{}", agent.pid),
-                            Style::default().fg(Color::DarkGray),
+                            format!("     ...+{} more", choices.len() - 4),
+                            Style::default().fg(theme().dim),
                         ),
-                        Span::styled(" │ ", Style::default().fg(Color::DarkGray)),
-                        Span::styled(agent.uptime_str(), Style::default().fg(Color::DarkGray)),
-                    ];
-
-                    // Context bar if available
-                    if let Some(ctx) = agent.context_remaining {
-                        let bar_color = if ctx > 50 {
-                            Color::Green
-                        } else if ctx > 20 {
-                            Color::Yellow
-                        } else {
-                            Color::Red
-                        };
-                        info_parts.push(Span::styled(" │ ", Style::default().fg(Color::DarkGray)));
-                        info_parts.push(Span::styled(
-                            context_bar(ctx),
-                            Style::default().fg(bar_color),
-                        ));
-                    }
+                    ]);
+                    items.push(ListItem::new(more_line).style(item_style));
+                }
+            }
+        }
+        AgentStatus::Processing { activity } => {
+            if !activity.is_empty() {
+                let activity_text = truncate_str(activity, available_width.saturating_sub(14));
+                let activity_line = Line::from(vec![
+                    Span::raw("  "),
+                    Span::styled(
+                        format!("{}│  ", cont_prefix),
+                        Style::default().fg(theme().dim),
+                    ),
+                    Span::styled(
+                        format!("{} ", icons().spinner_frame(state.tick)),
+                        Style::default().fg(theme().highlight),
+                    ),
+                    Span::styled(activity_text, Style::default().fg(theme().highlight)),
+                ]);
+                items.push(ListItem::new(activity_line).style(item_style));
+            }
+        }
+        AgentStatus::Error { message } => {
+            let error_text = truncate_str(message, available_width.saturating_sub(14));
+            let error_line = Line::from(vec![
+                Span::raw("  "),
+                Span::styled(
+                    format!("{}│  ", cont_prefix),
+                    Style::default().fg(theme().dim),
+                ),
+                Span::styled("✗ ", Style::default().fg(theme().danger)),
+                Span::styled(error_text, Style::default().fg(theme().danger)),
+            ]);
+            items.push(ListItem::new(error_line).style(item_style));
+        }
+        // Without anything else to show, the last thing the pane printed
+        _ => {
+            if let Some(output) = agent.last_output_line() {
+                let output_line = Line::from(vec![
+                    Span::raw("  "),
+                    Span::styled(
+                        format!("{}│  ", cont_prefix),
+                        Style::default().fg(theme().dim),
+                    ),
+                    Span::styled(
+                        truncate_str(output, available_width.saturating_sub(12)),
+                        Style::default().fg(theme().dim),
+                    ),
+                ]);
+                items.push(ListItem::new(output_line).style(item_style));
+            }
+        }
+    }
 
-                    items.push(ListItem::new(Line::from(info_parts)).style(item_style));
-
-                    // Status details
-                    match &agent.status {
-                        AgentStatus::AwaitingApproval {
-                            approval_type,
-                            details,
-                        } => {
-                            let approval_line = Line::from(vec![
-                                Span::raw("  "),
-                                Span::styled(
-                                    format!("{}│  ", cont_prefix),
-                                    Style::default().fg(Color::DarkGray),
-                                ),
-                                Span::styled("⚠ ", Style::default().fg(Color::Red)),
-                                Span::styled(
-                                    format!("{}", approval_type),
-                                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-                                ),
-                            ]);
-                            items.push(ListItem::new(approval_line).style(item_style));
-
-                            if !details.is_empty() {
-                                let detail_text =
-                                    truncate_str(details, available_width.saturating_sub(14));
-                                let detail_line = Line::from(vec![
-                                    Span::raw("  "),
-                                    Span::styled(
-                                        format!("{}│  ", cont_prefix),
-                                        Style::default().fg(Color::DarkGray),
-                                    ),
-                                    Span::styled("  → ", Style::default().fg(Color::DarkGray)),
-                                    Span::styled(detail_text, Style::default().fg(Color::White)),
-                                ]);
-                                items.push(ListItem::new(detail_line).style(item_style));
-                            }
-
-                            if let ApprovalType::UserQuestion { choices, .. } = approval_type {
-                                for (i, choice) in choices.iter().take(4).enumerate() {
-                                    let choice_text =
-                                        truncate_str(choice, available_width.saturating_sub(14));
-                                    let choice_line = Line::from(vec![
-                                        Span::raw("  "),
-                                        Span::styled(
-                                            format!("{}│  ", cont_prefix),
-                                            Style::default().fg(Color::DarkGray),
-                                        ),
-                                        Span::styled(
-                                            format!("  {}. ", i + 1),
-                                            Style::default().fg(Color::Yellow),
-                                        ),
-                                        Span::styled(
-                                            choice_text,
-                                            Style::default().fg(Color::White),
-                                        ),
-                                    ]);
-                                    items.push(ListItem::new(choice_line).style(item_style));
-                                }
-                                if choices.len() > 4 {
-                                    let more_line = Line::from(vec![
-                                        Span::raw("  "),
-                                        Span::styled(
-                                            format!("{}│  ", cont_prefix),
-                                            Style::default().fg(Color::DarkGray),
-                                        ),
-                                        Span::styled(
-                                            format!("     ...+{} more", choices.len() - 4),
-                                            Style::default().fg(Color::DarkGray),
-                                        ),
-                                    ]);
-                                    items.push(ListItem::new(more_line).style(item_style));
-                                }
-                            }
-                        }
-                        AgentStatus::Processing { activity } => {
-                            if !activity.is_empty() {
-                                let activity_text =
-                                    truncate_str(activity, available_width.saturating_sub(14));
-                                let activity_line = Line::from(vec![
-                                    Span::raw("  "),
-                                    Span::styled(
-                                        format!("{}│  ", cont_prefix),
-                                        Style::default().fg(Color::DarkGray),
-                                    ),
-                                    Span::styled(
-                                        format!("{} ", state.spinner_frame()),
-                                        Style::default().fg(Color::Yellow),
-                                    ),
-                                    Span::styled(activity_text, Style::default().fg(Color::Yellow)),
-                                ]);
-                                items.push(ListItem::new(activity_line).style(item_style));
-                            }
-                        }
-                        AgentStatus::Error { message } => {
-                            let error_text =
-                                truncate_str(message, available_width.saturating_sub(14));
-                            let error_line = Line::from(vec![
-                                Span::raw("  "),
-                                Span::styled(
-                                    format!("{}│  ", cont_prefix),
-                                    Style::default().fg(Color::DarkGray),
-                                ),
-                                Span::styled("✗ ", Style::default().fg(Color::Red)),
-                                Span::styled(error_text, Style::default().fg(Color::Red)),
-                            ]);
-                            items.push(ListItem::new(error_line).style(item_style));
-                        }
-                        _ => {}
-                    }
+    // Subagents
+    for (sub_idx, subagent) in agent.subagents.iter().enumerate() {
+        let is_last_sub = sub_idx == agent.subagents.len() - 1;
+        let sub_branch = if is_last_sub { "└─" } else { "├─" };
+
+        let (sub_char, sub_style) = match subagent.status {
+            SubagentStatus::Running => (
+                icons().spinner_frame(state.tick),
+                Style::default().fg(theme().accent),
+            ),
+            SubagentStatus::Completed => (icons().done, Style::default().fg(theme().success)),
+            SubagentStatus::Failed => (icons().error, Style::default().fg(theme().danger)),
+            SubagentStatus::Unknown => ("?", Style::default().fg(theme().dim)),
+        };
 
-                    // Subagents
-                    for (sub_idx, subagent) in agent.subagents.iter().enumerate() {
-                        let is_last_sub = sub_idx == agent.subagents.len() - 1;
-                        let sub_branch = if is_last_sub { "└─" } else { "├─" };
-
-                        let (sub_char, sub_style) = match subagent.status {
-                            SubagentStatus::Running => {
-                                (state.spinner_frame(), Style::default().fg(Color::Cyan))
-                            }
-                            SubagentStatus::Completed => ("✓", Style::default().fg(Color::Green)),
-                            SubagentStatus::Failed => ("✗", Style::default().fg(Color::Red)),
-                            SubagentStatus::Unknown => ("?", Style::default().fg(Color::DarkGray)),
-                        };
-
-                        let duration = if matches!(subagent.status, SubagentStatus::Running) {
-                            format!(" ({})", subagent.duration_str())
-                        } else {
-                            String::new()
-                        };
-
-                        let sub_line = Line::from(vec![
-                            Span::raw("  "),
-                            Span::styled(
-                                format!("{}{}", cont_prefix, sub_branch),
-                                Style::default().fg(Color::DarkGray),
-                            ),
-                            Span::styled(sub_char, sub_style),
-                            Span::raw(" "),
-                            Span::styled(
-                                subagent.subagent_type.display_name(),
-                                Style::default()
-                                    .fg(Color::White)
-                                    .add_modifier(Modifier::BOLD),
-                            ),
-                            Span::styled(duration, Style::default().fg(Color::Yellow)),
-                        ]);
-                        items.push(ListItem::new(sub_line));
-
-                        if !subagent.description.is_empty() {
-                            let desc_prefix = if is_last_sub { "   " } else { "│  " };
-                            let desc_text = truncate_str(
-                                &subagent.description,
-                                available_width.saturating_sub(14),
-                            );
-                            let desc_line = Line::from(vec![
-                                Span::raw("  "),
-                                Span::styled(
-                                    format!("{}{}", cont_prefix, desc_prefix),
-                                    Style::default().fg(Color::DarkGray),
-                                ),
-                                Span::styled("  ", Style::default()),
-                                Span::styled(desc_text, Style::default().fg(Color::DarkGray)),
-                            ]);
-                            items.push(ListItem::new(desc_line));
-                        }
-                    }
-                }
+        let duration = if matches!(subagent.status, SubagentStatus::Running) {
+            format!(" ({})", subagent.duration_str())
+        } else {
+            String::new()
+        };
+
+        let sub_line = Line::from(vec![
+            Span::raw("  "),
+            Span::styled(
+                format!("{}{}", cont_prefix, sub_branch),
+                Style::default().fg(theme().dim),
+            ),
+            Span::styled(sub_char, sub_style),
+            Span::raw(" "),
+            Span::styled(
+                subagent.subagent_type.display_name(),
+                Style::default()
+                    .fg(theme().text)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(duration, Style::default().fg(theme().highlight)),
+        ]);
+        items.push(ListItem::new(sub_line));
+
+        if !subagent.description.is_empty() {
+            let desc_prefix = if is_last_sub { "   " } else { "│  " };
+            let desc_text = truncate_str(&subagent.description, available_width.saturating_sub(14));
+            let desc_line = Line::from(vec![
+                Span::raw("  "),
+                Span::styled(
+                    format!("{}{}", cont_prefix, desc_prefix),
+                    Style::default().fg(theme().dim),
+                ),
+                Span::styled("  ", Style::default()),
+                Span::styled(desc_text, Style::default().fg(theme().dim)),
+            ]);
+            items.push(ListItem::new(desc_line));
+        }
+    }
+}
+
+impl AgentTreeWidget {
+    /// Builds list items grouped by repository: a header per repository
+    /// (`~/code/myapp (3 agents)`) followed by its panes unless collapsed
+    ///
+    /// Also returns what each item belongs to and the index of the item the
+    /// cursor is on.
+    fn group_items(
+        state: &AppState,
+        available_width: usize,
+    ) -> (Vec<ListItem<'_>>, Vec<Option<TreeRow>>, Option<usize>) {
+        let mut items = Vec::new();
+        let mut rows = Vec::new();
+        let mut cursor_item = None;
+
+        for (key, indices) in state.visible_groups() {
+            Self::push_group(
+                &mut items,
+                &mut rows,
+                &mut cursor_item,
+                state,
+                key,
+                &indices,
+                available_width,
+            );
+        }
+
+        (items, rows, cursor_item)
+    }
+
+    /// Appends the header of the group `key` and, unless it is collapsed, its
+    /// agents to the list items, rows and cursor item
+    fn push_group<'a>(
+        items: &mut Vec<ListItem<'a>>,
+        rows: &mut Vec<Option<TreeRow>>,
+        cursor_item: &mut Option<usize>,
+        state: &'a AppState,
+        key: String,
+        indices: &[usize],
+        available_width: usize,
+    ) {
+        let agents = &state.agents.root_agents;
+        let collapsed = state.collapsed_groups.contains(&key);
+        let has_cursor = indices.contains(&state.selected_index);
+        let pinned = key == PINNED_GROUP;
+        let title = if pinned {
+            format!("{} pinned", icons().pin)
+        } else if state.group_by_repo {
+            tilde_path(&key)
+        } else {
+            key
+        };
+        if collapsed && has_cursor {
+            *cursor_item = Some(items.len());
+        }
+        items.push(group_header(
+            state,
+            title,
+            indices,
+            collapsed,
+            collapsed && has_cursor,
+        ));
+        rows.push(Some(TreeRow::Group(indices[0])));
+
+        if collapsed {
+            return;
+        }
+
+        for (pos, &idx) in indices.iter().enumerate() {
+            let agent = &agents[idx];
+            let is_last = pos == indices.len() - 1;
+            let cont_prefix = if is_last { "  " } else { "│ " };
+            let tree_prefix = if is_last && !shows_subagents(state, agent) {
+                "└─"
+            } else {
+                "├─"
+            };
+            if idx == state.selected_index {
+                *cursor_item = Some(items.len());
             }
+            // The header already names the repository or session, so show where the pane is instead
+            let location = if pinned {
+                format!("{} {}", agent.target, agent.abbreviated_path())
+            } else if state.group_by_repo {
+                agent.target.clone()
+            } else {
+                format!(
+                    "{}.{} {}",
+                    agent.window,
+                    agent.pane,
+                    agent.abbreviated_path()
+                )
+            };
+            rows.resize(items.len(), None);
+            push_agent_items(
+                items,
+                state,
+                idx,
+                agent,
+                tree_prefix,
+                cont_prefix,
+                location,
+                available_width,
+            );
+            rows.resize(items.len(), Some(TreeRow::Agent(idx)));
+        }
+    }
+}
+
+/// Replaces the home directory prefix with `~`
+/// Builds the header line of a group with its agent count and how many of its
+/// agents are in each state
+fn group_header<'a>(
+    state: &AppState,
+    title: String,
+    indices: &[usize],
+    collapsed: bool,
+    is_cursor: bool,
+) -> ListItem<'a> {
+    let agents = &state.agents.root_agents;
+    let count =
+        |f: fn(&AgentStatus) -> bool| indices.iter().filter(|&&i| f(&agents[i].status)).count();
+
+    let mut header = vec![
+        Span::styled(
+            if collapsed { "▶ " } else { "▼ " },
+            Style::default().fg(theme().accent),
+        ),
+        Span::styled(
+            title,
+            Style::default()
+                .fg(theme().accent)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!(
+                " ({} agent{})",
+                indices.len(),
+                if indices.len() == 1 { "" } else { "s" }
+            ),
+            Style::default().fg(theme().dim),
+        ),
+    ];
+    let counts = [
+        (
+            count(|s| matches!(s, AgentStatus::AwaitingApproval { .. })),
+            icons().permission,
+            Styles::awaiting_approval().add_modifier(Modifier::BOLD),
+        ),
+        (
+            count(|s| matches!(s, AgentStatus::Error { .. })),
+            icons().error,
+            Styles::error(),
+        ),
+        (
+            count(|s| matches!(s, AgentStatus::Processing { .. })),
+            icons().spinner_frame(state.tick),
+            Styles::processing(),
+        ),
+        (
+            count(|s| matches!(s, AgentStatus::Idle)),
+            icons().idle,
+            Styles::idle(),
+        ),
+    ];
+    for (count, icon, style) in counts {
+        if count > 0 {
+            header.push(Span::styled(format!(" {}{}", icon, count), style));
         }
+    }
 
-        let list = List::new(items).block(block);
-        let mut list_state = ListState::default();
-        list_state.select(Some(state.selected_index));
-        frame.render_stateful_widget(list, area, &mut list_state);
+    let style = if is_cursor {
+        Style::default().bg(theme().cursor)
+    } else {
+        Style::default()
+    };
+    ListItem::new(Line::from(header)).style(style)
+}
+
+fn tilde_path(path: &str) -> String {
+    if let Some(home) = dirs::home_dir() {
+        let home = home.to_string_lossy();
+        if let Some(rest) = path.strip_prefix(home.as_ref()) {
+            if rest.is_empty() || rest.starts_with('/') {
+                return format!("~{}", rest);
+            }
+        }
     }
+    path.to_string()
 }
 
 fn truncate_str(s: &str, max_len: usize) -> String {
@@ -432,6 +904,41 @@ fn truncate_str(s: &str, max_len: usize) -> String {
     }
 }
 
+/// Returns the sparkline of the agent's output per minute, highlighted while
+/// it produces output in the current minute
+fn activity_span<'a>(agent: &MonitoredAgent) -> Span<'a> {
+    let active = agent.activity.last().is_some_and(|&n| n > 0);
+    Span::styled(
+        sparkline(&agent.activity),
+        Style::default().fg(if active { theme().working } else { theme().dim }),
+    )
+}
+
+/// Draws line counts as bars on a fixed scale, each level about doubling the
+/// lines, so panes can be compared: `·` is no output, `█` over 160 lines
+fn sparkline(counts: &[u32]) -> String {
+    const LEVELS: [(u32, char); 8] = [
+        (2, '▁'),
+        (5, '▂'),
+        (10, '▃'),
+        (20, '▄'),
+        (40, '▅'),
+        (80, '▆'),
+        (160, '▇'),
+        (u32::MAX, '█'),
+    ];
+    counts
+        .iter()
+        .map(|&n| match n {
+            0 => '·',
+            n => LEVELS
+                .iter()
+                .find(|(max, _)| n <= *max)
+                .map_or('█', |(_, c)| *c),
+        })
+        .collect()
+}
+
 fn context_bar(percent: u8) -> String {
     let total_blocks = 10;
     let filled = (percent as usize * total_blocks) / 100;
@@ -0,0 +1,45 @@
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::app::AppState;
+use crate::ui::theme;
+
+/// Yes/no confirmation popup for destructive actions
+pub struct ConfirmWidget;
+
+impl ConfirmWidget {
+    pub fn render(frame: &mut Frame, area: Rect, state: &AppState) {
+        let Some(confirm) = &state.confirm else {
+            return;
+        };
+
+        let width = area.width.saturating_sub(4).clamp(20, 60);
+        let popup_area = Rect::new(
+            area.x + (area.width.saturating_sub(width)) / 2,
+            area.y + area.height.saturating_sub(4) / 2,
+            width,
+            4.min(area.height),
+        );
+
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .title(" Confirm ")
+            .title_bottom(Line::from(" y: Yes  any other key: No ").right_aligned())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(theme().danger))
+            .style(Style::default().bg(theme().background));
+
+        let paragraph = Paragraph::new(confirm.message())
+            .style(Style::default().fg(theme().text))
+            .wrap(Wrap { trim: true })
+            .block(block);
+        frame.render_widget(paragraph, popup_area);
+    }
+}
@@ -1,12 +1,13 @@
 use ratatui::{
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::Paragraph,
     Frame,
 };
 
 use crate::app::AppState;
+use crate::ui::theme;
 
 /// Button definitions for footer
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -73,21 +74,21 @@ impl FooterWidget {
     }
 
     pub fn render(frame: &mut Frame, area: Rect, state: &AppState) {
-        let btn_y = Style::default().fg(Color::Black).bg(Color::Green);
-        let btn_n = Style::default().fg(Color::Black).bg(Color::Red);
-        let btn_a = Style::default().fg(Color::Black).bg(Color::Yellow);
-        let btn_sel = Style::default().fg(Color::Black).bg(Color::Cyan);
-        let btn_def = Style::default().fg(Color::Black).bg(Color::Gray);
-        let sep = Style::default().fg(Color::DarkGray);
-        let key = Style::default().fg(Color::Yellow);
-        let txt = Style::default().fg(Color::White);
+        let btn_y = Style::default().fg(theme().inverse).bg(theme().success);
+        let btn_n = Style::default().fg(theme().inverse).bg(theme().danger);
+        let btn_a = Style::default().fg(theme().inverse).bg(theme().highlight);
+        let btn_sel = Style::default().fg(theme().inverse).bg(theme().accent);
+        let btn_def = Style::default().fg(theme().inverse).bg(theme().muted);
+        let sep = Style::default().fg(theme().dim);
+        let key = Style::default().fg(theme().highlight);
+        let txt = Style::default().fg(theme().text);
 
         let line: Line = if state.is_input_focused() {
             Line::from(vec![
                 Span::styled(
                     " INPUT ",
                     Style::default()
-                        .fg(Color::Green)
+                        .fg(theme().success)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::styled("│", sep),
@@ -120,7 +121,7 @@ impl FooterWidget {
             if !state.selected_agents.is_empty() {
                 spans.push(Span::styled(
                     format!(" ({}sel)", state.selected_agents.len()),
-                    Style::default().fg(Color::Cyan),
+                    Style::default().fg(theme().accent),
                 ));
             }
 
@@ -128,7 +129,13 @@ impl FooterWidget {
                 spans.push(Span::styled(" │ ", sep));
                 spans.push(Span::styled(
                     format!("✗ {}", truncate_error(error, 30)),
-                    Style::default().fg(Color::Red),
+                    Style::default().fg(theme().danger),
+                ));
+            } else if let Some(message) = &state.last_message {
+                spans.push(Span::styled(" │ ", sep));
+                spans.push(Span::styled(
+                    format!("✓ {}", truncate_error(message, 50)),
+                    Style::default().fg(theme().success),
                 ));
             }
 
@@ -1,8 +1,9 @@
 use crate::app::AppState;
+use crate::ui::{icons, theme};
 use chrono::Local;
 use ratatui::{
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Paragraph},
     Frame,
@@ -21,44 +22,64 @@ impl HeaderWidget {
             Span::styled(
                 " TmuxCC ",
                 Style::default()
-                    .fg(Color::Cyan)
+                    .fg(theme().accent)
                     .add_modifier(Modifier::BOLD),
             ),
-            Span::styled("│", Style::default().fg(Color::DarkGray)),
+            Span::styled("│", Style::default().fg(theme().dim)),
             Span::styled(
                 format!(" {} agents ", total),
-                Style::default().fg(Color::White),
+                Style::default().fg(theme().text),
             ),
         ];
 
         // Processing count
         if processing > 0 {
-            spans.push(Span::styled("│", Style::default().fg(Color::DarkGray)));
+            spans.push(Span::styled("│", Style::default().fg(theme().dim)));
             spans.push(Span::styled(
-                format!(" {} {} working ", state.spinner_frame(), processing),
-                Style::default().fg(Color::Yellow),
+                format!(
+                    " {} {} working ",
+                    icons().spinner_frame(state.tick),
+                    processing
+                ),
+                Style::default().fg(theme().highlight),
             ));
         }
 
         // Pending count
-        spans.push(Span::styled("│", Style::default().fg(Color::DarkGray)));
+        spans.push(Span::styled("│", Style::default().fg(theme().dim)));
         if pending > 0 {
             spans.push(Span::styled(
-                format!(" ⚠ {} pending ", pending),
-                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                format!(" {} {} pending ", icons().permission, pending),
+                Style::default()
+                    .fg(theme().danger)
+                    .add_modifier(Modifier::BOLD),
             ));
         } else {
-            spans.push(Span::styled(" ✓ ready ", Style::default().fg(Color::Green)));
+            spans.push(Span::styled(
+                format!(" {} ready ", icons().done),
+                Style::default().fg(theme().success),
+            ));
+        }
+
+        // Kill switch for automatic answers
+        if state.automation_paused {
+            spans.push(Span::styled("│", Style::default().fg(theme().dim)));
+            spans.push(Span::styled(
+                " ⏸ auto-answers paused ",
+                Style::default()
+                    .fg(theme().special)
+                    .add_modifier(Modifier::BOLD),
+            ));
         }
 
         // System stats: CPU
-        spans.push(Span::styled("│", Style::default().fg(Color::DarkGray)));
+        spans.push(Span::styled("│", Style::default().fg(theme().dim)));
         let cpu_color = if state.system_stats.cpu_usage > 80.0 {
-            Color::Red
+            theme().danger
         } else if state.system_stats.cpu_usage > 50.0 {
-            Color::Yellow
+            theme().highlight
         } else {
-            Color::Green
+            theme().success
         };
         spans.push(Span::styled(
             format!(" CPU {:4.1}% ", state.system_stats.cpu_usage),
@@ -66,14 +87,14 @@ impl HeaderWidget {
         ));
 
         // System stats: Memory
-        spans.push(Span::styled("│", Style::default().fg(Color::DarkGray)));
+        spans.push(Span::styled("│", Style::default().fg(theme().dim)));
         let mem_percent = state.system_stats.memory_percent();
         let mem_color = if mem_percent > 80.0 {
-            Color::Red
+            theme().danger
         } else if mem_percent > 60.0 {
-            Color::Yellow
+            theme().highlight
         } else {
-            Color::Green
+            theme().success
         };
         spans.push(Span::styled(
             format!(
@@ -85,19 +106,63 @@ impl HeaderWidget {
         ));
 
         // Time
-        spans.push(Span::styled("│", Style::default().fg(Color::DarkGray)));
+        spans.push(Span::styled("│", Style::default().fg(theme().dim)));
         spans.push(Span::styled(
             format!(" {} ", time),
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme().dim),
         ));
 
         let line = Line::from(spans);
-        let block = Block::default()
+        let mut block = Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(Color::Gray));
+            .border_style(Style::default().fg(theme().muted));
+        if state.servers.len() > 1 {
+            block = block.title(Self::server_tabs(state));
+        }
 
         let paragraph = Paragraph::new(line).block(block);
         frame.render_widget(paragraph, area);
     }
+
+    /// Builds the server tabs, numbered for Alt+number, with the agents and
+    /// pending requests of each
+    fn server_tabs(state: &AppState) -> Line<'static> {
+        let agents = &state.agents.root_agents;
+        let tabs = std::iter::once(("all".to_string(), None))
+            .chain(state.servers.iter().map(|s| (s.label(), Some(s))));
+
+        let mut spans = vec![Span::raw(" ")];
+        for (i, (label, server)) in tabs.enumerate() {
+            let on_tab: Vec<_> = agents
+                .iter()
+                .filter(|a| server.is_none_or(|s| a.server == *s))
+                .collect();
+            let pending = on_tab.iter().filter(|a| a.status.needs_attention()).count();
+            let active = state.server_tab == i.checked_sub(1);
+
+            let style = if active {
+                Style::default()
+                    .fg(theme().inverse)
+                    .bg(theme().accent)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme().muted)
+            };
+            spans.push(Span::styled(
+                format!(" {} {} {} ", i, label, on_tab.len()),
+                style,
+            ));
+            if pending > 0 {
+                spans.push(Span::styled(
+                    format!("⚠{} ", pending),
+                    Style::default()
+                        .fg(theme().danger)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            spans.push(Span::raw(" "));
+        }
+        Line::from(spans)
+    }
 }
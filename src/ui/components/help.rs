@@ -1,130 +1,463 @@
 use ratatui::{
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Clear, Paragraph},
     Frame,
 };
 
-use crate::ui::Layout;
+use crate::app::{Action, AppState, Config, KeyProfile, Keymap, UseIcons, PREVIEW_PAGE};
+use crate::ui::{icons, theme, Layout};
+
+/// Keys of a help row: the current bindings of actions, or fixed keys
+enum HelpKeys {
+    Bound(&'static [Action]),
+    Fixed(&'static str),
+}
+
+/// Help rows per section; rows whose actions are all unbound are left out
+const SECTIONS: &[(&str, &[(HelpKeys, &str)])] = &[
+    (
+        "Navigation",
+        &[
+            (HelpKeys::Bound(&[Action::NextAgent]), "Next agent"),
+            (HelpKeys::Bound(&[Action::PrevAgent]), "Previous agent"),
+            (
+                HelpKeys::Bound(&[Action::FirstAgent, Action::LastAgent]),
+                "First / last agent",
+            ),
+            (
+                HelpKeys::Bound(&[Action::Search]),
+                "Search panes (Esc clears)",
+            ),
+            (HelpKeys::Fixed("Alt+0-9"), "Server tab (0: all servers)"),
+        ],
+    ),
+    (
+        "Selection",
+        &[
+            (
+                HelpKeys::Bound(&[Action::ToggleSelection]),
+                "Toggle selection of current agent",
+            ),
+            (HelpKeys::Bound(&[Action::SelectAll]), "Select all agents"),
+            (
+                HelpKeys::Bound(&[Action::ToggleBroadcast]),
+                "Broadcast input to selected",
+            ),
+            (
+                HelpKeys::Bound(&[Action::BroadcastFilter]),
+                "Broadcast to panes matching a filter",
+            ),
+            (
+                HelpKeys::Fixed("Esc"),
+                "Clear selection / Close subagent log / Clear search",
+            ),
+        ],
+    ),
+    (
+        "Actions",
+        &[
+            (
+                HelpKeys::Bound(&[Action::Approve]),
+                "Approve pending request(s)",
+            ),
+            (
+                HelpKeys::Bound(&[Action::Reject]),
+                "Reject pending request(s)",
+            ),
+            (
+                HelpKeys::Bound(&[Action::ApproveAll]),
+                "Approve all pending requests",
+            ),
+            (HelpKeys::Fixed("1-9"), "Send number choice to agent"),
+            (
+                HelpKeys::Bound(&[Action::FocusInput]),
+                "Focus input (Esc: back to sidebar)",
+            ),
+            (
+                HelpKeys::Bound(&[Action::FocusPane]),
+                "Focus on selected pane in tmux",
+            ),
+            (
+                HelpKeys::Bound(&[Action::JumpToPane]),
+                "Jump to pane (prefix+return_key: back)",
+            ),
+        ],
+    ),
+    (
+        "View",
+        &[
+            (
+                HelpKeys::Bound(&[Action::ToggleSubagentLog]),
+                "Toggle subagent log",
+            ),
+            (
+                HelpKeys::Bound(&[Action::ToggleSummaryDetail]),
+                "Toggle TODO/Tools display",
+            ),
+            (
+                HelpKeys::Bound(&[Action::NewAgent]),
+                "Launch agent in new window/split",
+            ),
+            (
+                HelpKeys::Bound(&[Action::KillPane, Action::RespawnPane]),
+                "Kill pane / Restart agent",
+            ),
+            (
+                HelpKeys::Bound(&[Action::IgnorePane]),
+                "Stop monitoring pane",
+            ),
+            (
+                HelpKeys::Bound(&[Action::NewWorktree]),
+                "Spawn agent in new worktree",
+            ),
+            (
+                HelpKeys::Bound(&[Action::OpenBranchPicker]),
+                "Checkout branch (F: force)",
+            ),
+            (
+                HelpKeys::Bound(&[Action::CreatePullRequest]),
+                "Create GitHub PR (idle agent)",
+            ),
+            (
+                HelpKeys::Bound(&[Action::ShowCommitLog]),
+                "Show recent commits",
+            ),
+            (
+                HelpKeys::Bound(&[Action::ShowDiff]),
+                "Show working tree diff",
+            ),
+            (HelpKeys::Bound(&[Action::ToggleZoom]), "Zoom/unzoom pane"),
+            (
+                HelpKeys::Bound(&[Action::EditLabel, Action::EditNote]),
+                "Edit label / note",
+            ),
+            (
+                HelpKeys::Bound(&[Action::QueuePrompt, Action::EditQueue]),
+                "Queue prompt / reorder queue",
+            ),
+            (
+                HelpKeys::Bound(&[Action::RunTests]),
+                "Ask agent to run the tests and fix failures",
+            ),
+            (
+                HelpKeys::Bound(&[Action::ToggleAutomation, Action::ToggleAutomationPause]),
+                "Auto-answers off for pane / pause all",
+            ),
+            (
+                HelpKeys::Bound(&[Action::ShowOutput, Action::YankOutput]),
+                "Show / copy pane output",
+            ),
+            (
+                HelpKeys::Bound(&[Action::ShowScrollback]),
+                "Scrollback history (/: find, n/N: next/prev)",
+            ),
+            (
+                HelpKeys::Bound(&[Action::ShowDetails]),
+                "Pane details (agent, git, cost, history)",
+            ),
+            (
+                HelpKeys::Bound(&[Action::ShowNotifications]),
+                "Notification history (Enter: jump, r/R: read)",
+            ),
+            (
+                HelpKeys::Bound(&[Action::ShowUsage]),
+                "Token usage and cost",
+            ),
+            (
+                HelpKeys::Bound(&[
+                    Action::ScrollPreviewUp(PREVIEW_PAGE),
+                    Action::ScrollPreviewDown(PREVIEW_PAGE),
+                ]),
+                "Scroll preview up / down (also the mouse wheel)",
+            ),
+            (
+                HelpKeys::Bound(&[Action::ToggleGroupByRepo]),
+                "Group by repository",
+            ),
+            (
+                HelpKeys::Bound(&[Action::ToggleCollapseGroup]),
+                "Collapse/expand session or repository",
+            ),
+            (
+                HelpKeys::Bound(&[Action::CycleSort]),
+                "Sort by tmux order / state / activity / cost / name",
+            ),
+            (
+                HelpKeys::Bound(&[Action::TogglePin]),
+                "Pin/unpin pane to the top",
+            ),
+            (
+                HelpKeys::Bound(&[Action::ToggleCompactList]),
+                "Compact / detailed pane list",
+            ),
+            (
+                HelpKeys::Bound(&[Action::SidebarNarrower, Action::SidebarWider]),
+                "Narrow / widen sidebar",
+            ),
+            (HelpKeys::Bound(&[Action::Refresh]), "Refresh / clear error"),
+        ],
+    ),
+    (
+        "General",
+        &[
+            (HelpKeys::Bound(&[Action::ToggleMute]), "Mute/unmute sounds"),
+            (
+                HelpKeys::Bound(&[Action::ShowPalette]),
+                "Command palette (every action)",
+            ),
+            (HelpKeys::Bound(&[Action::ShowHelp]), "Toggle this help"),
+            (HelpKeys::Bound(&[Action::Quit]), "Quit"),
+        ],
+    ),
+];
 
 /// Help popup widget
 pub struct HelpWidget;
 
 impl HelpWidget {
-    pub fn render(frame: &mut Frame, area: Rect) {
+    /// Renders the help with the keys currently bound in `keymap` and the
+    /// settings in effect, returning how far it can be scrolled
+    pub fn render(
+        frame: &mut Frame,
+        area: Rect,
+        state: &AppState,
+        keymap: &Keymap,
+        config: &Config,
+    ) -> usize {
         let popup_area = Layout::centered_popup(area, 60, 70);
 
         // Clear the background
         frame.render_widget(Clear, popup_area);
 
         let key_style = Style::default()
-            .fg(Color::Yellow)
+            .fg(theme().highlight)
             .add_modifier(Modifier::BOLD);
-        let desc_style = Style::default().fg(Color::White);
+        let desc_style = Style::default().fg(theme().text);
         let section_style = Style::default()
-            .fg(Color::Cyan)
+            .fg(theme().accent)
             .add_modifier(Modifier::BOLD);
 
-        let help_text = vec![
-            Line::from(vec![Span::styled("Navigation", section_style)]),
-            Line::from(vec![]),
-            Line::from(vec![
-                Span::styled("  j / ↓    ", key_style),
-                Span::styled("Next agent", desc_style),
-            ]),
-            Line::from(vec![
-                Span::styled("  k / ↑    ", key_style),
-                Span::styled("Previous agent", desc_style),
-            ]),
-            Line::from(vec![
-                Span::styled("  Tab      ", key_style),
-                Span::styled("Next agent (cycle)", desc_style),
-            ]),
-            Line::from(vec![]),
-            Line::from(vec![Span::styled("Selection", section_style)]),
-            Line::from(vec![]),
-            Line::from(vec![
-                Span::styled("  Space    ", key_style),
-                Span::styled("Toggle selection of current agent", desc_style),
-            ]),
-            Line::from(vec![
-                Span::styled("  Ctrl+a   ", key_style),
-                Span::styled("Select all agents", desc_style),
-            ]),
-            Line::from(vec![
-                Span::styled("  Esc      ", key_style),
-                Span::styled("Clear selection / Close subagent log", desc_style),
-            ]),
-            Line::from(vec![]),
-            Line::from(vec![Span::styled("Actions", section_style)]),
-            Line::from(vec![]),
-            Line::from(vec![
-                Span::styled("  y / Y    ", key_style),
-                Span::styled("Approve pending request(s)", desc_style),
-            ]),
-            Line::from(vec![
-                Span::styled("  n / N    ", key_style),
-                Span::styled("Reject pending request(s)", desc_style),
-            ]),
-            Line::from(vec![
-                Span::styled("  a / A    ", key_style),
-                Span::styled("Approve all pending requests", desc_style),
-            ]),
-            Line::from(vec![
-                Span::styled("  1-9      ", key_style),
-                Span::styled("Send number choice to agent", desc_style),
-            ]),
-            Line::from(vec![
-                Span::styled("  ← / →    ", key_style),
-                Span::styled("Switch focus (Sidebar / Input)", desc_style),
-            ]),
-            Line::from(vec![
-                Span::styled("  f / F    ", key_style),
-                Span::styled("Focus on selected pane in tmux", desc_style),
-            ]),
-            Line::from(vec![]),
-            Line::from(vec![Span::styled("View", section_style)]),
-            Line::from(vec![]),
-            Line::from(vec![
-                Span::styled("  s / S    ", key_style),
-                Span::styled("Toggle subagent log", desc_style),
-            ]),
-            Line::from(vec![
-                Span::styled("  t / T    ", key_style),
-                Span::styled("Toggle TODO/Tools display", desc_style),
-            ]),
-            Line::from(vec![
-                Span::styled("  r        ", key_style),
-                Span::styled("Refresh / clear error", desc_style),
-            ]),
-            Line::from(vec![]),
-            Line::from(vec![Span::styled("General", section_style)]),
-            Line::from(vec![]),
-            Line::from(vec![
-                Span::styled("  h / ?    ", key_style),
-                Span::styled("Toggle this help", desc_style),
-            ]),
-            Line::from(vec![
-                Span::styled("  q        ", key_style),
-                Span::styled("Quit", desc_style),
-            ]),
-            Line::from(vec![]),
-            Line::from(vec![Span::styled(
-                "  Press any key to close this help",
-                Style::default().fg(Color::DarkGray),
-            )]),
-        ];
+        let sections: Vec<(&str, Vec<(String, &str)>)> = SECTIONS
+            .iter()
+            .map(|(title, rows)| {
+                let rows = rows
+                    .iter()
+                    .map(|(keys, desc)| {
+                        let keys = match keys {
+                            HelpKeys::Bound(actions) => {
+                                let keys: Vec<String> = actions
+                                    .iter()
+                                    .map(|a| keymap.keys_for(a))
+                                    .filter(|k| !k.is_empty())
+                                    .collect();
+                                keys.join(" / ")
+                            }
+                            HelpKeys::Fixed(keys) => keys.to_string(),
+                        };
+                        (keys, *desc)
+                    })
+                    .filter(|(keys, _)| !keys.is_empty())
+                    .collect();
+                (*title, rows)
+            })
+            .collect();
+        let key_width = sections
+            .iter()
+            .flat_map(|(_, rows)| rows.iter().map(|(keys, _)| keys.chars().count()))
+            .max()
+            .unwrap_or(0)
+            .max(8);
+
+        let mut help_text = Vec::new();
+        for (title, rows) in sections {
+            if !help_text.is_empty() {
+                help_text.push(Line::from(vec![]));
+            }
+            help_text.push(Line::from(vec![Span::styled(title, section_style)]));
+            help_text.push(Line::from(vec![]));
+            for (keys, desc) in rows {
+                help_text.push(Line::from(vec![
+                    Span::styled(format!("  {:<width$} ", keys, width = key_width), key_style),
+                    Span::styled(desc, desc_style),
+                ]));
+            }
+        }
+
+        help_text.push(Line::from(vec![]));
+        help_text.push(Line::from(vec![Span::styled("Settings", section_style)]));
+        help_text.push(Line::from(vec![]));
+        for (name, value) in settings(config) {
+            help_text.push(Line::from(vec![
+                Span::styled(
+                    format!("  {:<width$} ", name, width = key_width),
+                    Style::default().fg(theme().muted),
+                ),
+                Span::styled(value, desc_style),
+            ]));
+        }
+
+        help_text.push(Line::from(vec![]));
+        help_text.push(Line::from(vec![Span::styled(
+            "  j/k, PgUp/PgDn scroll · any other key closes this help",
+            Style::default().fg(theme().dim),
+        )]));
+
+        let max_scroll = help_text
+            .len()
+            .saturating_sub(popup_area.height.saturating_sub(2) as usize);
+        let scroll = state.help_scroll.min(max_scroll);
 
         let block = Block::default()
             .title(" Help ")
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(Color::Cyan))
-            .style(Style::default().bg(Color::Black));
+            .border_style(Style::default().fg(theme().accent))
+            .style(Style::default().bg(theme().background));
 
-        let paragraph = Paragraph::new(help_text).block(block);
+        let paragraph = Paragraph::new(help_text)
+            .block(block)
+            .scroll((scroll as u16, 0));
 
         frame.render_widget(paragraph, popup_area);
+        max_scroll
     }
 }
+
+/// Settings shown at the end of the help, as (name, value) rows
+fn settings(config: &Config) -> Vec<(&'static str, String)> {
+    let mut config_file = match (&config.source, &config.profile) {
+        (Some(path), Some(profile)) => format!("{}, profile {}", path.display(), profile),
+        (Some(path), None) => path.display().to_string(),
+        (None, _) => match Config::default_path() {
+            Some(path) => format!("none, defaults ({} not found)", path.display()),
+            None => "none, defaults".to_string(),
+        },
+    };
+    if !config.env.is_empty() {
+        config_file.push_str(&format!(", with {}", config.env.join(", ")));
+    }
+    let profile = match config.keys.profile {
+        KeyProfile::Vim => "vim",
+        KeyProfile::Classic => "classic",
+    };
+    let overrides = config.keys.bindings.len();
+    let updates = match (config.control_mode, config.tmux_hooks) {
+        (true, _) => "tmux control mode",
+        (false, true) => "polling + tmux hooks",
+        (false, false) => "polling",
+    };
+    let servers: Vec<String> = config.servers().iter().map(|s| s.label()).collect();
+
+    vec![
+        ("Config", config_file),
+        (
+            "Keys",
+            match overrides {
+                0 => format!("{} profile", profile),
+                n => format!("{} profile, {} overridden", profile, n),
+            },
+        ),
+        (
+            "Polling",
+            match config.pane_polling.len() {
+                0 => format!(
+                    "every {} ms, {} lines per pane",
+                    config.poll_interval_ms, config.capture_lines
+                ),
+                n => format!(
+                    "every {} ms, {} lines per pane, {} pane rules",
+                    config.poll_interval_ms, config.capture_lines, n
+                ),
+            },
+        ),
+        ("Updates", updates.to_string()),
+        ("Servers", servers.join(", ")),
+        (
+            "Icons",
+            match config.use_icons {
+                UseIcons::Auto => format!("{} (detected)", icons().name),
+                _ => icons().name.to_string(),
+            },
+        ),
+        (
+            "Desktop",
+            if config.desktop_notifications.enabled {
+                let events: Vec<String> = config
+                    .desktop_notifications
+                    .on
+                    .iter()
+                    .map(|event| format!("{:?}", event).to_lowercase())
+                    .collect();
+                format!("notifications on {}", events.join(", "))
+            } else {
+                "no notifications".to_string()
+            },
+        ),
+        (
+            "tmux alerts",
+            if config.tmux_alerts.enabled {
+                let events: Vec<String> = config
+                    .tmux_alerts
+                    .on
+                    .iter()
+                    .map(|event| format!("{:?}", event).to_lowercase())
+                    .collect();
+                let bell = if config.tmux_alerts.bell {
+                    " with a bell"
+                } else {
+                    ""
+                };
+                format!("on {}{}", events.join(", "), bell)
+            } else {
+                "off".to_string()
+            },
+        ),
+        ("Webhooks", {
+            // URLs and tokens are secrets, so only say what is set up
+            let mut targets = match config.webhooks.len() {
+                0 => Vec::new(),
+                1 => vec!["1 URL".to_string()],
+                n => vec![format!("{} URLs", n)],
+            };
+            if !config.slack.webhook_url.is_empty() || !config.slack.token.is_empty() {
+                targets.push("Slack".to_string());
+            }
+            if !config.discord.webhook_url.is_empty() {
+                targets.push("Discord".to_string());
+            }
+            if targets.is_empty() {
+                "none".to_string()
+            } else {
+                targets.join(", ")
+            }
+        }),
+        (
+            "Routing",
+            format!(
+                "{} notify rules, repeats held back {} s",
+                config.notify_rules.len(),
+                config.notify_dedup_secs
+            ),
+        ),
+        (
+            "Hooks",
+            if config.hooks.is_empty() {
+                "none".to_string()
+            } else if config.hooks.cost_threshold > 0.0 {
+                format!("set, cost at ${:.2}", config.hooks.cost_threshold)
+            } else {
+                "set".to_string()
+            },
+        ),
+        (
+            "Tests",
+            if config.test_command.is_empty() {
+                "none (or per .tmuxcc.toml)".to_string()
+            } else {
+                config.test_command.clone()
+            },
+        ),
+    ]
+}
@@ -1,6 +1,6 @@
 use ratatui::{
     layout::Rect,
-    style::{Color, Style},
+    style::Style,
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Paragraph, Wrap},
     Frame,
@@ -8,6 +8,7 @@ use ratatui::{
 use unicode_width::UnicodeWidthStr;
 
 use crate::app::AppState;
+use crate::ui::theme;
 
 /// Input widget for text entry at the bottom of the right column
 pub struct InputWidget;
@@ -24,12 +25,19 @@ impl InputWidget {
             .map(|a| a.abbreviated_path())
             .unwrap_or_else(|| "None".to_string());
 
-        let title = format!(" Input → {} ", target_name);
+        let targets = state.input_targets().len();
+        let title = if state.broadcast && targets > 1 {
+            format!(" Broadcast → {} panes ", targets)
+        } else {
+            format!(" Input → {} ", target_name)
+        };
 
-        let border_color = if is_focused {
-            Color::Green
+        let border_color = if state.broadcast && targets > 1 {
+            theme().special
+        } else if is_focused {
+            theme().success
         } else {
-            Color::DarkGray
+            theme().dim
         };
 
         let block = Block::default()
@@ -39,7 +47,28 @@ impl InputWidget {
             .border_style(Style::default().fg(border_color));
 
         // Build content with cursor (only show cursor when focused)
-        let lines: Vec<Line> = Self::build_lines_with_cursor(buffer, cursor_pos, is_focused);
+        let mut lines: Vec<Line> = Self::build_lines_with_cursor(buffer, cursor_pos, is_focused);
+        // Until the next prompt is typed, confirm what the last one sent
+        if let Some((target, text)) = state.last_sent.as_ref().filter(|_| buffer.is_empty()) {
+            let first_line = text.lines().next().unwrap_or_default();
+            let more = if text.lines().count() > 1 { " …" } else { "" };
+            let mut spans = Vec::new();
+            if is_focused {
+                spans.push(Span::styled(
+                    "█ ",
+                    Style::default().fg(theme().inverse).bg(theme().success),
+                ));
+            }
+            spans.push(Span::styled(
+                format!("✓ Sent to {}: ", target),
+                Style::default().fg(theme().success),
+            ));
+            spans.push(Span::styled(
+                format!("{}{}", first_line, more),
+                Style::default().fg(theme().muted),
+            ));
+            lines = vec![Line::from(spans)];
+        }
 
         let paragraph = Paragraph::new(lines)
             .block(block)
@@ -59,9 +88,9 @@ impl InputWidget {
         cursor_pos: usize,
         is_focused: bool,
     ) -> Vec<Line<'static>> {
-        let cursor_style = Style::default().fg(Color::Black).bg(Color::Green);
-        let text_style = Style::default().fg(Color::White);
-        let hint_style = Style::default().fg(Color::DarkGray);
+        let cursor_style = Style::default().fg(theme().inverse).bg(theme().success);
+        let text_style = Style::default().fg(theme().text);
+        let hint_style = Style::default().fg(theme().dim);
 
         if buffer.is_empty() {
             if is_focused {
@@ -74,7 +103,7 @@ impl InputWidget {
                 ])];
             } else {
                 return vec![Line::from(vec![Span::styled(
-                    "← arrow key to input",
+                    "i or → to type a prompt",
                     hint_style,
                 )])];
             }
@@ -1,15 +1,27 @@
 mod agent_tree;
+mod confirm;
 mod footer;
 mod header;
 mod help;
 mod input;
+mod palette;
 mod pane_preview;
+mod picker;
+mod prompt;
+mod status_bar;
 mod subagent_log;
+mod viewer;
 
-pub use agent_tree::AgentTreeWidget;
+pub use agent_tree::{AgentTreeWidget, TreeRow};
+pub use confirm::ConfirmWidget;
 pub use footer::{FooterButton, FooterWidget};
 pub use header::HeaderWidget;
 pub use help::HelpWidget;
 pub use input::InputWidget;
+pub use palette::PaletteWidget;
 pub use pane_preview::PanePreviewWidget;
+pub use picker::PickerWidget;
+pub use prompt::PromptWidget;
+pub use status_bar::StatusBarWidget;
 pub use subagent_log::SubagentLogWidget;
+pub use viewer::ViewerWidget;
@@ -0,0 +1,90 @@
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::app::AppState;
+use crate::ui::theme;
+
+/// Command palette popup: the query above the matching actions
+pub struct PaletteWidget;
+
+impl PaletteWidget {
+    pub fn render(frame: &mut Frame, area: Rect, state: &AppState) {
+        let Some(palette) = &state.palette else {
+            return;
+        };
+        let matches = palette.matches();
+
+        let width = area.width.saturating_sub(4).clamp(20, 60);
+        let height = (matches.len() as u16 + 4).clamp(5, area.height.saturating_sub(4).max(5));
+        let popup_area = Rect::new(
+            area.x + (area.width.saturating_sub(width)) / 2,
+            area.y + (area.height.saturating_sub(height)) / 3,
+            width,
+            height.min(area.height),
+        );
+
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .title(" Command palette ")
+            .title_bottom(Line::from(" Enter: Run  ↑↓: Select  Esc: Cancel ").right_aligned())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(theme().accent))
+            .style(Style::default().bg(theme().background));
+        let inner = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        let [query_area, list_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Min(0)]).areas(inner);
+        let query = Paragraph::new(Line::from(vec![
+            Span::styled("> ", Style::default().fg(theme().accent)),
+            Span::styled(palette.query.as_str(), Style::default().fg(theme().text)),
+            Span::styled("█", Style::default().fg(theme().accent)),
+        ]));
+        frame.render_widget(query, query_area);
+
+        if matches.is_empty() {
+            let empty = Paragraph::new(Span::styled(
+                "  no matching command",
+                Style::default().fg(theme().muted),
+            ));
+            frame.render_widget(empty, list_area);
+            return;
+        }
+
+        let items: Vec<ListItem> = matches
+            .iter()
+            .map(|entry| {
+                let description = entry.action.description();
+                let pad = (list_area.width as usize)
+                    .saturating_sub(description.chars().count() + entry.keys.chars().count() + 3);
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!(" {}", description),
+                        Style::default().fg(theme().text),
+                    ),
+                    Span::raw(" ".repeat(pad)),
+                    Span::styled(
+                        format!("{} ", entry.keys),
+                        Style::default().fg(theme().muted),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items).highlight_style(
+            Style::default()
+                .bg(theme().dim)
+                .add_modifier(Modifier::BOLD),
+        );
+
+        let mut list_state = ListState::default().with_selected(Some(palette.selected));
+        frame.render_stateful_widget(list, list_area, &mut list_state);
+    }
+}
@@ -1,6 +1,6 @@
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Paragraph, Wrap},
     Frame,
@@ -8,6 +8,8 @@ use ratatui::{
 
 use crate::agents::AgentStatus;
 use crate::app::AppState;
+use crate::git::{CiState, GitInfo, ReviewState};
+use crate::ui::{ansi, icons, theme, Styles};
 
 /// Parsed summary info from Claude Code content
 struct ClaudeCodeSummary {
@@ -98,7 +100,7 @@ impl PanePreviewWidget {
                 .title(format!(" {} ", agent.agent_type.short_name()))
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
-                .border_style(Style::default().fg(Color::Gray));
+                .border_style(Style::default().fg(theme().muted));
 
             let inner_area = outer_block.inner(area);
             frame.render_widget(outer_block, area);
@@ -115,14 +117,14 @@ impl PanePreviewWidget {
                 todo_lines.push(Line::from(vec![Span::styled(
                     "TODOs:",
                     Style::default()
-                        .fg(Color::Gray)
+                        .fg(theme().muted)
                         .add_modifier(Modifier::BOLD),
                 )]));
                 for (completed, text) in &summary.todos {
                     let (icon, style) = if *completed {
-                        ("☑ ", Style::default().fg(Color::DarkGray))
+                        ("☑ ", Style::default().fg(theme().dim))
                     } else {
-                        ("☐ ", Style::default().fg(Color::White))
+                        ("☐ ", Style::default().fg(theme().text))
                     };
                     todo_lines.push(Line::from(vec![
                         Span::styled(format!(" {}", icon), style),
@@ -132,24 +134,113 @@ impl PanePreviewWidget {
             } else {
                 todo_lines.push(Line::from(vec![Span::styled(
                     "No TODOs",
-                    Style::default().fg(Color::DarkGray),
+                    Style::default().fg(theme().dim),
                 )]));
             }
 
             let todo_paragraph = Paragraph::new(todo_lines).wrap(Wrap { trim: false });
             frame.render_widget(todo_paragraph, columns[0]);
 
-            // Right column: Activity and tools
+            // Right column: note and state, then activity and tools
+            let mut header_lines: Vec<Line> = Vec::new();
+
+            // Note left by the user
+            if let Some(note) = &agent.note {
+                header_lines.push(Line::from(vec![
+                    Span::styled("✎ ", Style::default().fg(theme().special)),
+                    Span::styled(note.clone(), Style::default().fg(theme().special)),
+                ]));
+                header_lines.push(Line::from(""));
+            }
+
+            // State badge and recent changes
+            header_lines.push(Line::from(vec![
+                Span::styled(
+                    format!(" {} ", agent.state_label()),
+                    Styles::agent_badge(agent),
+                ),
+                Span::styled(
+                    format!(
+                        " for {} · {}",
+                        agent.state_age_str(),
+                        agent.session_times_str()
+                    ),
+                    Style::default().fg(theme().dim),
+                ),
+            ]));
+            if let (Some(countdown), Some(reset)) =
+                (agent.rate_limit_countdown(), agent.rate_limit_reset)
+            {
+                header_lines.push(Line::from(vec![Span::styled(
+                    format!(
+                        " ⏳ Rate limited, resets {} (in {})",
+                        reset.format("%H:%M"),
+                        countdown
+                    ),
+                    Style::default().fg(theme().highlight),
+                )]));
+            }
+            if let Some(ctx) = agent.context_remaining {
+                let low = ctx < state.context_warning;
+                let style = if low {
+                    Style::default()
+                        .fg(theme().danger)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(theme().dim)
+                };
+                header_lines.push(Line::from(vec![Span::styled(
+                    format!(
+                        " Context {}% used{}",
+                        100 - ctx.min(100),
+                        if low { " · auto-compact soon" } else { "" }
+                    ),
+                    style,
+                )]));
+            }
+            let key = agent.key();
+            for transition in state.transitions_for(&key).take(3) {
+                let from = transition.from.map_or("NEW", |s| s.label());
+                header_lines.push(Line::from(vec![Span::styled(
+                    format!(
+                        " {} {} → {}",
+                        transition.at.format("%H:%M:%S"),
+                        from,
+                        transition.to.label()
+                    ),
+                    Style::default().fg(theme().dim),
+                )]));
+            }
+            header_lines.push(Line::from(""));
+
+            // Prompts waiting for the agent to become idle
+            let queued: Vec<&String> = state.queued(&key).collect();
+            if !queued.is_empty() {
+                header_lines.push(Line::from(vec![Span::styled(
+                    format!("Queued ({}, U to reorder):", queued.len()),
+                    Style::default()
+                        .fg(theme().muted)
+                        .add_modifier(Modifier::BOLD),
+                )]));
+                for (i, text) in queued.iter().enumerate() {
+                    header_lines.push(Line::from(vec![
+                        Span::styled(format!(" {}. ", i + 1), Style::default().fg(theme().info)),
+                        Span::styled(text.to_string(), Style::default().fg(theme().text)),
+                    ]));
+                }
+                header_lines.push(Line::from(""));
+            }
+
             let mut activity_lines: Vec<Line> = Vec::new();
 
             // Current activity
             if let Some(activity) = &summary.current_activity {
                 activity_lines.push(Line::from(vec![
-                    Span::styled("▶ ", Style::default().fg(Color::Yellow)),
+                    Span::styled("▶ ", Style::default().fg(theme().highlight)),
                     Span::styled(
                         activity.clone(),
                         Style::default()
-                            .fg(Color::Yellow)
+                            .fg(theme().highlight)
                             .add_modifier(Modifier::BOLD),
                     ),
                 ]));
@@ -161,13 +252,13 @@ impl PanePreviewWidget {
                 activity_lines.push(Line::from(vec![Span::styled(
                     "Tools:",
                     Style::default()
-                        .fg(Color::Gray)
+                        .fg(theme().muted)
                         .add_modifier(Modifier::BOLD),
                 )]));
                 for tool in &summary.recent_tools {
                     activity_lines.push(Line::from(vec![
-                        Span::styled(" ⏺ ", Style::default().fg(Color::Cyan)),
-                        Span::styled(tool.clone(), Style::default().fg(Color::White)),
+                        Span::styled(" ⏺ ", Style::default().fg(theme().accent)),
+                        Span::styled(tool.clone(), Style::default().fg(theme().text)),
                     ]));
                 }
             }
@@ -179,10 +270,12 @@ impl PanePreviewWidget {
                     AgentStatus::Processing { activity } => activity.as_str(),
                     AgentStatus::AwaitingApproval { approval_type, .. } => {
                         activity_lines.push(Line::from(vec![
-                            Span::styled("⚠ ", Style::default().fg(Color::Red)),
+                            Span::styled("⚠ ", Style::default().fg(theme().danger)),
                             Span::styled(
                                 format!("Waiting: {}", approval_type),
-                                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                                Style::default()
+                                    .fg(theme().danger)
+                                    .add_modifier(Modifier::BOLD),
                             ),
                         ]));
                         ""
@@ -193,12 +286,36 @@ impl PanePreviewWidget {
                 if !status_text.is_empty() && activity_lines.is_empty() {
                     activity_lines.push(Line::from(vec![Span::styled(
                         status_text,
-                        Style::default().fg(Color::Gray),
+                        Style::default().fg(theme().muted),
                     )]));
                 }
             }
 
-            let activity_paragraph = Paragraph::new(activity_lines).wrap(Wrap { trim: false });
+            // Git repository details
+            if let Some(git) = &agent.git {
+                activity_lines.push(Line::from(""));
+                activity_lines.extend(git_lines(git));
+            }
+
+            // Statistics kept by the pane registry
+            if agent.stats.first_seen > 0 {
+                activity_lines.push(Line::from(""));
+                let mut stats = format!(
+                    "{} working · {} prompts",
+                    agent.stats.working_str(),
+                    agent.stats.prompts
+                );
+                if agent.stats.usage.cost_usd > 0.0 || agent.stats.usage.tokens() > 0 {
+                    stats.push_str(&format!(" · {}", agent.stats.usage.summary()));
+                }
+                activity_lines.push(Line::from(vec![Span::styled(
+                    stats,
+                    Style::default().fg(theme().dim),
+                )]));
+            }
+
+            header_lines.extend(activity_lines);
+            let activity_paragraph = Paragraph::new(header_lines).wrap(Wrap { trim: false });
             frame.render_widget(activity_paragraph, columns[1]);
         } else {
             // No agent selected
@@ -206,11 +323,11 @@ impl PanePreviewWidget {
                 .title(" Summary ")
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
-                .border_style(Style::default().fg(Color::Gray));
+                .border_style(Style::default().fg(theme().muted));
 
             let paragraph = Paragraph::new(vec![Line::from(vec![Span::styled(
                 "No agent selected",
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme().dim),
             )])])
             .block(block);
 
@@ -250,12 +367,12 @@ impl PanePreviewWidget {
             .title(title)
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(Color::Gray));
+            .border_style(Style::default().fg(theme().muted));
 
         let paragraph = Paragraph::new(content)
             .block(block)
             .wrap(Wrap { trim: false })
-            .style(Style::default().fg(Color::White));
+            .style(Style::default().fg(theme().text));
 
         frame.render_widget(paragraph, area);
     }
@@ -268,27 +385,44 @@ impl PanePreviewWidget {
         let available_lines = area.height.saturating_sub(2) as usize;
 
         let (title, lines) = if let Some(agent) = agent {
-            let title = format!(" {} ({}) ", agent.target, agent.agent_type);
+            let scroll = state.preview_scroll();
+            let title = if scroll > 0 {
+                format!(" {} ({}) ↑{} ", agent.target, agent.agent_type, scroll)
+            } else {
+                format!(" {} ({}) ", agent.target, agent.agent_type)
+            };
+
+            // Keep the pane's own colors when they were captured
+            if !agent.last_content_styled.is_empty() {
+                let mut lines = ansi::styled_lines(&agent.last_content_styled);
+                lines.truncate(lines.len().saturating_sub(scroll).max(1));
+                let start = lines.len().saturating_sub(available_lines);
+                let lines = lines.split_off(start);
+                Self::render_lines(frame, area, title, lines);
+                return;
+            }
 
             let mut styled_lines: Vec<Line> = Vec::new();
 
-            // Take enough lines to fill the area
+            // Take enough lines to fill the area, ending `scroll` lines before the last
             let content_lines: Vec<&str> = agent.last_content.lines().collect();
-            let start = content_lines.len().saturating_sub(available_lines);
+            let end = content_lines.len().saturating_sub(scroll).max(1);
+            let end = end.min(content_lines.len());
+            let start = end.saturating_sub(available_lines);
 
-            for line in &content_lines[start..] {
+            for line in &content_lines[start..end] {
                 let spans = if line.starts_with('+') && !line.starts_with("+++") {
-                    vec![Span::styled(*line, Style::default().fg(Color::Green))]
+                    vec![Span::styled(*line, Style::default().fg(theme().success))]
                 } else if line.starts_with('-') && !line.starts_with("---") {
-                    vec![Span::styled(*line, Style::default().fg(Color::Red))]
+                    vec![Span::styled(*line, Style::default().fg(theme().danger))]
                 } else if line.starts_with("@@") {
-                    vec![Span::styled(*line, Style::default().fg(Color::Cyan))]
+                    vec![Span::styled(*line, Style::default().fg(theme().accent))]
                 } else if line.contains("[y/n]") || line.contains("[Y/n]") {
-                    vec![Span::styled(*line, Style::default().fg(Color::Yellow))]
+                    vec![Span::styled(*line, Style::default().fg(theme().highlight))]
                 } else if line.contains("⚠") || line.contains("Error") || line.contains("error") {
-                    vec![Span::styled(*line, Style::default().fg(Color::Red))]
+                    vec![Span::styled(*line, Style::default().fg(theme().danger))]
                 } else if line.starts_with("❯") || line.starts_with(">") {
-                    vec![Span::styled(*line, Style::default().fg(Color::Cyan))]
+                    vec![Span::styled(*line, Style::default().fg(theme().accent))]
                 } else {
                     vec![Span::raw(*line)]
                 };
@@ -302,16 +436,20 @@ impl PanePreviewWidget {
                 " Preview ".to_string(),
                 vec![Line::from(vec![Span::styled(
                     "No agent selected",
-                    Style::default().fg(Color::DarkGray),
+                    Style::default().fg(theme().dim),
                 )])],
             )
         };
 
+        Self::render_lines(frame, area, title, lines);
+    }
+
+    fn render_lines(frame: &mut Frame, area: Rect, title: String, lines: Vec<Line>) {
         let block = Block::default()
             .title(title)
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(Color::Gray));
+            .border_style(Style::default().fg(theme().muted));
 
         let paragraph = Paragraph::new(lines)
             .block(block)
@@ -320,3 +458,156 @@ impl PanePreviewWidget {
         frame.render_widget(paragraph, area);
     }
 }
+
+/// Builds the git detail lines for the summary panel
+fn git_lines(git: &GitInfo) -> Vec<Line<'static>> {
+    let label_style = Style::default()
+        .fg(theme().muted)
+        .add_modifier(Modifier::BOLD);
+    let mut lines = vec![Line::from(vec![Span::styled("Git:", label_style)])];
+
+    let mut branch_spans = vec![
+        Span::styled(
+            format!(" {} ", icons().branch),
+            Style::default().fg(theme().special),
+        ),
+        Span::styled(git.label(), Style::default().fg(theme().text)),
+    ];
+    if let Some(upstream) = &git.upstream {
+        branch_spans.push(Span::styled(
+            format!(" → {}", upstream),
+            Style::default().fg(theme().dim),
+        ));
+    }
+    if git.upstream_gone {
+        branch_spans.push(Span::styled(
+            " (gone)",
+            Style::default()
+                .fg(theme().danger)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    if git.bare {
+        branch_spans.push(Span::styled(
+            " (bare repository)",
+            Style::default().fg(theme().dim),
+        ));
+    }
+    lines.push(Line::from(branch_spans));
+
+    if let Some(pr) = &git.pull_request {
+        let (ci_text, ci_color) = match pr.ci {
+            CiState::Success => ("CI passed", theme().success),
+            CiState::Failure => ("CI failed", theme().danger),
+            CiState::Pending => ("CI running", theme().highlight),
+            CiState::None => ("no checks", theme().dim),
+        };
+        let mut spans = vec![
+            Span::styled(
+                format!(" PR #{} ", pr.number),
+                Style::default().fg(theme().info),
+            ),
+            Span::styled(pr.state.to_lowercase(), Style::default().fg(theme().dim)),
+        ];
+        if !pr.review.label().is_empty() {
+            let review_color = match pr.review {
+                ReviewState::Approved => theme().success,
+                ReviewState::ChangesRequested => theme().danger,
+                _ => theme().highlight,
+            };
+            spans.push(Span::styled(" · ", Style::default().fg(theme().dim)));
+            spans.push(Span::styled(
+                pr.review.label(),
+                Style::default().fg(review_color),
+            ));
+        }
+        spans.push(Span::styled(" · ", Style::default().fg(theme().dim)));
+        spans.push(Span::styled(
+            format!("{} {}", pr.ci.icon(), ci_text)
+                .trim_start()
+                .to_string(),
+            Style::default().fg(ci_color),
+        ));
+        lines.push(Line::from(spans));
+    }
+
+    if git.can_open_pr() {
+        lines.push(Line::from(vec![Span::styled(
+            " Press P to open a pull request",
+            Style::default().fg(theme().info),
+        )]));
+    }
+
+    if git.upstream_gone {
+        lines.push(Line::from(vec![Span::styled(
+            " ⚠ Upstream branch was deleted; pushes will recreate it",
+            Style::default().fg(theme().danger),
+        )]));
+    }
+
+    if let Some(op) = git.operation {
+        lines.push(Line::from(vec![Span::styled(
+            format!(" ⚠ {} in progress", op.label()),
+            Style::default()
+                .fg(theme().danger)
+                .add_modifier(Modifier::BOLD),
+        )]));
+    }
+
+    if let Some(slug) = &git.repo_slug {
+        lines.push(Line::from(vec![
+            Span::styled(" ⌂ ", Style::default().fg(theme().accent)),
+            Span::styled(slug.clone(), Style::default().fg(theme().accent)),
+        ]));
+    }
+
+    if let Some(commit) = &git.last_commit {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!(" {} ", commit.short_hash),
+                Style::default().fg(theme().highlight),
+            ),
+            Span::styled(commit.subject.clone(), Style::default().fg(theme().text)),
+            Span::styled(
+                format!(" ({})", commit.age_str()),
+                Style::default().fg(theme().dim),
+            ),
+        ]));
+    }
+
+    if git.stash_count > 0 {
+        lines.push(Line::from(vec![Span::styled(
+            format!(
+                " ≡ {} stash{}",
+                git.stash_count,
+                if git.stash_count == 1 { "" } else { "es" }
+            ),
+            Style::default().fg(theme().highlight),
+        )]));
+    }
+
+    if git.worktrees.len() > 1 {
+        lines.push(Line::from(vec![Span::styled(
+            format!(" Worktrees ({}):", git.worktrees.len()),
+            Style::default().fg(theme().muted),
+        )]));
+        for wt in &git.worktrees {
+            let mut spans = vec![
+                Span::styled(
+                    format!("  {} ", wt.display_branch()),
+                    Style::default().fg(theme().special),
+                ),
+                Span::styled(
+                    wt.path.display().to_string(),
+                    Style::default().fg(theme().dim),
+                ),
+            ];
+            if wt.locked {
+                spans.push(Span::styled(" 🔒", Style::default().fg(theme().highlight)));
+            }
+            lines.push(Line::from(spans));
+        }
+    }
+
+    lines
+}
@@ -0,0 +1,68 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::app::AppState;
+use crate::ui::theme;
+
+/// List picker popup
+pub struct PickerWidget;
+
+impl PickerWidget {
+    pub fn render(frame: &mut Frame, area: Rect, state: &AppState) {
+        let Some(picker) = &state.picker else {
+            return;
+        };
+
+        let width = area.width.saturating_sub(4).clamp(20, 50);
+        let height = (picker.items.len() as u16 + 2).clamp(3, area.height.saturating_sub(4));
+        let popup_area = Rect::new(
+            area.x + (area.width.saturating_sub(width)) / 2,
+            area.y + (area.height.saturating_sub(height)) / 2,
+            width,
+            height,
+        );
+
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .title(format!(" {} ", picker.kind.title()))
+            .title_bottom(Line::from(" Enter: OK  F: Force  Esc: Cancel ").right_aligned())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(theme().success))
+            .style(Style::default().bg(theme().background));
+
+        let items: Vec<ListItem> = picker
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let is_current = picker.current == Some(i);
+                let marker = if is_current { "● " } else { "  " };
+                let style = if is_current {
+                    Style::default().fg(theme().success)
+                } else {
+                    Style::default().fg(theme().text)
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(marker, Style::default().fg(theme().success)),
+                    Span::styled(item.clone(), style),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items).block(block).highlight_style(
+            Style::default()
+                .bg(theme().dim)
+                .add_modifier(Modifier::BOLD),
+        );
+
+        let mut list_state = ListState::default().with_selected(Some(picker.selected));
+        frame.render_stateful_widget(list, popup_area, &mut list_state);
+    }
+}
@@ -0,0 +1,46 @@
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::AppState;
+use crate::ui::theme;
+
+/// Single-line prompt popup
+pub struct PromptWidget;
+
+impl PromptWidget {
+    pub fn render(frame: &mut Frame, area: Rect, state: &AppState) {
+        let Some(prompt) = &state.prompt else {
+            return;
+        };
+
+        let width = area.width.saturating_sub(4).clamp(20, 60);
+        let popup_area = Rect::new(
+            area.x + (area.width.saturating_sub(width)) / 2,
+            area.y + area.height.saturating_sub(3) / 2,
+            width,
+            3,
+        );
+
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .title(format!(" {} ", prompt.kind.title()))
+            .title_bottom(Line::from(" Enter: OK  Esc: Cancel ").right_aligned())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(theme().success))
+            .style(Style::default().bg(theme().background));
+
+        let line = Line::from(vec![
+            Span::styled(prompt.buffer.clone(), Style::default().fg(theme().text)),
+            Span::styled("█", Style::default().fg(theme().success)),
+        ]);
+
+        frame.render_widget(Paragraph::new(line).block(block), popup_area);
+    }
+}
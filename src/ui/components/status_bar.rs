@@ -0,0 +1,143 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::agents::AgentState;
+use crate::app::{AppState, SortMode};
+use crate::ui::{icons, theme};
+
+/// Bottom line with totals per state, alerts, cost, the sound switch and
+/// the list settings, shown under every view
+pub struct StatusBarWidget;
+
+impl StatusBarWidget {
+    /// Renders the bar and returns where its sound switch is, for clicks
+    pub fn render(frame: &mut Frame, area: Rect, state: &AppState) -> Rect {
+        let agents = &state.agents.root_agents;
+        let sep = Style::default().fg(theme().dim);
+        let count = |s: Option<AgentState>| agents.iter().filter(|a| a.state == s).count();
+
+        let mut spans = Vec::new();
+        let states = [
+            (
+                count(Some(AgentState::WaitingForPermission)),
+                icons().permission,
+                "permission",
+                theme().permission,
+            ),
+            (
+                count(Some(AgentState::WaitingForInput)),
+                icons().input,
+                "input",
+                theme().waiting,
+            ),
+            (
+                count(Some(AgentState::Error)),
+                icons().error,
+                "error",
+                theme().error,
+            ),
+            (
+                count(Some(AgentState::Working)),
+                icons().spinner_frame(state.tick),
+                "working",
+                theme().working,
+            ),
+            (
+                count(Some(AgentState::Idle)),
+                icons().idle,
+                "idle",
+                theme().idle,
+            ),
+            (count(None), icons().unknown, "unknown", theme().unknown),
+            (
+                agents.iter().filter(|a| a.stalled_since.is_some()).count(),
+                icons().stalled,
+                "stalled",
+                theme().stalled,
+            ),
+        ];
+        for (n, icon, label, color) in states {
+            if n > 0 {
+                spans.push(Span::styled(
+                    format!(" {} {} {} ", icon, n, label),
+                    Style::default().fg(color),
+                ));
+            }
+        }
+        if spans.is_empty() {
+            spans.push(Span::styled(" no agents ", sep));
+        }
+
+        let unread = state.unread_count();
+        if unread > 0 {
+            spans.push(Span::styled("│", sep));
+            spans.push(Span::styled(
+                format!(" {} {} unread ", icons().alert, unread),
+                Style::default()
+                    .fg(theme().alert)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+
+        let cost: f64 = agents.iter().map(|a| a.stats.usage.cost_usd).sum();
+        if cost > 0.0 {
+            spans.push(Span::styled("│", sep));
+            spans.push(Span::styled(
+                format!(" ${:.2} ", cost),
+                Style::default().fg(theme().highlight),
+            ));
+        }
+
+        let mut mute = Rect::default();
+        if state.sounds {
+            spans.push(Span::styled("│", sep));
+            let (text, color) = if state.muted {
+                (format!(" {} muted ", icons().muted), theme().muted)
+            } else {
+                (format!(" {} sound ", icons().sound), theme().accent)
+            };
+            let x: usize = spans.iter().map(Span::width).sum();
+            mute = Rect::new(area.x + x as u16, area.y, text.width() as u16, 1).intersection(area);
+            spans.push(Span::styled(text, Style::default().fg(color)));
+        }
+
+        if let Some(server) = state.server_tab.and_then(|i| state.servers.get(i)) {
+            spans.push(Span::styled("│", sep));
+            spans.push(Span::styled(
+                format!(" on {} ", server.label()),
+                Style::default().fg(theme().accent),
+            ));
+        }
+        if let Some(search) = &state.search {
+            spans.push(Span::styled("│", sep));
+            spans.push(Span::styled(
+                format!(" /{} ", search.query),
+                Style::default().fg(theme().accent),
+            ));
+        }
+        if state.sort != SortMode::Tmux {
+            spans.push(Span::styled("│", sep));
+            spans.push(Span::styled(
+                format!(" sorted by {} ", state.sort.label()),
+                Style::default().fg(theme().muted),
+            ));
+        }
+        if state.group_by_repo {
+            spans.push(Span::styled("│", sep));
+            spans.push(Span::styled(
+                " grouped by repository ",
+                Style::default().fg(theme().muted),
+            ));
+        }
+
+        frame.render_widget(Paragraph::new(Line::from(spans)), area);
+        mute
+    }
+}
@@ -1,6 +1,6 @@
 use ratatui::{
     layout::Rect,
-    style::{Color, Style},
+    style::Style,
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, List, ListItem},
     Frame,
@@ -8,6 +8,7 @@ use ratatui::{
 
 use crate::agents::SubagentStatus;
 use crate::app::AppState;
+use crate::ui::{icons, theme};
 
 /// Widget for displaying subagent activity log
 pub struct SubagentLogWidget;
@@ -26,13 +27,13 @@ impl SubagentLogWidget {
             .title(title)
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(Color::Gray));
+            .border_style(Style::default().fg(theme().muted));
 
         let items: Vec<ListItem> = if let Some(agent) = agent {
             if agent.subagents.is_empty() {
                 vec![ListItem::new(Line::from(vec![Span::styled(
                     "  No subagent activity detected",
-                    Style::default().fg(Color::DarkGray),
+                    Style::default().fg(theme().dim),
                 )]))]
             } else {
                 agent
@@ -40,10 +41,16 @@ impl SubagentLogWidget {
                     .iter()
                     .map(|subagent| {
                         let (indicator, style) = match subagent.status {
-                            SubagentStatus::Running => ("▶", Style::default().fg(Color::Cyan)),
-                            SubagentStatus::Completed => ("✓", Style::default().fg(Color::Green)),
-                            SubagentStatus::Failed => ("✗", Style::default().fg(Color::Red)),
-                            SubagentStatus::Unknown => ("?", Style::default().fg(Color::DarkGray)),
+                            SubagentStatus::Running => {
+                                (icons().running, Style::default().fg(theme().accent))
+                            }
+                            SubagentStatus::Completed => {
+                                (icons().done, Style::default().fg(theme().success))
+                            }
+                            SubagentStatus::Failed => {
+                                (icons().error, Style::default().fg(theme().danger))
+                            }
+                            SubagentStatus::Unknown => ("?", Style::default().fg(theme().dim)),
                         };
 
                         let duration = subagent.duration_str();
@@ -54,14 +61,14 @@ impl SubagentLogWidget {
                             Span::raw(" "),
                             Span::styled(
                                 subagent.subagent_type.display_name(),
-                                Style::default().fg(Color::White),
+                                Style::default().fg(theme().text),
                             ),
                             Span::raw("  "),
-                            Span::styled(&subagent.description, Style::default().fg(Color::Gray)),
+                            Span::styled(&subagent.description, Style::default().fg(theme().muted)),
                             Span::raw("  "),
                             Span::styled(
                                 format!("[{}]", duration),
-                                Style::default().fg(Color::DarkGray),
+                                Style::default().fg(theme().dim),
                             ),
                         ]);
 
@@ -72,7 +79,7 @@ impl SubagentLogWidget {
         } else {
             vec![ListItem::new(Line::from(vec![Span::styled(
                 "  No agent selected",
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme().dim),
             )]))]
         };
 
@@ -0,0 +1,134 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::{AppState, ViewerKind};
+use crate::ui::theme;
+
+/// Scrollable text viewer drawn over the content area
+pub struct ViewerWidget;
+
+impl ViewerWidget {
+    pub fn render(frame: &mut Frame, area: Rect, state: &AppState) {
+        let Some(viewer) = &state.viewer else {
+            return;
+        };
+
+        frame.render_widget(Clear, area);
+
+        let visible = area.height.saturating_sub(2) as usize;
+        let start = viewer
+            .scroll
+            .min(viewer.lines.len().saturating_sub(visible));
+        let end = (start + visible).min(viewer.lines.len());
+
+        let mut position = if viewer.lines.is_empty() {
+            " 0/0 ".to_string()
+        } else {
+            format!(" {}-{}/{} ", start + 1, end, viewer.lines.len())
+        };
+        if let Some(search) = &viewer.search {
+            position = format!(" /{} │{}", search, position);
+        }
+
+        let block = Block::default()
+            .title(format!(" {} ", viewer.title))
+            .title_bottom(Line::from(position).right_aligned())
+            .title_bottom(Line::from(
+                " j/k: Scroll  g/G: Top/Bottom  /: Find  n/N: Next/Prev  v: Select  y: Copy  Esc: Close ",
+            ))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(theme().accent));
+
+        let selection = viewer.selection();
+        let lines: Vec<Line> = viewer.lines[start..end]
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let line = match viewer.kind {
+                    ViewerKind::CommitLog => commit_line(line),
+                    ViewerKind::Diff => diff_line(line),
+                    ViewerKind::Output | ViewerKind::Scrollback => {
+                        text_line(line, viewer.search.as_deref())
+                    }
+                };
+                if selection.as_ref().is_some_and(|r| r.contains(&(start + i))) {
+                    line.style(Style::default().bg(theme().cursor))
+                } else {
+                    line
+                }
+            })
+            .collect();
+
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
+
+/// Shows a line of plain text with the matches of `search` highlighted
+fn text_line(line: &str, search: Option<&str>) -> Line<'static> {
+    let line = line.replace('\t', "    ");
+    let Some(search) = search else {
+        return Line::from(line);
+    };
+    let match_style = Style::default().fg(theme().inverse).bg(theme().highlight);
+    // Lowercasing can change byte lengths, so only ASCII is matched in place
+    if !line.is_ascii() || !search.is_ascii() {
+        return Line::from(line);
+    }
+    let lower = line.to_ascii_lowercase();
+    let mut spans = Vec::new();
+    let mut rest = 0;
+    for (at, _) in lower.match_indices(search) {
+        spans.push(Span::raw(line[rest..at].to_string()));
+        spans.push(Span::styled(
+            line[at..at + search.len()].to_string(),
+            match_style,
+        ));
+        rest = at + search.len();
+    }
+    spans.push(Span::raw(line[rest..].to_string()));
+    Line::from(spans)
+}
+
+/// Colors a line of unified diff output
+fn diff_line(line: &str) -> Line<'static> {
+    let style = if line.starts_with("diff --git") || line.starts_with("══") {
+        Style::default()
+            .fg(theme().highlight)
+            .add_modifier(Modifier::BOLD)
+    } else if line.starts_with("+++") || line.starts_with("---") || line.starts_with("index ") {
+        Style::default()
+            .fg(theme().text)
+            .add_modifier(Modifier::BOLD)
+    } else if line.starts_with("@@") {
+        Style::default().fg(theme().accent)
+    } else if line.starts_with('+') {
+        Style::default().fg(theme().success)
+    } else if line.starts_with('-') {
+        Style::default().fg(theme().danger)
+    } else {
+        Style::default().fg(theme().muted)
+    };
+    // Tabs would be passed through to the terminal and break the layout
+    Line::from(Span::styled(line.replace('\t', "    "), style))
+}
+
+/// Colors a tab-separated "hash, age, author, subject" line
+fn commit_line(line: &str) -> Line<'static> {
+    let mut parts = line.splitn(4, '\t');
+    let hash = parts.next().unwrap_or("").to_string();
+    let age = parts.next().unwrap_or("").to_string();
+    let author = parts.next().unwrap_or("").to_string();
+    let subject = parts.next().unwrap_or("").to_string();
+    Line::from(vec![
+        Span::styled(format!("{} ", hash), Style::default().fg(theme().highlight)),
+        Span::styled(format!("{:>8} ", age), Style::default().fg(theme().dim)),
+        Span::styled(format!("{} ", author), Style::default().fg(theme().accent)),
+        Span::styled(subject, Style::default().fg(theme().text)),
+    ])
+}
@@ -0,0 +1,133 @@
+use std::process::{Command, Stdio};
+
+use tracing::warn;
+
+use crate::agents::MonitoredAgent;
+use crate::app::NotifyOn;
+
+/// Returns the title and body of the notification about `event` in `agent`:
+/// its label or pane in the title, what it asks or says in the body
+pub fn message(agent: &MonitoredAgent, event: NotifyOn) -> (String, String) {
+    let name = agent.label.clone().unwrap_or_else(|| agent.key());
+    (format!("{} {}", name, event.text()), agent.snippet())
+}
+
+/// Quotes `text` as an AppleScript string literal
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Returns the command showing a notification on Linux or macOS
+///
+/// macOS uses terminal-notifier when it is installed (it groups the
+/// notifications of one pane), osascript otherwise.
+fn notifier(title: &str, body: &str, macos: bool, has_terminal_notifier: bool) -> Command {
+    if !macos {
+        let mut command = Command::new("notify-send");
+        command.args(["--app-name", "tmuxcc", title, body]);
+        command
+    } else if has_terminal_notifier {
+        let mut command = Command::new("terminal-notifier");
+        command.args(["-title", "tmuxcc", "-subtitle", title, "-message", body]);
+        command.args(["-group", title]);
+        command
+    } else {
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(format!(
+            "display notification {} with title \"tmuxcc\" subtitle {}",
+            applescript_string(body),
+            applescript_string(title)
+        ));
+        command
+    }
+}
+
+/// Returns true if `program` is on `PATH`
+pub(super) fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+}
+
+/// Shows a desktop notification without waiting for the notifier
+pub fn notify(title: &str, body: &str) {
+    let macos = cfg!(target_os = "macos");
+    let spawned = notifier(title, body, macos, macos && on_path("terminal-notifier"))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match spawned {
+        // Reap the notifier without holding up the UI
+        Ok(mut child) => {
+            std::thread::spawn(move || child.wait());
+        }
+        Err(e) => warn!("Failed to show desktop notification: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::{AgentStatus, AgentType, ApprovalType};
+
+    fn args(command: &Command) -> Vec<String> {
+        command
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_notifier() {
+        let linux = notifier("main:0.1 failed", "boom", false, false);
+        assert_eq!(linux.get_program(), "notify-send");
+        assert_eq!(
+            args(&linux),
+            ["--app-name", "tmuxcc", "main:0.1 failed", "boom"]
+        );
+
+        let osascript = notifier("api asks for permission", "Shell: rm \"a b\"", true, false);
+        assert_eq!(osascript.get_program(), "osascript");
+        assert_eq!(
+            args(&osascript)[1],
+            "display notification \"Shell: rm \\\"a b\\\"\" with title \"tmuxcc\" \
+             subtitle \"api asks for permission\""
+        );
+
+        let terminal_notifier = notifier("api failed", "boom", true, true);
+        assert_eq!(terminal_notifier.get_program(), "terminal-notifier");
+    }
+
+    #[test]
+    fn test_message() {
+        let mut agent = MonitoredAgent::new(
+            "1".to_string(),
+            "main:0.1".to_string(),
+            "main".to_string(),
+            0,
+            "editor".to_string(),
+            1,
+            "/tmp".to_string(),
+            AgentType::ClaudeCode,
+            42,
+        );
+        agent.status = AgentStatus::AwaitingApproval {
+            approval_type: ApprovalType::ShellCommand,
+            details: "cargo test".to_string(),
+        };
+        let (title, body) = message(&agent, NotifyOn::Permission);
+        assert_eq!(title, "main:0.1 asks for permission");
+        assert_eq!(body, "Shell Command: cargo test");
+
+        agent.label = Some("api".to_string());
+        agent.status = AgentStatus::Idle;
+        agent.last_content = "Done.\n\n  Anything else?  \n\n".to_string();
+        assert_eq!(
+            message(&agent, NotifyOn::Finished),
+            ("api finished".to_string(), "Anything else?".to_string())
+        );
+
+        agent.last_content = "x".repeat(300);
+        assert_eq!(message(&agent, NotifyOn::Stalled).1.chars().count(), 201);
+    }
+}
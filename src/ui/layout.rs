@@ -12,6 +12,7 @@ impl Layout {
                 Constraint::Length(3), // Header
                 Constraint::Min(10),   // Content area
                 Constraint::Length(1), // Footer (1 line, no border)
+                Constraint::Length(1), // Status bar
             ])
             .split(area)
             .to_vec()
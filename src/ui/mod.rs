@@ -1,8 +1,16 @@
+mod ansi;
 mod app;
+mod clipboard;
 pub mod components;
+mod desktop;
 mod layout;
+pub(crate) mod monitoring;
+mod mouse;
+mod popup;
+mod sound;
 mod styles;
 
 pub use app::run_app;
 pub use layout::Layout;
-pub use styles::Styles;
+pub use popup::run_popup;
+pub use styles::{icons, set_icons, set_theme, theme, Styles};
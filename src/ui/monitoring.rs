@@ -0,0 +1,279 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::Result;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::app::{Config, Notifier, NotifyOn};
+use crate::monitor::{
+    webhook_targets, AutoApprover, AutoResponder, AutomationSwitches, MonitorReload, MonitorTask,
+    MonitorUpdate, NotifyRouter, PaneRegistry, PollSchedule, SharedRegistry, SharedSwitches,
+    SummaryCache,
+};
+use crate::parsers::ParserRegistry;
+use crate::tmux::{HookListener, TmuxClient};
+
+use super::desktop;
+use super::sound;
+
+/// How often the config file is checked for changes
+pub(crate) const CONFIG_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The monitor of the dashboard or `tmuxcc daemon`, which approves,
+/// answers and records, and what it is changed through while it runs
+///
+/// Dropping it stops the monitor and removes its tmux hooks.
+pub(crate) struct Monitoring {
+    pub registry: SharedRegistry,
+    pub switches: SharedSwitches,
+    pub reloads: mpsc::UnboundedSender<MonitorReload>,
+    pub router: NotifyRouter,
+    /// Why the tmux hooks couldn't be installed, if `tmux_hooks` is on
+    pub hook_error: Option<String>,
+    _hook_listener: Option<HookListener>,
+    handle: JoinHandle<()>,
+}
+
+impl Monitoring {
+    /// Starts the monitor `config` describes, sending its updates to `tx`;
+    /// fails without starting anything if the config is invalid
+    pub fn start(
+        config: &Config,
+        tmux_client: Arc<TmuxClient>,
+        tx: mpsc::Sender<MonitorUpdate>,
+    ) -> Result<Self> {
+        let parser_registry = Arc::new(ParserRegistry::from_config(config)?);
+        let auto_approver = config
+            .auto_approve
+            .enabled
+            .then(|| AutoApprover::new(&config.auto_approve))
+            .transpose()?;
+        let auto_responder = (!config.auto_respond.is_empty())
+            .then(|| AutoResponder::new(&config.auto_respond))
+            .transpose()?;
+        let router = NotifyRouter::new(config)?;
+        let schedule = poll_schedule(config)?;
+
+        let mut monitor = MonitorTask::new(
+            tmux_client,
+            parser_registry,
+            tx,
+            Duration::from_millis(config.poll_interval_ms),
+        )
+        .with_servers(config.servers())
+        .with_poll_schedule(schedule);
+        if config.control_mode {
+            monitor = monitor.with_control_mode();
+        }
+        monitor = monitor.with_status_titles(config.status_titles);
+        if let Some(path) = SummaryCache::default_path() {
+            monitor = monitor.with_summary_cache(path);
+        }
+        let registry = PaneRegistry::load(PaneRegistry::default_path()).shared();
+        monitor = monitor.with_registry(registry.clone());
+        monitor = monitor
+            .with_context_warning(config.context_warning)
+            .with_rate_limit_resume(config.rate_limit_resume.clone())
+            .with_summarize_cmd(config.summarize_cmd.clone())
+            .with_webhooks(webhook_targets(config), router.clone())
+            .with_event_hooks(config.hooks.clone())
+            .with_watchdog(
+                Duration::from_secs(config.watchdog.stall_secs),
+                config.watchdog.hook.clone(),
+            );
+        if let Some(approver) = auto_approver {
+            monitor = monitor.with_auto_approver(approver);
+        }
+        if let Some(responder) = auto_responder {
+            monitor = monitor.with_auto_responder(responder);
+        }
+        let switches = AutomationSwitches::default().shared();
+        monitor = monitor.with_switches(switches.clone());
+        let (reloads, reload_rx) = mpsc::unbounded_channel();
+        monitor = monitor.with_reloads(reload_rx);
+        let mut hook_error = None;
+        let hook_listener = if config.tmux_hooks {
+            let (hook_tx, hook_rx) = mpsc::channel(64);
+            match HookListener::start(&config.servers(), hook_tx) {
+                Ok(listener) => {
+                    monitor = monitor.with_hook_events(hook_rx);
+                    Some(listener)
+                }
+                Err(e) => {
+                    hook_error = Some(format!("Failed to install tmux hooks: {}", e));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let handle = tokio::spawn(async move {
+            monitor.run().await;
+        });
+
+        Ok(Self {
+            registry,
+            switches,
+            reloads,
+            router,
+            hook_error,
+            _hook_listener: hook_listener,
+            handle,
+        })
+    }
+
+    /// Hands the monitor the parts of a reloaded config
+    pub fn reload(&mut self, reload: MonitorReload) {
+        self.router = reload.router.clone();
+        let _ = self.reloads.send(reload);
+    }
+
+    /// Raises the desktop notifications, tmux alerts and sounds `config`
+    /// and the notify rules ask for `update`, sounds only if not `muted`
+    pub fn alert(
+        &mut self,
+        update: &MonitorUpdate,
+        config: &Config,
+        tmux_client: &TmuxClient,
+        muted: bool,
+    ) {
+        let events: Vec<(&str, NotifyOn)> = update
+            .transitions
+            .iter()
+            .filter_map(|t| Some((t.key.as_str(), NotifyOn::from_transition(t.from, t.to)?)))
+            .chain(
+                update
+                    .stalls
+                    .iter()
+                    .map(|s| (s.key.as_str(), NotifyOn::Stalled)),
+            )
+            .collect();
+        // Several panes changing at once play each sound once
+        let mut played = Vec::new();
+        for &(key, event) in &events {
+            let agents = &update.agents.root_agents;
+            let Some(agent) = agents.iter().find(|a| a.key() == key) else {
+                continue;
+            };
+            let route = self.router.route(agent, event, Instant::now());
+            let desktop = &config.desktop_notifications;
+            if route.sends_to(Notifier::Desktop, desktop.enabled, desktop.wants(event)) {
+                let (title, body) = desktop::message(agent, event);
+                desktop::notify(&title, &body);
+            }
+            let alerts = &config.tmux_alerts;
+            if route.sends_to(Notifier::Tmux, alerts.enabled, alerts.wants(event)) {
+                let (title, body) = desktop::message(agent, event);
+                let text = if body.is_empty() {
+                    title
+                } else {
+                    format!("{}: {}", title, body)
+                };
+                let _ = tmux_client.display_message(&format!("tmuxcc: {}", text));
+                if alerts.bell {
+                    let _ = tmux_client
+                        .for_server(&agent.server)
+                        .ring_bell(&agent.target);
+                }
+            }
+            let sounds = &config.sounds;
+            let wants = sounds.sound_for(event).is_some();
+            if !muted && route.sends_to(Notifier::Sound, sounds.enabled, wants) {
+                // A rule may route events without a sound of their own to the bell
+                let sound = sounds.sound_for(event).unwrap_or("bell");
+                if !played.contains(&sound) {
+                    sound::play(sound);
+                    played.push(sound);
+                }
+            }
+        }
+        let warnings = update.context_warnings.iter().map(|w| w.message());
+        for message in warnings.chain(update.stalls.iter().map(|s| s.message())) {
+            // Also reaches the user while they are in another pane
+            let _ = tmux_client.display_message(&format!("tmuxcc: {}", message));
+        }
+    }
+}
+
+impl Drop for Monitoring {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Tells when the config file changed, looking at most every
+/// [`CONFIG_CHECK_INTERVAL`]
+pub(crate) struct ConfigWatch {
+    /// Watched for changes even if it doesn't exist yet
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    checked: Instant,
+}
+
+impl ConfigWatch {
+    pub fn new(config: &Config) -> Self {
+        let path = config.source.clone().or_else(Config::default_path);
+        Self {
+            modified: path.as_deref().and_then(modified_time),
+            path,
+            checked: Instant::now(),
+        }
+    }
+
+    /// Returns the path of the config if it was modified since the last call
+    pub fn changed(&mut self) -> Option<PathBuf> {
+        if self.checked.elapsed() < CONFIG_CHECK_INTERVAL {
+            return None;
+        }
+        self.checked = Instant::now();
+        let modified = self.path.as_deref().and_then(modified_time);
+        let previous = std::mem::replace(&mut self.modified, modified);
+        // A removed file keeps the config running
+        match (&self.path, modified) {
+            (Some(path), Some(_)) if modified != previous => Some(path.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Returns when the file at `path` was last modified, `None` if it is missing
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Reads the config at `path` again and rebuilds the parts of the monitor
+/// that can change while it runs; nothing of it is used if any is invalid
+pub(crate) fn reload_config(path: &Path, current: &Config) -> Result<(Config, MonitorReload)> {
+    let mut config = Config::load_from(path, current.profile.as_deref())?;
+    // Fixed at startup, possibly by command line options
+    config.poll_interval_ms = current.poll_interval_ms;
+    config.capture_lines = current.capture_lines;
+    config.control_mode = current.control_mode;
+
+    let reload = MonitorReload {
+        parser_registry: Arc::new(ParserRegistry::from_config(&config)?),
+        auto_approver: config
+            .auto_approve
+            .enabled
+            .then(|| AutoApprover::new(&config.auto_approve))
+            .transpose()?,
+        auto_responder: (!config.auto_respond.is_empty())
+            .then(|| AutoResponder::new(&config.auto_respond))
+            .transpose()?,
+        webhook_targets: webhook_targets(&config),
+        router: NotifyRouter::new(&config)?,
+        hooks: config.hooks.clone(),
+        schedule: poll_schedule(&config)?,
+    };
+    Ok((config, reload))
+}
+
+/// Returns the schedule of the `pane_polling` rules
+fn poll_schedule(config: &Config) -> Result<PollSchedule> {
+    PollSchedule::new(
+        Duration::from_millis(config.poll_interval_ms),
+        &config.pane_polling,
+    )
+}
@@ -0,0 +1,222 @@
+use std::time::{Duration, Instant};
+
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+
+use crate::app::{Action, AppState};
+
+use super::components::{FooterButton, FooterWidget, TreeRow};
+
+/// Longest gap between the clicks of a double click
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Lines scrolled per wheel step
+const WHEEL_LINES: usize = 3;
+
+/// Where the last frame drew the parts of the dashboard that take clicks
+#[derive(Debug, Default)]
+pub struct ScreenMap {
+    /// Area split between the sidebar and the preview
+    pub content: Rect,
+    pub sidebar: Rect,
+    /// Screen row of each agent and group line of the sidebar
+    pub tree_rows: Vec<(u16, TreeRow)>,
+    pub preview: Rect,
+    pub input: Rect,
+    pub footer: Rect,
+    /// Sound switch of the status bar, empty without sounds
+    pub mute: Rect,
+}
+
+/// Remembers the last left click to recognize double clicks, and whether
+/// the sidebar border is being dragged
+#[derive(Debug, Default)]
+pub struct Clicks {
+    last: Option<(Instant, TreeRow)>,
+    resizing: bool,
+}
+
+impl Clicks {
+    /// Records a click on `row`, returning whether it completes a double click
+    fn click(&mut self, row: TreeRow) -> bool {
+        let now = Instant::now();
+        let double = matches!(
+            self.last,
+            Some((at, last)) if last == row && now.duration_since(at) <= DOUBLE_CLICK
+        );
+        // A third click starts over instead of making another double click
+        self.last = (!double).then_some((now, row));
+        double
+    }
+}
+
+/// Maps a mouse event to an action
+///
+/// A click selects the agent of a sidebar line and a double click jumps to
+/// its pane (or collapses a group); dragging the sidebar's right border
+/// resizes it. The wheel moves the cursor in the sidebar and scrolls the
+/// preview and viewers. Popups only take the keyboard, apart from a click
+/// closing the help.
+pub fn map_mouse_to_action(
+    mouse: MouseEvent,
+    state: &AppState,
+    screen: &ScreenMap,
+    clicks: &mut Clicks,
+) -> Action {
+    let position = Position::new(mouse.column, mouse.row);
+
+    if state.show_help {
+        return match mouse.kind {
+            MouseEventKind::Down(_) => Action::HideHelp,
+            MouseEventKind::ScrollUp => Action::HelpUp(WHEEL_LINES),
+            MouseEventKind::ScrollDown => Action::HelpDown(WHEEL_LINES),
+            _ => Action::None,
+        };
+    }
+    if state.prompt.is_some()
+        || state.picker.is_some()
+        || state.confirm.is_some()
+        || state.palette.is_some()
+    {
+        return Action::None;
+    }
+    if state.viewer.is_some() {
+        return match mouse.kind {
+            MouseEventKind::ScrollUp => Action::ViewerUp(WHEEL_LINES),
+            MouseEventKind::ScrollDown => Action::ViewerDown(WHEEL_LINES),
+            _ => Action::None,
+        };
+    }
+
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left)
+            if mouse.column + 1 == screen.sidebar.right() && screen.sidebar.contains(position) =>
+        {
+            clicks.resizing = true;
+            Action::None
+        }
+        MouseEventKind::Drag(MouseButton::Left) if clicks.resizing && screen.content.width > 0 => {
+            let width = (mouse.column + 1).saturating_sub(screen.content.x) as u32;
+            Action::ResizeSidebar((width * 100 / screen.content.width as u32) as u16)
+        }
+        MouseEventKind::Up(MouseButton::Left) => {
+            clicks.resizing = false;
+            Action::None
+        }
+        MouseEventKind::Down(MouseButton::Left) if screen.mute.contains(position) => {
+            Action::ToggleMute
+        }
+        MouseEventKind::Down(MouseButton::Left) => {
+            if let Some(button) =
+                FooterWidget::hit_test(mouse.column, mouse.row, screen.footer, state)
+            {
+                return match button {
+                    FooterButton::Approve => Action::Approve,
+                    FooterButton::Reject => Action::Reject,
+                    FooterButton::ApproveAll => Action::ApproveAll,
+                    FooterButton::ToggleSelect => Action::ToggleSelection,
+                    FooterButton::Focus => Action::FocusPane,
+                    FooterButton::Help => Action::ShowHelp,
+                    FooterButton::Quit => Action::Quit,
+                };
+            }
+            if screen.sidebar.contains(position) {
+                let row = screen.tree_rows.iter().find(|(y, _)| *y == mouse.row);
+                return match row {
+                    Some(&(_, row)) => match (row, clicks.click(row)) {
+                        (TreeRow::Agent(_), true) => Action::JumpToPane,
+                        (TreeRow::Group(_), true) => Action::ToggleCollapseGroup,
+                        (TreeRow::Agent(idx) | TreeRow::Group(idx), false) => {
+                            Action::SelectAgent(idx)
+                        }
+                    },
+                    None => Action::FocusSidebar,
+                };
+            }
+            if screen.input.contains(position) {
+                return Action::FocusInput;
+            }
+            Action::None
+        }
+        MouseEventKind::ScrollUp if screen.preview.contains(position) => {
+            Action::ScrollPreviewUp(WHEEL_LINES)
+        }
+        MouseEventKind::ScrollDown if screen.preview.contains(position) => {
+            Action::ScrollPreviewDown(WHEEL_LINES)
+        }
+        MouseEventKind::ScrollUp => Action::ScrollUp,
+        MouseEventKind::ScrollDown => Action::ScrollDown,
+        _ => Action::None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn test_clicks_and_wheel() {
+        let state = AppState::new();
+        let screen = ScreenMap {
+            content: Rect::new(0, 3, 80, 20),
+            sidebar: Rect::new(0, 3, 30, 20),
+            tree_rows: vec![(5, TreeRow::Group(2)), (6, TreeRow::Agent(2))],
+            preview: Rect::new(30, 3, 50, 15),
+            input: Rect::new(30, 18, 50, 5),
+            footer: Rect::new(0, 23, 80, 1),
+            mute: Rect::new(60, 24, 9, 1),
+        };
+        let mut clicks = Clicks::default();
+        let left = MouseEventKind::Down(MouseButton::Left);
+
+        let click = |clicks: &mut Clicks, column, row| {
+            map_mouse_to_action(mouse(left, column, row), &state, &screen, clicks)
+        };
+        assert_eq!(click(&mut clicks, 4, 6), Action::SelectAgent(2));
+        assert_eq!(click(&mut clicks, 4, 6), Action::JumpToPane);
+        assert_eq!(click(&mut clicks, 4, 6), Action::SelectAgent(2));
+        assert_eq!(click(&mut clicks, 4, 5), Action::SelectAgent(2));
+        assert_eq!(click(&mut clicks, 4, 5), Action::ToggleCollapseGroup);
+        assert_eq!(click(&mut clicks, 4, 10), Action::FocusSidebar);
+        assert_eq!(click(&mut clicks, 40, 20), Action::FocusInput);
+        assert_eq!(click(&mut clicks, 62, 24), Action::ToggleMute);
+
+        let scroll = |kind, column, row| {
+            map_mouse_to_action(
+                mouse(kind, column, row),
+                &state,
+                &screen,
+                &mut Clicks::default(),
+            )
+        };
+        assert_eq!(
+            scroll(MouseEventKind::ScrollUp, 40, 5),
+            Action::ScrollPreviewUp(WHEEL_LINES)
+        );
+        assert_eq!(scroll(MouseEventKind::ScrollDown, 4, 5), Action::ScrollDown);
+
+        // Dragging the sidebar border resizes it
+        let drag = MouseEventKind::Drag(MouseButton::Left);
+        assert_eq!(click(&mut clicks, 29, 8), Action::None);
+        assert_eq!(
+            map_mouse_to_action(mouse(drag, 39, 8), &state, &screen, &mut clicks),
+            Action::ResizeSidebar(50)
+        );
+        let up = MouseEventKind::Up(MouseButton::Left);
+        map_mouse_to_action(mouse(up, 39, 8), &state, &screen, &mut clicks);
+        assert_eq!(
+            map_mouse_to_action(mouse(drag, 20, 8), &state, &screen, &mut clicks),
+            Action::None
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame, Terminal,
+};
+use tokio::sync::mpsc;
+
+use crate::agents::MonitoredAgent;
+use crate::app::{Config, Theme};
+use crate::monitor::{status_title, MonitorTask};
+use crate::parsers::ParserRegistry;
+use crate::tmux::{SendMode, TmuxClient};
+use crate::ui::{set_theme, theme};
+
+/// What a key press in the popup does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PopupAction {
+    Up,
+    Down,
+    Jump,
+    Approve,
+    Reject,
+    /// Send the canned reply at this index
+    Reply(usize),
+    Close,
+    None,
+}
+
+/// Maps a key to its popup action
+fn popup_action(code: KeyCode) -> PopupAction {
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => PopupAction::Up,
+        KeyCode::Down | KeyCode::Char('j') => PopupAction::Down,
+        KeyCode::Enter | KeyCode::Char('o') => PopupAction::Jump,
+        KeyCode::Char('y') => PopupAction::Approve,
+        KeyCode::Char('n') => PopupAction::Reject,
+        KeyCode::Char(c @ '1'..='9') => PopupAction::Reply(c as usize - '1' as usize),
+        KeyCode::Esc | KeyCode::Char('q') => PopupAction::Close,
+        _ => PopupAction::None,
+    }
+}
+
+/// Runs the compact agent picker meant for `tmux display-popup -E tmuxcc popup`
+///
+/// tmux is polled once, the user picks an agent and the popup exits right
+/// after jumping to it or sending a reply.
+pub async fn run_popup(config: Config) -> Result<()> {
+    set_theme(Theme::from_config(&config.theme)?);
+    let tmux_client = Arc::new(TmuxClient::with_capture_lines(config.capture_lines));
+    let (tx, _rx) = mpsc::channel(1);
+    let mut monitor = MonitorTask::new(
+        tmux_client.clone(),
+        Arc::new(ParserRegistry::from_config(&config)?),
+        tx,
+        Duration::from_millis(config.poll_interval_ms),
+    )
+    .with_servers(config.servers());
+    let agents = monitor.poll_once().await?.root_agents;
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let result = popup_loop(&mut terminal, &agents, &tmux_client, &config.popup_replies);
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    result
+}
+
+fn popup_loop(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    agents: &[MonitoredAgent],
+    tmux_client: &TmuxClient,
+    replies: &[String],
+) -> Result<()> {
+    let mut list_state = ListState::default();
+    list_state.select((!agents.is_empty()).then_some(0));
+
+    loop {
+        terminal.draw(|frame| render(frame, agents, replies, &mut list_state))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let selected = list_state.selected().and_then(|i| agents.get(i));
+
+        let action = popup_action(key.code);
+        match action {
+            PopupAction::Up => list_state.select_previous(),
+            PopupAction::Down => {
+                if list_state.selected().is_some_and(|i| i + 1 < agents.len()) {
+                    list_state.select_next();
+                }
+            }
+            PopupAction::Jump => {
+                if let Some(agent) = selected {
+                    let client = tmux_client.for_server(&agent.server);
+                    // switch-client only reaches panes on the popup's own server
+                    if agent.server.is_default() {
+                        client.switch_client(&agent.target)?;
+                    } else {
+                        client.focus_pane(&agent.target)?;
+                    }
+                    return Ok(());
+                }
+            }
+            PopupAction::Approve | PopupAction::Reject => {
+                if let Some(agent) = selected {
+                    let answer = if action == PopupAction::Approve {
+                        "y"
+                    } else {
+                        "n"
+                    };
+                    let client = tmux_client.for_server(&agent.server);
+                    client.send_keys(&agent.target, answer)?;
+                    client.send_keys(&agent.target, "Enter")?;
+                    return Ok(());
+                }
+            }
+            PopupAction::Reply(index) => {
+                if let (Some(agent), Some(reply)) = (selected, replies.get(index)) {
+                    let client = tmux_client.for_server(&agent.server);
+                    client.send_text(&agent.target, reply, SendMode::for_text(reply))?;
+                    client.send_keys(&agent.target, "Enter")?;
+                    return Ok(());
+                }
+            }
+            PopupAction::Close => return Ok(()),
+            PopupAction::None => {}
+        }
+    }
+}
+
+fn render(
+    frame: &mut Frame,
+    agents: &[MonitoredAgent],
+    replies: &[String],
+    list_state: &mut ListState,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(3),
+            Constraint::Length(replies.len().min(9) as u16 + 2),
+            Constraint::Length(1),
+        ])
+        .split(frame.area());
+
+    let items: Vec<ListItem> = agents
+        .iter()
+        .map(|agent| {
+            ListItem::new(Line::from(vec![
+                Span::raw(status_title(agent)),
+                Span::styled(
+                    format!("  {}", agent.key()),
+                    Style::default().fg(theme().dim),
+                ),
+                Span::styled(
+                    format!("  {}", agent.abbreviated_path()),
+                    Style::default().fg(theme().accent),
+                ),
+            ]))
+        })
+        .collect();
+    let title = if agents.is_empty() {
+        " No agents "
+    } else {
+        " Agents "
+    };
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(
+            Style::default()
+                .bg(theme().cursor)
+                .add_modifier(Modifier::BOLD),
+        );
+    frame.render_stateful_widget(list, chunks[0], list_state);
+
+    let reply_lines: Vec<Line> = replies
+        .iter()
+        .take(9)
+        .enumerate()
+        .map(|(i, reply)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{} ", i + 1),
+                    Style::default().fg(theme().highlight),
+                ),
+                Span::raw(reply.as_str()),
+            ])
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(reply_lines)
+            .block(Block::default().borders(Borders::ALL).title(" Replies ")),
+        chunks[1],
+    );
+
+    frame.render_widget(
+        Paragraph::new("Enter: jump  y/n: approve/reject  1-9: reply  q: close")
+            .style(Style::default().fg(theme().dim)),
+        chunks[2],
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_popup_action() {
+        assert_eq!(popup_action(KeyCode::Enter), PopupAction::Jump);
+        assert_eq!(popup_action(KeyCode::Char('j')), PopupAction::Down);
+        assert_eq!(popup_action(KeyCode::Char('1')), PopupAction::Reply(0));
+        assert_eq!(popup_action(KeyCode::Char('9')), PopupAction::Reply(8));
+        assert_eq!(popup_action(KeyCode::Char('0')), PopupAction::None);
+        assert_eq!(popup_action(KeyCode::Esc), PopupAction::Close);
+    }
+}
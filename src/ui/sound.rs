@@ -0,0 +1,59 @@
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use tracing::warn;
+
+use super::desktop::on_path;
+
+/// Returns the command playing the sound file at `path`
+fn player(path: &str, macos: bool, has_paplay: bool) -> Command {
+    let program = match (macos, has_paplay) {
+        (true, _) => "afplay",
+        (false, true) => "paplay",
+        (false, false) => "aplay",
+    };
+    let mut command = Command::new(program);
+    if program == "aplay" {
+        command.arg("--quiet");
+    }
+    command.arg(path);
+    command
+}
+
+/// Plays a sound: "bell" rings the terminal bell, anything else is a sound
+/// file played without waiting for it to end
+pub fn play(sound: &str) {
+    if sound == "bell" {
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(b"\x07").and_then(|()| stdout.flush());
+        return;
+    }
+    let macos = cfg!(target_os = "macos");
+    let spawned = player(sound, macos, !macos && on_path("paplay"))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match spawned {
+        // Reap the player without holding up the UI
+        Ok(mut child) => {
+            std::thread::spawn(move || child.wait());
+        }
+        Err(e) => warn!("Failed to play {}: {}", sound, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_player() {
+        let command = player("/tmp/done.wav", true, false);
+        assert_eq!(command.get_program(), "afplay");
+        assert_eq!(player("/tmp/done.wav", false, true).get_program(), "paplay");
+        let aplay = player("/tmp/done.wav", false, false);
+        assert_eq!(aplay.get_program(), "aplay");
+        assert_eq!(aplay.get_args().count(), 2);
+    }
+}
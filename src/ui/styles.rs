@@ -1,4 +1,38 @@
-use ratatui::style::{Color, Modifier, Style};
+use std::sync::{OnceLock, PoisonError, RwLock};
+
+use ratatui::style::{Modifier, Style};
+
+use crate::agents::{AgentState, MonitoredAgent};
+use crate::app::{Icons, Theme};
+
+static THEME: RwLock<Option<&'static Theme>> = RwLock::new(None);
+static DEFAULT_THEME: OnceLock<Theme> = OnceLock::new();
+static ICONS: OnceLock<&'static Icons> = OnceLock::new();
+
+/// Sets the theme used for drawing, replacing the current one
+///
+/// Themes are only set at startup and on config reloads, so each one is
+/// simply kept for the rest of the run.
+pub fn set_theme(theme: Theme) {
+    let theme: &'static Theme = Box::leak(Box::new(theme));
+    *THEME.write().unwrap_or_else(PoisonError::into_inner) = Some(theme);
+}
+
+/// Returns the theme used for drawing, the dark palette until one is set
+pub fn theme() -> &'static Theme {
+    let current = *THEME.read().unwrap_or_else(PoisonError::into_inner);
+    current.unwrap_or_else(|| DEFAULT_THEME.get_or_init(Theme::default))
+}
+
+/// Sets the icons used for drawing; only the first call takes effect
+pub fn set_icons(icons: &'static Icons) {
+    let _ = ICONS.set(icons);
+}
+
+/// Returns the icons used for drawing, the Unicode ones until some are set
+pub fn icons() -> &'static Icons {
+    ICONS.get_or_init(|| &Icons::UNICODE)
+}
 
 /// Central style definitions for the application
 pub struct Styles;
@@ -6,99 +40,126 @@ pub struct Styles;
 impl Styles {
     // Status colors
     pub fn idle() -> Style {
-        Style::default().fg(Color::Green)
+        Style::default().fg(theme().idle)
     }
 
     pub fn processing() -> Style {
-        Style::default().fg(Color::Yellow)
+        Style::default().fg(theme().working)
     }
 
     pub fn awaiting_approval() -> Style {
-        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+        Style::default()
+            .fg(theme().permission)
+            .add_modifier(Modifier::BOLD)
     }
 
     pub fn error() -> Style {
-        Style::default().fg(Color::Red)
+        Style::default().fg(theme().danger)
     }
 
     pub fn unknown() -> Style {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(theme().unknown)
+    }
+
+    /// Colored badge for an agent state, `None` while it is unknown
+    pub fn state_badge(state: Option<AgentState>) -> Style {
+        let theme = theme();
+        let bg = match state {
+            Some(AgentState::Idle) => theme.idle,
+            Some(AgentState::Working) => theme.working,
+            Some(AgentState::WaitingForInput) => theme.waiting,
+            Some(AgentState::WaitingForPermission) => theme.permission,
+            Some(AgentState::Error) => theme.error,
+            None => theme.unknown,
+        };
+        Style::default()
+            .fg(theme.inverse)
+            .bg(bg)
+            .add_modifier(Modifier::BOLD)
+    }
+
+    /// Badge for an agent's state, red while the watchdog flags it as stalled
+    pub fn agent_badge(agent: &MonitoredAgent) -> Style {
+        if agent.stalled_since.is_some() {
+            return Self::state_badge(None).fg(theme().text).bg(theme().stalled);
+        }
+        Self::state_badge(agent.state)
     }
 
     // UI element styles
     pub fn header() -> Style {
         Style::default()
-            .fg(Color::Cyan)
+            .fg(theme().accent)
             .add_modifier(Modifier::BOLD)
     }
 
     pub fn selected() -> Style {
         Style::default()
-            .fg(Color::Black)
-            .bg(Color::Cyan)
+            .fg(theme().inverse)
+            .bg(theme().accent)
             .add_modifier(Modifier::BOLD)
     }
 
     pub fn normal() -> Style {
-        Style::default().fg(Color::White)
+        Style::default().fg(theme().text)
     }
 
     pub fn dimmed() -> Style {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(theme().dim)
     }
 
     pub fn highlight() -> Style {
         Style::default()
-            .fg(Color::Yellow)
+            .fg(theme().highlight)
             .add_modifier(Modifier::BOLD)
     }
 
     pub fn border() -> Style {
-        Style::default().fg(Color::Gray)
+        Style::default().fg(theme().muted)
     }
 
     pub fn border_focused() -> Style {
-        Style::default().fg(Color::Cyan)
+        Style::default().fg(theme().accent)
     }
 
     // Agent type colors
     pub fn claude_code() -> Style {
-        Style::default().fg(Color::Magenta)
+        Style::default().fg(theme().special)
     }
 
     pub fn opencode() -> Style {
-        Style::default().fg(Color::Blue)
+        Style::default().fg(theme().info)
     }
 
     pub fn codex_cli() -> Style {
-        Style::default().fg(Color::Green)
+        Style::default().fg(theme().success)
     }
 
     pub fn gemini_cli() -> Style {
-        Style::default().fg(Color::Yellow)
+        Style::default().fg(theme().highlight)
     }
 
     // Subagent styles
     pub fn subagent_running() -> Style {
-        Style::default().fg(Color::Cyan)
+        Style::default().fg(theme().accent)
     }
 
     pub fn subagent_completed() -> Style {
-        Style::default().fg(Color::Green)
+        Style::default().fg(theme().success)
     }
 
     pub fn subagent_failed() -> Style {
-        Style::default().fg(Color::Red)
+        Style::default().fg(theme().danger)
     }
 
     // Footer/Help styles
     pub fn footer_key() -> Style {
         Style::default()
-            .fg(Color::Yellow)
+            .fg(theme().highlight)
             .add_modifier(Modifier::BOLD)
     }
 
     pub fn footer_text() -> Style {
-        Style::default().fg(Color::White)
+        Style::default().fg(theme().text)
     }
 }